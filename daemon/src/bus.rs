@@ -21,6 +21,18 @@ pub enum DaemonEventType {
     Custom(String),
 }
 
+impl From<&str> for DaemonEventType {
+    fn from(event_type: &str) -> Self {
+        match event_type {
+            "compact" => DaemonEventType::Compact,
+            "escalation" => DaemonEventType::Escalation,
+            "pane_status" => DaemonEventType::PaneStatus,
+            "session_status" => DaemonEventType::SessionStatus,
+            other => DaemonEventType::Custom(other.to_string()),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct DaemonEvent {
     pub event_type: DaemonEventType,
@@ -30,6 +42,10 @@ pub struct DaemonEvent {
     pub payload: Option<Value>,
 }
 
+/// [`ClientUpdate::kind`] for a pane capture, with `paneId` and `output`
+/// in the payload. Feeds `panes.tail`.
+pub const PANE_OUTPUT_UPDATE: &str = "pane.output";
+
 #[derive(Clone, Debug)]
 pub struct ClientUpdate {
     pub kind: String,
@@ -155,9 +171,10 @@ mod tests {
         StateChange {
             sessions: vec![Session {
                 session_uid: "sess".to_string(),
-                source_id: "src".to_string(),
+                source_id: "src".into(),
                 tmux_session_id: None,
                 name: "name".to_string(),
+                raw_name: None,
                 created_at: 1,
                 last_seen_at: 1,
                 ended_at: None,
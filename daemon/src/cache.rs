@@ -1,9 +1,14 @@
+use crate::db::EventIdSequence;
+use crate::locks;
 use crate::models::pane::Pane;
 use crate::models::session::Session;
+use crate::parsers::tmux_panes::TmuxPaneMeta;
 use serde::Serialize;
 use dashmap::DashMap;
-use std::collections::VecDeque;
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use std::sync::RwLock;
 
 #[derive(Clone, Debug, Default)]
@@ -24,10 +29,80 @@ pub struct EventRecord {
     pub status: Option<String>,
 }
 
+/// Machine-readable cause of a degraded health status.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DegradedReason {
+    /// Repeated collector poll failures.
+    PollErrors,
+    /// The ntm binary is missing or cannot be spawned.
+    NtmUnavailable,
+    /// The tmux binary is missing or cannot be spawned.
+    TmuxUnavailable,
+    /// An external command exceeded its timeout.
+    CommandTimeout,
+    /// The database stayed busy or locked past the busy timeout.
+    DbLocked,
+}
+
+impl DegradedReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DegradedReason::PollErrors => "poll_errors",
+            DegradedReason::NtmUnavailable => "ntm_unavailable",
+            DegradedReason::TmuxUnavailable => "tmux_unavailable",
+            DegradedReason::CommandTimeout => "command_timeout",
+            DegradedReason::DbLocked => "db_locked",
+        }
+    }
+}
+
+/// Subsystem that reports into the cache's health.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum HealthSource {
+    Ntm,
+    Tmux,
+    Db,
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct HealthStatus {
     pub status: String,
     pub last_error: Option<String>,
+    /// Set by whichever subsystem degraded health; `None` while healthy.
+    pub degraded_reason: Option<DegradedReason>,
+}
+
+/// The last whole health set plus what each subsystem has reported since.
+#[derive(Debug, Default)]
+struct HealthReports {
+    base: HealthStatus,
+    by_source: BTreeMap<HealthSource, HealthStatus>,
+}
+
+impl HealthReports {
+    /// Overall health: the first degraded report wins, otherwise the base
+    /// status with the first reported error.
+    fn overall(&self) -> HealthStatus {
+        if let Some(degraded) = self
+            .by_source
+            .values()
+            .find(|health| health.status == "degraded")
+        {
+            return degraded.clone();
+        }
+        if self.by_source.is_empty() || self.base.status == "degraded" {
+            return self.base.clone();
+        }
+        HealthStatus {
+            status: "ok".to_string(),
+            last_error: self
+                .base
+                .last_error
+                .clone()
+                .or_else(|| self.by_source.values().find_map(|h| h.last_error.clone())),
+            degraded_reason: None,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default, PartialEq, Serialize)]
@@ -45,6 +120,14 @@ pub struct PollingState {
     pub ntm: PollingDatum,
 }
 
+/// Polling loops whose error streaks the cache tracks.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PollChannel {
+    Snapshot,
+    Tmux,
+    Ntm,
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct CacheSnapshot {
     pub sessions: Vec<Session>,
@@ -62,13 +145,117 @@ pub struct CacheMetrics {
     pub pane_misses: u64,
 }
 
+/// The recent-event ring plus lookups kept in step with it on every insert
+/// and eviction, so filtered reads need not scan the whole ring.
+#[derive(Debug, Default)]
+struct EventRing {
+    events: VecDeque<EventRecord>,
+    /// Events evicted so far: `events[i]` was inserted at position `evicted + i`.
+    evicted: u64,
+    count_by_type: HashMap<String, usize>,
+    /// Insertion positions of each session's events, oldest first.
+    positions_by_session: HashMap<String, VecDeque<u64>>,
+}
+
+impl EventRing {
+    fn push(&mut self, event: EventRecord, max_events: usize) {
+        if self.events.len() >= max_events {
+            self.evict_oldest();
+        }
+        let position = self.evicted + self.events.len() as u64;
+        *self
+            .count_by_type
+            .entry(event.event_type.clone())
+            .or_insert(0) += 1;
+        self.positions_by_session
+            .entry(event.session_uid.clone())
+            .or_default()
+            .push_back(position);
+        self.events.push_back(event);
+    }
+
+    fn evict_oldest(&mut self) {
+        let Some(event) = self.events.pop_front() else {
+            return;
+        };
+        self.evicted += 1;
+        if let Some(count) = self.count_by_type.get_mut(&event.event_type) {
+            *count -= 1;
+            if *count == 0 {
+                self.count_by_type.remove(&event.event_type);
+            }
+        }
+        if let Some(positions) = self.positions_by_session.get_mut(&event.session_uid) {
+            // The oldest event overall is also its session's oldest
+            positions.pop_front();
+            if positions.is_empty() {
+                self.positions_by_session.remove(&event.session_uid);
+            }
+        }
+    }
+
+    /// Events of `event_type` from `pane_uid` detected at or after `since`,
+    /// scanning only the session's indexed events.
+    fn pane_event_count(
+        &self,
+        session_uid: &str,
+        pane_uid: &str,
+        event_type: &str,
+        since: i64,
+    ) -> usize {
+        if !self.count_by_type.contains_key(event_type) {
+            return 0;
+        }
+        self.positions_by_session
+            .get(session_uid)
+            .map(|positions| {
+                positions
+                    .iter()
+                    .filter_map(|position| self.events.get((position - self.evicted) as usize))
+                    .filter(|event| {
+                        event.pane_uid == pane_uid
+                            && event.event_type == event_type
+                            && event.detected_at >= since
+                    })
+                    .count()
+            })
+            .unwrap_or(0)
+    }
+
+    fn session_events(&self, session_uid: &str) -> Vec<EventRecord> {
+        self.positions_by_session
+            .get(session_uid)
+            .map(|positions| {
+                positions
+                    .iter()
+                    .filter_map(|position| self.events.get((position - self.evicted) as usize))
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
 pub struct Cache {
     sessions: DashMap<String, Session>,
     panes: DashMap<String, Pane>,
-    recent_events: RwLock<VecDeque<EventRecord>>,
+    /// Hash of the last captured output per pane uid.
+    pane_output_hashes: DashMap<String, u64>,
+    /// Newest `compact` event time per pane uid.
+    pane_last_compacts: DashMap<String, i64>,
+    /// Client-assigned display names keyed by session uid.
+    session_aliases: DashMap<String, String>,
+    /// Client-assigned tags keyed by session uid.
+    session_tags: DashMap<String, Vec<String>>,
+    recent_events: RwLock<EventRing>,
+    event_ids: EventIdSequence,
     stats_today: RwLock<StatsAggregate>,
-    health: RwLock<HealthStatus>,
+    health: RwLock<HealthReports>,
+    /// Panes the tmux collector's latest successful poll listed.
+    tmux_panes: RwLock<Option<Vec<TmuxPaneMeta>>>,
     polling_state: RwLock<PollingState>,
+    /// Consecutive degraded polls, indexed by `PollChannel`.
+    poll_error_streaks: [AtomicU32; 3],
     max_events: usize,
     session_hits: AtomicU64,
     session_misses: AtomicU64,
@@ -81,10 +268,17 @@ impl Cache {
         Self {
             sessions: DashMap::new(),
             panes: DashMap::new(),
-            recent_events: RwLock::new(VecDeque::with_capacity(max_events)),
+            pane_output_hashes: DashMap::new(),
+            pane_last_compacts: DashMap::new(),
+            session_aliases: DashMap::new(),
+            session_tags: DashMap::new(),
+            recent_events: RwLock::new(EventRing::default()),
+            event_ids: EventIdSequence::in_memory(),
             stats_today: RwLock::new(StatsAggregate::default()),
-            health: RwLock::new(HealthStatus::default()),
+            health: RwLock::new(HealthReports::default()),
+            tmux_panes: RwLock::new(None),
             polling_state: RwLock::new(PollingState::default()),
+            poll_error_streaks: [AtomicU32::new(0), AtomicU32::new(0), AtomicU32::new(0)],
             max_events: max_events.max(1),
             session_hits: AtomicU64::new(0),
             session_misses: AtomicU64::new(0),
@@ -93,11 +287,68 @@ impl Cache {
         }
     }
 
-    pub fn upsert_session(&self, session: Session) {
+    /// Assign event ids from `event_ids` (e.g. one persisted across restarts).
+    pub fn with_event_ids(mut self, event_ids: EventIdSequence) -> Self {
+        self.event_ids = event_ids;
+        self
+    }
+
+    pub fn upsert_session(&self, mut session: Session) {
+        self.apply_session_alias(&mut session);
         self.sessions
             .insert(session.session_uid.clone(), session);
     }
 
+    /// Show `alias` instead of the source's name for a session; `None` clears
+    /// it. The cached session is updated right away and later upserts keep
+    /// the alias. Returns the updated session, if it is cached.
+    pub fn set_session_alias(&self, session_uid: &str, alias: Option<String>) -> Option<Session> {
+        match alias {
+            Some(alias) => self.session_aliases.insert(session_uid.to_string(), alias),
+            None => self.session_aliases.remove(session_uid).map(|(_, alias)| alias),
+        };
+        let mut entry = self.sessions.get_mut(session_uid)?;
+        self.apply_session_alias(entry.value_mut());
+        Some(entry.clone())
+    }
+
+    pub fn session_alias(&self, session_uid: &str) -> Option<String> {
+        self.session_aliases.get(session_uid).map(|alias| alias.clone())
+    }
+
+    /// Replace a session's tags; an empty list clears them. Tags are kept
+    /// beside the session rather than on it, so upserts and reconcile keep them.
+    pub fn set_session_tags(&self, session_uid: &str, tags: Vec<String>) {
+        if tags.is_empty() {
+            self.session_tags.remove(session_uid);
+        } else {
+            self.session_tags.insert(session_uid.to_string(), tags);
+        }
+    }
+
+    pub fn session_tags(&self, session_uid: &str) -> Vec<String> {
+        self.session_tags
+            .get(session_uid)
+            .map(|tags| tags.clone())
+            .unwrap_or_default()
+    }
+
+    /// Put a session's alias (if any) in `name`, keeping the source's own name
+    /// in `raw_name`. Without an alias, `name` goes back to the source's name.
+    pub fn apply_session_alias(&self, session: &mut Session) {
+        let raw_name = match session.raw_name.take() {
+            Some(raw_name) => raw_name,
+            None => std::mem::take(&mut session.name),
+        };
+        match self.session_aliases.get(&session.session_uid) {
+            Some(alias) => {
+                session.name = alias.clone();
+                session.raw_name = Some(raw_name);
+            }
+            None => session.name = raw_name,
+        }
+    }
+
     pub fn get_session(&self, session_uid: &str) -> Option<Session> {
         if let Some(entry) = self.sessions.get(session_uid) {
             self.session_hits.fetch_add(1, Ordering::Relaxed);
@@ -112,8 +363,58 @@ impl Cache {
         self.sessions.iter().map(|entry| entry.value().clone()).collect()
     }
 
+    /// Drop a session with its alias and tags.
     pub fn remove_session(&self, session_uid: &str) {
         self.sessions.remove(session_uid);
+        self.session_aliases.remove(session_uid);
+        self.session_tags.remove(session_uid);
+    }
+
+    /// Drop sessions that ended more than `ttl_secs` before `now`, along with
+    /// their panes. Returns the number of sessions removed.
+    pub fn prune_ended_sessions(&self, now: i64, ttl_secs: i64) -> usize {
+        let cutoff = now.saturating_sub(ttl_secs);
+        let expired: Vec<String> = self
+            .sessions
+            .iter()
+            .filter(|entry| entry.ended_at.is_some_and(|ended| ended <= cutoff))
+            .map(|entry| entry.key().clone())
+            .collect();
+        for session_uid in &expired {
+            self.remove_session(session_uid);
+            let pane_uids: Vec<String> = self
+                .panes
+                .iter()
+                .filter(|entry| entry.session_uid == *session_uid)
+                .map(|entry| entry.key().clone())
+                .collect();
+            for pane_uid in pane_uids {
+                self.remove_pane(&pane_uid);
+            }
+        }
+        expired.len()
+    }
+
+    /// Drop panes last seen more than `ttl_secs` before `now` whose session
+    /// has ended or is no longer cached. Panes of live sessions are kept
+    /// however old. Returns the number of panes removed.
+    pub fn prune_stale_panes(&self, now: i64, ttl_secs: i64) -> usize {
+        let cutoff = now.saturating_sub(ttl_secs);
+        let stale: Vec<String> = self
+            .panes
+            .iter()
+            .filter(|entry| entry.last_seen_at <= cutoff)
+            .filter(|entry| {
+                self.sessions
+                    .get(&entry.session_uid)
+                    .is_none_or(|session| session.ended_at.is_some())
+            })
+            .map(|entry| entry.key().clone())
+            .collect();
+        for pane_uid in &stale {
+            self.remove_pane(pane_uid);
+        }
+        stale.len()
     }
 
     pub fn upsert_pane(&self, pane: Pane) {
@@ -136,64 +437,140 @@ impl Cache {
 
     pub fn remove_pane(&self, pane_uid: &str) {
         self.panes.remove(pane_uid);
+        self.pane_output_hashes.remove(pane_uid);
+        self.pane_last_compacts.remove(pane_uid);
     }
 
-    pub fn record_event(&self, event: EventRecord) {
-        let mut events = self
-            .recent_events
-            .write()
-            .expect("cache recent_events lock");
-        if events.len() == self.max_events {
-            events.pop_front();
+    /// When the pane's newest cached `compact` event was detected.
+    pub fn pane_last_compact_at(&self, pane_uid: &str) -> Option<i64> {
+        self.pane_last_compacts.get(pane_uid).map(|entry| *entry)
+    }
+
+    /// Record captured output for a pane. When it differs from the previous
+    /// capture, `last_activity_at` advances to `now` and `true` is returned.
+    /// The first capture only sets the baseline.
+    pub fn record_pane_output(&self, pane_uid: &str, output: &[u8], now: i64) -> bool {
+        let mut hasher = DefaultHasher::new();
+        output.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let previous = self.pane_output_hashes.insert(pane_uid.to_string(), hash);
+        let changed = previous.is_some_and(|previous| previous != hash);
+        if changed {
+            if let Some(mut pane) = self.panes.get_mut(pane_uid) {
+                pane.last_activity_at = Some(pane.last_activity_at.unwrap_or(now).max(now));
+            }
+        }
+        changed
+    }
+
+    /// Store an event, assigning the next sequence id when it has none.
+    /// Ids supplied by the caller are kept and never handed out again.
+    pub fn record_event(&self, mut event: EventRecord) {
+        match event.event_id {
+            Some(id) => self.event_ids.observe(id),
+            None => event.event_id = Some(self.event_ids.next()),
+        }
+        self.note_compact(&event);
+        locks::write(&self.recent_events, "cache recent_events").push(event, self.max_events);
+    }
+
+    /// Advance the pane's newest compact time when `event` is a compact.
+    fn note_compact(&self, event: &EventRecord) {
+        if event.event_type == "compact" && !event.pane_uid.is_empty() {
+            self.pane_last_compacts
+                .entry(event.pane_uid.clone())
+                .and_modify(|at| *at = (*at).max(event.detected_at))
+                .or_insert(event.detected_at);
         }
-        events.push_back(event);
     }
 
     pub fn recent_events(&self) -> Vec<EventRecord> {
-        self.recent_events
-            .read()
-            .expect("cache recent_events lock")
+        locks::read(&self.recent_events, "cache recent_events")
+            .events
             .iter()
             .cloned()
             .collect()
     }
 
+    /// Cached events for one session, oldest first, read from the index.
+    pub fn session_events(&self, session_uid: &str) -> Vec<EventRecord> {
+        locks::read(&self.recent_events, "cache recent_events").session_events(session_uid)
+    }
+
+    /// Cached events of `event_type` from one pane detected at or after
+    /// `since`, read through the session index.
+    pub fn pane_event_count(
+        &self,
+        session_uid: &str,
+        pane_uid: &str,
+        event_type: &str,
+        since: i64,
+    ) -> usize {
+        locks::read(&self.recent_events, "cache recent_events")
+            .pane_event_count(session_uid, pane_uid, event_type, since)
+    }
+
+    /// Number of cached events of `event_type`, read from the index.
+    pub fn event_type_count(&self, event_type: &str) -> usize {
+        locks::read(&self.recent_events, "cache recent_events")
+            .count_by_type
+            .get(event_type)
+            .copied()
+            .unwrap_or(0)
+    }
+
     pub fn set_stats_today(&self, stats: StatsAggregate) {
-        let mut guard = self.stats_today.write().expect("cache stats lock");
+        let mut guard = locks::write(&self.stats_today, "cache stats");
         *guard = stats;
     }
 
     pub fn stats_today(&self) -> StatsAggregate {
-        self.stats_today
-            .read()
-            .expect("cache stats lock")
-            .clone()
+        locks::read(&self.stats_today, "cache stats").clone()
+    }
+
+    pub fn set_tmux_panes(&self, metas: Vec<TmuxPaneMeta>) {
+        *locks::write(&self.tmux_panes, "cache tmux_panes") = Some(metas);
     }
 
+    /// What tmux listed on its latest poll; `None` until tmux has been polled.
+    pub fn tmux_panes(&self) -> Option<Vec<TmuxPaneMeta>> {
+        locks::read(&self.tmux_panes, "cache tmux_panes").clone()
+    }
+
+    /// Replace health outright, dropping every subsystem's report.
     pub fn set_health(&self, health: HealthStatus) {
-        let mut guard = self.health.write().expect("cache health lock");
-        *guard = health;
+        let mut guard = locks::write(&self.health, "cache health");
+        *guard = HealthReports {
+            base: health,
+            by_source: BTreeMap::new(),
+        };
+    }
+
+    /// Record `source`'s health; other subsystems' reports are kept.
+    pub fn report_health(&self, source: HealthSource, health: HealthStatus) {
+        locks::write(&self.health, "cache health")
+            .by_source
+            .insert(source, health);
+    }
+
+    /// Withdraw `source`'s report once it has recovered.
+    pub fn clear_health(&self, source: HealthSource) {
+        locks::write(&self.health, "cache health")
+            .by_source
+            .remove(&source);
     }
 
     pub fn health(&self) -> HealthStatus {
-        self.health
-            .read()
-            .expect("cache health lock")
-            .clone()
+        locks::read(&self.health, "cache health").overall()
     }
 
     pub fn polling_state(&self) -> PollingState {
-        self.polling_state
-            .read()
-            .expect("cache polling_state lock")
-            .clone()
+        locks::read(&self.polling_state, "cache polling_state").clone()
     }
 
     pub fn update_polling_snapshot(&self, next: PollingDatum) -> bool {
-        let mut guard = self
-            .polling_state
-            .write()
-            .expect("cache polling_state lock");
+        let mut guard = locks::write(&self.polling_state, "cache polling_state");
         if guard.snapshot == next {
             return false;
         }
@@ -202,10 +579,7 @@ impl Cache {
     }
 
     pub fn update_polling_tmux(&self, next: PollingDatum) -> bool {
-        let mut guard = self
-            .polling_state
-            .write()
-            .expect("cache polling_state lock");
+        let mut guard = locks::write(&self.polling_state, "cache polling_state");
         if guard.tmux == next {
             return false;
         }
@@ -214,10 +588,7 @@ impl Cache {
     }
 
     pub fn update_polling_ntm(&self, next: PollingDatum) -> bool {
-        let mut guard = self
-            .polling_state
-            .write()
-            .expect("cache polling_state lock");
+        let mut guard = locks::write(&self.polling_state, "cache polling_state");
         if guard.ntm == next {
             return false;
         }
@@ -225,6 +596,26 @@ impl Cache {
         true
     }
 
+    /// Record one poll on `channel`: a degraded or failed poll extends its
+    /// error streak and a clean one resets it. Returns the new streak.
+    pub fn record_poll_result(&self, channel: PollChannel, degraded: bool) -> u32 {
+        let streak = &self.poll_error_streaks[channel as usize];
+        if !degraded {
+            streak.store(0, Ordering::Relaxed);
+            return 0;
+        }
+        let previous = streak
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |n| {
+                Some(n.saturating_add(1))
+            })
+            .unwrap_or_default();
+        previous.saturating_add(1)
+    }
+
+    pub fn poll_error_streak(&self, channel: PollChannel) -> u32 {
+        self.poll_error_streaks[channel as usize].load(Ordering::Relaxed)
+    }
+
     pub fn metrics(&self) -> CacheMetrics {
         CacheMetrics {
             session_hits: self.session_hits.load(Ordering::Relaxed),
@@ -246,10 +637,7 @@ impl Cache {
 
     /// Get the number of cached events.
     pub fn event_count(&self) -> usize {
-        self.recent_events
-            .read()
-            .expect("cache recent_events lock")
-            .len()
+        locks::read(&self.recent_events, "cache recent_events").events.len()
     }
 
     pub fn apply_snapshot(&self, snapshot: CacheSnapshot) {
@@ -264,13 +652,12 @@ impl Cache {
         }
 
         {
-            let mut events = self
-                .recent_events
-                .write()
-                .expect("cache recent_events lock");
-            events.clear();
+            let mut events = locks::write(&self.recent_events, "cache recent_events");
+            *events = EventRing::default();
+            self.pane_last_compacts.clear();
             for event in snapshot.events.into_iter().take(self.max_events) {
-                events.push_back(event);
+                self.note_compact(&event);
+                events.push(event, self.max_events);
             }
         }
 
@@ -286,9 +673,10 @@ mod tests {
     fn make_session(uid: &str, name: &str) -> Session {
         Session {
             session_uid: uid.to_string(),
-            source_id: "src".to_string(),
+            source_id: "src".into(),
             tmux_session_id: None,
             name: name.to_string(),
+            raw_name: None,
             created_at: 1,
             last_seen_at: 1,
             ended_at: None,
@@ -315,9 +703,98 @@ mod tests {
             ended_at: None,
             status: crate::models::pane::PaneStatus::Active,
             status_reason: None,
+            context: None,
         }
     }
 
+    #[test]
+    fn prune_removes_sessions_ended_past_ttl() {
+        let cache = Cache::new(10);
+        let mut old = make_session("old", "old");
+        old.ended_at = Some(1_000);
+        let mut recent = make_session("recent", "recent");
+        recent.ended_at = Some(4_500);
+        cache.upsert_session(old);
+        cache.upsert_session(recent);
+        cache.upsert_session(make_session("live", "live"));
+        cache.upsert_pane(make_pane("p-old", "old"));
+        cache.upsert_pane(make_pane("p-recent", "recent"));
+        cache.set_session_alias("old", Some("renamed".to_string()));
+        cache.set_session_tags("old", vec!["ci".to_string()]);
+
+        assert_eq!(cache.prune_ended_sessions(5_000, 3_600), 1);
+        assert_eq!(cache.session_alias("old"), None);
+        assert!(cache.session_tags("old").is_empty());
+
+        let mut remaining: Vec<String> =
+            cache.all_sessions().into_iter().map(|s| s.session_uid).collect();
+        remaining.sort();
+        assert_eq!(remaining, vec!["live", "recent"]);
+        assert!(cache.get_pane("p-old").is_none());
+        assert!(cache.get_pane("p-recent").is_some());
+    }
+
+    #[test]
+    fn prune_removes_stale_panes_of_ended_or_missing_sessions() {
+        let cache = Cache::new(10);
+        let mut ended = make_session("ended", "ended");
+        ended.ended_at = Some(4_900);
+        cache.upsert_session(ended);
+        cache.upsert_session(make_session("live", "live"));
+
+        let pane = |uid: &str, session_uid: &str, last_seen_at: i64| Pane {
+            last_seen_at,
+            ..make_pane(uid, session_uid)
+        };
+        cache.upsert_pane(pane("stale-ended", "ended", 1_000));
+        cache.upsert_pane(pane("stale-orphan", "gone", 1_000));
+        cache.upsert_pane(pane("stale-live", "live", 1_000));
+        cache.upsert_pane(pane("recent-ended", "ended", 4_800));
+        cache.upsert_pane(pane("recent-orphan", "gone", 4_900));
+
+        assert_eq!(cache.prune_stale_panes(5_000, 600), 2);
+
+        let mut remaining: Vec<String> =
+            cache.all_panes().into_iter().map(|p| p.pane_uid).collect();
+        remaining.sort();
+        assert_eq!(remaining, vec!["recent-ended", "recent-orphan", "stale-live"]);
+        assert_eq!(cache.session_count(), 2, "sessions are left alone");
+    }
+
+    #[test]
+    fn changed_output_advances_last_activity() {
+        let cache = Cache::new(10);
+        cache.upsert_pane(make_pane("pane-1", "sess-1"));
+
+        assert!(!cache.record_pane_output("pane-1", b"$ cargo build", 100), "baseline");
+        assert_eq!(cache.get_pane("pane-1").unwrap().last_activity_at, Some(1));
+
+        assert!(cache.record_pane_output("pane-1", b"$ cargo build\nCompiling", 200));
+        assert_eq!(cache.get_pane("pane-1").unwrap().last_activity_at, Some(200));
+    }
+
+    #[test]
+    fn unchanged_output_keeps_last_activity() {
+        let cache = Cache::new(10);
+        cache.upsert_pane(make_pane("pane-1", "sess-1"));
+        cache.record_pane_output("pane-1", b"idle prompt", 100);
+        cache.record_pane_output("pane-1", b"busy", 150);
+
+        assert!(!cache.record_pane_output("pane-1", b"busy", 300));
+        assert!(!cache.record_pane_output("pane-1", b"busy", 400));
+        assert_eq!(cache.get_pane("pane-1").unwrap().last_activity_at, Some(150));
+    }
+
+    #[test]
+    fn removing_pane_forgets_output_hash() {
+        let cache = Cache::new(10);
+        cache.upsert_pane(make_pane("pane-1", "sess-1"));
+        cache.record_pane_output("pane-1", b"a", 100);
+        cache.remove_pane("pane-1");
+        cache.upsert_pane(make_pane("pane-1", "sess-1"));
+        assert!(!cache.record_pane_output("pane-1", b"b", 200), "new baseline");
+    }
+
     #[test]
     fn cache_metrics_track_hits() {
         let cache = Cache::new(10);
@@ -352,14 +829,173 @@ mod tests {
         assert_eq!(events[1].event_id, Some(2));
     }
 
+    fn typed_event(session_uid: &str, event_type: &str) -> EventRecord {
+        EventRecord {
+            event_id: None,
+            session_uid: session_uid.to_string(),
+            pane_uid: "pane".to_string(),
+            event_type: event_type.to_string(),
+            detected_at: 1,
+            severity: None,
+            status: None,
+        }
+    }
+
+    #[test]
+    fn compact_events_track_last_compact_per_pane() {
+        let cache = Cache::new(10);
+        assert_eq!(cache.pane_last_compact_at("pane"), None);
+
+        cache.record_event(EventRecord { detected_at: 100, ..typed_event("sess", "compact") });
+        cache.record_event(EventRecord { detected_at: 50, ..typed_event("sess", "compact") });
+        cache.record_event(EventRecord { detected_at: 200, ..typed_event("sess", "idle") });
+        assert_eq!(cache.pane_last_compact_at("pane"), Some(100), "older compacts never win");
+
+        cache.record_event(EventRecord { detected_at: 150, ..typed_event("sess", "compact") });
+        assert_eq!(cache.pane_last_compact_at("pane"), Some(150));
+
+        cache.remove_pane("pane");
+        assert_eq!(cache.pane_last_compact_at("pane"), None);
+    }
+
+    /// Rebuild what the index should hold by scanning the ring.
+    fn assert_index_matches_ring(cache: &Cache) {
+        let events = cache.recent_events();
+        for event in &events {
+            let scanned: Vec<Option<i64>> = events
+                .iter()
+                .filter(|e| e.session_uid == event.session_uid)
+                .map(|e| e.event_id)
+                .collect();
+            let indexed: Vec<Option<i64>> =
+                cache.session_events(&event.session_uid).iter().map(|e| e.event_id).collect();
+            assert_eq!(indexed, scanned);
+            let of_type = events.iter().filter(|e| e.event_type == event.event_type).count();
+            assert_eq!(cache.event_type_count(&event.event_type), of_type);
+        }
+    }
+
+    #[test]
+    fn event_index_tracks_inserts_and_evictions() {
+        let cache = Cache::new(4);
+        let sessions = ["a", "b", "a", "c", "a", "b", "b", "c", "a"];
+        let types = ["compact", "escalation", "compact", "idle"];
+        for (i, session) in sessions.iter().enumerate() {
+            cache.record_event(typed_event(session, types[i % types.len()]));
+            assert_index_matches_ring(&cache);
+        }
+
+        // Only the last four events (ids 6..=9) remain
+        let ids: Vec<Option<i64>> = cache.session_events("b").iter().map(|e| e.event_id).collect();
+        assert_eq!(ids, vec![Some(6), Some(7)]);
+        assert_eq!(cache.session_events("a").len(), 1);
+        assert!(cache.session_events("missing").is_empty());
+        assert_eq!(cache.event_type_count("escalation"), 1);
+        assert_eq!(cache.event_type_count("unknown"), 0);
+    }
+
+    #[test]
+    fn event_index_is_rebuilt_by_apply_snapshot() {
+        let cache = Cache::new(3);
+        cache.record_event(typed_event("old", "compact"));
+        cache.apply_snapshot(CacheSnapshot {
+            events: vec![
+                EventRecord { event_id: Some(10), ..typed_event("new", "idle") },
+                EventRecord { event_id: Some(11), ..typed_event("new", "compact") },
+            ],
+            ..CacheSnapshot::default()
+        });
+
+        assert!(cache.session_events("old").is_empty());
+        assert_eq!(cache.session_events("new").len(), 2);
+        assert_eq!(cache.event_type_count("compact"), 1);
+        cache.record_event(typed_event("new", "idle"));
+        cache.record_event(typed_event("old", "idle"));
+        assert_index_matches_ring(&cache);
+        assert_eq!(cache.event_type_count("idle"), 2);
+    }
+
+    #[test]
+    fn last_compacts_are_rebuilt_by_apply_snapshot() {
+        let cache = Cache::new(10);
+        cache.record_event(EventRecord { detected_at: 50, ..typed_event("old", "compact") });
+        let event = |pane_uid: &str, event_type: &str, detected_at: i64| EventRecord {
+            pane_uid: pane_uid.to_string(),
+            detected_at,
+            ..typed_event("sess", event_type)
+        };
+        cache.apply_snapshot(CacheSnapshot {
+            events: vec![
+                event("p1", "compact", 100),
+                event("p1", "compact", 300),
+                event("p1", "idle", 400),
+                event("p2", "idle", 200),
+            ],
+            ..CacheSnapshot::default()
+        });
+
+        assert_eq!(cache.pane_last_compact_at("p1"), Some(300));
+        assert_eq!(cache.pane_last_compact_at("p2"), None);
+        assert_eq!(cache.pane_last_compact_at("pane"), None, "dropped with the old events");
+    }
+
+    #[test]
+    fn record_event_assigns_increasing_ids() {
+        let cache = Cache::new(10);
+        let event = EventRecord {
+            event_id: None,
+            session_uid: "sess".to_string(),
+            pane_uid: "pane".to_string(),
+            event_type: "compact".to_string(),
+            detected_at: 1,
+            severity: None,
+            status: None,
+        };
+        cache.record_event(event.clone());
+        cache.record_event(EventRecord {
+            event_id: Some(5),
+            ..event.clone()
+        });
+        cache.record_event(event);
+
+        let ids: Vec<Option<i64>> = cache.recent_events().iter().map(|e| e.event_id).collect();
+        assert_eq!(ids, vec![Some(1), Some(5), Some(6)]);
+    }
+
+    #[test]
+    fn poisoned_locks_keep_serving_reads_and_writes() {
+        use std::sync::Arc;
+
+        let cache = Arc::new(Cache::new(10));
+        cache.record_event(typed_event("sess", "compact"));
+
+        let poisoner = cache.clone();
+        let _ = std::thread::spawn(move || {
+            let _events = poisoner.recent_events.write().unwrap();
+            let _health = poisoner.health.write().unwrap();
+            panic!("poison the cache locks");
+        })
+        .join();
+        assert!(cache.recent_events.is_poisoned());
+
+        assert_eq!(cache.event_count(), 1);
+        cache.record_event(typed_event("sess", "idle"));
+        assert_eq!(cache.event_type_count("idle"), 1);
+        cache.set_health(HealthStatus::default());
+        let _ = cache.health();
+        assert!(!cache.recent_events.is_poisoned());
+        assert!(!cache.health.is_poisoned());
+    }
+
     #[test]
     fn snapshot_overwrites_state() {
         let cache = Cache::new(5);
         cache.upsert_session(Session {
             session_uid: "old".to_string(),
-            source_id: "src".to_string(),
+            source_id: "src".into(),
             tmux_session_id: None,
             name: "old".to_string(),
+            raw_name: None,
             created_at: 0,
             last_seen_at: 0,
             ended_at: None,
@@ -372,9 +1008,10 @@ mod tests {
         let snapshot = CacheSnapshot {
             sessions: vec![Session {
                 session_uid: "new".to_string(),
-                source_id: "src".to_string(),
+                source_id: "src".into(),
                 tmux_session_id: None,
                 name: "new".to_string(),
+                raw_name: None,
                 created_at: 2,
                 last_seen_at: 2,
                 ended_at: None,
@@ -393,6 +1030,7 @@ mod tests {
             health: HealthStatus {
                 status: "ok".to_string(),
                 last_error: None,
+                degraded_reason: None,
             },
         };
 
@@ -442,8 +1080,13 @@ mod tests {
         cache.upsert_session(make_session("sess-1", "alpha"));
         assert!(cache.get_session("sess-1").is_some());
 
+        cache.set_session_alias("sess-1", Some("renamed".to_string()));
+        cache.set_session_tags("sess-1", vec!["ci".to_string()]);
+
         cache.remove_session("sess-1");
         assert!(cache.get_session("sess-1").is_none());
+        assert_eq!(cache.session_alias("sess-1"), None);
+        assert!(cache.session_tags("sess-1").is_empty());
     }
 
     #[test]
@@ -470,6 +1113,28 @@ mod tests {
         assert_eq!(cache.session_count(), 1);
     }
 
+    #[test]
+    fn session_alias_replaces_name_and_keeps_raw_name() {
+        let cache = Cache::new(10);
+        cache.upsert_session(make_session("sess-1", "alpha"));
+
+        let aliased = cache
+            .set_session_alias("sess-1", Some("Frontend".to_string()))
+            .unwrap();
+        assert_eq!(aliased.name, "Frontend");
+        assert_eq!(aliased.raw_name.as_deref(), Some("alpha"));
+
+        // A fresh upsert from a collector keeps the alias
+        cache.upsert_session(make_session("sess-1", "alpha-2"));
+        let retrieved = cache.get_session("sess-1").unwrap();
+        assert_eq!(retrieved.name, "Frontend");
+        assert_eq!(retrieved.source_name(), "alpha-2");
+
+        let cleared = cache.set_session_alias("sess-1", None).unwrap();
+        assert_eq!(cleared.name, "alpha-2");
+        assert_eq!(cleared.raw_name, None);
+    }
+
     #[test]
     fn upsert_pane_updates_existing() {
         let cache = Cache::new(10);
@@ -506,6 +1171,7 @@ mod tests {
         let health = HealthStatus {
             status: "degraded".to_string(),
             last_error: Some("connection timeout".to_string()),
+            degraded_reason: None,
         };
         cache.set_health(health);
 
@@ -655,6 +1321,24 @@ mod tests {
 
     // --- Polling state tests ---
 
+    #[test]
+    fn poll_error_streak_tracks_and_resets_per_channel() {
+        let cache = Cache::new(10);
+        assert_eq!(cache.record_poll_result(PollChannel::Tmux, true), 1);
+        assert_eq!(cache.record_poll_result(PollChannel::Tmux, true), 2);
+        assert_eq!(cache.record_poll_result(PollChannel::Ntm, true), 1);
+        assert_eq!(cache.poll_error_streak(PollChannel::Tmux), 2);
+        assert_eq!(cache.poll_error_streak(PollChannel::Snapshot), 0);
+
+        assert_eq!(cache.record_poll_result(PollChannel::Tmux, false), 0);
+        assert_eq!(cache.poll_error_streak(PollChannel::Tmux), 0);
+        assert_eq!(
+            cache.poll_error_streak(PollChannel::Ntm),
+            1,
+            "other channels untouched"
+        );
+    }
+
     #[test]
     fn update_polling_snapshot_returns_true_on_change() {
         let cache = Cache::new(10);
@@ -774,12 +1458,14 @@ mod tests {
         cache.set_health(HealthStatus {
             status: "ok".to_string(),
             last_error: None,
+            degraded_reason: None,
         });
         assert_eq!(cache.health().status, "ok");
 
         cache.set_health(HealthStatus {
             status: "degraded".to_string(),
             last_error: Some("tmux timeout".to_string()),
+            degraded_reason: None,
         });
         assert_eq!(cache.health().status, "degraded");
         assert_eq!(cache.health().last_error.as_deref(), Some("tmux timeout"));
@@ -787,11 +1473,57 @@ mod tests {
         cache.set_health(HealthStatus {
             status: "ok".to_string(),
             last_error: None,
+            degraded_reason: None,
         });
         assert_eq!(cache.health().status, "ok");
         assert!(cache.health().last_error.is_none());
     }
 
+    #[test]
+    fn subsystems_clear_only_their_own_degradation() {
+        let cache = Cache::new(10);
+        cache.set_health(HealthStatus {
+            status: "ok".to_string(),
+            last_error: None,
+            degraded_reason: None,
+        });
+        cache.report_health(
+            HealthSource::Db,
+            HealthStatus {
+                status: "degraded".to_string(),
+                last_error: Some("db: database is locked".to_string()),
+                degraded_reason: Some(DegradedReason::DbLocked),
+            },
+        );
+        cache.report_health(
+            HealthSource::Tmux,
+            HealthStatus {
+                status: "ok".to_string(),
+                last_error: Some("tmux: timed out".to_string()),
+                degraded_reason: None,
+            },
+        );
+        cache.clear_health(HealthSource::Tmux);
+        cache.clear_health(HealthSource::Ntm);
+        let health = cache.health();
+        assert_eq!(health.status, "degraded");
+        assert_eq!(health.degraded_reason, Some(DegradedReason::DbLocked));
+
+        cache.clear_health(HealthSource::Db);
+        cache.report_health(
+            HealthSource::Ntm,
+            HealthStatus {
+                status: "ok".to_string(),
+                last_error: Some("ntm: exit code".to_string()),
+                degraded_reason: None,
+            },
+        );
+        let health = cache.health();
+        assert_eq!(health.status, "ok");
+        assert_eq!(health.last_error.as_deref(), Some("ntm: exit code"));
+        assert_eq!(health.degraded_reason, None);
+    }
+
     // --- Concurrent access ---
 
     #[test]
@@ -894,6 +1626,7 @@ mod tests {
             health: HealthStatus {
                 status: "ok".to_string(),
                 last_error: None,
+                degraded_reason: None,
             },
         };
 
@@ -1,23 +1,94 @@
 //! CLI commands for daemon management.
 //!
-//! Client commands (health, status, events, self-test) connect to a running daemon
+//! Client commands (health, status, events, stats, self-test) connect to a running daemon
 //! via HTTP and issue RPC requests.
 
+use crate::service::InstanceMetadata;
 use serde::Deserialize;
 use serde_json::{json, Value};
 use std::io::{Read, Write};
-use std::net::{Shutdown, TcpStream};
+use std::net::{Shutdown, SocketAddr, TcpStream};
 use std::path::PathBuf;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 /// Default HTTP port for client connections.
 pub const DEFAULT_PORT: u16 = 3847;
 
+/// Port client commands connect to: an explicit `--port`, else the running
+/// daemon's HTTP port from its instance metadata, else [`DEFAULT_PORT`].
+pub fn resolve_port(explicit: Option<u16>) -> u16 {
+    match explicit {
+        Some(port) => port,
+        None => port_from_metadata(crate::service::discover_instance().as_ref()),
+    }
+}
+
+fn port_from_metadata(metadata: Option<&InstanceMetadata>) -> u16 {
+    metadata
+        .and_then(|metadata| metadata.port)
+        .unwrap_or(DEFAULT_PORT)
+}
+
+/// Default time a client command waits for the daemon to respond.
+pub const DEFAULT_TIMEOUT_MS: u64 = 10_000;
+
+/// Default number of extra connection attempts when the daemon is not listening yet.
+pub const DEFAULT_CONNECT_RETRIES: u32 = 3;
+
+/// Delay before the first connection retry; doubles on each further attempt.
+const CONNECT_BACKOFF_BASE: Duration = Duration::from_millis(100);
+
+/// Connection settings shared by the client commands.
+#[derive(Debug, Clone)]
+pub struct ClientOptions {
+    pub port: u16,
+    pub admin_token: Option<String>,
+    /// Upper bound on connecting, sending, and reading the response.
+    pub timeout: Duration,
+    /// Extra attempts made while the daemon refuses connections.
+    pub connect_retries: u32,
+}
+
+impl ClientOptions {
+    pub fn new(port: u16) -> Self {
+        Self {
+            port,
+            admin_token: None,
+            timeout: Duration::from_millis(DEFAULT_TIMEOUT_MS),
+            connect_retries: DEFAULT_CONNECT_RETRIES,
+        }
+    }
+
+    pub fn client(&self) -> DaemonClient {
+        let client = DaemonClient::new(self.port)
+            .with_timeout(self.timeout)
+            .with_connect_retries(self.connect_retries);
+        match &self.admin_token {
+            Some(value) => client.with_admin_token(value.clone()),
+            None => client,
+        }
+    }
+}
+
+/// Something client commands can send RPC requests through; the daemon over
+/// HTTP in practice, canned results in tests.
+pub trait RpcClient {
+    fn call(&self, method: &str, params: Value) -> Result<Value, CliError>;
+}
+
+impl RpcClient for DaemonClient {
+    fn call(&self, method: &str, params: Value) -> Result<Value, CliError> {
+        DaemonClient::call(self, method, params)
+    }
+}
+
 /// HTTP client for daemon RPC calls.
 pub struct DaemonClient {
     host: String,
     port: u16,
     admin_auth_header: Option<String>,
+    timeout: Duration,
+    connect_retries: u32,
 }
 
 impl DaemonClient {
@@ -26,6 +97,8 @@ impl DaemonClient {
             host: "127.0.0.1".to_string(),
             port,
             admin_auth_header: None,
+            timeout: Duration::from_millis(DEFAULT_TIMEOUT_MS),
+            connect_retries: DEFAULT_CONNECT_RETRIES,
         }
     }
 
@@ -34,6 +107,16 @@ impl DaemonClient {
         self
     }
 
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_connect_retries(mut self, retries: u32) -> Self {
+        self.connect_retries = retries;
+        self
+    }
+
     /// Make an RPC call to the daemon.
     pub fn call(&self, method: &str, params: Value) -> Result<Value, CliError> {
         let request = json!({
@@ -63,32 +146,31 @@ impl DaemonClient {
         http_request.push_str("\r\n");
         http_request.push_str(&body);
 
-        // Connect and send
-        let addr = format!("{}:{}", self.host, self.port);
-        let mut stream = TcpStream::connect(&addr).map_err(|e| {
-            if e.kind() == std::io::ErrorKind::ConnectionRefused {
-                CliError::DaemonNotRunning
-            } else {
-                CliError::Connection(e.to_string())
-            }
-        })?;
+        // Connect and send; the whole exchange shares one deadline
+        let deadline = Instant::now() + self.timeout;
+        let mut stream = self.connect(deadline)?;
 
         stream
-            .set_read_timeout(Some(Duration::from_secs(10)))
+            .set_write_timeout(Some(remaining(deadline, self.timeout)?))
             .ok();
-        stream
-            .set_write_timeout(Some(Duration::from_secs(5)))
-            .ok();
-
         stream
             .write_all(http_request.as_bytes())
-            .map_err(|e| CliError::Connection(e.to_string()))?;
+            .map_err(|e| self.io_error(e))?;
 
-        // Read response
+        // Read response until the daemon closes the connection
         let mut response = Vec::new();
-        stream
-            .read_to_end(&mut response)
-            .map_err(|e| CliError::Connection(e.to_string()))?;
+        let mut chunk = [0u8; 8192];
+        loop {
+            stream
+                .set_read_timeout(Some(remaining(deadline, self.timeout)?))
+                .ok();
+            match stream.read(&mut chunk) {
+                Ok(0) => break,
+                Ok(n) => response.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(self.io_error(e)),
+            }
+        }
         let _ = stream.shutdown(Shutdown::Both);
 
         let response_str = String::from_utf8_lossy(&response);
@@ -112,6 +194,51 @@ impl DaemonClient {
             Ok(rpc_response.result.unwrap_or(Value::Null))
         }
     }
+
+    /// Connect to the daemon, retrying with backoff while it refuses
+    /// connections (e.g. it was started moments ago and is not listening yet).
+    fn connect(&self, deadline: Instant) -> Result<TcpStream, CliError> {
+        let addr = format!("{}:{}", self.host, self.port);
+        let socket_addr: SocketAddr = addr
+            .parse()
+            .map_err(|_| CliError::Connection(format!("invalid address {addr}")))?;
+
+        let mut attempt = 0;
+        loop {
+            let left = remaining(deadline, self.timeout)?;
+            match TcpStream::connect_timeout(&socket_addr, left) {
+                Ok(stream) => return Ok(stream),
+                Err(e) if e.kind() == std::io::ErrorKind::ConnectionRefused => {
+                    if attempt >= self.connect_retries {
+                        return Err(CliError::DaemonNotRunning);
+                    }
+                    let backoff = CONNECT_BACKOFF_BASE.saturating_mul(1 << attempt.min(6));
+                    let left = remaining(deadline, self.timeout)?;
+                    std::thread::sleep(backoff.min(left));
+                    attempt += 1;
+                }
+                Err(e) => return Err(self.io_error(e)),
+            }
+        }
+    }
+
+    fn io_error(&self, err: std::io::Error) -> CliError {
+        match err.kind() {
+            std::io::ErrorKind::TimedOut | std::io::ErrorKind::WouldBlock => {
+                CliError::Timeout(self.timeout)
+            }
+            _ => CliError::Connection(err.to_string()),
+        }
+    }
+}
+
+/// Time left before `deadline`, or a timeout error once it has passed.
+fn remaining(deadline: Instant, timeout: Duration) -> Result<Duration, CliError> {
+    let left = deadline.saturating_duration_since(Instant::now());
+    if left.is_zero() {
+        return Err(CliError::Timeout(timeout));
+    }
+    Ok(left)
 }
 
 #[derive(Debug, Deserialize)]
@@ -130,11 +257,50 @@ struct RpcErrorData {
     message: String,
 }
 
+/// Process exit codes, so scripts can branch on daemon state. Every client
+/// command maps its outcome through [`exit_code_of`].
+pub mod exit_code {
+    /// The command succeeded (and `health` reported `ok`).
+    pub const OK: i32 = 0;
+    /// Any failure without a more specific code below.
+    pub const FAILURE: i32 = 1;
+    /// Invalid configuration or arguments; clap also exits 2 on bad usage.
+    pub const CONFIG: i32 = 2;
+    /// The daemon answered but is degraded (`health`, or a `DEGRADED` RPC error).
+    pub const DEGRADED: i32 = 3;
+    /// No daemon is running, or it refused every connection attempt.
+    pub const NOT_RUNNING: i32 = 4;
+    /// The daemon did not respond within `--timeout-ms`.
+    pub const TIMEOUT: i32 = 5;
+    /// The requested session or resource does not exist.
+    pub const NOT_FOUND: i32 = 6;
+    /// The admin token was missing or rejected.
+    pub const UNAUTHORIZED: i32 = 7;
+}
+
+/// Exit code for a finished command: its own code on success, else the
+/// error's [`CliError::exit_code`].
+pub fn exit_code_of(result: &Result<i32, CliError>) -> i32 {
+    match result {
+        Ok(code) => *code,
+        Err(err) => err.exit_code(),
+    }
+}
+
 /// CLI errors.
 #[derive(Debug)]
 pub enum CliError {
     DaemonNotRunning,
+    /// The PID file names a process that no longer exists.
+    StalePidFile(i32),
+    /// `stop` signalled the daemon but it had not exited when the wait ran out.
+    StillRunning {
+        pid: i32,
+        waited: Duration,
+    },
+    NotFound(String),
     Connection(String),
+    Timeout(Duration),
     Protocol(String),
     Rpc { code: String, message: String },
     Io(std::io::Error),
@@ -147,7 +313,21 @@ impl std::fmt::Display for CliError {
             Self::DaemonNotRunning => {
                 write!(f, "Daemon is not running. Start with: ntm-tracker-daemon start")
             }
+            Self::StalePidFile(pid) => {
+                write!(f, "Daemon is not running (stale PID file for PID {pid})")
+            }
+            Self::StillRunning { pid, waited } => write!(
+                f,
+                "Daemon (PID {pid}) still running after {}ms; use --force to kill it",
+                waited.as_millis()
+            ),
+            Self::NotFound(msg) => write!(f, "{msg}"),
             Self::Connection(msg) => write!(f, "Connection error: {msg}"),
+            Self::Timeout(timeout) => write!(
+                f,
+                "Timed out after {}ms waiting for the daemon (see --timeout-ms)",
+                timeout.as_millis()
+            ),
             Self::Protocol(msg) => write!(f, "Protocol error: {msg}"),
             Self::Rpc { code, message } => write!(f, "RPC error [{code}]: {message}"),
             Self::Io(e) => write!(f, "I/O error: {e}"),
@@ -156,6 +336,29 @@ impl std::fmt::Display for CliError {
     }
 }
 
+impl CliError {
+    /// The [`exit_code`] this error ends the process with.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::DaemonNotRunning | Self::StalePidFile(_) => exit_code::NOT_RUNNING,
+            Self::Timeout(_) => exit_code::TIMEOUT,
+            Self::NotFound(_) => exit_code::NOT_FOUND,
+            Self::Rpc { code, .. } => match code.as_str() {
+                "DEGRADED" => exit_code::DEGRADED,
+                "NOT_FOUND" => exit_code::NOT_FOUND,
+                "UNAUTHORIZED" | "FORBIDDEN" => exit_code::UNAUTHORIZED,
+                "INVALID_PARAMS" => exit_code::CONFIG,
+                _ => exit_code::FAILURE,
+            },
+            Self::StillRunning { .. }
+            | Self::Connection(_)
+            | Self::Protocol(_)
+            | Self::Io(_)
+            | Self::Json(_) => exit_code::FAILURE,
+        }
+    }
+}
+
 impl std::error::Error for CliError {}
 
 impl From<std::io::Error> for CliError {
@@ -228,23 +431,100 @@ fn print_simple_value(value: &Value) {
     }
 }
 
-/// Execute the 'health' command.
-pub fn cmd_health(port: u16, format: OutputFormat, admin_token: Option<String>) -> Result<(), CliError> {
-    let mut client = DaemonClient::new(port);
-    if let Some(value) = admin_token {
-        client = client.with_admin_token(value);
+/// Output format for the 'health' command, which adds a compact one-line
+/// form for shell prompts and status bars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HealthFormat {
+    Text,
+    Json,
+    Oneline,
+}
+
+impl From<OutputFormat> for HealthFormat {
+    fn from(format: OutputFormat) -> Self {
+        match format {
+            OutputFormat::Text => Self::Text,
+            OutputFormat::Json => Self::Json,
+        }
     }
+}
+
+impl std::str::FromStr for HealthFormat {
+    type Err = String;
 
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            "oneline" => Ok(Self::Oneline),
+            other => Err(format!(
+                "unknown health format {other:?} (expected text, json, or oneline)"
+            )),
+        }
+    }
+}
+
+/// Execute the 'health' command. Returns [`exit_code::DEGRADED`] when the
+/// daemon answers but reports anything other than `ok`.
+pub fn cmd_health(client: &impl RpcClient, format: HealthFormat) -> Result<i32, CliError> {
     let result = client.call("health.get", json!({}))?;
-    print_output(&result, format);
-    Ok(())
+    match format {
+        HealthFormat::Text => print_output(&result, OutputFormat::Text),
+        HealthFormat::Json => print_output(&result, OutputFormat::Json),
+        HealthFormat::Oneline => println!("{}", format_health_oneline(&result)),
+    }
+    match result.get("status").and_then(|v| v.as_str()) {
+        Some("ok") => Ok(exit_code::OK),
+        _ => Ok(exit_code::DEGRADED),
+    }
+}
+
+/// Render a `health.get` result as one line, e.g. `ok uptime=2h13m events=42`
+/// or `degraded reason=tmux_unavailable uptime=5m events=7`.
+pub fn format_health_oneline(health: &Value) -> String {
+    let status = health
+        .get("status")
+        .and_then(|v| v.as_str())
+        .filter(|v| !v.is_empty())
+        .unwrap_or("unknown");
+    let mut out = status.to_string();
+
+    if let Some(reason) = health.get("degradedReason").and_then(|v| v.as_str()) {
+        out.push_str(&format!(" reason={reason}"));
+    }
+    let uptime = health
+        .get("uptimeDetail")
+        .and_then(|d| d.get("humanized"))
+        .and_then(|v| v.as_str())
+        .map(|v| v.replace(' ', ""))
+        .or_else(|| health.get("uptime").and_then(|v| v.as_u64()).map(|v| format!("{v}s")));
+    if let Some(uptime) = uptime {
+        out.push_str(&format!(" uptime={uptime}"));
+    }
+    if let Some(last_event_id) = health.get("lastEventId").and_then(|v| v.as_i64()) {
+        out.push_str(&format!(" events={last_event_id}"));
+    }
+    if health.get("safeMode").and_then(|v| v.as_bool()) == Some(true) {
+        out.push_str(" safe-mode");
+    }
+    out
 }
 
 /// Execute the 'status' command (list sessions).
-pub fn cmd_status(port: u16, format: OutputFormat, admin_token: Option<String>) -> Result<(), CliError> {
-    let mut client = DaemonClient::new(port);
-    if let Some(value) = admin_token {
-        client = client.with_admin_token(value);
+pub fn cmd_status(
+    client: &impl RpcClient,
+    format: OutputFormat,
+    polling: bool,
+) -> Result<(), CliError> {
+    if polling {
+        let result = client.call("health.get", json!({}))?;
+        let polling = result.get("polling").cloned().unwrap_or(Value::Null);
+        if format == OutputFormat::Text {
+            print!("{}", format_polling_state(&polling));
+        } else {
+            print_output(&polling, format);
+        }
+        return Ok(());
     }
 
     let result = client.call("sessions.list", json!({}))?;
@@ -277,18 +557,123 @@ pub fn cmd_status(port: u16, format: OutputFormat, admin_token: Option<String>)
     Ok(())
 }
 
+/// Execute the 'panes' command (list panes for one session).
+pub fn cmd_panes(
+    client: &impl RpcClient,
+    format: OutputFormat,
+    session_id: &str,
+) -> Result<(), CliError> {
+    let result = client
+        .call("panes.list", json!({ "sessionId": session_id }))
+        .map_err(|err| match err {
+            CliError::Rpc { ref code, .. } if code == "NOT_FOUND" => {
+                CliError::NotFound(format!("Session '{session_id}' not found"))
+            }
+            other => other,
+        })?;
+
+    if format == OutputFormat::Text {
+        match result.get("panes").and_then(|value| value.as_array()) {
+            Some(panes) if panes.is_empty() => println!("Session '{session_id}' has no panes"),
+            Some(panes) => print!("{}", format_panes_table(panes)),
+            None => print_output(&result, format),
+        }
+    } else {
+        print_output(&result, format);
+    }
+    Ok(())
+}
+
+/// Render pane views as a fixed-width table (one header row, one row per pane).
+pub fn format_panes_table(panes: &[Value]) -> String {
+    const HEADERS: [&str; 5] = ["INDEX", "PANE", "STATUS", "AGENT", "COMMAND"];
+
+    let rows: Vec<[String; 5]> = panes
+        .iter()
+        .map(|pane| {
+            let text = |key: &str| {
+                pane.get(key)
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("-")
+                    .to_string()
+            };
+            let index = pane
+                .get("paneIndex")
+                .and_then(|v| v.as_i64())
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "-".to_string());
+            let pane_ref = pane
+                .get("tmuxPaneId")
+                .and_then(|v| v.as_str())
+                .or_else(|| pane.get("paneId").and_then(|v| v.as_str()))
+                .unwrap_or("-")
+                .to_string();
+            [index, pane_ref, text("status"), text("agentType"), text("currentCommand")]
+        })
+        .collect();
+
+    let mut widths = HEADERS.map(str::len);
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let mut out = String::new();
+    let mut push_row = |cells: [&str; 5]| {
+        let line = cells
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{cell:<width$}"))
+            .collect::<Vec<_>>()
+            .join("  ");
+        out.push_str(line.trim_end());
+        out.push('\n');
+    };
+    push_row(HEADERS);
+    for row in &rows {
+        push_row([&row[0], &row[1], &row[2], &row[3], &row[4]]);
+    }
+    out
+}
+
+/// Render the per-channel polling decisions from `health.get`'s `polling` field.
+pub fn format_polling_state(polling: &Value) -> String {
+    const CHANNELS: [&str; 3] = ["snapshot", "tmux", "ntm"];
+
+    let mut out = String::new();
+    for channel in CHANNELS {
+        let datum = polling.get(channel);
+        let text = |key: &str| {
+            datum
+                .and_then(|d| d.get(key))
+                .and_then(|v| v.as_str())
+                .filter(|v| !v.is_empty())
+                .unwrap_or("-")
+                .to_string()
+        };
+        let interval = datum
+            .and_then(|d| d.get("interval_ms"))
+            .and_then(|v| v.as_u64())
+            .filter(|v| *v > 0)
+            .map(|v| format!("{v}ms"))
+            .unwrap_or_else(|| "-".to_string());
+        out.push_str(&format!(
+            "{channel:<9} mode={:<10} interval={:<8} reason={}\n",
+            text("mode"),
+            interval,
+            text("reason"),
+        ));
+    }
+    out
+}
+
 /// Execute the 'events' command.
 pub fn cmd_events(
-    port: u16,
+    client: &impl RpcClient,
     format: OutputFormat,
-    admin_token: Option<String>,
     limit: Option<u32>,
 ) -> Result<(), CliError> {
-    let mut client = DaemonClient::new(port);
-    if let Some(value) = admin_token {
-        client = client.with_admin_token(value);
-    }
-
     let params = json!({
         "limit": limit.unwrap_or(20),
     });
@@ -332,17 +717,122 @@ pub fn cmd_events(
     Ok(())
 }
 
-/// Execute the 'self-test' command.
-pub fn cmd_self_test(port: u16, format: OutputFormat, admin_token: Option<String>) -> Result<(), CliError> {
-    let mut client = DaemonClient::new(port);
-    if let Some(value) = admin_token {
-        client = client.with_admin_token(value);
+/// Which `stats.*` view the 'stats' command shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatsView {
+    Summary,
+    Hourly,
+    Daily,
+}
+
+impl StatsView {
+    fn method(self) -> &'static str {
+        match self {
+            Self::Summary => "stats.summary",
+            Self::Hourly => "stats.hourly",
+            Self::Daily => "stats.daily",
+        }
+    }
+}
+
+/// Execute the 'stats' command.
+pub fn cmd_stats(
+    client: &impl RpcClient,
+    format: OutputFormat,
+    view: StatsView,
+) -> Result<(), CliError> {
+    let result = client.call(view.method(), json!({}))?;
+
+    if format == OutputFormat::Text {
+        let key = match view {
+            StatsView::Summary => {
+                match result.get("summary") {
+                    Some(summary) => print!("{}", format_stats_summary(summary)),
+                    None => print_output(&result, format),
+                }
+                return Ok(());
+            }
+            StatsView::Hourly => "hourly",
+            StatsView::Daily => "daily",
+        };
+        match result.get(key).and_then(|value| value.as_array()) {
+            Some(rows) if rows.is_empty() => println!("No {key} stats recorded yet"),
+            Some(rows) => print!("{}", format_stats_table(rows, view)),
+            None => print_output(&result, format),
+        }
+    } else {
+        print_output(&result, format);
+    }
+    Ok(())
+}
+
+/// Render the `stats.summary` payload as aligned label/value lines.
+pub fn format_stats_summary(summary: &Value) -> String {
+    const FIELDS: [(&str, &str); 5] = [
+        ("sessions", "Sessions"),
+        ("panes", "Panes"),
+        ("totalCompacts", "Compacts today"),
+        ("activeMinutes", "Active minutes"),
+        ("estimatedTokens", "Est. tokens"),
+    ];
+
+    let mut out = String::new();
+    for (key, label) in FIELDS {
+        let value = summary
+            .get(key)
+            .and_then(|v| v.as_u64())
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| "-".to_string());
+        out.push_str(&format!("{label:<16}{value}\n"));
     }
+    out
+}
+
+/// Render hourly or daily stats rows as a table with a token bar per row.
+pub fn format_stats_table(rows: &[Value], view: StatsView) -> String {
+    const BARS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+    let (time_key, time_header, time_len) = match view {
+        StatsView::Daily => ("dayStart", "DAY", 10),
+        _ => ("hourStart", "HOUR", 16),
+    };
 
+    let count = |row: &Value, key: &str| row.get(key).and_then(|v| v.as_u64()).unwrap_or(0);
+    let max_tokens = rows
+        .iter()
+        .map(|row| count(row, "estimatedTokens"))
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    let mut out = format!(
+        "{time_header:<time_len$}  {:<12}  {:>8}  {:>7}  {:>10}\n",
+        "SESSION", "COMPACTS", "MINUTES", "TOKENS"
+    );
+    for row in rows {
+        let time = row
+            .get(time_key)
+            .and_then(|v| v.as_i64())
+            .map(|ts| crate::rpc::handlers::core::iso8601_utc(ts)[..time_len].replace('T', " "))
+            .unwrap_or_else(|| "-".to_string());
+        let session = row.get("sessionId").and_then(|v| v.as_str()).unwrap_or("-");
+        let tokens = count(row, "estimatedTokens");
+        let bar = BARS[(tokens * 7 / max_tokens) as usize];
+        out.push_str(&format!(
+            "{time:<time_len$}  {session:<12}  {:>8}  {:>7}  {tokens:>10}  {bar}\n",
+            count(row, "totalCompacts"),
+            count(row, "activeMinutes"),
+        ));
+    }
+    out
+}
+
+/// Execute the 'self-test' command. Returns [`exit_code::FAILURE`] when any
+/// check failed.
+pub fn cmd_self_test(client: &impl RpcClient, format: OutputFormat) -> Result<i32, CliError> {
     let result = client.call("debug.selfTest", json!({}))?;
+    let ok = result.get("ok").and_then(|v| v.as_bool()).unwrap_or(false);
 
     if format == OutputFormat::Text {
-        let ok = result.get("ok").and_then(|v| v.as_bool()).unwrap_or(false);
         let checks = result.get("checks").and_then(|v| v.as_array());
 
         if ok {
@@ -365,23 +855,23 @@ pub fn cmd_self_test(port: u16, format: OutputFormat, admin_token: Option<String
     } else {
         print_output(&result, format);
     }
-    Ok(())
+    Ok(if ok { exit_code::OK } else { exit_code::FAILURE })
 }
 
 /// Execute the 'config' command.
-pub fn cmd_config(port: u16, format: OutputFormat, admin_token: Option<String>) -> Result<(), CliError> {
-    let mut client = DaemonClient::new(port);
-    if let Some(value) = admin_token {
-        client = client.with_admin_token(value);
-    }
-
+pub fn cmd_config(client: &impl RpcClient, format: OutputFormat) -> Result<(), CliError> {
     let result = client.call("config.get", json!({}))?;
     print_output(&result, format);
     Ok(())
 }
 
-/// Stop a running daemon by sending a shutdown signal.
-pub fn cmd_stop(pid_file: Option<PathBuf>) -> Result<(), CliError> {
+/// How often `cmd_stop` checks whether the daemon has exited.
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Stop a running daemon: send SIGTERM, then wait up to `wait` for the process
+/// to exit and release the instance lock. With `force`, a daemon still running
+/// after `wait` is sent SIGKILL.
+pub fn cmd_stop(pid_file: Option<PathBuf>, wait: Duration, force: bool) -> Result<(), CliError> {
     let pid_path = pid_file.unwrap_or_else(|| {
         crate::service::data_dir().join("daemon.pid")
     });
@@ -398,26 +888,249 @@ pub fn cmd_stop(pid_file: Option<PathBuf>) -> Result<(), CliError> {
 
     #[cfg(unix)]
     {
+        use crate::service::{is_instance_locked, wait_for_exit};
+
         // Send SIGTERM
-        let result = unsafe { libc::kill(pid, libc::SIGTERM) };
-        if result == 0 {
-            println!("Sent shutdown signal to daemon (PID {pid})");
-            Ok(())
-        } else {
-            Err(CliError::DaemonNotRunning)
+        send_signal(pid, libc::SIGTERM)?;
+        println!("Sent shutdown signal to daemon (PID {pid})");
+
+        // The daemon drains before exiting; report only once it is gone
+        let lock_path = pid_path.with_file_name("daemon.lock");
+        let running = || process_alive(pid) || is_instance_locked(&lock_path);
+        if wait_for_exit(wait, STOP_POLL_INTERVAL, &running) {
+            println!("Daemon stopped");
+            return Ok(());
+        }
+        if !force {
+            return Err(CliError::StillRunning { pid, waited: wait });
+        }
+
+        send_signal(pid, libc::SIGKILL)?;
+        if !wait_for_exit(wait, STOP_POLL_INTERVAL, &running) {
+            return Err(CliError::StillRunning { pid, waited: wait });
         }
+        // A killed daemon never got to remove its PID file
+        let _ = std::fs::remove_file(&pid_path);
+        println!("Daemon killed (PID {pid})");
+        Ok(())
     }
 
     #[cfg(not(unix))]
     {
+        let _ = (wait, force);
         Err(CliError::Protocol("Stop command not supported on this platform".to_string()))
     }
 }
 
+#[cfg(unix)]
+fn send_signal(pid: i32, signal: libc::c_int) -> Result<(), CliError> {
+    if unsafe { libc::kill(pid, signal) } == 0 {
+        return Ok(());
+    }
+    let err = std::io::Error::last_os_error();
+    Err(match err.raw_os_error() {
+        Some(libc::ESRCH) => CliError::StalePidFile(pid),
+        _ => CliError::Io(err),
+    })
+}
+
+#[cfg(unix)]
+fn process_alive(pid: i32) -> bool {
+    unsafe { libc::kill(pid, 0) == 0 }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn format_polling_state_handles_unset_channels() {
+        let polling = json!({
+            "snapshot": {"interval_ms": 2000, "mode": "active", "reason": "recent_activity", "last_change_at": 1},
+            "tmux": {"interval_ms": 0, "mode": "", "reason": "", "last_change_at": 0},
+        });
+        let out = format_polling_state(&polling);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("snapshot") && lines[0].contains("mode=active"));
+        assert!(lines[0].contains("interval=2000ms") && lines[0].contains("reason=recent_activity"));
+        assert!(lines[1].contains("mode=-") && lines[1].contains("interval=-"));
+        assert!(lines[2].starts_with("ntm") && lines[2].contains("reason=-"));
+    }
+
+    #[test]
+    fn format_health_oneline_ok() {
+        let health = json!({
+            "status": "ok",
+            "uptime": 7980,
+            "uptimeDetail": {"secs": 7980, "since": "2026-01-01T00:00:00Z", "humanized": "2h 13m"},
+            "lastEventId": 42,
+            "degradedReason": null,
+            "safeMode": false,
+        });
+        assert_eq!(format_health_oneline(&health), "ok uptime=2h13m events=42");
+    }
+
+    #[test]
+    fn format_health_oneline_degraded() {
+        let health = json!({
+            "status": "degraded",
+            "uptime": 300,
+            "lastEventId": 7,
+            "degradedReason": "tmux_unavailable",
+            "safeMode": true,
+        });
+        assert_eq!(
+            format_health_oneline(&health),
+            "degraded reason=tmux_unavailable uptime=300s events=7 safe-mode"
+        );
+        assert_eq!(format_health_oneline(&json!({})), "unknown");
+    }
+
+    #[test]
+    fn health_format_parses_names() {
+        assert_eq!("oneline".parse::<HealthFormat>(), Ok(HealthFormat::Oneline));
+        assert_eq!(" JSON ".parse::<HealthFormat>(), Ok(HealthFormat::Json));
+        assert!("yaml".parse::<HealthFormat>().is_err());
+    }
+
+    type Reply = fn() -> Result<Value, CliError>;
+
+    /// Answers every call with a canned result.
+    struct CannedClient(Reply);
+
+    impl RpcClient for CannedClient {
+        fn call(&self, _method: &str, _params: Value) -> Result<Value, CliError> {
+            (self.0)()
+        }
+    }
+
+    fn not_running() -> Result<Value, CliError> {
+        Err(CliError::DaemonNotRunning)
+    }
+
+    fn rpc_error(code: &str) -> CliError {
+        CliError::Rpc { code: code.to_string(), message: String::new() }
+    }
+
+    /// Every client command, run against `client`, mapped to its exit code.
+    fn exit_codes(client: &CannedClient) -> Vec<(&'static str, i32)> {
+        let ok = |result: Result<(), CliError>| exit_code_of(&result.map(|()| exit_code::OK));
+        let json = OutputFormat::Json;
+        vec![
+            ("health", exit_code_of(&cmd_health(client, HealthFormat::Json))),
+            ("status", ok(cmd_status(client, json, false))),
+            ("panes", ok(cmd_panes(client, json, "dev"))),
+            ("events", ok(cmd_events(client, json, Some(5)))),
+            ("stats", ok(cmd_stats(client, json, StatsView::Summary))),
+            ("config", ok(cmd_config(client, json))),
+            ("self-test", exit_code_of(&cmd_self_test(client, json))),
+        ]
+    }
+
+    #[test]
+    fn client_commands_share_error_exit_codes() {
+        let cases: [(Reply, i32); 5] = [
+            (not_running, exit_code::NOT_RUNNING),
+            (|| Err(CliError::Timeout(Duration::from_millis(5))), exit_code::TIMEOUT),
+            (|| Err(rpc_error("UNAUTHORIZED")), exit_code::UNAUTHORIZED),
+            (|| Err(rpc_error("DEGRADED")), exit_code::DEGRADED),
+            (|| Err(CliError::Protocol("bad".into())), exit_code::FAILURE),
+        ];
+        for (result, expected) in cases {
+            for (command, code) in exit_codes(&CannedClient(result)) {
+                assert_eq!(code, expected, "{command}");
+            }
+        }
+
+        let missing = CannedClient(|| Err(rpc_error("NOT_FOUND")));
+        assert_eq!(
+            exit_code_of(&cmd_panes(&missing, OutputFormat::Json, "x").map(|()| 0)),
+            exit_code::NOT_FOUND
+        );
+        let invalid = CannedClient(|| Err(rpc_error("INVALID_PARAMS")));
+        assert_eq!(exit_code_of(&cmd_config(&invalid, OutputFormat::Json).map(|()| 0)), 2);
+    }
+
+    #[test]
+    fn client_commands_exit_ok_on_success() {
+        let client = CannedClient(|| Ok(json!({"status": "ok", "ok": true})));
+        for (command, code) in exit_codes(&client) {
+            assert_eq!(code, exit_code::OK, "{command}");
+        }
+    }
+
+    #[test]
+    fn health_and_self_test_exit_codes_reflect_payload() {
+        let degraded = CannedClient(|| Ok(json!({"status": "degraded"})));
+        let result = cmd_health(&degraded, HealthFormat::Oneline);
+        assert_eq!(exit_code_of(&result), exit_code::DEGRADED);
+
+        let failed = CannedClient(|| Ok(json!({"ok": false, "checks": []})));
+        let result = cmd_self_test(&failed, OutputFormat::Json);
+        assert_eq!(exit_code_of(&result), exit_code::FAILURE);
+    }
+
+    #[test]
+    fn stop_without_pid_file_exits_not_running() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let result = cmd_stop(Some(dir.path().join("daemon.pid")), Duration::ZERO, false);
+        assert_eq!(exit_code_of(&result.map(|()| 0)), exit_code::NOT_RUNNING);
+        assert_eq!(CliError::StalePidFile(1).exit_code(), exit_code::NOT_RUNNING);
+    }
+
+    #[test]
+    fn discovered_port_comes_from_instance_metadata() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let metadata = InstanceMetadata {
+            pid: 1,
+            instance_id: "abc".to_string(),
+            label: String::new(),
+            port: Some(3901),
+            ws_port: Some(3900),
+            uds_path: None,
+        };
+        std::fs::write(
+            dir.path().join(crate::service::INSTANCE_METADATA_FILE),
+            serde_json::to_string(&metadata).unwrap(),
+        )
+        .unwrap();
+
+        let read = crate::service::read_instance_metadata_in(dir.path());
+        assert_eq!(port_from_metadata(read.as_ref()), 3901);
+        let no_http = InstanceMetadata {
+            port: None,
+            ..metadata
+        };
+        assert_eq!(port_from_metadata(Some(&no_http)), DEFAULT_PORT);
+        assert_eq!(port_from_metadata(None), DEFAULT_PORT);
+    }
+
+    #[test]
+    fn explicit_port_wins_over_discovery() {
+        assert_eq!(resolve_port(Some(4100)), 4100);
+    }
+
+    #[test]
+    fn stats_table_scales_bars_to_busiest_row() {
+        let rows = vec![
+            json!({"hourStart": 1_700_000_000 / 3600 * 3600, "sessionId": "s1", "totalCompacts": 2, "activeMinutes": 30, "estimatedTokens": 800}),
+            json!({"hourStart": 1_700_000_000 / 3600 * 3600 + 3600, "sessionId": "s1", "totalCompacts": 0, "activeMinutes": 5, "estimatedTokens": 100}),
+        ];
+        let table = format_stats_table(&rows, StatsView::Hourly);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("HOUR") && lines[0].contains("TOKENS"));
+        assert!(lines[1].starts_with("2023-11-14 22:00") && lines[1].ends_with('█'));
+        assert!(lines[2].starts_with("2023-11-14 23:00") && lines[2].ends_with('▁'));
+
+        let daily = format_stats_table(
+            &[json!({"dayStart": 1_699_920_000, "estimatedTokens": 0})],
+            StatsView::Daily,
+        );
+        assert!(daily.lines().nth(1).unwrap().starts_with("2023-11-14  -"));
+    }
+
     #[test]
     fn output_format_text_handles_simple_values() {
         let value = json!({"key": "value", "number": 42});
@@ -432,6 +1145,166 @@ mod tests {
         print_output(&value, OutputFormat::Json);
     }
 
+    #[test]
+    fn panes_table_has_header_and_rows() {
+        let panes = vec![
+            json!({"paneIndex": 0, "tmuxPaneId": "%3", "status": "active", "agentType": "claude", "currentCommand": "node"}),
+            json!({"paneIndex": 1, "paneId": "uid-2", "status": "idle", "currentCommand": null}),
+        ];
+        let table = format_panes_table(&panes);
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert!(lines[0].starts_with("INDEX"));
+        assert!(lines[1].contains("%3") && lines[1].contains("claude") && lines[1].contains("node"));
+        assert!(lines[2].contains("uid-2") && lines[2].contains("idle"));
+    }
+
+    #[test]
+    fn stalled_response_times_out() {
+        // Accept the connection but never answer
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            std::thread::sleep(Duration::from_millis(1_000));
+            drop(stream);
+        });
+
+        let client = DaemonClient::new(port).with_timeout(Duration::from_millis(150));
+        let started = Instant::now();
+        let err = client.call("health.get", json!({})).unwrap_err();
+        assert!(matches!(err, CliError::Timeout(_)), "unexpected error: {err}");
+        assert!(started.elapsed() < Duration::from_millis(900));
+        assert!(err.to_string().contains("Timed out after 150ms"));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn connect_retries_until_daemon_listens() {
+        // Reserve a free port, then release it so the first attempts are refused
+        let port = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        let server = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(150));
+            let listener = std::net::TcpListener::bind(("127.0.0.1", port)).unwrap();
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 4096];
+            let _ = stream.read(&mut buf);
+            let body = r#"{"jsonrpc":"2.0","id":1,"result":{"status":"ok"}}"#;
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+
+        let client = DaemonClient::new(port)
+            .with_timeout(Duration::from_secs(5))
+            .with_connect_retries(5);
+        let result = client.call("health.get", json!({})).unwrap();
+        assert_eq!(result["status"], "ok");
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn connect_without_retries_reports_daemon_not_running() {
+        let port = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        let client = DaemonClient::new(port).with_connect_retries(0);
+        let err = client.call("health.get", json!({})).unwrap_err();
+        assert!(matches!(err, CliError::DaemonNotRunning), "unexpected error: {err}");
+    }
+
+    #[test]
+    fn client_options_apply_timeout_and_token() {
+        let options = ClientOptions {
+            admin_token: Some("secret".to_string()),
+            timeout: Duration::from_millis(250),
+            connect_retries: 1,
+            ..ClientOptions::new(4000)
+        };
+        let client = options.client();
+        assert_eq!(client.port, 4000);
+        assert_eq!(client.timeout, Duration::from_millis(250));
+        assert_eq!(client.connect_retries, 1);
+        assert_eq!(client.admin_auth_header, Some("Bearer secret".to_string()));
+    }
+
+    #[test]
+    fn stop_without_pid_file_reports_not_running() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let pid_path = dir.path().join("daemon.pid");
+        let err = cmd_stop(Some(pid_path), Duration::from_secs(1), false).unwrap_err();
+        assert!(matches!(err, CliError::DaemonNotRunning), "unexpected error: {err}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stop_reports_stale_pid_file() {
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let pid_path = dir.path().join("daemon.pid");
+        std::fs::write(&pid_path, format!("{pid}\n")).unwrap();
+        let err = cmd_stop(Some(pid_path), Duration::from_secs(1), false).unwrap_err();
+        assert!(
+            matches!(err, CliError::StalePidFile(p) if p == pid as i32),
+            "unexpected error: {err}"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stop_waits_for_the_process_to_exit() {
+        let mut child = std::process::Command::new("sleep")
+            .arg("30")
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        // Reap the child as soon as it exits so it does not linger as a zombie
+        let reaper = std::thread::spawn(move || child.wait().unwrap());
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let pid_path = dir.path().join("daemon.pid");
+        std::fs::write(&pid_path, format!("{pid}\n")).unwrap();
+        cmd_stop(Some(pid_path), Duration::from_secs(5), false).unwrap();
+        assert!(!reaper.join().unwrap().success());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn stop_reports_a_daemon_that_ignores_sigterm_and_force_kills_it() {
+        // A shell that ignores SIGTERM, like a daemon stuck in its drain
+        let mut child = std::process::Command::new("sh")
+            .args(["-c", "trap '' TERM; sleep 30"])
+            .spawn()
+            .unwrap();
+        let pid = child.id();
+        std::thread::sleep(Duration::from_millis(100));
+        let reaper = std::thread::spawn(move || child.wait().unwrap());
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let pid_path = dir.path().join("daemon.pid");
+        std::fs::write(&pid_path, format!("{pid}\n")).unwrap();
+        let wait = Duration::from_millis(200);
+        let err = cmd_stop(Some(pid_path.clone()), wait, false).unwrap_err();
+        assert!(
+            matches!(err, CliError::StillRunning { .. }),
+            "unexpected error: {err}"
+        );
+        assert!(err.to_string().contains("--force"));
+
+        cmd_stop(Some(pid_path.clone()), Duration::from_secs(5), true).unwrap();
+        assert!(!reaper.join().unwrap().success());
+        assert!(!pid_path.exists());
+    }
+
     #[test]
     fn daemon_client_creation() {
         let client = DaemonClient::new(3847);
@@ -0,0 +1,83 @@
+//! Wall-clock abstraction so time-dependent logic can be tested
+//! deterministically.
+//!
+//! Production code uses [`SystemClock`]; tests drive a [`MockClock`] to exact
+//! timestamps.
+
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Source of the current Unix time in seconds.
+pub trait Clock: Send + Sync {
+    fn now_unix(&self) -> i64;
+}
+
+/// Shared handle to a clock.
+pub type SharedClock = Arc<dyn Clock>;
+
+/// The real system clock.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_unix(&self) -> i64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_else(|_| Duration::from_secs(0))
+            .as_secs() as i64
+    }
+}
+
+/// The default clock for production code.
+pub fn system_clock() -> SharedClock {
+    Arc::new(SystemClock)
+}
+
+/// A manually driven clock for tests.
+#[derive(Debug, Default)]
+pub struct MockClock {
+    now: AtomicI64,
+}
+
+impl MockClock {
+    pub fn new(now: i64) -> Self {
+        Self {
+            now: AtomicI64::new(now),
+        }
+    }
+
+    pub fn set(&self, now: i64) {
+        self.now.store(now, Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, secs: i64) {
+        self.now.fetch_add(secs, Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn now_unix(&self) -> i64 {
+        self.now.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mock_clock_set_and_advance() {
+        let clock = MockClock::new(100);
+        assert_eq!(clock.now_unix(), 100);
+        clock.advance(5);
+        assert_eq!(clock.now_unix(), 105);
+        clock.set(42);
+        assert_eq!(clock.now_unix(), 42);
+    }
+
+    #[test]
+    fn system_clock_is_after_2020() {
+        assert!(SystemClock.now_unix() > 1_577_836_800);
+    }
+}
@@ -1,2 +1,251 @@
 pub mod tmux;
 pub mod ntm;
+
+use crate::cache::{DegradedReason, HealthStatus};
+use crate::command::CommandError;
+use crate::ntm::NtmError;
+use crate::rpc::SharedCapabilities;
+use std::fmt;
+
+/// Consecutive non-fatal failures before a collector reports degraded health.
+pub const DEGRADED_FAILURE_THRESHOLD: u32 = 3;
+
+/// Consecutive command-not-found polls before a collector disables itself.
+pub const MISSING_BINARY_THRESHOLD: u32 = 3;
+
+/// External tool a collector shells out to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CollectorKind {
+    Tmux,
+    Ntm,
+}
+
+impl CollectorKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CollectorKind::Tmux => "tmux",
+            CollectorKind::Ntm => "ntm",
+        }
+    }
+}
+
+/// Why a collector poll failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CollectorError {
+    /// The collector's binary is missing or cannot be spawned.
+    CommandNotFound(CollectorKind),
+    /// The command exceeded its timeout.
+    Timeout,
+    /// The command ran but failed (non-zero exit, I/O, oversized output, open circuit).
+    CommandFailed(String),
+    /// The command output could not be parsed.
+    ParseFailed(String),
+}
+
+impl CollectorError {
+    pub fn from_command(kind: CollectorKind, err: CommandError) -> Self {
+        match err {
+            CommandError::Spawn(_) => CollectorError::CommandNotFound(kind),
+            CommandError::Timeout => CollectorError::Timeout,
+            CommandError::ExitNonZero(code) => {
+                CollectorError::CommandFailed(format!("exit code {code}"))
+            }
+            CommandError::OutputTooLarge => {
+                CollectorError::CommandFailed("output too large".to_string())
+            }
+            CommandError::Io(err) => CollectorError::CommandFailed(err.to_string()),
+            CommandError::CircuitOpen => CollectorError::CommandFailed("circuit open".to_string()),
+        }
+    }
+
+    /// Fatal errors will not clear by retrying; transient ones may.
+    pub fn is_fatal(&self) -> bool {
+        matches!(self, CollectorError::CommandNotFound(_))
+    }
+
+    pub fn degraded_reason(&self) -> DegradedReason {
+        match self {
+            CollectorError::CommandNotFound(CollectorKind::Ntm) => DegradedReason::NtmUnavailable,
+            CollectorError::CommandNotFound(CollectorKind::Tmux) => DegradedReason::TmuxUnavailable,
+            CollectorError::Timeout => DegradedReason::CommandTimeout,
+            CollectorError::CommandFailed(_) | CollectorError::ParseFailed(_) => {
+                DegradedReason::PollErrors
+            }
+        }
+    }
+
+    /// Health to report after `failure_count` consecutive failures ending in
+    /// this error. Fatal errors degrade immediately.
+    pub fn health(&self, failure_count: u32) -> HealthStatus {
+        let degraded = self.is_fatal() || failure_count >= DEGRADED_FAILURE_THRESHOLD;
+        HealthStatus {
+            status: if degraded { "degraded" } else { "ok" }.to_string(),
+            last_error: Some(self.to_string()),
+            degraded_reason: degraded.then(|| self.degraded_reason()),
+        }
+    }
+}
+
+/// Watches poll outcomes for a collector whose binary may disappear at runtime.
+///
+/// A single missing-binary error can be a race with a package upgrade, so the
+/// capability is only turned off after [`MISSING_BINARY_THRESHOLD`] in a row.
+#[derive(Debug)]
+pub struct MissingBinaryGuard {
+    kind: CollectorKind,
+    streak: u32,
+}
+
+impl MissingBinaryGuard {
+    pub fn new(kind: CollectorKind) -> Self {
+        Self { kind, streak: 0 }
+    }
+
+    /// Record a poll outcome (`None` on success). Returns `true` when the
+    /// collector should stop; its capability has then been marked unavailable.
+    pub fn observe(&mut self, err: Option<&CollectorError>, capabilities: &SharedCapabilities) -> bool {
+        match err {
+            Some(CollectorError::CommandNotFound(kind)) if *kind == self.kind => {
+                self.streak = self.streak.saturating_add(1);
+            }
+            _ => self.streak = 0,
+        }
+        if self.streak < MISSING_BINARY_THRESHOLD {
+            return false;
+        }
+        capabilities.set_available(self.kind, false);
+        true
+    }
+}
+
+impl From<NtmError> for CollectorError {
+    fn from(err: NtmError) -> Self {
+        match err {
+            NtmError::Unavailable => CollectorError::CommandNotFound(CollectorKind::Ntm),
+            NtmError::CommandFailed(reason) if reason == "timeout" => CollectorError::Timeout,
+            NtmError::CommandFailed(reason) => CollectorError::CommandFailed(reason),
+            NtmError::ParseFailed(reason) => CollectorError::ParseFailed(reason),
+        }
+    }
+}
+
+impl fmt::Display for CollectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CollectorError::CommandNotFound(kind) => write!(f, "{} not found", kind.as_str()),
+            CollectorError::Timeout => write!(f, "command timed out"),
+            CollectorError::CommandFailed(reason) => write!(f, "command failed: {reason}"),
+            CollectorError::ParseFailed(reason) => write!(f, "parse failed: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for CollectorError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_not_found_is_fatal_and_degrades_immediately() {
+        let err = CollectorError::CommandNotFound(CollectorKind::Tmux);
+        assert!(err.is_fatal());
+        let health = err.health(1);
+        assert_eq!(health.status, "degraded");
+        assert_eq!(health.degraded_reason, Some(DegradedReason::TmuxUnavailable));
+        assert_eq!(
+            CollectorError::CommandNotFound(CollectorKind::Ntm).degraded_reason(),
+            DegradedReason::NtmUnavailable
+        );
+    }
+
+    #[test]
+    fn transient_errors_degrade_after_threshold() {
+        let cases = [
+            (CollectorError::Timeout, DegradedReason::CommandTimeout),
+            (CollectorError::CommandFailed("exit code 1".into()), DegradedReason::PollErrors),
+            (CollectorError::ParseFailed("no table".into()), DegradedReason::PollErrors),
+        ];
+        for (err, reason) in cases {
+            assert!(!err.is_fatal(), "{err} should be transient");
+            let health = err.health(DEGRADED_FAILURE_THRESHOLD - 1);
+            assert_eq!(health.status, "ok");
+            assert_eq!(health.degraded_reason, None);
+            assert_eq!(health.last_error, Some(err.to_string()));
+
+            let health = err.health(DEGRADED_FAILURE_THRESHOLD);
+            assert_eq!(health.status, "degraded");
+            assert_eq!(health.degraded_reason, Some(reason));
+        }
+    }
+
+    fn caps() -> SharedCapabilities {
+        SharedCapabilities::new(crate::rpc::Capabilities {
+            ntm: true,
+            tmux: true,
+            stream: false,
+            systemd: false,
+        })
+    }
+
+    #[test]
+    fn command_not_found_streak_disables_collector() {
+        let capabilities = caps();
+        let mut guard = MissingBinaryGuard::new(CollectorKind::Tmux);
+        let err = CollectorError::CommandNotFound(CollectorKind::Tmux);
+        for _ in 1..MISSING_BINARY_THRESHOLD {
+            assert!(!guard.observe(Some(&err), &capabilities));
+            assert!(capabilities.current().tmux);
+        }
+        assert!(guard.observe(Some(&err), &capabilities));
+        assert!(!capabilities.current().tmux);
+        assert!(capabilities.current().ntm);
+    }
+
+    #[test]
+    fn other_outcomes_reset_missing_binary_streak() {
+        let capabilities = caps();
+        let mut guard = MissingBinaryGuard::new(CollectorKind::Ntm);
+        let err = CollectorError::CommandNotFound(CollectorKind::Ntm);
+        for _ in 1..MISSING_BINARY_THRESHOLD {
+            guard.observe(Some(&err), &capabilities);
+        }
+        assert!(!guard.observe(Some(&CollectorError::Timeout), &capabilities));
+        assert!(!guard.observe(Some(&err), &capabilities));
+        assert!(!guard.observe(None, &capabilities));
+        assert!(capabilities.current().ntm);
+    }
+
+    #[test]
+    fn command_errors_map_to_variants() {
+        let spawn = CommandError::Spawn(std::io::Error::from(std::io::ErrorKind::NotFound));
+        assert_eq!(
+            CollectorError::from_command(CollectorKind::Tmux, spawn),
+            CollectorError::CommandNotFound(CollectorKind::Tmux)
+        );
+        assert_eq!(
+            CollectorError::from_command(CollectorKind::Tmux, CommandError::Timeout),
+            CollectorError::Timeout
+        );
+        assert!(matches!(
+            CollectorError::from_command(CollectorKind::Tmux, CommandError::ExitNonZero(1)),
+            CollectorError::CommandFailed(_)
+        ));
+    }
+
+    #[test]
+    fn ntm_errors_map_to_variants() {
+        assert_eq!(
+            CollectorError::from(NtmError::Unavailable),
+            CollectorError::CommandNotFound(CollectorKind::Ntm)
+        );
+        assert_eq!(
+            CollectorError::from(NtmError::CommandFailed("timeout".into())),
+            CollectorError::Timeout
+        );
+        assert_eq!(
+            CollectorError::from(NtmError::ParseFailed("bad".into())),
+            CollectorError::ParseFailed("bad".into())
+        );
+    }
+}
@@ -1,17 +1,29 @@
 use crate::bus::{EventBus, StateChange};
-use crate::cache::{Cache, HealthStatus};
+use crate::clock::{system_clock, SharedClock};
+use crate::cache::{Cache, HealthSource, HealthStatus};
+use crate::collector::CollectorError;
 use crate::metrics::{Timer, METRICS};
 use crate::ntm::{NtmClient, NtmError};
-use crate::reconcile::reconcile_ntm_markdown;
+use crate::parsers::ntm_markdown::{parse_ntm_markdown, NtmMarkdown};
+use crate::reconcile::{reconcile_ntm_markdown_keyed, SessionEndGrace, SessionIdentity};
+use std::collections::hash_map::DefaultHasher;
 use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
 use std::sync::Arc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::Duration;
 
 #[derive(Clone, Debug)]
 pub struct NtmCollectorConfig {
     pub active_interval: Duration,
     pub idle_interval: Duration,
     pub idle_threshold_secs: i64,
+    /// Force a reconcile on every Nth poll even when the output is unchanged
+    /// (1 = every poll).
+    pub reconcile_every_n: u32,
+    /// Seconds a session must be missing from ntm output before it is ended.
+    pub session_end_grace_secs: i64,
+    /// What sessions are matched on across polls.
+    pub session_identity: SessionIdentity,
 }
 
 impl Default for NtmCollectorConfig {
@@ -20,10 +32,53 @@ impl Default for NtmCollectorConfig {
             active_interval: Duration::from_secs(15),
             idle_interval: Duration::from_secs(60),
             idle_threshold_secs: 300,
+            reconcile_every_n: 1,
+            session_end_grace_secs: 10,
+            session_identity: SessionIdentity::Name,
         }
     }
 }
 
+/// Decides which polls run the (relatively expensive) reconcile.
+///
+/// The first poll always reconciles so the cache is populated on startup.
+/// After that a poll reconciles when the ntm output changed, or when
+/// `every_n` polls have passed so `last_seen_at` stays fresh.
+#[derive(Clone, Debug)]
+pub struct ReconcileSchedule {
+    every_n: u32,
+    polls_since_reconcile: Option<u32>,
+}
+
+impl ReconcileSchedule {
+    pub fn new(every_n: u32) -> Self {
+        Self {
+            every_n: every_n.max(1),
+            polls_since_reconcile: None,
+        }
+    }
+
+    /// Record a poll and return whether it should reconcile.
+    pub fn tick(&mut self, changed: bool) -> bool {
+        let due = match self.polls_since_reconcile {
+            None => true,
+            Some(count) => changed || count.saturating_add(1) >= self.every_n,
+        };
+        self.polls_since_reconcile = Some(if due {
+            0
+        } else {
+            self.polls_since_reconcile.unwrap_or(0).saturating_add(1)
+        });
+        due
+    }
+}
+
+pub(super) fn hash_output(raw: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    raw.hash(&mut hasher);
+    hasher.finish()
+}
+
 #[derive(Clone, Debug)]
 pub struct NtmPollResult {
     pub changed: usize,
@@ -34,21 +89,27 @@ pub struct NtmPollResult {
 
 pub struct NtmCollector {
     client: NtmClient,
-    bus: EventBus,
+    bus: Arc<EventBus>,
     cache: Arc<Cache>,
     config: NtmCollectorConfig,
     session_uid_by_name: HashMap<String, String>,
     pane_uid_by_key: HashMap<String, String>,
+    session_end_grace: SessionEndGrace,
     failure_count: u32,
+    schedule: ReconcileSchedule,
+    last_output_hash: Option<u64>,
+    clock: SharedClock,
 }
 
 impl NtmCollector {
     pub fn new(
         client: NtmClient,
-        bus: EventBus,
+        bus: Arc<EventBus>,
         cache: Arc<Cache>,
         config: NtmCollectorConfig,
     ) -> Self {
+        let schedule = ReconcileSchedule::new(config.reconcile_every_n);
+        let session_end_grace = SessionEndGrace::new(config.session_end_grace_secs);
         Self {
             client,
             bus,
@@ -56,46 +117,115 @@ impl NtmCollector {
             config,
             session_uid_by_name: HashMap::new(),
             pane_uid_by_key: HashMap::new(),
+            session_end_grace,
             failure_count: 0,
+            schedule,
+            last_output_hash: None,
+            clock: system_clock(),
         }
     }
 
-    pub async fn poll_once(&mut self) -> Result<NtmPollResult, String> {
+    /// Use `clock` instead of the system clock for timestamps.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    pub async fn poll_once(&mut self) -> Result<NtmPollResult, CollectorError> {
         let _timer = Timer::new(&METRICS.poll_cycle);
-        let now = current_unix_ts();
+        let now = self.clock.now_unix();
         let fallback_interval = self.next_interval(now);
 
-        let markdown = match self.client.robot_markdown().await {
-            Ok(markdown) => markdown,
-            Err(err) => {
-                self.failure_count = self.failure_count.saturating_add(1);
-                let degraded = matches!(err, NtmError::Unavailable) || self.failure_count >= 3;
-                let health = HealthStatus {
-                    status: if degraded { "degraded" } else { "ok" }.to_string(),
-                    last_error: Some(format!("ntm: {err:?}")),
-                };
-                self.cache.set_health(health);
-                return Ok(NtmPollResult {
-                    changed: 0,
-                    ended: 0,
-                    degraded,
-                    next_interval: fallback_interval,
-                });
-            }
+        let raw = match self.client.robot_markdown_raw().await {
+            Ok(raw) => raw,
+            Err(err) => return self.poll_failed(err, fallback_interval),
         };
 
+        match self.apply_output(&raw, now) {
+            Ok(result) => Ok(result),
+            Err(err) => self.poll_failed(err, fallback_interval),
+        }
+    }
+
+    /// Record a failed poll. Fatal errors are returned so the polling loop can
+    /// react; transient ones become a (possibly degraded) result.
+    fn poll_failed(
+        &mut self,
+        err: NtmError,
+        next_interval: Duration,
+    ) -> Result<NtmPollResult, CollectorError> {
+        let err = CollectorError::from(err);
+        let result = self.record_failure(err.clone(), next_interval);
+        if err.is_fatal() {
+            return Err(err);
+        }
+        Ok(result)
+    }
+
+    fn record_failure(
+        &mut self,
+        err: impl Into<CollectorError>,
+        next_interval: Duration,
+    ) -> NtmPollResult {
+        self.failure_count = self.failure_count.saturating_add(1);
+        let err = err.into();
+        let health = err.health(self.failure_count);
+        let degraded = health.degraded_reason.is_some();
+        self.cache.report_health(
+            HealthSource::Ntm,
+            HealthStatus {
+                last_error: health.last_error.map(|message| format!("ntm: {message}")),
+                ..health
+            },
+        );
+        NtmPollResult {
+            changed: 0,
+            ended: 0,
+            degraded,
+            next_interval,
+        }
+    }
+
+    /// Process raw `--robot-markdown` output.
+    ///
+    /// Output identical to the previous poll skips reconcile unless the
+    /// schedule forces one; changed output is parsed and reconciled.
+    pub fn apply_output(&mut self, raw: &str, now: i64) -> Result<NtmPollResult, NtmError> {
+        let hash = hash_output(raw);
+        let changed = self.last_output_hash != Some(hash);
+
+        if !self.schedule.tick(changed) {
+            self.mark_healthy();
+            return Ok(NtmPollResult {
+                changed: 0,
+                ended: 0,
+                degraded: false,
+                next_interval: self.next_interval(now),
+            });
+        }
+
+        let markdown =
+            parse_ntm_markdown(raw).map_err(|err| NtmError::ParseFailed(err.reason))?;
+        self.last_output_hash = Some(hash);
+        self.mark_healthy();
+        Ok(self.apply_markdown(&markdown, now))
+    }
+
+    fn mark_healthy(&mut self) {
         self.failure_count = 0;
-        self.cache.set_health(HealthStatus {
-            status: "ok".to_string(),
-            last_error: None,
-        });
+        self.cache.clear_health(HealthSource::Ntm);
+    }
 
-        let reconcile = reconcile_ntm_markdown(
+    /// Reconcile a parsed markdown snapshot into the cache.
+    pub fn apply_markdown(&mut self, markdown: &NtmMarkdown, now: i64) -> NtmPollResult {
+        let reconcile = reconcile_ntm_markdown_keyed(
             &self.cache,
-            &markdown,
+            markdown,
             now,
             &mut self.session_uid_by_name,
             &mut self.pane_uid_by_key,
+            &mut self.session_end_grace,
+            &self.config.session_identity,
         );
 
         for session in reconcile.sessions.iter().cloned() {
@@ -105,6 +235,15 @@ impl NtmCollector {
             self.cache.upsert_pane(pane);
         }
 
+        METRICS.reconcile.record(reconcile.counts());
+        tracing::debug!(
+            sessions_created = reconcile.sessions_created,
+            sessions_updated = reconcile.sessions_updated,
+            sessions_ended = reconcile.ended_sessions,
+            panes_created = reconcile.panes_created,
+            panes_updated = reconcile.panes_updated,
+            "ntm reconcile"
+        );
         let changed = reconcile.change_count();
         if changed > 0 {
             let change = StateChange {
@@ -115,12 +254,12 @@ impl NtmCollector {
             let _ = self.bus.publish_state(change);
         }
 
-        Ok(NtmPollResult {
+        NtmPollResult {
             changed,
             ended: reconcile.ended_sessions,
             degraded: false,
             next_interval: self.next_interval(now),
-        })
+        }
     }
 
     fn next_interval(&self, now: i64) -> Duration {
@@ -140,9 +279,142 @@ impl NtmCollector {
     }
 }
 
-fn current_unix_ts() -> i64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_else(|_| Duration::from_secs(0))
-        .as_secs() as i64
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::DegradedReason;
+    use crate::command::{CommandConfig, CommandRunner};
+    use crate::ntm::NtmConfig;
+
+    fn make_collector(reconcile_every_n: u32) -> NtmCollector {
+        let client = NtmClient::new(CommandRunner::new(CommandConfig::default()), NtmConfig::default());
+        let config = NtmCollectorConfig {
+            reconcile_every_n,
+            ..NtmCollectorConfig::default()
+        };
+        NtmCollector::new(client, Arc::new(EventBus::new(4)), Arc::new(Cache::new(100)), config)
+    }
+
+    const OUTPUT: &str = "| session | pane | status |\n| --- | --- | --- |\n| alpha | 0 | active |";
+    const CHANGED_OUTPUT: &str =
+        "| session | pane | status |\n| --- | --- | --- |\n| alpha | 0 | active |\n| alpha | 1 | idle |";
+
+    fn reconciled(collector: &mut NtmCollector, raw: &str, now: i64) -> bool {
+        collector.apply_output(raw, now).expect("apply output").changed > 0
+    }
+
+    #[test]
+    fn schedule_every_poll_by_default() {
+        let mut schedule = ReconcileSchedule::new(1);
+        assert!((0..5).all(|_| schedule.tick(false)));
+    }
+
+    #[test]
+    fn schedule_every_third_poll() {
+        let mut schedule = ReconcileSchedule::new(3);
+        let ticks: Vec<bool> = (0..7).map(|_| schedule.tick(false)).collect();
+        assert_eq!(ticks, vec![true, false, false, true, false, false, true]);
+    }
+
+    #[test]
+    fn schedule_change_forces_reconcile() {
+        let mut schedule = ReconcileSchedule::new(5);
+        assert!(schedule.tick(false));
+        assert!(!schedule.tick(false));
+        assert!(schedule.tick(true));
+        assert!(!schedule.tick(false));
+    }
+
+    #[test]
+    fn schedule_clamps_zero_to_one() {
+        let mut schedule = ReconcileSchedule::new(0);
+        assert!(schedule.tick(false));
+        assert!(schedule.tick(false));
+    }
+
+    #[test]
+    fn collector_reconciles_on_every_third_poll() {
+        let mut collector = make_collector(3);
+        let ticks: Vec<bool> = (0..6)
+            .map(|i| reconciled(&mut collector, OUTPUT, 1_700_000_000 + i))
+            .collect();
+        assert_eq!(ticks, vec![true, false, false, true, false, false]);
+        assert_eq!(collector.cache.session_count(), 1);
+    }
+
+    #[test]
+    fn identical_output_skips_reconcile() {
+        let mut collector = make_collector(10);
+        let now = 1_700_000_000;
+        assert!(reconciled(&mut collector, OUTPUT, now));
+        assert!(!reconciled(&mut collector, OUTPUT, now + 1));
+        assert!(!reconciled(&mut collector, OUTPUT, now + 2));
+
+        let session = collector.cache.all_sessions().remove(0);
+        assert_eq!(session.last_seen_at, now, "skipped polls do not touch the cache");
+    }
+
+    #[test]
+    fn default_config_reconciles_every_poll() {
+        let every_n = NtmCollectorConfig::default().reconcile_every_n;
+        assert_eq!(every_n, 1);
+        let mut collector = make_collector(every_n);
+        for i in 0..3 {
+            assert!(reconciled(&mut collector, OUTPUT, 1_700_000_000 + i));
+        }
+    }
+
+    #[test]
+    fn changed_output_triggers_reconcile() {
+        let mut collector = make_collector(10);
+        let now = 1_700_000_000;
+        assert!(reconciled(&mut collector, OUTPUT, now));
+        assert!(!reconciled(&mut collector, OUTPUT, now + 1));
+        assert!(reconciled(&mut collector, CHANGED_OUTPUT, now + 2));
+        assert_eq!(collector.cache.pane_count(), 2);
+    }
+
+    #[test]
+    fn unparseable_output_is_an_error() {
+        let mut collector = make_collector(1);
+        assert!(collector.apply_output("no table here", 1).is_err());
+    }
+
+    #[test]
+    fn repeated_poll_errors_degrade_with_poll_errors_reason() {
+        let mut collector = make_collector(1);
+        let interval = Duration::from_secs(1);
+        for _ in 0..2 {
+            collector.record_failure(NtmError::CommandFailed("exit code".into()), interval);
+            assert_eq!(collector.cache.health().status, "ok");
+            assert_eq!(collector.cache.health().degraded_reason, None);
+        }
+        let result =
+            collector.record_failure(NtmError::CommandFailed("exit code".into()), interval);
+        assert!(result.degraded);
+        let health = collector.cache.health();
+        assert_eq!(health.status, "degraded");
+        assert_eq!(health.degraded_reason, Some(DegradedReason::PollErrors));
+
+        collector.mark_healthy();
+        assert_eq!(collector.cache.health().degraded_reason, None);
+    }
+
+    #[test]
+    fn degraded_reason_reflects_error_kind() {
+        let mut collector = make_collector(1);
+        let interval = Duration::from_secs(1);
+        collector.record_failure(NtmError::Unavailable, interval);
+        assert_eq!(
+            collector.cache.health().degraded_reason,
+            Some(DegradedReason::NtmUnavailable)
+        );
+        for _ in 0..3 {
+            collector.record_failure(NtmError::CommandFailed("timeout".into()), interval);
+        }
+        assert_eq!(
+            collector.cache.health().degraded_reason,
+            Some(DegradedReason::CommandTimeout)
+        );
+    }
 }
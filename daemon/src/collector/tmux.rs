@@ -1,27 +1,57 @@
-use crate::bus::{EventBus, StateChange};
-use crate::cache::Cache;
+use crate::bus::{ClientUpdate, EventBus, StateChange, PANE_OUTPUT_UPDATE};
+use crate::clock::{system_clock, SharedClock};
+use crate::cache::{Cache, HealthSource, HealthStatus};
+use crate::collector::ntm::hash_output;
+use crate::collector::{CollectorError, CollectorKind, DEGRADED_FAILURE_THRESHOLD};
 use crate::command::{CommandCategory, CommandRunner, CommandSpec};
 use crate::metrics::{Timer, METRICS};
-use crate::models::pane::{Pane, PaneStatus};
-use crate::models::session::{Session, SessionStatus};
-use crate::parsers::tmux_panes::{parse_tmux_panes, TmuxPaneMeta};
-use std::collections::HashMap;
+use crate::models::pane::{Pane, PaneContext};
+use crate::models::session::Session;
+use crate::ntm::NtmClient;
+use crate::parsers::ntm_markdown::{parse_ntm_markdown, NtmMarkdown};
+use crate::parsers::tmux_panes::{parse_tmux_panes_with, TmuxFormat, TmuxPaneMeta, DEFAULT_FORMAT};
+use crate::reconcile::{reconcile_all, ReconcileLookups, SessionEndGrace, SessionIdentity};
+use crate::redaction::{default_redactor, Redactor};
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::Duration;
 
 #[derive(Clone, Debug)]
 pub struct TmuxCollectorConfig {
     pub poll_interval: Duration,
     pub format: String,
     pub max_output_bytes: usize,
+    /// Capture live panes after each poll so output changes advance
+    /// `last_activity_at` (`capture.capture-output`).
+    pub capture_output: bool,
+    /// Also record each pane's working directory (`capture.capture-cwd`).
+    pub capture_cwd: bool,
+    /// Session environment variables copied onto panes
+    /// (`capture.env-allowlist`).
+    pub env_allowlist: Vec<String>,
+}
+
+/// Lines of scrollback hashed per pane when capturing for activity.
+const ACTIVITY_CAPTURE_LINES: u32 = 50;
+
+/// A context lookup issued after a poll; see [`TmuxCollector::context_commands`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum ContextQuery {
+    /// `list-panes` output of `<pane_id> <pane_current_path>` lines.
+    Paths,
+    /// `show-environment` output for one tmux session id.
+    Environment(String),
 }
 
 impl Default for TmuxCollectorConfig {
     fn default() -> Self {
         Self {
             poll_interval: Duration::from_millis(1500),
-            format: "#{session_id}:#{session_name}:#{window_id}:#{pane_id}:#{pane_index}:#{pane_pid}:#{pane_current_command}:#{pane_last_activity}:#{pane_dead}:#{pane_in_mode}".to_string(),
+            format: DEFAULT_FORMAT.to_string(),
             max_output_bytes: 256 * 1024,
+            capture_output: false,
+            capture_cwd: false,
+            env_allowlist: Vec::new(),
         }
     }
 }
@@ -35,35 +65,68 @@ pub struct TmuxPollResult {
 
 pub struct TmuxCollector {
     runner: CommandRunner,
-    bus: EventBus,
+    bus: Arc<EventBus>,
     cache: Arc<Cache>,
     config: TmuxCollectorConfig,
+    layout: TmuxFormat,
     last_state: HashMap<String, TmuxPaneMeta>,
-    pane_uid_by_tmux: HashMap<String, String>,
-    session_uid_by_tmux: HashMap<String, String>,
+    lookups: ReconcileLookups,
+    /// ntm, when it runs alongside tmux; see [`TmuxCollector::with_ntm`].
+    ntm: Option<NtmClient>,
+    last_ntm_hash: Option<u64>,
     failure_count: u32,
+    clock: SharedClock,
 }
 
 impl TmuxCollector {
     pub fn new(
         runner: CommandRunner,
-        bus: EventBus,
+        bus: Arc<EventBus>,
         cache: Arc<Cache>,
         config: TmuxCollectorConfig,
     ) -> Self {
+        // Config validation rejects bad formats; fall back rather than poll
+        // with output we cannot parse.
+        let layout = TmuxFormat::parse(&config.format).unwrap_or_else(|err| {
+            tracing::warn!(error = %err, format = %config.format, "invalid tmux format; using default");
+            TmuxFormat::default()
+        });
         Self {
             runner,
             bus,
             cache,
             config,
+            layout,
             last_state: HashMap::new(),
-            pane_uid_by_tmux: HashMap::new(),
-            session_uid_by_tmux: HashMap::new(),
+            lookups: ReconcileLookups::default(),
+            ntm: None,
+            last_ntm_hash: None,
             failure_count: 0,
+            clock: system_clock(),
         }
     }
 
-    pub async fn poll_once(&mut self) -> Result<TmuxPollResult, String> {
+    /// Use `clock` instead of the system clock for timestamps.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Also read ntm on each poll and merge it into the tmux records (see
+    /// [`reconcile_all`]), so one collector owns both sources' sessions.
+    pub fn with_ntm(
+        mut self,
+        client: NtmClient,
+        identity: SessionIdentity,
+        session_end_grace_secs: i64,
+    ) -> Self {
+        self.ntm = Some(client);
+        self.lookups.identity = identity;
+        self.lookups.grace = SessionEndGrace::new(session_end_grace_secs);
+        self
+    }
+
+    pub async fn poll_once(&mut self) -> Result<TmuxPollResult, CollectorError> {
         let _timer = Timer::new(&METRICS.poll_cycle);
         let spec = CommandSpec {
             program: "tmux".to_string(),
@@ -71,7 +134,7 @@ impl TmuxCollector {
                 "list-panes".to_string(),
                 "-a".to_string(),
                 "-F".to_string(),
-                self.config.format.clone(),
+                self.layout.to_format_string(),
             ],
             timeout: Duration::from_secs(0),
             max_output_bytes: self.config.max_output_bytes,
@@ -81,34 +144,36 @@ impl TmuxCollector {
         let output = match self.runner.run(spec).await {
             Ok(output) => output,
             Err(err) => {
-                self.failure_count = self.failure_count.saturating_add(1);
-                let degraded = self.failure_count >= 3;
-                if degraded {
-                    return Ok(TmuxPollResult {
-                        changed: 0,
-                        removed: 0,
-                        degraded: true,
-                    });
-                }
-                return Err(format!("tmux poll error: {err:?}"));
+                return self.record_failure(CollectorError::from_command(CollectorKind::Tmux, err));
             }
         };
 
-        self.failure_count = 0;
+        if self.failure_count > 0 {
+            self.failure_count = 0;
+            self.cache.clear_health(HealthSource::Tmux);
+        }
         let text = String::from_utf8_lossy(&output.stdout);
-        let metas = parse_tmux_panes(&text).map_err(|err| err.reason)?;
+        let metas = parse_tmux_panes_with(&text, &self.layout)
+            .map_err(|err| CollectorError::ParseFailed(err.reason))?;
+        self.cache.set_tmux_panes(metas.clone());
         let (changed, removed) = self.diff_state(&metas);
+        let (markdown, ntm_changed) = self.poll_ntm().await;
 
-        if changed > 0 || removed > 0 {
-            let (sessions, panes) = self.update_cache(&metas);
+        if changed > 0 || removed > 0 || ntm_changed {
+            let (sessions, panes) = self.update_cache_with(&metas, markdown.as_ref());
             let change = StateChange {
                 sessions,
                 panes,
-                observed_at: current_unix_ts(),
+                observed_at: self.clock.now_unix(),
             };
             let _ = self.bus.publish_state(change);
         }
 
+        if self.config.capture_output {
+            self.capture_activity().await;
+            self.capture_context().await;
+        }
+
         Ok(TmuxPollResult {
             changed,
             removed,
@@ -116,6 +181,55 @@ impl TmuxCollector {
         })
     }
 
+    /// Read and parse ntm's output when it is merged in. `None` when ntm is
+    /// not configured or failed this poll, which ends none of its sessions;
+    /// the flag says whether the output differs from the last one read.
+    async fn poll_ntm(&mut self) -> (Option<NtmMarkdown>, bool) {
+        let Some(client) = &self.ntm else {
+            return (None, false);
+        };
+        let raw = match client.robot_markdown_raw().await {
+            Ok(raw) => raw,
+            Err(err) => {
+                tracing::warn!(error = ?err, "ntm poll failed; merging tmux only");
+                return (None, false);
+            }
+        };
+        let markdown = match parse_ntm_markdown(&raw) {
+            Ok(markdown) => markdown,
+            Err(err) => {
+                tracing::warn!(error = %err.reason, "unparseable ntm output; merging tmux only");
+                return (None, false);
+            }
+        };
+        let hash = hash_output(&raw);
+        let changed = self.last_ntm_hash != Some(hash);
+        self.last_ntm_hash = Some(hash);
+        (Some(markdown), changed)
+    }
+
+    /// Count a failed poll and report health. Transient failures are returned
+    /// as errors until they reach the degraded threshold; fatal ones always are.
+    fn record_failure(&mut self, err: CollectorError) -> Result<TmuxPollResult, CollectorError> {
+        self.failure_count = self.failure_count.saturating_add(1);
+        let health = err.health(self.failure_count);
+        self.cache.report_health(
+            HealthSource::Tmux,
+            HealthStatus {
+                last_error: health.last_error.map(|message| format!("tmux: {message}")),
+                ..health
+            },
+        );
+        if err.is_fatal() || self.failure_count < DEGRADED_FAILURE_THRESHOLD {
+            return Err(err);
+        }
+        Ok(TmuxPollResult {
+            changed: 0,
+            removed: 0,
+            degraded: true,
+        })
+    }
+
     fn diff_state(&mut self, metas: &[TmuxPaneMeta]) -> (usize, usize) {
         let mut changed = 0;
         let mut next_state = HashMap::new();
@@ -142,90 +256,248 @@ impl TmuxCollector {
         (changed, removed)
     }
 
-    fn update_cache(&mut self, metas: &[TmuxPaneMeta]) -> (Vec<Session>, Vec<Pane>) {
-        let mut sessions = Vec::new();
-        let mut panes = Vec::new();
+    /// Capture each live pane and let output changes advance its activity.
+    async fn capture_activity(&mut self) {
+        let pane_ids: Vec<String> = self
+            .last_state
+            .values()
+            .filter(|meta| !meta.pane_dead)
+            .map(|meta| meta.pane_id.clone())
+            .collect();
+        for pane_id in pane_ids {
+            let spec = CommandSpec {
+                program: "tmux".to_string(),
+                args: vec![
+                    "capture-pane".to_string(),
+                    "-p".to_string(),
+                    "-t".to_string(),
+                    pane_id.clone(),
+                    "-S".to_string(),
+                    format!("-{ACTIVITY_CAPTURE_LINES}"),
+                ],
+                timeout: Duration::from_secs(0),
+                max_output_bytes: self.config.max_output_bytes,
+                category: CommandCategory::TmuxFast,
+            };
+            match self.runner.run(spec).await {
+                Ok(output) => {
+                    let now = self.clock.now_unix();
+                    self.record_capture(&pane_id, &output.stdout, now);
+                    self.publish_capture(&pane_id, &output.stdout);
+                }
+                Err(err) => {
+                    tracing::debug!(pane_id = %pane_id, error = ?err, "activity capture failed");
+                }
+            }
+        }
+    }
 
-        for meta in metas {
-            let session_uid = self
-                .session_uid_by_tmux
-                .entry(meta.session_id.clone())
-                .or_insert_with(|| uuid::Uuid::now_v7().to_string())
-                .clone();
+    /// Look up working directories and environment for live panes.
+    async fn capture_context(&mut self) {
+        let mut results = Vec::new();
+        for (query, spec) in self.context_commands() {
+            match self.runner.run(spec).await {
+                Ok(output) => {
+                    results.push((query, String::from_utf8_lossy(&output.stdout).into_owned()));
+                }
+                Err(err) => {
+                    tracing::debug!(query = ?query, error = ?err, "context capture failed");
+                }
+            }
+        }
+        if !results.is_empty() {
+            self.apply_context(results);
+        }
+    }
 
-            let pane_uid = self
-                .pane_uid_by_tmux
-                .entry(meta.pane_id.clone())
-                .or_insert_with(|| uuid::Uuid::now_v7().to_string())
-                .clone();
-
-            let now = current_unix_ts();
-            let activity_ts = if meta.pane_last_activity > 0 {
-                meta.pane_last_activity
-            } else {
-                now
-            };
+    /// tmux commands gathering pane context; none unless capture is on and
+    /// `capture_cwd` or `env_allowlist` asks for something.
+    fn context_commands(&self) -> Vec<(ContextQuery, CommandSpec)> {
+        let spec = |args: Vec<String>| CommandSpec {
+            program: "tmux".to_string(),
+            args,
+            timeout: Duration::from_secs(0),
+            max_output_bytes: self.config.max_output_bytes,
+            category: CommandCategory::TmuxFast,
+        };
+        let mut commands = Vec::new();
+        if !self.config.capture_output {
+            return commands;
+        }
+        if self.config.capture_cwd {
+            let args = ["list-panes", "-a", "-F", "#{pane_id} #{pane_current_path}"];
+            commands.push((ContextQuery::Paths, spec(args.map(String::from).to_vec())));
+        }
+        if !self.config.env_allowlist.is_empty() {
+            let mut session_ids: Vec<&String> = self
+                .last_state
+                .values()
+                .filter(|meta| !meta.pane_dead)
+                .map(|meta| &meta.session_id)
+                .collect();
+            session_ids.sort();
+            session_ids.dedup();
+            for session_id in session_ids {
+                let args = ["show-environment", "-t", session_id.as_str()];
+                let args = args.map(String::from).to_vec();
+                commands.push((ContextQuery::Environment(session_id.clone()), spec(args)));
+            }
+        }
+        commands
+    }
 
-            let session = Session {
-                session_uid: session_uid.clone(),
-                source_id: "tmux".to_string(),
-                tmux_session_id: Some(meta.session_id.clone()),
-                name: meta.session_name.clone(),
-                created_at: activity_ts,
-                last_seen_at: now,
-                ended_at: None,
-                status: SessionStatus::Active,
-                status_reason: Some("tmux_poll".to_string()),
-                pane_count: 0,
-                metadata: None,
-            };
+    /// Store context command output on the cached panes. Parts whose
+    /// command failed keep their previous value. Returns how many panes changed.
+    fn apply_context(&self, results: Vec<(ContextQuery, String)>) -> usize {
+        let mut cwd_by_pane = None;
+        let mut env_by_session = HashMap::new();
+        for (query, output) in results {
+            match query {
+                ContextQuery::Paths => cwd_by_pane = Some(parse_pane_paths(&output)),
+                ContextQuery::Environment(session_id) => {
+                    let allowlist = &self.config.env_allowlist;
+                    let env = parse_environment(&output, allowlist, default_redactor());
+                    env_by_session.insert(session_id, env);
+                }
+            }
+        }
 
-            let pane = Pane {
-                pane_uid: pane_uid.clone(),
-                session_uid: session_uid.clone(),
-                tmux_pane_id: Some(meta.pane_id.clone()),
-                tmux_window_id: Some(meta.window_id.clone()),
-                tmux_pane_pid: Some(meta.pane_pid),
-                pane_index: meta.pane_index,
-                agent_type: None,
-                created_at: activity_ts,
-                last_seen_at: now,
-                last_activity_at: Some(activity_ts),
-                current_command: Some(meta.pane_current_command.clone()),
-                ended_at: if meta.pane_dead { Some(now) } else { None },
-                status: if meta.pane_dead {
-                    PaneStatus::Ended
-                } else {
-                    PaneStatus::Active
+        let mut changed = 0;
+        for meta in self.last_state.values().filter(|meta| !meta.pane_dead) {
+            let Some(mut pane) = self
+                .lookups
+                .pane_uid_by_tmux
+                .get(&meta.pane_id)
+                .and_then(|pane_uid| self.cache.get_pane(pane_uid))
+            else {
+                continue;
+            };
+            let previous = pane.context.clone().unwrap_or_default();
+            let context = PaneContext {
+                cwd: match &cwd_by_pane {
+                    Some(paths) => paths.get(&meta.pane_id).cloned(),
+                    None => previous.cwd,
                 },
-                status_reason: Some("tmux_poll".to_string()),
+                env: env_by_session
+                    .get(&meta.session_id)
+                    .cloned()
+                    .unwrap_or(previous.env),
             };
+            let context = (!context.is_empty()).then_some(context);
+            if pane.context != context {
+                pane.context = context;
+                self.cache.upsert_pane(pane);
+                changed += 1;
+            }
+        }
+        changed
+    }
 
+    /// Record captured output for a tmux pane id; returns whether it changed.
+    fn record_capture(&self, tmux_pane_id: &str, output: &[u8], now: i64) -> bool {
+        self.lookups
+            .pane_uid_by_tmux
+            .get(tmux_pane_id)
+            .is_some_and(|pane_uid| self.cache.record_pane_output(pane_uid, output, now))
+    }
+
+    /// Hand a capture to connections tailing the pane (`panes.tail`).
+    fn publish_capture(&self, tmux_pane_id: &str, output: &[u8]) {
+        let Some(pane_uid) = self.lookups.pane_uid_by_tmux.get(tmux_pane_id) else {
+            return;
+        };
+        // No subscribers just means no client is connected
+        let _ = self.bus.publish_client_update(ClientUpdate {
+            kind: PANE_OUTPUT_UPDATE.to_string(),
+            payload: Some(serde_json::json!({
+                "paneId": pane_uid,
+                "output": String::from_utf8_lossy(output),
+            })),
+        });
+    }
+
+    /// Reconcile the listed panes alone into the cache.
+    #[cfg(test)]
+    fn update_cache(&mut self, metas: &[TmuxPaneMeta]) -> (Vec<Session>, Vec<Pane>) {
+        self.update_cache_with(metas, None)
+    }
+
+    /// Reconcile the listed panes and, when polled, ntm's sessions into the
+    /// cache (see [`reconcile_all`]).
+    fn update_cache_with(
+        &mut self,
+        metas: &[TmuxPaneMeta],
+        markdown: Option<&NtmMarkdown>,
+    ) -> (Vec<Session>, Vec<Pane>) {
+        let reconcile = reconcile_all(
+            &self.cache,
+            markdown,
+            Some(metas),
+            self.clock.now_unix(),
+            &mut self.lookups,
+        );
+        for session in &reconcile.sessions {
             self.cache.upsert_session(session.clone());
+        }
+        for pane in &reconcile.panes {
             self.cache.upsert_pane(pane.clone());
-            sessions.push(session);
-            panes.push(pane);
         }
+        tracing::debug!(
+            sessions_created = reconcile.sessions_created,
+            sessions_updated = reconcile.sessions_updated,
+            sessions_ended = reconcile.ended_sessions,
+            panes_created = reconcile.panes_created,
+            panes_updated = reconcile.panes_updated,
+            "tmux reconcile"
+        );
 
-        (sessions, panes)
+        (reconcile.sessions, reconcile.panes)
     }
 }
 
-fn current_unix_ts() -> i64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_else(|_| Duration::from_secs(0))
-        .as_secs() as i64
+/// Parse `<pane_id> <path>` lines; paths may contain spaces.
+fn parse_pane_paths(output: &str) -> HashMap<String, String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once(' '))
+        .filter(|(_, path)| !path.is_empty())
+        .map(|(pane_id, path)| (pane_id.to_string(), path.to_string()))
+        .collect()
+}
+
+/// Pick allowlisted `NAME=value` lines out of `show-environment` output.
+/// Removed variables (`-NAME`) are skipped. Values go through `redactor`,
+/// and one whose name matched a pattern (`TOKEN=...`) is replaced outright.
+fn parse_environment(
+    output: &str,
+    allowlist: &[String],
+    redactor: &Redactor,
+) -> BTreeMap<String, String> {
+    output
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .filter(|(name, _)| allowlist.iter().any(|allowed| allowed == name))
+        .map(|(name, value)| {
+            let pair = redactor.redact(&format!("{name}={value}"));
+            let value = match pair.strip_prefix(name).and_then(|rest| rest.strip_prefix('=')) {
+                Some(redacted) => redacted.to_string(),
+                None => redactor.replacement().to_string(),
+            };
+            (name.to_string(), value)
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::pane::PaneStatus;
+    use crate::models::source::Source;
 
     #[test]
     fn diff_detects_changes_and_removals() {
         let runner = CommandRunner::new(crate::command::CommandConfig::default());
-        let bus = EventBus::new(4);
+        let bus = Arc::new(EventBus::new(4));
         let cache = std::sync::Arc::new(Cache::new(100));
         let mut collector = TmuxCollector::new(runner, bus, cache, TmuxCollectorConfig::default());
 
@@ -233,6 +505,7 @@ mod tests {
             session_id: "$1".to_string(),
             session_name: "test_session".to_string(),
             window_id: "@1".to_string(),
+            window_index: 0,
             pane_id: "%1".to_string(),
             pane_index: 0,
             pane_pid: 42,
@@ -255,14 +528,14 @@ mod tests {
 
     fn make_collector() -> TmuxCollector {
         let runner = CommandRunner::new(crate::command::CommandConfig::default());
-        let bus = EventBus::new(4);
+        let bus = Arc::new(EventBus::new(4));
         let cache = Arc::new(Cache::new(100));
         TmuxCollector::new(runner, bus, cache, TmuxCollectorConfig::default())
     }
 
     fn make_collector_with_cache(cache: Arc<Cache>) -> TmuxCollector {
         let runner = CommandRunner::new(crate::command::CommandConfig::default());
-        let bus = EventBus::new(4);
+        let bus = Arc::new(EventBus::new(4));
         TmuxCollector::new(runner, bus, cache, TmuxCollectorConfig::default())
     }
 
@@ -271,6 +544,7 @@ mod tests {
             session_id: session_id.to_string(),
             session_name: format!("sess-{session_id}"),
             window_id: "@1".to_string(),
+            window_index: 0,
             pane_id: pane_id.to_string(),
             pane_index: 0,
             pane_pid: 100,
@@ -281,6 +555,141 @@ mod tests {
         }
     }
 
+    #[test]
+    fn ntm_sessions_merge_into_tmux_records() {
+        let cache = Arc::new(Cache::new(100));
+        let runner = CommandRunner::new(crate::command::CommandConfig::default());
+        let ntm = NtmClient::new(
+            CommandRunner::new(crate::command::CommandConfig::default()),
+            crate::ntm::NtmConfig::default(),
+        );
+        let mut c = TmuxCollector::new(
+            runner,
+            Arc::new(EventBus::new(4)),
+            cache.clone(),
+            TmuxCollectorConfig::default(),
+        )
+        .with_ntm(ntm, SessionIdentity::default(), 0);
+        let markdown = parse_ntm_markdown(
+            "| session | pane | status | agent |\n| --- | --- | --- | --- |\n| sess-$1 | 0 | active | claude |\n| beta | 0 | idle | codex |\n",
+        )
+        .unwrap();
+
+        let (sessions, panes) = c.update_cache_with(&[meta("$1", "%1")], Some(&markdown));
+
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(cache.all_sessions().len(), 2, "one record per session across sources");
+        let merged = sessions.iter().find(|s| s.name == "sess-$1").unwrap();
+        assert_eq!(merged.tmux_session_id.as_deref(), Some("$1"));
+        let pane = pane_by_tmux_id(&cache, "%1");
+        assert_eq!(pane.session_uid, merged.session_uid);
+        assert_eq!(pane.agent_type.as_deref(), Some("claude"));
+        assert_eq!(panes.len(), 2);
+
+        // A poll without ntm output keeps its sessions
+        let (sessions, _) = c.update_cache_with(&[meta("$1", "%1")], None);
+        assert!(sessions.iter().all(|s| s.ended_at.is_none()));
+    }
+
+    fn context_collector(cache: Arc<Cache>, capture_output: bool) -> TmuxCollector {
+        let runner = CommandRunner::new(crate::command::CommandConfig::default());
+        let config = TmuxCollectorConfig {
+            capture_output,
+            capture_cwd: true,
+            env_allowlist: vec!["VIRTUAL_ENV".to_string(), "API_TOKEN".to_string()],
+            ..TmuxCollectorConfig::default()
+        };
+        let mut collector = TmuxCollector::new(runner, Arc::new(EventBus::new(4)), cache, config);
+        let metas = vec![meta("$1", "%1"), meta("$1", "%2")];
+        collector.diff_state(&metas);
+        collector.update_cache(&metas);
+        collector
+    }
+
+    fn pane_by_tmux_id(cache: &Cache, tmux_pane_id: &str) -> Pane {
+        cache
+            .all_panes()
+            .into_iter()
+            .find(|pane| pane.tmux_pane_id.as_deref() == Some(tmux_pane_id))
+            .unwrap()
+    }
+
+    #[test]
+    fn context_capture_populates_cwd_and_env_when_enabled() {
+        let cache = Arc::new(Cache::new(100));
+        let mut c = context_collector(cache.clone(), true);
+
+        let commands = c.context_commands();
+        let queries: Vec<&ContextQuery> = commands.iter().map(|(query, _)| query).collect();
+        assert_eq!(queries, [&ContextQuery::Paths, &ContextQuery::Environment("$1".into())]);
+        assert_eq!(commands[0].1.args[3], "#{pane_id} #{pane_current_path}");
+        assert_eq!(commands[1].1.args, ["show-environment", "-t", "$1"]);
+
+        // What the runner would have returned for those commands
+        let changed = c.apply_context(vec![
+            (ContextQuery::Paths, "%1 /work/my repo\n%2 /tmp\n%7 /elsewhere\n".into()),
+            (
+                ContextQuery::Environment("$1".into()),
+                "VIRTUAL_ENV=/work/.venv\nAPI_TOKEN=abc123\n-REMOVED\nPATH=/bin\n".into(),
+            ),
+        ]);
+        assert_eq!(changed, 2);
+
+        let pane = pane_by_tmux_id(&cache, "%1");
+        let context = pane.context.clone().unwrap();
+        assert_eq!(context.cwd.as_deref(), Some("/work/my repo"));
+        assert_eq!(context.env["VIRTUAL_ENV"], "/work/.venv");
+        assert_eq!(context.env["API_TOKEN"], "[REDACTED]");
+        assert!(!context.env.contains_key("PATH"));
+        assert_eq!(pane_by_tmux_id(&cache, "%2").context.unwrap().cwd.as_deref(), Some("/tmp"));
+
+        let view = serde_json::to_value(crate::rpc::handlers::panes::PaneView::from(pane)).unwrap();
+        assert_eq!(view["cwd"], "/work/my repo");
+        assert_eq!(view["env"]["VIRTUAL_ENV"], "/work/.venv");
+
+        // The next reconcile keeps it; a repeat capture changes nothing
+        c.update_cache(&[meta("$1", "%1"), meta("$1", "%2")]);
+        assert_eq!(pane_by_tmux_id(&cache, "%1").context, Some(context));
+        let changed = c.apply_context(vec![(ContextQuery::Paths, "%1 /work/my repo\n%2 /tmp\n".into())]);
+        assert_eq!(changed, 0, "a failed environment lookup keeps the old env");
+    }
+
+    #[test]
+    fn context_capture_absent_when_disabled() {
+        let cache = Arc::new(Cache::new(100));
+        let c = context_collector(cache.clone(), false);
+        assert!(c.context_commands().is_empty(), "capture-output gates context capture");
+
+        let pane = pane_by_tmux_id(&cache, "%1");
+        assert_eq!(pane.context, None);
+        let view = serde_json::to_value(crate::rpc::handlers::panes::PaneView::from(pane)).unwrap();
+        assert!(view.get("cwd").is_none());
+        assert!(view.get("env").is_none());
+
+        let cache = Arc::new(Cache::new(100));
+        let runner = CommandRunner::new(crate::command::CommandConfig::default());
+        let config = TmuxCollectorConfig {
+            capture_output: true,
+            ..TmuxCollectorConfig::default()
+        };
+        let c = TmuxCollector::new(runner, Arc::new(EventBus::new(4)), cache, config);
+        assert!(c.context_commands().is_empty(), "nothing asked for");
+    }
+
+    #[test]
+    fn update_cache_uses_injected_clock() {
+        let cache = Arc::new(Cache::new(100));
+        let clock = Arc::new(crate::clock::MockClock::new(5_000));
+        let mut c = make_collector_with_cache(cache.clone()).with_clock(clock.clone());
+
+        let (sessions, _) = c.update_cache(&[meta("$1", "%1")]);
+        assert_eq!(sessions[0].last_seen_at, 5_000);
+
+        clock.advance(30);
+        c.update_cache(&[meta("$1", "%1")]);
+        assert_eq!(cache.all_sessions()[0].last_seen_at, 5_030);
+    }
+
     // --- diff_state edge cases ---
 
     #[test]
@@ -358,7 +767,9 @@ mod tests {
         let metas = vec![meta("$1", "%1"), meta("$1", "%2"), meta("$2", "%3")];
         let (sessions, panes) = c.update_cache(&metas);
 
-        assert_eq!(sessions.len(), 3); // one per meta entry
+        assert_eq!(sessions.len(), 2, "one per tmux session");
+        assert_eq!(sessions[0].pane_count, 2);
+        assert_eq!(sessions[1].pane_count, 1);
         assert_eq!(panes.len(), 3);
 
         // Cache should have the sessions and panes
@@ -372,7 +783,7 @@ mod tests {
         let mut c = make_collector_with_cache(cache.clone());
         let metas = vec![meta("$1", "%1")];
         let (sessions, _panes) = c.update_cache(&metas);
-        assert_eq!(sessions[0].source_id, "tmux");
+        assert_eq!(sessions[0].source_id, Source::Tmux);
     }
 
     #[test]
@@ -526,6 +937,29 @@ mod tests {
 
     // --- Pane belongs to correct session ---
 
+    #[test]
+    fn captured_output_changes_advance_pane_activity() {
+        let cache = Arc::new(Cache::new(100));
+        let clock = Arc::new(crate::clock::MockClock::new(2_000));
+        let mut c = make_collector_with_cache(cache.clone()).with_clock(clock.clone());
+        let (_, panes) = c.update_cache(&[meta("$1", "%1")]);
+        let pane_uid = panes[0].pane_uid.clone();
+        assert_eq!(cache.get_pane(&pane_uid).unwrap().last_activity_at, Some(1000));
+
+        assert!(!c.record_capture("%1", b"prompt", 2_000), "first capture is the baseline");
+        assert!(!c.record_capture("%1", b"prompt", 2_100));
+        assert_eq!(cache.get_pane(&pane_uid).unwrap().last_activity_at, Some(1000));
+
+        assert!(c.record_capture("%1", b"prompt\nbuilding", 2_200));
+        assert_eq!(cache.get_pane(&pane_uid).unwrap().last_activity_at, Some(2_200));
+        assert!(!c.record_capture("%unknown", b"x", 2_300));
+
+        // A later tmux poll with an older pane_last_activity keeps the newer time
+        clock.set(2_500);
+        let (_, panes) = c.update_cache(&[meta("$1", "%1")]);
+        assert_eq!(panes[0].last_activity_at, Some(2_200));
+    }
+
     #[test]
     fn pane_session_uid_matches_parent() {
         let cache = Arc::new(Cache::new(100));
@@ -540,6 +974,35 @@ mod tests {
         assert_eq!(panes[1].session_uid, sessions[1].session_uid);
     }
 
+    #[test]
+    fn parsed_output_populates_tmux_ids_and_associates_panes() {
+        let cache = Arc::new(Cache::new(100));
+        let mut c = make_collector_with_cache(cache.clone());
+        let output = "$0:main:@0:0:%0:0:100:zsh:1700000000:0:0\n\
+                      $3:work:@7:2:%9:0:103:bash:1700000003:0:0\n\
+                      $0:main:@1:1:%5:1:102:cargo:1700000002:0:0\n";
+        let metas = crate::parsers::tmux_panes::parse_tmux_panes(output).expect("parse");
+        let (sessions, panes) = c.update_cache(&metas);
+
+        assert_eq!(sessions.len(), 2);
+        let main = &sessions[0];
+        let work = &sessions[1];
+        assert_eq!(main.tmux_session_id.as_deref(), Some("$0"));
+        assert_eq!(main.pane_count, 2);
+        assert_eq!(work.tmux_session_id.as_deref(), Some("$3"));
+        assert_eq!(work.pane_count, 1);
+
+        let pane = |id: &str| panes.iter().find(|p| p.tmux_pane_id.as_deref() == Some(id)).unwrap();
+        assert_eq!(pane("%0").session_uid, main.session_uid);
+        assert_eq!(pane("%5").session_uid, main.session_uid, "listed after another session");
+        assert_eq!(pane("%5").tmux_window_id.as_deref(), Some("@1"));
+        assert_eq!(pane("%5").tmux_pane_pid, Some(102));
+        assert_eq!(pane("%9").session_uid, work.session_uid);
+        assert_eq!(pane("%9").tmux_window_id.as_deref(), Some("@7"));
+
+        assert_eq!(cache.get_session(&main.session_uid).unwrap().pane_count, 2);
+    }
+
     // ========================================================
     // Collector failure and recovery (bd-2wun)
     // ========================================================
@@ -551,9 +1014,11 @@ mod tests {
             tmux_timeout: Duration::from_millis(1), // extremely short timeout
             ..crate::command::CommandConfig::default()
         });
-        let bus = EventBus::new(4);
-        let mut config = TmuxCollectorConfig::default();
-        config.format = "#{session_id}:#{session_name}:#{window_id}:#{pane_id}:#{pane_index}:#{pane_pid}:#{pane_current_command}:#{pane_last_activity}:#{pane_dead}:#{pane_in_mode}".to_string();
+        let bus = Arc::new(EventBus::new(4));
+        let config = TmuxCollectorConfig {
+            format: "#{session_id}:#{session_name}:#{window_id}:#{window_index}:#{pane_id}:#{pane_index}:#{pane_pid}:#{pane_current_command}:#{pane_last_activity}:#{pane_dead}:#{pane_in_mode}".to_string(),
+            ..TmuxCollectorConfig::default()
+        };
         let mut collector = TmuxCollector::new(runner, bus, cache, config);
 
         // First poll attempt — will fail (tmux either not installed or timeout)
@@ -579,7 +1044,7 @@ mod tests {
             tmux_timeout: Duration::from_millis(1),
             ..crate::command::CommandConfig::default()
         });
-        let bus = EventBus::new(4);
+        let bus = Arc::new(EventBus::new(4));
         let config = TmuxCollectorConfig::default();
         let mut collector = TmuxCollector::new(runner, bus, cache, config);
 
@@ -602,6 +1067,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn command_not_found_degrades_health_immediately() {
+        let cache = Arc::new(Cache::new(100));
+        let mut c = make_collector_with_cache(cache.clone());
+        let err = CollectorError::CommandNotFound(CollectorKind::Tmux);
+        assert_eq!(c.record_failure(err.clone()).unwrap_err(), err);
+        let health = cache.health();
+        assert_eq!(health.status, "degraded");
+        assert_eq!(
+            health.degraded_reason,
+            Some(crate::cache::DegradedReason::TmuxUnavailable)
+        );
+    }
+
+    #[test]
+    fn transient_failures_degrade_at_threshold() {
+        let cache = Arc::new(Cache::new(100));
+        let mut c = make_collector_with_cache(cache.clone());
+        for _ in 1..DEGRADED_FAILURE_THRESHOLD {
+            assert!(c.record_failure(CollectorError::Timeout).is_err());
+            assert_eq!(cache.health().degraded_reason, None);
+        }
+        let result = c.record_failure(CollectorError::Timeout).unwrap();
+        assert!(result.degraded);
+        assert_eq!(
+            cache.health().degraded_reason,
+            Some(crate::cache::DegradedReason::CommandTimeout)
+        );
+    }
+
     #[test]
     fn failure_count_resets_on_success() {
         // This tests that successful poll_once would reset failure_count.
@@ -674,4 +1169,39 @@ mod tests {
         assert_eq!(r.removed, 0);
         assert!(!r.degraded);
     }
+
+    #[tokio::test]
+    async fn capture_reaches_pane_tail_on_the_shared_bus() {
+        use crate::config::ConfigManager;
+        use crate::rpc::{Capabilities, RpcContext};
+        use crate::transport::PaneTailFeed;
+
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("config.toml");
+        std::fs::write(&config_path, "[capture]\ncapture-output = true\n").unwrap();
+        let config = ConfigManager::load_from_fs(Some(config_path)).expect("config");
+        let caps = Capabilities { ntm: false, tmux: true, stream: true, systemd: false };
+        let ctx = RpcContext::with_capabilities(Arc::new(Cache::new(100)), config, caps)
+            .with_pane_tails();
+        let runner = CommandRunner::new(crate::command::CommandConfig::default());
+        let mut collector = TmuxCollector::new(
+            runner,
+            ctx.bus.clone(),
+            ctx.cache.clone(),
+            TmuxCollectorConfig::default(),
+        );
+        collector.update_cache(&[meta("$1", "%1")]);
+        let pane_uid = pane_by_tmux_id(&ctx.cache, "%1").pane_uid;
+
+        let mut feed = PaneTailFeed::new(&ctx.bus);
+        crate::rpc::handlers::panes::tail(&ctx, serde_json::json!({ "paneId": pane_uid })).unwrap();
+        collector.publish_capture("%1", b"$ make\n");
+        collector.publish_capture("%1", b"$ make\nbuilding\n");
+
+        let notifications = feed.next(&ctx).await.expect("bus open");
+        assert_eq!(notifications.len(), 1);
+        assert_eq!(notifications[0].method, "pane.output");
+        assert_eq!(notifications[0].params["paneId"], pane_uid.as_str());
+        assert_eq!(notifications[0].params["lines"], serde_json::json!(["building"]));
+    }
 }
@@ -1,6 +1,7 @@
 use crate::metrics::METRICS;
 use std::collections::HashMap;
 use std::process::Stdio;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::io::{AsyncRead, AsyncReadExt};
 use tokio::process::Command;
@@ -130,14 +131,23 @@ impl CircuitBreaker {
 #[derive(Debug)]
 pub struct CommandRunner {
     config: CommandConfig,
-    semaphore: Semaphore,
+    semaphore: Arc<Semaphore>,
     breaker: CircuitBreaker,
 }
 
 impl CommandRunner {
     pub fn new(config: CommandConfig) -> Self {
+        let permits = Arc::new(Semaphore::new(config.max_concurrent));
+        Self::with_permits(config, permits)
+    }
+
+    /// Runner drawing from a shared permit pool, so every runner built from
+    /// the same pool counts toward one process limit (`config.max_concurrent`
+    /// is ignored). Commands beyond the limit wait for a permit instead of
+    /// spawning.
+    pub fn with_permits(config: CommandConfig, permits: Arc<Semaphore>) -> Self {
         Self {
-            semaphore: Semaphore::new(config.max_concurrent),
+            semaphore: permits,
             config,
             breaker: CircuitBreaker::new(),
         }
@@ -396,6 +406,61 @@ mod tests {
         assert!(result.status.success());
     }
 
+    fn sleep_spec(secs: &str) -> CommandSpec {
+        CommandSpec {
+            program: "sleep".to_string(),
+            args: vec![secs.to_string()],
+            timeout: Duration::from_secs(5),
+            max_output_bytes: 1024,
+            category: CommandCategory::TmuxFast,
+        }
+    }
+
+    #[tokio::test]
+    async fn commands_beyond_the_limit_are_serialized() {
+        let config = CommandConfig {
+            max_concurrent: 1,
+            ..CommandConfig::default()
+        };
+        let runner = Arc::new(CommandRunner::new(config));
+        let start = Instant::now();
+        let handles: Vec<_> = (0..3)
+            .map(|_| {
+                let runner = runner.clone();
+                tokio::spawn(async move { runner.run(sleep_spec("0.1")).await })
+            })
+            .collect();
+        for handle in handles {
+            assert!(handle.await.unwrap().unwrap().status.success());
+        }
+        // One at a time: three 100ms sleeps cannot overlap
+        assert!(start.elapsed() >= Duration::from_millis(300));
+    }
+
+    #[tokio::test]
+    async fn runners_sharing_permits_share_the_limit() {
+        let permits = Arc::new(Semaphore::new(2));
+        let runners: Vec<_> = (0..4)
+            .map(|_| {
+                Arc::new(CommandRunner::with_permits(
+                    CommandConfig::default(),
+                    permits.clone(),
+                ))
+            })
+            .collect();
+        let start = Instant::now();
+        let handles: Vec<_> = runners
+            .into_iter()
+            .map(|runner| tokio::spawn(async move { runner.run(sleep_spec("0.1")).await }))
+            .collect();
+        for handle in handles {
+            assert!(handle.await.unwrap().unwrap().status.success());
+        }
+        // Four commands through two slots take at least two rounds
+        assert!(start.elapsed() >= Duration::from_millis(200));
+        assert_eq!(permits.available_permits(), 2);
+    }
+
     #[tokio::test]
     async fn circuit_breaker_starts_closed() {
         let breaker = CircuitBreaker::new();
@@ -1,5 +1,7 @@
+use crate::locks;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
 use std::fmt;
 use std::fs;
@@ -41,6 +43,24 @@ impl Default for ServerConfig {
     }
 }
 
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ServiceConfig {
+    /// Name shown to clients to tell instances apart; the hostname when unset.
+    pub label: Option<String>,
+}
+
+impl ServiceConfig {
+    /// The configured label (an explicit empty string stays empty), else the
+    /// hostname, else empty.
+    pub fn resolved_label(&self) -> String {
+        match &self.label {
+            Some(label) => label.trim().to_string(),
+            None => crate::service::hostname().unwrap_or_default(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "kebab-case")]
 pub struct PollingConfig {
@@ -54,6 +74,23 @@ pub struct PollingConfig {
     pub snapshot_degraded_interval_ms: u64,
     /// Idle threshold (seconds) to classify sessions as active vs idle.
     pub idle_threshold_secs: i64,
+    /// Per-source overrides of `idle_threshold_secs`, keyed by session
+    /// `source_id` (`ntm`, `tmux`).
+    pub idle_threshold_by_source: BTreeMap<String, i64>,
+    /// Force an ntm reconcile on every Nth poll even when the output is
+    /// unchanged (1 = every poll, so unchanged output is never skipped).
+    pub reconcile_every_n: u32,
+    /// Seconds an ntm session must stay missing from ntm output before it is
+    /// marked ended (0 = end as soon as it is missing).
+    pub session_end_grace_secs: u64,
+    /// What ntm reconcile matches sessions on across polls: "name",
+    /// "tmux-session-id", or "metadata". Only the latter two survive renames.
+    pub session_identity: String,
+    /// metadata: the ntm metadata column holding the session identity
+    pub session_identity_key: String,
+    /// Daily window (`"22:00-06:00"`, optionally followed by `UTC` or a
+    /// `±HH:MM` offset) during which polling stays at the background interval.
+    pub quiet_hours: Option<String>,
 }
 
 impl Default for PollingConfig {
@@ -64,15 +101,48 @@ impl Default for PollingConfig {
             snapshot_background_interval_ms: 15_000,
             snapshot_degraded_interval_ms: 10_000,
             idle_threshold_secs: 300,
+            idle_threshold_by_source: BTreeMap::new(),
+            reconcile_every_n: 1,
+            session_end_grace_secs: 10,
+            session_identity: "name".to_string(),
+            session_identity_key: "id".to_string(),
+            quiet_hours: None,
         }
     }
 }
 
+impl PollingConfig {
+    /// Idle threshold for sessions from `source_id`, falling back to the global one.
+    pub fn idle_threshold_for(&self, source_id: &str) -> i64 {
+        self.idle_threshold_by_source
+            .get(source_id)
+            .copied()
+            .unwrap_or(self.idle_threshold_secs)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "kebab-case")]
-#[derive(Default)]
 pub struct CaptureConfig {
     pub capture_output: bool,
+    /// Byte cap for `panes.outputPreview` content (after redaction).
+    pub preview_max_bytes: usize,
+    /// Record each pane's working directory. Needs `capture_output`.
+    pub capture_cwd: bool,
+    /// Session environment variables copied onto each pane, by exact name.
+    /// Values are redacted. Needs `capture_output`.
+    pub env_allowlist: Vec<String>,
+}
+
+impl Default for CaptureConfig {
+    fn default() -> Self {
+        Self {
+            capture_output: false,
+            preview_max_bytes: 256 * 1024,
+            capture_cwd: false,
+            env_allowlist: Vec::new(),
+        }
+    }
 }
 
 
@@ -119,6 +189,206 @@ impl Default for LoggingConfig {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct GroupingConfig {
+    /// How sessions are grouped: "name-prefix", "metadata", or "cwd"
+    pub rule: String,
+    /// name-prefix: the group is the session name up to the first separator
+    pub separator: String,
+    /// metadata: the ntm metadata column holding the group
+    pub metadata_key: String,
+    /// cwd: leading path components kept, so sessions under one project
+    /// directory share a group. 0 keeps the whole directory.
+    pub cwd_depth: usize,
+}
+
+impl Default for GroupingConfig {
+    fn default() -> Self {
+        Self {
+            rule: "name-prefix".to_string(),
+            separator: "-".to_string(),
+            metadata_key: "project".to_string(),
+            cwd_depth: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct TmuxConfig {
+    /// `tmux list-panes -F` format: `#{field}` placeholders joined by `:`
+    pub list_format: String,
+}
+
+impl Default for TmuxConfig {
+    fn default() -> Self {
+        Self {
+            list_format: crate::parsers::tmux_panes::DEFAULT_FORMAT.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct RpcConfig {
+    /// Events returned by `events.list` when the client omits `limit`.
+    pub default_event_limit: usize,
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        Self {
+            default_event_limit: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct CacheConfig {
+    /// Seconds an ended session stays in the live cache (0 = keep until restart).
+    pub ended_session_ttl_secs: u64,
+    /// Seconds since `last_seen_at` before a pane whose session has ended or
+    /// is no longer cached is dropped from the live cache (0 = keep).
+    pub pane_stale_ttl_secs: u64,
+}
+
+impl Default for CacheConfig {
+    fn default() -> Self {
+        Self {
+            ended_session_ttl_secs: 3600,
+            pane_stale_ttl_secs: 3600,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct TransportsConfig {
+    /// Newline-delimited JSON-RPC over stdin/stdout. Unset: enabled only
+    /// when no network transport is.
+    pub stdio: Option<bool>,
+    pub ws: bool,
+    pub ws_port: u16,
+    pub http: bool,
+    pub http_port: u16,
+    /// Extra ports the ws/http servers try, one after another, when their
+    /// configured port is already in use. 0 fails on a busy port.
+    pub port_scan: u16,
+    /// Indent stdio responses and notifications for reading by hand. Such
+    /// output is no longer one message per line.
+    pub stdio_pretty: bool,
+    /// How stdio messages are delimited in both directions.
+    pub stdio_framing: StdioFraming,
+    /// Unix domain socket transport (not implemented yet; ignored).
+    pub uds: bool,
+    pub uds_path: Option<PathBuf>,
+}
+
+impl Default for TransportsConfig {
+    fn default() -> Self {
+        Self {
+            stdio: None,
+            ws: false,
+            ws_port: 3847,
+            http: false,
+            http_port: 3848,
+            port_scan: 0,
+            stdio_pretty: false,
+            stdio_framing: StdioFraming::Newline,
+            uds: false,
+            uds_path: None,
+        }
+    }
+}
+
+/// Message framing on the stdio transport.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StdioFraming {
+    /// One JSON message per line.
+    #[default]
+    Newline,
+    /// `Content-Length: <n>` header, a blank line, then `n` bytes of JSON
+    /// (the LSP base protocol).
+    ContentLength,
+}
+
+impl std::str::FromStr for StdioFraming {
+    type Err = ConfigError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_lowercase().as_str() {
+            "newline" => Ok(Self::Newline),
+            "content-length" => Ok(Self::ContentLength),
+            other => Err(ConfigError::new(format!(
+                "unknown stdio framing {other:?} (expected newline or content-length)"
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ExternalCommandConfig {
+    /// Most tmux/ntm subprocesses running at once across the daemon; further
+    /// commands wait for a slot. Read at startup.
+    pub max_concurrent_processes: usize,
+}
+
+impl Default for ExternalCommandConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrent_processes: 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct NotificationsConfig {
+    /// Minimum gap between `sessions.snapshot` notifications; triggers inside
+    /// the window are folded into one send of the latest state. 0 disables it.
+    pub coalesce_ms: u64,
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self { coalesce_ms: 1_000 }
+    }
+}
+
+/// Severities `detector.repeat-severity` accepts, lowest first.
+pub const SEVERITY_LEVELS: [&str; 3] = ["info", "warn", "error"];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct DetectorConfig {
+    /// Events of one type from one pane within `repeat-window-secs` (this
+    /// one included) that promote its severity. 0 disables the rule.
+    pub repeat_threshold: usize,
+    pub repeat_window_secs: i64,
+    /// Severity a repeated event is raised to; higher severities are kept.
+    pub repeat_severity: String,
+    /// Also record an `escalation` event when a pane first reaches the threshold.
+    pub repeat_escalation: bool,
+}
+
+impl Default for DetectorConfig {
+    fn default() -> Self {
+        Self {
+            repeat_threshold: 0,
+            repeat_window_secs: 600,
+            repeat_severity: "warn".to_string(),
+            repeat_escalation: false,
+        }
+    }
+}
+
+/// `maintenance.max-db-mb` above this (10 GiB) draws a validation warning.
+const MAX_DB_MB_WARN: u64 = 10 * 1024;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default, rename_all = "kebab-case")]
 pub struct MaintenanceConfig {
@@ -154,18 +424,49 @@ impl Default for MaintenanceConfig {
 #[derive(Default)]
 pub struct DaemonConfig {
     pub server: ServerConfig,
+    pub service: ServiceConfig,
     pub polling: PollingConfig,
     pub capture: CaptureConfig,
     pub security: SecurityConfig,
     pub privacy: PrivacyConfig,
     pub logging: LoggingConfig,
     pub maintenance: MaintenanceConfig,
+    pub grouping: GroupingConfig,
+    pub rpc: RpcConfig,
+    pub tmux: TmuxConfig,
+    pub cache: CacheConfig,
+    pub transports: TransportsConfig,
+    pub command: ExternalCommandConfig,
+    pub notifications: NotificationsConfig,
+    pub detector: DetectorConfig,
 }
 
 
 impl DaemonConfig {
+    /// Parse a config file, logging a deprecation warning for each legacy
+    /// snake_case key it had to migrate.
     pub fn from_toml_str(raw: &str) -> Result<Self, ConfigError> {
-        toml::from_str(raw).map_err(|err| ConfigError::new(format!("TOML parse error: {err}")))
+        let (config, warnings) = Self::from_toml_str_with_warnings(raw)?;
+        for warning in &warnings {
+            tracing::warn!("{warning}");
+        }
+        Ok(config)
+    }
+
+    /// Parse a config file, accepting keys written before the kebab-case
+    /// rename (`snapshot_interval_ms`) as their kebab-case field. Returns one
+    /// warning per legacy key.
+    pub fn from_toml_str_with_warnings(raw: &str) -> Result<(Self, Vec<String>), ConfigError> {
+        let parse_error =
+            |err: toml::de::Error| ConfigError::new(format!("TOML parse error: {err}"));
+        let mut table: toml::Table = raw.parse().map_err(parse_error)?;
+        let warnings = migrate_legacy_keys(&mut table);
+        if warnings.is_empty() {
+            // Parse the text itself so errors keep their line and column
+            return Ok((toml::from_str(raw).map_err(parse_error)?, warnings));
+        }
+        let config = toml::Value::Table(table).try_into().map_err(parse_error)?;
+        Ok((config, warnings))
     }
 
     pub fn apply_env_overrides(&mut self) {
@@ -199,10 +500,140 @@ impl DaemonConfig {
                 self.polling.idle_threshold_secs = parsed;
             }
         }
+        if let Ok(every_n) = env::var("NTM_TRACKER_POLLING_RECONCILE_EVERY_N") {
+            if let Ok(parsed) = every_n.trim().parse::<u32>() {
+                self.polling.reconcile_every_n = parsed;
+            }
+        }
+        if let Ok(grace) = env::var("NTM_TRACKER_POLLING_SESSION_END_GRACE_SECS") {
+            if let Ok(parsed) = grace.trim().parse::<u64>() {
+                self.polling.session_end_grace_secs = parsed;
+            }
+        }
+        if let Ok(identity) = env::var("NTM_TRACKER_POLLING_SESSION_IDENTITY") {
+            let trimmed = identity.trim();
+            if !trimmed.is_empty() {
+                self.polling.session_identity = trimmed.to_string();
+            }
+        }
+        if let Ok(window) = env::var("NTM_TRACKER_POLLING_QUIET_HOURS") {
+            let trimmed = window.trim();
+            if !trimmed.is_empty() {
+                self.polling.quiet_hours = Some(trimmed.to_string());
+            }
+        }
         if let Ok(capture) = env::var("NTM_TRACKER_CAPTURE_OUTPUT") {
             let value = capture.trim().to_lowercase();
             self.capture.capture_output = matches!(value.as_str(), "1" | "true" | "yes" | "on");
         }
+        if let Ok(cwd) = env::var("NTM_TRACKER_CAPTURE_CWD") {
+            let value = cwd.trim().to_lowercase();
+            self.capture.capture_cwd = matches!(value.as_str(), "1" | "true" | "yes" | "on");
+        }
+        if let Ok(names) = env::var("NTM_TRACKER_CAPTURE_ENV_ALLOWLIST") {
+            self.capture.env_allowlist = names
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        if let Ok(max_bytes) = env::var("NTM_TRACKER_CAPTURE_PREVIEW_MAX_BYTES") {
+            if let Ok(parsed) = max_bytes.trim().parse::<usize>() {
+                self.capture.preview_max_bytes = parsed;
+            }
+        }
+        if let Ok(rule) = env::var("NTM_TRACKER_GROUPING_RULE") {
+            let trimmed = rule.trim();
+            if !trimmed.is_empty() {
+                self.grouping.rule = trimmed.to_string();
+            }
+        }
+        if let Ok(format) = env::var("NTM_TRACKER_TMUX_LIST_FORMAT") {
+            let trimmed = format.trim();
+            if !trimmed.is_empty() {
+                self.tmux.list_format = trimmed.to_string();
+            }
+        }
+        if let Ok(limit) = env::var("NTM_TRACKER_RPC_DEFAULT_EVENT_LIMIT") {
+            if let Ok(parsed) = limit.trim().parse::<usize>() {
+                self.rpc.default_event_limit = parsed;
+            }
+        }
+        if let Ok(label) = env::var("NTM_TRACKER_SERVICE_LABEL") {
+            self.service.label = Some(label);
+        }
+        if let Ok(ttl) = env::var("NTM_TRACKER_CACHE_ENDED_SESSION_TTL_SECS") {
+            if let Ok(parsed) = ttl.trim().parse::<u64>() {
+                self.cache.ended_session_ttl_secs = parsed;
+            }
+        }
+        if let Ok(ttl) = env::var("NTM_TRACKER_CACHE_PANE_STALE_TTL_SECS") {
+            if let Ok(parsed) = ttl.trim().parse::<u64>() {
+                self.cache.pane_stale_ttl_secs = parsed;
+            }
+        }
+        if let Ok(ws) = env::var("NTM_TRACKER_TRANSPORTS_WS") {
+            let value = ws.trim().to_lowercase();
+            self.transports.ws = matches!(value.as_str(), "1" | "true" | "yes" | "on");
+        }
+        if let Ok(port) = env::var("NTM_TRACKER_TRANSPORTS_WS_PORT") {
+            if let Ok(parsed) = port.trim().parse::<u16>() {
+                self.transports.ws_port = parsed;
+            }
+        }
+        if let Ok(http) = env::var("NTM_TRACKER_TRANSPORTS_HTTP") {
+            let value = http.trim().to_lowercase();
+            self.transports.http = matches!(value.as_str(), "1" | "true" | "yes" | "on");
+        }
+        if let Ok(port) = env::var("NTM_TRACKER_TRANSPORTS_HTTP_PORT") {
+            if let Ok(parsed) = port.trim().parse::<u16>() {
+                self.transports.http_port = parsed;
+            }
+        }
+        if let Ok(scan) = env::var("NTM_TRACKER_TRANSPORTS_PORT_SCAN") {
+            if let Ok(parsed) = scan.trim().parse::<u16>() {
+                self.transports.port_scan = parsed;
+            }
+        }
+        if let Ok(pretty) = env::var("NTM_TRACKER_TRANSPORTS_STDIO_PRETTY") {
+            let value = pretty.trim().to_lowercase();
+            self.transports.stdio_pretty = matches!(value.as_str(), "1" | "true" | "yes" | "on");
+        }
+        if let Ok(framing) = env::var("NTM_TRACKER_TRANSPORTS_STDIO_FRAMING") {
+            if let Ok(parsed) = framing.parse::<StdioFraming>() {
+                self.transports.stdio_framing = parsed;
+            }
+        }
+        if let Ok(limit) = env::var("NTM_TRACKER_COMMAND_MAX_CONCURRENT_PROCESSES") {
+            if let Ok(parsed) = limit.trim().parse::<usize>() {
+                self.command.max_concurrent_processes = parsed;
+            }
+        }
+        if let Ok(coalesce) = env::var("NTM_TRACKER_NOTIFICATIONS_COALESCE_MS") {
+            if let Ok(parsed) = coalesce.trim().parse::<u64>() {
+                self.notifications.coalesce_ms = parsed;
+            }
+        }
+        if let Ok(threshold) = env::var("NTM_TRACKER_DETECTOR_REPEAT_THRESHOLD") {
+            if let Ok(parsed) = threshold.trim().parse::<usize>() {
+                self.detector.repeat_threshold = parsed;
+            }
+        }
+        if let Ok(window) = env::var("NTM_TRACKER_DETECTOR_REPEAT_WINDOW_SECS") {
+            if let Ok(parsed) = window.trim().parse::<i64>() {
+                self.detector.repeat_window_secs = parsed;
+            }
+        }
+        if let Ok(severity) = env::var("NTM_TRACKER_DETECTOR_REPEAT_SEVERITY") {
+            if !severity.trim().is_empty() {
+                self.detector.repeat_severity = severity.trim().to_lowercase();
+            }
+        }
+        if let Ok(value) = env::var("NTM_TRACKER_DETECTOR_REPEAT_ESCALATION") {
+            self.detector.repeat_escalation =
+                matches!(value.trim().to_lowercase().as_str(), "1" | "true" | "yes" | "on");
+        }
         if let Ok(patterns) = env::var("NTM_TRACKER_PRIVACY_REDACTION_PATTERNS") {
             let parsed: Vec<String> = patterns
                 .split(',')
@@ -291,7 +722,9 @@ impl DaemonConfig {
         }
     }
 
-    pub fn validate(&self) -> Result<(), ConfigError> {
+    /// Check the config. Hard errors reject it; questionable but workable
+    /// settings come back as warnings.
+    pub fn validate(&self) -> Result<Vec<String>, ConfigError> {
         if self.polling.snapshot_interval_ms < 250 {
             return Err(ConfigError::new(
                 "polling.snapshot-interval-ms must be >= 250",
@@ -342,6 +775,146 @@ impl DaemonConfig {
                 "polling.idle-threshold-secs must be <= 7200",
             ));
         }
+        for (source, threshold) in &self.polling.idle_threshold_by_source {
+            if !(30..=7_200).contains(threshold) {
+                return Err(ConfigError::new(format!(
+                    "polling.idle-threshold-by-source.{source} must be between 30 and 7200"
+                )));
+            }
+        }
+
+        if self.polling.reconcile_every_n == 0 {
+            return Err(ConfigError::new(
+                "polling.reconcile-every-n must be >= 1",
+            ));
+        }
+        if self.polling.reconcile_every_n > 100 {
+            return Err(ConfigError::new(
+                "polling.reconcile-every-n must be <= 100",
+            ));
+        }
+        if self.polling.session_end_grace_secs > 3600 {
+            return Err(ConfigError::new(
+                "polling.session-end-grace-secs must be <= 3600",
+            ));
+        }
+        match self.polling.session_identity.as_str() {
+            "name" | "tmux-session-id" => {}
+            "metadata" => {
+                if self.polling.session_identity_key.trim().is_empty() {
+                    return Err(ConfigError::new(
+                        "polling.session-identity-key must not be empty for identity 'metadata'",
+                    ));
+                }
+            }
+            _ => {
+                return Err(ConfigError::new(
+                    "polling.session-identity must be one of 'name', 'tmux-session-id', or 'metadata'",
+                ));
+            }
+        }
+
+        if let Some(window) = &self.polling.quiet_hours {
+            crate::polling::QuietHours::parse(window).map_err(|err| {
+                ConfigError::new(format!("polling.quiet-hours is invalid: {err}"))
+            })?;
+        }
+
+        if self.capture.preview_max_bytes < 1024 {
+            return Err(ConfigError::new(
+                "capture.preview-max-bytes must be >= 1024",
+            ));
+        }
+        if let Some(name) = self.capture.env_allowlist.iter().find(|name| !is_env_name(name)) {
+            return Err(ConfigError::new(format!(
+                "capture.env-allowlist entry {name:?} is not a variable name"
+            )));
+        }
+
+        match self.grouping.rule.as_str() {
+            "name-prefix" => {
+                if self.grouping.separator.is_empty() {
+                    return Err(ConfigError::new(
+                        "grouping.separator must not be empty for rule 'name-prefix'",
+                    ));
+                }
+            }
+            "metadata" => {
+                if self.grouping.metadata_key.trim().is_empty() {
+                    return Err(ConfigError::new(
+                        "grouping.metadata-key must not be empty for rule 'metadata'",
+                    ));
+                }
+            }
+            "cwd" => {}
+            _ => {
+                return Err(ConfigError::new(
+                    "grouping.rule must be one of 'name-prefix', 'metadata', or 'cwd'",
+                ));
+            }
+        }
+
+        crate::parsers::tmux_panes::TmuxFormat::parse(&self.tmux.list_format)
+            .map_err(|err| ConfigError::new(format!("tmux.list-format is invalid: {err}")))?;
+
+        if self.rpc.default_event_limit < 1 {
+            return Err(ConfigError::new("rpc.default-event-limit must be >= 1"));
+        }
+        if self.rpc.default_event_limit > 1000 {
+            return Err(ConfigError::new("rpc.default-event-limit must be <= 1000"));
+        }
+
+        if self.transports.ws_port == 0 || self.transports.http_port == 0 {
+            return Err(ConfigError::new(
+                "transports.ws-port and transports.http-port must be >= 1",
+            ));
+        }
+        if self.transports.ws
+            && self.transports.http
+            && self.transports.ws_port == self.transports.http_port
+        {
+            return Err(ConfigError::new(
+                "transports.ws-port and transports.http-port must differ when both are enabled",
+            ));
+        }
+
+        if self.transports.port_scan > 100 {
+            return Err(ConfigError::new("transports.port-scan must be <= 100"));
+        }
+
+        if self.command.max_concurrent_processes == 0 {
+            return Err(ConfigError::new(
+                "command.max-concurrent-processes must be >= 1",
+            ));
+        }
+        if self.command.max_concurrent_processes > 64 {
+            return Err(ConfigError::new(
+                "command.max-concurrent-processes must be <= 64",
+            ));
+        }
+
+        if self.notifications.coalesce_ms > 60_000 {
+            return Err(ConfigError::new(
+                "notifications.coalesce-ms must be <= 60000",
+            ));
+        }
+
+        if self.detector.repeat_threshold == 1 || self.detector.repeat_threshold > 1_000 {
+            return Err(ConfigError::new(
+                "detector.repeat-threshold must be 0 (off) or between 2 and 1000",
+            ));
+        }
+        if !(1..=86_400).contains(&self.detector.repeat_window_secs) {
+            return Err(ConfigError::new(
+                "detector.repeat-window-secs must be between 1 and 86400",
+            ));
+        }
+        if !SEVERITY_LEVELS.contains(&self.detector.repeat_severity.as_str()) {
+            return Err(ConfigError::new(format!(
+                "detector.repeat-severity must be one of {}",
+                SEVERITY_LEVELS.join(", ")
+            )));
+        }
 
         for pattern in &self.privacy.redaction_patterns {
             Regex::new(pattern).map_err(|err| {
@@ -393,7 +966,35 @@ impl DaemonConfig {
             ));
         }
 
-        Ok(())
+        Ok(self.warnings())
+    }
+
+    fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if self.polling.snapshot_idle_interval_ms == self.polling.snapshot_interval_ms {
+            warnings.push(
+                "polling.snapshot-idle-interval-ms equals snapshot-interval-ms; idle sessions are polled as often as active ones"
+                    .to_string(),
+            );
+        }
+        if self.polling.snapshot_background_interval_ms == self.polling.snapshot_idle_interval_ms {
+            warnings.push(
+                "polling.snapshot-background-interval-ms equals snapshot-idle-interval-ms; polling never backs off when no sessions exist"
+                    .to_string(),
+            );
+        }
+        if self.transports.uds {
+            warnings.push(
+                "transports.uds is not supported by this daemon yet and is ignored".to_string(),
+            );
+        }
+        if self.maintenance.max_db_mb > MAX_DB_MB_WARN {
+            warnings.push(format!(
+                "maintenance.max-db-mb is {}; above {MAX_DB_MB_WARN} the database is unlikely to ever be pruned for size",
+                self.maintenance.max_db_mb
+            ));
+        }
+        warnings
     }
 }
 
@@ -401,34 +1002,29 @@ impl DaemonConfig {
 pub struct ConfigManager {
     path: Option<PathBuf>,
     config: Arc<RwLock<DaemonConfig>>,
+    /// Non-fatal findings from the last load: legacy keys and validation warnings.
+    warnings: Arc<RwLock<Vec<String>>>,
 }
 
 impl ConfigManager {
     pub fn load_from_fs(config_override: Option<PathBuf>) -> Result<Self, ConfigError> {
         let path = resolve_config_path(config_override);
-        let mut config = if let Some(ref path) = path {
-            let raw = fs::read_to_string(path).map_err(|err| {
-                ConfigError::new(format!("Unable to read config '{}': {err}", path.display()))
-            })?;
-            DaemonConfig::from_toml_str(&raw)?
-        } else {
-            DaemonConfig::default()
-        };
-
-        config.apply_env_overrides();
-        config.validate()?;
+        let (config, warnings) = load_config(path.as_deref())?;
 
         Ok(Self {
             path,
             config: Arc::new(RwLock::new(config)),
+            warnings: Arc::new(RwLock::new(warnings)),
         })
     }
 
+    /// Warnings from the last successful load or reload.
+    pub fn warnings(&self) -> Vec<String> {
+        locks::read(&self.warnings, "config warnings").clone()
+    }
+
     pub fn current(&self) -> DaemonConfig {
-        self.config
-            .read()
-            .unwrap_or_else(|poisoned| poisoned.into_inner())
-            .clone()
+        locks::read(&self.config, "config").clone()
     }
 
     pub fn reload(&self) -> Result<DaemonConfig, ConfigError> {
@@ -437,18 +1033,10 @@ impl ConfigManager {
             return Ok(self.current());
         };
 
-        let raw = fs::read_to_string(path).map_err(|err| {
-            ConfigError::new(format!("Unable to read config '{}': {err}", path.display()))
-        })?;
-        let mut config = DaemonConfig::from_toml_str(&raw)?;
-        config.apply_env_overrides();
-        config.validate()?;
+        let (config, warnings) = load_config(Some(path))?;
 
-        let mut guard = self
-            .config
-            .write()
-            .unwrap_or_else(|poisoned| poisoned.into_inner());
-        *guard = config.clone();
+        *locks::write(&self.config, "config") = config.clone();
+        *locks::write(&self.warnings, "config warnings") = warnings;
 
         Ok(config)
     }
@@ -463,6 +1051,7 @@ impl Default for ConfigManager {
         Self {
             path: None,
             config: Arc::new(RwLock::new(DaemonConfig::default())),
+            warnings: Arc::new(RwLock::new(Vec::new())),
         }
     }
 }
@@ -483,6 +1072,70 @@ pub async fn watch_sighup_for_reload(config: ConfigManager) -> Result<(), Config
     Ok(())
 }
 
+/// Read, parse, override, and validate a config file (defaults when `path` is
+/// `None`), logging every warning found along the way.
+fn load_config(path: Option<&Path>) -> Result<(DaemonConfig, Vec<String>), ConfigError> {
+    let (mut config, mut warnings) = match path {
+        Some(path) => {
+            let raw = fs::read_to_string(path).map_err(|err| {
+                ConfigError::new(format!("Unable to read config '{}': {err}", path.display()))
+            })?;
+            DaemonConfig::from_toml_str_with_warnings(&raw)?
+        }
+        None => (DaemonConfig::default(), Vec::new()),
+    };
+
+    config.apply_env_overrides();
+    warnings.extend(config.validate()?);
+    for warning in &warnings {
+        tracing::warn!("{warning}");
+    }
+    Ok((config, warnings))
+}
+
+/// Rename snake_case keys inside each section to kebab-case. A legacy key is
+/// dropped when its kebab-case spelling is also present. Nested tables (such
+/// as `polling.idle-threshold-by-source`) are user-keyed and left alone.
+fn migrate_legacy_keys(table: &mut toml::Table) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for (section, value) in table.iter_mut() {
+        let toml::Value::Table(fields) = value else {
+            continue;
+        };
+        let legacy: Vec<String> = fields
+            .keys()
+            .filter(|key| key.contains('_'))
+            .cloned()
+            .collect();
+        for key in legacy {
+            let kebab = key.replace('_', "-");
+            let Some(value) = fields.remove(&key) else {
+                continue;
+            };
+            if fields.contains_key(&kebab) {
+                warnings.push(format!(
+                    "config key {section}.{key} is ignored because {section}.{kebab} is also set"
+                ));
+            } else {
+                warnings.push(format!(
+                    "config key {section}.{key} is deprecated; rename it to {section}.{kebab}"
+                ));
+                fields.insert(kebab, value);
+            }
+        }
+    }
+    warnings
+}
+
+/// A shell-style variable name: a letter or `_`, then letters, digits and `_`.
+fn is_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
 fn resolve_config_path(config_override: Option<PathBuf>) -> Option<PathBuf> {
     if let Some(path) = config_override {
         return Some(path);
@@ -534,6 +1187,21 @@ fn validate_token_file_permissions(_path: &Path) -> Result<(), ConfigError> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn service_label_parse_and_resolve() {
+        let config = DaemonConfig::from_toml_str("[service]\nlabel = \" wsl-main \"\n").unwrap();
+        assert_eq!(config.service.resolved_label(), "wsl-main");
+
+        let blank = ServiceConfig {
+            label: Some(String::new()),
+        };
+        assert_eq!(blank.resolved_label(), "");
+        assert_eq!(
+            ServiceConfig::default().resolved_label(),
+            crate::service::hostname().unwrap_or_default()
+        );
+    }
+
     #[test]
     fn defaults_parse_and_validate() {
         let mut config = DaemonConfig::default();
@@ -579,6 +1247,7 @@ capture-output = true
         assert_eq!(config.snapshot_background_interval_ms, 15_000);
         assert_eq!(config.snapshot_degraded_interval_ms, 10_000);
         assert_eq!(config.idle_threshold_secs, 300);
+        assert_eq!(config.reconcile_every_n, 1);
     }
 
     #[test]
@@ -606,6 +1275,119 @@ capture-output = true
     fn capture_config_defaults() {
         let config = CaptureConfig::default();
         assert!(!config.capture_output);
+        assert_eq!(config.preview_max_bytes, 256 * 1024);
+        assert!(!config.capture_cwd);
+        assert!(config.env_allowlist.is_empty());
+    }
+
+    #[test]
+    fn capture_context_options_parse_and_validate() {
+        let config = DaemonConfig::from_toml_str(
+            "[capture]\ncapture-output = true\ncapture-cwd = true\nenv-allowlist = [\"VIRTUAL_ENV\", \"AWS_PROFILE\"]\n",
+        )
+        .unwrap();
+        assert!(config.capture.capture_cwd);
+        assert_eq!(config.capture.env_allowlist, vec!["VIRTUAL_ENV", "AWS_PROFILE"]);
+        config.validate().unwrap();
+
+        let mut bad = config.clone();
+        bad.capture.env_allowlist = vec!["PATH=/bin".to_string()];
+        let err = bad.validate().unwrap_err();
+        assert!(err.message.contains("capture.env-allowlist"), "{}", err.message);
+        bad.capture.env_allowlist = vec!["9LIVES".to_string()];
+        assert!(bad.validate().is_err());
+    }
+
+    #[test]
+    fn grouping_config_from_toml() {
+        let config = DaemonConfig::from_toml_str(
+            r#"
+[grouping]
+rule = "metadata"
+metadata-key = "repo"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.grouping.rule, "metadata");
+        assert_eq!(config.grouping.metadata_key, "repo");
+        assert_eq!(config.grouping.separator, "-");
+        assert_eq!(config.grouping.cwd_depth, 0);
+        config.validate().unwrap();
+
+        let config = DaemonConfig::from_toml_str("[grouping]\nrule = \"cwd\"\ncwd-depth = 2\n").unwrap();
+        assert_eq!(config.grouping.cwd_depth, 2);
+        config.validate().unwrap();
+    }
+
+    #[test]
+    fn validation_grouping_rule_invalid() {
+        let mut config = DaemonConfig::default();
+        config.grouping.rule = "owner".to_string();
+        let err = config.validate().unwrap_err();
+        assert!(err.message.contains("grouping.rule must be one of"));
+    }
+
+    #[test]
+    fn tmux_list_format_from_toml() {
+        let raw = "[tmux]\nlist-format = \"#{pane_id}:#{pane_index}:#{session_id}:#{session_name}:#{window_id}\"\n";
+        let config = DaemonConfig::from_toml_str(raw).unwrap();
+        assert!(config.validate().is_ok());
+        assert!(config.tmux.list_format.starts_with("#{pane_id}"));
+    }
+
+    #[test]
+    fn validation_tmux_list_format_missing_required_field() {
+        let mut config = DaemonConfig::default();
+        config.tmux.list_format = "#{session_id}:#{session_name}:#{window_id}:#{pane_index}".to_string();
+        let err = config.validate().unwrap_err();
+        assert!(err.message.contains("tmux.list-format is invalid"));
+        assert!(err.message.contains("#{pane_id}"));
+    }
+
+    #[test]
+    fn rpc_config_from_toml() {
+        let config = DaemonConfig::from_toml_str("[rpc]\ndefault-event-limit = 25\n").unwrap();
+        assert_eq!(config.rpc.default_event_limit, 25);
+        assert_eq!(DaemonConfig::default().rpc.default_event_limit, 100);
+    }
+
+    #[test]
+    fn cache_config_from_toml() {
+        let config = DaemonConfig::from_toml_str(
+            "[cache]\nended-session-ttl-secs = 120\npane-stale-ttl-secs = 600\n",
+        )
+        .unwrap();
+        assert_eq!(config.cache.ended_session_ttl_secs, 120);
+        assert_eq!(config.cache.pane_stale_ttl_secs, 600);
+        assert_eq!(DaemonConfig::default().cache.ended_session_ttl_secs, 3600);
+        assert_eq!(DaemonConfig::default().cache.pane_stale_ttl_secs, 3600);
+    }
+
+    #[test]
+    fn validation_rpc_default_event_limit_bounds() {
+        let mut config = DaemonConfig::default();
+        config.rpc.default_event_limit = 0;
+        let err = config.validate().unwrap_err();
+        assert!(err.message.contains("rpc.default-event-limit must be >= 1"));
+        config.rpc.default_event_limit = 1001;
+        let err = config.validate().unwrap_err();
+        assert!(err.message.contains("rpc.default-event-limit must be <= 1000"));
+    }
+
+    #[test]
+    fn validation_grouping_empty_separator() {
+        let mut config = DaemonConfig::default();
+        config.grouping.separator = String::new();
+        let err = config.validate().unwrap_err();
+        assert!(err.message.contains("grouping.separator must not be empty"));
+    }
+
+    #[test]
+    fn validation_capture_preview_max_bytes_too_small() {
+        let mut config = DaemonConfig::default();
+        config.capture.preview_max_bytes = 100;
+        let err = config.validate().unwrap_err();
+        assert!(err.message.contains("capture.preview-max-bytes must be >= 1024"));
     }
 
     #[test]
@@ -721,6 +1503,163 @@ max-db-mb = 256
         assert!(err.message.contains("idle-threshold-secs must be <= 7200"));
     }
 
+    #[test]
+    fn idle_threshold_by_source_from_toml() {
+        let config = DaemonConfig::from_toml_str(
+            "[polling]\nidle-threshold-secs = 600\n\n[polling.idle-threshold-by-source]\ntmux = 60\n",
+        )
+        .unwrap();
+        assert_eq!(config.polling.idle_threshold_for("tmux"), 60);
+        assert_eq!(config.polling.idle_threshold_for("ntm"), 600);
+    }
+
+    #[test]
+    fn validation_idle_threshold_by_source_out_of_range() {
+        let mut config = DaemonConfig::default();
+        config.polling.idle_threshold_by_source.insert("tmux".to_string(), 5);
+        let err = config.validate().unwrap_err();
+        assert!(err.message.contains("idle-threshold-by-source.tmux"));
+    }
+
+    #[test]
+    fn validation_reconcile_every_n_zero() {
+        let mut config = DaemonConfig::default();
+        config.polling.reconcile_every_n = 0;
+        let err = config.validate().unwrap_err();
+        assert!(err.message.contains("reconcile-every-n must be >= 1"));
+    }
+
+    #[test]
+    fn suspicious_config_validates_with_warnings() {
+        let mut config = DaemonConfig::default();
+        config.polling.snapshot_idle_interval_ms = config.polling.snapshot_interval_ms;
+        config.maintenance.max_db_mb = 50_000;
+        let warnings = config.validate().expect("still valid");
+        assert_eq!(warnings.len(), 2, "{warnings:?}");
+        assert!(warnings[0].contains("snapshot-idle-interval-ms equals snapshot-interval-ms"));
+        assert!(warnings[1].contains("max-db-mb is 50000"));
+    }
+
+    #[test]
+    fn clean_config_has_no_validation_warnings() {
+        let warnings = DaemonConfig::default().validate().unwrap();
+        assert!(warnings.is_empty(), "{warnings:?}");
+    }
+
+    #[test]
+    fn quiet_hours_parse_from_toml_and_validate() {
+        let config = DaemonConfig::from_toml_str(
+            r#"
+[polling]
+quiet-hours = "22:00-06:00 +02:00"
+"#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.polling.quiet_hours.as_deref(), Some("22:00-06:00 +02:00"));
+    }
+
+    #[test]
+    fn validation_quiet_hours_invalid() {
+        let mut config = DaemonConfig::default();
+        config.polling.quiet_hours = Some("10pm-6am".to_string());
+        let err = config.validate().unwrap_err();
+        assert!(err.message.starts_with("polling.quiet-hours is invalid"), "{}", err.message);
+    }
+
+    #[test]
+    fn reconcile_every_n_parses_from_toml() {
+        let config = DaemonConfig::from_toml_str(
+            r#"
+[polling]
+reconcile-every-n = 3
+"#,
+        )
+        .unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.polling.reconcile_every_n, 3);
+    }
+
+    #[test]
+    fn session_end_grace_parses_and_is_bounded() {
+        let mut config =
+            DaemonConfig::from_toml_str("[polling]\nsession-end-grace-secs = 0\n").unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.polling.session_end_grace_secs, 0);
+        assert_eq!(DaemonConfig::default().polling.session_end_grace_secs, 10);
+
+        config.polling.session_end_grace_secs = 3601;
+        let err = config.validate().unwrap_err();
+        assert!(err.message.contains("session-end-grace-secs"));
+    }
+
+    #[test]
+    fn session_identity_parses_and_validates() {
+        let mut config = DaemonConfig::from_toml_str(
+            "[polling]\nsession-identity = \"metadata\"\nsession-identity-key = \"Project\"\n",
+        )
+        .unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.polling.session_identity, "metadata");
+        assert_eq!(DaemonConfig::default().polling.session_identity, "name");
+
+        config.polling.session_identity_key = " ".to_string();
+        let err = config.validate().unwrap_err();
+        assert!(err.message.contains("session-identity-key must not be empty"));
+
+        config.polling.session_identity = "uuid".to_string();
+        let err = config.validate().unwrap_err();
+        assert!(err.message.contains("session-identity must be one of"));
+    }
+
+    #[test]
+    fn legacy_snake_case_key_populates_field_with_warning() {
+        let (config, warnings) = DaemonConfig::from_toml_str_with_warnings(
+            r#"
+[polling]
+snapshot_interval_ms = 750
+reconcile-every-n = 2
+
+[polling.idle-threshold-by-source]
+my_source = 42
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.polling.snapshot_interval_ms, 750);
+        assert_eq!(config.polling.reconcile_every_n, 2);
+        assert_eq!(config.polling.idle_threshold_by_source.get("my_source"), Some(&42));
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("polling.snapshot_interval_ms"));
+        assert!(warnings[0].contains("polling.snapshot-interval-ms"));
+    }
+
+    #[test]
+    fn kebab_case_wins_over_legacy_duplicate() {
+        let (config, warnings) = DaemonConfig::from_toml_str_with_warnings(
+            r#"
+[maintenance]
+max_db_mb = 64
+max-db-mb = 128
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.maintenance.max_db_mb, 128);
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("ignored"));
+    }
+
+    #[test]
+    fn kebab_case_config_has_no_migration_warnings() {
+        let (_, warnings) = DaemonConfig::from_toml_str_with_warnings(
+            r#"
+[polling]
+snapshot-interval-ms = 750
+"#,
+        )
+        .unwrap();
+        assert!(warnings.is_empty());
+    }
+
     #[test]
     fn validation_logging_format_invalid() {
         let mut config = DaemonConfig::default();
@@ -779,6 +1718,109 @@ max-db-mb = 256
         config.validate().unwrap();
     }
 
+    #[test]
+    fn stdio_framing_parses_from_toml_and_str() {
+        let config =
+            DaemonConfig::from_toml_str("[transports]\nstdio-framing = \"content-length\"\n").unwrap();
+        assert_eq!(config.transports.stdio_framing, StdioFraming::ContentLength);
+        assert!(DaemonConfig::from_toml_str("[transports]\nstdio-framing = \"lsp\"\n").is_err());
+
+        assert_eq!("newline".parse::<StdioFraming>().unwrap(), StdioFraming::Newline);
+        assert_eq!(" Content-Length ".parse::<StdioFraming>().unwrap(), StdioFraming::ContentLength);
+        assert!("crlf".parse::<StdioFraming>().is_err());
+    }
+
+    #[test]
+    fn transports_section_parses() {
+        let config = DaemonConfig::from_toml_str(
+            "[transports]\nstdio = false\nws = true\nws-port = 4000\nuds = true\nstdio-pretty = true\n",
+        )
+        .unwrap();
+        assert_eq!(config.transports.stdio, Some(false));
+        assert!(config.transports.stdio_pretty);
+        assert!(!DaemonConfig::default().transports.stdio_pretty);
+        assert_eq!(config.transports.stdio_framing, StdioFraming::Newline);
+        assert!(config.transports.ws);
+        assert_eq!(config.transports.ws_port, 4000);
+        assert!(!config.transports.http);
+        let warnings = config.validate().unwrap();
+        assert!(
+            warnings.iter().any(|w| w.contains("transports.uds")),
+            "{warnings:?}"
+        );
+    }
+
+    #[test]
+    fn transports_port_scan_parses_and_validates() {
+        let config = DaemonConfig::from_toml_str("[transports]\nport-scan = 5\n").unwrap();
+        assert_eq!(config.transports.port_scan, 5);
+        config.validate().unwrap();
+
+        let mut config = DaemonConfig::default();
+        assert_eq!(config.transports.port_scan, 0);
+        config.transports.port_scan = 500;
+        let err = config.validate().unwrap_err();
+        assert!(err.message.contains("transports.port-scan"));
+    }
+
+    #[test]
+    fn validation_transports_same_port() {
+        let mut config = DaemonConfig::default();
+        config.transports.ws = true;
+        config.transports.http = true;
+        config.transports.http_port = config.transports.ws_port;
+        let err = config.validate().unwrap_err();
+        assert!(err.message.contains("must differ"));
+    }
+
+    #[test]
+    fn command_section_parses_and_validates() {
+        let config =
+            DaemonConfig::from_toml_str("[command]\nmax-concurrent-processes = 2\n").unwrap();
+        assert_eq!(config.command.max_concurrent_processes, 2);
+        config.validate().unwrap();
+
+        let mut config = DaemonConfig::default();
+        assert_eq!(config.command.max_concurrent_processes, 4);
+        config.command.max_concurrent_processes = 0;
+        let err = config.validate().unwrap_err();
+        assert!(err.message.contains("command.max-concurrent-processes"));
+    }
+
+    #[test]
+    fn notifications_section_parses_and_validates() {
+        let config = DaemonConfig::from_toml_str("[notifications]\ncoalesce-ms = 250\n").unwrap();
+        assert_eq!(config.notifications.coalesce_ms, 250);
+        config.validate().unwrap();
+
+        let mut config = DaemonConfig::default();
+        assert_eq!(config.notifications.coalesce_ms, 1_000);
+        config.notifications.coalesce_ms = 120_000;
+        let err = config.validate().unwrap_err();
+        assert!(err.message.contains("notifications.coalesce-ms"));
+    }
+
+    #[test]
+    fn detector_section_parses_and_validates() {
+        let raw = "[detector]\nrepeat-threshold = 3\nrepeat-window-secs = 60\n\
+                   repeat-severity = \"error\"\nrepeat-escalation = true\n";
+        let config = DaemonConfig::from_toml_str(raw).unwrap();
+        assert_eq!(config.detector.repeat_threshold, 3);
+        assert_eq!(config.detector.repeat_window_secs, 60);
+        assert_eq!(config.detector.repeat_severity, "error");
+        assert!(config.detector.repeat_escalation);
+        config.validate().unwrap();
+
+        let mut config = DaemonConfig::default();
+        assert_eq!(config.detector.repeat_threshold, 0);
+        config.detector.repeat_severity = "loud".to_string();
+        let err = config.validate().unwrap_err();
+        assert!(err.message.contains("detector.repeat-severity"));
+        config.detector.repeat_severity = "warn".to_string();
+        config.detector.repeat_threshold = 1;
+        assert!(config.validate().unwrap_err().message.contains("detector.repeat-threshold"));
+    }
+
     // --- ConfigManager tests ---
 
     #[test]
@@ -796,6 +1838,40 @@ max-db-mb = 256
         assert_eq!(result.server.bind, "127.0.0.1:3847");
     }
 
+    #[test]
+    fn config_manager_survives_poisoned_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("daemon.toml");
+        fs::write(&path, "[transports]\nws-port = 4000\n").unwrap();
+        let mgr = ConfigManager::load_from_fs(Some(path.clone())).unwrap();
+
+        let poisoner = mgr.clone();
+        let _ = std::thread::spawn(move || {
+            let _guard = poisoner.config.write().unwrap();
+            panic!("poison the config lock");
+        })
+        .join();
+        assert!(mgr.config.is_poisoned());
+
+        assert_eq!(mgr.current().transports.ws_port, 4000);
+        fs::write(&path, "[transports]\nws-port = 4100\n").unwrap();
+        assert_eq!(mgr.reload().unwrap().transports.ws_port, 4100);
+        assert_eq!(mgr.current().transports.ws_port, 4100);
+        assert!(!mgr.config.is_poisoned());
+    }
+
+    #[test]
+    fn config_manager_keeps_load_warnings() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("daemon.toml");
+        fs::write(&path, "[maintenance]\nmax_db_mb = 20480\n").unwrap();
+        let mgr = ConfigManager::load_from_fs(Some(path)).unwrap();
+        assert_eq!(mgr.current().maintenance.max_db_mb, 20_480);
+        let warnings = mgr.warnings();
+        assert!(warnings.iter().any(|w| w.contains("max_db_mb is deprecated")), "{warnings:?}");
+        assert!(warnings.iter().any(|w| w.contains("max-db-mb is 20480")), "{warnings:?}");
+    }
+
     #[test]
     fn config_error_display() {
         let err = ConfigError::new("test error message");
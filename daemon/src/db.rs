@@ -1,8 +1,16 @@
+use crate::cache::{Cache, EventRecord};
+use crate::locks;
 use crate::metrics::{Timer, METRICS};
+use crate::models::pane::{Pane, PaneStatus};
+use crate::models::session::{Session, SessionStatus};
 use rusqlite::{Connection, OptionalExtension, Transaction};
 use std::path::Path;
+use std::sync::Mutex;
 
 const SCHEMA_VERSION_KEY: &str = "schema_version";
+const LAST_EVENT_ID_KEY: &str = "last_event_id";
+/// Event ids reserved per `meta` write; see [`EventIdSequence`].
+const EVENT_ID_BLOCK: i64 = 100;
 
 struct Migration {
     version: u32,
@@ -34,6 +42,275 @@ pub fn migrate(conn: &mut Connection) -> rusqlite::Result<u32> {
     Ok(latest_version())
 }
 
+/// Whether an error means another connection held the database past the
+/// busy timeout.
+pub fn is_locked(err: &rusqlite::Error) -> bool {
+    matches!(
+        err.sqlite_error_code(),
+        Some(rusqlite::ErrorCode::DatabaseBusy | rusqlite::ErrorCode::DatabaseLocked)
+    )
+}
+
+/// Prove the database accepts writes: create and fill a scratch table in a
+/// transaction that is rolled back, so nothing is left behind.
+pub fn check_writable(conn: &mut Connection) -> rusqlite::Result<()> {
+    let tx = conn.transaction()?;
+    tx.execute_batch(
+        "CREATE TABLE writable_probe (id INTEGER);
+         INSERT INTO writable_probe (id) VALUES (1);",
+    )?;
+    tx.rollback()
+}
+
+/// Monotonic source of event ids.
+///
+/// When backed by a database, ids are reserved in blocks of
+/// [`EVENT_ID_BLOCK`] and the end of the current block is stored in `meta`,
+/// so ids keep increasing across daemon restarts instead of starting over at
+/// 1 and confusing clients that track `last_event_id`. A restart resumes past
+/// the stored block, skipping whatever of it was unused.
+pub struct EventIdSequence {
+    state: Mutex<SequenceState>,
+}
+
+struct SequenceState {
+    last: i64,
+    /// Highest id covered by the stored reservation.
+    reserved: i64,
+    conn: Option<Connection>,
+}
+
+impl EventIdSequence {
+    /// A sequence that starts at zero and is not persisted.
+    pub fn in_memory() -> Self {
+        Self {
+            state: Mutex::new(SequenceState {
+                last: 0,
+                reserved: 0,
+                conn: None,
+            }),
+        }
+    }
+
+    /// Resume from the last id stored in `conn` (or the highest persisted
+    /// event id, whichever is larger). The connection must be migrated.
+    pub fn load(conn: Connection) -> rusqlite::Result<Self> {
+        let stored: Option<String> = conn
+            .query_row(
+                "SELECT value FROM meta WHERE key = ?1;",
+                [LAST_EVENT_ID_KEY],
+                |row| row.get(0),
+            )
+            .optional()?;
+        let stored = stored.and_then(|raw| raw.parse::<i64>().ok()).unwrap_or(0);
+        let max_event: i64 =
+            conn.query_row("SELECT COALESCE(MAX(id), 0) FROM events;", [], |row| row.get(0))?;
+        let last = stored.max(max_event);
+        Ok(Self {
+            state: Mutex::new(SequenceState {
+                last,
+                reserved: last,
+                conn: Some(conn),
+            }),
+        })
+    }
+
+    /// Allocate the next id.
+    pub fn next(&self) -> i64 {
+        let mut state = self.lock();
+        state.last += 1;
+        state.reserve();
+        state.last
+    }
+
+    /// Advance past an id assigned elsewhere so it is never handed out again.
+    pub fn observe(&self, id: i64) {
+        let mut state = self.lock();
+        if id > state.last {
+            state.last = id;
+            state.reserve();
+        }
+    }
+
+    pub fn last(&self) -> i64 {
+        self.lock().last
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, SequenceState> {
+        locks::lock(&self.state, "event id sequence")
+    }
+}
+
+impl SequenceState {
+    /// Store a new block once `last` runs past the current one; ids within
+    /// a block cost no write.
+    fn reserve(&mut self) {
+        if self.last <= self.reserved {
+            return;
+        }
+        self.reserved = self.last + EVENT_ID_BLOCK - 1;
+        let Some(conn) = &self.conn else {
+            return;
+        };
+        let _timer = Timer::new(&METRICS.db_write);
+        if let Err(err) = conn.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2);",
+            (LAST_EVENT_ID_KEY, self.reserved.to_string()),
+        ) {
+            tracing::warn!(error = %err, last_event_id = self.last, "failed to persist event id");
+        }
+    }
+}
+
+/// Rows loaded by [`warm_cache`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WarmStart {
+    pub sessions: usize,
+    pub panes: usize,
+    pub events: usize,
+}
+
+/// Fill `cache` with the sessions, panes and most recent events stored in
+/// `conn`, so a daemon that does not poll still has something to serve.
+/// At most `max_events` events are loaded, oldest first.
+pub fn warm_cache(
+    conn: &Connection,
+    cache: &Cache,
+    max_events: usize,
+) -> rusqlite::Result<WarmStart> {
+    let mut loaded = WarmStart::default();
+
+    let mut stmt = conn.prepare(
+        "SELECT session_uid, source_id, tmux_session_id, name, created_at, last_seen_at,
+                ended_at, status, status_reason, pane_count, metadata
+         FROM sessions;",
+    )?;
+    let sessions = stmt.query_map([], |row| {
+        let status: String = row.get(7)?;
+        let metadata: Option<String> = row.get(10)?;
+        Ok(Session {
+            session_uid: row.get(0)?,
+            source_id: row.get::<_, String>(1)?.into(),
+            tmux_session_id: row.get(2)?,
+            name: row.get(3)?,
+            raw_name: None,
+            created_at: row.get(4)?,
+            last_seen_at: row.get(5)?,
+            ended_at: row.get(6)?,
+            status: SessionStatus::parse(&status),
+            status_reason: row.get(8)?,
+            pane_count: row.get::<_, Option<u32>>(9)?.unwrap_or(0),
+            metadata: metadata.and_then(|raw| serde_json::from_str(&raw).ok()),
+        })
+    })?;
+    for session in sessions {
+        cache.upsert_session(session?);
+        loaded.sessions += 1;
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT pane_uid, session_uid, tmux_pane_id, tmux_window_id, tmux_pane_pid, pane_index,
+                agent_type, created_at, last_seen_at, last_activity_at, current_command,
+                ended_at, status, status_reason
+         FROM panes;",
+    )?;
+    let panes = stmt.query_map([], |row| {
+        let status: String = row.get(12)?;
+        Ok(Pane {
+            pane_uid: row.get(0)?,
+            session_uid: row.get(1)?,
+            tmux_pane_id: row.get(2)?,
+            tmux_window_id: row.get(3)?,
+            tmux_pane_pid: row.get(4)?,
+            pane_index: row.get(5)?,
+            agent_type: row.get(6)?,
+            created_at: row.get(7)?,
+            last_seen_at: row.get(8)?,
+            last_activity_at: row.get(9)?,
+            current_command: row.get(10)?,
+            ended_at: row.get(11)?,
+            status: PaneStatus::parse(&status),
+            status_reason: row.get(13)?,
+            context: None,
+        })
+    })?;
+    for pane in panes {
+        cache.upsert_pane(pane?);
+        loaded.panes += 1;
+    }
+
+    let mut stmt = conn.prepare(
+        "SELECT id, session_uid, pane_uid, type, detected_at, severity, status
+         FROM (SELECT * FROM events ORDER BY id DESC LIMIT ?1)
+         ORDER BY id ASC;",
+    )?;
+    let events = stmt.query_map([max_events as i64], |row| {
+        Ok(EventRecord {
+            event_id: Some(row.get(0)?),
+            session_uid: row.get(1)?,
+            pane_uid: row.get(2)?,
+            event_type: row.get(3)?,
+            detected_at: row.get(4)?,
+            severity: row.get(5)?,
+            status: row.get(6)?,
+        })
+    })?;
+    for event in events {
+        cache.record_event(event?);
+        loaded.events += 1;
+    }
+
+    Ok(loaded)
+}
+
+/// A full row of the `events` table, including the detail the cache drops.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StoredEvent {
+    pub id: i64,
+    pub session_uid: String,
+    pub pane_uid: String,
+    pub event_type: String,
+    pub detected_at: i64,
+    pub source: String,
+    pub confidence: Option<f64>,
+    pub severity: Option<String>,
+    pub status: Option<String>,
+    pub resolved_at: Option<i64>,
+    pub trigger: Option<String>,
+    pub message: Option<String>,
+    pub context_before: Option<i64>,
+    /// Raw JSON text as stored.
+    pub payload: Option<String>,
+}
+
+pub fn load_event(conn: &Connection, id: i64) -> rusqlite::Result<Option<StoredEvent>> {
+    conn.query_row(
+        "SELECT id, session_uid, pane_uid, type, detected_at, source, confidence, severity,
+                status, resolved_at, trigger, message, context_before, payload
+         FROM events WHERE id = ?1;",
+        [id],
+        |row| {
+            Ok(StoredEvent {
+                id: row.get(0)?,
+                session_uid: row.get(1)?,
+                pane_uid: row.get(2)?,
+                event_type: row.get(3)?,
+                detected_at: row.get(4)?,
+                source: row.get(5)?,
+                confidence: row.get(6)?,
+                severity: row.get(7)?,
+                status: row.get(8)?,
+                resolved_at: row.get(9)?,
+                trigger: row.get(10)?,
+                message: row.get(11)?,
+                context_before: row.get(12)?,
+                payload: row.get(13)?,
+            })
+        },
+    )
+    .optional()
+}
+
 fn latest_version() -> u32 {
     MIGRATIONS
         .last()
@@ -101,6 +378,144 @@ fn write_schema_version(tx: &Transaction<'_>, version: u32) -> rusqlite::Result<
 mod tests {
     use super::*;
 
+    #[test]
+    fn check_writable_leaves_no_probe_table() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        check_writable(&mut conn).unwrap();
+        let tables: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE name = 'writable_probe';",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(tables, 0);
+    }
+
+    #[test]
+    fn is_locked_detects_busy_database() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("lock.db");
+        let holder = Connection::open(&path).unwrap();
+        holder
+            .execute_batch("CREATE TABLE t (x INTEGER); BEGIN EXCLUSIVE; INSERT INTO t VALUES (1);")
+            .unwrap();
+
+        let other = Connection::open(&path).unwrap();
+        other.busy_timeout(std::time::Duration::ZERO).unwrap();
+        let err = other.execute("INSERT INTO t VALUES (2)", []).unwrap_err();
+        assert!(is_locked(&err), "unexpected error: {err:?}");
+        assert!(!is_locked(&rusqlite::Error::QueryReturnedNoRows));
+    }
+
+    #[test]
+    fn event_ids_strictly_increase() {
+        let seq = EventIdSequence::in_memory();
+        let ids: Vec<i64> = (0..5).map(|_| seq.next()).collect();
+        assert_eq!(ids, vec![1, 2, 3, 4, 5]);
+        seq.observe(3);
+        assert_eq!(seq.next(), 6);
+        seq.observe(10);
+        assert_eq!(seq.next(), 11);
+    }
+
+    #[test]
+    fn event_ids_continue_after_restart() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("events.db");
+
+        let seq = EventIdSequence::load(open_database(&path).unwrap()).unwrap();
+        assert_eq!(seq.next(), 1);
+        assert_eq!(seq.next(), 2);
+        assert_eq!(seq.next(), 3);
+        drop(seq);
+
+        // The unused rest of the stored block is skipped
+        let seq = EventIdSequence::load(open_database(&path).unwrap()).unwrap();
+        assert_eq!(seq.last(), EVENT_ID_BLOCK);
+        assert_eq!(seq.next(), EVENT_ID_BLOCK + 1);
+    }
+
+    #[test]
+    fn event_ids_are_persisted_once_per_block() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        let seq = EventIdSequence::load(conn).unwrap();
+        let stored = |seq: &EventIdSequence| -> String {
+            let state = seq.lock();
+            let conn = state.conn.as_ref().unwrap();
+            conn.query_row("SELECT value FROM meta WHERE key = ?1;", [LAST_EVENT_ID_KEY], |row| {
+                row.get(0)
+            })
+            .unwrap()
+        };
+
+        seq.next();
+        assert_eq!(stored(&seq), EVENT_ID_BLOCK.to_string());
+        for _ in 1..EVENT_ID_BLOCK {
+            seq.next();
+        }
+        assert_eq!(stored(&seq), EVENT_ID_BLOCK.to_string(), "still inside the first block");
+        seq.next();
+        assert_eq!(stored(&seq), (2 * EVENT_ID_BLOCK).to_string());
+        seq.observe(5 * EVENT_ID_BLOCK);
+        assert_eq!(stored(&seq), (6 * EVENT_ID_BLOCK - 1).to_string());
+    }
+
+    #[test]
+    fn event_ids_resume_past_persisted_events() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO sources (source_id, kind, distro, created_at, last_seen_at, status)
+             VALUES ('src', 'tmux', 'local', 0, 0, 'ok');
+             INSERT INTO sessions (session_uid, source_id, name, created_at, last_seen_at, status)
+             VALUES ('s', 'src', 'work', 0, 0, 'idle');
+             INSERT INTO panes (pane_uid, session_uid, pane_index, created_at, last_seen_at, status)
+             VALUES ('p', 's', 0, 0, 0, 'idle');
+             INSERT INTO events (id, session_uid, pane_uid, type, detected_at, source)
+             VALUES (41, 's', 'p', 'compact', 1, 'test');",
+        )
+        .unwrap();
+        let seq = EventIdSequence::load(conn).unwrap();
+        assert_eq!(seq.next(), 42);
+    }
+
+    #[test]
+    fn warm_cache_loads_stored_rows() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO sources (source_id, kind, distro, created_at, last_seen_at, status)
+             VALUES ('src', 'tmux', 'local', 0, 0, 'ok');
+             INSERT INTO sessions (session_uid, source_id, name, created_at, last_seen_at, status)
+             VALUES ('s1', 'src', 'work', 10, 20, 'idle');
+             INSERT INTO panes (pane_uid, session_uid, pane_index, created_at, last_seen_at, status)
+             VALUES ('p1', 's1', 0, 10, 20, 'waiting');
+             INSERT INTO events (id, session_uid, pane_uid, type, detected_at, source)
+             VALUES (1, 's1', 'p1', 'compact', 11, 'test'),
+                    (2, 's1', 'p1', 'escalation', 12, 'test'),
+                    (3, 's1', 'p1', 'compact', 13, 'test');",
+        )
+        .unwrap();
+
+        let cache = Cache::new(10);
+        let loaded = warm_cache(&conn, &cache, 2).unwrap();
+
+        let expected = WarmStart {
+            sessions: 1,
+            panes: 1,
+            events: 2,
+        };
+        assert_eq!(loaded, expected);
+        let session = cache.get_session("s1").unwrap();
+        assert_eq!(session.name, "work");
+        assert_eq!(session.status, SessionStatus::Idle);
+        assert_eq!(cache.get_pane("p1").unwrap().status, PaneStatus::Waiting);
+        let ids: Vec<_> = cache.recent_events().iter().map(|e| e.event_id).collect();
+        assert_eq!(ids, vec![Some(2), Some(3)]);
+    }
+
     #[test]
     fn migrations_are_idempotent() {
         let mut conn = Connection::open_in_memory().expect("open in-memory db");
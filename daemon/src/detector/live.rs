@@ -0,0 +1,231 @@
+//! Compact detection on the pane captures the tmux collector publishes.
+//!
+//! Detected events are recorded through [`events::publish`], which applies the
+//! `[detector]` repeat rule and pushes them to connected clients.
+
+use crate::bus::{ClientUpdate, PANE_OUTPUT_UPDATE};
+use crate::cache::{Cache, EventRecord};
+use crate::detector::compact::{CompactDetector, CompactInput};
+use crate::rpc::handlers::events;
+use crate::rpc::{PaneTails, RpcContext};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+/// Per-pane detection state: the last capture, so only appended lines are
+/// scanned, and a debounced compact detector.
+#[derive(Debug, Default)]
+pub struct LiveDetector {
+    captures: PaneTails,
+    compact: HashMap<String, CompactDetector>,
+}
+
+impl LiveDetector {
+    /// Events in the lines a `pane.output` capture appended. The first
+    /// capture of a pane only sets the baseline, so output already on screen
+    /// when the daemon starts is not reported.
+    pub fn detect(&mut self, cache: &Cache, update: &ClientUpdate, now: i64) -> Vec<EventRecord> {
+        if update.kind != PANE_OUTPUT_UPDATE {
+            return Vec::new();
+        }
+        let Some(payload) = update.payload.as_ref() else {
+            return Vec::new();
+        };
+        let (Some(pane_uid), Some(output)) = (
+            payload.get("paneId").and_then(|v| v.as_str()),
+            payload.get("output").and_then(|v| v.as_str()),
+        ) else {
+            return Vec::new();
+        };
+        let Some(pane) = cache.get_pane(pane_uid) else {
+            return Vec::new();
+        };
+        self.captures.start(pane_uid);
+        let lines = self.captures.append(pane_uid, output).unwrap_or_default();
+        let detector = self.compact.entry(pane_uid.to_string()).or_default();
+        lines
+            .iter()
+            .filter_map(|line| {
+                detector.detect(CompactInput {
+                    now,
+                    line,
+                    ntm_compact_count: None,
+                    context_tokens: None,
+                    previous_tokens: None,
+                })
+            })
+            .map(|_| EventRecord {
+                event_id: None,
+                session_uid: pane.session_uid.clone(),
+                pane_uid: pane.pane_uid.clone(),
+                event_type: "compact".to_string(),
+                detected_at: now,
+                severity: Some("info".to_string()),
+                status: None,
+            })
+            .collect()
+    }
+
+    /// Drop state for panes that left the cache.
+    pub fn forget_gone(&mut self, cache: &Cache) {
+        for pane_uid in self.captures.tailed() {
+            if cache.get_pane(&pane_uid).is_none() {
+                self.captures.stop(&pane_uid);
+                self.compact.remove(&pane_uid);
+            }
+        }
+    }
+}
+
+/// Detect events in `update` and record each through [`events::publish`];
+/// returns how many were recorded.
+pub fn record_detected(
+    ctx: &RpcContext,
+    detector: &mut LiveDetector,
+    update: &ClientUpdate,
+    now: i64,
+) -> usize {
+    let detected = detector.detect(&ctx.cache, update, now);
+    let count = detected.len();
+    for record in detected {
+        events::publish(ctx, record);
+    }
+    count
+}
+
+/// Record events detected in captures on `ctx.bus` until shutdown.
+pub async fn run(ctx: Arc<RpcContext>, mut shutdown_rx: broadcast::Receiver<()>) {
+    let mut captures = ctx.bus.subscribe_clients();
+    let mut states = ctx.bus.subscribe_state();
+    let mut detector = LiveDetector::default();
+    loop {
+        tokio::select! {
+            received = captures.recv() => match received {
+                Ok(update) => {
+                    record_detected(&ctx, &mut detector, &update, ctx.clock.now_unix());
+                }
+                // Missed captures only widen the next diff
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            received = states.recv() => match received {
+                Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {
+                    detector.forget_gone(&ctx.cache);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            },
+            _ = shutdown_rx.recv() => break,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigManager;
+    use crate::models::pane::Pane;
+    use crate::rpc::Capabilities;
+    use crate::transport::EventPushFeed;
+    use serde_json::json;
+    use std::time::Duration;
+
+    fn capture(pane_uid: &str, output: &str) -> ClientUpdate {
+        ClientUpdate {
+            kind: PANE_OUTPUT_UPDATE.to_string(),
+            payload: Some(json!({ "paneId": pane_uid, "output": output })),
+        }
+    }
+
+    fn pane(pane_uid: &str) -> Pane {
+        let mut pane = Pane::new("sess".to_string(), 0, 0, None, None, None);
+        pane.pane_uid = pane_uid.to_string();
+        pane
+    }
+
+    #[test]
+    fn only_appended_lines_are_scanned() {
+        let cache = Cache::new(100);
+        cache.upsert_pane(pane("p1"));
+        let mut detector = LiveDetector::default();
+
+        let on_screen = "Conversation compacted\n$ ";
+        assert!(detector.detect(&cache, &capture("p1", on_screen), 100).is_empty(), "baseline");
+        assert!(detector.detect(&cache, &capture("p1", on_screen), 200).is_empty());
+
+        let events = detector.detect(&cache, &capture("p1", "$ \nAuto-compacting conversation\n"), 300);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, "compact");
+        assert_eq!(events[0].session_uid, "sess");
+        assert!(detector.detect(&cache, &capture("unknown", "compacting\n"), 300).is_empty());
+
+        cache.remove_pane("p1");
+        detector.forget_gone(&cache);
+        assert!(detector.captures.tailed().is_empty());
+        assert!(detector.compact.is_empty());
+    }
+
+    #[test]
+    fn repeated_compacts_are_promoted_when_recorded() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("config.toml");
+        std::fs::write(
+            &config_path,
+            "[detector]\nrepeat-threshold = 2\nrepeat-window-secs = 600\nrepeat-escalation = true\n",
+        )
+        .unwrap();
+        let config = ConfigManager::load_from_fs(Some(config_path)).expect("config");
+        let caps = Capabilities { ntm: false, tmux: true, stream: true, systemd: false };
+        let ctx = RpcContext::with_capabilities(Arc::new(Cache::new(100)), config, caps);
+        ctx.cache.upsert_pane(pane("p1"));
+        let mut detector = LiveDetector::default();
+
+        let mut output = String::from("$ claude\n");
+        assert_eq!(record_detected(&ctx, &mut detector, &capture("p1", &output), 100), 0);
+        // Past the compact detector's debounce each time
+        for (i, now) in [200, 300].into_iter().enumerate() {
+            output.push_str(&format!("Conversation compacted ({i})\n"));
+            assert_eq!(record_detected(&ctx, &mut detector, &capture("p1", &output), now), 1);
+        }
+
+        let compacts: Vec<EventRecord> = ctx
+            .cache
+            .recent_events()
+            .into_iter()
+            .filter(|event| event.event_type == "compact")
+            .collect();
+        let severities: Vec<_> = compacts.iter().map(|e| e.severity.as_deref()).collect();
+        assert_eq!(severities, [Some("info"), Some("warn")]);
+        assert_eq!(ctx.cache.event_type_count("escalation"), 1);
+    }
+
+    #[tokio::test]
+    async fn detected_compact_is_pushed_as_events_push() {
+        let caps = Capabilities { ntm: false, tmux: true, stream: true, systemd: false };
+        let ctx = Arc::new(RpcContext::with_capabilities(
+            Arc::new(Cache::new(100)),
+            ConfigManager::default(),
+            caps,
+        ));
+        ctx.cache.upsert_pane(pane("p1"));
+        let mut feed = EventPushFeed::new(&ctx.bus);
+        let (shutdown_tx, shutdown_rx) = broadcast::channel(1);
+        let task = tokio::spawn(run(ctx.clone(), shutdown_rx));
+        tokio::task::yield_now().await;
+
+        // What the tmux collector publishes for each capture
+        let _ = ctx.bus.publish_client_update(capture("p1", "$ claude\n"));
+        let _ = ctx.bus.publish_client_update(capture("p1", "$ claude\nConversation compacted\n"));
+
+        let notification = tokio::time::timeout(Duration::from_secs(5), feed.next())
+            .await
+            .expect("events.push in time")
+            .expect("bus open");
+        assert_eq!(notification.method, "events.push");
+        assert_eq!(notification.params["events"][0]["eventType"], "compact");
+        assert_eq!(notification.params["events"][0]["paneId"], "p1");
+        assert_eq!(ctx.cache.event_type_count("compact"), 1);
+
+        let _ = shutdown_tx.send(());
+        task.await.unwrap();
+    }
+}
@@ -1,5 +1,7 @@
 pub mod agent_type;
 pub mod compact;
 pub mod escalation;
+pub mod live;
 pub mod loader;
+pub mod repeat;
 pub mod status;
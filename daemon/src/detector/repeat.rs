@@ -0,0 +1,135 @@
+//! Severity promotion for events one pane keeps emitting (`[detector]`).
+
+use crate::cache::{Cache, EventRecord};
+use crate::config::{DetectorConfig, SEVERITY_LEVELS};
+
+/// Apply the repeat rule to `event` before it is recorded. When its pane has
+/// emitted `repeat-threshold` events of its type within the window, counting
+/// this one, its severity is raised to `repeat-severity`. Returns the
+/// escalation to record as well, only for the event that reaches the threshold.
+pub fn promote_repeats(
+    cache: &Cache,
+    config: &DetectorConfig,
+    event: &mut EventRecord,
+) -> Option<EventRecord> {
+    // Escalations would otherwise escalate themselves
+    if config.repeat_threshold == 0 || event.event_type == "escalation" {
+        return None;
+    }
+    let since = event.detected_at.saturating_sub(config.repeat_window_secs);
+    let recorded =
+        cache.pane_event_count(&event.session_uid, &event.pane_uid, &event.event_type, since);
+    let count = recorded + 1;
+    if count < config.repeat_threshold {
+        return None;
+    }
+
+    if rank(event.severity.as_deref()) < rank(Some(config.repeat_severity.as_str())) {
+        event.severity = Some(config.repeat_severity.clone());
+    }
+    (config.repeat_escalation && count == config.repeat_threshold).then(|| EventRecord {
+        event_id: None,
+        session_uid: event.session_uid.clone(),
+        pane_uid: event.pane_uid.clone(),
+        event_type: "escalation".to_string(),
+        detected_at: event.detected_at,
+        severity: Some(config.repeat_severity.clone()),
+        status: Some("pending".to_string()),
+    })
+}
+
+/// Position in [`SEVERITY_LEVELS`]; none sorts lowest and unknown severities
+/// highest, so they are never overwritten.
+fn rank(severity: Option<&str>) -> usize {
+    match severity {
+        None => 0,
+        Some(severity) => SEVERITY_LEVELS
+            .iter()
+            .position(|level| *level == severity)
+            .map_or(usize::MAX, |index| index + 1),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(threshold: usize, escalation: bool) -> DetectorConfig {
+        DetectorConfig {
+            repeat_threshold: threshold,
+            repeat_window_secs: 60,
+            repeat_severity: "warn".to_string(),
+            repeat_escalation: escalation,
+        }
+    }
+
+    fn compact(pane_uid: &str, detected_at: i64) -> EventRecord {
+        EventRecord {
+            event_id: None,
+            session_uid: "sess".to_string(),
+            pane_uid: pane_uid.to_string(),
+            event_type: "compact".to_string(),
+            detected_at,
+            severity: Some("info".to_string()),
+            status: None,
+        }
+    }
+
+    /// Run each event through the rule and record it, as `events::publish` does.
+    fn record(cache: &Cache, config: &DetectorConfig, mut event: EventRecord) -> EventRecord {
+        if let Some(escalation) = promote_repeats(cache, config, &mut event) {
+            cache.record_event(escalation);
+        }
+        cache.record_event(event.clone());
+        event
+    }
+
+    #[test]
+    fn threshold_within_window_promotes_and_escalates_once() {
+        let cache = Cache::new(100);
+        let config = rule(3, true);
+        assert_eq!(record(&cache, &config, compact("p1", 100)).severity.as_deref(), Some("info"));
+        assert_eq!(record(&cache, &config, compact("p1", 110)).severity.as_deref(), Some("info"));
+        assert_eq!(cache.event_type_count("escalation"), 0);
+
+        assert_eq!(record(&cache, &config, compact("p1", 120)).severity.as_deref(), Some("warn"));
+        assert_eq!(cache.event_type_count("escalation"), 1);
+        assert_eq!(record(&cache, &config, compact("p1", 130)).severity.as_deref(), Some("warn"));
+        assert_eq!(cache.event_type_count("escalation"), 1, "one escalation per crossing");
+    }
+
+    #[test]
+    fn fewer_occurrences_do_not_promote() {
+        let cache = Cache::new(100);
+        let config = rule(3, true);
+        record(&cache, &config, compact("p1", 0));
+        // Outside the 60s window by the time the third arrives
+        record(&cache, &config, compact("p1", 50));
+        assert_eq!(record(&cache, &config, compact("p1", 100)).severity.as_deref(), Some("info"));
+        // Other panes and event types count separately
+        record(&cache, &config, compact("p2", 100));
+        let mut idle = compact("p1", 100);
+        idle.event_type = "idle".to_string();
+        assert_eq!(record(&cache, &config, idle).severity.as_deref(), Some("info"));
+        assert_eq!(cache.event_type_count("escalation"), 0);
+
+        let off = rule(0, true);
+        assert_eq!(record(&cache, &off, compact("p1", 101)).severity.as_deref(), Some("info"));
+    }
+
+    #[test]
+    fn promotion_never_lowers_severity() {
+        let cache = Cache::new(100);
+        let config = rule(2, false);
+        record(&cache, &config, compact("p1", 0));
+        let mut event = compact("p1", 1);
+        event.severity = Some("error".to_string());
+        assert!(promote_repeats(&cache, &config, &mut event).is_none());
+        assert_eq!(event.severity.as_deref(), Some("error"));
+
+        let mut unset = compact("p1", 1);
+        unset.severity = None;
+        promote_repeats(&cache, &config, &mut unset);
+        assert_eq!(unset.severity.as_deref(), Some("warn"));
+    }
+}
@@ -0,0 +1,190 @@
+//! Session grouping by project, so clients can render sessions as a tree.
+
+use crate::config::GroupingConfig;
+use crate::models::session::Session;
+use serde_json::Value;
+
+/// ntm metadata columns that may hold a session's working directory.
+const CWD_KEYS: [&str; 5] = ["cwd", "dir", "directory", "workdir", "path"];
+
+/// Compute the group for `session` under the configured rule.
+///
+/// `pane_cwds` are the working directories captured for the session's panes
+/// (`capture.capture-cwd`), in pane order; only the `cwd` rule reads them.
+/// Sessions the rule cannot place (no separator in the name, missing
+/// metadata or cwd) form a group of their own, named after the session.
+pub fn session_group(session: &Session, pane_cwds: &[&str], config: &GroupingConfig) -> String {
+    let group = match config.rule.as_str() {
+        "metadata" => metadata_value(session, &[config.metadata_key.as_str()]),
+        "cwd" => session_cwd(pane_cwds)
+            .or_else(|| metadata_value(session, &CWD_KEYS))
+            .and_then(|cwd| cwd_prefix(&cwd, config.cwd_depth)),
+        _ => name_prefix(&session.name, &config.separator),
+    };
+    group.unwrap_or_else(|| session.name.clone())
+}
+
+/// The directory the session's panes share. Panes with nothing in common
+/// but `/` fall back to the first pane's directory.
+fn session_cwd(pane_cwds: &[&str]) -> Option<String> {
+    let (first, rest) = pane_cwds.split_first()?;
+    let mut common: Vec<&str> = path_components(first).collect();
+    for cwd in rest {
+        let shared = common
+            .iter()
+            .zip(path_components(cwd))
+            .take_while(|(a, b)| *a == b)
+            .count();
+        common.truncate(shared);
+    }
+    if common.is_empty() {
+        return Some(first.to_string());
+    }
+    Some(format!("/{}", common.join("/")))
+}
+
+/// `cwd` without a trailing slash, cut to its first `depth` components
+/// (all of them when `depth` is 0).
+fn cwd_prefix(cwd: &str, depth: usize) -> Option<String> {
+    let absolute = cwd.starts_with('/');
+    let mut components: Vec<&str> = path_components(cwd).collect();
+    if depth > 0 {
+        components.truncate(depth);
+    }
+    if components.is_empty() {
+        return None;
+    }
+    let joined = components.join("/");
+    Some(if absolute { format!("/{joined}") } else { joined })
+}
+
+fn path_components(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|part| !part.is_empty())
+}
+
+fn name_prefix(name: &str, separator: &str) -> Option<String> {
+    if separator.is_empty() {
+        return None;
+    }
+    name.split_once(separator)
+        .map(|(prefix, _)| prefix)
+        .filter(|prefix| !prefix.is_empty())
+        .map(str::to_string)
+}
+
+fn metadata_value(session: &Session, keys: &[&str]) -> Option<String> {
+    let metadata = session.metadata.as_ref()?.as_object()?;
+    keys.iter().find_map(|key| {
+        metadata
+            .get(&key.to_lowercase())
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn session(name: &str, metadata: Option<Value>) -> Session {
+        let mut session = Session::new("ntm", name, None, 1);
+        session.metadata = metadata;
+        session
+    }
+
+    fn config(rule: &str) -> GroupingConfig {
+        GroupingConfig {
+            rule: rule.to_string(),
+            ..GroupingConfig::default()
+        }
+    }
+
+    #[test]
+    fn shared_name_prefix_shares_group() {
+        let config = config("name-prefix");
+        let a = session_group(&session("tracker-api", None), &[], &config);
+        let b = session_group(&session("tracker-tui", None), &[], &config);
+        let c = session_group(&session("website-dev", None), &[], &config);
+        let d = session_group(&session("scratch", None), &[], &config);
+        assert_eq!(a, "tracker");
+        assert_eq!(a, b);
+        assert_eq!(c, "website");
+        assert_eq!(d, "scratch", "no separator: own group");
+    }
+
+    #[test]
+    fn leading_separator_falls_back_to_name() {
+        let config = config("name-prefix");
+        assert_eq!(session_group(&session("-odd", None), &[], &config), "-odd");
+    }
+
+    #[test]
+    fn metadata_rule_uses_configured_key() {
+        let config = GroupingConfig {
+            rule: "metadata".to_string(),
+            metadata_key: "Project".to_string(),
+            ..GroupingConfig::default()
+        };
+        let a = session(
+            "one",
+            Some(json!({"project": "ntm-tracker"})),
+        );
+        let b = session("two", Some(json!({"project": "ntm-tracker"})));
+        let c = session("three", Some(json!({"project": "other"})));
+        let d = session("four", None);
+        assert_eq!(session_group(&a, &[], &config), "ntm-tracker");
+        assert_eq!(session_group(&b, &[], &config), "ntm-tracker");
+        assert_eq!(session_group(&c, &[], &config), "other");
+        assert_eq!(session_group(&d, &[], &config), "four");
+    }
+
+    #[test]
+    fn cwd_rule_normalizes_trailing_slash() {
+        let config = config("cwd");
+        let a = session("a", Some(json!({"cwd": "/src/tracker/"})));
+        let b = session("b", Some(json!({"workdir": "/src/tracker"})));
+        assert_eq!(session_group(&a, &[], &config), "/src/tracker");
+        assert_eq!(session_group(&b, &[], &config), "/src/tracker");
+    }
+
+    #[test]
+    fn cwd_rule_groups_sessions_sharing_a_captured_prefix() {
+        let depth_two = GroupingConfig {
+            cwd_depth: 2,
+            ..config("cwd")
+        };
+        let api = session_group(&session("api", None), &["/src/tracker/daemon"], &depth_two);
+        let tui = session_group(
+            &session("tui", None),
+            &["/src/tracker/tui", "/src/tracker/tui/src"],
+            &depth_two,
+        );
+        let site = session_group(&session("site", None), &["/src/website"], &depth_two);
+        assert_eq!(api, "/src/tracker");
+        assert_eq!(api, tui);
+        assert_eq!(site, "/src/website");
+
+        // Without a depth the whole shared directory is the group
+        let whole = config("cwd");
+        let tui = session_group(
+            &session("tui", None),
+            &["/src/tracker/tui/", "/src/tracker/tui/src"],
+            &whole,
+        );
+        assert_eq!(tui, "/src/tracker/tui");
+    }
+
+    #[test]
+    fn cwd_rule_prefers_captured_cwd_and_falls_back() {
+        let config = config("cwd");
+        let s = session("s", Some(json!({"cwd": "/from/metadata"})));
+        assert_eq!(session_group(&s, &["/captured"], &config), "/captured");
+        assert_eq!(session_group(&s, &[], &config), "/from/metadata");
+        assert_eq!(session_group(&session("bare", None), &[], &config), "bare");
+        // Panes sharing only `/` use the first pane's directory
+        assert_eq!(session_group(&session("x", None), &["/a/b", "/c"], &config), "/a/b");
+    }
+}
@@ -0,0 +1,79 @@
+//! Id generation behind a trait so tests can get predictable ids.
+//!
+//! Production code uses [`UuidV7Ids`]; tests pass a [`SequentialIds`] and
+//! assert on exact uids.
+
+use std::fmt::Debug;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Source of fresh unique ids (session and pane uids, instance and run ids).
+pub trait IdGenerator: Debug + Send + Sync {
+    fn next_id(&self) -> String;
+}
+
+/// Shared handle to an id generator.
+pub type SharedIds = Arc<dyn IdGenerator>;
+
+/// Time-ordered v7 uuids.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct UuidV7Ids;
+
+impl IdGenerator for UuidV7Ids {
+    fn next_id(&self) -> String {
+        Uuid::now_v7().to_string()
+    }
+}
+
+/// The default id generator for production code.
+pub fn uuid_v7_ids() -> SharedIds {
+    Arc::new(UuidV7Ids)
+}
+
+/// Numbered ids (`id-1`, `id-2`, ...) for tests.
+#[derive(Debug)]
+pub struct SequentialIds {
+    prefix: String,
+    next: AtomicU64,
+}
+
+impl SequentialIds {
+    pub fn new(prefix: impl Into<String>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            next: AtomicU64::new(1),
+        }
+    }
+}
+
+impl Default for SequentialIds {
+    fn default() -> Self {
+        Self::new("id")
+    }
+}
+
+impl IdGenerator for SequentialIds {
+    fn next_id(&self) -> String {
+        let n = self.next.fetch_add(1, Ordering::SeqCst);
+        format!("{}-{n}", self.prefix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sequential_ids_count_up() {
+        let ids = SequentialIds::new("pane");
+        assert_eq!(ids.next_id(), "pane-1");
+        assert_eq!(ids.next_id(), "pane-2");
+    }
+
+    #[test]
+    fn uuid_v7_ids_are_unique() {
+        let ids = UuidV7Ids;
+        assert_ne!(ids.next_id(), ids.next_id());
+    }
+}
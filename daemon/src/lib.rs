@@ -3,22 +3,28 @@ pub const APP_NAME: &str = "ntm-tracker-daemon";
 pub mod bus;
 pub mod cache;
 pub mod cli;
+pub mod clock;
 pub mod collector;
 pub mod command;
 pub mod config;
 pub mod db;
 pub mod detector;
+pub mod grouping;
+pub mod ids;
+pub mod locks;
 pub mod logging;
 pub mod maintenance;
 pub mod metrics;
 pub mod models;
 pub mod ntm;
 pub mod parsers;
+pub mod polling;
 pub mod reconcile;
 pub mod redaction;
 pub mod rpc;
 pub mod service;
 pub mod state;
+pub mod systemd;
 pub mod token_estimator;
 pub mod transport;
 
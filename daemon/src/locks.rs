@@ -0,0 +1,84 @@
+//! Poison-tolerant `RwLock` and `Mutex` access for long-lived shared state.
+//!
+//! A panic while a lock is held poisons it, and every later `expect` would
+//! panic again. Config is swapped in whole and cached state is rebuilt by
+//! the next poll, so carrying on with the data is better than taking the
+//! daemon down. The first access after a poisoning logs a warning and clears
+//! the flag, so each poisoning is reported once.
+
+use std::sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// Read `lock`, recovering it if a writer panicked. `name` identifies the
+/// lock in the warning.
+pub fn read<'a, T>(lock: &'a RwLock<T>, name: &str) -> RwLockReadGuard<'a, T> {
+    lock.read().unwrap_or_else(|poisoned| {
+        recovered(lock, name);
+        poisoned.into_inner()
+    })
+}
+
+/// Write `lock`, recovering it if a holder panicked.
+pub fn write<'a, T>(lock: &'a RwLock<T>, name: &str) -> RwLockWriteGuard<'a, T> {
+    lock.write().unwrap_or_else(|poisoned| {
+        recovered(lock, name);
+        poisoned.into_inner()
+    })
+}
+
+/// Lock `mutex`, recovering it if a holder panicked.
+pub fn lock<'a, T>(mutex: &'a Mutex<T>, name: &str) -> MutexGuard<'a, T> {
+    mutex.lock().unwrap_or_else(|poisoned| {
+        mutex.clear_poison();
+        warn_recovered(name);
+        poisoned.into_inner()
+    })
+}
+
+fn recovered<T>(lock: &RwLock<T>, name: &str) {
+    lock.clear_poison();
+    warn_recovered(name);
+}
+
+fn warn_recovered(name: &str) {
+    tracing::warn!(lock = name, "recovered a lock poisoned by a panicking thread");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn recovers_and_clears_poison() {
+        let lock = Arc::new(RwLock::new(1));
+        let poisoner = lock.clone();
+        let _ = std::thread::spawn(move || {
+            let mut guard = poisoner.write().unwrap();
+            *guard = 2;
+            panic!("poison the lock");
+        })
+        .join();
+        assert!(lock.is_poisoned());
+
+        assert_eq!(*read(&lock, "test"), 2);
+        assert!(!lock.is_poisoned());
+        *write(&lock, "test") = 3;
+        assert_eq!(*lock.read().unwrap(), 3);
+    }
+
+    #[test]
+    fn recovers_poisoned_mutex() {
+        let mutex = Arc::new(Mutex::new(1));
+        let poisoner = mutex.clone();
+        let _ = std::thread::spawn(move || {
+            let _guard = poisoner.lock().unwrap();
+            panic!("poison the mutex");
+        })
+        .join();
+        assert!(mutex.is_poisoned());
+
+        *lock(&mutex, "test") += 1;
+        assert!(!mutex.is_poisoned());
+        assert_eq!(*mutex.lock().unwrap(), 2);
+    }
+}
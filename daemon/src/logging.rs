@@ -262,6 +262,197 @@ impl Write for RotatingFileWriter {
     }
 }
 
+/// A log file (current or rotated) found next to the configured log path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogFileInfo {
+    pub name: String,
+    pub size_bytes: u64,
+    pub modified_at: Option<i64>,
+}
+
+/// Whether `name` is the configured log file or one of its rotations
+/// (`<name>.1`, `<name>.2`, ...).
+fn is_log_file_name(base_name: &str, name: &str) -> bool {
+    if name == base_name {
+        return true;
+    }
+    name.strip_prefix(base_name)
+        .and_then(|rest| rest.strip_prefix('.'))
+        .is_some_and(|index| !index.is_empty() && index.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// List the current log file and its rotations, newest first.
+pub fn list_log_files(base_path: &Path) -> io::Result<Vec<LogFileInfo>> {
+    let Some(base_name) = base_path.file_name().map(|n| n.to_string_lossy().into_owned()) else {
+        return Ok(Vec::new());
+    };
+    let dir = base_path.parent().unwrap_or_else(|| Path::new("."));
+
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if !is_log_file_name(&base_name, &name) {
+            continue;
+        }
+        let metadata = entry.metadata()?;
+        if !metadata.is_file() {
+            continue;
+        }
+        let modified_at = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64);
+        files.push(LogFileInfo {
+            name,
+            size_bytes: metadata.len(),
+            modified_at,
+        });
+    }
+
+    files.sort_by_key(|file| rotation_index(&base_name, &file.name));
+    Ok(files)
+}
+
+fn rotation_index(base_name: &str, name: &str) -> u64 {
+    name.strip_prefix(base_name)
+        .and_then(|rest| rest.strip_prefix('.'))
+        .and_then(|index| index.parse().ok())
+        .unwrap_or(0)
+}
+
+/// Why a requested log file name could not be resolved.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogFileError {
+    /// Not the log file or one of its rotations, or outside the log directory.
+    InvalidName,
+    /// A valid name that does not exist on disk.
+    NotFound,
+}
+
+/// Resolve a log file name requested by a client to a path inside the log
+/// directory.
+///
+/// Only bare names of the configured log file or its rotations are accepted;
+/// anything containing path separators or resolving outside the directory
+/// (e.g. via symlinks) is rejected.
+pub fn resolve_log_file(base_path: &Path, name: &str) -> Result<PathBuf, LogFileError> {
+    let base_name = base_path
+        .file_name()
+        .ok_or(LogFileError::InvalidName)?
+        .to_string_lossy()
+        .into_owned();
+    if name.contains(['/', '\\']) || !is_log_file_name(&base_name, name) {
+        return Err(LogFileError::InvalidName);
+    }
+
+    let dir = base_path.parent().unwrap_or_else(|| Path::new("."));
+    let dir = std::fs::canonicalize(dir).map_err(|_| LogFileError::NotFound)?;
+    let path = std::fs::canonicalize(dir.join(name)).map_err(|_| LogFileError::NotFound)?;
+    if path.parent() != Some(dir.as_path()) {
+        return Err(LogFileError::InvalidName);
+    }
+    Ok(path)
+}
+
+/// Read up to `limit` bytes of `path` starting at `offset`.
+///
+/// Returns the bytes read and the total file size.
+pub fn read_log_slice(path: &Path, offset: u64, limit: usize) -> io::Result<(Vec<u8>, u64)> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = std::fs::File::open(path)?;
+    let size = file.metadata()?.len();
+    if offset >= size {
+        return Ok((Vec::new(), size));
+    }
+    file.seek(SeekFrom::Start(offset))?;
+    let mut buf = Vec::with_capacity(limit.min((size - offset) as usize));
+    file.take(limit as u64).read_to_end(&mut buf)?;
+    Ok((buf, size))
+}
+
+/// The last lines of a log file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogTail {
+    pub lines: Vec<String>,
+    /// True when older content exists before the returned lines.
+    pub truncated: bool,
+    pub size_bytes: u64,
+}
+
+/// Read the last `max_lines` lines of `path`, looking at no more than the
+/// final `max_bytes` bytes of the file.
+///
+/// The read window may start or end inside a multibyte character; those
+/// partial sequences are dropped rather than decoded as garbage, and a
+/// partial first line is discarded when the window does not start at the
+/// beginning of the file.
+pub fn tail_log(path: &Path, max_lines: usize, max_bytes: u64) -> io::Result<LogTail> {
+    use std::io::{Read, Seek, SeekFrom};
+
+    let mut file = std::fs::File::open(path)?;
+    let size = file.metadata()?.len();
+    let start = size.saturating_sub(max_bytes);
+    file.seek(SeekFrom::Start(start))?;
+    let mut buf = Vec::with_capacity((size - start) as usize);
+    file.take(size - start).read_to_end(&mut buf)?;
+
+    let mut window = trim_utf8_boundaries(&buf);
+    let mut truncated = start > 0;
+    if truncated {
+        if let Some(newline) = window.iter().position(|&b| b == b'\n') {
+            window = &window[newline + 1..];
+        }
+    }
+
+    let text = String::from_utf8_lossy(window);
+    let mut lines: Vec<String> = text.lines().map(str::to_string).collect();
+    if lines.len() > max_lines {
+        lines.drain(..lines.len() - max_lines);
+        truncated = true;
+    }
+
+    Ok(LogTail {
+        lines,
+        truncated,
+        size_bytes: size,
+    })
+}
+
+/// Drop continuation bytes at the start and an incomplete sequence at the
+/// end of a byte window cut out of a UTF-8 stream.
+fn trim_utf8_boundaries(bytes: &[u8]) -> &[u8] {
+    let leading = bytes
+        .iter()
+        .take(3)
+        .take_while(|&&b| b & 0b1100_0000 == 0b1000_0000)
+        .count();
+    let bytes = &bytes[leading..];
+
+    // Only the last 3 bytes can hold an incomplete trailing sequence.
+    let tail_start = bytes.len().saturating_sub(3);
+    for idx in (tail_start..bytes.len()).rev() {
+        let b = bytes[idx];
+        if b & 0b1100_0000 == 0b1000_0000 {
+            continue;
+        }
+        let width = match b {
+            0x00..=0x7F => 1,
+            0xC0..=0xDF => 2,
+            0xE0..=0xEF => 3,
+            0xF0..=0xF7 => 4,
+            _ => 1,
+        };
+        if idx + width > bytes.len() {
+            return &bytes[..idx];
+        }
+        break;
+    }
+    bytes
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -287,16 +478,8 @@ mod tests {
 
     #[test]
     fn rotating_writer_rotates_by_size() {
-        let dir = std::env::temp_dir().join(format!(
-            "ntm-tracker-logging-test-{}-{}",
-            std::process::id(),
-            std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_nanos()
-        ));
-        std::fs::create_dir_all(&dir).expect("create temp log dir");
-        let path = dir.join("daemon.log");
+        let dir = tempfile::tempdir().expect("create temp log dir");
+        let path = dir.path().join("daemon.log");
 
         let config = LoggingConfig {
             file: Some(path.clone()),
@@ -315,4 +498,116 @@ mod tests {
         assert!(path.exists(), "current log exists");
         assert!(writer.rotated_path(1).exists(), "rotated log exists");
     }
+
+    #[test]
+    fn log_file_names_match_rotations_only() {
+        assert!(is_log_file_name("daemon.log", "daemon.log"));
+        assert!(is_log_file_name("daemon.log", "daemon.log.3"));
+        assert!(!is_log_file_name("daemon.log", "daemon.log."));
+        assert!(!is_log_file_name("daemon.log", "daemon.log.old"));
+        assert!(!is_log_file_name("daemon.log", "other.log"));
+    }
+
+    #[test]
+    fn list_and_resolve_log_files() {
+        let dir = tempfile::TempDir::new().expect("temp dir");
+        let path = dir.path().join("daemon.log");
+        std::fs::write(&path, "current\n").unwrap();
+        std::fs::write(dir.path().join("daemon.log.2"), "older\n").unwrap();
+        std::fs::write(dir.path().join("daemon.log.1"), "old\n").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "ignored").unwrap();
+
+        let names: Vec<String> = list_log_files(&path)
+            .unwrap()
+            .into_iter()
+            .map(|f| f.name)
+            .collect();
+        assert_eq!(names, vec!["daemon.log", "daemon.log.1", "daemon.log.2"]);
+
+        assert!(resolve_log_file(&path, "daemon.log.1").is_ok());
+        assert_eq!(
+            resolve_log_file(&path, "notes.txt"),
+            Err(LogFileError::InvalidName)
+        );
+        assert_eq!(
+            resolve_log_file(&path, "../daemon.log"),
+            Err(LogFileError::InvalidName)
+        );
+        assert_eq!(
+            resolve_log_file(&path, "daemon.log.9"),
+            Err(LogFileError::NotFound)
+        );
+    }
+
+    #[test]
+    fn read_log_slice_respects_offset_and_limit() {
+        let dir = tempfile::TempDir::new().expect("temp dir");
+        let path = dir.path().join("daemon.log");
+        std::fs::write(&path, "0123456789").unwrap();
+
+        let (bytes, size) = read_log_slice(&path, 3, 4).unwrap();
+        assert_eq!(bytes, b"3456");
+        assert_eq!(size, 10);
+
+        let (bytes, _) = read_log_slice(&path, 20, 4).unwrap();
+        assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn tail_log_reads_only_the_end_of_large_files() {
+        let dir = tempfile::TempDir::new().expect("temp dir");
+        let path = dir.path().join("daemon.log");
+        let mut contents = String::new();
+        for i in 0..100_000 {
+            contents.push_str(&format!("line {i:06} padding padding\n"));
+        }
+        assert!(contents.len() > 2 * 1024 * 1024);
+        std::fs::write(&path, &contents).unwrap();
+
+        let tail = tail_log(&path, 3, 4096).unwrap();
+        assert_eq!(
+            tail.lines,
+            vec![
+                "line 099997 padding padding",
+                "line 099998 padding padding",
+                "line 099999 padding padding",
+            ]
+        );
+        assert!(tail.truncated);
+        assert_eq!(tail.size_bytes, contents.len() as u64);
+
+        // A window smaller than the file never returns its start.
+        let tail = tail_log(&path, usize::MAX, 4096).unwrap();
+        assert!(tail.lines.len() < 200);
+        assert!(tail.lines.iter().all(|line| line.starts_with("line 09")));
+    }
+
+    #[test]
+    fn tail_log_handles_multibyte_boundary() {
+        let dir = tempfile::TempDir::new().expect("temp dir");
+        let path = dir.path().join("daemon.log");
+        // "é" is two bytes; "✓" is three. A 5-byte window starts inside "✓".
+        std::fs::write(&path, "first\nzé✓\nok\n").unwrap();
+
+        for max_bytes in 1..=16 {
+            let tail = tail_log(&path, 10, max_bytes).unwrap();
+            for line in &tail.lines {
+                assert!(!line.contains('\u{FFFD}'), "garbled line {line:?} at {max_bytes}");
+            }
+        }
+
+        let tail = tail_log(&path, 10, 8).unwrap();
+        assert_eq!(tail.lines, vec!["ok"]);
+        let tail = tail_log(&path, 10, 64).unwrap();
+        assert_eq!(tail.lines, vec!["first", "zé✓", "ok"]);
+        assert!(!tail.truncated);
+    }
+
+    #[test]
+    fn trim_utf8_boundaries_drops_partial_sequences() {
+        let text = "a✓b✓".as_bytes();
+        assert_eq!(trim_utf8_boundaries(&text[2..]), "b✓".as_bytes());
+        assert_eq!(trim_utf8_boundaries(&text[..text.len() - 1]), "a✓b".as_bytes());
+        assert_eq!(trim_utf8_boundaries(text), text);
+    }
 }
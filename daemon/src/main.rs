@@ -1,18 +1,25 @@
 use clap::{Parser, Subcommand};
-use ntm_tracker_daemon::bus::EventBus;
-use ntm_tracker_daemon::cache::{Cache, PollingDatum};
-use ntm_tracker_daemon::cli::{self, OutputFormat, DEFAULT_PORT};
+use ntm_tracker_daemon::cache::{Cache, PollChannel, PollingDatum};
+use ntm_tracker_daemon::cli::{self, exit_code, OutputFormat};
 use ntm_tracker_daemon::collector::ntm::{NtmCollector, NtmCollectorConfig};
+use ntm_tracker_daemon::collector::{CollectorKind, MissingBinaryGuard};
 use ntm_tracker_daemon::collector::tmux::{TmuxCollector, TmuxCollectorConfig};
-use ntm_tracker_daemon::command::{CommandConfig, CommandRunner};
-use ntm_tracker_daemon::config::{ConfigManager, PollingConfig};
+use ntm_tracker_daemon::config::{ConfigManager, StdioFraming};
+use ntm_tracker_daemon::db;
 use ntm_tracker_daemon::logging;
 use ntm_tracker_daemon::maintenance;
+use ntm_tracker_daemon::metrics::{PollOutcome, METRICS};
 use ntm_tracker_daemon::ntm::{NtmClient, NtmConfig};
+use ntm_tracker_daemon::polling::compute_polling_decision;
+use ntm_tracker_daemon::reconcile::SessionIdentity;
 use ntm_tracker_daemon::rpc::handlers;
 use ntm_tracker_daemon::rpc::RpcContext;
-use ntm_tracker_daemon::service::{InstanceGuard, ShutdownHandler};
-use ntm_tracker_daemon::transport;
+use ntm_tracker_daemon::service::{
+    log_shutdown_complete, CollectorTasks, InstanceGuard, InstanceMetadata, ShutdownHandler,
+    ShutdownOutcome, StartupPlan,
+};
+use ntm_tracker_daemon::systemd;
+use ntm_tracker_daemon::transport::{self, TransportOverrides, TransportSelection};
 use std::sync::Arc;
 use tokio::sync::mpsc;
 
@@ -31,14 +38,23 @@ struct Args {
     #[arg(long, global = true)]
     config: Option<std::path::PathBuf>,
 
-    /// Port to connect to for client commands.
-    #[arg(long, global = true, default_value_t = DEFAULT_PORT)]
-    port: u16,
+    /// Port to connect to for client commands. Defaults to the running
+    /// daemon's HTTP port from its instance metadata, else 3847.
+    #[arg(long, global = true)]
+    port: Option<u16>,
 
     /// Admin token for privileged operations.
     #[arg(long, global = true)]
     admin_token: Option<String>,
 
+    /// How long client commands wait for the daemon, in milliseconds.
+    #[arg(long, global = true, default_value_t = cli::DEFAULT_TIMEOUT_MS)]
+    timeout_ms: u64,
+
+    /// Connection attempts to retry while the daemon is not listening yet.
+    #[arg(long, global = true, default_value_t = cli::DEFAULT_CONNECT_RETRIES)]
+    connect_retries: u32,
+
     #[command(subcommand)]
     command: Option<Command>,
 }
@@ -55,31 +71,85 @@ enum Command {
         #[arg(long)]
         log_format: Option<String>,
 
-        /// Use stdio transport (newline-delimited JSON-RPC over stdin/stdout).
-        #[arg(long)]
+        /// Use stdio transport (JSON-RPC over stdin/stdout).
+        #[arg(long, conflicts_with = "no_stdio")]
         stdio: bool,
 
-        /// Start WebSocket server on specified port.
+        /// Disable the stdio transport even if config enables it.
         #[arg(long)]
+        no_stdio: bool,
+
+        /// Start WebSocket server on specified port.
+        #[arg(long, conflicts_with = "no_ws")]
         ws_port: Option<u16>,
 
-        /// Start HTTP server on specified port.
+        /// Disable the WebSocket server even if config enables it.
         #[arg(long)]
+        no_ws: bool,
+
+        /// Start HTTP server on specified port.
+        #[arg(long, conflicts_with = "no_http")]
         http_port: Option<u16>,
 
+        /// Disable the HTTP server even if config enables it.
+        #[arg(long)]
+        no_http: bool,
+
+        /// Try up to this many following ports when a ws/http port is busy.
+        /// Overrides `transports.port-scan`.
+        #[arg(long)]
+        port_scan: Option<u16>,
+
+        /// Pretty-print stdio responses and notifications for manual
+        /// debugging. Overrides `transports.stdio-pretty`.
+        #[arg(long)]
+        pretty: bool,
+
+        /// stdio framing: "newline" (one message per line) or
+        /// "content-length" (LSP-style headers). Overrides
+        /// `transports.stdio-framing`.
+        #[arg(long)]
+        framing: Option<StdioFraming>,
+
         /// Allow multiple daemon instances (for testing).
         #[arg(long)]
         no_single_instance: bool,
+
+        /// Serve cached/db data read-only: no collectors or maintenance run
+        /// and write methods are rejected.
+        #[arg(long)]
+        safe_mode: bool,
     },
 
-    /// Stop the running daemon.
-    Stop,
+    /// Stop the running daemon and wait for it to exit.
+    Stop {
+        /// Kill the daemon if it is still running once the timeout passes.
+        #[arg(long)]
+        force: bool,
+    },
 
     /// Show daemon health status.
-    Health,
+    Health {
+        /// Output format: "text", "json", or "oneline" (e.g.
+        /// `ok uptime=2h13m events=42`, for prompts and status bars).
+        /// Defaults to json with `--json`, otherwise text.
+        #[arg(long)]
+        format: Option<cli::HealthFormat>,
+    },
 
     /// Show session summary.
-    Status,
+    Status {
+        /// Print the current polling mode, reason, and interval per channel.
+        #[arg(long)]
+        polling: bool,
+    },
+
+    /// List panes for a session.
+    Panes {
+        /// Session ID to list panes for.
+        #[arg(long)]
+        session: String,
+    },
 
     /// List recent events.
     Events {
@@ -88,6 +158,17 @@ enum Command {
         limit: u32,
     },
 
+    /// Show usage stats (today's summary by default).
+    Stats {
+        /// Show per-hour buckets instead of the summary.
+        #[arg(long, conflicts_with = "daily")]
+        hourly: bool,
+
+        /// Show per-day buckets instead of the summary.
+        #[arg(long)]
+        daily: bool,
+    },
+
     /// Show or modify configuration.
     Config,
 
@@ -103,15 +184,29 @@ async fn main() {
     } else {
         OutputFormat::Text
     };
+    let client_options = cli::ClientOptions {
+        admin_token: args.admin_token,
+        timeout: std::time::Duration::from_millis(args.timeout_ms),
+        connect_retries: args.connect_retries,
+        ..cli::ClientOptions::new(cli::resolve_port(args.port))
+    };
+    let client = client_options.client();
 
     // Default to Start command if none specified
     let command = args.command.unwrap_or(Command::Start {
         log_level: None,
         log_format: None,
         stdio: false,
+        no_stdio: false,
         ws_port: None,
+        no_ws: false,
         http_port: None,
+        no_http: false,
+        port_scan: None,
+        pretty: false,
+        framing: None,
         no_single_instance: false,
+        safe_mode: false,
     });
 
     match command {
@@ -119,75 +214,109 @@ async fn main() {
             log_level,
             log_format,
             stdio,
+            no_stdio,
             ws_port,
+            no_ws,
             http_port,
+            no_http,
+            port_scan,
+            pretty,
+            framing,
             no_single_instance,
+            safe_mode,
         } => {
-            run_daemon(args.config, log_level, log_format, stdio, ws_port, http_port, no_single_instance).await;
+            let transports = TransportOverrides {
+                stdio: if stdio {
+                    Some(true)
+                } else {
+                    no_stdio.then_some(false)
+                },
+                ws: no_ws.then_some(false),
+                ws_port,
+                http: no_http.then_some(false),
+                http_port,
+                port_scan,
+                stdio_pretty: pretty.then_some(true),
+                stdio_framing: framing,
+            };
+            run_daemon(
+                args.config,
+                log_level,
+                log_format,
+                transports,
+                no_single_instance,
+                safe_mode,
+            )
+            .await;
         }
 
-        Command::Stop => {
-            if let Err(e) = cli::cmd_stop(None) {
-                eprintln!("Error: {e}");
-                std::process::exit(1);
-            }
+        Command::Stop { force } => {
+            finish(cli::cmd_stop(None, client_options.timeout, force).map(|()| exit_code::OK));
         }
 
-        Command::Health => {
-            if let Err(e) = cli::cmd_health(args.port, format, args.admin_token) {
-                eprintln!("Error: {e}");
-                std::process::exit(1);
-            }
+        Command::Health { format: health_format } => {
+            let health_format = health_format.unwrap_or_else(|| format.into());
+            finish(cli::cmd_health(&client, health_format));
         }
 
-        Command::Status => {
-            if let Err(e) = cli::cmd_status(args.port, format, args.admin_token) {
-                eprintln!("Error: {e}");
-                std::process::exit(1);
-            }
+        Command::Status { polling } => {
+            finish(cli::cmd_status(&client, format, polling).map(|()| exit_code::OK));
+        }
+
+        Command::Panes { session } => {
+            finish(cli::cmd_panes(&client, format, &session).map(|()| exit_code::OK));
         }
 
         Command::Events { limit } => {
-            if let Err(e) = cli::cmd_events(args.port, format, args.admin_token, Some(limit)) {
-                eprintln!("Error: {e}");
-                std::process::exit(1);
-            }
+            finish(cli::cmd_events(&client, format, Some(limit)).map(|()| exit_code::OK));
+        }
+
+        Command::Stats { hourly, daily } => {
+            let view = if hourly {
+                cli::StatsView::Hourly
+            } else if daily {
+                cli::StatsView::Daily
+            } else {
+                cli::StatsView::Summary
+            };
+            finish(cli::cmd_stats(&client, format, view).map(|()| exit_code::OK));
         }
 
         Command::Config => {
-            if let Err(e) = cli::cmd_config(args.port, format, args.admin_token) {
-                eprintln!("Error: {e}");
-                std::process::exit(1);
-            }
+            finish(cli::cmd_config(&client, format).map(|()| exit_code::OK));
         }
 
         Command::SelfTest => {
-            if let Err(e) = cli::cmd_self_test(args.port, format, args.admin_token) {
-                eprintln!("Error: {e}");
-                std::process::exit(1);
-            }
+            finish(cli::cmd_self_test(&client, format));
         }
     }
 }
 
+/// Report a client command's error and exit with its [`exit_code`].
+fn finish(result: Result<i32, cli::CliError>) -> ! {
+    if let Err(e) = &result {
+        eprintln!("Error: {e}");
+    }
+    std::process::exit(cli::exit_code_of(&result))
+}
+
 async fn run_daemon(
     config_path: Option<std::path::PathBuf>,
     log_level: Option<String>,
     log_format: Option<String>,
-    stdio: bool,
-    ws_port: Option<u16>,
-    http_port: Option<u16>,
+    transports: TransportOverrides,
     no_single_instance: bool,
+    safe_mode: bool,
 ) {
     // Acquire single-instance lock (unless disabled for testing)
-    let _instance_guard = if no_single_instance {
+    let instance_guard = if no_single_instance {
         None
     } else {
         match InstanceGuard::acquire() {
             Ok(guard) => Some(guard),
             Err(err) => {
                 eprintln!("Error: {err}");
-                std::process::exit(1);
+                std::process::exit(exit_code::FAILURE);
             }
         }
     };
@@ -197,7 +326,7 @@ async fn run_daemon(
         Ok(manager) => manager,
         Err(err) => {
             eprintln!("Error: Failed to load config: {err}");
-            std::process::exit(2);
+            std::process::exit(exit_code::CONFIG);
         }
     };
 
@@ -217,7 +346,7 @@ async fn run_daemon(
         Ok(credential) => credential,
         Err(err) => {
             tracing::error!(error = %err, "Failed to load admin token");
-            std::process::exit(2);
+            std::process::exit(exit_code::CONFIG);
         }
     };
 
@@ -227,231 +356,296 @@ async fn run_daemon(
         config_path = %config_path_str(&config),
         log_level = %log_config.level,
         log_format = %log_config.format,
+        safe_mode,
         "daemon bootstrap"
     );
 
-    // Create shared state
-    let cache = Arc::new(Cache::new(1000));
-    let ctx = Arc::new(RpcContext::new(cache, config));
+    // Create shared state; event ids continue from the previous run
+    let data_dir = ntm_tracker_daemon::service::data_dir();
+    let _ = std::fs::create_dir_all(&data_dir);
+    let db_path = data_dir.join("ntm-tracker.db");
+    let event_ids = match db::open_database(&db_path).and_then(db::EventIdSequence::load) {
+        Ok(event_ids) => event_ids,
+        Err(err) => {
+            tracing::warn!(error = %err, "event id sequence not persisted; ids restart from 1");
+            db::EventIdSequence::in_memory()
+        }
+    };
+    let cache = Arc::new(Cache::new(1000).with_event_ids(event_ids));
+    if safe_mode {
+        // Nothing will poll, so serve whatever the last run stored.
+        match db::open_database(&db_path).and_then(|conn| db::warm_cache(&conn, &cache, 1000)) {
+            Ok(loaded) => tracing::info!(
+                sessions = loaded.sessions,
+                panes = loaded.panes,
+                events = loaded.events,
+                "safe mode: cache warmed from db"
+            ),
+            Err(err) => tracing::warn!(error = %err, "safe mode: failed to warm cache from db"),
+        }
+    }
+    let mut ctx = RpcContext::new(cache, config);
+    ctx.safe_mode = safe_mode;
+    ctx.db_path = Some(db_path.clone());
+    let ctx = Arc::new(ctx);
 
     // Create shutdown handler for graceful shutdown
     let shutdown_handler = ShutdownHandler::new();
 
-    let maintenance_runner = maintenance::MaintenanceRunner::new(
-        ntm_tracker_daemon::service::data_dir().join("ntm-tracker.db"),
-        ctx.config.current().maintenance,
-    );
-    let maintenance_shutdown = shutdown_handler.subscribe();
-    tokio::spawn(async move {
-        maintenance_runner.run_loop(maintenance_shutdown).await;
-    });
+    let capabilities = ctx.capabilities.current();
+    let plan = StartupPlan::new(&capabilities, safe_mode);
 
-    // Perform initial polls before starting transports so first snapshot.get has data
-    // Note: NTM and tmux collectors create separate sessions with different UIDs,
-    // causing duplicate sessions and panes not matching NTM sessions.
-    // For now, prefer tmux collector when available since it provides actual pane data.
-    // TODO: Properly reconcile NTM and tmux collectors to share session UIDs.
-    let use_ntm_collector = ctx.capabilities.ntm && !ctx.capabilities.tmux;
+    if plan.maintenance {
+        let maintenance_runner =
+            maintenance::MaintenanceRunner::new(db_path, ctx.config.current().maintenance)
+                .with_cache(ctx.cache.clone())
+                .with_clock(ctx.clock.clone());
+        let maintenance_shutdown = shutdown_handler.subscribe();
+        tokio::spawn(async move {
+            maintenance_runner.run_loop(maintenance_shutdown).await;
+        });
 
-    if use_ntm_collector {
+        let prune_shutdown = shutdown_handler.subscribe();
+        spawn_cache_pruner(ctx.clone(), prune_shutdown);
+    }
+
+    // Perform initial polls before starting transports so first snapshot.get has data.
+    // When tmux is available its collector also merges ntm (see build_tmux_collector),
+    // so the standalone ntm collector only runs without tmux.
+    if plan.ntm_collector {
         tracing::info!(kind = "ntm", "performing initial poll on startup");
         let polling = ctx.config.current().polling;
         let collector_config = NtmCollectorConfig {
             active_interval: std::time::Duration::from_millis(polling.snapshot_interval_ms),
             idle_interval: std::time::Duration::from_millis(polling.snapshot_idle_interval_ms),
-            idle_threshold_secs: polling.idle_threshold_secs,
+            idle_threshold_secs: polling.idle_threshold_for("ntm"),
+            reconcile_every_n: polling.reconcile_every_n,
+            session_end_grace_secs: polling.session_end_grace_secs as i64,
+            session_identity: SessionIdentity::from_config(&polling),
         };
-        let runner = CommandRunner::new(CommandConfig::default());
+        let runner = ctx.command_runner();
         let client = NtmClient::new(runner, NtmConfig::default());
-        let bus = EventBus::new(8);
-        let mut collector = NtmCollector::new(client, bus, ctx.cache.clone(), collector_config);
+        let bus = ctx.bus.clone();
+        let mut collector = NtmCollector::new(client, bus, ctx.cache.clone(), collector_config)
+            .with_clock(ctx.clock.clone());
         if let Err(err) = collector.poll_once().await {
             tracing::warn!(error = %err, "ntm initial poll failed");
         }
-
-        let ntm_shutdown = shutdown_handler.subscribe();
-        spawn_ntm_collector(ctx.clone(), ntm_shutdown);
-    } else if ctx.capabilities.ntm {
-        tracing::info!("NTM available but tmux preferred; skipping NTM collector");
+    } else if safe_mode {
+        tracing::info!("safe mode; skipping collectors and maintenance");
+    } else if capabilities.ntm {
+        tracing::info!("NTM available alongside tmux; merging it in the tmux collector");
     } else {
         tracing::info!("NTM not detected; skipping NTM collector");
     }
 
-    if ctx.capabilities.tmux {
+    if plan.tmux_collector {
         tracing::info!(kind = "tmux", "performing initial poll on startup");
-        let polling = ctx.config.current().polling;
-        let collector_config = TmuxCollectorConfig {
-            poll_interval: std::time::Duration::from_millis(polling.snapshot_interval_ms),
-            ..TmuxCollectorConfig::default()
-        };
-        let runner = CommandRunner::new(CommandConfig::default());
-        let bus = EventBus::new(8);
-        let mut collector = TmuxCollector::new(runner, bus, ctx.cache.clone(), collector_config);
+        let mut collector = build_tmux_collector(&ctx);
         if let Err(err) = collector.poll_once().await {
             tracing::warn!(error = %err, "tmux initial poll failed");
         }
-
-        let tmux_shutdown = shutdown_handler.subscribe();
-        spawn_tmux_collector(ctx.clone(), tmux_shutdown);
-    } else {
+    } else if !safe_mode {
         tracing::info!("tmux not detected; skipping tmux collector");
     }
 
-    // Determine which transports to start
-    let use_stdio = stdio || (ws_port.is_none() && http_port.is_none());
+    if plan.supervisor {
+        let mut collectors = CollectorTasks::default();
+        collectors.apply(
+            &capabilities,
+            || spawn_ntm_collector(ctx.clone(), shutdown_handler.subscribe()),
+            || spawn_tmux_collector(ctx.clone(), shutdown_handler.subscribe()),
+        );
+        let supervisor_shutdown = shutdown_handler.subscribe();
+        spawn_collector_supervisor(ctx.clone(), collectors, supervisor_shutdown);
+    }
+
+    if !safe_mode {
+        // Compact events from the captures collectors publish on the bus
+        let detector_shutdown = shutdown_handler.subscribe();
+        tokio::spawn(ntm_tracker_daemon::detector::live::run(ctx.clone(), detector_shutdown));
+    }
+
+    // Determine which transports to start: CLI flags override [transports]
+    let selection = TransportSelection::resolve(&ctx.config.current().transports, &transports);
+    if !selection.stdio && selection.ws_port.is_none() && selection.http_port.is_none() {
+        tracing::error!("no transports enabled; check [transports] and the start flags");
+        std::process::exit(exit_code::CONFIG);
+    }
+    tracing::info!(
+        stdio = selection.stdio,
+        ws_port = ?selection.ws_port,
+        http_port = ?selection.http_port,
+        "transports selected"
+    );
+
+    // Bind network transports before spawning them so the chosen ports are
+    // known up front; busy ports are skipped when port-scan allows it.
+    let port_scan = transports
+        .port_scan
+        .unwrap_or(ctx.config.current().transports.port_scan);
+    let mut ws_bound = None;
+    let mut http_bound = None;
 
     // Spawn WS server if requested
-    if let Some(port) = ws_port {
-        let ws_config = transport::ws::WsConfig {
-            port,
-            admin_credential: admin_credential.clone(),
-            tokens: Vec::new(),
-        };
-        let ws_server = transport::ws::WsServer::new(ws_config);
-        let ws_ctx = ctx.clone();
-        tokio::spawn(async move {
-            let _ = ws_server.run(ws_ctx).await;
-        });
+    if let Some(port) = selection.ws_port {
+        if let Some((port, listener)) = bind_transport("ws", port, port_scan) {
+            let ws_config = transport::ws::WsConfig {
+                port,
+                admin_credential: admin_credential.clone(),
+                tokens: Vec::new(),
+            };
+            let ws_server = transport::ws::WsServer::new(ws_config);
+            let ws_ctx = ctx.clone();
+            tokio::spawn(async move {
+                ws_server.serve(listener, ws_ctx).await;
+            });
+            ws_bound = Some(port);
+        }
     }
 
     // Spawn HTTP server if requested
-    if let Some(port) = http_port {
-        let http_config = transport::http::HttpConfig {
-            port,
-            admin_credential: admin_credential.clone(),
-            tokens: Vec::new(),
+    if let Some(port) = selection.http_port {
+        if let Some((port, listener)) = bind_transport("http", port, port_scan) {
+            let http_config = transport::http::HttpConfig {
+                port,
+                admin_credential: admin_credential.clone(),
+                tokens: Vec::new(),
+            };
+            let http_server = transport::http::HttpServer::new(http_config);
+            let http_ctx = ctx.clone();
+            tokio::spawn(async move {
+                http_server.serve(listener, http_ctx).await;
+            });
+            http_bound = Some(port);
+        }
+    }
+
+    // Record the chosen ports so clients can find this instance
+    if let Some(guard) = &instance_guard {
+        let metadata = InstanceMetadata {
+            pid: std::process::id(),
+            instance_id: ctx.instance_id.clone(),
+            label: ctx.config.current().service.resolved_label(),
+            port: http_bound,
+            ws_port: ws_bound,
+            // transports.uds is not served yet, so there is no socket to advertise
+            uds_path: None,
         };
-        let http_server = transport::http::HttpServer::new(http_config);
-        let http_ctx = ctx.clone();
-        tokio::spawn(async move {
-            let _ = http_server.run(http_ctx).await;
-        });
+        if let Err(err) = guard.write_metadata(&metadata) {
+            tracing::warn!(error = %err, "failed to write instance metadata");
+        }
     }
 
-    if use_stdio {
+    // Under systemd, report readiness once transports are spawned and keep the watchdog fed.
+    let under_systemd = capabilities.systemd;
+    if under_systemd {
+        systemd::notify_ready();
+        if let Some(interval) = systemd::watchdog_interval() {
+            let watchdog_shutdown = shutdown_handler.subscribe();
+            tokio::spawn(systemd::run_watchdog(interval, watchdog_shutdown));
+        }
+    }
+
+    if selection.stdio {
         // stdio is the primary transport when no other is specified
+        let ctx = Arc::new(ctx.with_pane_tails());
         let (notif_tx, notif_rx) = transport::stdio::notification_channel();
         let snapshot_shutdown = shutdown_handler.subscribe();
         spawn_stdio_snapshot_notifier(ctx.clone(), notif_tx.clone(), snapshot_shutdown);
-
-        transport::stdio::run(ctx, notif_rx).await;
+        let events_shutdown = shutdown_handler.subscribe();
+        spawn_stdio_event_notifier(ctx.clone(), notif_tx.clone(), events_shutdown);
+        let tail_shutdown = shutdown_handler.subscribe();
+        spawn_stdio_tail_notifier(ctx.clone(), notif_tx.clone(), tail_shutdown);
+
+        let stdio_config = ctx.config.current().transports;
+        let options = transport::stdio::StdioOptions {
+            pretty: transports.stdio_pretty.unwrap_or(stdio_config.stdio_pretty),
+            framing: transports.stdio_framing.unwrap_or(stdio_config.stdio_framing),
+        };
+        let reason = transport::stdio::run(ctx, notif_rx, options).await;
+        // Requests are answered inline, so nothing is in flight to drain; just
+        // let background tasks see the shutdown
+        shutdown_handler.stop(reason);
+        log_shutdown_complete(shutdown_handler.reason().as_ref(), None);
     } else {
         // If WS or HTTP is running, we need to keep the main task alive
         // Wait for shutdown signal (SIGTERM, SIGINT)
         tracing::info!("Running with WS/HTTP transports, waiting for shutdown signal");
-        shutdown_handler.wait_for_signal().await;
+        let signal = shutdown_handler.wait_for_signal().await;
+        tracing::debug!(?signal, "shutdown signal received");
+        if under_systemd {
+            systemd::notify_stopping();
+        }
 
-        // Allow graceful shutdown (1 second timeout)
-        shutdown_handler
+        // Allow graceful shutdown (1 second timeout); a second Ctrl-C skips it.
+        let drain_started = std::time::Instant::now();
+        let outcome = shutdown_handler
             .graceful_shutdown(std::time::Duration::from_secs(1))
             .await;
-    }
-
-    tracing::info!("Daemon shutdown complete");
-}
-
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-enum PollingMode {
-    Active,
-    Idle,
-    Background,
-    Degraded,
-}
-
-impl PollingMode {
-    fn as_str(&self) -> &'static str {
-        match self {
-            PollingMode::Active => "active",
-            PollingMode::Idle => "idle",
-            PollingMode::Background => "background",
-            PollingMode::Degraded => "degraded",
+        log_shutdown_complete(
+            shutdown_handler.reason().as_ref(),
+            Some((outcome, drain_started.elapsed())),
+        );
+        if outcome == ShutdownOutcome::Forced {
+            tracing::warn!("Daemon forced to exit immediately");
+            std::process::exit(130);
         }
     }
 }
 
-#[derive(Clone, Copy, Debug)]
-struct PollingDecision {
-    mode: PollingMode,
-    reason: &'static str,
-    interval_ms: u64,
-}
-
-fn compute_polling_decision(
-    cache: &Cache,
-    polling: &PollingConfig,
-    error_streak: u32,
-) -> PollingDecision {
-    let now = current_unix_ts();
-    let sessions = cache.all_sessions();
-    let has_sessions = !sessions.is_empty();
-    let is_active = sessions.iter().any(|session| {
-        session.ended_at.is_none()
-            && now.saturating_sub(session.last_seen_at) <= polling.idle_threshold_secs
-    });
-
-    let mut mode = if !has_sessions {
-        PollingMode::Background
-    } else if is_active {
-        PollingMode::Active
-    } else {
-        PollingMode::Idle
-    };
-
-    let mut reason = match mode {
-        PollingMode::Active => "recent_activity",
-        PollingMode::Idle => "idle_timeout",
-        PollingMode::Background => "no_sessions",
-        PollingMode::Degraded => "degraded",
-    };
-
-    let mut interval_ms = match mode {
-        PollingMode::Active => polling.snapshot_interval_ms,
-        PollingMode::Idle => polling.snapshot_idle_interval_ms,
-        PollingMode::Background => polling.snapshot_background_interval_ms,
-        PollingMode::Degraded => polling.snapshot_degraded_interval_ms,
-    };
-
-    let health = cache.health();
-    if error_streak > 0 {
-        mode = PollingMode::Degraded;
-        reason = "poll_errors";
-        interval_ms = polling.snapshot_degraded_interval_ms;
-    } else if !health.status.trim().is_empty() && health.status != "ok" {
-        mode = PollingMode::Degraded;
-        reason = "health_degraded";
-        interval_ms = polling.snapshot_degraded_interval_ms;
-    }
-
-    interval_ms = interval_ms.max(250);
-
-    PollingDecision {
-        mode,
-        reason,
-        interval_ms,
+/// Bind a loopback listener for `kind`, logging the port that was chosen.
+fn bind_transport(kind: &str, port: u16, scan: u16) -> Option<(u16, tokio::net::TcpListener)> {
+    match transport::bind_loopback(port, scan) {
+        Ok((chosen, listener)) => {
+            if chosen != port {
+                tracing::warn!(
+                    transport = kind,
+                    requested = port,
+                    port = chosen,
+                    "port busy; using next free port"
+                );
+            }
+            tracing::info!(transport = kind, port = chosen, "transport bound");
+            Some((chosen, listener))
+        }
+        Err(err) => {
+            tracing::error!(transport = kind, port, scan, error = %err, "failed to bind transport");
+            None
+        }
     }
 }
 
 fn spawn_ntm_collector(
     ctx: Arc<RpcContext>,
     mut shutdown_rx: tokio::sync::broadcast::Receiver<()>,
-) {
+) -> tokio::task::JoinHandle<()> {
     tokio::spawn(async move {
         let polling = ctx.config.current().polling;
         let collector_config = NtmCollectorConfig {
             active_interval: std::time::Duration::from_millis(polling.snapshot_interval_ms),
             idle_interval: std::time::Duration::from_millis(polling.snapshot_idle_interval_ms),
-            idle_threshold_secs: polling.idle_threshold_secs,
+            idle_threshold_secs: polling.idle_threshold_for("ntm"),
+            reconcile_every_n: polling.reconcile_every_n,
+            session_end_grace_secs: polling.session_end_grace_secs as i64,
+            session_identity: SessionIdentity::from_config(&polling),
         };
-        let runner = CommandRunner::new(CommandConfig::default());
+        let runner = ctx.command_runner();
         let client = NtmClient::new(runner, NtmConfig::default());
-        let bus = EventBus::new(8);
-        let mut collector = NtmCollector::new(client, bus, ctx.cache.clone(), collector_config);
+        let bus = ctx.bus.clone();
+        let mut collector = NtmCollector::new(client, bus, ctx.cache.clone(), collector_config)
+            .with_clock(ctx.clock.clone());
 
-        let mut error_streak = 0u32;
+        let mut missing_binary = MissingBinaryGuard::new(CollectorKind::Ntm);
         loop {
             let polling = ctx.config.current().polling;
-            let decision = compute_polling_decision(ctx.cache.as_ref(), &polling, error_streak);
-            let now = current_unix_ts();
+            let decision = compute_polling_decision(
+                ctx.cache.as_ref(),
+                &polling,
+                PollChannel::Ntm,
+                ctx.clock.as_ref(),
+            );
+            let now = ctx.clock.now_unix();
             let updated = ctx.cache.update_polling_ntm(PollingDatum {
                 interval_ms: decision.interval_ms,
                 mode: decision.mode.as_str().to_string(),
@@ -472,18 +666,26 @@ fn spawn_ntm_collector(
             tokio::pin!(sleep);
             tokio::select! {
                 _ = &mut sleep => {
-                    match collector.poll_once().await {
-                        Ok(result) => {
-                            if result.degraded {
-                                error_streak = error_streak.saturating_add(1);
-                            } else {
-                                error_streak = 0;
-                            }
+                    let outcome = collector.poll_once().await;
+                    let poll_outcome = match &outcome {
+                        Ok(result) if result.degraded => PollOutcome::Degraded,
+                        Ok(_) => PollOutcome::Ok,
+                        Err(err) if err.is_fatal() => {
+                            tracing::error!(error = %err, "ntm poll failed");
+                            PollOutcome::Failed
                         }
                         Err(err) => {
-                            error_streak = error_streak.saturating_add(1);
                             tracing::warn!(error = %err, "ntm poll failed");
+                            PollOutcome::Failed
                         }
+                    };
+                    record_poll(ctx.cache.as_ref(), PollChannel::Ntm, poll_outcome);
+                    if missing_binary.observe(outcome.as_ref().err(), &ctx.capabilities) {
+                        tracing::warn!(
+                            kind = "ntm",
+                            "ntm binary missing; disabling collector until capabilities are rechecked"
+                        );
+                        break;
                     }
                 }
                 _ = shutdown_rx.recv() => {
@@ -491,28 +693,55 @@ fn spawn_ntm_collector(
                 }
             }
         }
-    });
+    })
+}
+
+/// The tmux collector, also reading ntm when it is available so both
+/// sources reconcile into the same session and pane records.
+fn build_tmux_collector(ctx: &Arc<RpcContext>) -> TmuxCollector {
+    let config = ctx.config.current();
+    let collector_config = TmuxCollectorConfig {
+        poll_interval: std::time::Duration::from_millis(config.polling.snapshot_interval_ms),
+        format: config.tmux.list_format,
+        capture_output: config.capture.capture_output,
+        capture_cwd: config.capture.capture_cwd,
+        env_allowlist: config.capture.env_allowlist,
+        ..TmuxCollectorConfig::default()
+    };
+    let collector = TmuxCollector::new(
+        ctx.command_runner(),
+        ctx.bus.clone(),
+        ctx.cache.clone(),
+        collector_config,
+    )
+    .with_clock(ctx.clock.clone());
+    if !ctx.capabilities.current().ntm {
+        return collector;
+    }
+    collector.with_ntm(
+        NtmClient::new(ctx.command_runner(), NtmConfig::default()),
+        SessionIdentity::from_config(&config.polling),
+        config.polling.session_end_grace_secs as i64,
+    )
 }
 
 fn spawn_tmux_collector(
     ctx: Arc<RpcContext>,
     mut shutdown_rx: tokio::sync::broadcast::Receiver<()>,
-) {
+) -> tokio::task::JoinHandle<()> {
     tokio::spawn(async move {
-        let polling = ctx.config.current().polling;
-        let collector_config = TmuxCollectorConfig {
-            poll_interval: std::time::Duration::from_millis(polling.snapshot_interval_ms),
-            ..TmuxCollectorConfig::default()
-        };
-        let runner = CommandRunner::new(CommandConfig::default());
-        let bus = EventBus::new(8);
-        let mut collector = TmuxCollector::new(runner, bus, ctx.cache.clone(), collector_config);
+        let mut collector = build_tmux_collector(&ctx);
 
-        let mut error_streak = 0u32;
+        let mut missing_binary = MissingBinaryGuard::new(CollectorKind::Tmux);
         loop {
             let polling = ctx.config.current().polling;
-            let decision = compute_polling_decision(ctx.cache.as_ref(), &polling, error_streak);
-            let now = current_unix_ts();
+            let decision = compute_polling_decision(
+                ctx.cache.as_ref(),
+                &polling,
+                PollChannel::Tmux,
+                ctx.clock.as_ref(),
+            );
+            let now = ctx.clock.now_unix();
             let updated = ctx.cache.update_polling_tmux(PollingDatum {
                 interval_ms: decision.interval_ms,
                 mode: decision.mode.as_str().to_string(),
@@ -533,18 +762,26 @@ fn spawn_tmux_collector(
             tokio::pin!(sleep);
             tokio::select! {
                 _ = &mut sleep => {
-                    match collector.poll_once().await {
-                        Ok(result) => {
-                            if result.degraded {
-                                error_streak = error_streak.saturating_add(1);
-                            } else {
-                                error_streak = 0;
-                            }
+                    let outcome = collector.poll_once().await;
+                    let poll_outcome = match &outcome {
+                        Ok(result) if result.degraded => PollOutcome::Degraded,
+                        Ok(_) => PollOutcome::Ok,
+                        Err(err) if err.is_fatal() => {
+                            tracing::error!(error = %err, "tmux poll failed");
+                            PollOutcome::Failed
                         }
                         Err(err) => {
-                            error_streak = error_streak.saturating_add(1);
                             tracing::warn!(error = %err, "tmux poll failed");
+                            PollOutcome::Failed
                         }
+                    };
+                    record_poll(ctx.cache.as_ref(), PollChannel::Tmux, poll_outcome);
+                    if missing_binary.observe(outcome.as_ref().err(), &ctx.capabilities) {
+                        tracing::warn!(
+                            kind = "tmux",
+                            "tmux binary missing; disabling collector until capabilities are rechecked"
+                        );
+                        break;
                     }
                 }
                 _ = shutdown_rx.recv() => {
@@ -552,53 +789,194 @@ fn spawn_tmux_collector(
                 }
             }
         }
-    });
+    })
 }
 
-fn spawn_stdio_snapshot_notifier(
+/// Report one poll: count it in the metrics and extend or reset the
+/// channel's error streak in the cache.
+fn record_poll(cache: &Cache, channel: PollChannel, outcome: PollOutcome) {
+    let counters = match channel {
+        PollChannel::Snapshot => &METRICS.snapshot_poll,
+        PollChannel::Tmux => &METRICS.tmux_poll,
+        PollChannel::Ntm => &METRICS.ntm_poll,
+    };
+    counters.record(outcome);
+    cache.record_poll_result(channel, outcome != PollOutcome::Ok);
+}
+
+/// Start and stop collectors as tools come and go at runtime, e.g. after
+/// `capabilities.recheck`. Collectors whose binary disappears stop themselves
+/// and are restarted once it is back.
+fn spawn_collector_supervisor(
     ctx: Arc<RpcContext>,
-    notification_tx: mpsc::Sender<transport::JsonRpcNotification>,
+    mut collectors: CollectorTasks,
     mut shutdown_rx: tokio::sync::broadcast::Receiver<()>,
 ) {
+    let mut changes = ctx.capabilities.subscribe();
     tokio::spawn(async move {
-        let mut error_streak = 0u32;
+        changes.borrow_and_update();
         loop {
-            let polling = ctx.config.current().polling;
-            let decision = compute_polling_decision(ctx.cache.as_ref(), &polling, error_streak);
-            let now = current_unix_ts();
-            let updated = ctx.cache.update_polling_snapshot(PollingDatum {
-                interval_ms: decision.interval_ms,
-                mode: decision.mode.as_str().to_string(),
-                reason: decision.reason.to_string(),
-                last_change_at: now,
-            });
-            if updated {
-                tracing::info!(
-                    kind = "snapshot",
-                    interval_ms = decision.interval_ms,
-                    mode = %decision.mode.as_str(),
-                    reason = decision.reason,
-                    "polling interval updated"
-                );
+            tokio::select! {
+                changed = changes.changed() => {
+                    if changed.is_err() {
+                        break;
+                    }
+                    let current = changes.borrow_and_update().clone();
+                    collectors.apply(
+                        &current,
+                        || spawn_ntm_collector(ctx.clone(), shutdown_rx.resubscribe()),
+                        || spawn_tmux_collector(ctx.clone(), shutdown_rx.resubscribe()),
+                    );
+                }
+                _ = shutdown_rx.recv() => {
+                    break;
+                }
             }
+        }
+    });
+}
 
-            let sleep = tokio::time::sleep(std::time::Duration::from_millis(decision.interval_ms));
-            tokio::pin!(sleep);
+/// Periodically drop ended sessions older than `cache.ended-session-ttl-secs`
+/// and stale panes (`cache.pane-stale-ttl-secs`) from the live cache. The
+/// database keeps them.
+fn spawn_cache_pruner(
+    ctx: Arc<RpcContext>,
+    mut shutdown_rx: tokio::sync::broadcast::Receiver<()>,
+) {
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(60));
+        loop {
             tokio::select! {
-                _ = &mut sleep => {
-                    match handlers::core::snapshot_get(ctx.as_ref()) {
+                _ = ticker.tick() => {
+                    let config = ctx.config.current().cache;
+                    let now = ctx.clock.now_unix();
+                    if config.ended_session_ttl_secs > 0 {
+                        let ttl = i64::try_from(config.ended_session_ttl_secs).unwrap_or(i64::MAX);
+                        let pruned = ctx.cache.prune_ended_sessions(now, ttl);
+                        if pruned > 0 {
+                            tracing::debug!(pruned, "pruned ended sessions from cache");
+                        }
+                    }
+                    if config.pane_stale_ttl_secs > 0 {
+                        let ttl = i64::try_from(config.pane_stale_ttl_secs).unwrap_or(i64::MAX);
+                        let pruned = ctx.cache.prune_stale_panes(now, ttl);
+                        if pruned > 0 {
+                            tracing::debug!(pruned, "pruned stale panes from cache");
+                        }
+                    }
+                }
+                _ = shutdown_rx.recv() => {
+                    break;
+                }
+            }
+        }
+    });
+}
+
+/// Send `sessions.snapshot` notifications when collectors publish a state
+/// change, and on the snapshot polling cadence otherwise. Both kinds of
+/// trigger go through a [`transport::NotificationCoalescer`], so a burst of
+/// changes sends the latest state once per `notifications.coalesce-ms`.
+fn spawn_stdio_snapshot_notifier(
+    ctx: Arc<RpcContext>,
+    notification_tx: mpsc::Sender<transport::JsonRpcNotification>,
+    mut shutdown_rx: tokio::sync::broadcast::Receiver<()>,
+) {
+    let mut states = ctx.bus.subscribe_state();
+    tokio::spawn(async move {
+        let mut coalescer = transport::NotificationCoalescer::new(std::time::Duration::ZERO);
+        let mut next_tick = tokio::time::Instant::now() + snapshot_interval(ctx.as_ref());
+        let mut send_at: Option<tokio::time::Instant> = None;
+        loop {
+            let triggered = tokio::select! {
+                _ = tokio::time::sleep_until(next_tick) => {
+                    next_tick = tokio::time::Instant::now() + snapshot_interval(ctx.as_ref());
+                    true
+                }
+                received = states.recv() => match received {
+                    Ok(_) | Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => true,
+                    Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+                },
+                _ = tokio::time::sleep_until(send_at.unwrap_or(next_tick)), if send_at.is_some() => {
+                    send_at = None;
+                    match handlers::core::snapshot_get(ctx.as_ref(), serde_json::Value::Null) {
                         Ok(snapshot) => {
-                            error_streak = 0;
-                            let notification = transport::JsonRpcNotification::new("sessions.snapshot", snapshot);
+                            record_poll(ctx.cache.as_ref(), PollChannel::Snapshot, PollOutcome::Ok);
+                            let notification =
+                                transport::JsonRpcNotification::new("sessions.snapshot", snapshot);
                             if notification_tx.send(notification).await.is_err() {
                                 break;
                             }
                         }
                         Err(err) => {
-                            error_streak = error_streak.saturating_add(1);
+                            record_poll(ctx.cache.as_ref(), PollChannel::Snapshot, PollOutcome::Failed);
                             tracing::warn!(error = %err.message, "snapshot notification failed");
                         }
                     }
+                    // A failed snapshot is retried by the next trigger, still paced by the window
+                    coalescer.sent(tokio::time::Instant::now().into_std());
+                    false
+                }
+                _ = shutdown_rx.recv() => break,
+            };
+            if triggered {
+                let window = ctx.config.current().notifications.coalesce_ms;
+                coalescer.set_window(std::time::Duration::from_millis(window));
+                // Triggers while a send is pending fold into it
+                if let Some(at) = coalescer.trigger(tokio::time::Instant::now().into_std()) {
+                    send_at = Some(at.into());
+                }
+            }
+        }
+    });
+}
+
+/// The snapshot polling interval for the current cache state, recording any
+/// change in the cache's polling state.
+fn snapshot_interval(ctx: &RpcContext) -> std::time::Duration {
+    let polling = ctx.config.current().polling;
+    let decision = compute_polling_decision(
+        ctx.cache.as_ref(),
+        &polling,
+        PollChannel::Snapshot,
+        ctx.clock.as_ref(),
+    );
+    let updated = ctx.cache.update_polling_snapshot(PollingDatum {
+        interval_ms: decision.interval_ms,
+        mode: decision.mode.as_str().to_string(),
+        reason: decision.reason.to_string(),
+        last_change_at: ctx.clock.now_unix(),
+    });
+    if updated {
+        tracing::info!(
+            kind = "snapshot",
+            interval_ms = decision.interval_ms,
+            mode = %decision.mode.as_str(),
+            reason = decision.reason,
+            "polling interval updated"
+        );
+    }
+    std::time::Duration::from_millis(decision.interval_ms)
+}
+
+/// Forward events published on the daemon bus as `events.push` notifications,
+/// independent of the snapshot cadence. Events already queued are batched.
+fn spawn_stdio_event_notifier(
+    ctx: Arc<RpcContext>,
+    notification_tx: mpsc::Sender<transport::JsonRpcNotification>,
+    mut shutdown_rx: tokio::sync::broadcast::Receiver<()>,
+) {
+    let mut feed = transport::EventPushFeed::new(&ctx.bus);
+    tokio::spawn(async move {
+        loop {
+            tokio::select! {
+                notification = feed.next() => {
+                    let Some(notification) = notification else {
+                        break;
+                    };
+                    if notification_tx.send(notification).await.is_err() {
+                        break;
+                    }
                 }
                 _ = shutdown_rx.recv() => {
                     break;
@@ -608,11 +986,32 @@ fn spawn_stdio_snapshot_notifier(
     });
 }
 
-fn current_unix_ts() -> i64 {
-    let now = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_else(|_| std::time::Duration::from_secs(0));
-    now.as_secs() as i64
+/// Push `pane.output` for panes tailed over stdio (`panes.tail`).
+fn spawn_stdio_tail_notifier(
+    ctx: Arc<RpcContext>,
+    notification_tx: mpsc::Sender<transport::JsonRpcNotification>,
+    mut shutdown_rx: tokio::sync::broadcast::Receiver<()>,
+) {
+    let mut feed = transport::PaneTailFeed::new(&ctx.bus);
+    tokio::spawn(async move {
+        'feed: loop {
+            tokio::select! {
+                notifications = feed.next(&ctx) => {
+                    let Some(notifications) = notifications else {
+                        break;
+                    };
+                    for notification in notifications {
+                        if notification_tx.send(notification).await.is_err() {
+                            break 'feed;
+                        }
+                    }
+                }
+                _ = shutdown_rx.recv() => {
+                    break;
+                }
+            }
+        }
+    });
 }
 
 fn config_path_str(config: &ConfigManager) -> String {
@@ -1,10 +1,13 @@
 //! Database maintenance routines (rollups, retention, vacuum).
 
+use crate::cache::{Cache, DegradedReason, HealthSource, HealthStatus};
+use crate::clock::{system_clock, SharedClock};
 use crate::config::MaintenanceConfig;
 use crate::db;
 use rusqlite::{params, Connection, OptionalExtension};
 use std::path::{Path, PathBuf};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::broadcast;
 use tracing::{debug, info, warn};
 
@@ -35,6 +38,8 @@ pub struct MaintenanceRunner {
     db_path: PathBuf,
     config: MaintenanceConfig,
     tz_offset_min: i64,
+    cache: Option<Arc<Cache>>,
+    clock: SharedClock,
 }
 
 impl MaintenanceRunner {
@@ -43,6 +48,45 @@ impl MaintenanceRunner {
             db_path,
             config,
             tz_offset_min: 0,
+            cache: None,
+            clock: system_clock(),
+        }
+    }
+
+    /// Time maintenance windows by `clock` instead of the system clock.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Report database lock failures to this cache's health status.
+    pub fn with_cache(mut self, cache: Arc<Cache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Degrade health when a cycle failed because the database was locked.
+    fn report_failure(&self, err: &rusqlite::Error) {
+        warn!(error = %err, "maintenance cycle failed");
+        let Some(cache) = &self.cache else {
+            return;
+        };
+        if db::is_locked(err) {
+            cache.report_health(
+                HealthSource::Db,
+                HealthStatus {
+                    status: "degraded".to_string(),
+                    last_error: Some(format!("db: {err}")),
+                    degraded_reason: Some(DegradedReason::DbLocked),
+                },
+            );
+        }
+    }
+
+    /// Lift a database degradation once a cycle completes.
+    fn report_success(&self) {
+        if let Some(cache) = &self.cache {
+            cache.clear_health(HealthSource::Db);
         }
     }
 
@@ -56,7 +100,7 @@ impl MaintenanceRunner {
         }
 
         let mut conn = db::open_database(&self.db_path)?;
-        let now = now_ts()?;
+        let now = self.clock.now_unix();
         run_cycle(
             &mut conn,
             &self.config,
@@ -78,10 +122,8 @@ impl MaintenanceRunner {
                     if let Some(handle) = in_flight.take() {
                         if handle.is_finished() {
                             match handle.await {
-                                Ok(Ok(_summary)) => {}
-                                Ok(Err(err)) => {
-                                    warn!(error = %err, "maintenance cycle failed");
-                                }
+                                Ok(Ok(_summary)) => self.report_success(),
+                                Ok(Err(err)) => self.report_failure(&err),
                                 Err(err) => {
                                     warn!(error = %err, "maintenance task panicked");
                                 }
@@ -96,12 +138,15 @@ impl MaintenanceRunner {
                     let db_path = self.db_path.clone();
                     let config = self.config.clone();
                     let tz_offset_min = self.tz_offset_min;
+                    let clock = self.clock.clone();
 
                     in_flight = Some(tokio::task::spawn_blocking(move || {
                         let runner = MaintenanceRunner {
                             db_path,
                             config,
                             tz_offset_min,
+                            cache: None,
+                            clock,
                         };
                         runner.run_once()
                     }));
@@ -110,10 +155,8 @@ impl MaintenanceRunner {
                     if let Some(handle) = in_flight.take() {
                         if handle.is_finished() {
                             match handle.await {
-                                Ok(Ok(_summary)) => {}
-                                Ok(Err(err)) => {
-                                    warn!(error = %err, "maintenance cycle failed");
-                                }
+                                Ok(Ok(_summary)) => self.report_success(),
+                                Ok(Err(err)) => self.report_failure(&err),
                                 Err(err) => {
                                     warn!(error = %err, "maintenance task panicked");
                                 }
@@ -131,13 +174,6 @@ impl MaintenanceRunner {
     }
 }
 
-fn now_ts() -> rusqlite::Result<i64> {
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map_err(|err| rusqlite::Error::ToSqlConversionFailure(Box::new(err)))?;
-    Ok(now.as_secs() as i64)
-}
-
 fn read_meta_i64(conn: &Connection, key: &str) -> rusqlite::Result<Option<i64>> {
     let value: Option<String> = conn
         .query_row("SELECT value FROM meta WHERE key = ?1;", [key], |row| {
@@ -298,6 +334,38 @@ pub fn rollup_hour(conn: &Connection, hour_start: i64) -> rusqlite::Result<()> {
     Ok(())
 }
 
+/// Compacts and estimated tokens recorded in one time window.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WindowTotals {
+    pub compacts: u64,
+    pub tokens: u64,
+}
+
+/// Totals for `[end - window_secs, end)`, read from the same tables the
+/// hourly rollup uses, so the window need not line up with rolled hours.
+pub fn window_totals(
+    conn: &Connection,
+    end: i64,
+    window_secs: i64,
+) -> rusqlite::Result<WindowTotals> {
+    let start = end.saturating_sub(window_secs);
+    let compacts: i64 = conn.query_row(
+        "SELECT COUNT(*) FROM events WHERE type = 'compact' AND detected_at >= ?1 AND detected_at < ?2;",
+        [start, end],
+        |row| row.get(0),
+    )?;
+    let tokens: i64 = conn.query_row(
+        "SELECT COALESCE(SUM(estimated_tokens), 0) FROM pane_minute_samples
+         WHERE minute_start >= ?1 AND minute_start < ?2;",
+        [start, end],
+        |row| row.get(0),
+    )?;
+    Ok(WindowTotals {
+        compacts: compacts.max(0) as u64,
+        tokens: tokens.max(0) as u64,
+    })
+}
+
 pub fn rollup_day(conn: &Connection, day_start: i64, tz_offset_min: i64) -> rusqlite::Result<()> {
     conn.execute(
         r#"
@@ -541,4 +609,52 @@ mod tests {
             .unwrap();
         assert_eq!(status_reason.as_deref(), Some("archived"));
     }
+
+    #[test]
+    fn run_once_times_windows_by_the_injected_clock() {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("tracker.db");
+        db::open_database(&db_path).unwrap();
+        let clock = Arc::new(crate::clock::MockClock::new(1_700_000_000));
+        let runner = MaintenanceRunner::new(db_path.clone(), MaintenanceConfig::default())
+            .with_clock(clock.clone());
+
+        runner.run_once().unwrap();
+        let conn = db::open_database(&db_path).unwrap();
+        assert_eq!(read_meta_i64(&conn, META_LAST_RETENTION).unwrap(), Some(1_700_000_000));
+
+        // Retention waits a day from the clock's point of view
+        clock.advance(3_600);
+        runner.run_once().unwrap();
+        assert_eq!(read_meta_i64(&conn, META_LAST_RETENTION).unwrap(), Some(1_700_000_000));
+        clock.advance(86_400);
+        runner.run_once().unwrap();
+        assert_eq!(read_meta_i64(&conn, META_LAST_RETENTION).unwrap(), Some(1_700_090_000));
+    }
+
+    #[test]
+    fn locked_database_degrades_health_with_db_locked() {
+        let cache = Arc::new(Cache::new(10));
+        let runner = MaintenanceRunner::new(PathBuf::from("unused.db"), MaintenanceConfig::default())
+            .with_cache(cache.clone());
+
+        runner.report_failure(&rusqlite::Error::QueryReturnedNoRows);
+        assert_eq!(cache.health().degraded_reason, None, "only lock errors degrade");
+
+        let busy = rusqlite::Error::SqliteFailure(
+            rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_BUSY),
+            Some("database is locked".to_string()),
+        );
+        runner.report_failure(&busy);
+        let health = cache.health();
+        assert_eq!(health.status, "degraded");
+        assert_eq!(health.degraded_reason, Some(DegradedReason::DbLocked));
+        assert!(health.last_error.unwrap().starts_with("db: "));
+
+        // A collector recovering does not lift the database degradation
+        cache.clear_health(HealthSource::Tmux);
+        assert_eq!(cache.health().degraded_reason, Some(DegradedReason::DbLocked));
+        runner.report_success();
+        assert_eq!(cache.health().degraded_reason, None);
+    }
 }
@@ -3,7 +3,9 @@
 //! This module provides utilities for tracking timing metrics across the daemon.
 //! All metrics are exposed through tracing spans and can be aggregated by log analysis tools.
 
+use std::collections::BTreeMap;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
 use std::time::{Duration, Instant};
 
 /// Global metrics collector.
@@ -98,6 +100,342 @@ pub struct HistogramStats {
     pub avg_us: u64,
 }
 
+/// Upper bounds (µs) of the latency buckets used for percentile estimates.
+const LATENCY_BUCKETS_US: [u64; 16] = [
+    50, 100, 250, 500, 1_000, 2_500, 5_000, 10_000, 25_000, 50_000, 100_000, 250_000,
+    500_000, 1_000_000, 2_500_000, 5_000_000,
+];
+
+/// Methods tracked individually; anything beyond is folded into `OTHER_METHOD`
+/// so unknown method names from clients cannot grow the map without bound.
+const MAX_TRACKED_METHODS: usize = 128;
+const OTHER_METHOD: &str = "other";
+
+/// Bucketed latency counts supporting approximate percentiles.
+#[derive(Debug, Clone, Default)]
+pub struct LatencyBuckets {
+    /// One count per bound in `LATENCY_BUCKETS_US`, plus an overflow bucket.
+    counts: [u64; LATENCY_BUCKETS_US.len() + 1],
+    count: u64,
+    max_us: u64,
+}
+
+impl LatencyBuckets {
+    pub fn record(&mut self, duration: Duration) {
+        let us = duration.as_micros() as u64;
+        let bucket = LATENCY_BUCKETS_US
+            .iter()
+            .position(|&bound| us <= bound)
+            .unwrap_or(LATENCY_BUCKETS_US.len());
+        self.counts[bucket] += 1;
+        self.count += 1;
+        self.max_us = self.max_us.max(us);
+    }
+
+    /// Estimate the `p`th percentile (0.0..=1.0) as the upper bound of the
+    /// bucket containing it, capped at the largest recorded value.
+    pub fn percentile(&self, p: f64) -> u64 {
+        if self.count == 0 {
+            return 0;
+        }
+        let rank = ((p.clamp(0.0, 1.0) * self.count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (idx, &count) in self.counts.iter().enumerate() {
+            seen += count;
+            if seen >= rank {
+                let bound = LATENCY_BUCKETS_US.get(idx).copied().unwrap_or(u64::MAX);
+                return bound.min(self.max_us);
+            }
+        }
+        self.max_us
+    }
+
+    pub fn summary(&self) -> LatencySummary {
+        LatencySummary {
+            count: self.count,
+            p50_us: self.percentile(0.50),
+            p95_us: self.percentile(0.95),
+            p99_us: self.percentile(0.99),
+            max_us: self.max_us,
+        }
+    }
+}
+
+/// Percentile snapshot for one RPC method.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatencySummary {
+    pub count: u64,
+    pub p50_us: u64,
+    pub p95_us: u64,
+    pub p99_us: u64,
+    pub max_us: u64,
+}
+
+/// Latency buckets keyed by RPC method name.
+#[derive(Debug, Default)]
+pub struct MethodLatencies {
+    methods: Mutex<BTreeMap<String, LatencyBuckets>>,
+}
+
+impl MethodLatencies {
+    pub const fn new() -> Self {
+        Self {
+            methods: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub fn record(&self, method: &str, duration: Duration) {
+        let mut methods = self
+            .methods
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let key = if methods.contains_key(method) || methods.len() < MAX_TRACKED_METHODS {
+            method
+        } else {
+            OTHER_METHOD
+        };
+        methods.entry(key.to_string()).or_default().record(duration);
+    }
+
+    pub fn summary(&self) -> BTreeMap<String, LatencySummary> {
+        self.methods
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .iter()
+            .map(|(method, buckets)| (method.clone(), buckets.summary()))
+            .collect()
+    }
+
+    pub fn reset(&self) {
+        self.methods
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clear();
+    }
+}
+
+/// Request counts and approximate payload sizes across all transports.
+#[derive(Debug, Default)]
+pub struct RpcTraffic {
+    requests: AtomicU64,
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+    methods: Mutex<BTreeMap<String, u64>>,
+}
+
+/// Snapshot of [`RpcTraffic`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RpcTrafficTotals {
+    pub requests: u64,
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    pub methods: BTreeMap<String, u64>,
+}
+
+impl RpcTraffic {
+    pub const fn new() -> Self {
+        Self {
+            requests: AtomicU64::new(0),
+            bytes_in: AtomicU64::new(0),
+            bytes_out: AtomicU64::new(0),
+            methods: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Count one incoming message of `bytes` length. `method` is `None` when
+    /// the message could not be parsed.
+    pub fn record_request(&self, method: Option<&str>, bytes: usize) {
+        self.requests.fetch_add(1, Ordering::Relaxed);
+        self.bytes_in.fetch_add(bytes as u64, Ordering::Relaxed);
+        let Some(method) = method else {
+            return;
+        };
+        let mut methods = self
+            .methods
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let key = if methods.contains_key(method) || methods.len() < MAX_TRACKED_METHODS {
+            method
+        } else {
+            OTHER_METHOD
+        };
+        *methods.entry(key.to_string()).or_default() += 1;
+    }
+
+    /// Count `bytes` of serialized responses or notifications sent.
+    pub fn record_bytes_out(&self, bytes: usize) {
+        self.bytes_out.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub fn totals(&self) -> RpcTrafficTotals {
+        RpcTrafficTotals {
+            requests: self.requests.load(Ordering::Relaxed),
+            bytes_in: self.bytes_in.load(Ordering::Relaxed),
+            bytes_out: self.bytes_out.load(Ordering::Relaxed),
+            methods: self
+                .methods
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .clone(),
+        }
+    }
+
+    pub fn reset(&self) {
+        self.requests.store(0, Ordering::Relaxed);
+        self.bytes_in.store(0, Ordering::Relaxed);
+        self.bytes_out.store(0, Ordering::Relaxed);
+        self.methods
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clear();
+    }
+}
+
+/// Per-reconcile created/updated/ended counts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReconcileCounts {
+    pub sessions_created: u64,
+    pub sessions_updated: u64,
+    pub sessions_ended: u64,
+    pub panes_created: u64,
+    pub panes_updated: u64,
+}
+
+/// Cumulative reconcile activity since start (or the last reset).
+#[derive(Debug, Default)]
+pub struct ReconcileCounters {
+    runs: AtomicU64,
+    sessions_created: AtomicU64,
+    sessions_updated: AtomicU64,
+    sessions_ended: AtomicU64,
+    panes_created: AtomicU64,
+    panes_updated: AtomicU64,
+}
+
+/// Snapshot of [`ReconcileCounters`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReconcileTotals {
+    pub runs: u64,
+    pub counts: ReconcileCounts,
+}
+
+impl ReconcileCounters {
+    pub const fn new() -> Self {
+        Self {
+            runs: AtomicU64::new(0),
+            sessions_created: AtomicU64::new(0),
+            sessions_updated: AtomicU64::new(0),
+            sessions_ended: AtomicU64::new(0),
+            panes_created: AtomicU64::new(0),
+            panes_updated: AtomicU64::new(0),
+        }
+    }
+
+    /// Add one reconcile's counts to the running totals.
+    pub fn record(&self, counts: ReconcileCounts) {
+        self.runs.fetch_add(1, Ordering::Relaxed);
+        self.sessions_created
+            .fetch_add(counts.sessions_created, Ordering::Relaxed);
+        self.sessions_updated
+            .fetch_add(counts.sessions_updated, Ordering::Relaxed);
+        self.sessions_ended
+            .fetch_add(counts.sessions_ended, Ordering::Relaxed);
+        self.panes_created
+            .fetch_add(counts.panes_created, Ordering::Relaxed);
+        self.panes_updated
+            .fetch_add(counts.panes_updated, Ordering::Relaxed);
+    }
+
+    pub fn totals(&self) -> ReconcileTotals {
+        ReconcileTotals {
+            runs: self.runs.load(Ordering::Relaxed),
+            counts: ReconcileCounts {
+                sessions_created: self.sessions_created.load(Ordering::Relaxed),
+                sessions_updated: self.sessions_updated.load(Ordering::Relaxed),
+                sessions_ended: self.sessions_ended.load(Ordering::Relaxed),
+                panes_created: self.panes_created.load(Ordering::Relaxed),
+                panes_updated: self.panes_updated.load(Ordering::Relaxed),
+            },
+        }
+    }
+
+    pub fn reset(&self) {
+        self.runs.store(0, Ordering::Relaxed);
+        self.sessions_created.store(0, Ordering::Relaxed);
+        self.sessions_updated.store(0, Ordering::Relaxed);
+        self.sessions_ended.store(0, Ordering::Relaxed);
+        self.panes_created.store(0, Ordering::Relaxed);
+        self.panes_updated.store(0, Ordering::Relaxed);
+    }
+}
+
+/// How one collector poll went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PollOutcome {
+    Ok,
+    /// The poll ran but reported itself degraded.
+    Degraded,
+    /// The poll returned an error.
+    Failed,
+}
+
+/// Poll outcomes for one polling loop. The current error streak lives in
+/// the cache (`Cache::poll_error_streak`).
+#[derive(Debug, Default)]
+pub struct PollCounters {
+    ticks: AtomicU64,
+    ok: AtomicU64,
+    degraded: AtomicU64,
+    failed: AtomicU64,
+}
+
+/// Snapshot of [`PollCounters`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PollTotals {
+    pub ticks: u64,
+    pub ok: u64,
+    pub degraded: u64,
+    pub failed: u64,
+}
+
+impl PollCounters {
+    pub const fn new() -> Self {
+        Self {
+            ticks: AtomicU64::new(0),
+            ok: AtomicU64::new(0),
+            degraded: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+        }
+    }
+
+    /// Count one poll.
+    pub fn record(&self, outcome: PollOutcome) {
+        self.ticks.fetch_add(1, Ordering::Relaxed);
+        let counter = match outcome {
+            PollOutcome::Ok => &self.ok,
+            PollOutcome::Degraded => &self.degraded,
+            PollOutcome::Failed => &self.failed,
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn totals(&self) -> PollTotals {
+        PollTotals {
+            ticks: self.ticks.load(Ordering::Relaxed),
+            ok: self.ok.load(Ordering::Relaxed),
+            degraded: self.degraded.load(Ordering::Relaxed),
+            failed: self.failed.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn reset(&self) {
+        self.ticks.store(0, Ordering::Relaxed);
+        self.ok.store(0, Ordering::Relaxed);
+        self.degraded.store(0, Ordering::Relaxed);
+        self.failed.store(0, Ordering::Relaxed);
+    }
+}
+
 /// Global metrics storage.
 pub struct Metrics {
     /// tmux command execution times
@@ -112,6 +450,18 @@ pub struct Metrics {
     pub db_write: Histogram,
     /// RPC request handling time
     pub rpc_request: Histogram,
+    /// Per-method `rpc::handle` latency
+    pub rpc_methods: MethodLatencies,
+    /// Request counts and bytes in/out across transports
+    pub rpc_traffic: RpcTraffic,
+    /// Cumulative ntm reconcile counts
+    pub reconcile: ReconcileCounters,
+    /// tmux collector poll outcomes
+    pub tmux_poll: PollCounters,
+    /// ntm collector poll outcomes
+    pub ntm_poll: PollCounters,
+    /// Snapshot notifier outcomes
+    pub snapshot_poll: PollCounters,
 }
 
 impl Default for Metrics {
@@ -129,6 +479,12 @@ impl Metrics {
             event_processing: Histogram::new(),
             db_write: Histogram::new(),
             rpc_request: Histogram::new(),
+            rpc_methods: MethodLatencies::new(),
+            rpc_traffic: RpcTraffic::new(),
+            reconcile: ReconcileCounters::new(),
+            tmux_poll: PollCounters::new(),
+            ntm_poll: PollCounters::new(),
+            snapshot_poll: PollCounters::new(),
         }
     }
 
@@ -141,6 +497,12 @@ impl Metrics {
             event_processing: self.event_processing.stats(),
             db_write: self.db_write.stats(),
             rpc_request: self.rpc_request.stats(),
+            rpc_methods: self.rpc_methods.summary(),
+            rpc_traffic: self.rpc_traffic.totals(),
+            reconcile: self.reconcile.totals(),
+            tmux_poll: self.tmux_poll.totals(),
+            ntm_poll: self.ntm_poll.totals(),
+            snapshot_poll: self.snapshot_poll.totals(),
         }
     }
 
@@ -152,6 +514,12 @@ impl Metrics {
         self.event_processing.reset();
         self.db_write.reset();
         self.rpc_request.reset();
+        self.rpc_methods.reset();
+        self.rpc_traffic.reset();
+        self.reconcile.reset();
+        self.tmux_poll.reset();
+        self.ntm_poll.reset();
+        self.snapshot_poll.reset();
     }
 }
 
@@ -164,6 +532,12 @@ pub struct MetricsSummary {
     pub event_processing: HistogramStats,
     pub db_write: HistogramStats,
     pub rpc_request: HistogramStats,
+    pub rpc_methods: BTreeMap<String, LatencySummary>,
+    pub rpc_traffic: RpcTrafficTotals,
+    pub reconcile: ReconcileTotals,
+    pub tmux_poll: PollTotals,
+    pub ntm_poll: PollTotals,
+    pub snapshot_poll: PollTotals,
 }
 
 /// RAII timer that records duration on drop.
@@ -200,6 +574,81 @@ macro_rules! time_it {
 mod tests {
     use super::*;
 
+    #[test]
+    fn rpc_traffic_counts_requests_and_bytes() {
+        let traffic = RpcTraffic::new();
+        traffic.record_request(Some("health.get"), 40);
+        traffic.record_request(Some("health.get"), 40);
+        traffic.record_request(Some("sessions.list"), 60);
+        traffic.record_request(None, 12);
+        traffic.record_bytes_out(300);
+
+        let totals = traffic.totals();
+        assert_eq!(totals.requests, 4);
+        assert_eq!(totals.bytes_in, 152);
+        assert_eq!(totals.bytes_out, 300);
+        assert_eq!(totals.methods["health.get"], 2);
+        assert_eq!(totals.methods["sessions.list"], 1);
+        assert_eq!(totals.methods.len(), 2);
+
+        traffic.reset();
+        assert_eq!(traffic.totals(), RpcTrafficTotals::default());
+    }
+
+    #[test]
+    fn poll_counters_count_outcomes() {
+        let counters = PollCounters::new();
+        counters.record(PollOutcome::Ok);
+        counters.record(PollOutcome::Degraded);
+        counters.record(PollOutcome::Failed);
+        counters.record(PollOutcome::Failed);
+        assert_eq!(
+            counters.totals(),
+            PollTotals {
+                ticks: 4,
+                ok: 1,
+                degraded: 1,
+                failed: 2,
+            }
+        );
+
+        counters.reset();
+        assert_eq!(counters.totals(), PollTotals::default());
+    }
+
+    #[test]
+    fn reconcile_counters_accumulate() {
+        let counters = ReconcileCounters::new();
+        counters.record(ReconcileCounts {
+            sessions_created: 2,
+            sessions_updated: 1,
+            sessions_ended: 0,
+            panes_created: 3,
+            panes_updated: 0,
+        });
+        counters.record(ReconcileCounts {
+            sessions_updated: 3,
+            sessions_ended: 1,
+            panes_updated: 3,
+            ..ReconcileCounts::default()
+        });
+        let totals = counters.totals();
+        assert_eq!(totals.runs, 2);
+        assert_eq!(
+            totals.counts,
+            ReconcileCounts {
+                sessions_created: 2,
+                sessions_updated: 4,
+                sessions_ended: 1,
+                panes_created: 3,
+                panes_updated: 3,
+            }
+        );
+
+        counters.reset();
+        assert_eq!(counters.totals(), ReconcileTotals::default());
+    }
+
     #[test]
     fn histogram_records_values() {
         let h = Histogram::new();
@@ -245,4 +694,44 @@ mod tests {
         let summary = METRICS.summary();
         assert!(summary.tmux_cmd.count >= 1);
     }
+
+    #[test]
+    fn latency_percentiles_from_known_durations() {
+        let mut buckets = LatencyBuckets::default();
+        for _ in 0..50 {
+            buckets.record(Duration::from_millis(1));
+        }
+        for _ in 0..45 {
+            buckets.record(Duration::from_millis(5));
+        }
+        for _ in 0..5 {
+            buckets.record(Duration::from_millis(40));
+        }
+
+        let summary = buckets.summary();
+        assert_eq!(summary.count, 100);
+        assert_eq!(summary.p50_us, 1_000);
+        assert_eq!(summary.p95_us, 5_000);
+        assert_eq!(summary.p99_us, 40_000, "capped at the max observed value");
+        assert_eq!(summary.max_us, 40_000);
+    }
+
+    #[test]
+    fn latency_percentile_empty_and_overflow() {
+        let mut buckets = LatencyBuckets::default();
+        assert_eq!(buckets.percentile(0.5), 0);
+        buckets.record(Duration::from_secs(60));
+        assert_eq!(buckets.percentile(0.99), 60_000_000);
+    }
+
+    #[test]
+    fn method_latencies_bound_cardinality() {
+        let latencies = MethodLatencies::new();
+        for i in 0..MAX_TRACKED_METHODS + 10 {
+            latencies.record(&format!("method.{i}"), Duration::from_micros(10));
+        }
+        let summary = latencies.summary();
+        assert_eq!(summary.len(), MAX_TRACKED_METHODS + 1);
+        assert_eq!(summary[OTHER_METHOD].count, 10);
+    }
 }
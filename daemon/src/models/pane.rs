@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use uuid::Uuid;
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -20,6 +21,17 @@ impl PaneStatus {
             PaneStatus::Ended => "ended",
         }
     }
+
+    /// Inverse of [`PaneStatus::as_str`]; unrecognised values map to `Unknown`.
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "active" => PaneStatus::Active,
+            "waiting" => PaneStatus::Waiting,
+            "idle" => PaneStatus::Idle,
+            "ended" => PaneStatus::Ended,
+            _ => PaneStatus::Unknown,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -38,6 +50,25 @@ pub struct Pane {
     pub ended_at: Option<i64>,
     pub status: PaneStatus,
     pub status_reason: Option<String>,
+    /// Working directory and environment, when capture collects them.
+    /// Held in memory only.
+    #[serde(default)]
+    pub context: Option<PaneContext>,
+}
+
+/// What a pane is working in, gathered by the tmux collector
+/// (`capture.capture-cwd`, `capture.env-allowlist`).
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct PaneContext {
+    pub cwd: Option<String>,
+    /// Allowlisted session environment variables, values redacted.
+    pub env: BTreeMap<String, String>,
+}
+
+impl PaneContext {
+    pub fn is_empty(&self) -> bool {
+        self.cwd.is_none() && self.env.is_empty()
+    }
 }
 
 impl Pane {
@@ -64,6 +95,7 @@ impl Pane {
             ended_at: None,
             status: PaneStatus::Unknown,
             status_reason: None,
+            context: None,
         }
     }
 }
@@ -1,3 +1,4 @@
+use crate::models::source::Source;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -18,14 +19,27 @@ impl SessionStatus {
             SessionStatus::Ended => "ended",
         }
     }
+
+    /// Inverse of [`SessionStatus::as_str`]; unrecognised values map to `Unknown`.
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "active" => SessionStatus::Active,
+            "idle" => SessionStatus::Idle,
+            "ended" => SessionStatus::Ended,
+            _ => SessionStatus::Unknown,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Session {
     pub session_uid: String,
-    pub source_id: String,
+    pub source_id: Source,
     pub tmux_session_id: Option<String>,
     pub name: String,
+    /// The source's own name when `name` holds a client-assigned alias.
+    #[serde(default)]
+    pub raw_name: Option<String>,
     pub created_at: i64,
     pub last_seen_at: i64,
     pub ended_at: Option<i64>,
@@ -37,7 +51,7 @@ pub struct Session {
 
 impl Session {
     pub fn new(
-        source_id: impl Into<String>,
+        source_id: impl Into<Source>,
         name: impl Into<String>,
         tmux_session_id: Option<String>,
         now: i64,
@@ -47,6 +61,7 @@ impl Session {
             source_id: source_id.into(),
             tmux_session_id,
             name: name.into(),
+            raw_name: None,
             created_at: now,
             last_seen_at: now,
             ended_at: None,
@@ -56,4 +71,9 @@ impl Session {
             metadata: None,
         }
     }
+
+    /// The name the source reports, ignoring any alias.
+    pub fn source_name(&self) -> &str {
+        self.raw_name.as_deref().unwrap_or(&self.name)
+    }
 }
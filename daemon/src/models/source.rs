@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Where a session was discovered. Serialized as the plain strings used
+/// before this type existed (`"ntm"`, `"tmux"`); any other value is kept
+/// verbatim in `Other` so newer sources round-trip.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum Source {
+    Ntm,
+    Tmux,
+    Other(String),
+}
+
+impl Source {
+    pub fn as_str(&self) -> &str {
+        match self {
+            Source::Ntm => "ntm",
+            Source::Tmux => "tmux",
+            Source::Other(other) => other,
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "ntm" => Source::Ntm,
+            "tmux" => Source::Tmux,
+            other => Source::Other(other.to_string()),
+        }
+    }
+}
+
+impl From<&str> for Source {
+    fn from(value: &str) -> Self {
+        Source::parse(value)
+    }
+}
+
+impl From<String> for Source {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "ntm" => Source::Ntm,
+            "tmux" => Source::Tmux,
+            _ => Source::Other(value),
+        }
+    }
+}
+
+impl From<Source> for String {
+    fn from(source: Source) -> Self {
+        match source {
+            Source::Other(other) => other,
+            known => known.as_str().to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_sources_round_trip_as_plain_strings() {
+        for (source, raw) in [(Source::Ntm, "\"ntm\""), (Source::Tmux, "\"tmux\"")] {
+            assert_eq!(serde_json::to_string(&source).unwrap(), raw);
+            assert_eq!(serde_json::from_str::<Source>(raw).unwrap(), source);
+        }
+    }
+
+    #[test]
+    fn unknown_source_is_kept_verbatim() {
+        let source: Source = serde_json::from_str("\"wezterm\"").unwrap();
+        assert_eq!(source, Source::Other("wezterm".to_string()));
+        assert_eq!(serde_json::to_string(&source).unwrap(), "\"wezterm\"");
+        // Matching is exact, so a differently cased name is not ntm
+        assert_eq!(Source::parse("NTM"), Source::Other("NTM".to_string()));
+    }
+}
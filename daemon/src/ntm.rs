@@ -43,6 +43,12 @@ impl NtmClient {
     }
 
     pub async fn robot_markdown(&self) -> Result<NtmMarkdown, NtmError> {
+        let text = self.robot_markdown_raw().await?;
+        parse_ntm_markdown(&text).map_err(|err| NtmError::ParseFailed(err.reason))
+    }
+
+    /// Fetch `--robot-markdown` output without parsing it.
+    pub async fn robot_markdown_raw(&self) -> Result<String, NtmError> {
         let spec = CommandSpec {
             program: self.config.ntm_path.clone(),
             args: vec![
@@ -59,8 +65,7 @@ impl NtmClient {
             .run(spec)
             .await
             .map_err(map_command_error)?;
-        let text = String::from_utf8_lossy(&output.stdout);
-        parse_ntm_markdown(&text).map_err(|err| NtmError::ParseFailed(err.reason))
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
     }
 
     pub async fn robot_tail(&self, session: &str, lines: u32) -> Result<NtmTail, NtmError> {
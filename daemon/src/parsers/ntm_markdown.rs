@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::HashMap;
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -59,7 +60,17 @@ pub fn parse_ntm_markdown(input: &str) -> Result<NtmMarkdown, ParseError> {
     Ok(NtmMarkdown { sessions, panes })
 }
 
+/// Convert CRLF and bare CR line endings (WSL, Windows terminals) to LF.
+fn normalize_line_endings(input: &str) -> Cow<'_, str> {
+    if input.contains('\r') {
+        Cow::Owned(input.replace("\r\n", "\n").replace('\r', "\n"))
+    } else {
+        Cow::Borrowed(input)
+    }
+}
+
 fn parse_markdown_table(input: &str) -> Result<Vec<HashMap<String, String>>, ParseError> {
+    let input = normalize_line_endings(input);
     let mut lines = input.lines().filter(|line| line.contains('|'));
     let header = lines
         .next()
@@ -115,6 +126,33 @@ fn get_field(row: &HashMap<String, String>, keys: &[&str]) -> Option<String> {
 mod tests {
     use super::*;
 
+    const LF_DOC: &str = "| session | pane | status | agent |\n| --- | --- | --- | --- |\n| api | 0 | active | claude |\n| api | 1 | idle | codex |\n";
+
+    #[test]
+    fn crlf_and_lf_documents_parse_identically() {
+        let expected = parse_ntm_markdown(LF_DOC).expect("parse lf");
+        let crlf = LF_DOC.replace('\n', "\r\n");
+        let parsed = parse_ntm_markdown(&crlf).expect("parse crlf");
+        assert_eq!(parsed, expected);
+        assert_eq!(parsed.panes.len(), 2);
+        assert_eq!(parsed.panes[1].agent.as_deref(), Some("codex"));
+    }
+
+    #[test]
+    fn bare_cr_line_endings_parse_identically() {
+        let expected = parse_ntm_markdown(LF_DOC).expect("parse lf");
+        let cr = LF_DOC.replace('\n', "\r");
+        let parsed = parse_ntm_markdown(&cr).expect("parse cr");
+        assert_eq!(parsed, expected);
+        assert_eq!(parsed.panes.len(), 2);
+    }
+
+    #[test]
+    fn normalize_line_endings_borrows_lf_input() {
+        assert!(matches!(normalize_line_endings("a\nb"), Cow::Borrowed(_)));
+        assert_eq!(normalize_line_endings("a\r\nb\rc"), "a\nb\nc");
+    }
+
     #[test]
     fn parses_markdown_rows() {
         let markdown = "| session | pane | status |\n| --- | --- | --- |\n| alpha | 0 | active |";
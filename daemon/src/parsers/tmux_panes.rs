@@ -5,6 +5,7 @@ pub struct TmuxPaneMeta {
     pub session_id: String,
     pub session_name: String,
     pub window_id: String,
+    pub window_index: i32,
     pub pane_id: String,
     pub pane_index: i32,
     pub pane_pid: i64,
@@ -14,60 +15,182 @@ pub struct TmuxPaneMeta {
     pub pane_in_mode: bool,
 }
 
+impl TmuxPaneMeta {
+    /// tmux target in `session:window.pane` form.
+    pub fn target(&self) -> String {
+        format!("{}:{}.{}", self.session_name, self.window_index, self.pane_index)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ParseError {
     pub line: String,
     pub reason: String,
 }
 
+/// Default `list-panes -F` format; fields are `:`-separated.
+pub const DEFAULT_FORMAT: &str = "#{session_id}:#{session_name}:#{window_id}:#{window_index}:#{pane_id}:#{pane_index}:#{pane_pid}:#{pane_current_command}:#{pane_last_activity}:#{pane_dead}:#{pane_in_mode}";
+
+/// Fields every format must include to identify a pane and its session.
+pub const REQUIRED_FIELDS: &[&str] = &[
+    "session_id",
+    "session_name",
+    "window_id",
+    "pane_id",
+    "pane_index",
+];
+
+/// Field order of a `list-panes -F` format string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TmuxFormat {
+    fields: Vec<String>,
+}
+
+impl Default for TmuxFormat {
+    fn default() -> Self {
+        Self::parse(DEFAULT_FORMAT).expect("default tmux format is valid")
+    }
+}
+
+impl TmuxFormat {
+    /// Parse a format of `#{field}` placeholders joined by `:`.
+    ///
+    /// Fields outside the known set are accepted and ignored when parsing
+    /// output; every entry in [`REQUIRED_FIELDS`] must be present exactly once.
+    pub fn parse(format: &str) -> Result<Self, String> {
+        let mut fields = Vec::new();
+        for segment in format.split(':') {
+            let name = segment
+                .strip_prefix("#{")
+                .and_then(|rest| rest.strip_suffix('}'))
+                .filter(|name| !name.is_empty() && !name.contains(['#', '{', '}']))
+                .ok_or_else(|| format!("segment '{segment}' is not a #{{field}} placeholder"))?;
+            if fields.iter().any(|existing| existing == name) {
+                return Err(format!("field '{name}' appears more than once"));
+            }
+            fields.push(name.to_string());
+        }
+        for required in REQUIRED_FIELDS {
+            if !fields.iter().any(|field| field == required) {
+                return Err(format!("missing required field #{{{required}}}"));
+            }
+        }
+        Ok(Self { fields })
+    }
+
+    /// The `-F` argument that produces this layout.
+    pub fn to_format_string(&self) -> String {
+        self.fields
+            .iter()
+            .map(|field| format!("#{{{field}}}"))
+            .collect::<Vec<_>>()
+            .join(":")
+    }
+
+    fn position(&self, field: &str) -> Option<usize> {
+        self.fields.iter().position(|name| name == field)
+    }
+
+    /// Split a line into one value per field. `pane_current_command` may
+    /// contain `:`, so it absorbs whatever the fields around it leave over.
+    fn split<'a>(&self, line: &'a str) -> Option<Vec<&'a str>> {
+        let count = self.fields.len();
+        let Some(command) = self.position("pane_current_command") else {
+            let parts: Vec<&str> = line.split(':').collect();
+            return (parts.len() == count).then_some(parts);
+        };
+
+        let mut leading: Vec<&str> = line.splitn(command + 1, ':').collect();
+        if leading.len() != command + 1 {
+            return None;
+        }
+        let rest = leading.pop()?;
+        let trailing_count = count - command - 1;
+        let mut trailing: Vec<&str> = rest.rsplitn(trailing_count + 1, ':').collect();
+        if trailing.len() != trailing_count + 1 {
+            return None;
+        }
+        trailing.reverse();
+        leading.extend(trailing);
+        Some(leading)
+    }
+}
+
 pub fn parse_tmux_panes(output: &str) -> Result<Vec<TmuxPaneMeta>, ParseError> {
+    parse_tmux_panes_with(output, &TmuxFormat::default())
+}
+
+/// Parse `list-panes` output produced with `format`.
+pub fn parse_tmux_panes_with(
+    output: &str,
+    format: &TmuxFormat,
+) -> Result<Vec<TmuxPaneMeta>, ParseError> {
     let mut metas = Vec::new();
     for line in output.lines().filter(|line| !line.trim().is_empty()) {
-        let meta = parse_line(line)?;
+        let meta = parse_line(line.trim_end_matches('\r'), format)?;
         metas.push(meta);
     }
     Ok(metas)
 }
 
-fn parse_line(line: &str) -> Result<TmuxPaneMeta, ParseError> {
-    let parts: Vec<&str> = line.splitn(10, ':').collect();
-    if parts.len() != 10 {
-        return Err(ParseError {
-            line: line.to_string(),
-            reason: "expected 10 fields".to_string(),
-        });
+fn parse_line(line: &str, format: &TmuxFormat) -> Result<TmuxPaneMeta, ParseError> {
+    let invalid = |reason: &str| ParseError {
+        line: line.to_string(),
+        reason: reason.to_string(),
+    };
+
+    let parts = format
+        .split(line)
+        .ok_or_else(|| invalid(&format!("expected {} fields", format.fields.len())))?;
+    let field = |name: &str| format.position(name).map(|idx| parts[idx]);
+    let text = |name: &str| field(name).unwrap_or_default().to_string();
+
+    let window_index = match field("window_index") {
+        Some(raw) => raw
+            .parse::<i32>()
+            .map_err(|_| invalid("invalid window_index"))?,
+        None => 0,
+    };
+
+    let pane_id = text("pane_id");
+    if !pane_id.starts_with('%') {
+        return Err(invalid("invalid pane_id"));
     }
 
-    let pane_index = parts[4].parse::<i32>().map_err(|_| ParseError {
-        line: line.to_string(),
-        reason: "invalid pane_index".to_string(),
-    })?;
+    let pane_index = text("pane_index")
+        .parse::<i32>()
+        .map_err(|_| invalid("invalid pane_index"))?;
 
-    let pane_pid = parts[5].parse::<i64>().map_err(|_| ParseError {
-        line: line.to_string(),
-        reason: "invalid pane_pid".to_string(),
-    })?;
+    let pane_pid = match field("pane_pid") {
+        Some(raw) => raw.parse::<i64>().map_err(|_| invalid("invalid pane_pid"))?,
+        None => 0,
+    };
 
-    let pane_last_activity = if parts[7].is_empty() {
-        0
-    } else {
-        parts[7].parse::<i64>().map_err(|_| ParseError {
-            line: line.to_string(),
-            reason: "invalid pane_last_activity".to_string(),
-        })?
+    let pane_last_activity = match field("pane_last_activity") {
+        None | Some("") => 0,
+        Some(raw) => raw
+            .parse::<i64>()
+            .map_err(|_| invalid("invalid pane_last_activity"))?,
     };
 
-    let pane_dead = parse_bool(parts[8], line, "pane_dead")?;
-    let pane_in_mode = parse_bool(parts[9], line, "pane_in_mode")?;
+    let pane_dead = match field("pane_dead") {
+        Some(raw) => parse_bool(raw, line, "pane_dead")?,
+        None => false,
+    };
+    let pane_in_mode = match field("pane_in_mode") {
+        Some(raw) => parse_bool(raw, line, "pane_in_mode")?,
+        None => false,
+    };
 
     Ok(TmuxPaneMeta {
-        session_id: parts[0].to_string(),
-        session_name: parts[1].to_string(),
-        window_id: parts[2].to_string(),
-        pane_id: parts[3].to_string(),
+        session_id: text("session_id"),
+        session_name: text("session_name"),
+        window_id: text("window_id"),
+        window_index,
+        pane_id,
         pane_index,
         pane_pid,
-        pane_current_command: parts[6].to_string(),
+        pane_current_command: text("pane_current_command"),
         pane_last_activity,
         pane_dead,
         pane_in_mode,
@@ -91,7 +214,7 @@ mod tests {
 
     #[test]
     fn parses_tmux_line() {
-        let line = "$1:mysession:@2:%3:0:111:fish:1700000000:0:1";
+        let line = "$1:mysession:@2:0:%3:0:111:fish:1700000000:0:1";
         let meta = parse_tmux_panes(line).expect("parse").remove(0);
         assert_eq!(meta.session_id, "$1");
         assert_eq!(meta.session_name, "mysession");
@@ -106,7 +229,7 @@ mod tests {
 
     #[test]
     fn parses_multiple_lines() {
-        let input = "$1:sess1:@2:%3:0:111:fish:1700000000:0:0\n$1:sess1:@2:%4:1:222:vim:1700000001:0:0";
+        let input = "$1:sess1:@2:0:%3:0:111:fish:1700000000:0:0\n$1:sess1:@2:0:%4:1:222:vim:1700000001:0:0";
         let metas = parse_tmux_panes(input).expect("parse");
         assert_eq!(metas.len(), 2);
         assert_eq!(metas[0].pane_id, "%3");
@@ -128,15 +251,15 @@ mod tests {
 
     #[test]
     fn fails_on_insufficient_fields() {
-        let line = "$1:sess:@2:%3:0:111:fish";
+        let line = "$1:sess:@2:0:%3:0:111:fish";
         let result = parse_tmux_panes(line);
         assert!(result.is_err());
-        assert!(result.unwrap_err().reason.contains("expected 10 fields"));
+        assert!(result.unwrap_err().reason.contains("expected 11 fields"));
     }
 
     #[test]
     fn fails_on_invalid_pane_index() {
-        let line = "$1:sess:@2:%3:abc:111:fish:1700000000:0:0";
+        let line = "$1:sess:@2:0:%3:abc:111:fish:1700000000:0:0";
         let result = parse_tmux_panes(line);
         assert!(result.is_err());
         assert!(result.unwrap_err().reason.contains("invalid pane_index"));
@@ -144,7 +267,7 @@ mod tests {
 
     #[test]
     fn fails_on_invalid_pid() {
-        let line = "$1:sess:@2:%3:0:notapid:fish:1700000000:0:0";
+        let line = "$1:sess:@2:0:%3:0:notapid:fish:1700000000:0:0";
         let result = parse_tmux_panes(line);
         assert!(result.is_err());
         assert!(result.unwrap_err().reason.contains("invalid pane_pid"));
@@ -152,7 +275,7 @@ mod tests {
 
     #[test]
     fn fails_on_invalid_timestamp() {
-        let line = "$1:sess:@2:%3:0:111:fish:notanumber:0:0";
+        let line = "$1:sess:@2:0:%3:0:111:fish:notanumber:0:0";
         let result = parse_tmux_panes(line);
         assert!(result.is_err());
         assert!(result.unwrap_err().reason.contains("invalid pane_last_activity"));
@@ -160,7 +283,7 @@ mod tests {
 
     #[test]
     fn fails_on_invalid_bool() {
-        let line = "$1:sess:@2:%3:0:111:fish:1700000000:2:0";
+        let line = "$1:sess:@2:0:%3:0:111:fish:1700000000:2:0";
         let result = parse_tmux_panes(line);
         assert!(result.is_err());
         assert!(result.unwrap_err().reason.contains("invalid pane_dead"));
@@ -168,7 +291,7 @@ mod tests {
 
     #[test]
     fn handles_dead_pane() {
-        let line = "$1:sess:@2:%3:0:111:fish:1700000000:1:0";
+        let line = "$1:sess:@2:0:%3:0:111:fish:1700000000:1:0";
         let meta = parse_tmux_panes(line).expect("parse").remove(0);
         assert!(meta.pane_dead);
         assert!(!meta.pane_in_mode);
@@ -176,15 +299,14 @@ mod tests {
 
     #[test]
     fn handles_large_pane_index() {
-        let line = "$1:sess:@2:%3:99:111:bash:1700000000:0:0";
+        let line = "$1:sess:@2:0:%3:99:111:bash:1700000000:0:0";
         let meta = parse_tmux_panes(line).expect("parse").remove(0);
         assert_eq!(meta.pane_index, 99);
     }
 
     #[test]
     fn handles_command_with_spaces() {
-        // Note: command field is limited by splitn(10) so spaces after 10th field are included
-        let line = "$1:sess:@2:%3:0:111:some command:1700000000:0:0";
+        let line = "$1:sess:@2:0:%3:0:111:some command:1700000000:0:0";
         let meta = parse_tmux_panes(line).expect("parse").remove(0);
         assert_eq!(meta.pane_current_command, "some command");
     }
@@ -192,7 +314,7 @@ mod tests {
     #[test]
     fn handles_empty_pane_last_activity() {
         // Some tmux versions return empty string for pane_last_activity
-        let line = "$1:cloud_function3:@1:%5:0:1572932:bash::0:0";
+        let line = "$1:cloud_function3:@1:0:%5:0:1572932:bash::0:0";
         let meta = parse_tmux_panes(line).expect("parse").remove(0);
         assert_eq!(meta.pane_last_activity, 0);
         assert_eq!(meta.pane_pid, 1572932);
@@ -202,21 +324,21 @@ mod tests {
 
     #[test]
     fn handles_unicode_session_name() {
-        let line = "$1:项目-α:@2:%3:0:111:fish:1700000000:0:0";
+        let line = "$1:项目-α:@2:0:%3:0:111:fish:1700000000:0:0";
         let meta = parse_tmux_panes(line).expect("parse").remove(0);
         assert_eq!(meta.session_name, "项目-α");
     }
 
     #[test]
     fn handles_pid_zero() {
-        let line = "$1:sess:@2:%3:0:0:bash:1700000000:0:0";
+        let line = "$1:sess:@2:0:%3:0:0:bash:1700000000:0:0";
         let meta = parse_tmux_panes(line).expect("parse").remove(0);
         assert_eq!(meta.pane_pid, 0);
     }
 
     #[test]
     fn handles_negative_pane_index() {
-        let line = "$1:sess:@2:%3:-1:111:bash:1700000000:0:0";
+        let line = "$1:sess:@2:0:%3:-1:111:bash:1700000000:0:0";
         let meta = parse_tmux_panes(line).expect("parse").remove(0);
         assert_eq!(meta.pane_index, -1);
     }
@@ -224,34 +346,117 @@ mod tests {
     #[test]
     fn handles_very_long_command() {
         let long_cmd = "a".repeat(1000);
-        let line = format!("$1:sess:@2:%3:0:111:{long_cmd}:1700000000:0:0");
+        let line = format!("$1:sess:@2:0:%3:0:111:{long_cmd}:1700000000:0:0");
         let meta = parse_tmux_panes(&line).expect("parse").remove(0);
         assert_eq!(meta.pane_current_command.len(), 1000);
     }
 
     #[test]
-    fn handles_colons_in_command_via_splitn() {
-        // splitn(10, ':') means the 10th field captures everything after the 9th colon
-        // But the command is field 7 (index 6), so extra colons would break parsing
-        // This verifies the actual behavior — fails because colons shift fields
-        let line = "$1:sess:@2:%3:0:111:cmd:with:colons:0:0";
-        // splitn(10) produces: ["$1","sess","@2","%3","0","111","cmd","with","colons","0:0"]
-        // field 6 = "cmd", field 7 = "with" (activity), field 8 = "colons" (dead)
-        // This will fail because "with" isn't a valid timestamp and "colons" isn't 0/1
-        let result = parse_tmux_panes(line);
-        assert!(result.is_err());
+    fn handles_colons_in_command() {
+        // The command sits between fixed leading and trailing fields, so colons
+        // inside it do not shift the fields after it
+        let line = "$1:sess:@2:0:%3:0:111:ssh host:22:1700000000:0:1";
+        let meta = parse_tmux_panes(line).expect("parse").remove(0);
+        assert_eq!(meta.pane_current_command, "ssh host:22");
+        assert_eq!(meta.pane_last_activity, 1700000000);
+        assert!(!meta.pane_dead);
+        assert!(meta.pane_in_mode);
+    }
+
+    #[test]
+    fn parses_window_and_session_context() {
+        let input = "$0:main:@0:0:%0:0:100:zsh:1700000000:0:0\r\n\
+                     $0:main:@1:1:%4:0:101:vim:1700000001:0:0\r\n\
+                     $0:main:@1:1:%5:1:102:cargo:1700000002:0:0\r\n\
+                     $3:work:@7:2:%9:3:103:bash:1700000003:0:0\r\n";
+        let metas = parse_tmux_panes(input).expect("parse");
+        assert_eq!(metas.len(), 4);
+
+        let targets: Vec<String> = metas.iter().map(TmuxPaneMeta::target).collect();
+        assert_eq!(targets, vec!["main:0.0", "main:1.0", "main:1.1", "work:2.3"]);
+
+        assert_eq!(metas[2].session_id, "$0");
+        assert_eq!(metas[2].window_id, "@1");
+        assert_eq!(metas[2].window_index, 1);
+        assert_eq!(metas[2].pane_id, "%5");
+        assert_eq!(metas[3].session_id, "$3");
+        assert_eq!(metas[3].window_id, "@7");
+        assert!(!metas[3].pane_dead);
+    }
+
+    #[test]
+    fn fails_on_invalid_window_index() {
+        let line = "$1:sess:@2:x:%3:0:111:fish:1700000000:0:0";
+        let err = parse_tmux_panes(line).unwrap_err();
+        assert!(err.reason.contains("invalid window_index"));
+    }
+
+    #[test]
+    fn default_format_round_trips() {
+        let format = TmuxFormat::default();
+        assert_eq!(format.to_format_string(), DEFAULT_FORMAT);
+    }
+
+    #[test]
+    fn custom_format_parses_in_its_field_order() {
+        let format = TmuxFormat::parse(
+            "#{pane_id}:#{pane_index}:#{session_name}:#{session_id}:#{window_id}:#{pane_current_command}:#{pane_title}",
+        )
+        .expect("format");
+        let metas =
+            parse_tmux_panes_with("%7:2:work:$4:@9:ssh a:b:my title\n", &format).expect("parse");
+        let meta = &metas[0];
+        assert_eq!(meta.pane_id, "%7");
+        assert_eq!(meta.pane_index, 2);
+        assert_eq!(meta.session_name, "work");
+        assert_eq!(meta.session_id, "$4");
+        assert_eq!(meta.window_id, "@9");
+        assert_eq!(meta.pane_current_command, "ssh a:b");
+        // Fields absent from the format take defaults
+        assert_eq!(meta.pane_pid, 0);
+        assert_eq!(meta.pane_last_activity, 0);
+        assert!(!meta.pane_dead);
+    }
+
+    #[test]
+    fn format_missing_required_field_is_rejected() {
+        let err = TmuxFormat::parse("#{session_id}:#{session_name}:#{pane_id}:#{pane_index}")
+            .unwrap_err();
+        assert!(err.contains("missing required field #{window_id}"), "{err}");
+    }
+
+    #[test]
+    fn format_with_non_placeholder_segment_is_rejected() {
+        let err = TmuxFormat::parse("#{session_id}:literal:#{session_name}").unwrap_err();
+        assert!(err.contains("'literal'"), "{err}");
+        let err = TmuxFormat::parse("#{pane_id}:#{pane_id}").unwrap_err();
+        assert!(err.contains("more than once"), "{err}");
+    }
+
+    #[test]
+    fn fails_on_legacy_ten_field_format() {
+        let line = "$1:sess:@2:%3:0:111:fish:1700000000:0:0";
+        let err = parse_tmux_panes(line).unwrap_err();
+        assert!(err.reason.contains("expected 11 fields"));
+    }
+
+    #[test]
+    fn fails_on_missing_pane_id_marker() {
+        let line = "$1:sess:@2:0:3:0:111:fish:1700000000:0:0";
+        let err = parse_tmux_panes(line).unwrap_err();
+        assert!(err.reason.contains("invalid pane_id"));
     }
 
     #[test]
     fn skips_blank_lines_between_entries() {
-        let input = "$1:s1:@1:%1:0:111:bash:1700000000:0:0\n\n\n$2:s2:@2:%2:1:222:vim:1700000001:0:0";
+        let input = "$1:s1:@1:0:%1:0:111:bash:1700000000:0:0\n\n\n$2:s2:@2:0:%2:1:222:vim:1700000001:0:0";
         let metas = parse_tmux_panes(input).expect("parse");
         assert_eq!(metas.len(), 2);
     }
 
     #[test]
     fn first_error_aborts_entire_parse() {
-        let input = "$1:s1:@1:%1:0:111:bash:1700000000:0:0\nbadline\n$2:s2:@2:%2:1:222:vim:1700000001:0:0";
+        let input = "$1:s1:@1:0:%1:0:111:bash:1700000000:0:0\nbadline\n$2:s2:@2:0:%2:1:222:vim:1700000001:0:0";
         let result = parse_tmux_panes(input);
         assert!(result.is_err());
     }
@@ -0,0 +1,364 @@
+//! Adaptive polling: pick an interval from session activity and health.
+
+use crate::cache::{Cache, PollChannel};
+use crate::clock::Clock;
+use crate::config::PollingConfig;
+use crate::models::session::Session;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PollingMode {
+    Active,
+    Idle,
+    Background,
+    Degraded,
+}
+
+impl PollingMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PollingMode::Active => "active",
+            PollingMode::Idle => "idle",
+            PollingMode::Background => "background",
+            PollingMode::Degraded => "degraded",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct PollingDecision {
+    pub mode: PollingMode,
+    pub reason: &'static str,
+    pub interval_ms: u64,
+}
+
+/// A daily window, in minutes since midnight at a fixed UTC offset, during
+/// which polling stays at the background interval.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuietHours {
+    start_min: u32,
+    end_min: u32,
+    offset_secs: i64,
+}
+
+impl QuietHours {
+    /// Parse `"HH:MM-HH:MM"`, optionally followed by `UTC`, `Z`, or a
+    /// `±HH:MM` offset. Without a suffix the times are UTC.
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let raw = raw.trim();
+        let (range, zone) = match raw.split_once(char::is_whitespace) {
+            Some((range, zone)) => (range, zone.trim()),
+            None => (raw, ""),
+        };
+        let (start, end) = range
+            .split_once('-')
+            .ok_or_else(|| format!("expected HH:MM-HH:MM, got '{range}'"))?;
+        let start_min = parse_hh_mm(start)?;
+        let end_min = parse_hh_mm(end)?;
+        if start_min == end_min {
+            return Err("start and end must differ".to_string());
+        }
+        let offset_secs = match zone {
+            "" | "UTC" | "utc" | "Z" => 0,
+            _ => {
+                let (sign, rest) = match zone.as_bytes()[0] {
+                    b'+' => (1, &zone[1..]),
+                    b'-' => (-1, &zone[1..]),
+                    _ => return Err(format!("unknown timezone '{zone}' (use UTC or ±HH:MM)")),
+                };
+                let minutes = parse_hh_mm(rest)
+                    .map_err(|_| format!("unknown timezone '{zone}' (use UTC or ±HH:MM)"))?;
+                sign * i64::from(minutes) * 60
+            }
+        };
+        Ok(Self {
+            start_min,
+            end_min,
+            offset_secs,
+        })
+    }
+
+    /// Whether unix time `now` falls inside the window. Windows that wrap
+    /// midnight (`22:00-06:00`) are handled; the end minute is exclusive.
+    pub fn contains(&self, now: i64) -> bool {
+        let minute = ((now + self.offset_secs).rem_euclid(86_400) / 60) as u32;
+        if self.start_min < self.end_min {
+            (self.start_min..self.end_min).contains(&minute)
+        } else {
+            minute >= self.start_min || minute < self.end_min
+        }
+    }
+}
+
+fn parse_hh_mm(raw: &str) -> Result<u32, String> {
+    let invalid = || format!("invalid time '{raw}' (expected HH:MM)");
+    let (hours, minutes) = raw.split_once(':').ok_or_else(invalid)?;
+    if hours.len() != 2 || minutes.len() != 2 {
+        return Err(invalid());
+    }
+    let hours: u32 = hours.parse().map_err(|_| invalid())?;
+    let minutes: u32 = minutes.parse().map_err(|_| invalid())?;
+    if hours > 23 || minutes > 59 {
+        return Err(invalid());
+    }
+    Ok(hours * 60 + minutes)
+}
+
+/// Whether `session` has been seen within its source's idle threshold.
+pub fn session_is_active(session: &Session, polling: &PollingConfig, now: i64) -> bool {
+    session.ended_at.is_none()
+        && now.saturating_sub(session.last_seen_at)
+            <= polling.idle_threshold_for(session.source_id.as_str())
+}
+
+/// Pick the next interval for the polling loop on `channel`. Quiet hours force
+/// background mode; any error streak recorded for that channel forces
+/// degraded mode, which takes precedence.
+pub fn compute_polling_decision(
+    cache: &Cache,
+    polling: &PollingConfig,
+    channel: PollChannel,
+    clock: &dyn Clock,
+) -> PollingDecision {
+    let now = clock.now_unix();
+    let sessions = cache.all_sessions();
+    let has_sessions = !sessions.is_empty();
+    let is_active = sessions
+        .iter()
+        .any(|session| session_is_active(session, polling, now));
+
+    let mut mode = if !has_sessions {
+        PollingMode::Background
+    } else if is_active {
+        PollingMode::Active
+    } else {
+        PollingMode::Idle
+    };
+
+    let mut reason = match mode {
+        PollingMode::Active => "recent_activity",
+        PollingMode::Idle => "idle_timeout",
+        PollingMode::Background => "no_sessions",
+        PollingMode::Degraded => "degraded",
+    };
+
+    let mut interval_ms = match mode {
+        PollingMode::Active => polling.snapshot_interval_ms,
+        PollingMode::Idle => polling.snapshot_idle_interval_ms,
+        PollingMode::Background => polling.snapshot_background_interval_ms,
+        PollingMode::Degraded => polling.snapshot_degraded_interval_ms,
+    };
+
+    // Validated at load, so an unparsable window is simply ignored here
+    let quiet = polling
+        .quiet_hours
+        .as_deref()
+        .and_then(|window| QuietHours::parse(window).ok())
+        .is_some_and(|window| window.contains(now));
+    if quiet {
+        mode = PollingMode::Background;
+        reason = "quiet_hours";
+        interval_ms = polling.snapshot_background_interval_ms;
+    }
+
+    let health = cache.health();
+    if cache.poll_error_streak(channel) > 0 {
+        mode = PollingMode::Degraded;
+        reason = "poll_errors";
+        interval_ms = polling.snapshot_degraded_interval_ms;
+    } else if !health.status.trim().is_empty() && health.status != "ok" {
+        mode = PollingMode::Degraded;
+        reason = "health_degraded";
+        interval_ms = polling.snapshot_degraded_interval_ms;
+    }
+
+    interval_ms = interval_ms.max(250);
+
+    PollingDecision {
+        mode,
+        reason,
+        interval_ms,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clock::MockClock;
+    use crate::models::session::SessionStatus;
+
+    fn session(last_seen_at: i64) -> Session {
+        session_from("src", last_seen_at)
+    }
+
+    fn session_from(source_id: &str, last_seen_at: i64) -> Session {
+        Session {
+            session_uid: format!("{source_id}-1"),
+            source_id: source_id.into(),
+            tmux_session_id: None,
+            name: "alpha".to_string(),
+            raw_name: None,
+            created_at: last_seen_at,
+            last_seen_at,
+            ended_at: None,
+            status: SessionStatus::Active,
+            status_reason: None,
+            pane_count: 0,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn mode_transitions_at_exact_idle_threshold() {
+        let cache = Cache::new(10);
+        let polling = PollingConfig::default();
+        let clock = MockClock::new(1_000);
+
+        let decision = compute_polling_decision(&cache, &polling, PollChannel::Snapshot, &clock);
+        assert_eq!(decision.mode, PollingMode::Background);
+
+        cache.upsert_session(session(1_000));
+        let decision = compute_polling_decision(&cache, &polling, PollChannel::Snapshot, &clock);
+        assert_eq!(decision.mode, PollingMode::Active);
+        assert_eq!(decision.interval_ms, polling.snapshot_interval_ms);
+
+        clock.set(1_000 + polling.idle_threshold_secs);
+        let decision = compute_polling_decision(&cache, &polling, PollChannel::Snapshot, &clock);
+        assert_eq!(decision.mode, PollingMode::Active, "threshold is inclusive");
+
+        clock.advance(1);
+        let decision = compute_polling_decision(&cache, &polling, PollChannel::Snapshot, &clock);
+        assert_eq!(decision.mode, PollingMode::Idle);
+        assert_eq!(decision.reason, "idle_timeout");
+        assert_eq!(decision.interval_ms, polling.snapshot_idle_interval_ms);
+    }
+
+    #[test]
+    fn per_source_threshold_classifies_idle_sooner() {
+        let mut polling = PollingConfig {
+            idle_threshold_secs: 600,
+            ..PollingConfig::default()
+        };
+        polling.idle_threshold_by_source.insert("tmux".to_string(), 60);
+        let tmux = session_from("tmux", 1_000);
+        let ntm = session_from("ntm", 1_000);
+
+        assert!(session_is_active(&tmux, &polling, 1_060));
+        assert!(!session_is_active(&tmux, &polling, 1_061));
+        assert!(session_is_active(&ntm, &polling, 1_061));
+        assert!(!session_is_active(&ntm, &polling, 1_601));
+    }
+
+    #[test]
+    fn per_source_threshold_drives_polling_mode() {
+        let cache = Cache::new(10);
+        let mut polling = PollingConfig::default();
+        polling.idle_threshold_by_source.insert("tmux".to_string(), 60);
+        let clock = MockClock::new(1_100);
+        cache.upsert_session(session_from("tmux", 1_000));
+
+        let decision = compute_polling_decision(&cache, &polling, PollChannel::Snapshot, &clock);
+        assert_eq!(decision.mode, PollingMode::Idle);
+
+        cache.upsert_session(session_from("ntm", 1_000));
+        let decision = compute_polling_decision(&cache, &polling, PollChannel::Snapshot, &clock);
+        assert_eq!(decision.mode, PollingMode::Active, "ntm uses the global 300s");
+    }
+
+    #[test]
+    fn quiet_hours_parse_formats() {
+        let window = QuietHours::parse("22:00-06:00").unwrap();
+        assert_eq!(
+            (window.start_min, window.end_min, window.offset_secs),
+            (1320, 360, 0)
+        );
+        assert_eq!(QuietHours::parse("22:00-06:00 UTC").unwrap(), window);
+        assert_eq!(
+            QuietHours::parse("01:30-02:00 -05:30").unwrap().offset_secs,
+            -19_800
+        );
+
+        for bad in [
+            "",
+            "22:00",
+            "22-06",
+            "24:00-06:00",
+            "22:00-06:60",
+            "09:00-09:00",
+            "22:00-06:00 PST",
+        ] {
+            assert!(
+                QuietHours::parse(bad).is_err(),
+                "{bad:?} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn quiet_hours_contains_wraps_midnight() {
+        let window = QuietHours::parse("22:00-06:00").unwrap();
+        let day = 19_000 * 86_400;
+        assert!(window.contains(day + 23 * 3600));
+        assert!(window.contains(day + 5 * 3600 + 59 * 60));
+        assert!(!window.contains(day + 6 * 3600), "end is exclusive");
+        assert!(!window.contains(day + 12 * 3600));
+
+        // 22:00 at +02:00 is 20:00 UTC
+        let shifted = QuietHours::parse("22:00-06:00 +02:00").unwrap();
+        assert!(shifted.contains(day + 20 * 3600));
+        assert!(!shifted.contains(day + 5 * 3600));
+    }
+
+    #[test]
+    fn quiet_hours_force_background_mode() {
+        let cache = Cache::new(10);
+        let polling = PollingConfig {
+            quiet_hours: Some("22:00-06:00".to_string()),
+            ..PollingConfig::default()
+        };
+        let day = 19_000 * 86_400;
+
+        // Active session at 23:00, inside quiet hours
+        let clock = MockClock::new(day + 23 * 3600);
+        cache.upsert_session(session(day + 23 * 3600));
+        let decision = compute_polling_decision(&cache, &polling, PollChannel::Snapshot, &clock);
+        assert_eq!(decision.mode, PollingMode::Background);
+        assert_eq!(decision.reason, "quiet_hours");
+        assert_eq!(
+            decision.interval_ms,
+            polling.snapshot_background_interval_ms
+        );
+
+        // Active session at noon, outside quiet hours
+        let clock = MockClock::new(day + 12 * 3600);
+        cache.upsert_session(session(day + 12 * 3600));
+        let decision = compute_polling_decision(&cache, &polling, PollChannel::Snapshot, &clock);
+        assert_eq!(decision.mode, PollingMode::Active);
+    }
+
+    #[test]
+    fn error_streak_forces_degraded() {
+        let cache = Cache::new(10);
+        let polling = PollingConfig::default();
+        let clock = MockClock::new(1_000);
+        cache.upsert_session(session(1_000));
+
+        cache.record_poll_result(PollChannel::Tmux, true);
+        cache.record_poll_result(PollChannel::Tmux, true);
+
+        let decision = compute_polling_decision(&cache, &polling, PollChannel::Tmux, &clock);
+        assert_eq!(decision.mode, PollingMode::Degraded);
+        assert_eq!(decision.reason, "poll_errors");
+        assert_eq!(decision.interval_ms, polling.snapshot_degraded_interval_ms);
+
+        let decision = compute_polling_decision(&cache, &polling, PollChannel::Ntm, &clock);
+        assert_eq!(
+            decision.mode,
+            PollingMode::Active,
+            "streaks are per channel"
+        );
+
+        cache.record_poll_result(PollChannel::Tmux, false);
+        let decision = compute_polling_decision(&cache, &polling, PollChannel::Tmux, &clock);
+        assert_eq!(decision.mode, PollingMode::Active);
+    }
+}
@@ -1,7 +1,12 @@
 use crate::cache::Cache;
+use crate::config::PollingConfig;
+use crate::ids::{IdGenerator, SharedIds, UuidV7Ids};
+use crate::metrics::ReconcileCounts;
 use crate::models::pane::{Pane, PaneStatus};
 use crate::models::session::{Session, SessionStatus};
+use crate::models::source::Source;
 use crate::parsers::ntm_markdown::{NtmMarkdown, NtmSession};
+use crate::parsers::tmux_panes::TmuxPaneMeta;
 use serde_json::{Map, Value};
 use std::collections::{HashMap, HashSet};
 
@@ -10,31 +15,217 @@ pub struct ReconcileResult {
     pub sessions: Vec<Session>,
     pub panes: Vec<Pane>,
     pub ended_sessions: usize,
+    /// Sessions whose uid was not in the cache before this reconcile.
+    pub sessions_created: usize,
+    /// Sessions whose uid was already cached.
+    pub sessions_updated: usize,
+    /// Panes whose uid was not in the cache before this reconcile.
+    pub panes_created: usize,
+    /// Panes whose uid was already cached.
+    pub panes_updated: usize,
 }
 
 impl ReconcileResult {
     pub fn change_count(&self) -> usize {
         self.sessions.len() + self.panes.len()
     }
+
+    /// Created/updated/ended breakdown for the metrics counters.
+    pub fn counts(&self) -> ReconcileCounts {
+        ReconcileCounts {
+            sessions_created: self.sessions_created as u64,
+            sessions_updated: self.sessions_updated as u64,
+            sessions_ended: self.ended_sessions as u64,
+            panes_created: self.panes_created as u64,
+            panes_updated: self.panes_updated as u64,
+        }
+    }
+}
+
+/// Holds off ending ntm sessions that drop out of the output, so a session
+/// missing from a single poll does not flap to ended and back.
+#[derive(Debug, Default)]
+pub struct SessionEndGrace {
+    grace_secs: i64,
+    /// When each not-yet-ended session was first found missing, by ntm name.
+    missing_since: HashMap<String, i64>,
+}
+
+impl SessionEndGrace {
+    pub fn new(grace_secs: i64) -> Self {
+        Self {
+            grace_secs,
+            missing_since: HashMap::new(),
+        }
+    }
+
+    /// Record that `name` is missing at `now`; true once it has been missing
+    /// for the whole grace window.
+    fn expired(&mut self, name: &str, now: i64) -> bool {
+        let since = *self.missing_since.entry(name.to_string()).or_insert(now);
+        if now - since < self.grace_secs {
+            return false;
+        }
+        self.missing_since.remove(name);
+        true
+    }
+
+    fn forget_seen(&mut self, seen: &HashSet<String>) {
+        self.missing_since.retain(|name, _| !seen.contains(name));
+    }
+}
+
+/// ntm columns that may carry a session's tmux `session_id`.
+const TMUX_SESSION_ID_KEYS: [&str; 2] = ["tmux_session_id", "session_id"];
+
+/// What ntm reconcile matches sessions on across polls
+/// (`polling.session-identity`). Sessions lacking the chosen key fall back to
+/// their name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum SessionIdentity {
+    /// The session name ntm reports; a rename starts a new session.
+    #[default]
+    Name,
+    /// The tmux `session_id`, which survives renames.
+    TmuxSessionId,
+    /// The value of this (lowercase) ntm metadata column.
+    Metadata(String),
+}
+
+impl SessionIdentity {
+    pub fn from_config(polling: &PollingConfig) -> Self {
+        match polling.session_identity.as_str() {
+            "tmux-session-id" => Self::TmuxSessionId,
+            "metadata" => Self::Metadata(polling.session_identity_key.trim().to_lowercase()),
+            _ => Self::Name,
+        }
+    }
+
+    /// Identity key of a session in an ntm snapshot.
+    fn ntm_key(&self, session: &NtmSession) -> String {
+        self.metadata_key(|key| session.metadata.get(key).cloned())
+            .unwrap_or_else(|| session.name.clone())
+    }
+
+    /// Identity key of a cached session.
+    fn cached_key(&self, session: &Session) -> String {
+        let metadata = session.metadata.as_ref().and_then(Value::as_object);
+        self.metadata_key(|key| metadata?.get(key)?.as_str().map(str::to_string))
+            .or_else(|| match self {
+                Self::TmuxSessionId => session.tmux_session_id.clone(),
+                _ => None,
+            })
+            .unwrap_or_else(|| session.source_name().to_string())
+    }
+
+    fn metadata_key(&self, get: impl Fn(&str) -> Option<String>) -> Option<String> {
+        let value = match self {
+            Self::Name => None,
+            Self::TmuxSessionId => TMUX_SESSION_ID_KEYS.iter().find_map(|key| get(key)),
+            Self::Metadata(key) => get(key),
+        };
+        value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    }
 }
 
+/// Reconcile with no grace period: missing sessions end immediately.
 pub fn reconcile_ntm_markdown(
     cache: &Cache,
     markdown: &NtmMarkdown,
     now: i64,
     session_uid_by_name: &mut HashMap<String, String>,
     pane_uid_by_key: &mut HashMap<String, String>,
+) -> ReconcileResult {
+    reconcile_ntm_markdown_with_grace(
+        cache,
+        markdown,
+        now,
+        session_uid_by_name,
+        pane_uid_by_key,
+        &mut SessionEndGrace::default(),
+    )
+}
+
+/// Reconcile an ntm snapshot into session and pane records. Cached ntm
+/// sessions missing from it are ended once `grace` says they have been gone
+/// long enough.
+pub fn reconcile_ntm_markdown_with_grace(
+    cache: &Cache,
+    markdown: &NtmMarkdown,
+    now: i64,
+    session_uid_by_name: &mut HashMap<String, String>,
+    pane_uid_by_key: &mut HashMap<String, String>,
+    grace: &mut SessionEndGrace,
+) -> ReconcileResult {
+    reconcile_ntm_markdown_keyed(
+        cache,
+        markdown,
+        now,
+        session_uid_by_name,
+        pane_uid_by_key,
+        grace,
+        &SessionIdentity::Name,
+    )
+}
+
+/// Like [`reconcile_ntm_markdown_with_grace`], but sessions are matched on
+/// the key `identity` picks rather than always on their name, so the lookup
+/// tables are keyed by that identity.
+pub fn reconcile_ntm_markdown_keyed(
+    cache: &Cache,
+    markdown: &NtmMarkdown,
+    now: i64,
+    session_uid_by_name: &mut HashMap<String, String>,
+    pane_uid_by_key: &mut HashMap<String, String>,
+    grace: &mut SessionEndGrace,
+    identity: &SessionIdentity,
+) -> ReconcileResult {
+    reconcile_ntm_markdown_with_ids(
+        cache,
+        markdown,
+        now,
+        session_uid_by_name,
+        pane_uid_by_key,
+        grace,
+        identity,
+        &UuidV7Ids,
+    )
+}
+
+/// Like [`reconcile_ntm_markdown_keyed`], with new session and pane uids
+/// drawn from `ids`.
+#[allow(clippy::too_many_arguments)]
+pub fn reconcile_ntm_markdown_with_ids(
+    cache: &Cache,
+    markdown: &NtmMarkdown,
+    now: i64,
+    session_uid_by_name: &mut HashMap<String, String>,
+    pane_uid_by_key: &mut HashMap<String, String>,
+    grace: &mut SessionEndGrace,
+    identity: &SessionIdentity,
+    ids: &dyn IdGenerator,
 ) -> ReconcileResult {
     let existing_sessions = cache.all_sessions();
     let mut session_by_name = HashMap::new();
     let mut session_name_by_uid = HashMap::new();
+    // Sessions are matched on their identity key (by default the name ntm
+    // reports), not on any alias
     for session in &existing_sessions {
-        session_by_name.insert(session.name.clone(), session.clone());
-        session_name_by_uid.insert(session.session_uid.clone(), session.name.clone());
+        let name = identity.cached_key(session);
+        session_by_name.insert(name.clone(), session.clone());
+        session_name_by_uid.insert(session.session_uid.clone(), name.clone());
         session_uid_by_name
-            .entry(session.name.clone())
+            .entry(name)
             .or_insert_with(|| session.session_uid.clone());
     }
+    // Panes name their session; map that to the session's identity key
+    let key_by_name: HashMap<&str, String> = markdown
+        .sessions
+        .iter()
+        .map(|session| (session.name.as_str(), identity.ntm_key(session)))
+        .collect();
 
     if pane_uid_by_key.is_empty() {
         for pane in cache.all_panes() {
@@ -49,44 +240,68 @@ pub fn reconcile_ntm_markdown(
     let mut panes_out: Vec<Pane> = Vec::new();
     let mut seen_sessions: HashSet<String> = HashSet::new();
     let mut pane_counts: HashMap<String, u32> = HashMap::new();
+    let mut sessions_created = 0;
+    let mut sessions_updated = 0;
+    let mut panes_created = 0;
+    let mut panes_updated = 0;
 
     for session in &markdown.sessions {
+        let key = identity.ntm_key(session);
         let session = upsert_session(
+            cache,
             session,
+            &key,
             now,
             &session_by_name,
             session_uid_by_name,
+            ids,
         );
-        seen_sessions.insert(session.name.clone());
-        sessions_out.insert(session.name.clone(), session);
+        if cache.get_session(&session.session_uid).is_some() {
+            sessions_updated += 1;
+        } else {
+            sessions_created += 1;
+        }
+        seen_sessions.insert(key.clone());
+        sessions_out.insert(key, session);
     }
 
     for pane in &markdown.panes {
-        let session_name = pane.session.clone();
+        let session_name = key_by_name
+            .get(pane.session.as_str())
+            .cloned()
+            .unwrap_or_else(|| pane.session.clone());
         let session_uid = session_uid_by_name
             .entry(session_name.clone())
             .or_insert_with(|| {
                 session_by_name
                     .get(&session_name)
                     .map(|session| session.session_uid.clone())
-                    .unwrap_or_else(|| Session::new("ntm", session_name.clone(), None, now).session_uid)
+                    .unwrap_or_else(|| ids.next_id())
             })
             .clone();
 
         if !seen_sessions.contains(&session_name) {
             let fallback_session = NtmSession {
-                name: session_name.clone(),
+                name: pane.session.clone(),
                 status: None,
                 metadata: HashMap::new(),
             };
             let session = upsert_session(
+                cache,
                 &fallback_session,
+                &session_name,
                 now,
                 &session_by_name,
                 session_uid_by_name,
+                ids,
             );
-            seen_sessions.insert(session.name.clone());
-            sessions_out.insert(session.name.clone(), session);
+            if cache.get_session(&session.session_uid).is_some() {
+                sessions_updated += 1;
+            } else {
+                sessions_created += 1;
+            }
+            seen_sessions.insert(session_name.clone());
+            sessions_out.insert(session_name.clone(), session);
         }
 
         let pane_index = parse_pane_index(&pane.pane);
@@ -96,13 +311,20 @@ pub fn reconcile_ntm_markdown(
             .get(&pane_key)
             .or_else(|| pane_uid_by_key.get(&numeric_key))
             .cloned()
-            .unwrap_or_else(|| uuid::Uuid::now_v7().to_string());
+            .unwrap_or_else(|| ids.next_id());
         pane_uid_by_key.entry(pane_key).or_insert(pane_uid.clone());
         pane_uid_by_key.entry(numeric_key).or_insert(pane_uid.clone());
 
-        let mut pane_record = cache
-            .get_pane(&pane_uid)
-            .unwrap_or_else(|| Pane::new(session_uid.clone(), pane_index, now, None, None, None));
+        let mut pane_record = match cache.get_pane(&pane_uid) {
+            Some(existing) => {
+                panes_updated += 1;
+                existing
+            }
+            None => {
+                panes_created += 1;
+                Pane::new(session_uid.clone(), pane_index, now, None, None, None)
+            }
+        };
         pane_record.pane_uid = pane_uid.clone();
         pane_record.session_uid = session_uid.clone();
         pane_record.pane_index = pane_index;
@@ -124,15 +346,27 @@ pub fn reconcile_ntm_markdown(
         *pane_counts.entry(session_uid).or_insert(0) += 1;
     }
 
+    grace.forget_seen(&seen_sessions);
+    // A name whose uid this pass already emitted under another name was
+    // renamed, not ended; drop it so later passes do not see it either
+    let emitted_uids: HashSet<String> = sessions_out
+        .values()
+        .map(|session| session.session_uid.clone())
+        .collect();
+    session_uid_by_name
+        .retain(|name, uid| seen_sessions.contains(name) || !emitted_uids.contains(uid));
     let mut ended_sessions = 0;
-    for (session_name, _session_uid) in session_uid_by_name.iter() {
+    for session_name in session_uid_by_name.keys() {
         if seen_sessions.contains(session_name) {
             continue;
         }
         let Some(mut session) = session_by_name.get(session_name).cloned() else {
             continue;
         };
-        if session.source_id != "ntm" || session.ended_at.is_some() {
+        if session.source_id != Source::Ntm || session.ended_at.is_some() {
+            continue;
+        }
+        if !grace.expired(session_name, now) {
             continue;
         }
         session.ended_at = Some(now);
@@ -152,29 +386,436 @@ pub fn reconcile_ntm_markdown(
         sessions: sessions_out.into_values().collect(),
         panes: panes_out,
         ended_sessions,
+        sessions_created,
+        sessions_updated,
+        panes_created,
+        panes_updated,
+    }
+}
+
+/// Reconcile one `tmux list-panes -a` poll into session and pane records.
+///
+/// Sessions are keyed by tmux `session_id` and panes by `pane_id`; the lookup
+/// tables keep their uids stable across polls and are seeded from live cached
+/// tmux records on first use. Cached tmux sessions missing from the poll are
+/// ended and their `session_id` forgotten, since tmux reuses ids.
+pub fn reconcile_tmux(
+    cache: &Cache,
+    metas: &[TmuxPaneMeta],
+    now: i64,
+    session_uid_by_tmux: &mut HashMap<String, String>,
+    pane_uid_by_tmux: &mut HashMap<String, String>,
+) -> ReconcileResult {
+    reconcile_tmux_with_ids(
+        cache,
+        metas,
+        now,
+        session_uid_by_tmux,
+        pane_uid_by_tmux,
+        &UuidV7Ids,
+    )
+}
+
+/// Like [`reconcile_tmux`], with new session and pane uids drawn from `ids`.
+pub fn reconcile_tmux_with_ids(
+    cache: &Cache,
+    metas: &[TmuxPaneMeta],
+    now: i64,
+    session_uid_by_tmux: &mut HashMap<String, String>,
+    pane_uid_by_tmux: &mut HashMap<String, String>,
+    ids: &dyn IdGenerator,
+) -> ReconcileResult {
+    let live_tmux_sessions: Vec<Session> = cache
+        .all_sessions()
+        .into_iter()
+        .filter(|session| session.source_id == Source::Tmux && session.ended_at.is_none())
+        .collect();
+    if session_uid_by_tmux.is_empty() {
+        for session in &live_tmux_sessions {
+            if let Some(tmux_id) = &session.tmux_session_id {
+                session_uid_by_tmux.insert(tmux_id.clone(), session.session_uid.clone());
+            }
+        }
+    }
+    let live_uids: HashSet<String> = live_tmux_sessions
+        .iter()
+        .map(|session| session.session_uid.clone())
+        .collect();
+    if pane_uid_by_tmux.is_empty() {
+        for pane in cache.all_panes() {
+            if !live_uids.contains(&pane.session_uid) {
+                continue;
+            }
+            if let Some(tmux_id) = &pane.tmux_pane_id {
+                pane_uid_by_tmux.insert(tmux_id.clone(), pane.pane_uid.clone());
+            }
+        }
+    }
+
+    let mut sessions_out: Vec<Session> = Vec::new();
+    let mut panes_out: Vec<Pane> = Vec::new();
+    let mut seen_sessions: HashSet<String> = HashSet::new();
+    let mut sessions_created = 0;
+    let mut sessions_updated = 0;
+    let mut panes_created = 0;
+    let mut panes_updated = 0;
+
+    for meta in metas {
+        let session_uid = session_uid_by_tmux
+            .entry(meta.session_id.clone())
+            .or_insert_with(|| ids.next_id())
+            .clone();
+        let pane_uid = pane_uid_by_tmux
+            .entry(meta.pane_id.clone())
+            .or_insert_with(|| ids.next_id())
+            .clone();
+
+        let activity_ts = if meta.pane_last_activity > 0 {
+            meta.pane_last_activity
+        } else {
+            now
+        };
+        let cached_pane = cache.get_pane(&pane_uid);
+        // Keep a later activity time derived from captured output
+        let last_activity_at = cached_pane
+            .as_ref()
+            .and_then(|pane| pane.last_activity_at)
+            .map_or(activity_ts, |previous| previous.max(activity_ts));
+
+        if let Some(existing) = sessions_out
+            .iter_mut()
+            .find(|session| session.session_uid == session_uid)
+        {
+            existing.pane_count += 1;
+            existing.created_at = existing.created_at.min(activity_ts);
+        } else {
+            if cache.get_session(&session_uid).is_some() {
+                sessions_updated += 1;
+            } else {
+                sessions_created += 1;
+            }
+            let mut session = Session {
+                session_uid: session_uid.clone(),
+                source_id: Source::Tmux,
+                tmux_session_id: Some(meta.session_id.clone()),
+                name: meta.session_name.clone(),
+                raw_name: None,
+                created_at: activity_ts,
+                last_seen_at: now,
+                ended_at: None,
+                status: SessionStatus::Active,
+                status_reason: Some("tmux_poll".to_string()),
+                pane_count: 1,
+                metadata: None,
+            };
+            cache.apply_session_alias(&mut session);
+            seen_sessions.insert(meta.session_id.clone());
+            sessions_out.push(session);
+        }
+
+        if cached_pane.is_some() {
+            panes_updated += 1;
+        } else {
+            panes_created += 1;
+        }
+        panes_out.push(Pane {
+            pane_uid,
+            session_uid,
+            tmux_pane_id: Some(meta.pane_id.clone()),
+            tmux_window_id: Some(meta.window_id.clone()),
+            tmux_pane_pid: Some(meta.pane_pid),
+            pane_index: meta.pane_index,
+            agent_type: None,
+            created_at: activity_ts,
+            last_seen_at: now,
+            last_activity_at: Some(last_activity_at),
+            current_command: Some(meta.pane_current_command.clone()),
+            ended_at: if meta.pane_dead { Some(now) } else { None },
+            status: if meta.pane_dead {
+                PaneStatus::Ended
+            } else {
+                PaneStatus::Active
+            },
+            status_reason: Some("tmux_poll".to_string()),
+            // Refreshed by the collector's context capture
+            context: cached_pane.and_then(|pane| pane.context),
+        });
+    }
+
+    let mut ended_sessions = 0;
+    for mut session in live_tmux_sessions {
+        let Some(tmux_id) = session.tmux_session_id.clone() else {
+            continue;
+        };
+        if seen_sessions.contains(&tmux_id) {
+            continue;
+        }
+        session_uid_by_tmux.remove(&tmux_id);
+        session.ended_at = Some(now);
+        session.status = SessionStatus::Ended;
+        session.status_reason = Some("tmux_missing".to_string());
+        sessions_out.push(session);
+        ended_sessions += 1;
+    }
+
+    // Panes tmux no longer lists end too, including every pane of a session
+    // ended above; forgetting their ids gives a reused id a fresh uid
+    let seen_panes: HashSet<&str> = metas.iter().map(|meta| meta.pane_id.as_str()).collect();
+    pane_uid_by_tmux.retain(|tmux_id, _| seen_panes.contains(tmux_id.as_str()));
+    for mut pane in cache.all_panes() {
+        let gone = pane
+            .tmux_pane_id
+            .as_deref()
+            .is_some_and(|tmux_id| !seen_panes.contains(tmux_id));
+        if !gone || pane.ended_at.is_some() || !live_uids.contains(&pane.session_uid) {
+            continue;
+        }
+        pane.ended_at = Some(now);
+        pane.status = PaneStatus::Ended;
+        pane.status_reason = Some("tmux_missing".to_string());
+        panes_out.push(pane);
+    }
+
+    ReconcileResult {
+        sessions: sessions_out,
+        panes: panes_out,
+        ended_sessions,
+        sessions_created,
+        sessions_updated,
+        panes_created,
+        panes_updated,
+    }
+}
+
+/// Uid lookup tables [`reconcile_all`] keeps across polls for both sources.
+#[derive(Debug)]
+pub struct ReconcileLookups {
+    pub session_uid_by_name: HashMap<String, String>,
+    pub pane_uid_by_key: HashMap<String, String>,
+    pub session_uid_by_tmux: HashMap<String, String>,
+    pub pane_uid_by_tmux: HashMap<String, String>,
+    pub grace: SessionEndGrace,
+    pub identity: SessionIdentity,
+    /// Where new session and pane uids come from.
+    pub ids: SharedIds,
+}
+
+impl Default for ReconcileLookups {
+    fn default() -> Self {
+        Self {
+            session_uid_by_name: HashMap::new(),
+            pane_uid_by_key: HashMap::new(),
+            session_uid_by_tmux: HashMap::new(),
+            pane_uid_by_tmux: HashMap::new(),
+            grace: SessionEndGrace::default(),
+            identity: SessionIdentity::default(),
+            ids: crate::ids::uuid_v7_ids(),
+        }
+    }
+}
+
+/// Reconcile an ntm snapshot and a tmux poll into one result. A source that
+/// was not polled is `None` and ends none of its sessions.
+///
+/// A session both sources report (same identity key, by default the name) is
+/// one record under the tmux uid; its panes are matched by pane index. tmux wins for identity, tmux ids,
+/// commands and whether the session has ended; ntm adds agent type, status
+/// and metadata. A live ntm session whose name tmux now reports under another
+/// uid is ended as `merged`.
+pub fn reconcile_all(
+    cache: &Cache,
+    markdown: Option<&NtmMarkdown>,
+    tmux_panes: Option<&[TmuxPaneMeta]>,
+    now: i64,
+    lookups: &mut ReconcileLookups,
+) -> ReconcileResult {
+    let tmux = tmux_panes
+        .map(|metas| {
+            reconcile_tmux_with_ids(
+                cache,
+                metas,
+                now,
+                &mut lookups.session_uid_by_tmux,
+                &mut lookups.pane_uid_by_tmux,
+                lookups.ids.as_ref(),
+            )
+        })
+        .unwrap_or_default();
+
+    // Point the ntm lookups at the tmux uids so both passes share records
+    let mut claimed_names: HashMap<String, String> = HashMap::new();
+    for session in tmux.sessions.iter().filter(|s| s.ended_at.is_none()) {
+        let name = lookups.identity.cached_key(session);
+        lookups
+            .session_uid_by_name
+            .insert(name.clone(), session.session_uid.clone());
+        claimed_names.insert(session.session_uid.clone(), name);
+    }
+    for pane in &tmux.panes {
+        if let Some(name) = claimed_names.get(&pane.session_uid) {
+            let key = format!("{}:{}", name, pane.pane_index);
+            lookups.pane_uid_by_key.insert(key, pane.pane_uid.clone());
+        }
+    }
+
+    let ntm = markdown
+        .map(|markdown| {
+            reconcile_ntm_markdown_with_ids(
+                cache,
+                markdown,
+                now,
+                &mut lookups.session_uid_by_name,
+                &mut lookups.pane_uid_by_key,
+                &mut lookups.grace,
+                &lookups.identity,
+                lookups.ids.as_ref(),
+            )
+        })
+        .unwrap_or_default();
+
+    let mut ended_sessions = tmux.ended_sessions + ntm.ended_sessions;
+    let mut sessions = tmux.sessions;
+    let mut session_index: HashMap<String, usize> = sessions
+        .iter()
+        .enumerate()
+        .map(|(i, session)| (session.session_uid.clone(), i))
+        .collect();
+    for session in ntm.sessions {
+        match session_index.get(&session.session_uid) {
+            Some(&i) => merge_ntm_session(&mut sessions[i], session),
+            None => {
+                session_index.insert(session.session_uid.clone(), sessions.len());
+                sessions.push(session);
+            }
+        }
+    }
+
+    let claimed: HashMap<&str, &str> = claimed_names
+        .iter()
+        .map(|(uid, name)| (name.as_str(), uid.as_str()))
+        .collect();
+    for mut session in cache.all_sessions() {
+        let duplicate = session.source_id == Source::Ntm
+            && session.ended_at.is_none()
+            && claimed
+                .get(lookups.identity.cached_key(&session).as_str())
+                .is_some_and(|uid| *uid != session.session_uid);
+        if !duplicate || session_index.contains_key(&session.session_uid) {
+            continue;
+        }
+        session.ended_at = Some(now);
+        session.status = SessionStatus::Ended;
+        session.status_reason = Some("merged".to_string());
+        sessions.push(session);
+        ended_sessions += 1;
+    }
+
+    let ended_uids: HashSet<String> = sessions
+        .iter()
+        .filter(|session| session.ended_at.is_some())
+        .map(|session| session.session_uid.clone())
+        .collect();
+    let mut panes = tmux.panes;
+    let pane_index: HashMap<String, usize> = panes
+        .iter()
+        .enumerate()
+        .map(|(i, pane)| (pane.pane_uid.clone(), i))
+        .collect();
+    for pane in ntm.panes {
+        match pane_index.get(&pane.pane_uid) {
+            Some(&i) => merge_ntm_pane(&mut panes[i], pane),
+            // tmux saw the session end; ntm's view of its panes is stale
+            None if ended_uids.contains(&pane.session_uid) => {}
+            None => panes.push(pane),
+        }
+    }
+
+    let mut pane_counts: HashMap<&str, u32> = HashMap::new();
+    for pane in &panes {
+        *pane_counts.entry(pane.session_uid.as_str()).or_insert(0) += 1;
+    }
+    let mut sessions_created = 0;
+    let mut sessions_updated = 0;
+    for session in sessions.iter_mut() {
+        if let Some(count) = pane_counts.get(session.session_uid.as_str()) {
+            session.pane_count = *count;
+        }
+        if session.ended_at.is_some() {
+            continue;
+        }
+        if cache.get_session(&session.session_uid).is_some() {
+            sessions_updated += 1;
+        } else {
+            sessions_created += 1;
+        }
+    }
+    let panes_updated = panes
+        .iter()
+        .filter(|pane| cache.get_pane(&pane.pane_uid).is_some())
+        .count();
+
+    ReconcileResult {
+        panes_created: panes.len() - panes_updated,
+        panes_updated,
+        sessions,
+        panes,
+        ended_sessions,
+        sessions_created,
+        sessions_updated,
+    }
+}
+
+/// Layer what ntm knows about a session over the tmux record.
+fn merge_ntm_session(base: &mut Session, ntm: Session) {
+    if base.ended_at.is_some() {
+        return;
+    }
+    if ntm.status_reason.as_deref() == Some("ntm_status") {
+        base.status = ntm.status;
+        base.status_reason = ntm.status_reason;
+    }
+    if ntm.metadata.is_some() {
+        base.metadata = ntm.metadata;
+    }
+}
+
+/// Layer ntm's agent type and status over the tmux pane.
+fn merge_ntm_pane(base: &mut Pane, ntm: Pane) {
+    if ntm.agent_type.is_some() {
+        base.agent_type = ntm.agent_type;
+    }
+    if base.ended_at.is_none() && ntm.status_reason.as_deref() == Some("ntm_status") {
+        base.status = ntm.status;
+        base.status_reason = ntm.status_reason;
     }
 }
 
 fn upsert_session(
+    cache: &Cache,
     session: &NtmSession,
+    key: &str,
     now: i64,
     session_by_name: &HashMap<String, Session>,
     session_uid_by_name: &mut HashMap<String, String>,
+    ids: &dyn IdGenerator,
 ) -> Session {
     let session_uid = session_uid_by_name
-        .entry(session.name.clone())
+        .entry(key.to_string())
         .or_insert_with(|| {
             session_by_name
-                .get(&session.name)
+                .get(key)
                 .map(|session| session.session_uid.clone())
-                .unwrap_or_else(|| uuid::Uuid::now_v7().to_string())
+                .unwrap_or_else(|| ids.next_id())
         })
         .clone();
-    let mut record = session_by_name
-        .get(&session.name)
-        .cloned()
+    // By uid, so a session ntm renamed keeps its record under the new name
+    let mut record = cache
+        .get_session(&session_uid)
         .unwrap_or_else(|| Session::new("ntm", session.name.clone(), None, now));
     record.session_uid = session_uid;
+    record.name = session.name.clone();
+    record.raw_name = None;
+    cache.apply_session_alias(&mut record);
     record.last_seen_at = now;
     record.ended_at = None;
     if let Some(status) = map_session_status(&session.status) {
@@ -235,10 +876,14 @@ fn extract_metadata(metadata: &HashMap<String, String>, keys: &[&str]) -> Option
 mod tests {
     use super::*;
     use crate::cache::Cache;
-    use crate::models::pane::Pane;
+    use crate::models::pane::{Pane, PaneStatus};
     use crate::models::session::{Session, SessionStatus};
+    use crate::models::source::Source;
     use crate::parsers::ntm_markdown::{NtmMarkdown, NtmPane, NtmSession};
+    use crate::ids::SequentialIds;
+    use crate::parsers::tmux_panes::TmuxPaneMeta;
     use std::collections::HashMap;
+    use std::sync::Arc;
 
     #[test]
     fn marks_missing_ntm_sessions_as_ended() {
@@ -537,6 +1182,106 @@ mod tests {
         assert_eq!(result.ended_sessions, 0);
     }
 
+    #[test]
+    fn ended_session_check_matches_the_source_enum() {
+        let cache = Cache::new(128);
+        let now = 1_700_000_000;
+
+        let mut ntm_sess = Session::new("ntm", "gone", None, now - 60);
+        ntm_sess.session_uid = "ntm-uid".to_string();
+        assert_eq!(ntm_sess.source_id, Source::Ntm);
+        cache.upsert_session(ntm_sess);
+        // A look-alike source string is not treated as ntm
+        let mut other_sess = Session::new(Source::Other("NTM".to_string()), "other", None, now - 60);
+        other_sess.session_uid = "other-uid".to_string();
+        cache.upsert_session(other_sess);
+
+        let mut session_uid_by_name = HashMap::new();
+        session_uid_by_name.insert("gone".to_string(), "ntm-uid".to_string());
+        session_uid_by_name.insert("other".to_string(), "other-uid".to_string());
+
+        let mut pane_uid_by_key = HashMap::new();
+        let result = reconcile_ntm_markdown(
+            &cache, &empty_markdown(), now, &mut session_uid_by_name, &mut pane_uid_by_key,
+        );
+
+        assert_eq!(result.ended_sessions, 1);
+        assert_eq!(result.sessions[0].session_uid, "ntm-uid");
+    }
+
+    fn cache_with_ntm_session(name: &str, uid: &str, now: i64) -> (Cache, HashMap<String, String>) {
+        let cache = Cache::new(128);
+        let mut session = Session::new("ntm", name, None, now - 60);
+        session.session_uid = uid.to_string();
+        cache.upsert_session(session);
+        let mut session_uid_by_name = HashMap::new();
+        session_uid_by_name.insert(name.to_string(), uid.to_string());
+        (cache, session_uid_by_name)
+    }
+
+    #[test]
+    fn grace_ignores_a_brief_absence() {
+        let now = 1_700_000_000;
+        let (cache, mut session_uid_by_name) = cache_with_ntm_session("alpha", "alpha-uid", now);
+        let mut pane_uid_by_key = HashMap::new();
+        let mut grace = SessionEndGrace::new(30);
+        let present = NtmMarkdown {
+            sessions: vec![make_ntm_session("alpha", Some("active"))],
+            panes: vec![],
+        };
+
+        let polls = [
+            (now, empty_markdown()),
+            (now + 5, present),
+            (now + 40, empty_markdown()),
+        ];
+        for (at, md) in polls {
+            let result = reconcile_ntm_markdown_with_grace(
+                &cache,
+                &md,
+                at,
+                &mut session_uid_by_name,
+                &mut pane_uid_by_key,
+                &mut grace,
+            );
+            // Reappearing resets the clock, so the absence at +40 starts over
+            assert_eq!(result.ended_sessions, 0, "poll at {at}");
+        }
+    }
+
+    #[test]
+    fn grace_ends_a_sustained_absence() {
+        let now = 1_700_000_000;
+        let (cache, mut session_uid_by_name) = cache_with_ntm_session("alpha", "alpha-uid", now);
+        let mut pane_uid_by_key = HashMap::new();
+        let mut grace = SessionEndGrace::new(30);
+
+        for at in [now, now + 10, now + 29] {
+            let result = reconcile_ntm_markdown_with_grace(
+                &cache,
+                &empty_markdown(),
+                at,
+                &mut session_uid_by_name,
+                &mut pane_uid_by_key,
+                &mut grace,
+            );
+            assert_eq!(result.ended_sessions, 0, "still within grace at {at}");
+        }
+
+        let result = reconcile_ntm_markdown_with_grace(
+            &cache,
+            &empty_markdown(),
+            now + 30,
+            &mut session_uid_by_name,
+            &mut pane_uid_by_key,
+            &mut grace,
+        );
+        assert_eq!(result.ended_sessions, 1);
+        let ended = &result.sessions[0];
+        assert_eq!(ended.ended_at, Some(now + 30));
+        assert_eq!(ended.status_reason.as_deref(), Some("ntm_missing"));
+    }
+
     // --- Already-ended session not re-ended ---
 
     #[test]
@@ -673,6 +1418,96 @@ mod tests {
         assert_eq!(result.panes[0].session_uid, sess.unwrap().session_uid);
     }
 
+    // --- Created vs updated counts ---
+
+    #[test]
+    fn identical_reconcile_reports_all_updated() {
+        let cache = Cache::new(128);
+        let now = 1_700_000_000;
+        let md = NtmMarkdown {
+            sessions: vec![
+                make_ntm_session("alpha", Some("active")),
+                make_ntm_session("beta", Some("idle")),
+            ],
+            panes: vec![
+                make_ntm_pane("alpha", "0", Some("active"), None),
+                make_ntm_pane("alpha", "1", Some("active"), None),
+                make_ntm_pane("beta", "0", Some("idle"), None),
+            ],
+        };
+        let mut session_uid_by_name = HashMap::new();
+        let mut pane_uid_by_key = HashMap::new();
+
+        let first = reconcile_ntm_markdown(
+            &cache, &md, now, &mut session_uid_by_name, &mut pane_uid_by_key,
+        );
+        assert_eq!((first.sessions_created, first.sessions_updated), (2, 0));
+        assert_eq!((first.panes_created, first.panes_updated), (3, 0));
+        for session in first.sessions {
+            cache.upsert_session(session);
+        }
+        for pane in first.panes {
+            cache.upsert_pane(pane);
+        }
+
+        let second = reconcile_ntm_markdown(
+            &cache, &md, now + 5, &mut session_uid_by_name, &mut pane_uid_by_key,
+        );
+        assert_eq!((second.sessions_created, second.sessions_updated), (0, 2));
+        assert_eq!((second.panes_created, second.panes_updated), (0, 3));
+        assert_eq!(second.change_count(), 5, "change_count still counts every entity");
+    }
+
+    #[test]
+    fn counts_split_created_and_updated() {
+        let cache = Cache::new(128);
+        let now = 1_700_000_000;
+
+        let md = NtmMarkdown {
+            sessions: vec![make_ntm_session("alpha", Some("active"))],
+            panes: vec![make_ntm_pane("alpha", "0", Some("active"), None)],
+        };
+        let mut session_uid_by_name = HashMap::new();
+        let mut pane_uid_by_key = HashMap::new();
+        let first = reconcile_ntm_markdown(
+            &cache, &md, now, &mut session_uid_by_name, &mut pane_uid_by_key,
+        );
+        assert_eq!((first.sessions_created, first.sessions_updated), (1, 0));
+        assert_eq!((first.panes_created, first.panes_updated), (1, 0));
+        for session in first.sessions {
+            cache.upsert_session(session);
+        }
+        for pane in first.panes {
+            cache.upsert_pane(pane);
+        }
+
+        // alpha and its pane 0 exist; beta, alpha:1, and orphan (pane-only) are new
+        let md = NtmMarkdown {
+            sessions: vec![
+                make_ntm_session("alpha", Some("idle")),
+                make_ntm_session("beta", Some("active")),
+            ],
+            panes: vec![
+                make_ntm_pane("alpha", "0", Some("idle"), None),
+                make_ntm_pane("alpha", "1", Some("active"), None),
+                make_ntm_pane("orphan", "0", Some("active"), None),
+            ],
+        };
+        let second = reconcile_ntm_markdown(
+            &cache, &md, now + 5, &mut session_uid_by_name, &mut pane_uid_by_key,
+        );
+        assert_eq!(second.sessions_created, 2);
+        assert_eq!(second.sessions_updated, 1);
+        assert_eq!(second.panes_created, 2);
+        assert_eq!(second.panes_updated, 1);
+        assert_eq!(second.ended_sessions, 0);
+
+        let counts = second.counts();
+        assert_eq!(counts.sessions_created, 2);
+        assert_eq!(counts.panes_updated, 1);
+        assert_eq!(counts.sessions_ended, 0);
+    }
+
     // --- change_count helper ---
 
     #[test]
@@ -680,7 +1515,7 @@ mod tests {
         let r = ReconcileResult {
             sessions: vec![Session::new("ntm", "a", None, 1)],
             panes: vec![Pane::new("uid".to_string(), 0, 1, None, None, None)],
-            ended_sessions: 0,
+            ..ReconcileResult::default()
         };
         assert_eq!(r.change_count(), 2);
     }
@@ -708,4 +1543,427 @@ mod tests {
         let result = reconcile(&cache, &md, now);
         assert!(result.panes[0].agent_type.is_none(), "empty agent should be ignored");
     }
+
+    // --- Aliases ---
+
+    #[test]
+    fn aliased_session_keeps_alias_while_raw_name_updates() {
+        let cache = Cache::new(128);
+        let now = 1_700_000_000;
+        let mut session_uid_by_name = HashMap::new();
+        let mut pane_uid_by_key = HashMap::new();
+
+        let md = NtmMarkdown {
+            sessions: vec![make_ntm_session("alpha", Some("active"))],
+            panes: vec![],
+        };
+        let first = reconcile_ntm_markdown(&cache, &md, now, &mut session_uid_by_name, &mut pane_uid_by_key);
+        let uid = first.sessions[0].session_uid.clone();
+        cache.upsert_session(first.sessions[0].clone());
+        cache.set_session_alias(&uid, Some("Frontend".to_string()));
+
+        // Same ntm name: matched on the raw name, alias kept
+        let second = reconcile_ntm_markdown(&cache, &md, now + 5, &mut session_uid_by_name, &mut pane_uid_by_key);
+        assert_eq!(second.sessions_created, 0);
+        assert_eq!(second.ended_sessions, 0);
+        assert_eq!(second.sessions[0].session_uid, uid);
+        assert_eq!(second.sessions[0].name, "Frontend");
+        assert_eq!(second.sessions[0].raw_name.as_deref(), Some("alpha"));
+        cache.upsert_session(second.sessions[0].clone());
+
+        // ntm renames the session and the uid table carries the uid over
+        session_uid_by_name.remove("alpha");
+        session_uid_by_name.insert("alpha-v2".to_string(), uid.clone());
+        let renamed = NtmMarkdown {
+            sessions: vec![make_ntm_session("alpha-v2", Some("active"))],
+            panes: vec![],
+        };
+        let third = reconcile_ntm_markdown(&cache, &renamed, now + 10, &mut session_uid_by_name, &mut pane_uid_by_key);
+        assert_eq!(third.ended_sessions, 0);
+        assert_eq!(third.sessions.len(), 1, "the old name is not ended under the same uid");
+        let session = &third.sessions[0];
+        assert_eq!(session.session_uid, uid);
+        assert_eq!(session.name, "Frontend");
+        assert_eq!(session.raw_name.as_deref(), Some("alpha-v2"));
+        assert_eq!(session.created_at, now, "existing record updated, not rebuilt");
+        assert!(!session_uid_by_name.contains_key("alpha"));
+    }
+
+    #[test]
+    fn session_tags_survive_reconcile() {
+        let cache = Cache::new(128);
+        let now = 1_700_000_000;
+        let mut session_uid_by_name = HashMap::new();
+        let mut pane_uid_by_key = HashMap::new();
+
+        let md = NtmMarkdown {
+            sessions: vec![make_ntm_session("alpha", Some("active"))],
+            panes: vec![],
+        };
+        let first = reconcile_ntm_markdown(&cache, &md, now, &mut session_uid_by_name, &mut pane_uid_by_key);
+        let uid = first.sessions[0].session_uid.clone();
+        cache.upsert_session(first.sessions[0].clone());
+        cache.set_session_tags(&uid, vec!["infra".to_string()]);
+
+        let second = reconcile_ntm_markdown(&cache, &md, now + 5, &mut session_uid_by_name, &mut pane_uid_by_key);
+        assert_eq!(second.sessions[0].session_uid, uid);
+        cache.upsert_session(second.sessions[0].clone());
+        assert_eq!(cache.session_tags(&uid), vec!["infra".to_string()]);
+    }
+
+    // --- Session identity ---
+
+    fn renamed_session_uids(identity: SessionIdentity) -> (String, String, usize) {
+        let cache = Cache::new(128);
+        let now = 1_700_000_000;
+        let session = |name: &str| NtmSession {
+            name: name.to_string(),
+            status: Some("active".to_string()),
+            metadata: HashMap::from([("session_id".to_string(), "$3".to_string())]),
+        };
+        let mut session_uid_by_name = HashMap::new();
+        let mut pane_uid_by_key = HashMap::new();
+        let mut grace = SessionEndGrace::default();
+        let mut run = |name: &str, now: i64| {
+            let md = NtmMarkdown {
+                sessions: vec![session(name)],
+                panes: vec![make_ntm_pane(name, "0", Some("active"), None)],
+            };
+            let result = reconcile_ntm_markdown_keyed(
+                &cache,
+                &md,
+                now,
+                &mut session_uid_by_name,
+                &mut pane_uid_by_key,
+                &mut grace,
+                &identity,
+            );
+            for session in &result.sessions {
+                cache.upsert_session(session.clone());
+            }
+            for pane in &result.panes {
+                cache.upsert_pane(pane.clone());
+            }
+            result
+        };
+
+        let before = run("api", now);
+        let after = run("api-renamed", now + 5);
+        let renamed = after.sessions.iter().find(|s| s.name == "api-renamed").unwrap();
+        (
+            before.sessions[0].session_uid.clone(),
+            renamed.session_uid.clone(),
+            after.ended_sessions,
+        )
+    }
+
+    #[test]
+    fn tmux_id_identity_keeps_uid_across_rename() {
+        let (before, after, ended) = renamed_session_uids(SessionIdentity::TmuxSessionId);
+        assert_eq!(before, after);
+        assert_eq!(ended, 0);
+    }
+
+    #[test]
+    fn name_identity_starts_a_new_session_on_rename() {
+        let (before, after, ended) = renamed_session_uids(SessionIdentity::Name);
+        assert_ne!(before, after);
+        assert_eq!(ended, 1);
+    }
+
+    #[test]
+    fn metadata_identity_reads_the_configured_column() {
+        let (before, after, _) = renamed_session_uids(SessionIdentity::Metadata("session_id".to_string()));
+        assert_eq!(before, after);
+
+        let polling = crate::config::PollingConfig {
+            session_identity: "metadata".to_string(),
+            session_identity_key: " Project ".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(SessionIdentity::from_config(&polling), SessionIdentity::Metadata("project".to_string()));
+        let unknown = crate::config::PollingConfig::default();
+        assert_eq!(SessionIdentity::from_config(&unknown), SessionIdentity::Name);
+    }
+
+    // --- tmux ---
+
+    fn tmux_meta(session_id: &str, session_name: &str, pane_id: &str) -> TmuxPaneMeta {
+        TmuxPaneMeta {
+            session_id: session_id.to_string(),
+            session_name: session_name.to_string(),
+            window_id: "@1".to_string(),
+            window_index: 0,
+            pane_id: pane_id.to_string(),
+            pane_index: 0,
+            pane_pid: 100,
+            pane_current_command: "bash".to_string(),
+            pane_last_activity: 0,
+            pane_dead: false,
+            pane_in_mode: false,
+        }
+    }
+
+    fn apply(cache: &Cache, result: &ReconcileResult) {
+        for session in &result.sessions {
+            cache.upsert_session(session.clone());
+        }
+        for pane in &result.panes {
+            cache.upsert_pane(pane.clone());
+        }
+    }
+
+    #[test]
+    fn tmux_new_sessions_discovered_with_pane_counts() {
+        let cache = Cache::new(128);
+        let now = 1_700_000_000;
+        let metas = vec![
+            tmux_meta("$1", "alpha", "%1"),
+            tmux_meta("$1", "alpha", "%2"),
+            tmux_meta("$2", "beta", "%3"),
+        ];
+        let result = reconcile_tmux(&cache, &metas, now, &mut HashMap::new(), &mut HashMap::new());
+
+        assert_eq!(result.sessions.len(), 2);
+        assert_eq!(result.panes.len(), 3);
+        assert_eq!((result.sessions_created, result.panes_created), (2, 3));
+        let alpha = result.sessions.iter().find(|s| s.name == "alpha").unwrap();
+        assert_eq!(alpha.source_id, Source::Tmux);
+        assert_eq!(alpha.tmux_session_id.as_deref(), Some("$1"));
+        assert_eq!(alpha.pane_count, 2);
+        assert!(result.panes[..2].iter().all(|p| p.session_uid == alpha.session_uid));
+    }
+
+    #[test]
+    fn tmux_uids_stable_across_reconciles_and_restarts() {
+        let cache = Cache::new(128);
+        let now = 1_700_000_000;
+        let metas = vec![tmux_meta("$1", "alpha", "%1")];
+        let mut session_uids = HashMap::new();
+        let mut pane_uids = HashMap::new();
+
+        let first = reconcile_tmux(&cache, &metas, now, &mut session_uids, &mut pane_uids);
+        apply(&cache, &first);
+        let second = reconcile_tmux(&cache, &metas, now + 5, &mut session_uids, &mut pane_uids);
+        assert_eq!(second.sessions[0].session_uid, first.sessions[0].session_uid);
+        assert_eq!(second.panes[0].pane_uid, first.panes[0].pane_uid);
+        assert_eq!((second.sessions_updated, second.panes_updated), (1, 1));
+
+        // Fresh lookup tables (a new collector) are seeded from the cache
+        let third = reconcile_tmux(&cache, &metas, now + 10, &mut HashMap::new(), &mut HashMap::new());
+        assert_eq!(third.sessions[0].session_uid, first.sessions[0].session_uid);
+        assert_eq!(third.panes[0].pane_uid, first.panes[0].pane_uid);
+    }
+
+    #[test]
+    fn tmux_missing_sessions_end_and_only_tmux_ones() {
+        let cache = Cache::new(128);
+        let now = 1_700_000_000;
+        let mut ntm = Session::new("ntm", "ntm-only", None, now - 60);
+        ntm.session_uid = "ntm_uid".to_string();
+        cache.upsert_session(ntm);
+
+        let mut session_uids = HashMap::new();
+        let mut pane_uids = HashMap::new();
+        let metas = vec![tmux_meta("$1", "alpha", "%1"), tmux_meta("$2", "beta", "%2")];
+        let first = reconcile_tmux(&cache, &metas, now, &mut session_uids, &mut pane_uids);
+        apply(&cache, &first);
+        let beta_uid = first.sessions.iter().find(|s| s.name == "beta").unwrap().session_uid.clone();
+        let beta_pane_uid = pane_uids["%2"].clone();
+
+        let second = reconcile_tmux(&cache, &metas[..1], now + 5, &mut session_uids, &mut pane_uids);
+        assert_eq!(second.ended_sessions, 1);
+        let beta = second.sessions.iter().find(|s| s.session_uid == beta_uid).unwrap();
+        assert_eq!(beta.status, SessionStatus::Ended);
+        assert_eq!(beta.ended_at, Some(now + 5));
+        assert_eq!(beta.status_reason.as_deref(), Some("tmux_missing"));
+        assert!(second.sessions.iter().all(|s| s.session_uid != "ntm_uid"));
+        let beta_pane = second.panes.iter().find(|p| p.session_uid == beta_uid).unwrap();
+        assert_eq!(beta_pane.status, PaneStatus::Ended);
+        assert_eq!(beta_pane.ended_at, Some(now + 5));
+        assert!(!pane_uids.contains_key("%2"), "gone pane forgotten");
+        apply(&cache, &second);
+
+        // Already ended: not ended again, and a reused tmux id gets a new uid
+        let third = reconcile_tmux(&cache, &metas[..1], now + 10, &mut session_uids, &mut pane_uids);
+        assert_eq!(third.ended_sessions, 0);
+        assert!(third.panes.iter().all(|p| p.session_uid != beta_uid), "already ended");
+        let reused = reconcile_tmux(&cache, &metas[1..], now + 15, &mut session_uids, &mut pane_uids);
+        assert_ne!(reused.sessions[0].session_uid, beta_uid);
+        assert_ne!(reused.panes[0].pane_uid, beta_pane_uid);
+    }
+
+    // --- reconcile_all ---
+
+    fn tmux_meta_at(session_id: &str, session_name: &str, pane_id: &str, index: i32) -> TmuxPaneMeta {
+        TmuxPaneMeta {
+            pane_index: index,
+            ..tmux_meta(session_id, session_name, pane_id)
+        }
+    }
+
+    fn overlapping_sources() -> (NtmMarkdown, Vec<TmuxPaneMeta>) {
+        let markdown = NtmMarkdown {
+            sessions: vec![make_ntm_session("alpha", Some("idle")), make_ntm_session("beta", Some("active"))],
+            panes: vec![
+                make_ntm_pane("alpha", "0", Some("waiting"), Some("claude")),
+                make_ntm_pane("alpha", "1", None, Some("codex")),
+                make_ntm_pane("beta", "0", Some("active"), None),
+            ],
+        };
+        let metas = vec![tmux_meta_at("$1", "alpha", "%1", 0), tmux_meta_at("$1", "alpha", "%2", 1)];
+        (markdown, metas)
+    }
+
+    #[test]
+    fn reconcile_all_merges_overlapping_sessions() {
+        let cache = Cache::new(128);
+        let now = 1_700_000_000;
+        let (markdown, metas) = overlapping_sources();
+        let mut lookups = ReconcileLookups::default();
+
+        let result = reconcile_all(&cache, Some(&markdown), Some(metas.as_slice()), now, &mut lookups);
+
+        assert_eq!(result.sessions.len(), 2);
+        assert_eq!(result.panes.len(), 3);
+        assert_eq!((result.sessions_created, result.panes_created), (2, 3));
+        let alpha = result.sessions.iter().find(|s| s.name == "alpha").unwrap();
+        assert_eq!(alpha.source_id, Source::Tmux);
+        assert_eq!(alpha.tmux_session_id.as_deref(), Some("$1"));
+        assert_eq!(alpha.status, SessionStatus::Idle);
+        assert_eq!(alpha.pane_count, 2);
+        let pane0 = result.panes.iter().find(|p| p.tmux_pane_id.as_deref() == Some("%1")).unwrap();
+        assert_eq!(pane0.session_uid, alpha.session_uid);
+        assert_eq!(pane0.agent_type.as_deref(), Some("claude"));
+        assert_eq!(pane0.status, PaneStatus::Waiting);
+        let pane1 = result.panes.iter().find(|p| p.tmux_pane_id.as_deref() == Some("%2")).unwrap();
+        assert_eq!(pane1.agent_type.as_deref(), Some("codex"));
+        assert_eq!(pane1.status, PaneStatus::Active);
+        let beta = result.sessions.iter().find(|s| s.name == "beta").unwrap();
+        assert_eq!(beta.source_id, Source::Ntm);
+        assert_eq!(beta.pane_count, 1);
+
+        // A second pass keeps the same records and reports them as updates
+        apply(&cache, &result);
+        let again = reconcile_all(&cache, Some(&markdown), Some(metas.as_slice()), now + 5, &mut lookups);
+        assert_eq!(again.sessions.len(), 2);
+        assert_eq!(again.panes.len(), 3);
+        assert_eq!((again.sessions_updated, again.panes_updated), (2, 3));
+        assert_eq!(again.ended_sessions, 0);
+        let again_alpha = again.sessions.iter().find(|s| s.name == "alpha").unwrap();
+        assert_eq!(again_alpha.session_uid, alpha.session_uid);
+        apply(&cache, &again);
+        assert_eq!(cache.session_count(), 2);
+        assert_eq!(cache.pane_count(), 3);
+    }
+
+    #[test]
+    fn reconcile_all_lets_tmux_decide_ended_and_skips_unpolled_sources() {
+        let cache = Cache::new(128);
+        let now = 1_700_000_000;
+        let (markdown, metas) = overlapping_sources();
+        let mut lookups = ReconcileLookups::default();
+        let first = reconcile_all(&cache, Some(&markdown), Some(metas.as_slice()), now, &mut lookups);
+        apply(&cache, &first);
+        let alpha_uid = first.sessions.iter().find(|s| s.name == "alpha").unwrap().session_uid.clone();
+
+        // Only tmux polled: ntm-only beta is not ended
+        let tmux_only = reconcile_all(&cache, None, Some(metas.as_slice()), now + 5, &mut lookups);
+        assert_eq!(tmux_only.ended_sessions, 0);
+
+        // tmux lost alpha while ntm still lists it: ended once with its
+        // panes, and no live panes from ntm's stale view
+        let gone = reconcile_all(&cache, Some(&markdown), Some(&[][..]), now + 10, &mut lookups);
+        assert_eq!(gone.ended_sessions, 1);
+        let alpha = gone.sessions.iter().find(|s| s.session_uid == alpha_uid).unwrap();
+        assert_eq!(alpha.status, SessionStatus::Ended);
+        assert_eq!(alpha.status_reason.as_deref(), Some("tmux_missing"));
+        let alpha_panes: Vec<&Pane> = gone.panes.iter().filter(|p| p.session_uid == alpha_uid).collect();
+        assert_eq!(alpha_panes.len(), 2);
+        assert!(alpha_panes.iter().all(|p| p.status == PaneStatus::Ended && p.ended_at == Some(now + 10)));
+        assert_eq!(gone.sessions.iter().filter(|s| s.session_uid == alpha_uid).count(), 1);
+    }
+
+    #[test]
+    fn reconcile_all_ends_ntm_duplicate_of_a_tmux_session() {
+        let cache = Cache::new(128);
+        let now = 1_700_000_000;
+        let mut stale = Session::new("ntm", "alpha", None, now - 60);
+        stale.session_uid = "ntm_alpha".to_string();
+        cache.upsert_session(stale);
+
+        let (markdown, metas) = overlapping_sources();
+        let result = reconcile_all(&cache, Some(&markdown), Some(metas.as_slice()), now, &mut ReconcileLookups::default());
+
+        let alphas: Vec<&Session> = result.sessions.iter().filter(|s| s.source_name() == "alpha").collect();
+        assert_eq!(alphas.len(), 2);
+        let stale = alphas.iter().find(|s| s.session_uid == "ntm_alpha").unwrap();
+        assert_eq!(stale.status_reason.as_deref(), Some("merged"));
+        assert_eq!(result.ended_sessions, 1);
+        let live = alphas.iter().find(|s| s.session_uid != "ntm_alpha").unwrap();
+        assert!(live.ended_at.is_none());
+        assert_eq!(live.source_id, Source::Tmux);
+    }
+
+    #[test]
+    fn reconcile_all_with_sequential_ids_is_deterministic() {
+        let cache = Cache::new(128);
+        let now = 1_700_000_000;
+        let (markdown, metas) = overlapping_sources();
+        let mut lookups = ReconcileLookups {
+            ids: Arc::new(SequentialIds::default()),
+            ..ReconcileLookups::default()
+        };
+
+        let first = reconcile_all(&cache, Some(&markdown), Some(metas.as_slice()), now, &mut lookups);
+        let uid_of = |result: &ReconcileResult, name: &str| {
+            result.sessions.iter().find(|s| s.name == name).unwrap().session_uid.clone()
+        };
+        let pane_uids = |result: &ReconcileResult| {
+            let mut uids: Vec<String> = result.panes.iter().map(|p| p.pane_uid.clone()).collect();
+            uids.sort();
+            uids
+        };
+        // tmux goes first: alpha, then its panes; ntm-only beta and its pane follow
+        assert_eq!(uid_of(&first, "alpha"), "id-1");
+        assert_eq!(uid_of(&first, "beta"), "id-4");
+        assert_eq!(pane_uids(&first), ["id-2", "id-3", "id-5"]);
+
+        apply(&cache, &first);
+        let again = reconcile_all(&cache, Some(&markdown), Some(metas.as_slice()), now + 5, &mut lookups);
+        assert_eq!(uid_of(&again, "alpha"), "id-1");
+        assert_eq!(uid_of(&again, "beta"), "id-4");
+        assert_eq!(pane_uids(&again), pane_uids(&first));
+        assert_eq!(lookups.ids.next_id(), "id-6", "no uids minted for known records");
+    }
+
+    #[test]
+    fn reconcile_ntm_markdown_with_ids_mints_pane_only_session_uid() {
+        let cache = Cache::new(128);
+        let now = 1_700_000_000;
+        // ntm lists a pane whose session has no row of its own
+        let markdown = NtmMarkdown {
+            sessions: vec![],
+            panes: vec![make_ntm_pane("orphan", "0", Some("active"), Some("claude"))],
+        };
+        let ids = SequentialIds::default();
+
+        let result = reconcile_ntm_markdown_with_ids(
+            &cache,
+            &markdown,
+            now,
+            &mut HashMap::new(),
+            &mut HashMap::new(),
+            &mut SessionEndGrace::default(),
+            &SessionIdentity::default(),
+            &ids,
+        );
+
+        assert_eq!(result.sessions.len(), 1);
+        assert_eq!(result.sessions[0].name, "orphan");
+        assert_eq!(result.sessions[0].session_uid, "id-1");
+        assert_eq!(result.panes[0].session_uid, "id-1");
+        assert_eq!(result.panes[0].pane_uid, "id-2");
+        assert_eq!(ids.next_id(), "id-3", "one uid per record");
+    }
 }
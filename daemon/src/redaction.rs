@@ -59,6 +59,11 @@ impl Redactor {
         output
     }
 
+    /// Text substituted for each match.
+    pub fn replacement(&self) -> &str {
+        &self.replacement
+    }
+
     pub fn redact_streaming<R: Read>(&self, mut reader: R) -> IoResult<String> {
         let mut buf = vec![0; self.max_scan_bytes];
         let bytes = reader.read(&mut buf)?;
@@ -1,17 +1,20 @@
-use crate::command::{CommandCategory, CommandConfig, CommandError, CommandOutput, CommandRunner, CommandSpec};
+use crate::command::{CommandCategory, CommandError, CommandOutput, CommandSpec};
 use crate::models::session::SessionStatus;
 use crate::rpc::{
-    parse_params, RpcContext, RpcError, RpcResult, CODE_FORBIDDEN, CODE_INVALID_PARAMS,
+    parse_params, IdempotencyClaim, RpcContext, RpcError, RpcResult, CODE_FORBIDDEN, CODE_INVALID_PARAMS,
     CODE_NOT_FOUND, CODE_UNSUPPORTED,
 };
 use serde::Deserialize;
 use serde_json::{json, Value};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::Duration;
 
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct SessionKillParams {
     session_id: String,
+    /// Repeats of a recent key return the first result without re-running.
+    #[serde(default)]
+    idempotency_key: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -22,6 +25,8 @@ struct PaneSendParams {
     /// If true, send Enter after the payload text.
     #[serde(default)]
     enter: bool,
+    #[serde(default)]
+    idempotency_key: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -41,6 +46,39 @@ fn require_admin_or_unsecured(ctx: &RpcContext) -> RpcResult<()> {
     }
 }
 
+/// Longest accepted `idempotencyKey`.
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 128;
+
+/// Run `action` once per idempotency key: a key already used for `method`
+/// within [`crate::rpc::IDEMPOTENCY_TTL`] returns the stored result instead,
+/// and a repeat arriving while the first run is in flight waits for it.
+/// Only successes are stored, so a failed action can be retried.
+fn once(
+    ctx: &RpcContext,
+    method: &str,
+    key: Option<&str>,
+    action: impl FnOnce() -> RpcResult<Value>,
+) -> RpcResult<Value> {
+    let Some(key) = key else {
+        return action();
+    };
+    if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+        return Err(RpcError::new(
+            CODE_INVALID_PARAMS,
+            format!("idempotencyKey must be 1-{MAX_IDEMPOTENCY_KEY_LEN} bytes"),
+        ));
+    }
+    let scoped = format!("{method}:{key}");
+    match ctx.idempotency.claim(&scoped) {
+        IdempotencyClaim::Replay(previous) => Ok(previous),
+        IdempotencyClaim::Run(reservation) => {
+            let result = action();
+            reservation.finish(&result);
+            result
+        }
+    }
+}
+
 /// Validates that a session target is safe for tmux commands.
 /// Allows alphanumeric, %, @, :, ., -, _, $
 fn is_valid_session_target(target: &str) -> bool {
@@ -65,8 +103,8 @@ fn is_valid_pane_id(pane_id: &str) -> bool {
 }
 
 /// Run a tmux command spec and map errors to RpcError.
-fn run_tmux(spec: CommandSpec) -> RpcResult<CommandOutput> {
-    let runner = CommandRunner::new(CommandConfig::default());
+fn run_tmux(ctx: &RpcContext, spec: CommandSpec) -> RpcResult<CommandOutput> {
+    let runner = ctx.command_runner();
     let result = if let Ok(handle) = tokio::runtime::Handle::try_current() {
         handle.block_on(runner.run(spec))
     } else {
@@ -105,9 +143,18 @@ fn tmux_spec(args: Vec<String>) -> CommandSpec {
 pub fn session_kill(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
     require_admin_or_unsecured(ctx)?;
     let params: SessionKillParams = parse_params(params)?;
+    once(
+        ctx,
+        "actions.sessionKill",
+        params.idempotency_key.as_deref(),
+        || kill_session(ctx, &params.session_id),
+    )
+}
+
+fn kill_session(ctx: &RpcContext, session_id: &str) -> RpcResult<Value> {
     let session = ctx
         .cache
-        .get_session(&params.session_id)
+        .get_session(session_id)
         .ok_or_else(|| RpcError::new(CODE_NOT_FOUND, "Session not found"))?;
 
     let target = session
@@ -128,18 +175,15 @@ pub fn session_kill(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
         target.to_string(),
     ]);
 
-    run_tmux(spec)?;
+    run_tmux(ctx, spec)?;
 
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_else(|_| Duration::from_secs(0))
-        .as_secs() as i64;
+    let now = ctx.clock.now_unix();
     let mut updated = session;
     updated.ended_at = Some(now);
     updated.status = SessionStatus::Ended;
     updated.status_reason = Some("killed".to_string());
     ctx.cache.upsert_session(updated);
-    Ok(json!({ "killed": true, "sessionId": params.session_id }))
+    Ok(json!({ "killed": true, "sessionId": session_id }))
 }
 
 /// Send literal text (and optionally Enter) to a tmux pane via `tmux send-keys`.
@@ -149,7 +193,15 @@ pub fn session_kill(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
 pub fn pane_send(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
     require_admin_or_unsecured(ctx)?;
     let params: PaneSendParams = parse_params(params)?;
+    once(
+        ctx,
+        "actions.paneSend",
+        params.idempotency_key.as_deref(),
+        || send_to_pane(ctx, &params),
+    )
+}
 
+fn send_to_pane(ctx: &RpcContext, params: &PaneSendParams) -> RpcResult<Value> {
     if !is_valid_pane_id(&params.pane_id) {
         return Err(RpcError::new(
             CODE_INVALID_PARAMS,
@@ -173,7 +225,7 @@ pub fn pane_send(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
         "--".to_string(),
         params.payload.clone(),
     ]);
-    run_tmux(spec)?;
+    run_tmux(ctx, spec)?;
 
     // Optionally send Enter after the literal text
     if params.enter {
@@ -183,7 +235,7 @@ pub fn pane_send(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
             params.pane_id.clone(),
             "Enter".to_string(),
         ]);
-        run_tmux(enter_spec)?;
+        run_tmux(ctx, enter_spec)?;
     }
 
     Ok(json!({
@@ -214,7 +266,9 @@ pub fn attach_command(_ctx: &RpcContext, params: Value) -> RpcResult<Value> {
 mod tests {
     use super::*;
     use crate::cache::Cache;
+    use crate::clock::MockClock;
     use crate::config::ConfigManager;
+    use crate::rpc::IDEMPOTENCY_TTL;
     use std::sync::Arc;
 
     fn test_ctx() -> crate::rpc::RpcContext {
@@ -303,4 +357,114 @@ mod tests {
         );
         assert!(result.is_err());
     }
+
+    #[test]
+    fn repeated_idempotency_key_returns_first_result() {
+        let ctx = test_ctx();
+        let mut runs = 0;
+        for _ in 0..2 {
+            let result = once(&ctx, "actions.paneSend", Some("retry-1"), || {
+                runs += 1;
+                Ok(json!({ "sent": true, "run": runs }))
+            })
+            .unwrap();
+            assert_eq!(result["run"], 1);
+        }
+        assert_eq!(runs, 1, "the action ran only once");
+
+        // Keys are scoped per method, and no key always runs
+        once(&ctx, "actions.sessionKill", Some("retry-1"), || {
+            runs += 1;
+            Ok(json!({}))
+        })
+        .unwrap();
+        once(&ctx, "actions.paneSend", None, || {
+            runs += 1;
+            Ok(json!({}))
+        })
+        .unwrap();
+        assert_eq!(runs, 3);
+    }
+
+    #[test]
+    fn repeat_during_a_run_waits_for_its_result() {
+        let ctx = test_ctx();
+        let runs = std::sync::atomic::AtomicUsize::new(0);
+        let (started_tx, started_rx) = std::sync::mpsc::channel();
+        let run = || {
+            once(&ctx, "actions.paneSend", Some("k"), || {
+                let run = runs.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+                let _ = started_tx.send(());
+                std::thread::sleep(Duration::from_millis(50));
+                Ok(json!({ "run": run }))
+            })
+            .unwrap()
+        };
+        let (first, repeat) = std::thread::scope(|scope| {
+            let first = scope.spawn(run);
+            started_rx.recv().unwrap();
+            let repeat = scope.spawn(run);
+            (first.join().unwrap(), repeat.join().unwrap())
+        });
+        assert_eq!(first, repeat);
+        assert_eq!(runs.into_inner(), 1, "the repeat reused the in-flight run");
+    }
+
+    #[test]
+    fn results_expire_by_the_injected_clock() {
+        let clock = Arc::new(MockClock::new(1_000));
+        let ctx = test_ctx().with_clock(clock.clone());
+        let mut runs = 0;
+        let mut run = || {
+            once(&ctx, "actions.sessionKill", Some("k"), || {
+                runs += 1;
+                Ok(json!({}))
+            })
+            .unwrap();
+        };
+        run();
+        clock.advance(IDEMPOTENCY_TTL.as_secs() as i64 - 1);
+        run();
+        clock.advance(1);
+        run();
+        assert_eq!(runs, 2);
+    }
+
+    #[test]
+    fn failed_action_is_not_remembered() {
+        let ctx = test_ctx();
+        let first = once(&ctx, "actions.sessionKill", Some("k"), || {
+            Err(RpcError::new(CODE_NOT_FOUND, "Session not found"))
+        });
+        assert!(first.is_err());
+        let second = once(&ctx, "actions.sessionKill", Some("k"), || {
+            Ok(json!({ "killed": true }))
+        });
+        assert_eq!(second.unwrap()["killed"], true);
+    }
+
+    #[test]
+    fn session_kill_replays_without_re_running() {
+        let ctx = test_ctx();
+        // The session does not exist, so a real run would fail with NOT_FOUND
+        let earlier = json!({ "killed": true, "sessionId": "s1" });
+        ctx.idempotency
+            .insert("actions.sessionKill:abc".to_string(), earlier.clone());
+        let result = session_kill(&ctx, json!({ "sessionId": "s1", "idempotencyKey": "abc" }));
+        assert_eq!(result.unwrap(), earlier);
+
+        let without_key = session_kill(&ctx, json!({ "sessionId": "s1" }));
+        assert_eq!(without_key.unwrap_err().code, CODE_NOT_FOUND);
+    }
+
+    #[test]
+    fn oversized_idempotency_key_is_rejected() {
+        let ctx = test_ctx();
+        let key = "k".repeat(MAX_IDEMPOTENCY_KEY_LEN + 1);
+        let result = pane_send(
+            &ctx,
+            json!({ "paneId": "%0", "payload": "x", "idempotencyKey": key }),
+        );
+        assert_eq!(result.unwrap_err().code, CODE_INVALID_PARAMS);
+    }
 }
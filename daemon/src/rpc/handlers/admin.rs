@@ -1,4 +1,7 @@
-use crate::rpc::{require_admin, RpcContext, RpcError, RpcResult, CODE_DEGRADED};
+use crate::rpc::{
+    require_admin, Capabilities, Prober, RpcContext, RpcError, RpcResult, SystemProber,
+    CODE_DEGRADED,
+};
 use serde_json::{json, Value};
 
 pub fn config_get(ctx: &RpcContext) -> RpcResult<Value> {
@@ -6,7 +9,8 @@ pub fn config_get(ctx: &RpcContext) -> RpcResult<Value> {
     Ok(json!({
         "config": config,
         "configPath": ctx.config.config_path().map(|path| path.display().to_string()),
-        "adminMode": ctx.is_admin
+        "adminMode": ctx.is_admin,
+        "warnings": ctx.config.warnings(),
     }))
 }
 
@@ -46,6 +50,19 @@ pub fn detectors_reload(ctx: &RpcContext) -> RpcResult<Value> {
     Ok(json!({ "reloaded": true }))
 }
 
+/// Re-probe the environment so tools installed (or removed) after startup are
+/// picked up. The daemon spawns collectors for tools that became available.
+pub fn capabilities_recheck(ctx: &RpcContext) -> RpcResult<Value> {
+    capabilities_recheck_with(ctx, &SystemProber)
+}
+
+fn capabilities_recheck_with(ctx: &RpcContext, prober: &dyn Prober) -> RpcResult<Value> {
+    require_admin(ctx)?;
+    let capabilities = Capabilities::probe_with(prober);
+    ctx.capabilities.replace(capabilities.clone());
+    Ok(json!({ "capabilities": capabilities }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -69,6 +86,7 @@ mod tests {
         let result = config_get(&ctx).unwrap();
         assert!(result["config"].is_object());
         assert_eq!(result["adminMode"], false);
+        assert_eq!(result["warnings"], json!([]));
     }
 
     #[test]
@@ -101,6 +119,44 @@ mod tests {
         assert_eq!(result.unwrap_err().code, CODE_FORBIDDEN);
     }
 
+    struct NtmOnlyProber;
+
+    impl Prober for NtmOnlyProber {
+        fn ntm_available(&self) -> bool {
+            true
+        }
+
+        fn tmux_available(&self) -> bool {
+            false
+        }
+
+        fn systemd_available(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn capabilities_recheck_requires_admin() {
+        let ctx = test_ctx(false);
+        let result = capabilities_recheck(&ctx);
+        assert_eq!(result.unwrap_err().code, CODE_FORBIDDEN);
+    }
+
+    #[test]
+    fn capabilities_recheck_updates_stored_capabilities() {
+        let ctx = test_ctx(true);
+        let changes = ctx.capabilities.subscribe();
+        let result = capabilities_recheck_with(&ctx, &NtmOnlyProber).unwrap();
+        assert_eq!(result["capabilities"]["ntm"], true);
+        assert_eq!(result["capabilities"]["systemd"], true);
+
+        let current = ctx.capabilities.current();
+        assert!(current.ntm);
+        assert!(!current.tmux);
+        assert!(current.systemd);
+        assert!(changes.has_changed().unwrap());
+    }
+
     #[test]
     fn detectors_list_returns_detectors() {
         let ctx = test_ctx(false);
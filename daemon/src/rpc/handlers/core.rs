@@ -1,5 +1,6 @@
 use crate::rpc::handlers::{events, panes, sessions, stats};
-use crate::rpc::{RpcContext, RpcResult};
+use crate::rpc::{parse_params, RpcContext, RpcResult};
+use serde::Deserialize;
 use serde_json::{json, Value};
 
 pub fn hello(ctx: &RpcContext) -> RpcResult<Value> {
@@ -11,18 +12,23 @@ pub fn health_get(ctx: &RpcContext) -> RpcResult<Value> {
     let last_event_id = events::last_event_id(ctx.cache.as_ref());
     let polling_state = ctx.cache.polling_state();
     let polling_config = ctx.config.current().polling;
+    let uptime = ctx.uptime_secs();
 
     Ok(json!({
         "status": health.status,
-        "uptime": ctx.uptime_secs(),
+        "uptime": uptime,
+        "uptimeDetail": uptime_detail(uptime, ctx.started_at_unix),
         "version": crate::version(),
         "instanceId": ctx.instance_id,
         "runId": ctx.run_id,
+        "label": ctx.config.current().service.resolved_label(),
+        "safeMode": ctx.safe_mode,
         "schemaVersion": ctx.schema_version,
         "protocolVersion": ctx.protocol_version,
-        "capabilities": ctx.capabilities,
+        "capabilities": ctx.capabilities.current(),
         "lastEventId": last_event_id,
         "lastError": health.last_error,
+        "degradedReason": health.degraded_reason.map(|reason| reason.as_str()),
         "polling": {
             "snapshot": polling_state.snapshot,
             "tmux": polling_state.tmux,
@@ -32,41 +38,138 @@ pub fn health_get(ctx: &RpcContext) -> RpcResult<Value> {
     }))
 }
 
+/// `secs` of uptime with the start time as ISO-8601 UTC and a short
+/// human-readable form, so clients don't each reimplement the formatting.
+fn uptime_detail(secs: u64, started_at_unix: i64) -> Value {
+    json!({
+        "secs": secs,
+        "since": iso8601_utc(started_at_unix),
+        "humanized": humanize_secs(secs),
+    })
+}
+
+/// The two largest non-trivial units, e.g. `2h 13m`, `3d 4h`, `45s`.
+fn humanize_secs(secs: u64) -> String {
+    let (days, hours, minutes, seconds) = (
+        secs / 86_400,
+        secs % 86_400 / 3600,
+        secs % 3600 / 60,
+        secs % 60,
+    );
+    if days > 0 {
+        format!("{days}d {hours}h")
+    } else if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else if minutes > 0 {
+        format!("{minutes}m {seconds}s")
+    } else {
+        format!("{seconds}s")
+    }
+}
+
+/// Format unix seconds as `YYYY-MM-DDTHH:MM:SSZ`.
+pub(crate) fn iso8601_utc(unix: i64) -> String {
+    let days = unix.div_euclid(86_400);
+    let secs = unix.rem_euclid(86_400);
+    // Civil-from-days (Howard Hinnant), valid for the proleptic Gregorian calendar
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        secs / 3600,
+        secs % 3600 / 60,
+        secs % 60
+    )
+}
+
 pub fn capabilities_get(ctx: &RpcContext) -> RpcResult<Value> {
     Ok(json!({
         "protocolVersion": ctx.protocol_version,
         "schemaVersion": ctx.schema_version,
-        "capabilities": ctx.capabilities,
+        "capabilities": ctx.capabilities.current(),
     }))
 }
 
-pub fn snapshot_get(ctx: &RpcContext) -> RpcResult<Value> {
-    let sessions = sessions::session_views(ctx.cache.as_ref());
-    let panes = panes::pane_views(ctx.cache.as_ref());
-    let events = events::event_views(ctx.cache.as_ref(), None, None);
-    let stats_summary = stats::summary_payload(ctx.cache.as_ref());
+/// Top-level snapshot sections a client can ask for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum SnapshotSection {
+    Sessions,
+    Panes,
+    Events,
+    Stats,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SnapshotGetParams {
+    /// Sections to return; all of them when omitted.
+    include: Option<Vec<SnapshotSection>>,
+}
+
+/// Build a snapshot. `lastEventId` and `polling` are always present; the
+/// other sections can be narrowed with `{ "include": [...] }`, in which case
+/// the keys of omitted sections are left out.
+pub fn snapshot_get(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
+    let params: SnapshotGetParams = if params.is_null() {
+        SnapshotGetParams::default()
+    } else {
+        parse_params(params)?
+    };
+    let wants = |section: SnapshotSection| {
+        params
+            .include
+            .as_ref()
+            .is_none_or(|include| include.contains(&section))
+    };
+
     let last_event_id = events::last_event_id(ctx.cache.as_ref());
+    let polling_state = ctx.cache.polling_state();
+    let mut snapshot = json!({
+        "lastEventId": last_event_id,
+        "polling": {
+            "snapshot": polling_state.snapshot,
+            "tmux": polling_state.tmux,
+            "ntm": polling_state.ntm,
+        },
+    });
 
-    Ok(json!({
-        "sessions": sessions,
-        "panes": panes,
-        "events": events,
-        "stats": {
-            "summary": stats_summary,
+    if wants(SnapshotSection::Sessions) {
+        let sessions = sessions::session_views(ctx.cache.as_ref(), &ctx.config.current().grouping);
+        snapshot["sessions"] = json!(sessions);
+    }
+    if wants(SnapshotSection::Panes) {
+        snapshot["panes"] = json!(panes::pane_views(ctx.cache.as_ref(), ctx.clock.now_unix()));
+    }
+    if wants(SnapshotSection::Events) {
+        snapshot["events"] = json!(events::event_views(ctx.cache.as_ref(), None, None));
+    }
+    if wants(SnapshotSection::Stats) {
+        snapshot["stats"] = json!({
+            "summary": stats::summary_payload(ctx.cache.as_ref()),
             "hourly": [],
             "daily": [],
-        },
-        "lastEventId": last_event_id,
-    }))
+        });
+    }
+
+    Ok(snapshot)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::cache::{Cache, EventRecord, HealthStatus, StatsAggregate};
+    use crate::cache::{Cache, DegradedReason, EventRecord, HealthStatus, PollingDatum, StatsAggregate};
     use crate::config::ConfigManager;
     use crate::models::pane::{Pane, PaneStatus};
     use crate::models::session::{Session, SessionStatus};
+    use crate::models::source::Source;
     use crate::rpc::{Capabilities, RpcContext};
     use std::sync::Arc;
 
@@ -83,9 +186,10 @@ mod tests {
     fn make_session(uid: &str, name: &str) -> Session {
         Session {
             session_uid: uid.to_string(),
-            source_id: "tmux".to_string(),
+            source_id: Source::Tmux,
             tmux_session_id: None,
             name: name.to_string(),
+            raw_name: None,
             created_at: 1000,
             last_seen_at: 2000,
             ended_at: None,
@@ -112,6 +216,7 @@ mod tests {
             ended_at: None,
             status: PaneStatus::Active,
             status_reason: None,
+            context: None,
         }
     }
 
@@ -127,19 +232,71 @@ mod tests {
         assert_eq!(result["capabilities"]["tmux"], true);
     }
 
+    fn ctx_with_config(dir: &std::path::Path, toml: &str) -> RpcContext {
+        let config_path = dir.join("config.toml");
+        std::fs::write(&config_path, toml).unwrap();
+        let config = ConfigManager::load_from_fs(Some(config_path)).expect("config");
+        RpcContext::with_capabilities(Arc::new(Cache::new(100)), config, test_caps())
+    }
+
+    #[test]
+    fn hello_includes_configured_label() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let ctx = ctx_with_config(dir.path(), "[service]\nlabel = \"build-box\"\n");
+        let result = hello(&ctx).unwrap();
+        assert_eq!(result["label"], "build-box");
+        assert_eq!(health_get(&ctx).unwrap()["label"], "build-box");
+    }
+
+    #[test]
+    fn hello_label_defaults_to_hostname() {
+        let ctx = test_ctx();
+        let expected = crate::service::hostname().unwrap_or_default();
+        assert_eq!(hello(&ctx).unwrap()["label"], expected.as_str());
+    }
+
     #[test]
     fn health_get_returns_status() {
         let ctx = test_ctx();
         ctx.cache.set_health(HealthStatus {
             status: "ok".to_string(),
             last_error: None,
+            degraded_reason: None,
         });
         let result = health_get(&ctx).unwrap();
         assert_eq!(result["status"], "ok");
         assert!(result["uptime"].is_number());
+        assert_eq!(result["uptimeDetail"]["secs"], result["uptime"]);
+        assert!(result["uptimeDetail"]["since"].is_string());
         assert!(result["version"].is_string());
         assert!(result["instanceId"].is_string());
         assert_eq!(result["lastError"], Value::Null);
+        assert_eq!(result["degradedReason"], Value::Null);
+    }
+
+    #[test]
+    fn uptime_detail_derives_from_started_at() {
+        // 2023-11-14T22:13:20Z, up 2h 13m 5s
+        let detail = uptime_detail(7985, 1_700_000_000);
+        assert_eq!(detail["secs"], 7985);
+        assert_eq!(detail["since"], "2023-11-14T22:13:20Z");
+        assert_eq!(detail["humanized"], "2h 13m");
+    }
+
+    #[test]
+    fn humanize_secs_picks_two_largest_units() {
+        assert_eq!(humanize_secs(0), "0s");
+        assert_eq!(humanize_secs(45), "45s");
+        assert_eq!(humanize_secs(125), "2m 5s");
+        assert_eq!(humanize_secs(3600), "1h 0m");
+        assert_eq!(humanize_secs(3 * 86_400 + 4 * 3600 + 59), "3d 4h");
+    }
+
+    #[test]
+    fn iso8601_utc_formats_dates() {
+        assert_eq!(iso8601_utc(0), "1970-01-01T00:00:00Z");
+        assert_eq!(iso8601_utc(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(iso8601_utc(1_735_689_599), "2024-12-31T23:59:59Z");
     }
 
     #[test]
@@ -148,10 +305,33 @@ mod tests {
         ctx.cache.set_health(HealthStatus {
             status: "degraded".to_string(),
             last_error: Some("tmux timeout".to_string()),
+            degraded_reason: Some(DegradedReason::CommandTimeout),
         });
         let result = health_get(&ctx).unwrap();
         assert_eq!(result["status"], "degraded");
         assert_eq!(result["lastError"], "tmux timeout");
+        assert_eq!(result["degradedReason"], "command_timeout");
+    }
+
+    #[test]
+    fn health_get_degraded_reason_distinguishes_subsystems() {
+        let ctx = test_ctx();
+        ctx.cache.set_health(HealthStatus {
+            status: "degraded".to_string(),
+            last_error: Some("ntm: CommandFailed(\"exit code\")".to_string()),
+            degraded_reason: Some(DegradedReason::PollErrors),
+        });
+        let poll = health_get(&ctx).unwrap()["degradedReason"].clone();
+
+        ctx.cache.set_health(HealthStatus {
+            status: "degraded".to_string(),
+            last_error: Some("db: database is locked".to_string()),
+            degraded_reason: Some(DegradedReason::DbLocked),
+        });
+        let db = health_get(&ctx).unwrap()["degradedReason"].clone();
+
+        assert_eq!(poll, "poll_errors");
+        assert_eq!(db, "db_locked");
     }
 
     #[test]
@@ -175,7 +355,7 @@ mod tests {
     #[test]
     fn snapshot_get_empty_cache() {
         let ctx = test_ctx();
-        let result = snapshot_get(&ctx).unwrap();
+        let result = snapshot_get(&ctx, Value::Null).unwrap();
         assert!(result["sessions"].as_array().unwrap().is_empty());
         assert!(result["panes"].as_array().unwrap().is_empty());
         assert!(result["events"].as_array().unwrap().is_empty());
@@ -203,11 +383,69 @@ mod tests {
             estimated_tokens: 10000,
         });
 
-        let result = snapshot_get(&ctx).unwrap();
+        let result = snapshot_get(&ctx, Value::Null).unwrap();
         assert_eq!(result["sessions"].as_array().unwrap().len(), 1);
         assert_eq!(result["panes"].as_array().unwrap().len(), 1);
         assert_eq!(result["events"].as_array().unwrap().len(), 1);
         assert_eq!(result["lastEventId"], 1);
         assert_eq!(result["stats"]["summary"]["totalCompacts"], 5);
     }
+
+    #[test]
+    fn snapshot_get_include_sessions_only_omits_other_sections() {
+        let ctx = test_ctx();
+        ctx.cache.upsert_session(make_session("s1", "alpha"));
+        ctx.cache.upsert_pane(make_pane("p1", "s1"));
+
+        let result = snapshot_get(&ctx, json!({ "include": ["sessions"] })).unwrap();
+        assert_eq!(result["sessions"].as_array().unwrap().len(), 1);
+        assert!(result.get("panes").is_none());
+        assert!(result.get("events").is_none());
+        assert!(result.get("stats").is_none());
+        assert_eq!(result["lastEventId"], 0);
+        assert!(result["polling"].is_object());
+    }
+
+    #[test]
+    fn snapshot_get_without_include_returns_everything() {
+        let ctx = test_ctx();
+        for params in [Value::Null, json!({})] {
+            let result = snapshot_get(&ctx, params).unwrap();
+            for key in ["sessions", "panes", "events", "stats", "lastEventId", "polling"] {
+                assert!(result.get(key).is_some(), "missing {key}");
+            }
+        }
+    }
+
+    #[test]
+    fn snapshot_get_rejects_unknown_section() {
+        let ctx = test_ctx();
+        let err = snapshot_get(&ctx, json!({ "include": ["sessions", "bogus"] })).unwrap_err();
+        assert_eq!(err.code, crate::rpc::CODE_INVALID_PARAMS);
+    }
+
+    #[test]
+    fn snapshot_get_includes_polling_per_channel() {
+        let ctx = test_ctx();
+        let datum = |interval_ms, mode: &str, reason: &str| PollingDatum {
+            interval_ms,
+            mode: mode.to_string(),
+            reason: reason.to_string(),
+            last_change_at: 1000,
+        };
+        ctx.cache.update_polling_snapshot(datum(2000, "active", "recent_activity"));
+        ctx.cache.update_polling_tmux(datum(5000, "idle", "idle_timeout"));
+        ctx.cache.update_polling_ntm(datum(10000, "degraded", "poll_errors"));
+
+        let result = snapshot_get(&ctx, Value::Null).unwrap();
+        let polling = &result["polling"];
+        for (channel, mode, interval) in [
+            ("snapshot", "active", 2000),
+            ("tmux", "idle", 5000),
+            ("ntm", "degraded", 10000),
+        ] {
+            assert_eq!(polling[channel]["mode"], mode, "{channel}");
+            assert_eq!(polling[channel]["interval_ms"], interval, "{channel}");
+        }
+    }
 }
@@ -1,10 +1,27 @@
 //! Debug and diagnostics endpoints (admin only).
 
-use crate::cache::PollingState;
+use crate::cache::{PollChannel, PollingState};
+use crate::db;
+use crate::logging::{self, LogFileError};
 use crate::metrics::METRICS;
-use crate::rpc::{require_admin, RpcContext, RpcResult};
-use serde::Serialize;
+use crate::parsers::ntm_markdown::parse_ntm_markdown;
+use crate::reconcile::{reconcile_all, ReconcileLookups, SessionIdentity};
+use crate::rpc::handlers::panes::PaneView;
+use crate::rpc::handlers::sessions::SessionView;
+use crate::rpc::{
+    parse_params, require_admin, RpcContext, RpcError, RpcResult, CODE_DEGRADED,
+    CODE_INVALID_PARAMS, CODE_NOT_FOUND, CODE_UNSUPPORTED,
+};
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
+use std::path::Path;
+
+/// Default and maximum number of bytes returned by debug.logFetch.
+const LOG_FETCH_DEFAULT_LIMIT: usize = 64 * 1024;
+const LOG_FETCH_MAX_LIMIT: usize = 1024 * 1024;
+
+/// Largest markdown document debug.reconcilePreview accepts.
+const RECONCILE_PREVIEW_MAX_BYTES: usize = 1024 * 1024;
 
 /// GET debug.diagnostics - Internal state inspection.
 pub fn diagnostics(ctx: &RpcContext) -> RpcResult<Value> {
@@ -40,7 +57,7 @@ pub fn diagnostics(ctx: &RpcContext) -> RpcResult<Value> {
         uptime_secs: ctx.uptime_secs(),
         protocol_version: ctx.protocol_version,
         schema_version: ctx.schema_version,
-        capabilities: ctx.capabilities.clone(),
+        capabilities: ctx.capabilities.current(),
         cache_stats: CacheStats {
             session_count: cache.session_count(),
             pane_count: cache.pane_count(),
@@ -54,18 +71,18 @@ pub fn diagnostics(ctx: &RpcContext) -> RpcResult<Value> {
     })
 }
 
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TestResult {
+    name: &'static str,
+    ok: bool,
+    detail: Option<String>,
+}
+
 /// GET debug.self-test - Validate daemon can reach dependencies.
 pub fn self_test(ctx: &RpcContext) -> RpcResult<Value> {
     require_admin(ctx)?;
 
-    #[derive(Serialize)]
-    #[serde(rename_all = "camelCase")]
-    struct TestResult {
-        name: &'static str,
-        ok: bool,
-        detail: Option<String>,
-    }
-
     #[derive(Serialize)]
     #[serde(rename_all = "camelCase")]
     struct SelfTestResult {
@@ -126,6 +143,11 @@ pub fn self_test(ctx: &RpcContext) -> RpcResult<Value> {
         detail: Some(format!("{} sessions cached", ctx.cache.session_count())),
     });
 
+    // Test the database accepts writes (read-only mounts, full disks)
+    if let Some(path) = &ctx.db_path {
+        checks.push(db_writable_check(path));
+    }
+
     let all_ok = checks.iter().filter(|c| c.name != "ntm").all(|c| c.ok);
 
     let result = SelfTestResult {
@@ -138,6 +160,23 @@ pub fn self_test(ctx: &RpcContext) -> RpcResult<Value> {
     })
 }
 
+/// Open the database and make a write that is rolled back.
+fn db_writable_check(path: &Path) -> TestResult {
+    let result = db::open_database(path).and_then(|mut conn| db::check_writable(&mut conn));
+    match result {
+        Ok(()) => TestResult {
+            name: "db",
+            ok: true,
+            detail: Some(format!("{} is writable", path.display())),
+        },
+        Err(e) => TestResult {
+            name: "db",
+            ok: false,
+            detail: Some(format!("{} is not writable: {e}", path.display())),
+        },
+    }
+}
+
 /// GET debug.metrics - Performance metrics.
 pub fn metrics(ctx: &RpcContext) -> RpcResult<Value> {
     require_admin(ctx)?;
@@ -153,6 +192,44 @@ pub fn metrics(ctx: &RpcContext) -> RpcResult<Value> {
             "dbWrite": histogram_json(&summary.db_write),
             "rpcRequest": histogram_json(&summary.rpc_request),
         },
+        "rpcMethods": summary
+            .rpc_methods
+            .iter()
+            .map(|(method, latency)| {
+                (
+                    method.clone(),
+                    json!({
+                        "count": latency.count,
+                        "p50Us": latency.p50_us,
+                        "p95Us": latency.p95_us,
+                        "p99Us": latency.p99_us,
+                        "maxUs": latency.max_us,
+                    }),
+                )
+            })
+            .collect::<serde_json::Map<String, Value>>(),
+        "traffic": {
+            "requests": summary.rpc_traffic.requests,
+            "bytesIn": summary.rpc_traffic.bytes_in,
+            "bytesOut": summary.rpc_traffic.bytes_out,
+            "methods": summary.rpc_traffic.methods,
+        },
+        "reconcile": {
+            "runs": summary.reconcile.runs,
+            "sessionsCreated": summary.reconcile.counts.sessions_created,
+            "sessionsUpdated": summary.reconcile.counts.sessions_updated,
+            "sessionsEnded": summary.reconcile.counts.sessions_ended,
+            "panesCreated": summary.reconcile.counts.panes_created,
+            "panesUpdated": summary.reconcile.counts.panes_updated,
+        },
+        "polls": {
+            "tmux": poll_json(&summary.tmux_poll, ctx.cache.poll_error_streak(PollChannel::Tmux)),
+            "ntm": poll_json(&summary.ntm_poll, ctx.cache.poll_error_streak(PollChannel::Ntm)),
+            "snapshot": poll_json(
+                &summary.snapshot_poll,
+                ctx.cache.poll_error_streak(PollChannel::Snapshot),
+            ),
+        },
         "counters": {
             "sessionCount": ctx.cache.session_count(),
             "paneCount": ctx.cache.pane_count(),
@@ -171,15 +248,195 @@ fn histogram_json(stats: &crate::metrics::HistogramStats) -> Value {
     })
 }
 
-/// GET debug.log-tail - Recent log lines.
-/// Note: This is a placeholder - actual log tailing requires ring buffer or file read.
-pub fn log_tail(ctx: &RpcContext) -> RpcResult<Value> {
+fn poll_json(totals: &crate::metrics::PollTotals, error_streak: u32) -> Value {
+    json!({
+        "ticks": totals.ticks,
+        "ok": totals.ok,
+        "degraded": totals.degraded,
+        "failed": totals.failed,
+        "errorStreak": error_streak,
+    })
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LogTailParams {
+    lines: Option<usize>,
+    bytes: Option<u64>,
+}
+
+/// Default and maximum line / byte caps for debug.logTail.
+const LOG_TAIL_DEFAULT_LINES: usize = 200;
+const LOG_TAIL_MAX_LINES: usize = 5_000;
+const LOG_TAIL_DEFAULT_BYTES: u64 = 256 * 1024;
+const LOG_TAIL_MAX_BYTES: u64 = 4 * 1024 * 1024;
+
+/// GET debug.log-tail - Recent log lines from the current log file.
+pub fn log_tail(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
+    require_admin(ctx)?;
+    let params: LogTailParams = if params.is_null() {
+        LogTailParams::default()
+    } else {
+        parse_params(params)?
+    };
+
+    let Some(path) = ctx.config.current().logging.file else {
+        return Err(RpcError::new(
+            CODE_UNSUPPORTED,
+            "File logging is not configured",
+        ));
+    };
+
+    let lines = params
+        .lines
+        .unwrap_or(LOG_TAIL_DEFAULT_LINES)
+        .clamp(1, LOG_TAIL_MAX_LINES);
+    let bytes = params
+        .bytes
+        .unwrap_or(LOG_TAIL_DEFAULT_BYTES)
+        .clamp(1, LOG_TAIL_MAX_BYTES);
+    let tail = logging::tail_log(&path, lines, bytes).map_err(|e| {
+        RpcError::new(CODE_DEGRADED, format!("Unable to read log file: {e}"))
+    })?;
+
+    Ok(json!({
+        "file": path.display().to_string(),
+        "lines": tail.lines,
+        "truncated": tail.truncated,
+        "sizeBytes": tail.size_bytes,
+    }))
+}
+
+/// GET debug.logFiles - Current and rotated log files.
+pub fn log_files(ctx: &RpcContext) -> RpcResult<Value> {
     require_admin(ctx)?;
 
-    // For now, return a message indicating logs should be read from file
+    let Some(path) = ctx.config.current().logging.file else {
+        return Ok(json!({ "directory": null, "files": [] }));
+    };
+
+    let files = logging::list_log_files(&path)
+        .map_err(|e| RpcError::new(CODE_DEGRADED, format!("Unable to list log files: {e}")))?;
+    let files: Vec<Value> = files
+        .into_iter()
+        .map(|file| {
+            json!({
+                "name": file.name,
+                "sizeBytes": file.size_bytes,
+                "modifiedAt": file.modified_at,
+            })
+        })
+        .collect();
+
     Ok(json!({
-        "message": "Log tailing not yet implemented. Check log file directly.",
-        "configuredFile": ctx.config.current().logging.file,
+        "directory": path.parent().map(|dir| dir.display().to_string()),
+        "files": files,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LogFetchParams {
+    file: String,
+    #[serde(default)]
+    offset: u64,
+    limit: Option<usize>,
+}
+
+/// GET debug.logFetch - Read a slice of a log file listed by debug.logFiles.
+pub fn log_fetch(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
+    require_admin(ctx)?;
+    let params: LogFetchParams = parse_params(params)?;
+
+    let Some(base_path) = ctx.config.current().logging.file else {
+        return Err(RpcError::new(
+            CODE_UNSUPPORTED,
+            "File logging is not configured",
+        ));
+    };
+
+    let path = logging::resolve_log_file(&base_path, &params.file).map_err(|err| match err {
+        LogFileError::InvalidName => RpcError::new(
+            CODE_INVALID_PARAMS,
+            format!("Invalid log file: {}", params.file),
+        ),
+        LogFileError::NotFound => RpcError::new(
+            CODE_NOT_FOUND,
+            format!("Log file not found: {}", params.file),
+        ),
+    })?;
+
+    let limit = params
+        .limit
+        .unwrap_or(LOG_FETCH_DEFAULT_LIMIT)
+        .min(LOG_FETCH_MAX_LIMIT);
+    let (bytes, size) = logging::read_log_slice(&path, params.offset, limit)
+        .map_err(|e| RpcError::new(CODE_DEGRADED, format!("Unable to read log file: {e}")))?;
+    let next_offset = params.offset.saturating_add(bytes.len() as u64);
+
+    Ok(json!({
+        "file": params.file,
+        "offset": params.offset,
+        "nextOffset": next_offset,
+        "sizeBytes": size,
+        "eof": next_offset >= size,
+        "content": String::from_utf8_lossy(&bytes),
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ReconcilePreviewParams {
+    markdown: String,
+}
+
+/// POST debug.reconcilePreview - Parse pasted `ntm` markdown and dry-run
+/// reconcile against the current cache, merged with tmux's latest pane list
+/// the way the collector merges them. Nothing is written: the result shows
+/// what would be created, updated and ended. Sessions missing from the
+/// markdown are reported as ended without waiting out the end grace.
+pub fn reconcile_preview(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
+    require_admin(ctx)?;
+    let params: ReconcilePreviewParams = parse_params(params)?;
+    if params.markdown.len() > RECONCILE_PREVIEW_MAX_BYTES {
+        return Err(RpcError::new(
+            CODE_INVALID_PARAMS,
+            format!("markdown must be at most {RECONCILE_PREVIEW_MAX_BYTES} bytes"),
+        ));
+    }
+    let markdown = parse_ntm_markdown(&params.markdown).map_err(|err| {
+        RpcError::new(CODE_INVALID_PARAMS, format!("Unable to parse markdown: {}", err.reason))
+    })?;
+
+    let mut lookups = ReconcileLookups {
+        identity: SessionIdentity::from_config(&ctx.config.current().polling),
+        ..ReconcileLookups::default()
+    };
+    let tmux_panes = ctx.cache.tmux_panes();
+    let result = reconcile_all(
+        &ctx.cache,
+        Some(&markdown),
+        tmux_panes.as_deref(),
+        ctx.clock.now_unix(),
+        &mut lookups,
+    );
+
+    let mut sessions: Vec<SessionView> =
+        result.sessions.into_iter().map(SessionView::from).collect();
+    sessions.sort_by(|a, b| a.name.cmp(&b.name));
+    let panes: Vec<PaneView> = result.panes.into_iter().map(PaneView::from).collect();
+    Ok(json!({
+        "parsed": {
+            "sessions": markdown.sessions.len(),
+            "panes": markdown.panes.len(),
+        },
+        "sessionsCreated": result.sessions_created,
+        "sessionsUpdated": result.sessions_updated,
+        "sessionsEnded": result.ended_sessions,
+        "panesCreated": result.panes_created,
+        "panesUpdated": result.panes_updated,
+        "sessions": sessions,
+        "panes": panes,
     }))
 }
 
@@ -204,6 +461,98 @@ mod tests {
         RpcContext::new(cache, config)
     }
 
+    fn cache_state(cache: &Cache) -> Value {
+        let mut sessions = cache.all_sessions();
+        sessions.sort_by(|a, b| a.session_uid.cmp(&b.session_uid));
+        let mut panes = cache.all_panes();
+        panes.sort_by(|a, b| a.pane_uid.cmp(&b.pane_uid));
+        json!({ "sessions": sessions, "panes": panes })
+    }
+
+    #[test]
+    fn reconcile_preview_reports_changes_without_touching_cache() {
+        use crate::models::pane::Pane;
+        use crate::models::session::{Session, SessionStatus};
+
+        let ctx = admin_context();
+        let mut api = Session::new("ntm", "api", None, 100);
+        api.status = SessionStatus::Active;
+        let mut old = Session::new("ntm", "old", None, 100);
+        old.status = SessionStatus::Active;
+        ctx.cache.upsert_pane(Pane::new(api.session_uid.clone(), 0, 100, None, None, None));
+        ctx.cache.upsert_session(api);
+        ctx.cache.upsert_session(old);
+        let before = cache_state(&ctx.cache);
+
+        let markdown = "| session | pane | status | agent |\n| --- | --- | --- | --- |\n\
+                        | api | 0 | active | claude |\n| api | 1 | idle | codex |\n\
+                        | web | 0 | active | claude |\n";
+        let preview = reconcile_preview(&ctx, json!({ "markdown": markdown })).unwrap();
+
+        assert_eq!(preview["parsed"]["panes"], 3);
+        assert_eq!(preview["sessionsCreated"], 1, "{preview}");
+        assert_eq!(preview["sessionsUpdated"], 1);
+        assert_eq!(preview["sessionsEnded"], 1);
+        assert_eq!(preview["panesCreated"], 2);
+        assert_eq!(preview["panesUpdated"], 1);
+        let names: Vec<&str> = preview["sessions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|session| session["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["api", "old", "web"]);
+        assert_eq!(preview["sessions"][1]["status"], "ended");
+        assert_eq!(preview["panes"].as_array().unwrap().len(), 3);
+        assert_eq!(preview["panes"][1]["agentType"], "codex");
+
+        assert_eq!(cache_state(&ctx.cache), before, "preview must not mutate the cache");
+    }
+
+    #[test]
+    fn reconcile_preview_merges_the_latest_tmux_panes() {
+        use crate::parsers::tmux_panes::TmuxPaneMeta;
+
+        let ctx = admin_context();
+        ctx.cache.set_tmux_panes(vec![TmuxPaneMeta {
+            session_id: "$1".to_string(),
+            session_name: "api".to_string(),
+            window_id: "@1".to_string(),
+            window_index: 0,
+            pane_id: "%1".to_string(),
+            pane_index: 0,
+            pane_pid: 100,
+            pane_current_command: "claude".to_string(),
+            pane_last_activity: 0,
+            pane_dead: false,
+            pane_in_mode: false,
+        }]);
+
+        let markdown = "| session | pane | status | agent |\n| --- | --- | --- | --- |\n\
+                        | api | 0 | active | claude |\n";
+        let preview = reconcile_preview(&ctx, json!({ "markdown": markdown })).unwrap();
+
+        assert_eq!(preview["sessionsCreated"], 1, "{preview}");
+        assert_eq!(preview["sessions"][0]["tmuxSessionId"], "$1");
+        assert_eq!(preview["panes"].as_array().unwrap().len(), 1);
+        assert_eq!(preview["panes"][0]["tmuxPaneId"], "%1");
+        assert_eq!(preview["panes"][0]["agentType"], "claude");
+        assert!(ctx.cache.all_sessions().is_empty());
+    }
+
+    #[test]
+    fn reconcile_preview_requires_admin_and_valid_params() {
+        let ctx = non_admin_context();
+        assert!(reconcile_preview(&ctx, json!({ "markdown": "" })).is_err());
+
+        let ctx = admin_context();
+        let err = reconcile_preview(&ctx, json!({})).unwrap_err();
+        assert_eq!(err.code, CODE_INVALID_PARAMS);
+        let huge = "x".repeat(RECONCILE_PREVIEW_MAX_BYTES + 1);
+        let err = reconcile_preview(&ctx, json!({ "markdown": huge })).unwrap_err();
+        assert_eq!(err.code, CODE_INVALID_PARAMS);
+    }
+
     #[test]
     fn diagnostics_requires_admin() {
         let ctx = non_admin_context();
@@ -227,6 +576,29 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn db_writable_check_passes_for_in_memory_db() {
+        let check = db_writable_check(Path::new(":memory:"));
+        assert_eq!(check.name, "db");
+        assert!(check.ok, "{:?}", check.detail);
+    }
+
+    #[test]
+    fn self_test_reports_unwritable_db() {
+        // A directory cannot be opened as a database file
+        let dir = tempfile::TempDir::new().unwrap();
+        let mut ctx = admin_context();
+        ctx.db_path = Some(dir.path().to_path_buf());
+
+        let result = self_test(&ctx).unwrap();
+        let checks = result["checks"].as_array().unwrap();
+        let db_check = checks.iter().find(|c| c["name"] == "db").unwrap();
+        assert_eq!(db_check["ok"], false);
+        let detail = db_check["detail"].as_str().unwrap();
+        assert!(detail.contains("not writable"), "{detail}");
+        assert_eq!(result["ok"], false);
+    }
+
     #[test]
     fn metrics_requires_admin() {
         let ctx = non_admin_context();
@@ -240,5 +612,107 @@ mod tests {
         let result = metrics(&ctx).unwrap();
         assert!(result.get("timings").is_some());
         assert!(result.get("counters").is_some());
+        assert!(result["rpcMethods"].is_object());
+        assert!(result["reconcile"]["runs"].is_u64());
+        assert!(result["traffic"]["requests"].is_u64());
+        assert!(result["traffic"]["bytesOut"].is_u64());
+        assert!(result["traffic"]["methods"].is_object());
+        assert!(result["reconcile"]["sessionsCreated"].is_u64());
+        for channel in ["tmux", "ntm", "snapshot"] {
+            assert!(result["polls"][channel]["ticks"].is_u64(), "{channel}");
+            assert!(result["polls"][channel]["errorStreak"].is_u64(), "{channel}");
+        }
+    }
+
+    #[test]
+    fn metrics_reports_error_streak_from_cache() {
+        let ctx = admin_context();
+        ctx.cache.record_poll_result(PollChannel::Ntm, true);
+        ctx.cache.record_poll_result(PollChannel::Ntm, true);
+        let result = metrics(&ctx).unwrap();
+        assert_eq!(result["polls"]["ntm"]["errorStreak"], 2);
+        assert_eq!(result["polls"]["tmux"]["errorStreak"], 0);
+    }
+
+    fn file_logging_context(dir: &std::path::Path) -> RpcContext {
+        let log_path = dir.join("daemon.log");
+        let config_path = dir.join("config.toml");
+        std::fs::write(
+            &config_path,
+            format!("[logging]\nfile = \"{}\"\n", log_path.display()),
+        )
+        .unwrap();
+        let config = ConfigManager::load_from_fs(Some(config_path)).expect("config");
+        let mut ctx = RpcContext::new(Arc::new(Cache::new(100)), config);
+        ctx.is_admin = true;
+        ctx
+    }
+
+    #[test]
+    fn log_files_lists_rotations() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("daemon.log"), "now\n").unwrap();
+        std::fs::write(dir.path().join("daemon.log.1"), "before\n").unwrap();
+        let ctx = file_logging_context(dir.path());
+
+        let result = log_files(&ctx).unwrap();
+        let files = result["files"].as_array().unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[1]["name"], "daemon.log.1");
+        assert_eq!(files[1]["sizeBytes"], 7);
+    }
+
+    #[test]
+    fn log_fetch_returns_slice() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("daemon.log.1"), "line one\nline two\n").unwrap();
+        let ctx = file_logging_context(dir.path());
+
+        let result = log_fetch(
+            &ctx,
+            json!({ "file": "daemon.log.1", "offset": 5, "limit": 8 }),
+        )
+        .unwrap();
+        assert_eq!(result["content"], "one\nline");
+        assert_eq!(result["nextOffset"], 13);
+        assert_eq!(result["eof"], false);
+    }
+
+    #[test]
+    fn log_fetch_rejects_path_traversal() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let ctx = file_logging_context(dir.path());
+
+        for file in ["../config.toml", "/etc/passwd", "config.toml", "daemon.log/../x"] {
+            let err = log_fetch(&ctx, json!({ "file": file })).unwrap_err();
+            assert_eq!(err.code, CODE_INVALID_PARAMS, "{file}");
+        }
+    }
+
+    #[test]
+    fn log_tail_returns_last_lines() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("daemon.log"), "a\nb\nc\n").unwrap();
+        let ctx = file_logging_context(dir.path());
+
+        let result = log_tail(&ctx, json!({ "lines": 2 })).unwrap();
+        assert_eq!(result["lines"], json!(["b", "c"]));
+        assert_eq!(result["truncated"], true);
+
+        let result = log_tail(&ctx, Value::Null).unwrap();
+        assert_eq!(result["lines"], json!(["a", "b", "c"]));
+    }
+
+    #[test]
+    fn log_tail_without_file_logging_is_unsupported() {
+        let err = log_tail(&admin_context(), Value::Null).unwrap_err();
+        assert_eq!(err.code, CODE_UNSUPPORTED);
+    }
+
+    #[test]
+    fn log_fetch_requires_admin() {
+        let ctx = non_admin_context();
+        assert!(log_fetch(&ctx, json!({ "file": "daemon.log" })).is_err());
+        assert!(log_files(&ctx).is_err());
     }
 }
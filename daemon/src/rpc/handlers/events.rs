@@ -1,5 +1,8 @@
+use crate::bus::DaemonEvent;
 use crate::cache::{Cache, EventRecord};
-use crate::rpc::{parse_params, RpcContext, RpcError, RpcResult, CODE_UNSUPPORTED};
+use crate::db::{self, StoredEvent};
+use crate::detector::repeat;
+use crate::rpc::{parse_params, RpcContext, RpcError, RpcResult, CODE_NOT_FOUND, CODE_UNSUPPORTED};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
@@ -15,6 +18,63 @@ pub struct EventView {
     status: Option<String>,
 }
 
+/// Everything known about one event. Fields only the database keeps are
+/// `null` when the event was served from the cache.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventDetail {
+    #[serde(flatten)]
+    event: EventView,
+    source: Option<String>,
+    confidence: Option<f64>,
+    resolved_at: Option<i64>,
+    trigger: Option<String>,
+    message: Option<String>,
+    context_before: Option<i64>,
+    payload: Option<Value>,
+}
+
+impl From<EventRecord> for EventDetail {
+    fn from(record: EventRecord) -> Self {
+        Self {
+            event: to_event_view(record),
+            source: None,
+            confidence: None,
+            resolved_at: None,
+            trigger: None,
+            message: None,
+            context_before: None,
+            payload: None,
+        }
+    }
+}
+
+impl From<StoredEvent> for EventDetail {
+    fn from(stored: StoredEvent) -> Self {
+        Self {
+            event: EventView {
+                id: stored.id,
+                event_type: stored.event_type,
+                session_id: stored.session_uid,
+                pane_id: stored.pane_uid,
+                detected_at: stored.detected_at,
+                severity: stored.severity,
+                status: stored.status,
+            },
+            source: Some(stored.source),
+            confidence: stored.confidence,
+            resolved_at: stored.resolved_at,
+            trigger: stored.trigger,
+            message: stored.message,
+            context_before: stored.context_before,
+            // Payloads are JSON text; anything else is passed through as a string
+            payload: stored
+                .payload
+                .map(|raw| serde_json::from_str(&raw).unwrap_or(Value::String(raw))),
+        }
+    }
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EscalationView {
@@ -29,7 +89,16 @@ pub struct EscalationView {
 #[serde(rename_all = "camelCase")]
 struct EventsListParams {
     cursor: Option<i64>,
+    offset: Option<usize>,
     limit: Option<usize>,
+    session_id: Option<String>,
+    event_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EventsGetParams {
+    event_id: i64,
 }
 
 #[derive(Debug, Deserialize)]
@@ -58,8 +127,39 @@ fn to_event_view(record: EventRecord) -> EventView {
 }
 
 pub fn event_views(cache: &Cache, cursor: Option<i64>, limit: Option<usize>) -> Vec<EventView> {
-    let mut records: Vec<EventView> = cache
-        .recent_events()
+    views_after(cache.recent_events(), cursor, limit)
+}
+
+/// Cached events matching the `events.list` filters. The cache's indexes
+/// answer the session filter and rule out absent types without a scan.
+fn filtered_views(cache: &Cache, params: &EventsListParams) -> Vec<EventView> {
+    if let Some(event_type) = &params.event_type {
+        if cache.event_type_count(event_type) == 0 {
+            return Vec::new();
+        }
+    }
+    let records = match &params.session_id {
+        Some(session_uid) => cache.session_events(session_uid),
+        None => cache.recent_events(),
+    };
+    let records = records
+        .into_iter()
+        .filter(|record| {
+            params
+                .event_type
+                .as_ref()
+                .is_none_or(|event_type| record.event_type == *event_type)
+        })
+        .collect();
+    views_after(records, params.cursor, None)
+}
+
+fn views_after(
+    records: Vec<EventRecord>,
+    cursor: Option<i64>,
+    limit: Option<usize>,
+) -> Vec<EventView> {
+    let mut records: Vec<EventView> = records
         .into_iter()
         .filter(|record| cursor.map(|c| record.event_id.unwrap_or(0) > c).unwrap_or(true))
         .map(to_event_view)
@@ -72,6 +172,34 @@ pub fn event_views(cache: &Cache, cursor: Option<i64>, limit: Option<usize>) ->
     records
 }
 
+/// Record an event in the cache and publish it on the daemon bus so push
+/// notifiers can forward it without waiting for the next snapshot. The
+/// `[detector]` repeat rule may raise its severity first, and publishes any
+/// escalation it raises ahead of the event.
+pub fn publish(ctx: &RpcContext, mut record: EventRecord) {
+    let detector = ctx.config.current().detector;
+    if let Some(escalation) = repeat::promote_repeats(ctx.cache.as_ref(), &detector, &mut record) {
+        publish(ctx, escalation);
+    }
+    ctx.cache.record_event(record.clone());
+    let view = to_event_view(record);
+    let event = DaemonEvent {
+        event_type: view.event_type.as_str().into(),
+        session_uid: view.session_id.clone(),
+        pane_uid: Some(view.pane_id.clone()),
+        detected_at: view.detected_at,
+        payload: serde_json::to_value(&view).ok(),
+    };
+    // No subscribers just means no push client is connected
+    let _ = ctx.bus.publish_event(event);
+}
+
+/// Params for an `events.push` notification carrying the given bus events.
+pub fn push_payload(events: &[DaemonEvent]) -> Value {
+    let events: Vec<&Value> = events.iter().filter_map(|e| e.payload.as_ref()).collect();
+    json!({ "events": events })
+}
+
 pub fn last_event_id(cache: &Cache) -> i64 {
     cache
         .recent_events()
@@ -85,21 +213,61 @@ pub fn list(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
     let params: EventsListParams = if params.is_null() {
         EventsListParams {
             cursor: None,
+            offset: None,
             limit: None,
+            session_id: None,
+            event_type: None,
         }
     } else {
         parse_params(params)?
     };
 
-    let events = event_views(ctx.cache.as_ref(), params.cursor, params.limit);
+    let limit = params
+        .limit
+        .unwrap_or(ctx.config.current().rpc.default_event_limit);
+    let offset = params.offset.unwrap_or(0);
+    let matching = filtered_views(ctx.cache.as_ref(), &params);
+    let total = matching.len();
+    let events: Vec<EventView> = matching.into_iter().skip(offset).take(limit).collect();
     let next_event_id = events.last().map(|event| event.id + 1).unwrap_or(0);
 
     Ok(json!({
         "events": events,
+        "total": total,
+        "offset": offset,
         "nextEventId": next_event_id
     }))
 }
 
+/// One event with full detail: the database row when there is one, else the
+/// cached record.
+pub fn get(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
+    let params: EventsGetParams = parse_params(params)?;
+    if let Some(stored) = stored_event(ctx, params.event_id) {
+        return Ok(json!({ "event": EventDetail::from(stored) }));
+    }
+    let record = ctx
+        .cache
+        .recent_events()
+        .into_iter()
+        .find(|record| record.event_id == Some(params.event_id))
+        .ok_or_else(|| RpcError::new(CODE_NOT_FOUND, "Event not found"))?;
+    Ok(json!({ "event": EventDetail::from(record) }))
+}
+
+/// Read an event from the database. Failures are logged and treated as a
+/// miss so the cache can still answer.
+fn stored_event(ctx: &RpcContext, event_id: i64) -> Option<StoredEvent> {
+    let path = ctx.db_path.as_ref()?;
+    match db::open_database(path).and_then(|conn| db::load_event(&conn, event_id)) {
+        Ok(stored) => stored,
+        Err(err) => {
+            tracing::warn!(error = %err, event_id, "events.get: database read failed");
+            None
+        }
+    }
+}
+
 pub fn subscribe(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
     let params: SubscribeParams = parse_params(params)?;
     let last_event_id = params
@@ -172,6 +340,128 @@ mod tests {
         ctx
     }
 
+    #[test]
+    fn events_list_filters_by_session_and_type() {
+        let ctx = test_ctx_with_events();
+        ctx.cache.record_event(EventRecord {
+            event_id: Some(6),
+            session_uid: "sess-3".to_string(),
+            pane_uid: "pane-3".to_string(),
+            event_type: "compact".to_string(),
+            detected_at: 1006,
+            severity: None,
+            status: None,
+        });
+
+        let result = list(&ctx, json!({"sessionId": "sess-3"})).unwrap();
+        let ids: Vec<&Value> = result["events"].as_array().unwrap().iter().map(|e| &e["id"]).collect();
+        assert_eq!(ids, vec![3, 6]);
+        assert_eq!(result["total"], 2);
+
+        let result = list(&ctx, json!({"sessionId": "sess-3", "eventType": "compact"})).unwrap();
+        assert_eq!(result["events"][0]["id"], 6);
+        assert_eq!(result["total"], 1);
+
+        let result = list(&ctx, json!({"eventType": "escalation", "cursor": 3})).unwrap();
+        assert_eq!(result["total"], 0);
+        let result = list(&ctx, json!({"eventType": "idle"})).unwrap();
+        assert_eq!(result["total"], 0);
+        let result = list(&ctx, json!({"sessionId": "sess-missing"})).unwrap();
+        assert_eq!(result["total"], 0);
+    }
+
+    #[test]
+    fn publish_records_and_broadcasts_event() {
+        let ctx = test_ctx();
+        let mut rx = ctx.bus.subscribe_events();
+        publish(
+            &ctx,
+            EventRecord {
+                event_id: Some(7),
+                session_uid: "sess-1".to_string(),
+                pane_uid: "pane-1".to_string(),
+                event_type: "escalation".to_string(),
+                detected_at: 1234,
+                severity: Some("high".to_string()),
+                status: Some("pending".to_string()),
+            },
+        );
+
+        assert_eq!(ctx.cache.event_count(), 1);
+        let event = rx.try_recv().expect("event published");
+        assert!(matches!(event.event_type, crate::bus::DaemonEventType::Escalation));
+        let payload = push_payload(&[event]);
+        assert_eq!(payload["events"][0]["id"], 7);
+        assert_eq!(payload["events"][0]["sessionId"], "sess-1");
+        assert_eq!(payload["events"][0]["severity"], "high");
+    }
+
+    #[test]
+    fn publish_without_subscribers_still_records() {
+        let ctx = test_ctx();
+        publish(
+            &ctx,
+            EventRecord {
+                event_id: Some(1),
+                session_uid: "sess-1".to_string(),
+                pane_uid: "pane-1".to_string(),
+                event_type: "compact".to_string(),
+                detected_at: 1,
+                severity: None,
+                status: None,
+            },
+        );
+        assert_eq!(last_event_id(ctx.cache.as_ref()), 1);
+    }
+
+    #[test]
+    fn events_get_returns_cached_event() {
+        let ctx = test_ctx_with_events();
+        let result = get(&ctx, json!({"eventId": 3})).unwrap();
+        let event = &result["event"];
+        assert_eq!(event["id"], 3);
+        assert_eq!(event["eventType"], "escalation");
+        assert_eq!(event["sessionId"], "sess-3");
+        assert_eq!(event["status"], "pending");
+        assert!(event["message"].is_null());
+    }
+
+    #[test]
+    fn events_get_prefers_full_database_row() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("events.db");
+        let conn = db::open_database(&path).unwrap();
+        conn.execute_batch(
+            "PRAGMA foreign_keys = OFF;
+             INSERT INTO events (id, session_uid, pane_uid, type, detected_at, source, confidence,
+                                 trigger, message, payload)
+             VALUES (3, 'sess-3', 'pane-3', 'escalation', 1003, 'detector', 0.9,
+                     'needs input', 'Waiting for approval', '{\"line\":42}');",
+        )
+        .unwrap();
+        drop(conn);
+
+        let mut ctx = test_ctx_with_events();
+        ctx.db_path = Some(path);
+        let event = get(&ctx, json!({"eventId": 3})).unwrap()["event"].clone();
+        assert_eq!(event["source"], "detector");
+        assert_eq!(event["message"], "Waiting for approval");
+        assert_eq!(event["payload"]["line"], 42);
+
+        // Not in the database: the cache still answers
+        let cached = get(&ctx, json!({"eventId": 4})).unwrap();
+        assert_eq!(cached["event"]["eventType"], "compact");
+    }
+
+    #[test]
+    fn events_get_missing_id_is_not_found() {
+        let ctx = test_ctx_with_events();
+        let err = get(&ctx, json!({"eventId": 99})).unwrap_err();
+        assert_eq!(err.code, CODE_NOT_FOUND);
+        let err = get(&ctx, json!({})).unwrap_err();
+        assert_eq!(err.code, crate::rpc::CODE_INVALID_PARAMS);
+    }
+
     #[test]
     fn events_list_empty_cache() {
         let ctx = test_ctx();
@@ -206,6 +496,49 @@ mod tests {
         assert_eq!(events.len(), 2);
     }
 
+    fn ctx_with_default_limit(dir: &std::path::Path, limit: usize) -> RpcContext {
+        let config_path = dir.join("config.toml");
+        std::fs::write(&config_path, format!("[rpc]\ndefault-event-limit = {limit}\n")).unwrap();
+        let config = ConfigManager::load_from_fs(Some(config_path)).expect("config");
+        let ctx = RpcContext::new(Arc::new(Cache::new(100)), config);
+        for i in 1..=5 {
+            ctx.cache.record_event(EventRecord {
+                event_id: Some(i),
+                session_uid: "s".to_string(),
+                pane_uid: "p".to_string(),
+                event_type: "compact".to_string(),
+                detected_at: 1000 + i,
+                severity: None,
+                status: None,
+            });
+        }
+        ctx
+    }
+
+    #[test]
+    fn events_list_omitted_limit_uses_configured_default() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let ctx = ctx_with_default_limit(dir.path(), 3);
+        let result = list(&ctx, Value::Null).unwrap();
+        let events = result["events"].as_array().unwrap();
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[0]["id"], 1);
+        assert_eq!(result["nextEventId"], 4);
+
+        let result = list(&ctx, serde_json::json!({"cursor": 1})).unwrap();
+        assert_eq!(result["events"].as_array().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn events_list_explicit_limit_overrides_default() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let ctx = ctx_with_default_limit(dir.path(), 3);
+        let result = list(&ctx, serde_json::json!({"limit": 5})).unwrap();
+        assert_eq!(result["events"].as_array().unwrap().len(), 5);
+        let result = list(&ctx, serde_json::json!({"limit": 1})).unwrap();
+        assert_eq!(result["events"].as_array().unwrap().len(), 1);
+    }
+
     #[test]
     fn events_list_cursor_and_limit_combined() {
         let ctx = test_ctx_with_events();
@@ -215,6 +548,43 @@ mod tests {
         assert_eq!(events[0]["id"], 3);
     }
 
+    #[test]
+    fn events_list_first_page_reports_total() {
+        let ctx = test_ctx_with_events();
+        let result = list(&ctx, serde_json::json!({"limit": 2})).unwrap();
+        let events = result["events"].as_array().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["id"], 1);
+        assert_eq!(result["total"], 5);
+        assert_eq!(result["offset"], 0);
+    }
+
+    #[test]
+    fn events_list_offset_pages_through_events() {
+        let ctx = test_ctx_with_events();
+        let result = list(&ctx, serde_json::json!({"offset": 2, "limit": 2})).unwrap();
+        let events = result["events"].as_array().unwrap();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["id"], 3);
+        assert_eq!(events[1]["id"], 4);
+        assert_eq!(result["total"], 5);
+        assert_eq!(result["offset"], 2);
+
+        // Past the end: empty page, total unchanged
+        let result = list(&ctx, serde_json::json!({"offset": 10})).unwrap();
+        assert!(result["events"].as_array().unwrap().is_empty());
+        assert_eq!(result["total"], 5);
+        assert_eq!(result["nextEventId"], 0);
+    }
+
+    #[test]
+    fn events_list_total_counts_events_after_cursor() {
+        let ctx = test_ctx_with_events();
+        let result = list(&ctx, serde_json::json!({"cursor": 3, "limit": 1})).unwrap();
+        assert_eq!(result["events"][0]["id"], 4);
+        assert_eq!(result["total"], 2);
+    }
+
     #[test]
     fn events_list_next_event_id() {
         let ctx = test_ctx_with_events();
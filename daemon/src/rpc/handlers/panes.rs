@@ -1,11 +1,17 @@
+use crate::bus::{ClientUpdate, PANE_OUTPUT_UPDATE};
 use crate::cache::Cache;
-use crate::models::pane::Pane;
-use crate::command::{CommandCategory, CommandConfig, CommandRunner, CommandSpec, CommandError};
+use crate::models::pane::{Pane, PaneStatus};
+use crate::models::session::SessionStatus;
+use crate::command::{CommandCategory, CommandSpec, CommandError};
 use crate::redaction::default_redactor;
-use crate::rpc::{parse_params, RpcContext, RpcError, RpcResult, CODE_DEGRADED, CODE_INVALID_PARAMS, CODE_NOT_FOUND};
+use crate::rpc::{
+    parse_params, PaneTails, RpcContext, RpcError, RpcResult, CODE_DEGRADED, CODE_INVALID_PARAMS,
+    CODE_NOT_FOUND, CODE_UNSUPPORTED,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::collections::BTreeMap;
+use std::time::Duration;
 
 /// Validates that a pane_id is safe for use with tmux commands.
 /// Valid tmux pane targets: %<digits>, @<digits>:<digits>, session:window.pane
@@ -36,10 +42,23 @@ pub struct PaneView {
     pub tmux_pane_id: Option<String>,
     pub tmux_window_id: Option<String>,
     pub tmux_pane_pid: Option<i64>,
+    /// Present only when `capture.capture-cwd` recorded one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// Allowlisted environment (`capture.env-allowlist`), values redacted.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<BTreeMap<String, String>>,
+    /// When the pane's newest cached `compact` event was detected.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_compact_at: Option<i64>,
+    /// Seconds from `last_compact_at` to when the view was built.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub secs_since_compact: Option<i64>,
 }
 
 impl From<Pane> for PaneView {
     fn from(pane: Pane) -> Self {
+        let context = pane.context.unwrap_or_default();
         Self {
             pane_id: pane.pane_uid,
             session_id: pane.session_uid,
@@ -55,16 +74,61 @@ impl From<Pane> for PaneView {
             tmux_pane_id: pane.tmux_pane_id,
             tmux_window_id: pane.tmux_window_id,
             tmux_pane_pid: pane.tmux_pane_pid,
+            cwd: context.cwd,
+            env: (!context.env.is_empty()).then_some(context.env),
+            last_compact_at: None,
+            secs_since_compact: None,
         }
     }
 }
 
+impl PaneView {
+    /// Fill in the compact fields from the pane's last compact, as of `now`.
+    pub fn with_last_compact(mut self, last_compact_at: Option<i64>, now: i64) -> Self {
+        self.last_compact_at = last_compact_at;
+        self.secs_since_compact = last_compact_at.map(|at| now.saturating_sub(at).max(0));
+        self
+    }
+}
+
+/// A cached pane's view, with its compact fields filled in from the cache.
+fn cached_pane_view(cache: &Cache, pane: Pane, now: i64) -> PaneView {
+    let last_compact_at = cache.pane_last_compact_at(&pane.pane_uid);
+    PaneView::from(pane).with_last_compact(last_compact_at, now)
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct PaneGetParams {
     pane_id: String,
 }
 
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PanesListParams {
+    session_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PanesSearchParams {
+    query: String,
+}
+
+/// Longest accepted `panes.search` query.
+const MAX_SEARCH_QUERY_LEN: usize = 128;
+
+/// A `panes.search` hit: the pane, the session it belongs to, and which
+/// field matched.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaneMatch {
+    pub pane: PaneView,
+    pub session_name: Option<String>,
+    pub session_status: Option<String>,
+    pub matched_field: &'static str,
+}
+
 #[derive(Debug, Deserialize)]
 #[serde(rename_all = "camelCase")]
 struct PanePreviewParams {
@@ -73,24 +137,117 @@ struct PanePreviewParams {
     max_chars: Option<usize>,
 }
 
-pub fn pane_views(cache: &Cache) -> Vec<PaneView> {
+pub fn pane_views(cache: &Cache, now: i64) -> Vec<PaneView> {
     cache
         .all_panes()
         .into_iter()
-        .map(PaneView::from)
+        .map(|pane| cached_pane_view(cache, pane, now))
         .collect()
 }
 
+pub fn list(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
+    let params: PanesListParams = if params.is_null() {
+        PanesListParams { session_id: None }
+    } else {
+        parse_params(params)?
+    };
+
+    let mut panes = pane_views(ctx.cache.as_ref(), ctx.clock.now_unix());
+    if let Some(ref session_id) = params.session_id {
+        if ctx.cache.get_session(session_id).is_none() {
+            return Err(RpcError::new(CODE_NOT_FOUND, "Session not found"));
+        }
+        panes.retain(|pane| pane.session_id == *session_id);
+    }
+    panes.sort_by(|a, b| {
+        a.session_id
+            .cmp(&b.session_id)
+            .then(a.pane_index.cmp(&b.pane_index))
+    });
+
+    Ok(json!({ "panes": panes }))
+}
+
 pub fn get(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
     let params: PaneGetParams = parse_params(params)?;
     let pane = ctx
         .cache
         .get_pane(&params.pane_id)
         .ok_or_else(|| RpcError::new(CODE_NOT_FOUND, "Pane not found"))?;
-    Ok(json!({ "pane": PaneView::from(pane) }))
+    let view = cached_pane_view(ctx.cache.as_ref(), pane, ctx.clock.now_unix());
+    Ok(json!({ "pane": view }))
+}
+
+/// The first searchable field of `pane` containing `needle` (lowercase).
+fn matched_field(pane: &PaneView, needle: &str) -> Option<&'static str> {
+    let fields = [
+        ("currentCommand", pane.current_command.as_deref()),
+        ("agentType", pane.agent_type.as_deref()),
+        ("tmuxPaneId", pane.tmux_pane_id.as_deref()),
+        ("tmuxWindowId", pane.tmux_window_id.as_deref()),
+    ];
+    fields.into_iter().find_map(|(name, value)| {
+        value
+            .filter(|value| value.to_lowercase().contains(needle))
+            .map(|_| name)
+    })
+}
+
+/// Case-insensitive substring search over every pane's command, agent type
+/// and tmux ids, across all sessions.
+pub fn search(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
+    let params: PanesSearchParams = parse_params(params)?;
+    let query = params.query.trim();
+    if query.is_empty() || query.len() > MAX_SEARCH_QUERY_LEN {
+        return Err(RpcError::new(
+            CODE_INVALID_PARAMS,
+            format!("query must be 1-{MAX_SEARCH_QUERY_LEN} bytes"),
+        ));
+    }
+    let needle = query.to_lowercase();
+
+    let mut matches: Vec<PaneMatch> = pane_views(ctx.cache.as_ref(), ctx.clock.now_unix())
+        .into_iter()
+        .filter_map(|pane| {
+            let matched_field = matched_field(&pane, &needle)?;
+            let session = ctx.cache.get_session(&pane.session_id);
+            Some(PaneMatch {
+                session_name: session.as_ref().map(|s| s.name.clone()),
+                session_status: session.map(|s| s.status.as_str().to_string()),
+                pane,
+                matched_field,
+            })
+        })
+        .collect();
+    matches.sort_by(|a, b| {
+        a.pane
+            .session_id
+            .cmp(&b.pane.session_id)
+            .then(a.pane.pane_index.cmp(&b.pane.pane_index))
+    });
+
+    Ok(json!({ "matches": matches }))
 }
 
-pub fn output_preview(_ctx: &RpcContext, params: Value) -> RpcResult<Value> {
+/// Appended to preview content cut by the byte cap.
+const PREVIEW_ELISION: &str = "\n…[truncated]";
+
+/// Cut `text` to at most `max_bytes` bytes on a UTF-8 boundary, ending with
+/// `PREVIEW_ELISION`. Returns the text and whether it was truncated.
+fn truncate_preview(text: String, max_bytes: usize) -> (String, bool) {
+    if text.len() <= max_bytes {
+        return (text, false);
+    }
+    let mut cut = max_bytes.saturating_sub(PREVIEW_ELISION.len());
+    while !text.is_char_boundary(cut) {
+        cut -= 1;
+    }
+    let mut truncated = text[..cut].to_string();
+    truncated.push_str(PREVIEW_ELISION);
+    (truncated, true)
+}
+
+pub fn output_preview(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
     let params: PanePreviewParams = parse_params(params)?;
 
     // Validate pane_id to prevent command injection
@@ -115,7 +272,7 @@ pub fn output_preview(_ctx: &RpcContext, params: Value) -> RpcResult<Value> {
         category: CommandCategory::TmuxFast,
     };
 
-    let runner = CommandRunner::new(CommandConfig::default());
+    let runner = ctx.command_runner();
     let output_result = if let Ok(handle) = tokio::runtime::Handle::try_current() {
         handle.block_on(runner.run(command))
     } else {
@@ -135,17 +292,17 @@ pub fn output_preview(_ctx: &RpcContext, params: Value) -> RpcResult<Value> {
 
     let raw = String::from_utf8_lossy(&output.stdout);
     let redacted = default_redactor().redact(&raw);
-    let truncated = redacted.len() > max_chars;
-    let content = if truncated {
+    let char_truncated = redacted.chars().count() > max_chars;
+    let content = if char_truncated {
         redacted.chars().take(max_chars).collect::<String>()
     } else {
         redacted
     };
+    let max_bytes = ctx.config.current().capture.preview_max_bytes;
+    let (content, byte_truncated) = truncate_preview(content, max_bytes);
+    let truncated = char_truncated || byte_truncated;
 
-    let captured_at = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|duration| duration.as_secs() as i64)
-        .unwrap_or(0);
+    let captured_at = ctx.clock.now_unix();
 
     let line_count = content.lines().count();
 
@@ -160,6 +317,91 @@ pub fn output_preview(_ctx: &RpcContext, params: Value) -> RpcResult<Value> {
     }))
 }
 
+/// Start pushing `pane.output` notifications with the lines appended to a
+/// pane's output. Captures come from the tmux collector, so this needs
+/// `capture.capture-output`; the stream ends with `ended: true` when the
+/// pane or its session ends, or on `panes.untail`.
+pub fn tail(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
+    let params: PaneGetParams = parse_params(params)?;
+    let tails = connection_tails(ctx)?;
+    if !ctx.config.current().capture.capture_output {
+        return Err(RpcError::new(
+            CODE_UNSUPPORTED,
+            "panes.tail requires capture.capture-output",
+        ));
+    }
+    let pane = ctx
+        .cache
+        .get_pane(&params.pane_id)
+        .ok_or_else(|| RpcError::new(CODE_NOT_FOUND, "Pane not found"))?;
+    if pane_ended(&ctx.cache, &pane) {
+        return Err(RpcError::new(CODE_INVALID_PARAMS, "Pane has ended"));
+    }
+    tails.start(&pane.pane_uid);
+    Ok(json!({ "paneId": pane.pane_uid, "tailing": true }))
+}
+
+/// Stop a `panes.tail` stream. Untailing a pane that is not tailed is not
+/// an error; `stopped` reports whether one was running.
+pub fn untail(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
+    let params: PaneGetParams = parse_params(params)?;
+    let stopped = connection_tails(ctx)?.stop(&params.pane_id);
+    Ok(json!({ "paneId": params.pane_id, "tailing": false, "stopped": stopped }))
+}
+
+fn connection_tails(ctx: &RpcContext) -> RpcResult<&PaneTails> {
+    ctx.tails.as_deref().ok_or_else(|| {
+        RpcError::new(
+            CODE_UNSUPPORTED,
+            "panes.tail needs a transport with push notifications (stdio or ws)",
+        )
+    })
+}
+
+fn pane_ended(cache: &Cache, pane: &Pane) -> bool {
+    pane.status == PaneStatus::Ended
+        || cache
+            .get_session(&pane.session_uid)
+            .is_some_and(|session| session.status == SessionStatus::Ended)
+}
+
+/// `pane.output` params for the new, redacted lines in a capture published
+/// by the tmux collector, if this connection tails that pane and any were
+/// appended.
+pub fn tail_output(ctx: &RpcContext, update: &ClientUpdate) -> Option<Value> {
+    if update.kind != PANE_OUTPUT_UPDATE {
+        return None;
+    }
+    let tails = ctx.tails.as_deref()?;
+    let payload = update.payload.as_ref()?;
+    let pane_id = payload.get("paneId")?.as_str()?;
+    let output = payload.get("output")?.as_str()?;
+    let lines = tails.append(pane_id, &default_redactor().redact(output))?;
+    if lines.is_empty() {
+        return None;
+    }
+    Some(json!({ "paneId": pane_id, "lines": lines, "ended": false }))
+}
+
+/// Stop tails whose pane ended or left the cache, returning the final
+/// `pane.output` params (`ended: true`) for each.
+pub fn ended_tails(ctx: &RpcContext) -> Vec<Value> {
+    let Some(tails) = ctx.tails.as_deref() else {
+        return Vec::new();
+    };
+    tails
+        .tailed()
+        .into_iter()
+        .filter(|pane_uid| {
+            ctx.cache
+                .get_pane(pane_uid)
+                .is_none_or(|pane| pane_ended(&ctx.cache, &pane))
+        })
+        .filter(|pane_uid| tails.stop(pane_uid))
+        .map(|pane_uid| json!({ "paneId": pane_uid, "lines": [], "ended": true }))
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -192,9 +434,32 @@ mod tests {
             ended_at: None,
             status: PaneStatus::Active,
             status_reason: None,
+            context: None,
         }
     }
 
+    #[test]
+    fn truncate_preview_cuts_oversized_line_on_char_boundary() {
+        // One long line of 3-byte characters, so most cut points land mid-char.
+        let line = "✓".repeat(2_000);
+        for max_bytes in [1024, 1025, 1026, 1500] {
+            let (content, truncated) = truncate_preview(line.clone(), max_bytes);
+            assert!(truncated);
+            assert!(content.len() <= max_bytes, "{} > {max_bytes}", content.len());
+            assert!(content.ends_with(PREVIEW_ELISION));
+            let body = content.strip_suffix(PREVIEW_ELISION).unwrap();
+            assert!(body.chars().all(|c| c == '✓'));
+            assert!(body.len() + PREVIEW_ELISION.len() + 3 > max_bytes, "cut too early");
+        }
+    }
+
+    #[test]
+    fn truncate_preview_leaves_small_output() {
+        let (content, truncated) = truncate_preview("hello\n".to_string(), 1024);
+        assert_eq!(content, "hello\n");
+        assert!(!truncated);
+    }
+
     #[test]
     fn valid_pane_ids() {
         assert!(is_valid_pane_id("%0"));
@@ -236,12 +501,99 @@ mod tests {
         assert_eq!(result.unwrap_err().code, CODE_NOT_FOUND);
     }
 
+    #[test]
+    fn panes_list_filters_by_session() {
+        let ctx = test_ctx();
+        ctx.cache.upsert_session(crate::models::session::Session::new("tmux", "alpha", None, 1000));
+        let session_uid = ctx.cache.all_sessions()[0].session_uid.clone();
+        let mut second = make_pane("p2", &session_uid);
+        second.pane_index = 1;
+        ctx.cache.upsert_pane(second);
+        ctx.cache.upsert_pane(make_pane("p1", &session_uid));
+        ctx.cache.upsert_pane(make_pane("p3", "other"));
+
+        let result = list(&ctx, json!({"sessionId": session_uid})).unwrap();
+        let panes = result["panes"].as_array().unwrap();
+        assert_eq!(panes.len(), 2);
+        assert_eq!(panes[0]["paneId"], "p1");
+        assert_eq!(panes[1]["paneId"], "p2");
+    }
+
+    #[test]
+    fn panes_list_unknown_session_not_found() {
+        let ctx = test_ctx();
+        let result = list(&ctx, json!({"sessionId": "missing"}));
+        assert_eq!(result.unwrap_err().code, CODE_NOT_FOUND);
+    }
+
+    #[test]
+    fn panes_list_without_filter_returns_all() {
+        let ctx = test_ctx();
+        ctx.cache.upsert_pane(make_pane("p1", "s1"));
+        ctx.cache.upsert_pane(make_pane("p2", "s2"));
+        let result = list(&ctx, Value::Null).unwrap();
+        assert_eq!(result["panes"].as_array().unwrap().len(), 2);
+    }
+
+    fn seed_search_panes(ctx: &RpcContext) {
+        let mut session = crate::models::session::Session::new("tmux", "work", None, 1000);
+        session.session_uid = "s1".to_string();
+        ctx.cache.upsert_session(session);
+
+        ctx.cache.upsert_pane(make_pane("p1", "s1"));
+        let mut editor = make_pane("p2", "s1");
+        editor.pane_index = 1;
+        editor.tmux_pane_id = Some("%7".to_string());
+        editor.agent_type = None;
+        editor.current_command = Some("nvim".to_string());
+        ctx.cache.upsert_pane(editor);
+        let mut codex = make_pane("p3", "s2");
+        codex.agent_type = Some("codex".to_string());
+        codex.current_command = Some("node".to_string());
+        ctx.cache.upsert_pane(codex);
+    }
+
+    #[test]
+    fn search_matches_command_substring_case_insensitively() {
+        let ctx = test_ctx();
+        seed_search_panes(&ctx);
+        let result = search(&ctx, json!({ "query": "VIM" })).unwrap();
+        let matches = result["matches"].as_array().unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0]["pane"]["paneId"], "p2");
+        assert_eq!(matches[0]["matchedField"], "currentCommand");
+        assert_eq!(matches[0]["sessionName"], "work");
+    }
+
+    #[test]
+    fn search_matches_agent_type_across_sessions() {
+        let ctx = test_ctx();
+        seed_search_panes(&ctx);
+        let result = search(&ctx, json!({ "query": "codex" })).unwrap();
+        let matches = result["matches"].as_array().unwrap();
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0]["pane"]["sessionId"], "s2");
+        assert_eq!(matches[0]["matchedField"], "agentType");
+        // The session is not in the cache, so there is no name to report
+        assert!(matches[0]["sessionName"].is_null());
+
+        let result = search(&ctx, json!({ "query": "%7" })).unwrap();
+        assert_eq!(result["matches"][0]["matchedField"], "tmuxPaneId");
+    }
+
+    #[test]
+    fn search_rejects_blank_query() {
+        let ctx = test_ctx();
+        let err = search(&ctx, json!({ "query": "  " })).unwrap_err();
+        assert_eq!(err.code, CODE_INVALID_PARAMS);
+    }
+
     #[test]
     fn pane_views_returns_all() {
         let ctx = test_ctx();
         ctx.cache.upsert_pane(make_pane("p1", "s1"));
         ctx.cache.upsert_pane(make_pane("p2", "s1"));
-        let views = pane_views(ctx.cache.as_ref());
+        let views = pane_views(ctx.cache.as_ref(), ctx.clock.now_unix());
         assert_eq!(views.len(), 2);
     }
 
@@ -256,4 +608,104 @@ mod tests {
         assert_eq!(view.tmux_pane_pid, Some(12345));
         assert_eq!(view.agent_type.as_deref(), Some("claude"));
     }
+
+    #[test]
+    fn compact_event_sets_secs_since_compact() {
+        let now = 1_700_000_000;
+        let ctx = test_ctx().with_clock(Arc::new(crate::clock::MockClock::new(now)));
+        ctx.cache.upsert_pane(make_pane("p1", "s1"));
+        let result = get(&ctx, json!({"paneId": "p1"})).unwrap();
+        assert!(result["pane"].get("secsSinceCompact").is_none());
+
+        ctx.cache.record_event(crate::cache::EventRecord {
+            event_id: None,
+            session_uid: "s1".to_string(),
+            pane_uid: "p1".to_string(),
+            event_type: "compact".to_string(),
+            detected_at: now - 90,
+            severity: None,
+            status: None,
+        });
+        let result = get(&ctx, json!({"paneId": "p1"})).unwrap();
+        assert_eq!(result["pane"]["lastCompactAt"], now - 90);
+        assert_eq!(result["pane"]["secsSinceCompact"], 90);
+
+        let views = pane_views(ctx.cache.as_ref(), ctx.clock.now_unix());
+        assert_eq!(views[0].last_compact_at, Some(now - 90));
+
+        let view = PaneView::from(make_pane("p1", "s1")).with_last_compact(Some(500), 400);
+        assert_eq!(view.secs_since_compact, Some(0), "clock skew never goes negative");
+    }
+
+    fn tail_ctx(dir: &std::path::Path) -> RpcContext {
+        let config_path = dir.join("config.toml");
+        std::fs::write(&config_path, "[capture]\ncapture-output = true\n").unwrap();
+        let config = ConfigManager::load_from_fs(Some(config_path)).expect("config");
+        let caps = Capabilities { ntm: false, tmux: true, stream: false, systemd: false };
+        RpcContext::with_capabilities(Arc::new(Cache::new(100)), config, caps).with_pane_tails()
+    }
+
+    fn capture(pane_uid: &str, output: &str) -> ClientUpdate {
+        ClientUpdate {
+            kind: PANE_OUTPUT_UPDATE.to_string(),
+            payload: Some(json!({ "paneId": pane_uid, "output": output })),
+        }
+    }
+
+    #[test]
+    fn tail_pushes_appended_lines_redacted() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = tail_ctx(dir.path());
+        ctx.cache.upsert_pane(make_pane("pane-1", "sess-1"));
+        ctx.cache.upsert_pane(make_pane("pane-2", "sess-1"));
+
+        let result = tail(&ctx, json!({ "paneId": "pane-1" })).unwrap();
+        assert_eq!(result["tailing"], true);
+
+        assert_eq!(tail_output(&ctx, &capture("pane-1", "$ make\n")), None, "baseline");
+        let pushed = tail_output(&ctx, &capture("pane-1", "$ make\nbuilding\ntoken=abc123\n")).unwrap();
+        assert_eq!(pushed["paneId"], "pane-1");
+        assert_eq!(pushed["lines"], json!(["building", "[REDACTED]"]));
+        assert_eq!(pushed["ended"], false);
+
+        let pushed = tail_output(&ctx, &capture("pane-1", "$ make\nbuilding\ntoken=abc123\ndone\n")).unwrap();
+        assert_eq!(pushed["lines"], json!(["done"]));
+        assert_eq!(tail_output(&ctx, &capture("pane-1", "$ make\nbuilding\ntoken=abc123\ndone\n")), None);
+        assert_eq!(tail_output(&ctx, &capture("pane-2", "other\n")), None, "not tailed");
+
+        let result = untail(&ctx, json!({ "paneId": "pane-1" })).unwrap();
+        assert_eq!(result["stopped"], true);
+        assert_eq!(tail_output(&ctx, &capture("pane-1", "more\n")), None);
+    }
+
+    #[test]
+    fn ending_the_pane_stops_the_tail() {
+        let dir = tempfile::tempdir().unwrap();
+        let ctx = tail_ctx(dir.path());
+        ctx.cache.upsert_pane(make_pane("pane-1", "sess-1"));
+        tail(&ctx, json!({ "paneId": "pane-1" })).unwrap();
+        tail_output(&ctx, &capture("pane-1", "first\n"));
+        assert!(ended_tails(&ctx).is_empty(), "pane still live");
+
+        let mut pane = make_pane("pane-1", "sess-1");
+        pane.status = PaneStatus::Ended;
+        ctx.cache.upsert_pane(pane);
+        let ended = ended_tails(&ctx);
+        assert_eq!(ended, vec![json!({ "paneId": "pane-1", "lines": [], "ended": true })]);
+        assert!(ended_tails(&ctx).is_empty(), "ended only once");
+        assert_eq!(tail_output(&ctx, &capture("pane-1", "first\nsecond\n")), None);
+
+        let err = tail(&ctx, json!({ "paneId": "pane-1" })).unwrap_err();
+        assert_eq!(err.code, CODE_INVALID_PARAMS);
+    }
+
+    #[test]
+    fn tail_needs_a_push_connection_and_capture() {
+        let err = tail(&test_ctx(), json!({ "paneId": "pane-1" })).unwrap_err();
+        assert_eq!(err.code, CODE_UNSUPPORTED);
+
+        let err = tail(&test_ctx().with_pane_tails(), json!({ "paneId": "pane-1" })).unwrap_err();
+        assert_eq!(err.code, CODE_UNSUPPORTED);
+        assert!(err.message.contains("capture-output"), "{}", err.message);
+    }
 }
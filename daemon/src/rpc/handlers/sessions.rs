@@ -1,6 +1,13 @@
 use crate::cache::Cache;
+use crate::config::GroupingConfig;
+use crate::grouping::session_group;
+use crate::models::pane::Pane;
 use crate::models::session::Session;
-use crate::rpc::{parse_params, RpcContext, RpcError, RpcResult, CODE_NOT_FOUND};
+use crate::models::source::Source;
+use crate::rpc::{
+    parse_params, require_admin, RpcContext, RpcError, RpcResult, CODE_INVALID_PARAMS,
+    CODE_NOT_FOUND,
+};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
@@ -10,6 +17,8 @@ use std::collections::HashMap;
 pub struct SessionView {
     pub session_id: String,
     pub name: String,
+    /// Name reported by the source; differs from `name` when aliased.
+    pub raw_name: String,
     pub status: String,
     pub status_reason: Option<String>,
     pub pane_count: u32,
@@ -17,14 +26,19 @@ pub struct SessionView {
     pub last_seen_at: i64,
     pub ended_at: Option<i64>,
     pub tmux_session_id: Option<String>,
-    pub source_id: String,
+    pub source_id: Source,
     pub metadata: Option<Value>,
+    /// Project group from the configured grouping rule.
+    pub group: Option<String>,
+    /// Client-assigned tags (`sessions.setTags`).
+    pub tags: Vec<String>,
 }
 
 impl SessionView {
     pub fn from_session_with_pane_count(session: Session, pane_count: u32) -> Self {
         Self {
             session_id: session.session_uid,
+            raw_name: session.raw_name.unwrap_or_else(|| session.name.clone()),
             name: session.name,
             status: session.status.as_str().to_string(),
             status_reason: session.status_reason,
@@ -35,6 +49,8 @@ impl SessionView {
             tmux_session_id: session.tmux_session_id,
             source_id: session.source_id,
             metadata: session.metadata,
+            group: None,
+            tags: Vec::new(),
         }
     }
 }
@@ -43,6 +59,7 @@ impl From<Session> for SessionView {
     fn from(session: Session) -> Self {
         Self {
             session_id: session.session_uid,
+            raw_name: session.raw_name.unwrap_or_else(|| session.name.clone()),
             name: session.name,
             status: session.status.as_str().to_string(),
             status_reason: session.status_reason,
@@ -53,6 +70,8 @@ impl From<Session> for SessionView {
             tmux_session_id: session.tmux_session_id,
             source_id: session.source_id,
             metadata: session.metadata,
+            group: None,
+            tags: Vec::new(),
         }
     }
 }
@@ -62,6 +81,23 @@ impl From<Session> for SessionView {
 struct SessionsListParams {
     status: Option<String>,
     session_ids: Option<Vec<String>>,
+    /// Only sessions seen within this many seconds that have not ended.
+    active_within_secs: Option<u64>,
+    /// Only sessions carrying these tags, combined per `tag_match`.
+    tags: Option<Vec<String>>,
+    #[serde(default)]
+    tag_match: TagMatch,
+}
+
+/// How `sessions.list` combines several `tags`.
+#[derive(Debug, Default, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum TagMatch {
+    /// Every listed tag must be present.
+    #[default]
+    All,
+    /// At least one listed tag must be present.
+    Any,
 }
 
 #[derive(Debug, Deserialize)]
@@ -70,19 +106,63 @@ struct SessionGetParams {
     session_id: String,
 }
 
-pub fn session_views(cache: &Cache) -> Vec<SessionView> {
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionSetAliasParams {
+    session_id: String,
+    /// `null` or blank clears the alias.
+    alias: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SessionSetTagsParams {
+    session_id: String,
+    /// Replaces the current tags; an empty list clears them.
+    tags: Vec<String>,
+}
+
+/// Tags per session accepted by `sessions.setTags`.
+const MAX_SESSION_TAGS: usize = 32;
+/// Longest tag, in characters, accepted by `sessions.setTags`.
+const MAX_TAG_CHARS: usize = 64;
+
+/// Captured pane working directories per session uid, in pane order, for
+/// the `cwd` grouping rule.
+fn pane_cwds_by_session(panes: &[Pane]) -> HashMap<&str, Vec<&str>> {
+    let mut ordered: Vec<&Pane> = panes.iter().collect();
+    ordered.sort_by_key(|pane| pane.pane_index);
+    let mut cwds: HashMap<&str, Vec<&str>> = HashMap::new();
+    for pane in ordered {
+        if let Some(cwd) = pane.context.as_ref().and_then(|context| context.cwd.as_deref()) {
+            cwds.entry(pane.session_uid.as_str()).or_default().push(cwd);
+        }
+    }
+    cwds
+}
+
+pub fn session_views(cache: &Cache, grouping: &GroupingConfig) -> Vec<SessionView> {
     // Calculate pane counts per session from actual panes
+    let panes = cache.all_panes();
     let mut pane_counts: HashMap<String, u32> = HashMap::new();
-    for pane in cache.all_panes() {
-        *pane_counts.entry(pane.session_uid).or_insert(0) += 1;
+    for pane in &panes {
+        *pane_counts.entry(pane.session_uid.clone()).or_insert(0) += 1;
     }
+    let pane_cwds = pane_cwds_by_session(&panes);
 
     cache
         .all_sessions()
         .into_iter()
         .map(|session| {
             let count = pane_counts.get(&session.session_uid).copied().unwrap_or(0);
-            SessionView::from_session_with_pane_count(session, count)
+            let cwds = pane_cwds.get(session.session_uid.as_str());
+            let group = session_group(&session, cwds.map(Vec::as_slice).unwrap_or_default(), grouping);
+            let tags = cache.session_tags(&session.session_uid);
+            SessionView {
+                group: Some(group),
+                tags,
+                ..SessionView::from_session_with_pane_count(session, count)
+            }
         })
         .collect()
 }
@@ -92,11 +172,14 @@ pub fn list(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
         SessionsListParams {
             status: None,
             session_ids: None,
+            active_within_secs: None,
+            tags: None,
+            tag_match: TagMatch::All,
         }
     } else {
         parse_params(params)?
     };
-    let mut sessions = session_views(ctx.cache.as_ref());
+    let mut sessions = session_views(ctx.cache.as_ref(), &ctx.config.current().grouping);
 
     if let Some(ref allowed) = params.session_ids {
         sessions.retain(|session| allowed.contains(&session.session_id));
@@ -104,6 +187,22 @@ pub fn list(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
     if let Some(ref status) = params.status {
         sessions.retain(|session| session.status == *status);
     }
+    if let Some(window) = params.active_within_secs {
+        let now = ctx.clock.now_unix();
+        let cutoff = now.saturating_sub(window.min(i64::MAX as u64) as i64);
+        sessions.retain(|session| session.ended_at.is_none() && session.last_seen_at >= cutoff);
+    }
+    if let Some(ref wanted) = params.tags {
+        if !wanted.is_empty() {
+            sessions.retain(|session| {
+                let has = |tag: &String| session.tags.contains(tag);
+                match params.tag_match {
+                    TagMatch::All => wanted.iter().all(has),
+                    TagMatch::Any => wanted.iter().any(has),
+                }
+            });
+        }
+    }
 
     Ok(json!({ "sessions": sessions }))
 }
@@ -114,32 +213,120 @@ pub fn get(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
         .cache
         .get_session(&params.session_id)
         .ok_or_else(|| RpcError::new(CODE_NOT_FOUND, "Session not found"))?;
-    Ok(json!({ "session": SessionView::from(session) }))
+    Ok(json!({ "session": session_view(ctx, session) }))
+}
+
+/// Give a session a display name that reconcile and the collectors keep;
+/// the source's own name stays available as `rawName`.
+pub fn set_alias(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
+    let params: SessionSetAliasParams = parse_params(params)?;
+    if ctx.cache.get_session(&params.session_id).is_none() {
+        return Err(RpcError::new(CODE_NOT_FOUND, "Session not found"));
+    }
+    let alias = params
+        .alias
+        .map(|alias| alias.trim().to_string())
+        .filter(|alias| !alias.is_empty());
+    let session = ctx
+        .cache
+        .set_session_alias(&params.session_id, alias)
+        .ok_or_else(|| RpcError::new(CODE_NOT_FOUND, "Session not found"))?;
+    Ok(json!({ "session": session_view(ctx, session) }))
+}
+
+/// Replace a session's tags. Tags are trimmed, blanks and duplicates are
+/// dropped, and reconcile and the collectors keep them.
+pub fn set_tags(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
+    let params: SessionSetTagsParams = parse_params(params)?;
+    if ctx.cache.get_session(&params.session_id).is_none() {
+        return Err(RpcError::new(CODE_NOT_FOUND, "Session not found"));
+    }
+    let mut tags: Vec<String> = Vec::new();
+    for tag in params.tags {
+        let tag = tag.trim();
+        if tag.chars().count() > MAX_TAG_CHARS {
+            return Err(RpcError::new(
+                CODE_INVALID_PARAMS,
+                format!("Invalid params: tags must be at most {MAX_TAG_CHARS} characters"),
+            ));
+        }
+        if !tag.is_empty() && !tags.iter().any(|existing| existing == tag) {
+            tags.push(tag.to_string());
+        }
+    }
+    if tags.len() > MAX_SESSION_TAGS {
+        return Err(RpcError::new(
+            CODE_INVALID_PARAMS,
+            format!("Invalid params: at most {MAX_SESSION_TAGS} tags per session"),
+        ));
+    }
+    ctx.cache.set_session_tags(&params.session_id, tags);
+    let session = ctx
+        .cache
+        .get_session(&params.session_id)
+        .ok_or_else(|| RpcError::new(CODE_NOT_FOUND, "Session not found"))?;
+    Ok(json!({ "session": session_view(ctx, session) }))
+}
+
+/// A single session as returned by `sessions.get` and the setters.
+fn session_view(ctx: &RpcContext, session: Session) -> SessionView {
+    let panes: Vec<Pane> = ctx
+        .cache
+        .all_panes()
+        .into_iter()
+        .filter(|pane| pane.session_uid == session.session_uid)
+        .collect();
+    let pane_cwds = pane_cwds_by_session(&panes);
+    let cwds = pane_cwds.get(session.session_uid.as_str());
+    let group = session_group(
+        &session,
+        cwds.map(Vec::as_slice).unwrap_or_default(),
+        &ctx.config.current().grouping,
+    );
+    let tags = ctx.cache.session_tags(&session.session_uid);
+    SessionView {
+        group: Some(group),
+        tags,
+        ..SessionView::from(session)
+    }
+}
+
+/// Remove every ended session (and its panes) from the live cache now,
+/// without waiting for `cache.ended-session-ttl-secs`. The database keeps them.
+pub fn prune_ended(ctx: &RpcContext) -> RpcResult<Value> {
+    require_admin(ctx)?;
+    let pruned = ctx.cache.prune_ended_sessions(ctx.clock.now_unix(), 0);
+    Ok(json!({ "pruned": pruned }))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::cache::Cache;
+    use crate::clock::MockClock;
     use crate::config::ConfigManager;
-    use crate::models::pane::{Pane, PaneStatus};
+    use crate::models::pane::{Pane, PaneContext, PaneStatus};
     use crate::models::session::{Session, SessionStatus};
     use crate::rpc::{Capabilities, RpcContext};
     use std::sync::Arc;
 
+    const NOW: i64 = 1_700_000_000;
+
     fn test_ctx() -> RpcContext {
         let cache = Arc::new(Cache::new(100));
         let config = ConfigManager::default();
         let caps = Capabilities { ntm: false, tmux: false, stream: false, systemd: false };
         RpcContext::with_capabilities(cache, config, caps)
+            .with_clock(Arc::new(MockClock::new(NOW)))
     }
 
     fn make_session(uid: &str, name: &str, status: SessionStatus) -> Session {
         Session {
             session_uid: uid.to_string(),
-            source_id: "tmux".to_string(),
+            source_id: Source::Tmux,
             tmux_session_id: None,
             name: name.to_string(),
+            raw_name: None,
             created_at: 1000,
             last_seen_at: 2000,
             ended_at: None,
@@ -158,7 +345,7 @@ mod tests {
             tmux_pane_id: None, tmux_window_id: None, tmux_pane_pid: None,
             agent_type: None, created_at: 1, last_seen_at: 1,
             last_activity_at: None, current_command: None, ended_at: None,
-            status: PaneStatus::Active, status_reason: None,
+            status: PaneStatus::Active, status_reason: None, context: None,
         }
     }
 
@@ -200,6 +387,43 @@ mod tests {
         assert_eq!(sessions.len(), 2);
     }
 
+    #[test]
+    fn sessions_list_active_within_keeps_recent_sessions() {
+        let ctx = test_ctx();
+        let now = NOW;
+        let mut recent = make_session("s1", "alpha", SessionStatus::Active);
+        recent.last_seen_at = now - 60;
+        let mut stale = make_session("s2", "beta", SessionStatus::Idle);
+        stale.last_seen_at = now - 3600;
+        ctx.cache.upsert_session(recent);
+        ctx.cache.upsert_session(stale);
+
+        let result = list(&ctx, json!({"activeWithinSecs": 600})).unwrap();
+        let sessions = result["sessions"].as_array().unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0]["sessionId"], "s1");
+    }
+
+    #[test]
+    fn sessions_list_active_within_excludes_ended_sessions() {
+        let ctx = test_ctx();
+        let now = NOW;
+        let mut ended = make_session("s1", "alpha", SessionStatus::Ended);
+        ended.last_seen_at = now - 10;
+        ended.ended_at = Some(now - 5);
+        ctx.cache.upsert_session(ended);
+
+        let result = list(&ctx, json!({"activeWithinSecs": 600})).unwrap();
+        assert!(result["sessions"].as_array().unwrap().is_empty());
+    }
+
+    #[test]
+    fn sessions_list_active_within_rejects_negative() {
+        let ctx = test_ctx();
+        let result = list(&ctx, json!({"activeWithinSecs": -1}));
+        assert_eq!(result.unwrap_err().code, crate::rpc::CODE_INVALID_PARAMS);
+    }
+
     #[test]
     fn sessions_get_found() {
         let ctx = test_ctx();
@@ -210,6 +434,110 @@ mod tests {
         assert_eq!(result["session"]["status"], "active");
     }
 
+    #[test]
+    fn sessions_set_alias_renames_and_clears() {
+        let ctx = test_ctx();
+        ctx.cache.upsert_session(make_session("s1", "alpha", SessionStatus::Active));
+
+        let result = set_alias(&ctx, json!({"sessionId": "s1", "alias": " Frontend "})).unwrap();
+        assert_eq!(result["session"]["name"], "Frontend");
+        assert_eq!(result["session"]["rawName"], "alpha");
+        let listed = list(&ctx, Value::Null).unwrap();
+        assert_eq!(listed["sessions"][0]["name"], "Frontend");
+
+        let result = set_alias(&ctx, json!({"sessionId": "s1", "alias": null})).unwrap();
+        assert_eq!(result["session"]["name"], "alpha");
+        assert_eq!(result["session"]["rawName"], "alpha");
+    }
+
+    #[test]
+    fn sessions_set_alias_unknown_session() {
+        let ctx = test_ctx();
+        let err = set_alias(&ctx, json!({"sessionId": "nope", "alias": "x"})).unwrap_err();
+        assert_eq!(err.code, CODE_NOT_FOUND);
+        assert!(ctx.cache.session_alias("nope").is_none());
+    }
+
+    #[test]
+    fn sessions_set_tags_normalizes_and_shows_in_get() {
+        let ctx = test_ctx();
+        ctx.cache.upsert_session(make_session("s1", "alpha", SessionStatus::Active));
+        ctx.cache.upsert_session(make_session("s2", "beta", SessionStatus::Active));
+
+        let tags = json!({"sessionId": "s1", "tags": [" infra ", "", "urgent", "infra"]});
+        let result = set_tags(&ctx, tags).unwrap();
+        assert_eq!(result["session"]["tags"], json!(["infra", "urgent"]));
+
+        let fetched = get(&ctx, json!({"sessionId": "s1"})).unwrap();
+        assert_eq!(fetched["session"]["tags"], json!(["infra", "urgent"]));
+        let other = get(&ctx, json!({"sessionId": "s2"})).unwrap();
+        assert_eq!(other["session"]["tags"], json!([]));
+
+        let tagged = list(&ctx, json!({"tags": ["urgent"]})).unwrap();
+        let sessions = tagged["sessions"].as_array().unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0]["sessionId"], "s1");
+
+        let cleared = set_tags(&ctx, json!({"sessionId": "s1", "tags": []})).unwrap();
+        assert_eq!(cleared["session"]["tags"], json!([]));
+    }
+
+    fn listed_ids(ctx: &RpcContext, params: Value) -> Vec<String> {
+        let result = list(ctx, params).unwrap();
+        let mut ids: Vec<String> = result["sessions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|session| session["sessionId"].as_str().unwrap().to_string())
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn sessions_list_filters_by_tags() {
+        let ctx = test_ctx();
+        let tagged = [
+            ("s1", vec!["prod", "api"]),
+            ("s2", vec!["prod"]),
+            ("s3", vec!["dev"]),
+        ];
+        for (uid, tags) in tagged {
+            ctx.cache.upsert_session(make_session(uid, uid, SessionStatus::Active));
+            ctx.cache.set_session_tags(uid, tags.into_iter().map(String::from).collect());
+        }
+
+        let all = listed_ids(&ctx, json!({"tags": ["prod", "api"], "tagMatch": "all"}));
+        assert_eq!(all, vec!["s1"]);
+        // `all` is the default
+        assert_eq!(listed_ids(&ctx, json!({"tags": ["prod", "api"]})), vec!["s1"]);
+
+        let any = listed_ids(&ctx, json!({"tags": ["api", "dev"], "tagMatch": "any"}));
+        assert_eq!(any, vec!["s1", "s3"]);
+
+        assert!(listed_ids(&ctx, json!({"tags": ["staging"]})).is_empty());
+        assert_eq!(listed_ids(&ctx, json!({"tags": []})).len(), 3);
+
+        let err = list(&ctx, json!({"tags": ["prod"], "tagMatch": "some"})).unwrap_err();
+        assert_eq!(err.code, CODE_INVALID_PARAMS);
+    }
+
+    #[test]
+    fn sessions_set_tags_rejects_bad_input() {
+        let ctx = test_ctx();
+        let err = set_tags(&ctx, json!({"sessionId": "nope", "tags": ["x"]})).unwrap_err();
+        assert_eq!(err.code, CODE_NOT_FOUND);
+
+        ctx.cache.upsert_session(make_session("s1", "alpha", SessionStatus::Active));
+        let long = "x".repeat(MAX_TAG_CHARS + 1);
+        let err = set_tags(&ctx, json!({"sessionId": "s1", "tags": [long]})).unwrap_err();
+        assert_eq!(err.code, CODE_INVALID_PARAMS);
+        let many: Vec<String> = (0..=MAX_SESSION_TAGS).map(|i| format!("t{i}")).collect();
+        let err = set_tags(&ctx, json!({"sessionId": "s1", "tags": many})).unwrap_err();
+        assert_eq!(err.code, CODE_INVALID_PARAMS);
+        assert!(ctx.cache.session_tags("s1").is_empty());
+    }
+
     #[test]
     fn sessions_get_not_found() {
         let ctx = test_ctx();
@@ -218,14 +546,103 @@ mod tests {
         assert_eq!(result.unwrap_err().code, CODE_NOT_FOUND);
     }
 
+    #[test]
+    fn sessions_prune_ended_requires_admin() {
+        let ctx = test_ctx();
+        let result = prune_ended(&ctx);
+        assert_eq!(result.unwrap_err().code, crate::rpc::CODE_FORBIDDEN);
+    }
+
+    #[test]
+    fn sessions_prune_ended_removes_only_ended_sessions() {
+        let mut ctx = test_ctx();
+        ctx.is_admin = true;
+        let mut ended = make_session("s1", "alpha", SessionStatus::Ended);
+        ended.ended_at = Some(NOW - 5);
+        let mut also_ended = make_session("s2", "beta", SessionStatus::Ended);
+        also_ended.ended_at = Some(1500);
+        // Ended after the context clock's now, so not yet prunable
+        let mut ending = make_session("s4", "delta", SessionStatus::Ended);
+        ending.ended_at = Some(NOW + 60);
+        ctx.cache.upsert_session(ended);
+        ctx.cache.upsert_session(also_ended);
+        ctx.cache.upsert_session(ending);
+        ctx.cache.upsert_session(make_session("s3", "gamma", SessionStatus::Active));
+        ctx.cache.upsert_pane(make_pane("p1", "s1"));
+
+        let result = prune_ended(&ctx).unwrap();
+        assert_eq!(result["pruned"], 2);
+        let mut remaining: Vec<String> =
+            ctx.cache.all_sessions().into_iter().map(|s| s.session_uid).collect();
+        remaining.sort();
+        assert_eq!(remaining, vec!["s3", "s4"]);
+        assert!(ctx.cache.get_pane("p1").is_none());
+    }
+
     #[test]
     fn session_views_counts_panes() {
         let ctx = test_ctx();
         ctx.cache.upsert_session(make_session("s1", "alpha", SessionStatus::Active));
         ctx.cache.upsert_pane(make_pane("p1", "s1"));
         ctx.cache.upsert_pane(make_pane("p2", "s1"));
-        let views = session_views(ctx.cache.as_ref());
+        let views = session_views(ctx.cache.as_ref(), &GroupingConfig::default());
         assert_eq!(views.len(), 1);
         assert_eq!(views[0].pane_count, 2);
     }
+
+    #[test]
+    fn session_views_group_by_name_prefix() {
+        let ctx = test_ctx();
+        ctx.cache.upsert_session(make_session("s1", "tracker-api", SessionStatus::Active));
+        ctx.cache.upsert_session(make_session("s2", "tracker-tui", SessionStatus::Active));
+        ctx.cache.upsert_session(make_session("s3", "docs", SessionStatus::Active));
+        let views = session_views(ctx.cache.as_ref(), &GroupingConfig::default());
+        let group_of = |id: &str| {
+            views
+                .iter()
+                .find(|view| view.session_id == id)
+                .and_then(|view| view.group.clone())
+                .unwrap()
+        };
+        assert_eq!(group_of("s1"), "tracker");
+        assert_eq!(group_of("s2"), "tracker");
+        assert_eq!(group_of("s3"), "docs");
+    }
+
+    #[test]
+    fn session_views_group_by_captured_cwd() {
+        let ctx = test_ctx();
+        for (session, pane, cwd) in [
+            ("s1", "p1", "/src/tracker/daemon"),
+            ("s2", "p2", "/src/tracker/tui"),
+            ("s3", "p3", "/src/website"),
+        ] {
+            ctx.cache.upsert_session(make_session(session, session, SessionStatus::Active));
+            let mut pane = make_pane(pane, session);
+            pane.context = Some(PaneContext {
+                cwd: Some(cwd.to_string()),
+                ..PaneContext::default()
+            });
+            ctx.cache.upsert_pane(pane);
+        }
+        ctx.cache.upsert_session(make_session("s4", "scratch", SessionStatus::Active));
+
+        let grouping = GroupingConfig {
+            rule: "cwd".to_string(),
+            cwd_depth: 2,
+            ..GroupingConfig::default()
+        };
+        let views = session_views(ctx.cache.as_ref(), &grouping);
+        let group_of = |id: &str| {
+            views
+                .iter()
+                .find(|view| view.session_id == id)
+                .and_then(|view| view.group.clone())
+                .unwrap()
+        };
+        assert_eq!(group_of("s1"), "/src/tracker");
+        assert_eq!(group_of("s2"), "/src/tracker");
+        assert_eq!(group_of("s3"), "/src/website");
+        assert_eq!(group_of("s4"), "scratch", "no cwd: own group");
+    }
 }
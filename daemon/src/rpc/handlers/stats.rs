@@ -1,5 +1,11 @@
 use crate::cache::Cache;
-use crate::rpc::{parse_params, RpcContext, RpcResult};
+use crate::db;
+use crate::maintenance::{window_totals, WindowTotals};
+use crate::rpc::{
+    parse_params, RpcContext, RpcError, RpcResult, CODE_DEGRADED, CODE_INVALID_PARAMS,
+    CODE_UNSUPPORTED,
+};
+use rusqlite::Connection;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
@@ -23,6 +29,60 @@ struct StatsRangeParams {
     limit: Option<usize>,
 }
 
+/// Trailing window for `stats.velocity` when `windowMinutes` is omitted.
+const DEFAULT_VELOCITY_WINDOW_MINUTES: u32 = 60;
+/// Longest accepted `windowMinutes` (one day).
+const MAX_VELOCITY_WINDOW_MINUTES: u32 = 1440;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StatsVelocityParams {
+    window_minutes: Option<u32>,
+}
+
+/// Activity per hour over one window.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsRates {
+    pub compacts_per_hour: f64,
+    pub tokens_per_hour: f64,
+}
+
+impl StatsRates {
+    fn over(totals: WindowTotals, window_secs: i64) -> Self {
+        let hours = window_secs as f64 / 3600.0;
+        Self {
+            compacts_per_hour: totals.compacts as f64 / hours,
+            tokens_per_hour: totals.tokens as f64 / hours,
+        }
+    }
+}
+
+/// Rates for the trailing window ending at `now` and the window before it;
+/// `current` above `previous` means activity is accelerating.
+#[derive(Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatsVelocity {
+    pub window_minutes: u32,
+    pub current: StatsRates,
+    pub previous: StatsRates,
+}
+
+pub fn velocity_at(
+    conn: &Connection,
+    now: i64,
+    window_minutes: u32,
+) -> rusqlite::Result<StatsVelocity> {
+    let window_secs = i64::from(window_minutes) * 60;
+    let current = window_totals(conn, now, window_secs)?;
+    let previous = window_totals(conn, now - window_secs, window_secs)?;
+    Ok(StatsVelocity {
+        window_minutes,
+        current: StatsRates::over(current, window_secs),
+        previous: StatsRates::over(previous, window_secs),
+    })
+}
+
 pub fn summary_payload(cache: &Cache) -> StatsSummary {
     let stats_today = cache.stats_today();
     StatsSummary {
@@ -38,6 +98,35 @@ pub fn summary(ctx: &RpcContext) -> RpcResult<Value> {
     Ok(json!({ "summary": summary_payload(ctx.cache.as_ref()) }))
 }
 
+pub fn velocity(ctx: &RpcContext, params: Value) -> RpcResult<Value> {
+    let params: StatsVelocityParams = if params.is_null() {
+        StatsVelocityParams::default()
+    } else {
+        parse_params(params)?
+    };
+    let window_minutes = params
+        .window_minutes
+        .unwrap_or(DEFAULT_VELOCITY_WINDOW_MINUTES);
+    if !(1..=MAX_VELOCITY_WINDOW_MINUTES).contains(&window_minutes) {
+        return Err(RpcError::new(
+            CODE_INVALID_PARAMS,
+            format!("windowMinutes must be between 1 and {MAX_VELOCITY_WINDOW_MINUTES}"),
+        ));
+    }
+    let Some(path) = &ctx.db_path else {
+        return Err(RpcError::new(
+            CODE_UNSUPPORTED,
+            "stats.velocity needs the daemon database",
+        ));
+    };
+    let velocity = db::open_database(path)
+        .and_then(|conn| velocity_at(&conn, ctx.clock.now_unix(), window_minutes))
+        .map_err(|err| {
+            RpcError::new(CODE_DEGRADED, format!("stats database read failed: {err}"))
+        })?;
+    Ok(json!({ "velocity": velocity }))
+}
+
 pub fn hourly(_ctx: &RpcContext, params: Value) -> RpcResult<Value> {
     let _params: StatsRangeParams = if params.is_null() {
         StatsRangeParams {
@@ -75,6 +164,7 @@ mod tests {
     use crate::config::ConfigManager;
     use crate::models::pane::{Pane, PaneStatus};
     use crate::models::session::{Session, SessionStatus};
+    use crate::models::source::Source;
     use crate::rpc::{Capabilities, RpcContext};
     use std::sync::Arc;
 
@@ -98,8 +188,9 @@ mod tests {
     fn summary_with_data() {
         let ctx = test_ctx();
         ctx.cache.upsert_session(Session {
-            session_uid: "s1".to_string(), source_id: "tmux".to_string(),
+            session_uid: "s1".to_string(), source_id: Source::Tmux,
             tmux_session_id: None, name: "alpha".to_string(),
+ raw_name: None,
             created_at: 1, last_seen_at: 1, ended_at: None,
             status: SessionStatus::Active, status_reason: None,
             pane_count: 0, metadata: None,
@@ -110,6 +201,7 @@ mod tests {
             tmux_pane_pid: None, agent_type: None, created_at: 1,
             last_seen_at: 1, last_activity_at: None, current_command: None,
             ended_at: None, status: PaneStatus::Active, status_reason: None,
+            context: None,
         });
         ctx.cache.set_stats_today(StatsAggregate {
             total_compacts: 10,
@@ -145,19 +237,88 @@ mod tests {
         assert!(result["hourly"].as_array().unwrap().is_empty());
     }
 
+    /// Compacts and minute samples spread over the two hours before `now`.
+    fn velocity_db(path: &std::path::Path, now: i64) -> Connection {
+        let conn = db::open_database(path).unwrap();
+        conn.execute_batch("PRAGMA foreign_keys = OFF;").unwrap();
+        // Previous hour: 2 compacts, 3 000 tokens. Last hour: 6 compacts, 9 000 tokens.
+        let compacts = [-6000, -4000, -3000, -2400, -1800, -1200, -600, -1];
+        for (i, offset) in compacts.iter().enumerate() {
+            conn.execute(
+                "INSERT INTO events (session_uid, pane_uid, type, detected_at, source) VALUES ('s1', 'p1', 'compact', ?1, 'detector');",
+                [now + offset],
+            )
+            .unwrap();
+            // Other event types never count
+            conn.execute(
+                "INSERT INTO events (session_uid, pane_uid, type, detected_at, source) VALUES ('s1', 'p1', 'escalation', ?1, 'detector');",
+                [now + offset - i as i64],
+            )
+            .unwrap();
+        }
+        for (offset, tokens) in [(-5400, 3000), (-3000, 4000), (-1200, 5000), (0, 99_000)] {
+            conn.execute(
+                "INSERT INTO pane_minute_samples (minute_start, pane_uid, status, estimated_tokens) VALUES (?1, 'p1', 'active', ?2);",
+                [now + offset, tokens],
+            )
+            .unwrap();
+        }
+        conn
+    }
+
+    #[test]
+    fn velocity_compares_trailing_window_with_the_one_before() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = 1_700_000_000;
+        let conn = velocity_db(&dir.path().join("stats.db"), now);
+
+        let hourly = velocity_at(&conn, now, 60).unwrap();
+        assert_eq!(hourly.current, StatsRates { compacts_per_hour: 6.0, tokens_per_hour: 9000.0 });
+        assert_eq!(hourly.previous, StatsRates { compacts_per_hour: 2.0, tokens_per_hour: 3000.0 });
+
+        // A two-hour window averages everything before `now`
+        let two_hours = velocity_at(&conn, now, 120).unwrap();
+        assert_eq!(two_hours.current, StatsRates { compacts_per_hour: 4.0, tokens_per_hour: 6000.0 });
+        assert_eq!(two_hours.previous, StatsRates { compacts_per_hour: 0.0, tokens_per_hour: 0.0 });
+
+        // Half-hour window: 4 compacts and 5 000 tokens become per-hour rates
+        let half_hour = velocity_at(&conn, now, 30).unwrap();
+        assert_eq!(half_hour.current, StatsRates { compacts_per_hour: 8.0, tokens_per_hour: 10_000.0 });
+    }
+
+    #[test]
+    fn velocity_handler_validates_window_and_needs_database() {
+        let mut ctx = test_ctx();
+        let err = velocity(&ctx, Value::Null).unwrap_err();
+        assert_eq!(err.code, CODE_UNSUPPORTED);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("stats.db");
+        drop(db::open_database(&path).unwrap());
+        ctx.db_path = Some(path);
+        let err = velocity(&ctx, json!({ "windowMinutes": 0 })).unwrap_err();
+        assert_eq!(err.code, CODE_INVALID_PARAMS);
+
+        let result = velocity(&ctx, json!({ "windowMinutes": 90 })).unwrap();
+        assert_eq!(result["velocity"]["windowMinutes"], 90);
+        assert_eq!(result["velocity"]["current"]["compactsPerHour"], 0.0);
+    }
+
     #[test]
     fn summary_payload_counts_sessions_and_panes() {
         let ctx = test_ctx();
         ctx.cache.upsert_session(Session {
-            session_uid: "s1".to_string(), source_id: "tmux".to_string(),
+            session_uid: "s1".to_string(), source_id: Source::Tmux,
             tmux_session_id: None, name: "a".to_string(),
+ raw_name: None,
             created_at: 1, last_seen_at: 1, ended_at: None,
             status: SessionStatus::Active, status_reason: None,
             pane_count: 0, metadata: None,
         });
         ctx.cache.upsert_session(Session {
-            session_uid: "s2".to_string(), source_id: "tmux".to_string(),
+            session_uid: "s2".to_string(), source_id: Source::Tmux,
             tmux_session_id: None, name: "b".to_string(),
+ raw_name: None,
             created_at: 1, last_seen_at: 1, ended_at: None,
             status: SessionStatus::Active, status_reason: None,
             pane_count: 0, metadata: None,
@@ -1,10 +1,19 @@
+use crate::bus::EventBus;
 use crate::cache::Cache;
+use crate::clock::{system_clock, Clock, SharedClock, SystemClock};
+use crate::collector::CollectorKind;
+use crate::command::{CommandConfig, CommandRunner};
 use crate::config::ConfigManager;
+use crate::ids::{IdGenerator, UuidV7Ids};
+use crate::locks;
+use crate::metrics::{MethodLatencies, METRICS};
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Condvar, Mutex};
 use std::time::{Duration, Instant};
-use uuid::Uuid;
+use tokio::sync::{watch, Semaphore};
 
 pub mod handlers;
 
@@ -17,6 +26,23 @@ pub const CODE_DEGRADED: &str = "DEGRADED";
 pub const CODE_NOT_FOUND: &str = "NOT_FOUND";
 pub const CODE_INVALID_PARAMS: &str = "INVALID_PARAMS";
 
+/// Methods that change daemon or tmux state, refused in safe mode.
+pub const SAFE_MODE_BLOCKED_METHODS: &[&str] = &[
+    "capabilities.recheck",
+    "sessions.setAlias",
+    "sessions.setTags",
+    "sessions.pruneEnded",
+    "escalations.dismiss",
+    "config.set",
+    "config.reload",
+    "detectors.reload",
+    "actions.sessionKill",
+    "actions.paneSend",
+];
+
+/// Buffered daemon events per subscriber before slow readers start lagging.
+pub const EVENT_BUS_CAPACITY: usize = 256;
+
 #[derive(Clone, Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Capabilities {
@@ -29,15 +55,88 @@ pub struct Capabilities {
 impl Capabilities {
     /// Probe the system to determine actual capabilities.
     pub fn probe() -> Self {
+        Self::probe_with(&SystemProber)
+    }
+
+    /// Determine capabilities from `prober` (a mock in tests).
+    pub fn probe_with(prober: &dyn Prober) -> Self {
         Self {
-            ntm: probe_ntm_available(),
-            tmux: probe_tmux_available(),
-            stream: false,
-            systemd: probe_systemd_available(),
+            ntm: prober.ntm_available(),
+            tmux: prober.tmux_available(),
+            // The stdio transport pushes `sessions.snapshot` and `events.push`,
+            // so its clients need not poll
+            stream: true,
+            systemd: prober.systemd_available(),
         }
     }
 }
 
+/// Answers whether each external tool is available.
+pub trait Prober {
+    fn ntm_available(&self) -> bool;
+    fn tmux_available(&self) -> bool;
+    fn systemd_available(&self) -> bool;
+}
+
+/// Checks the real environment with `which` and the systemd runtime dir.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemProber;
+
+impl Prober for SystemProber {
+    fn ntm_available(&self) -> bool {
+        probe_ntm_available()
+    }
+
+    fn tmux_available(&self) -> bool {
+        probe_tmux_available()
+    }
+
+    fn systemd_available(&self) -> bool {
+        probe_systemd_available()
+    }
+}
+
+/// Capabilities shared by every clone of an [`RpcContext`], so a collector
+/// that loses its binary is reflected in `capabilities.get` on all transports.
+/// Changes are broadcast to [`SharedCapabilities::subscribe`]rs.
+#[derive(Clone, Debug)]
+pub struct SharedCapabilities {
+    inner: Arc<watch::Sender<Capabilities>>,
+}
+
+impl SharedCapabilities {
+    pub fn new(capabilities: Capabilities) -> Self {
+        let (tx, _rx) = watch::channel(capabilities);
+        Self { inner: Arc::new(tx) }
+    }
+
+    pub fn current(&self) -> Capabilities {
+        self.inner.borrow().clone()
+    }
+
+    /// Mark a collector's tool (un)available. Returns whether it changed.
+    pub fn set_available(&self, kind: CollectorKind, available: bool) -> bool {
+        self.inner.send_if_modified(|caps| {
+            let flag = match kind {
+                CollectorKind::Tmux => &mut caps.tmux,
+                CollectorKind::Ntm => &mut caps.ntm,
+            };
+            let changed = *flag != available;
+            *flag = available;
+            changed
+        })
+    }
+
+    /// Replace all capabilities, returning the previous set.
+    pub fn replace(&self, capabilities: Capabilities) -> Capabilities {
+        self.inner.send_replace(capabilities)
+    }
+
+    pub fn subscribe(&self) -> watch::Receiver<Capabilities> {
+        self.inner.subscribe()
+    }
+}
+
 /// Check if NTM is available by testing if the binary can be found.
 fn probe_ntm_available() -> bool {
     std::process::Command::new("which")
@@ -65,6 +164,207 @@ fn probe_systemd_available() -> bool {
     std::path::Path::new("/run/systemd/system").exists()
 }
 
+/// How long an action result is replayed for a repeated `idempotencyKey`.
+pub const IDEMPOTENCY_TTL: Duration = Duration::from_secs(300);
+
+/// Results of recent actions by client-supplied idempotency key, so a request
+/// retried after a timeout gets the first result instead of running again.
+/// Shared by every clone of an [`RpcContext`].
+pub struct IdempotencyKeys {
+    entries: Mutex<HashMap<String, IdempotencySlot>>,
+    /// Signalled whenever an in-flight key finishes.
+    finished: Condvar,
+    clock: SharedClock,
+}
+
+#[derive(Debug)]
+enum IdempotencySlot {
+    /// An action for the key is running; repeats wait for its result.
+    InFlight,
+    /// Unix time the result was stored, and the result.
+    Done(i64, Value),
+}
+
+/// What [`IdempotencyKeys::claim`] decided for a key.
+pub enum IdempotencyClaim<'a> {
+    /// The key is reserved for the caller, who runs the action and reports
+    /// back through [`IdempotencyReservation::finish`].
+    Run(IdempotencyReservation<'a>),
+    /// An earlier run of the key succeeded with this result.
+    Replay(Value),
+}
+
+/// A key held in flight. Dropping it unfinished (the action panicked)
+/// releases the key like a failure does.
+pub struct IdempotencyReservation<'a> {
+    keys: &'a IdempotencyKeys,
+    key: Option<String>,
+}
+
+impl Default for IdempotencyKeys {
+    fn default() -> Self {
+        Self::with_clock(system_clock())
+    }
+}
+
+impl IdempotencyKeys {
+    /// Keys whose results expire by `clock`.
+    pub fn with_clock(clock: SharedClock) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            finished: Condvar::new(),
+            clock,
+        }
+    }
+
+    /// Reserve `key`, or replay its stored result. While another caller holds
+    /// the key this blocks until that run finishes: a success is replayed,
+    /// a failure hands the key to this caller.
+    pub fn claim(&self, key: &str) -> IdempotencyClaim<'_> {
+        let mut entries = locks::lock(&self.entries, "idempotency keys");
+        loop {
+            let now = self.clock.now_unix();
+            let ttl = IDEMPOTENCY_TTL.as_secs() as i64;
+            entries.retain(|_, slot| match slot {
+                IdempotencySlot::InFlight => true,
+                IdempotencySlot::Done(stored_at, _) => now - *stored_at < ttl,
+            });
+            match entries.get(key) {
+                Some(IdempotencySlot::Done(_, result)) => {
+                    return IdempotencyClaim::Replay(result.clone());
+                }
+                Some(IdempotencySlot::InFlight) => {
+                    entries = self
+                        .finished
+                        .wait(entries)
+                        .unwrap_or_else(|poisoned| poisoned.into_inner());
+                }
+                None => {
+                    entries.insert(key.to_string(), IdempotencySlot::InFlight);
+                    return IdempotencyClaim::Run(IdempotencyReservation {
+                        keys: self,
+                        key: Some(key.to_string()),
+                    });
+                }
+            }
+        }
+    }
+
+    /// Store `result` for `key` as if an action had just returned it.
+    pub fn insert(&self, key: String, result: Value) {
+        let stored_at = self.clock.now_unix();
+        locks::lock(&self.entries, "idempotency keys")
+            .insert(key, IdempotencySlot::Done(stored_at, result));
+        self.finished.notify_all();
+    }
+
+    fn release(&self, key: &str) {
+        locks::lock(&self.entries, "idempotency keys").remove(key);
+        self.finished.notify_all();
+    }
+}
+
+impl IdempotencyReservation<'_> {
+    /// Store a successful result for waiters and later repeats; a failure
+    /// frees the key so the action can be retried.
+    pub fn finish(mut self, result: &RpcResult<Value>) {
+        let Some(key) = self.key.take() else {
+            return;
+        };
+        match result {
+            Ok(value) => self.keys.insert(key, value.clone()),
+            Err(_) => self.keys.release(&key),
+        }
+    }
+}
+
+impl Drop for IdempotencyReservation<'_> {
+    fn drop(&mut self) {
+        if let Some(key) = self.key.take() {
+            self.keys.release(&key);
+        }
+    }
+}
+
+/// Panes one connection follows through `panes.tail`, each with the last
+/// (redacted) capture seen so only newly appended lines are pushed.
+#[derive(Debug, Default)]
+pub struct PaneTails {
+    entries: Mutex<HashMap<String, Option<Vec<String>>>>,
+}
+
+impl PaneTails {
+    /// Start tailing `pane_uid`; the next capture becomes the baseline.
+    pub fn start(&self, pane_uid: &str) {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .entry(pane_uid.to_string())
+            .or_insert(None);
+    }
+
+    /// Stop tailing `pane_uid`; returns whether it was tailed.
+    pub fn stop(&self, pane_uid: &str) -> bool {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .remove(pane_uid)
+            .is_some()
+    }
+
+    pub fn tailed(&self) -> Vec<String> {
+        self.entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// Lines of `capture` not present in the previous capture of a tailed
+    /// pane, or `None` when the pane is not tailed. The first capture after
+    /// [`PaneTails::start`] only sets the baseline.
+    pub fn append(&self, pane_uid: &str, capture: &str) -> Option<Vec<String>> {
+        let mut entries = self
+            .entries
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let previous = entries.get_mut(pane_uid)?;
+        let mut current: Vec<String> = capture.lines().map(str::to_string).collect();
+        // capture-pane pads the visible area with blank lines
+        while current.last().is_some_and(|line| line.trim().is_empty()) {
+            current.pop();
+        }
+        let lines = match previous.as_deref() {
+            Some(previous) => appended_lines(previous, &current),
+            None => Vec::new(),
+        };
+        *previous = Some(current);
+        Some(lines)
+    }
+}
+
+/// Lines at the end of `current` past its overlap with the end of
+/// `previous`. A last line that kept growing (a partial line) is repeated
+/// in full; with no overlap at all the whole capture is new.
+fn appended_lines(previous: &[String], current: &[String]) -> Vec<String> {
+    for overlap in (1..=previous.len().min(current.len())).rev() {
+        let tail = &previous[previous.len() - overlap..];
+        let head = &current[..overlap];
+        if tail[..overlap - 1] != head[..overlap - 1] {
+            continue;
+        }
+        let (last_seen, last_now) = (&tail[overlap - 1], &head[overlap - 1]);
+        if last_seen == last_now {
+            return current[overlap..].to_vec();
+        }
+        if last_now.starts_with(last_seen.as_str()) {
+            return current[overlap - 1..].to_vec();
+        }
+    }
+    current.to_vec()
+}
+
 #[derive(Clone)]
 pub struct RpcContext {
     pub cache: Arc<Cache>,
@@ -72,10 +372,31 @@ pub struct RpcContext {
     pub instance_id: String,
     pub run_id: String,
     pub started_at: Instant,
+    /// Wall-clock start time (unix seconds), for reporting.
+    pub started_at_unix: i64,
     pub protocol_version: u32,
     pub schema_version: u32,
-    pub capabilities: Capabilities,
+    pub capabilities: SharedCapabilities,
+    /// Daemon-wide bus; newly recorded events are published here for push.
+    pub bus: Arc<EventBus>,
     pub is_admin: bool,
+    /// Read-only mode: no collectors run and [`SAFE_MODE_BLOCKED_METHODS`]
+    /// are rejected.
+    pub safe_mode: bool,
+    /// Daemon database, for reads the cache cannot answer. `None` runs
+    /// cache-only (tests, or when the data dir is unavailable).
+    pub db_path: Option<PathBuf>,
+    /// Process slots shared by every [`CommandRunner`] the daemon builds
+    /// (`command.max-concurrent-processes`).
+    pub command_permits: Arc<Semaphore>,
+    /// Remembered action results, see [`IdempotencyKeys`].
+    pub idempotency: Arc<IdempotencyKeys>,
+    /// This connection's `panes.tail` state. `None` on transports that
+    /// cannot push (HTTP); see [`RpcContext::with_pane_tails`].
+    pub tails: Option<Arc<PaneTails>>,
+    /// Wall clock for handlers and the tasks serving them; see
+    /// [`RpcContext::with_clock`].
+    pub clock: SharedClock,
 }
 
 impl RpcContext {
@@ -86,19 +407,62 @@ impl RpcContext {
 
     /// Create a new RpcContext with explicit capabilities (for testing).
     pub fn with_capabilities(cache: Arc<Cache>, config: ConfigManager, capabilities: Capabilities) -> Self {
+        Self::with_ids(cache, config, capabilities, &UuidV7Ids)
+    }
+
+    /// Create a new RpcContext whose instance and run ids come from `ids`
+    /// (for testing).
+    pub fn with_ids(
+        cache: Arc<Cache>,
+        config: ConfigManager,
+        capabilities: Capabilities,
+        ids: &dyn IdGenerator,
+    ) -> Self {
+        let max_processes = config.current().command.max_concurrent_processes;
         Self {
             cache,
             config,
-            instance_id: Uuid::now_v7().to_string(),
-            run_id: Uuid::now_v7().to_string(),
+            instance_id: ids.next_id(),
+            run_id: ids.next_id(),
             started_at: Instant::now(),
+            started_at_unix: SystemClock.now_unix(),
             protocol_version: 1,
             schema_version: 1,
-            capabilities,
+            capabilities: SharedCapabilities::new(capabilities),
+            bus: Arc::new(EventBus::new(EVENT_BUS_CAPACITY)),
             is_admin: false,
+            safe_mode: false,
+            db_path: None,
+            command_permits: Arc::new(Semaphore::new(max_processes)),
+            idempotency: Arc::new(IdempotencyKeys::default()),
+            tails: None,
+            clock: system_clock(),
         }
     }
 
+    /// Read the time from `clock` instead of the system clock (for testing).
+    /// Idempotency keys expire by it too.
+    pub fn with_clock(mut self, clock: SharedClock) -> Self {
+        self.started_at_unix = clock.now_unix();
+        self.idempotency = Arc::new(IdempotencyKeys::with_clock(clock.clone()));
+        self.clock = clock;
+        self
+    }
+
+    /// A copy of this context with its own empty [`PaneTails`], for a
+    /// connection that receives push notifications.
+    pub fn with_pane_tails(&self) -> Self {
+        Self {
+            tails: Some(Arc::new(PaneTails::default())),
+            ..self.clone()
+        }
+    }
+
+    /// A command runner bounded by the daemon-wide process limit.
+    pub fn command_runner(&self) -> CommandRunner {
+        CommandRunner::with_permits(CommandConfig::default(), self.command_permits.clone())
+    }
+
     pub fn uptime_secs(&self) -> u64 {
         self.started_at.elapsed().as_secs()
     }
@@ -142,9 +506,10 @@ pub fn hello_payload(ctx: &RpcContext) -> Value {
         "daemonVersion": crate::version(),
         "protocolVersion": ctx.protocol_version,
         "schemaVersion": ctx.schema_version,
-        "capabilities": ctx.capabilities,
+        "capabilities": ctx.capabilities.current(),
         "instanceId": ctx.instance_id,
         "runId": ctx.run_id,
+        "label": ctx.config.current().service.resolved_label(),
     })
 }
 
@@ -159,33 +524,151 @@ pub fn require_admin(ctx: &RpcContext) -> RpcResult<()> {
     }
 }
 
+/// Field-level detail attached as `data` to `INVALID_PARAMS` errors.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InvalidParamsData {
+    /// Dotted path of the offending field; `None` when the params as a whole are wrong.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub field: Option<String>,
+    /// `missing`, `wrong_type`, `invalid_value`, `unknown_field`, or `invalid`.
+    pub reason: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    /// The underlying deserializer message.
+    pub detail: String,
+}
+
+impl InvalidParamsData {
+    /// Classify a serde error raised at `path` (`"."` for the params root).
+    fn from_serde(path: &str, message: &str) -> Self {
+        let at = |name: Option<&str>| {
+            let parent = (path != ".").then_some(path);
+            match (parent, name) {
+                (Some(parent), Some(name)) => Some(format!("{parent}.{name}")),
+                (None, Some(name)) => Some(name.to_string()),
+                (parent, None) => parent.map(str::to_string),
+            }
+        };
+        let backticked = |rest: &str| rest.split('`').nth(1).map(str::to_string);
+        let expected = message
+            .split_once(", expected ")
+            .map(|(_, expected)| {
+                let expected = expected.trim_end_matches('.');
+                expected
+                    .strip_prefix("a ")
+                    .or_else(|| expected.strip_prefix("an "))
+                    .unwrap_or(expected)
+                    .to_string()
+            });
+
+        let (field, reason, expected) = if let Some(rest) = message.strip_prefix("missing field ") {
+            (at(backticked(rest).as_deref()), "missing", None)
+        } else if let Some(rest) = message.strip_prefix("unknown field ") {
+            (at(backticked(rest).as_deref()), "unknown_field", None)
+        } else if message.starts_with("invalid type: ") {
+            (at(None), "wrong_type", expected)
+        } else if message.starts_with("invalid value: ")
+            || message.starts_with("unknown variant ")
+            || message.starts_with("invalid length ")
+        {
+            (at(None), "invalid_value", expected)
+        } else {
+            (at(None), "invalid", None)
+        };
+
+        Self {
+            field,
+            reason,
+            expected,
+            detail: message.to_string(),
+        }
+    }
+
+    fn message(&self) -> String {
+        match (&self.field, self.reason, &self.expected) {
+            (Some(field), "missing", _) => format!("Invalid params: missing field '{field}'"),
+            (Some(field), "unknown_field", _) => format!("Invalid params: unknown field '{field}'"),
+            (Some(field), _, Some(expected)) => {
+                format!("Invalid params: '{field}' must be {expected}")
+            }
+            (Some(field), _, None) => format!("Invalid params: '{field}' is invalid"),
+            (None, _, _) => "Invalid params".to_string(),
+        }
+    }
+}
+
 pub fn parse_params<T: for<'de> Deserialize<'de>>(params: Value) -> RpcResult<T> {
-    serde_json::from_value(params).map_err(|err| {
-        RpcError::with_data(
-            CODE_INVALID_PARAMS,
-            "Invalid params",
-            Value::String(err.to_string()),
-        )
+    serde_path_to_error::deserialize(params).map_err(|err| {
+        let path = err.path().to_string();
+        let data = InvalidParamsData::from_serde(&path, &err.into_inner().to_string());
+        RpcError::with_data(CODE_INVALID_PARAMS, data.message(), json!(data))
     })
 }
 
 pub fn handle(method: &str, params: Value, ctx: &RpcContext) -> RpcResult<Value> {
-    match method {
+    handle_known(method, params, ctx).unwrap_or_else(|| {
+        Err(RpcError::new(
+            CODE_UNSUPPORTED,
+            format!("Unsupported method: {method}"),
+        ))
+    })
+}
+
+/// Like [`handle`], but returns `None` for methods this daemon does not
+/// implement so transports can tell them apart from handler errors.
+pub fn handle_known(method: &str, params: Value, ctx: &RpcContext) -> Option<RpcResult<Value>> {
+    handle_timed(method, params, ctx, &METRICS.rpc_methods, Instant::now)
+}
+
+/// Dispatch a request and record its latency under `method`. Unknown methods
+/// are not recorded.
+fn handle_timed(
+    method: &str,
+    params: Value,
+    ctx: &RpcContext,
+    latencies: &MethodLatencies,
+    now: impl Fn() -> Instant,
+) -> Option<RpcResult<Value>> {
+    let start = now();
+    let result = dispatch(method, params, ctx)?;
+    latencies.record(method, now().saturating_duration_since(start));
+    Some(result)
+}
+
+fn dispatch(method: &str, params: Value, ctx: &RpcContext) -> Option<RpcResult<Value>> {
+    if ctx.safe_mode && SAFE_MODE_BLOCKED_METHODS.contains(&method) {
+        return Some(Err(RpcError::new(
+            CODE_UNSUPPORTED,
+            format!("{method} is unavailable: daemon is running in safe mode (read-only)"),
+        )));
+    }
+    let result = match method {
         "core.hello" => handlers::core::hello(ctx),
         "health.get" => handlers::core::health_get(ctx),
         "capabilities.get" => handlers::core::capabilities_get(ctx),
-        "snapshot.get" => handlers::core::snapshot_get(ctx),
+        "capabilities.recheck" => handlers::admin::capabilities_recheck(ctx),
+        "snapshot.get" => handlers::core::snapshot_get(ctx, params),
         "sessions.list" => handlers::sessions::list(ctx, params),
         "sessions.get" => handlers::sessions::get(ctx, params),
+        "sessions.setAlias" => handlers::sessions::set_alias(ctx, params),
+        "sessions.setTags" => handlers::sessions::set_tags(ctx, params),
+        "sessions.pruneEnded" => handlers::sessions::prune_ended(ctx),
+        "panes.list" => handlers::panes::list(ctx, params),
         "panes.get" => handlers::panes::get(ctx, params),
+        "panes.search" => handlers::panes::search(ctx, params),
         "panes.outputPreview" => handlers::panes::output_preview(ctx, params),
+        "panes.tail" => handlers::panes::tail(ctx, params),
+        "panes.untail" => handlers::panes::untail(ctx, params),
         "events.list" => handlers::events::list(ctx, params),
+        "events.get" => handlers::events::get(ctx, params),
         "subscribe" => handlers::events::subscribe(ctx, params),
         "escalations.list" => handlers::events::escalations_list(ctx),
         "escalations.dismiss" => handlers::events::escalations_dismiss(ctx, params),
         "stats.summary" => handlers::stats::summary(ctx),
         "stats.hourly" => handlers::stats::hourly(ctx, params),
         "stats.daily" => handlers::stats::daily(ctx, params),
+        "stats.velocity" => handlers::stats::velocity(ctx, params),
         "config.get" => handlers::admin::config_get(ctx),
         "config.set" => handlers::admin::config_set(ctx, params),
         "config.reload" => handlers::admin::config_reload(ctx),
@@ -197,12 +680,13 @@ pub fn handle(method: &str, params: Value, ctx: &RpcContext) -> RpcResult<Value>
         "debug.diagnostics" => handlers::debug::diagnostics(ctx),
         "debug.selfTest" => handlers::debug::self_test(ctx),
         "debug.metrics" => handlers::debug::metrics(ctx),
-        "debug.logTail" => handlers::debug::log_tail(ctx),
-        _ => Err(RpcError::new(
-            CODE_UNSUPPORTED,
-            format!("Unsupported method: {method}"),
-        )),
-    }
+        "debug.logTail" => handlers::debug::log_tail(ctx, params),
+        "debug.logFiles" => handlers::debug::log_files(ctx),
+        "debug.logFetch" => handlers::debug::log_fetch(ctx, params),
+        "debug.reconcilePreview" => handlers::debug::reconcile_preview(ctx, params),
+        _ => return None,
+    };
+    Some(result)
 }
 
 #[cfg(test)]
@@ -218,12 +702,124 @@ mod tests {
         }
     }
 
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    #[allow(dead_code)]
+    struct TargetParams {
+        session_id: String,
+        lines: Option<u32>,
+        filter: Option<TargetFilter>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct TargetFilter {
+        kind: String,
+    }
+
+    fn invalid_params_data(params: Value) -> Value {
+        let err = parse_params::<TargetParams>(params).unwrap_err();
+        assert_eq!(err.code, CODE_INVALID_PARAMS);
+        err.data.expect("structured data")
+    }
+
+    #[test]
+    fn parse_params_reports_missing_field() {
+        let data = invalid_params_data(json!({ "lines": 5 }));
+        assert_eq!(data["field"], "sessionId");
+        assert_eq!(data["reason"], "missing");
+        assert!(data.get("expected").is_none());
+    }
+
+    #[test]
+    fn parse_params_reports_wrong_type_with_expected() {
+        let err = parse_params::<TargetParams>(json!({ "sessionId": 42 })).unwrap_err();
+        assert_eq!(err.message, "Invalid params: 'sessionId' must be string");
+        let data = err.data.unwrap();
+        assert_eq!(data["field"], "sessionId");
+        assert_eq!(data["reason"], "wrong_type");
+        assert_eq!(data["expected"], "string");
+    }
+
+    #[test]
+    fn parse_params_reports_nested_field_path() {
+        let data = invalid_params_data(json!({ "sessionId": "s1", "filter": {} }));
+        assert_eq!(data["field"], "filter.kind");
+        assert_eq!(data["reason"], "missing");
+
+        let data = invalid_params_data(json!({ "sessionId": "s1", "lines": "many" }));
+        assert_eq!(data["field"], "lines");
+        assert_eq!(data["expected"], "u32");
+    }
+
+    #[test]
+    fn parse_params_root_type_error_has_no_field() {
+        let data = invalid_params_data(json!("sessions"));
+        assert!(data.get("field").is_none());
+        assert_eq!(data["reason"], "wrong_type");
+    }
+
     #[test]
     fn capabilities_probe_runs_without_panic() {
         // Just verify probing doesn't crash
         let _ = Capabilities::probe();
     }
 
+    struct MockProber {
+        ntm: bool,
+        tmux: bool,
+        systemd: bool,
+    }
+
+    impl Prober for MockProber {
+        fn ntm_available(&self) -> bool {
+            self.ntm
+        }
+
+        fn tmux_available(&self) -> bool {
+            self.tmux
+        }
+
+        fn systemd_available(&self) -> bool {
+            self.systemd
+        }
+    }
+
+    #[test]
+    fn probe_with_uses_prober_answers() {
+        let prober = MockProber { ntm: true, tmux: false, systemd: false };
+        let caps = Capabilities::probe_with(&prober);
+        assert!(caps.ntm);
+        assert!(!caps.tmux);
+        assert!(caps.stream);
+        assert!(!caps.systemd);
+    }
+
+    #[test]
+    fn shared_capabilities_changes_are_visible_to_clones() {
+        let ctx = RpcContext::with_capabilities(
+            Arc::new(Cache::new(10)),
+            ConfigManager::default(),
+            test_capabilities(),
+        );
+        let client_ctx = ctx.clone();
+        assert!(ctx.capabilities.set_available(CollectorKind::Tmux, false));
+        assert!(!ctx.capabilities.set_available(CollectorKind::Tmux, false));
+        assert!(!client_ctx.capabilities.current().tmux);
+    }
+
+    #[test]
+    fn context_ids_come_from_the_generator() {
+        let ctx = RpcContext::with_ids(
+            Arc::new(Cache::new(10)),
+            ConfigManager::default(),
+            test_capabilities(),
+            &crate::ids::SequentialIds::new("ctx"),
+        );
+        assert_eq!(ctx.instance_id, "ctx-1");
+        assert_eq!(ctx.run_id, "ctx-2");
+    }
+
     #[test]
     fn capabilities_can_be_constructed_manually() {
         let caps = Capabilities {
@@ -245,8 +841,8 @@ mod tests {
         let caps = test_capabilities();
 
         let ctx = RpcContext::with_capabilities(cache, config, caps);
-        assert!(!ctx.capabilities.ntm);
-        assert!(ctx.capabilities.tmux);
+        assert!(!ctx.capabilities.current().ntm);
+        assert!(ctx.capabilities.current().tmux);
     }
 
     #[test]
@@ -255,6 +851,7 @@ mod tests {
         cache.set_health(crate::cache::HealthStatus {
             status: "ok".to_string(),
             last_error: None,
+            degraded_reason: None,
         });
         let config = ConfigManager::default();
         let caps = Capabilities {
@@ -309,4 +906,87 @@ mod tests {
         // Similar to ntm, should not panic
         let _ = probe_tmux_available();
     }
+
+    #[test]
+    fn handle_records_per_method_latency() {
+        use std::cell::Cell;
+
+        let ctx = RpcContext::new(Arc::new(Cache::new(10)), ConfigManager::default());
+        let latencies = MethodLatencies::new();
+        let base = Instant::now();
+        // Each call reads the clock twice; advance by the next scripted step.
+        let steps_ms = [0, 2, 0, 2, 0, 2, 0, 30];
+        let tick = Cell::new(0usize);
+        let elapsed = Cell::new(0u64);
+        let clock = || {
+            let idx = tick.get();
+            tick.set(idx + 1);
+            elapsed.set(elapsed.get() + steps_ms[idx]);
+            base + Duration::from_millis(elapsed.get())
+        };
+
+        for _ in 0..4 {
+            handle_timed("health.get", Value::Null, &ctx, &latencies, clock)
+                .unwrap()
+                .unwrap();
+        }
+
+        let summary = latencies.summary();
+        let health = &summary["health.get"];
+        assert_eq!(health.count, 4);
+        assert_eq!(health.p50_us, 2_500);
+        assert_eq!(health.p99_us, 30_000);
+    }
+
+    #[test]
+    fn handle_known_skips_unknown_methods() {
+        let ctx = RpcContext::new(Arc::new(Cache::new(10)), ConfigManager::default());
+        let latencies = MethodLatencies::new();
+
+        let method = "no.such.method";
+        let result = handle_timed(method, Value::Null, &ctx, &latencies, Instant::now);
+        assert!(result.is_none());
+        assert!(latencies.summary().is_empty());
+
+        let err = handle(method, Value::Null, &ctx).unwrap_err();
+        assert_eq!(err.code, CODE_UNSUPPORTED);
+    }
+
+    #[test]
+    fn pane_tails_push_only_appended_lines() {
+        let tails = PaneTails::default();
+        assert_eq!(tails.append("pane-1", "a\nb\n"), None, "not tailed");
+
+        tails.start("pane-1");
+        assert_eq!(tails.append("pane-1", "a\nb\n\n\n"), Some(vec![]), "baseline");
+        assert_eq!(tails.append("pane-1", "a\nb\nc\nd\n"), Some(vec!["c".into(), "d".into()]));
+        // The capture window scrolled and the last line grew
+        assert_eq!(tails.append("pane-1", "c\nd and more\n"), Some(vec!["d and more".into()]));
+        assert_eq!(tails.append("pane-1", "c\nd and more\n"), Some(vec![]));
+
+        assert!(tails.stop("pane-1"));
+        assert!(!tails.stop("pane-1"));
+        assert_eq!(tails.append("pane-1", "e\n"), None);
+    }
+
+    #[test]
+    fn safe_mode_rejects_writes_but_serves_reads() {
+        let mut ctx = RpcContext::with_capabilities(
+            Arc::new(Cache::new(10)),
+            ConfigManager::default(),
+            test_capabilities(),
+        );
+        ctx.is_admin = true;
+        ctx.safe_mode = true;
+
+        let health = handle("health.get", Value::Null, &ctx).unwrap();
+        assert!(health.get("status").is_some());
+        assert!(handle("sessions.list", json!({}), &ctx).is_ok());
+
+        for method in SAFE_MODE_BLOCKED_METHODS {
+            let err = handle(method, json!({}), &ctx).unwrap_err();
+            assert_eq!(err.code, CODE_UNSUPPORTED, "{method}");
+            assert!(err.message.contains("safe mode"), "{}", err.message);
+        }
+    }
 }
@@ -1,10 +1,14 @@
 //! Service lifecycle management: single-instance guard, graceful shutdown.
 
+use crate::rpc::Capabilities;
+use serde::{Deserialize, Serialize};
 use std::fs::{self, File, OpenOptions};
 use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
 use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
 use tracing::{debug, info, warn};
 
 /// Global shutdown flag.
@@ -93,10 +97,24 @@ impl InstanceGuard {
     pub fn pid_path(&self) -> &PathBuf {
         &self.pid_path
     }
+
+    /// Path of the `instance.json` written by [`Self::write_metadata`].
+    pub fn metadata_path(&self) -> PathBuf {
+        self.pid_path.with_file_name(INSTANCE_METADATA_FILE)
+    }
+
+    /// Record where this instance can be reached; removed again on drop.
+    pub fn write_metadata(&self, metadata: &InstanceMetadata) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(metadata)
+            .map_err(|e| format!("Failed to encode instance metadata: {e}"))?;
+        fs::write(self.metadata_path(), json)
+            .map_err(|e| format!("Failed to write instance metadata: {e}"))
+    }
 }
 
 impl Drop for InstanceGuard {
     fn drop(&mut self) {
+        let _ = fs::remove_file(self.metadata_path());
         // Remove PID file on clean exit
         if let Err(e) = fs::remove_file(&self.pid_path) {
             debug!(error = %e, "Failed to remove PID file (may already be removed)");
@@ -106,6 +124,68 @@ impl Drop for InstanceGuard {
     }
 }
 
+/// File beside the PID file describing the running instance.
+pub const INSTANCE_METADATA_FILE: &str = "instance.json";
+
+/// Where a running daemon listens, so clients can find ports picked by
+/// `transports.port-scan` instead of assuming the configured ones.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceMetadata {
+    pub pid: u32,
+    /// Same id `hello` reports, so a client can tell restarts apart.
+    pub instance_id: String,
+    pub label: String,
+    /// HTTP RPC port; what the client commands connect to.
+    pub port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ws_port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uds_path: Option<PathBuf>,
+}
+
+/// Read the running instance's metadata from the data directory, if any.
+pub fn read_instance_metadata() -> Option<InstanceMetadata> {
+    read_instance_metadata_in(&data_dir())
+}
+
+/// Metadata of the daemon that is still running, if any.
+pub fn discover_instance() -> Option<InstanceMetadata> {
+    discover_instance_in(&data_dir())
+}
+
+/// Like [`read_instance_metadata_in`], but ignores a file left behind by an
+/// instance that is no longer running.
+pub fn discover_instance_in(dir: &Path) -> Option<InstanceMetadata> {
+    read_instance_metadata_in(dir).filter(|metadata| is_process_running(metadata.pid))
+}
+
+/// Read `instance.json` from `dir`. Missing or unreadable files yield `None`.
+pub fn read_instance_metadata_in(dir: &Path) -> Option<InstanceMetadata> {
+    let raw = fs::read_to_string(dir.join(INSTANCE_METADATA_FILE)).ok()?;
+    serde_json::from_str(&raw).ok()
+}
+
+/// This machine's hostname, if it can be read.
+#[cfg(unix)]
+pub fn hostname() -> Option<String> {
+    let mut buf = [0u8; 256];
+    let result = unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) };
+    if result != 0 {
+        return None;
+    }
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    let name = String::from_utf8_lossy(&buf[..len]).trim().to_string();
+    (!name.is_empty()).then_some(name)
+}
+
+#[cfg(not(unix))]
+pub fn hostname() -> Option<String> {
+    std::env::var("COMPUTERNAME")
+        .ok()
+        .filter(|name| !name.trim().is_empty())
+}
+
 /// Read PID from file.
 fn read_pid_file(path: &PathBuf) -> Result<u32, ()> {
     let mut file = File::open(path).map_err(|_| ())?;
@@ -127,6 +207,50 @@ fn is_process_running(_pid: u32) -> bool {
     false
 }
 
+/// Whether some process holds the single-instance lock at `lock_path`.
+#[cfg(unix)]
+pub fn is_instance_locked(lock_path: &Path) -> bool {
+    use std::os::unix::io::AsRawFd;
+
+    let Ok(file) = OpenOptions::new().write(true).open(lock_path) else {
+        // No lock file means no daemon ever took the lock here
+        return false;
+    };
+    let fd = file.as_raw_fd();
+    let result = unsafe { libc::flock(fd, libc::LOCK_EX | libc::LOCK_NB) };
+    if result == 0 {
+        unsafe { libc::flock(fd, libc::LOCK_UN) };
+        false
+    } else {
+        true
+    }
+}
+
+#[cfg(not(unix))]
+pub fn is_instance_locked(_lock_path: &Path) -> bool {
+    false
+}
+
+/// Poll `running` every `interval` until it reports false or `timeout`
+/// passes. Returns whether the process was seen to exit in time.
+pub fn wait_for_exit(
+    timeout: std::time::Duration,
+    interval: std::time::Duration,
+    mut running: impl FnMut() -> bool,
+) -> bool {
+    let deadline = std::time::Instant::now() + timeout;
+    loop {
+        if !running() {
+            return true;
+        }
+        let now = std::time::Instant::now();
+        if now >= deadline {
+            return false;
+        }
+        std::thread::sleep(interval.min(deadline - now));
+    }
+}
+
 /// Get the data directory for the daemon.
 pub fn data_dir() -> PathBuf {
     if let Some(dir) = std::env::var_os("XDG_DATA_HOME") {
@@ -138,16 +262,213 @@ pub fn data_dir() -> PathBuf {
     }
 }
 
+/// Background tasks `run_daemon` starts besides the transports.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StartupPlan {
+    pub ntm_collector: bool,
+    pub tmux_collector: bool,
+    /// Db maintenance and the ended-session cache pruner.
+    pub maintenance: bool,
+    /// Restarts collectors and spawns them for tools that appear later.
+    pub supervisor: bool,
+}
+
+impl StartupPlan {
+    /// tmux is preferred over NTM when both are present. In safe mode
+    /// nothing is started and the daemon only serves its warm cache.
+    pub fn new(capabilities: &Capabilities, safe_mode: bool) -> Self {
+        if safe_mode {
+            return Self {
+                ntm_collector: false,
+                tmux_collector: false,
+                maintenance: false,
+                supervisor: false,
+            };
+        }
+        Self {
+            ntm_collector: capabilities.ntm && !capabilities.tmux,
+            tmux_collector: capabilities.tmux,
+            maintenance: true,
+            supervisor: true,
+        }
+    }
+}
+
+/// The collector tasks currently running, so the supervisor neither starts a
+/// second copy of a live collector nor leaves a superseded one polling.
+#[derive(Debug, Default)]
+pub struct CollectorTasks {
+    ntm: Option<JoinHandle<()>>,
+    tmux: Option<JoinHandle<()>>,
+    /// Whether the running tmux collector was built with ntm merged in.
+    tmux_merges_ntm: bool,
+}
+
+impl CollectorTasks {
+    /// Start or stop collectors to match `capabilities` (see [`StartupPlan`]).
+    /// A tmux collector is restarted when ntm appears or disappears, since it
+    /// decides whether to merge ntm when built; the standalone ntm collector
+    /// is stopped once tmux takes it over. Collectors that stopped themselves
+    /// (their binary went missing) count as not running.
+    pub fn apply(
+        &mut self,
+        capabilities: &Capabilities,
+        spawn_ntm: impl FnOnce() -> JoinHandle<()>,
+        spawn_tmux: impl FnOnce() -> JoinHandle<()>,
+    ) {
+        let plan = StartupPlan::new(capabilities, false);
+        if is_running(&self.tmux)
+            && (!plan.tmux_collector || self.tmux_merges_ntm != capabilities.ntm)
+        {
+            info!(kind = "tmux", "stopping collector");
+            stop(&mut self.tmux);
+        }
+        if is_running(&self.ntm) && !plan.ntm_collector {
+            info!(kind = "ntm", "stopping collector");
+            stop(&mut self.ntm);
+        }
+        if plan.tmux_collector && !is_running(&self.tmux) {
+            info!(kind = "tmux", merges_ntm = capabilities.ntm, "starting collector");
+            self.tmux = Some(spawn_tmux());
+            self.tmux_merges_ntm = capabilities.ntm;
+        }
+        if plan.ntm_collector && !is_running(&self.ntm) {
+            info!(kind = "ntm", "starting collector");
+            self.ntm = Some(spawn_ntm());
+        }
+    }
+}
+
+fn is_running(task: &Option<JoinHandle<()>>) -> bool {
+    task.as_ref().is_some_and(|task| !task.is_finished())
+}
+
+fn stop(task: &mut Option<JoinHandle<()>>) {
+    if let Some(task) = task.take() {
+        task.abort();
+    }
+}
+
+/// Which signal asked the daemon to stop.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShutdownSignal {
+    /// SIGTERM (service manager / `cmd_stop`).
+    Terminate,
+    /// SIGINT (Ctrl-C) or the platform Ctrl-C event.
+    Interrupt,
+}
+
+/// Why the daemon stopped, reported in the final shutdown log line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShutdownReason {
+    /// A signal arrived (SIGTERM covers both service managers and `stop`).
+    Signal(ShutdownSignal),
+    /// The stdio client closed stdin.
+    StdinClosed,
+    /// A component failed in a way the daemon cannot recover from.
+    Fatal(String),
+}
+
+impl ShutdownReason {
+    /// Stable name for log fields.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Signal(ShutdownSignal::Terminate) => "sigterm",
+            Self::Signal(ShutdownSignal::Interrupt) => "sigint",
+            Self::StdinClosed => "stdin_closed",
+            Self::Fatal(_) => "fatal_error",
+        }
+    }
+}
+
+/// What the daemon should do in response to a signal.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShutdownAction {
+    /// Start a graceful drain and flush, bounded by the shutdown timeout.
+    Graceful,
+    /// Skip the remaining drain and exit right away.
+    Immediate,
+    /// A graceful shutdown is already running; nothing more to do.
+    Ignore,
+}
+
+/// How a graceful shutdown finished.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShutdownOutcome {
+    /// The drain ran to completion (or its timeout).
+    Completed,
+    /// A second interrupt cut the drain short.
+    Forced,
+}
+
+/// Signal state machine: SIGTERM always drains gracefully, while an interrupt
+/// that arrives during a drain escalates to an immediate exit. The first
+/// reason to start the drain is kept; later ones do not replace it.
+#[derive(Debug, Default)]
+pub struct ShutdownState {
+    draining: bool,
+    interrupts: u32,
+    reason: Option<ShutdownReason>,
+}
+
+impl ShutdownState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a signal and decide how to react to it.
+    pub fn on_signal(&mut self, signal: ShutdownSignal) -> ShutdownAction {
+        if signal == ShutdownSignal::Interrupt {
+            self.interrupts = self.interrupts.saturating_add(1);
+            if self.draining {
+                return ShutdownAction::Immediate;
+            }
+        }
+
+        self.on_stop(ShutdownReason::Signal(signal))
+    }
+
+    /// Record a non-signal stop request (stdin closed, fatal error).
+    pub fn on_stop(&mut self, reason: ShutdownReason) -> ShutdownAction {
+        if self.draining {
+            ShutdownAction::Ignore
+        } else {
+            self.draining = true;
+            self.reason = Some(reason);
+            ShutdownAction::Graceful
+        }
+    }
+
+    /// Why the drain started, once it has.
+    pub fn reason(&self) -> Option<&ShutdownReason> {
+        self.reason.as_ref()
+    }
+
+    /// Whether a graceful drain has been started.
+    pub fn is_draining(&self) -> bool {
+        self.draining
+    }
+
+    /// Number of interrupts received so far.
+    pub fn interrupts(&self) -> u32 {
+        self.interrupts
+    }
+}
+
 /// Graceful shutdown handler.
 pub struct ShutdownHandler {
     shutdown_tx: broadcast::Sender<()>,
+    state: Mutex<ShutdownState>,
 }
 
 impl ShutdownHandler {
     /// Create a new shutdown handler and install signal handlers.
     pub fn new() -> Self {
         let (shutdown_tx, _) = broadcast::channel(1);
-        Self { shutdown_tx }
+        Self {
+            shutdown_tx,
+            state: Mutex::new(ShutdownState::new()),
+        }
     }
 
     /// Get a receiver for shutdown notifications.
@@ -161,45 +482,125 @@ impl ShutdownHandler {
         let _ = self.shutdown_tx.send(());
     }
 
-    /// Wait for shutdown signal (SIGTERM, SIGINT, or manual trigger).
-    pub async fn wait_for_signal(&self) {
-        #[cfg(unix)]
-        {
-            use tokio::signal::unix::{signal, SignalKind};
+    /// Feed a signal through the state machine.
+    pub fn on_signal(&self, signal: ShutdownSignal) -> ShutdownAction {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .on_signal(signal)
+    }
 
-            let mut sigterm = signal(SignalKind::terminate()).expect("SIGTERM handler");
-            let mut sigint = signal(SignalKind::interrupt()).expect("SIGINT handler");
+    /// Stop for a reason other than a signal, notifying subscribers unless a
+    /// shutdown is already under way.
+    pub fn stop(&self, reason: ShutdownReason) -> ShutdownAction {
+        let action = self
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .on_stop(reason);
+        if action == ShutdownAction::Graceful {
+            self.shutdown();
+        }
+        action
+    }
 
-            tokio::select! {
-                _ = sigterm.recv() => {
-                    info!("Received SIGTERM, initiating shutdown");
-                }
-                _ = sigint.recv() => {
-                    info!("Received SIGINT, initiating shutdown");
+    /// Why the shutdown started, if it has.
+    pub fn reason(&self) -> Option<ShutdownReason> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .reason()
+            .cloned()
+    }
+
+    /// Wait for shutdown signal (SIGTERM, SIGINT, or manual trigger).
+    ///
+    /// Returns the signal that started the shutdown.
+    pub async fn wait_for_signal(&self) -> ShutdownSignal {
+        loop {
+            let signal = next_signal().await;
+            match signal {
+                ShutdownSignal::Terminate => info!("Received SIGTERM, initiating graceful shutdown"),
+                ShutdownSignal::Interrupt => {
+                    info!("Received SIGINT, initiating graceful shutdown (interrupt again to force)")
                 }
             }
+            if self.on_signal(signal) == ShutdownAction::Graceful {
+                self.shutdown();
+                return signal;
+            }
         }
-
-        #[cfg(not(unix))]
-        {
-            tokio::signal::ctrl_c().await.expect("Ctrl-C handler");
-            info!("Received Ctrl-C, initiating shutdown");
-        }
-
-        self.shutdown();
     }
 
     /// Wait for shutdown with timeout.
-    pub async fn graceful_shutdown(&self, timeout: std::time::Duration) {
+    ///
+    /// A further interrupt while waiting bypasses the timeout and returns
+    /// [`ShutdownOutcome::Forced`].
+    pub async fn graceful_shutdown(&self, timeout: std::time::Duration) -> ShutdownOutcome {
         info!("Starting graceful shutdown (timeout: {:?})", timeout);
 
         // Give components time to finish
-        tokio::time::sleep(timeout).await;
+        let outcome = tokio::select! {
+            _ = tokio::time::sleep(timeout) => ShutdownOutcome::Completed,
+            _ = self.wait_for_escalation() => ShutdownOutcome::Forced,
+        };
+
+        match outcome {
+            ShutdownOutcome::Completed => info!("Graceful shutdown complete"),
+            ShutdownOutcome::Forced => warn!("Second interrupt received, skipping graceful shutdown"),
+        }
+        outcome
+    }
+
+    async fn wait_for_escalation(&self) {
+        loop {
+            let signal = next_signal().await;
+            if self.on_signal(signal) == ShutdownAction::Immediate {
+                return;
+            }
+            debug!(?signal, "Shutdown already in progress, ignoring signal");
+        }
+    }
+}
+
+/// Log the daemon's final status line: why it stopped and, when there was a
+/// drain, how it went and how long it took.
+pub fn log_shutdown_complete(
+    reason: Option<&ShutdownReason>,
+    drain: Option<(ShutdownOutcome, std::time::Duration)>,
+) {
+    let detail = match reason {
+        Some(ShutdownReason::Fatal(detail)) => Some(detail.as_str()),
+        _ => None,
+    };
+    info!(
+        reason = reason.map_or("unknown", ShutdownReason::as_str),
+        detail,
+        outcome = drain.map(|(outcome, _)| tracing::field::debug(outcome)),
+        drain_ms = drain.map(|(_, elapsed)| elapsed.as_millis() as u64),
+        "Daemon shutdown complete"
+    );
+}
+
+#[cfg(unix)]
+async fn next_signal() -> ShutdownSignal {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut sigterm = signal(SignalKind::terminate()).expect("SIGTERM handler");
+    let mut sigint = signal(SignalKind::interrupt()).expect("SIGINT handler");
 
-        info!("Graceful shutdown complete");
+    tokio::select! {
+        _ = sigterm.recv() => ShutdownSignal::Terminate,
+        _ = sigint.recv() => ShutdownSignal::Interrupt,
     }
 }
 
+#[cfg(not(unix))]
+async fn next_signal() -> ShutdownSignal {
+    tokio::signal::ctrl_c().await.expect("Ctrl-C handler");
+    ShutdownSignal::Interrupt
+}
+
 impl Default for ShutdownHandler {
     fn default() -> Self {
         Self::new()
@@ -228,6 +629,94 @@ mod tests {
         env::remove_var("XDG_DATA_HOME");
     }
 
+    fn caps(ntm: bool, tmux: bool) -> Capabilities {
+        Capabilities {
+            ntm,
+            tmux,
+            stream: false,
+            systemd: false,
+        }
+    }
+
+    #[test]
+    fn startup_plan_prefers_tmux() {
+        let plan = StartupPlan::new(&caps(true, true), false);
+        assert!(plan.tmux_collector);
+        assert!(!plan.ntm_collector);
+        assert!(plan.maintenance && plan.supervisor);
+        assert!(StartupPlan::new(&caps(true, false), false).ntm_collector);
+    }
+
+    fn pending() -> JoinHandle<()> {
+        tokio::spawn(std::future::pending())
+    }
+
+    #[tokio::test]
+    async fn collector_tasks_follow_capability_flips() {
+        let spawned = std::cell::RefCell::new(Vec::new());
+        let apply = |tasks: &mut CollectorTasks, ntm: bool, tmux: bool| {
+            tasks.apply(
+                &caps(ntm, tmux),
+                || {
+                    spawned.borrow_mut().push("ntm");
+                    pending()
+                },
+                || {
+                    spawned.borrow_mut().push("tmux");
+                    pending()
+                },
+            );
+        };
+        let mut tasks = CollectorTasks::default();
+
+        apply(&mut tasks, true, false);
+        let ntm = tasks.ntm.as_ref().unwrap().abort_handle();
+        // tmux appears: it takes ntm over
+        apply(&mut tasks, true, true);
+        tokio::task::yield_now().await;
+        assert!(ntm.is_finished(), "ntm collector stopped");
+        assert!(tasks.ntm.is_none());
+        // tmux flaps while its collector is still running: no second copy
+        apply(&mut tasks, true, true);
+        assert_eq!(*spawned.borrow(), ["ntm", "tmux"]);
+
+        // ntm disappears: tmux restarts without the merge
+        apply(&mut tasks, false, true);
+        assert_eq!(*spawned.borrow(), ["ntm", "tmux", "tmux"]);
+        assert!(!tasks.tmux_merges_ntm);
+    }
+
+    #[tokio::test]
+    async fn finished_collectors_are_restarted() {
+        let mut tasks = CollectorTasks::default();
+        let mut starts = 0;
+        for _ in 0..2 {
+            tasks.apply(&caps(false, true), pending, || {
+                starts += 1;
+                tokio::spawn(async {})
+            });
+            // The collector stops itself when its binary goes missing
+            while !tasks.tmux.as_ref().unwrap().is_finished() {
+                tokio::task::yield_now().await;
+            }
+        }
+        assert_eq!(starts, 2);
+    }
+
+    #[test]
+    fn safe_mode_spawns_no_collectors() {
+        let plan = StartupPlan::new(&caps(true, true), true);
+        assert_eq!(
+            plan,
+            StartupPlan {
+                ntm_collector: false,
+                tmux_collector: false,
+                maintenance: false,
+                supervisor: false,
+            }
+        );
+    }
+
     #[test]
     fn shutdown_handler_broadcasts() {
         let handler = ShutdownHandler::new();
@@ -341,6 +830,81 @@ mod tests {
         env::remove_var("XDG_DATA_HOME");
     }
 
+    #[test]
+    fn instance_metadata_round_trips_and_is_removed_on_drop() {
+        let _lock = ENV_LOCK.lock().unwrap();
+        let temp = TempDir::new().unwrap();
+        env::set_var("XDG_DATA_HOME", temp.path());
+
+        let guard = InstanceGuard::acquire().expect("acquire");
+        assert_eq!(read_instance_metadata(), None);
+        let metadata = InstanceMetadata {
+            pid: std::process::id(),
+            instance_id: "0190-test".to_string(),
+            label: "build-box".to_string(),
+            port: Some(3849),
+            ws_port: None,
+            uds_path: None,
+        };
+        guard.write_metadata(&metadata).unwrap();
+        assert_eq!(read_instance_metadata(), Some(metadata));
+
+        let metadata_path = guard.metadata_path();
+        let raw: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&metadata_path).unwrap()).unwrap();
+        assert_eq!(raw["pid"], std::process::id());
+        assert_eq!(raw["instanceId"], "0190-test");
+        assert_eq!(raw["label"], "build-box");
+        assert_eq!(raw["port"], 3849);
+        assert!(raw.get("udsPath").is_none());
+        drop(guard);
+        assert!(!metadata_path.exists());
+
+        env::remove_var("XDG_DATA_HOME");
+    }
+
+    #[test]
+    fn instance_metadata_read_ignores_missing_or_malformed_files() {
+        let temp = TempDir::new().unwrap();
+        assert_eq!(read_instance_metadata_in(temp.path()), None);
+
+        fs::write(temp.path().join(INSTANCE_METADATA_FILE), "{not json").unwrap();
+        assert_eq!(read_instance_metadata_in(temp.path()), None);
+
+        fs::write(
+            temp.path().join(INSTANCE_METADATA_FILE),
+            r#"{"pid": 42, "instanceId": "abc", "label": "", "port": null}"#,
+        )
+        .unwrap();
+        let metadata = read_instance_metadata_in(temp.path()).unwrap();
+        assert_eq!(metadata.pid, 42);
+        assert_eq!(metadata.port, None);
+        assert_eq!(metadata.ws_port, None);
+    }
+
+    #[test]
+    fn discover_instance_skips_metadata_of_exited_daemon() {
+        let temp = TempDir::new().unwrap();
+        let mut metadata = InstanceMetadata {
+            pid: std::process::id(),
+            instance_id: "abc".to_string(),
+            label: String::new(),
+            port: Some(3901),
+            ws_port: None,
+            uds_path: None,
+        };
+        let path = temp.path().join(INSTANCE_METADATA_FILE);
+        fs::write(&path, serde_json::to_string(&metadata).unwrap()).unwrap();
+        assert_eq!(discover_instance_in(temp.path()), Some(metadata.clone()));
+
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        metadata.pid = child.id();
+        child.wait().unwrap();
+        fs::write(&path, serde_json::to_string(&metadata).unwrap()).unwrap();
+        assert_eq!(discover_instance_in(temp.path()), None);
+        assert!(read_instance_metadata_in(temp.path()).is_some());
+    }
+
     #[test]
     fn instance_guard_pid_file_contains_our_pid() {
         let _lock = ENV_LOCK.lock().unwrap();
@@ -372,6 +936,125 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    fn sigterm_starts_graceful_shutdown() {
+        let mut state = ShutdownState::new();
+        assert_eq!(state.on_signal(ShutdownSignal::Terminate), ShutdownAction::Graceful);
+        assert!(state.is_draining());
+        // Repeated SIGTERM does not escalate.
+        assert_eq!(state.on_signal(ShutdownSignal::Terminate), ShutdownAction::Ignore);
+    }
+
+    #[test]
+    fn second_interrupt_escalates_to_immediate() {
+        let mut state = ShutdownState::new();
+        assert_eq!(state.on_signal(ShutdownSignal::Interrupt), ShutdownAction::Graceful);
+        assert_eq!(state.on_signal(ShutdownSignal::Interrupt), ShutdownAction::Immediate);
+        assert_eq!(state.interrupts(), 2);
+    }
+
+    #[test]
+    fn interrupt_during_sigterm_drain_escalates() {
+        let mut state = ShutdownState::new();
+        assert_eq!(state.on_signal(ShutdownSignal::Terminate), ShutdownAction::Graceful);
+        assert_eq!(state.on_signal(ShutdownSignal::Interrupt), ShutdownAction::Immediate);
+    }
+
+    #[test]
+    fn handler_tracks_signal_state() {
+        let handler = ShutdownHandler::new();
+        assert_eq!(handler.on_signal(ShutdownSignal::Interrupt), ShutdownAction::Graceful);
+        assert_eq!(handler.on_signal(ShutdownSignal::Interrupt), ShutdownAction::Immediate);
+    }
+
+    #[test]
+    fn first_reason_to_stop_is_kept() {
+        let mut state = ShutdownState::new();
+        assert_eq!(state.reason(), None);
+        assert_eq!(state.on_signal(ShutdownSignal::Terminate), ShutdownAction::Graceful);
+        assert_eq!(state.reason(), Some(&ShutdownReason::Signal(ShutdownSignal::Terminate)));
+
+        // Neither a later stop nor an escalating interrupt rewrites the reason
+        let fatal = ShutdownReason::Fatal("stdout closed".to_string());
+        assert_eq!(state.on_stop(fatal), ShutdownAction::Ignore);
+        assert_eq!(state.on_signal(ShutdownSignal::Interrupt), ShutdownAction::Immediate);
+        assert_eq!(state.reason().map(ShutdownReason::as_str), Some("sigterm"));
+    }
+
+    #[test]
+    fn stop_without_signal_records_reason_and_still_escalates() {
+        let mut state = ShutdownState::new();
+        assert_eq!(state.on_stop(ShutdownReason::StdinClosed), ShutdownAction::Graceful);
+        assert!(state.is_draining());
+        assert_eq!(state.reason().map(ShutdownReason::as_str), Some("stdin_closed"));
+        assert_eq!(state.on_signal(ShutdownSignal::Interrupt), ShutdownAction::Immediate);
+    }
+
+    #[tokio::test]
+    async fn handler_stop_notifies_subscribers_once() {
+        let handler = ShutdownHandler::new();
+        let mut rx = handler.subscribe();
+        let fatal = ShutdownReason::Fatal("stdout closed".to_string());
+        assert_eq!(handler.stop(fatal.clone()), ShutdownAction::Graceful);
+        assert!(rx.recv().await.is_ok());
+        assert_eq!(handler.stop(ShutdownReason::StdinClosed), ShutdownAction::Ignore);
+        assert_eq!(handler.reason(), Some(fatal));
+    }
+
+    #[tokio::test]
+    async fn graceful_shutdown_completes_after_timeout() {
+        let handler = ShutdownHandler::new();
+        let outcome = handler
+            .graceful_shutdown(std::time::Duration::from_millis(10))
+            .await;
+        assert_eq!(outcome, ShutdownOutcome::Completed);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn wait_for_exit_sees_the_lock_released() {
+        use std::os::unix::io::AsRawFd;
+        use std::time::Duration;
+
+        let temp = TempDir::new().unwrap();
+        let lock_path = temp.path().join("daemon.lock");
+        assert!(!is_instance_locked(&lock_path));
+
+        // Stand in for a daemon that holds the lock and exits after a delay
+        let held = File::create(&lock_path).unwrap();
+        let locked = unsafe { libc::flock(held.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) };
+        assert_eq!(locked, 0);
+        assert!(is_instance_locked(&lock_path));
+        let daemon = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(150));
+            drop(held);
+        });
+
+        let interval = Duration::from_millis(10);
+        assert!(!wait_for_exit(Duration::from_millis(30), interval, || {
+            is_instance_locked(&lock_path)
+        }));
+        assert!(wait_for_exit(Duration::from_secs(5), interval, || {
+            is_instance_locked(&lock_path)
+        }));
+        daemon.join().unwrap();
+    }
+
+    #[test]
+    fn wait_for_exit_checks_before_sleeping() {
+        let mut polls = 0;
+        let exited = wait_for_exit(
+            std::time::Duration::ZERO,
+            std::time::Duration::from_secs(1),
+            || {
+                polls += 1;
+                false
+            },
+        );
+        assert!(exited);
+        assert_eq!(polls, 1);
+    }
+
     #[test]
     fn data_dir_returns_path_ending_with_ntm_tracker() {
         let dir = data_dir();
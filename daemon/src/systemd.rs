@@ -0,0 +1,162 @@
+//! Minimal systemd integration: `sd_notify` readiness and watchdog pings.
+//!
+//! Only active when the daemon runs under systemd (see `Capabilities::systemd`)
+//! and the unit provides `NOTIFY_SOCKET`. Everything here is a no-op otherwise.
+
+use std::io;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use tracing::{debug, info, warn};
+
+/// Send a raw `sd_notify` state string (e.g. `READY=1`).
+///
+/// Returns `Ok(false)` when `NOTIFY_SOCKET` is not set.
+#[cfg(unix)]
+pub fn notify(state: &str) -> io::Result<bool> {
+    use std::os::unix::net::UnixDatagram;
+
+    let Some(socket_path) = std::env::var_os("NOTIFY_SOCKET") else {
+        return Ok(false);
+    };
+    let socket_path = socket_path.to_string_lossy().into_owned();
+    if socket_path.is_empty() {
+        return Ok(false);
+    }
+
+    let socket = UnixDatagram::unbound()?;
+    if let Some(abstract_name) = socket_path.strip_prefix('@') {
+        send_abstract(&socket, abstract_name, state)?;
+    } else {
+        socket.send_to(state.as_bytes(), &socket_path)?;
+    }
+    Ok(true)
+}
+
+#[cfg(not(unix))]
+pub fn notify(_state: &str) -> io::Result<bool> {
+    Ok(false)
+}
+
+#[cfg(target_os = "linux")]
+fn send_abstract(
+    socket: &std::os::unix::net::UnixDatagram,
+    name: &str,
+    state: &str,
+) -> io::Result<()> {
+    use std::os::linux::net::SocketAddrExt;
+    use std::os::unix::net::SocketAddr;
+
+    let addr = SocketAddr::from_abstract_name(name.as_bytes())?;
+    socket.send_to_addr(state.as_bytes(), &addr)?;
+    Ok(())
+}
+
+#[cfg(all(unix, not(target_os = "linux")))]
+fn send_abstract(
+    _socket: &std::os::unix::net::UnixDatagram,
+    _name: &str,
+    _state: &str,
+) -> io::Result<()> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "abstract notify sockets are Linux-only",
+    ))
+}
+
+/// Tell systemd the daemon finished starting up.
+pub fn notify_ready() {
+    match notify("READY=1") {
+        Ok(true) => info!("Sent READY=1 to systemd"),
+        Ok(false) => debug!("NOTIFY_SOCKET not set; skipping sd_notify READY"),
+        Err(err) => warn!(error = %err, "sd_notify READY failed"),
+    }
+}
+
+/// Tell systemd the daemon is shutting down.
+pub fn notify_stopping() {
+    if let Err(err) = notify("STOPPING=1") {
+        warn!(error = %err, "sd_notify STOPPING failed");
+    }
+}
+
+/// Watchdog ping interval for the current process, if systemd asked for one.
+pub fn watchdog_interval() -> Option<Duration> {
+    let usec = std::env::var("WATCHDOG_USEC").ok();
+    let pid = std::env::var("WATCHDOG_PID").ok();
+    watchdog_interval_from(usec.as_deref(), pid.as_deref(), std::process::id())
+}
+
+/// Compute the ping interval from `WATCHDOG_USEC` / `WATCHDOG_PID`.
+///
+/// Pings are sent at half the configured timeout, as recommended by
+/// `sd_watchdog_enabled(3)`. Returns `None` when the watchdog is disabled,
+/// the value is invalid, or `WATCHDOG_PID` names another process.
+pub fn watchdog_interval_from(
+    watchdog_usec: Option<&str>,
+    watchdog_pid: Option<&str>,
+    own_pid: u32,
+) -> Option<Duration> {
+    let usec = watchdog_usec?.trim().parse::<u64>().ok()?;
+    if usec == 0 {
+        return None;
+    }
+    if let Some(pid) = watchdog_pid {
+        if pid.trim().parse::<u32>().ok()? != own_pid {
+            return None;
+        }
+    }
+    Some(Duration::from_micros(usec / 2).max(Duration::from_millis(1)))
+}
+
+/// Send `WATCHDOG=1` every `interval` until shutdown.
+pub async fn run_watchdog(interval: Duration, mut shutdown_rx: broadcast::Receiver<()>) {
+    info!(interval_ms = interval.as_millis() as u64, "systemd watchdog enabled");
+    let mut ticker = tokio::time::interval(interval);
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                if let Err(err) = notify("WATCHDOG=1") {
+                    warn!(error = %err, "sd_notify WATCHDOG failed");
+                }
+            }
+            _ = shutdown_rx.recv() => {
+                break;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn watchdog_interval_is_half_timeout() {
+        let interval = watchdog_interval_from(Some("30000000"), None, 42);
+        assert_eq!(interval, Some(Duration::from_secs(15)));
+    }
+
+    #[test]
+    fn watchdog_interval_respects_pid() {
+        assert_eq!(
+            watchdog_interval_from(Some("2000000"), Some("42"), 42),
+            Some(Duration::from_secs(1))
+        );
+        assert_eq!(watchdog_interval_from(Some("2000000"), Some("7"), 42), None);
+    }
+
+    #[test]
+    fn watchdog_interval_disabled_or_invalid() {
+        assert_eq!(watchdog_interval_from(None, None, 42), None);
+        assert_eq!(watchdog_interval_from(Some("0"), None, 42), None);
+        assert_eq!(watchdog_interval_from(Some("soon"), None, 42), None);
+        assert_eq!(watchdog_interval_from(Some("1000000"), Some("abc"), 42), None);
+    }
+
+    #[test]
+    fn notify_without_socket_is_noop() {
+        if std::env::var_os("NOTIFY_SOCKET").is_none() {
+            assert!(!notify("READY=1").unwrap());
+        }
+    }
+}
@@ -1,16 +1,17 @@
 //! HTTP transport adapter for simple request/response RPC.
 //!
 //! This is an optional fallback transport for clients that can't use stdio or WebSocket.
-//! It only supports request/response - no push notifications.
+//! It only supports request/response - no push notifications. `GET /healthz` is
+//! also answered, as a plain-text liveness probe for proxies and load balancers.
 
-use crate::rpc::{self, RpcContext};
-use crate::transport::{JsonRpcError, JsonRpcRequest, JsonRpcResponse};
-use serde_json::Value;
+use crate::metrics::METRICS;
+use crate::rpc::RpcContext;
+use crate::transport::{self, JsonRpcResponse};
 use std::net::SocketAddr;
 use std::sync::Arc;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::{TcpListener, TcpStream};
-use tracing::{debug, error, info, trace, warn};
+use tracing::{debug, error, info, trace};
 
 /// Configuration for the HTTP server.
 #[derive(Clone, Debug)]
@@ -53,7 +54,16 @@ impl HttpServer {
                 return;
             }
         };
+        self.serve(listener, ctx).await;
+    }
 
+    /// Serve on a listener the caller already bound (see
+    /// [`crate::transport::bind_loopback`]).
+    pub async fn serve(self, listener: TcpListener, ctx: Arc<RpcContext>) {
+        let addr = listener
+            .local_addr()
+            .map(|addr| addr.to_string())
+            .unwrap_or_default();
         info!(addr = %addr, "HTTP server listening");
 
         let server = Arc::new(self);
@@ -101,6 +111,13 @@ impl HttpServer {
             }
         };
 
+        // Liveness probes carry no token and get no RPC details
+        if is_healthz_request(headers) {
+            let response = healthz_response(&ctx);
+            stream.write_all(response.as_bytes()).await?;
+            return Ok(());
+        }
+
         // Check method and path
         if !headers.starts_with("POST /rpc") && !headers.starts_with("POST / ") {
             let response = http_response(404, "Not Found", "Only POST /rpc is supported");
@@ -124,8 +141,15 @@ impl HttpServer {
         client_ctx.is_admin = is_admin;
 
         // Process JSON-RPC request
-        let response = self.process_request(body, &client_ctx);
+        let Some(response) = self.process_request(body, &client_ctx) else {
+            stream
+                .write_all(b"HTTP/1.1 204 No Content\r\nConnection: close\r\n\r\n")
+                .await?;
+            debug!(addr = %addr, "HTTP notification acknowledged");
+            return Ok(());
+        };
         let response_json = serde_json::to_string(&response)?;
+        METRICS.rpc_traffic.record_bytes_out(response_json.len());
 
         // Send HTTP response
         let http_response = format!(
@@ -145,38 +169,9 @@ impl HttpServer {
         Ok(())
     }
 
-    fn process_request(&self, body: &str, ctx: &RpcContext) -> JsonRpcResponse {
-        // Parse JSON
-        let request: JsonRpcRequest = match serde_json::from_str(body) {
-            Ok(req) => req,
-            Err(e) => {
-                warn!(error = %e, "failed to parse JSON");
-                return JsonRpcResponse::error(
-                    Value::Null,
-                    JsonRpcError::parse_error(format!("Invalid JSON: {e}")),
-                );
-            }
-        };
-
-        // Validate JSON-RPC version
-        if request.jsonrpc != "2.0" {
-            warn!(version = %request.jsonrpc, "invalid JSON-RPC version");
-            return JsonRpcResponse::error(
-                request.id.unwrap_or(Value::Null),
-                JsonRpcError::invalid_request("Expected jsonrpc: \"2.0\""),
-            );
-        }
-
-        let id = request.id.unwrap_or(Value::Null);
-
-        // Handle the request
-        debug!(method = %request.method, "handling request");
-        let result = rpc::handle(&request.method, request.params, ctx);
-
-        match result {
-            Ok(value) => JsonRpcResponse::success(id, value),
-            Err(e) => JsonRpcResponse::error(id, JsonRpcError::from_rpc_error(&e)),
-        }
+    /// Returns `None` for notifications, which get an empty 204 reply.
+    fn process_request(&self, body: &str, ctx: &RpcContext) -> Option<JsonRpcResponse> {
+        transport::process_message(body, ctx)
     }
 
     fn extract_auth(&self, headers: &str) -> Option<bool> {
@@ -227,6 +222,22 @@ fn parse_http_request(request: &str) -> Option<(&str, &str)> {
     }
 }
 
+/// Whether the request line is `GET /healthz`.
+fn is_healthz_request(headers: &str) -> bool {
+    let mut request_line = headers.lines().next().unwrap_or("").split_whitespace();
+    request_line.next() == Some("GET") && request_line.next() == Some("/healthz")
+}
+
+/// `200 ok` while the daemon is healthy, `503` with its status otherwise.
+fn healthz_response(ctx: &RpcContext) -> String {
+    let status = ctx.cache.health().status;
+    if status == "ok" {
+        http_response(200, "OK", "ok")
+    } else {
+        http_response(503, "Service Unavailable", &status)
+    }
+}
+
 /// Create a simple HTTP response.
 fn http_response(status: u16, status_text: &str, body: &str) -> String {
     format!(
@@ -246,6 +257,7 @@ fn http_response(status: u16, status_text: &str, body: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::transport::test_context;
 
     #[test]
     fn default_config() {
@@ -297,6 +309,56 @@ mod tests {
         assert_eq!(server.extract_auth(headers), None);
     }
 
+    #[test]
+    fn process_request_echoes_string_and_numeric_ids() {
+        let server = HttpServer::new(HttpConfig::default());
+        let ctx = test_context();
+        let body = r#"{"jsonrpc":"2.0","method":"health.get","id":"abc-1"}"#;
+        let resp = server.process_request(body, &ctx).unwrap();
+        assert_eq!(resp.id, serde_json::json!("abc-1"));
+        let body = r#"{"jsonrpc":"2.0","method":"health.get","id":12}"#;
+        let resp = server.process_request(body, &ctx).unwrap();
+        assert_eq!(resp.id, serde_json::json!(12));
+    }
+
+    #[test]
+    fn process_request_notification_has_no_response() {
+        let server = HttpServer::new(HttpConfig::default());
+        let ctx = test_context();
+        let body = r#"{"jsonrpc":"2.0","method":"health.get","params":{}}"#;
+        assert!(server.process_request(body, &ctx).is_none());
+    }
+
+    #[test]
+    fn healthz_matches_only_its_path() {
+        assert!(is_healthz_request("GET /healthz HTTP/1.1\r\nHost: x"));
+        assert!(!is_healthz_request("GET /healthzz HTTP/1.1"));
+        assert!(!is_healthz_request("POST /healthz HTTP/1.1"));
+        assert!(!is_healthz_request("POST /rpc HTTP/1.1"));
+    }
+
+    #[test]
+    fn healthz_is_200_when_ok_and_503_when_degraded() {
+        let ctx = test_context();
+        ctx.cache.set_health(crate::cache::HealthStatus {
+            status: "ok".to_string(),
+            last_error: None,
+            degraded_reason: None,
+        });
+        let response = healthz_response(&ctx);
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.ends_with("\r\n\r\nok"));
+
+        ctx.cache.set_health(crate::cache::HealthStatus {
+            status: "degraded".to_string(),
+            last_error: Some("tmux unavailable".to_string()),
+            degraded_reason: None,
+        });
+        let response = healthz_response(&ctx);
+        assert!(response.starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+        assert!(response.ends_with("degraded"));
+    }
+
     #[test]
     fn extract_auth_allows_when_unconfigured() {
         let server = HttpServer::new(HttpConfig::default());
@@ -3,8 +3,15 @@
 //! All transports use the same RPC handlers - they just differ in how
 //! they receive requests and send responses/notifications.
 
-use serde::{Deserialize, Serialize};
+use crate::bus::{ClientUpdate, DaemonEvent, EventBus, StateChange};
+use crate::config::{StdioFraming, TransportsConfig};
+use crate::metrics::{RpcTraffic, METRICS};
+use crate::rpc::{self, handlers, RpcContext};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use tracing::{debug, warn};
 
 pub mod http;
 pub mod stdio;
@@ -17,11 +24,97 @@ pub struct JsonRpcRequest {
     pub method: String,
     #[serde(default)]
     pub params: Value,
-    /// If present, this is a request expecting a response.
-    /// If absent, this is a notification (no response expected).
+    /// If present (including an explicit `null`), this is a request expecting
+    /// a response. If absent, this is a notification (no response expected).
+    #[serde(default, deserialize_with = "deserialize_present")]
     pub id: Option<Value>,
 }
 
+/// Deserialize a field so that an explicit `null` is `Some(Value::Null)`;
+/// combined with `#[serde(default)]`, only a missing field yields `None`.
+fn deserialize_present<'de, D>(deserializer: D) -> Result<Option<Value>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Value::deserialize(deserializer).map(Some)
+}
+
+/// The spec only allows string, number, or null request ids.
+fn is_valid_id(id: &Value) -> bool {
+    matches!(id, Value::String(_) | Value::Number(_) | Value::Null)
+}
+
+/// Process one JSON-RPC message and return the response to send, if any.
+///
+/// Shared by every transport so ids are echoed the same way everywhere:
+/// string and numeric ids are returned verbatim, and notifications (no `id`)
+/// are handled without producing a response. Unknown notifications are
+/// dropped quietly; unknown requests get method-not-found.
+pub fn process_message(text: &str, ctx: &RpcContext) -> Option<JsonRpcResponse> {
+    process_message_counted(text, ctx, &METRICS.rpc_traffic)
+}
+
+/// [`process_message`], counting the request and its size in `traffic`.
+fn process_message_counted(
+    text: &str,
+    ctx: &RpcContext,
+    traffic: &RpcTraffic,
+) -> Option<JsonRpcResponse> {
+    // Parse JSON
+    let parsed = serde_json::from_str::<JsonRpcRequest>(text);
+    let method = parsed.as_ref().ok().map(|req| req.method.as_str());
+    traffic.record_request(method, text.len());
+    let request = match parsed {
+        Ok(req) => req,
+        Err(e) => {
+            warn!(error = %e, "failed to parse JSON");
+            return Some(JsonRpcResponse::error(
+                Value::Null,
+                JsonRpcError::parse_error(format!("Invalid JSON: {e}")),
+            ));
+        }
+    };
+
+    // Reject ids the spec does not allow; they cannot be echoed back
+    if let Some(id) = request.id.as_ref().filter(|id| !is_valid_id(id)) {
+        warn!(id = %id, "invalid JSON-RPC id");
+        return Some(JsonRpcResponse::error(
+            Value::Null,
+            JsonRpcError::invalid_request("id must be a string, number, or null"),
+        ));
+    }
+
+    // Validate JSON-RPC version
+    if request.jsonrpc != "2.0" {
+        warn!(version = %request.jsonrpc, "invalid JSON-RPC version");
+        return Some(JsonRpcResponse::error(
+            request.id.unwrap_or(Value::Null),
+            JsonRpcError::invalid_request("Expected jsonrpc: \"2.0\""),
+        ));
+    }
+
+    // If no id, this is a notification - no response expected
+    let id = match request.id {
+        Some(id) => id,
+        None => {
+            debug!(method = %request.method, "received notification (no response)");
+            if rpc::handle_known(&request.method, request.params, ctx).is_none() {
+                debug!(method = %request.method, "ignoring unknown notification");
+            }
+            return None;
+        }
+    };
+
+    // Handle the request
+    debug!(method = %request.method, "handling request");
+    let response = match rpc::handle_known(&request.method, request.params, ctx) {
+        Some(Ok(value)) => JsonRpcResponse::success(id, value),
+        Some(Err(e)) => JsonRpcResponse::error(id, JsonRpcError::from_rpc_error(&e)),
+        None => JsonRpcResponse::error(id, JsonRpcError::method_not_found(&request.method)),
+    };
+    Some(response)
+}
+
 /// JSON-RPC 2.0 response structure.
 #[derive(Debug, Clone, Serialize)]
 pub struct JsonRpcResponse {
@@ -151,10 +244,266 @@ impl JsonRpcNotification {
     }
 }
 
+/// Limits a notification to one send per window. Triggers that arrive while a
+/// send is already scheduled fold into it, and the send carries whatever the
+/// state is when it goes out.
+#[derive(Debug)]
+pub struct NotificationCoalescer {
+    window: Duration,
+    last_sent: Option<Instant>,
+    scheduled: bool,
+}
+
+impl NotificationCoalescer {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            last_sent: None,
+            scheduled: false,
+        }
+    }
+
+    /// Pick up a changed window (config reload); applies from the next trigger.
+    pub fn set_window(&mut self, window: Duration) {
+        self.window = window;
+    }
+
+    /// Note a reason to notify. Returns when to send, or `None` when an
+    /// earlier trigger already scheduled a send that will cover this one.
+    pub fn trigger(&mut self, now: Instant) -> Option<Instant> {
+        if self.scheduled {
+            return None;
+        }
+        self.scheduled = true;
+        let send_at = match self.last_sent {
+            Some(last) => (last + self.window).max(now),
+            None => now,
+        };
+        Some(send_at)
+    }
+
+    /// Record that the scheduled notification went out.
+    pub fn sent(&mut self, now: Instant) {
+        self.scheduled = false;
+        self.last_sent = Some(now);
+    }
+}
+
+/// Bus receivers that turn tmux captures and state changes into
+/// `pane.output` notifications for one connection's `panes.tail` streams.
+pub struct PaneTailFeed {
+    captures: broadcast::Receiver<ClientUpdate>,
+    states: broadcast::Receiver<StateChange>,
+}
+
+impl PaneTailFeed {
+    pub fn new(bus: &EventBus) -> Self {
+        Self {
+            captures: bus.subscribe_clients(),
+            states: bus.subscribe_state(),
+        }
+    }
+
+    /// Wait for the next notifications for `ctx`'s tails; `None` once the
+    /// bus is gone. Cancel-safe, so it can sit in a `select!`.
+    pub async fn next(&mut self, ctx: &RpcContext) -> Option<Vec<JsonRpcNotification>> {
+        loop {
+            let params = tokio::select! {
+                received = self.captures.recv() => match received {
+                    Ok(update) => handlers::panes::tail_output(ctx, &update).into_iter().collect(),
+                    // Lost captures only widen the next diff
+                    Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                },
+                received = self.states.recv() => match received {
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {
+                        handlers::panes::ended_tails(ctx)
+                    }
+                    Err(broadcast::error::RecvError::Closed) => return None,
+                },
+            };
+            if !params.is_empty() {
+                return Some(
+                    params
+                        .into_iter()
+                        .map(|params| JsonRpcNotification::new("pane.output", params))
+                        .collect(),
+                );
+            }
+        }
+    }
+}
+
+/// `events.push` notifications for the events published on the daemon bus,
+/// for transports that push.
+pub struct EventPushFeed {
+    events: broadcast::Receiver<DaemonEvent>,
+}
+
+impl EventPushFeed {
+    pub fn new(bus: &EventBus) -> Self {
+        Self {
+            events: bus.subscribe_events(),
+        }
+    }
+
+    /// Wait for the next event and batch any already queued behind it;
+    /// `None` once the bus is gone. Cancel-safe, so it can sit in a
+    /// `select!`.
+    pub async fn next(&mut self) -> Option<JsonRpcNotification> {
+        let first = loop {
+            match self.events.recv().await {
+                Ok(event) => break event,
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    // The next snapshot carries whatever was dropped
+                    warn!(skipped, "event push lagged");
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        };
+        let mut batch = vec![first];
+        while let Ok(event) = self.events.try_recv() {
+            batch.push(event);
+        }
+        let params = handlers::events::push_payload(&batch);
+        Some(JsonRpcNotification::new("events.push", params))
+    }
+}
+
+/// Transport choices from the `start` command line. `None` leaves the
+/// `[transports]` config in charge.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TransportOverrides {
+    pub stdio: Option<bool>,
+    pub ws: Option<bool>,
+    pub ws_port: Option<u16>,
+    pub http: Option<bool>,
+    pub http_port: Option<u16>,
+    /// Extra ports to try past a busy one (`--port-scan`).
+    pub port_scan: Option<u16>,
+    /// Pretty-print stdio output (`--pretty`).
+    pub stdio_pretty: Option<bool>,
+    /// stdio message framing (`--framing`).
+    pub stdio_framing: Option<StdioFraming>,
+}
+
+/// The transports the daemon actually starts.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TransportSelection {
+    pub stdio: bool,
+    pub ws_port: Option<u16>,
+    pub http_port: Option<u16>,
+}
+
+impl TransportSelection {
+    /// Combine config and CLI; CLI flags win. Passing a port on the command
+    /// line enables that transport. stdio falls back to on when neither
+    /// side set it and no network transport is enabled.
+    pub fn resolve(config: &TransportsConfig, cli: &TransportOverrides) -> Self {
+        let ws = cli.ws.or(cli.ws_port.map(|_| true)).unwrap_or(config.ws);
+        let http = cli
+            .http
+            .or(cli.http_port.map(|_| true))
+            .unwrap_or(config.http);
+        let stdio = cli.stdio.or(config.stdio).unwrap_or(!ws && !http);
+        Self {
+            stdio,
+            ws_port: ws.then_some(cli.ws_port.unwrap_or(config.ws_port)),
+            http_port: http.then_some(cli.http_port.unwrap_or(config.http_port)),
+        }
+    }
+}
+
+/// Try `port`, then up to `scan` ports after it, returning the first that
+/// `bind` accepts. Only "address in use" moves on to the next port; any other
+/// error is returned right away.
+pub fn bind_first_free<T>(
+    port: u16,
+    scan: u16,
+    mut bind: impl FnMut(u16) -> std::io::Result<T>,
+) -> std::io::Result<(u16, T)> {
+    let last = port.saturating_add(scan);
+    let mut candidate = port;
+    loop {
+        match bind(candidate) {
+            Ok(bound) => return Ok((candidate, bound)),
+            Err(err) if err.kind() == std::io::ErrorKind::AddrInUse && candidate < last => {
+                debug!(port = candidate, "port in use, trying the next one");
+                candidate += 1;
+            }
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Bind a loopback listener for a network transport, scanning past busy
+/// ports as [`bind_first_free`] does. `SO_REUSEADDR` is set so a restarted
+/// daemon gets its port back while the old connections sit in TIME_WAIT.
+/// Must be called inside the runtime.
+pub fn bind_loopback(port: u16, scan: u16) -> std::io::Result<(u16, tokio::net::TcpListener)> {
+    bind_first_free(port, scan, |p| {
+        let socket = tokio::net::TcpSocket::new_v4()?;
+        socket.set_reuseaddr(true)?;
+        socket.bind((std::net::Ipv4Addr::LOCALHOST, p).into())?;
+        socket.listen(1024)
+    })
+}
+
+/// The context transport tests run requests against: default config, tmux
+/// available and nothing pushed.
+#[cfg(test)]
+pub(crate) fn test_context() -> RpcContext {
+    let caps = crate::rpc::Capabilities {
+        ntm: false,
+        tmux: true,
+        stream: false,
+        systemd: false,
+    };
+    RpcContext::with_capabilities(
+        std::sync::Arc::new(crate::cache::Cache::new(100)),
+        crate::config::ConfigManager::default(),
+        caps,
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn rapid_triggers_within_the_window_send_once() {
+        let window = Duration::from_millis(500);
+        let start = Instant::now();
+        let mut coalescer = NotificationCoalescer::new(window);
+
+        // Nothing sent yet, so the first trigger goes out right away
+        assert_eq!(coalescer.trigger(start), Some(start));
+        coalescer.sent(start);
+
+        // A burst inside the window schedules a single send at its end
+        let mut sends = Vec::new();
+        for offset_ms in [50, 120, 300, 480] {
+            let now = start + Duration::from_millis(offset_ms);
+            sends.extend(coalescer.trigger(now));
+        }
+        assert_eq!(sends, vec![start + window]);
+        coalescer.sent(start + window);
+
+        // After a quiet spell longer than the window, triggers send at once
+        let later = start + Duration::from_secs(5);
+        assert_eq!(coalescer.trigger(later), Some(later));
+    }
+
+    #[test]
+    fn zero_window_never_delays() {
+        let start = Instant::now();
+        let mut coalescer = NotificationCoalescer::new(Duration::ZERO);
+        coalescer.trigger(start);
+        coalescer.sent(start);
+        let next = start + Duration::from_millis(1);
+        assert_eq!(coalescer.trigger(next), Some(next));
+    }
+
     #[test]
     fn deserialize_request_with_id() {
         let json = r#"{"jsonrpc":"2.0","method":"health.get","params":{},"id":1}"#;
@@ -171,6 +520,132 @@ mod tests {
         assert!(req.id.is_none());
     }
 
+    #[test]
+    fn deserialize_explicit_null_id_as_request() {
+        let json = r#"{"jsonrpc":"2.0","method":"health.get","id":null}"#;
+        let req: JsonRpcRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(req.id, Some(Value::Null));
+    }
+
+    #[test]
+    fn process_message_echoes_string_id_verbatim() {
+        let ctx = test_context();
+        let text = r#"{"jsonrpc":"2.0","method":"health.get","id":"0042"}"#;
+        let resp = process_message(text, &ctx).unwrap();
+        assert_eq!(resp.id, Value::String("0042".to_string()));
+        assert!(resp.result.is_some());
+    }
+
+    #[test]
+    fn process_message_preserves_numeric_ids() {
+        let ctx = test_context();
+        for id in ["7", "-3", "1.5", "18446744073709551615"] {
+            let text = format!(r#"{{"jsonrpc":"2.0","method":"health.get","id":{id}}}"#);
+            let resp = process_message(&text, &ctx).unwrap();
+            assert_eq!(serde_json::to_string(&resp.id).unwrap(), id);
+        }
+    }
+
+    #[test]
+    fn process_message_notification_has_no_response() {
+        let ctx = test_context();
+        let text = r#"{"jsonrpc":"2.0","method":"health.get","params":{}}"#;
+        assert!(process_message(text, &ctx).is_none());
+        let text = r#"{"jsonrpc":"2.0","method":"no.such.method"}"#;
+        assert!(process_message(text, &ctx).is_none());
+    }
+
+    /// Run `f` with a subscriber that captures WARN and above.
+    fn warnings_during<T>(f: impl FnOnce() -> T) -> (T, String) {
+        use std::io::Write;
+        use std::sync::{Arc, Mutex};
+
+        #[derive(Clone)]
+        struct Capture(Arc<Mutex<Vec<u8>>>);
+
+        impl Write for Capture {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().write(buf)
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let capture = Capture(Arc::new(Mutex::new(Vec::new())));
+        let writer = capture.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::WARN)
+            .with_writer(move || writer.clone())
+            .finish();
+        let result = tracing::subscriber::with_default(subscriber, f);
+        let logged = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+        (result, logged)
+    }
+
+    #[test]
+    fn process_message_ignores_unknown_notification_quietly() {
+        let ctx = test_context();
+        let text = r#"{"jsonrpc":"2.0","method":"no.such.method","params":{"x":1}}"#;
+        let (resp, logged) = warnings_during(|| process_message(text, &ctx));
+        assert!(resp.is_none());
+        assert!(logged.is_empty(), "unexpected log output: {logged}");
+
+        // The capture does see warnings, so the empty log above is meaningful
+        let (_, logged) = warnings_during(|| process_message("not json", &ctx));
+        assert!(logged.contains("failed to parse JSON"));
+    }
+
+    #[test]
+    fn process_message_unknown_request_is_method_not_found() {
+        let ctx = test_context();
+        let text = r#"{"jsonrpc":"2.0","method":"no.such.method","id":5}"#;
+        let resp = process_message(text, &ctx).unwrap();
+        assert_eq!(resp.id, Value::Number(5.into()));
+        let error = resp.error.unwrap();
+        assert_eq!(error.code, JsonRpcError::METHOD_NOT_FOUND);
+        assert_eq!(error.message, "Method not found: no.such.method");
+    }
+
+    #[test]
+    fn process_message_counts_requests_per_method() {
+        let ctx = test_context();
+        let traffic = RpcTraffic::new();
+        let health = r#"{"jsonrpc":"2.0","method":"health.get","id":1}"#;
+        let list = r#"{"jsonrpc":"2.0","method":"sessions.list","id":2}"#;
+        process_message_counted(health, &ctx, &traffic);
+        process_message_counted(health, &ctx, &traffic);
+        process_message_counted(list, &ctx, &traffic);
+        process_message_counted("not json", &ctx, &traffic);
+
+        let totals = traffic.totals();
+        assert_eq!(totals.requests, 4);
+        assert_eq!(totals.bytes_in as usize, 2 * health.len() + list.len() + 8);
+        assert_eq!(totals.methods["health.get"], 2);
+        assert_eq!(totals.methods["sessions.list"], 1);
+    }
+
+    #[test]
+    fn process_message_answers_explicit_null_id() {
+        let ctx = test_context();
+        let text = r#"{"jsonrpc":"2.0","method":"health.get","id":null}"#;
+        let resp = process_message(text, &ctx).unwrap();
+        assert_eq!(resp.id, Value::Null);
+        assert!(resp.result.is_some());
+    }
+
+    #[test]
+    fn process_message_rejects_structured_ids() {
+        let ctx = test_context();
+        for id in ["{}", "[1]", "true"] {
+            let text = format!(r#"{{"jsonrpc":"2.0","method":"health.get","id":{id}}}"#);
+            let resp = process_message(&text, &ctx).unwrap();
+            assert_eq!(resp.id, Value::Null);
+            assert_eq!(resp.error.unwrap().code, JsonRpcError::INVALID_REQUEST);
+        }
+    }
+
     #[test]
     fn serialize_success_response() {
         let resp = JsonRpcResponse::success(Value::Number(1.into()), Value::Bool(true));
@@ -189,4 +664,153 @@ mod tests {
         assert!(json.contains("\"error\""));
         assert!(!json.contains("result"));
     }
+
+    #[test]
+    fn port_scan_skips_a_busy_port() {
+        let busy = |port: u16| {
+            if port == 3847 {
+                Err(std::io::Error::from(std::io::ErrorKind::AddrInUse))
+            } else {
+                Ok(port)
+            }
+        };
+        assert_eq!(bind_first_free(3847, 3, busy).unwrap().0, 3848);
+        // Without a scan the busy port is an error, as before
+        let err = bind_first_free(3847, 0, busy).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse);
+    }
+
+    #[test]
+    fn port_scan_stops_at_other_errors_and_range_end() {
+        let mut tried = Vec::new();
+        let err = bind_first_free(9000, 5, |port| {
+            tried.push(port);
+            Err::<(), _>(std::io::Error::from(std::io::ErrorKind::PermissionDenied))
+        })
+        .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+        assert_eq!(tried, vec![9000]);
+
+        let mut tried = Vec::new();
+        let result = bind_first_free(u16::MAX - 1, 5, |port| {
+            tried.push(port);
+            Err::<(), _>(std::io::Error::from(std::io::ErrorKind::AddrInUse))
+        });
+        assert!(result.is_err());
+        assert_eq!(tried, vec![u16::MAX - 1, u16::MAX]);
+    }
+
+    #[tokio::test]
+    async fn bind_loopback_moves_past_a_real_listener() {
+        let taken = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = taken.local_addr().unwrap().port();
+        if port == u16::MAX {
+            return;
+        }
+        match bind_loopback(port, 1) {
+            Ok((chosen, listener)) => {
+                assert_eq!(chosen, port + 1);
+                assert_eq!(listener.local_addr().unwrap().port(), port + 1);
+            }
+            // The next port happened to be taken by another process
+            Err(err) => assert_eq!(err.kind(), std::io::ErrorKind::AddrInUse),
+        }
+    }
+
+    #[tokio::test]
+    async fn bind_loopback_rebinds_over_time_wait() {
+        let (port, listener) = bind_loopback(0, 0).unwrap();
+        let port = match port {
+            0 => listener.local_addr().unwrap().port(),
+            port => port,
+        };
+        let client = tokio::net::TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        let (server, _) = listener.accept().await.unwrap();
+        // Closing the server side first leaves its end in TIME_WAIT
+        drop(server);
+        drop(listener);
+        drop(client);
+
+        let (chosen, _listener) = bind_loopback(port, 0).expect("port reusable after restart");
+        assert_eq!(chosen, port);
+    }
+
+    #[test]
+    fn transports_default_to_stdio() {
+        let selection = TransportSelection::resolve(
+            &TransportsConfig::default(),
+            &TransportOverrides::default(),
+        );
+        assert_eq!(
+            selection,
+            TransportSelection {
+                stdio: true,
+                ws_port: None,
+                http_port: None,
+            }
+        );
+    }
+
+    #[test]
+    fn cli_disables_transport_enabled_in_config() {
+        let config = TransportsConfig {
+            ws: true,
+            ..TransportsConfig::default()
+        };
+        assert_eq!(
+            TransportSelection::resolve(&config, &TransportOverrides::default()).ws_port,
+            Some(3847)
+        );
+
+        let cli = TransportOverrides {
+            ws: Some(false),
+            ..TransportOverrides::default()
+        };
+        let selection = TransportSelection::resolve(&config, &cli);
+        assert_eq!(selection.ws_port, None);
+        assert!(selection.stdio);
+    }
+
+    #[test]
+    fn cli_enables_transport_disabled_in_config() {
+        let config = TransportsConfig {
+            http: false,
+            http_port: 9000,
+            stdio: Some(false),
+            ..TransportsConfig::default()
+        };
+        let cli = TransportOverrides {
+            http: Some(true),
+            ..TransportOverrides::default()
+        };
+        let selection = TransportSelection::resolve(&config, &cli);
+        assert_eq!(selection.http_port, Some(9000));
+        assert!(!selection.stdio);
+
+        let cli = TransportOverrides {
+            http_port: Some(9100),
+            ..TransportOverrides::default()
+        };
+        assert_eq!(
+            TransportSelection::resolve(&config, &cli).http_port,
+            Some(9100)
+        );
+    }
+
+    #[test]
+    fn network_transport_from_config_turns_off_implicit_stdio() {
+        let config = TransportsConfig {
+            http: true,
+            ..TransportsConfig::default()
+        };
+        let selection = TransportSelection::resolve(&config, &TransportOverrides::default());
+        assert!(!selection.stdio);
+        assert_eq!(selection.http_port, Some(3848));
+
+        let cli = TransportOverrides {
+            stdio: Some(true),
+            ..TransportOverrides::default()
+        };
+        assert!(TransportSelection::resolve(&config, &cli).stdio);
+    }
 }
@@ -1,144 +1,253 @@
-//! stdio transport adapter using newline-delimited JSON-RPC.
+//! stdio transport adapter for JSON-RPC over stdin/stdout.
 //!
 //! This is the default transport when the daemon is spawned via `wsl.exe`.
-//! It provides full duplex communication over stdin/stdout.
+//! It provides full duplex communication over stdin/stdout. Messages are
+//! newline-delimited by default; [`StdioFraming::ContentLength`] switches
+//! both directions to LSP-style `Content-Length` headers.
 
+pub use crate::config::StdioFraming;
 use crate::rpc::{self, RpcContext};
-use crate::transport::{JsonRpcError, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse};
+use crate::transport::{self, JsonRpcNotification, JsonRpcResponse};
 use crate::metrics::{Timer, METRICS};
-use serde_json::Value;
+use crate::service::ShutdownReason;
+use serde::Serialize;
+use std::io;
 use std::sync::Arc;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
 use tokio::sync::mpsc;
-use tracing::{debug, error, info, trace, warn};
+use tracing::{error, info, trace};
+
+/// Largest `Content-Length` body accepted; anything bigger is treated as a
+/// corrupt stream rather than allocated.
+const MAX_FRAME_BYTES: usize = 16 * 1024 * 1024;
+
+/// How the stdio transport reads and writes messages.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StdioOptions {
+    /// Indent output for humans (`--pretty`). With newline framing messages
+    /// then span several lines, so line-reading clients should leave this off.
+    pub pretty: bool,
+    /// Message delimiting, the same for input and output (`--framing`).
+    pub framing: StdioFraming,
+}
 
 /// Run the stdio transport, processing requests from stdin and writing responses to stdout.
 ///
-/// This function runs until stdin is closed or a fatal error occurs.
-pub async fn run(ctx: Arc<RpcContext>, mut notification_rx: mpsc::Receiver<JsonRpcNotification>) {
-    let stdin = tokio::io::stdin();
-    let stdout = tokio::io::stdout();
-
-    let reader = BufReader::new(stdin);
-    let mut lines = reader.lines();
-    let mut stdout = stdout;
+/// This function runs until stdin is closed or a fatal error occurs, and
+/// returns which of the two ended it.
+pub async fn run(
+    ctx: Arc<RpcContext>,
+    mut notification_rx: mpsc::Receiver<JsonRpcNotification>,
+    options: StdioOptions,
+) -> ShutdownReason {
+    let mut stdout = tokio::io::stdout();
+
+    // Reading a Content-Length frame takes several awaits and is not cancel
+    // safe, so frames are read on their own task and handed over a channel.
+    let (frame_tx, mut frame_rx) = mpsc::channel::<io::Result<Option<String>>>(16);
+    let framing = options.framing;
+    tokio::spawn(async move {
+        let mut reader = BufReader::new(tokio::io::stdin());
+        loop {
+            let frame = read_frame(&mut reader, framing).await;
+            let done = !matches!(frame, Ok(Some(_)));
+            if frame_tx.send(frame).await.is_err() || done {
+                break;
+            }
+        }
+    });
 
-    info!("stdio transport started");
+    info!(framing = ?options.framing, "stdio transport started");
 
     let hello = JsonRpcNotification::new("core.hello", rpc::hello_payload(ctx.as_ref()));
-    if let Err(e) = write_notification(&mut stdout, &hello).await {
+    if let Err(e) = write_notification(&mut stdout, &hello, options).await {
         error!(error = %e, "failed to write hello notification");
-        return;
+        return ShutdownReason::Fatal(format!("stdout write failed: {e}"));
     }
 
-    loop {
+    let reason = loop {
         tokio::select! {
             // Handle incoming requests from stdin
-            line_result = lines.next_line() => {
-                match line_result {
-                    Ok(Some(line)) => {
-                        if line.trim().is_empty() {
+            frame = frame_rx.recv() => {
+                match frame {
+                    Some(Ok(Some(message))) => {
+                        if message.trim().is_empty() {
                             continue;
                         }
-                        trace!(line = %line, "received request");
-                        if let Some(response) = process_line(&line, &ctx) {
-                            if let Err(e) = write_response(&mut stdout, &response).await {
+                        trace!(message = %message, "received request");
+                        if let Some(response) = process_line(&message, &ctx) {
+                            if let Err(e) = write_response(&mut stdout, &response, options).await {
                                 error!(error = %e, "failed to write response");
-                                break;
+                                break ShutdownReason::Fatal(format!("stdout write failed: {e}"));
                             }
                         }
                     }
-                    Ok(None) => {
+                    Some(Ok(None)) | None => {
                         info!("stdin closed, shutting down");
-                        break;
+                        break ShutdownReason::StdinClosed;
                     }
-                    Err(e) => {
+                    Some(Err(e)) => {
                         error!(error = %e, "error reading stdin");
-                        break;
+                        break ShutdownReason::Fatal(format!("stdin read failed: {e}"));
                     }
                 }
             }
             // Handle outgoing notifications
             Some(notification) = notification_rx.recv() => {
-                if let Err(e) = write_notification(&mut stdout, &notification).await {
+                if let Err(e) = write_notification(&mut stdout, &notification, options).await {
                     error!(error = %e, "failed to write notification");
-                    break;
+                    break ShutdownReason::Fatal(format!("stdout write failed: {e}"));
                 }
             }
         }
-    }
+    };
 
     info!("stdio transport stopped");
+    reason
 }
 
-/// Process a single line of input and return a response if needed.
+/// Process a single message and return a response if needed.
 fn process_line(line: &str, ctx: &RpcContext) -> Option<JsonRpcResponse> {
-    // Parse JSON
-    let request: JsonRpcRequest = match serde_json::from_str(line) {
-        Ok(req) => req,
-        Err(e) => {
-            warn!(error = %e, "failed to parse JSON");
-            return Some(JsonRpcResponse::error(
-                Value::Null,
-                JsonRpcError::parse_error(format!("Invalid JSON: {e}")),
-            ));
+    let _timer = Timer::new(&METRICS.rpc_request);
+    transport::process_message(line, ctx)
+}
+
+/// Read one message, or `None` once the input ends between messages.
+async fn read_frame<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+    framing: StdioFraming,
+) -> io::Result<Option<String>> {
+    match framing {
+        StdioFraming::Newline => {
+            let mut line = String::new();
+            if reader.read_line(&mut line).await? == 0 {
+                return Ok(None);
+            }
+            let trimmed = line.trim_end_matches(['\r', '\n']).len();
+            line.truncate(trimmed);
+            Ok(Some(line))
         }
-    };
+        StdioFraming::ContentLength => {
+            let Some(length) = read_content_length(reader).await? else {
+                return Ok(None);
+            };
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).await?;
+            String::from_utf8(body)
+                .map(Some)
+                .map_err(|err| invalid_frame(format!("body is not UTF-8: {err}")))
+        }
+    }
+}
 
-    // Validate JSON-RPC version
-    if request.jsonrpc != "2.0" {
-        warn!(version = %request.jsonrpc, "invalid JSON-RPC version");
-        return Some(JsonRpcResponse::error(
-            request.id.unwrap_or(Value::Null),
-            JsonRpcError::invalid_request("Expected jsonrpc: \"2.0\""),
-        ));
-    }
-
-    // If no id, this is a notification - no response expected
-    let id = match request.id {
-        Some(id) => id,
-        None => {
-            debug!(method = %request.method, "received notification (no response)");
-            // Process notification but don't respond
-            let _timer = Timer::new(&METRICS.rpc_request);
-            let _ = rpc::handle(&request.method, request.params, ctx);
-            return None;
+/// Read a header block up to its blank line and return the body length.
+/// Headers other than `Content-Length` (such as `Content-Type`) are ignored.
+async fn read_content_length<R: AsyncBufRead + Unpin>(
+    reader: &mut R,
+) -> io::Result<Option<usize>> {
+    let mut length = None;
+    let mut seen_header = false;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).await? == 0 {
+            if seen_header {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "stdin closed inside a frame header",
+                ));
+            }
+            return Ok(None);
         }
-    };
+        let header = header.trim_end_matches(['\r', '\n']);
+        if header.is_empty() {
+            match length {
+                Some(length) => return Ok(Some(length)),
+                // Stray blank line between frames
+                None if !seen_header => continue,
+                None => return Err(invalid_frame("header block has no Content-Length")),
+            }
+        }
+        seen_header = true;
+        let Some((name, value)) = header.split_once(':') else {
+            return Err(invalid_frame(format!("malformed header {header:?}")));
+        };
+        if name.trim().eq_ignore_ascii_case("content-length") {
+            let parsed = value
+                .trim()
+                .parse::<usize>()
+                .map_err(|_| invalid_frame(format!("bad Content-Length {:?}", value.trim())))?;
+            if parsed > MAX_FRAME_BYTES {
+                return Err(invalid_frame(format!(
+                    "Content-Length {parsed} exceeds {MAX_FRAME_BYTES} bytes"
+                )));
+            }
+            length = Some(parsed);
+        }
+    }
+}
 
-    // Handle the request
-    debug!(method = %request.method, "handling request");
-    let _timer = Timer::new(&METRICS.rpc_request);
-    let result = rpc::handle(&request.method, request.params, ctx);
+fn invalid_frame(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
 
-    Some(match result {
-        Ok(value) => JsonRpcResponse::success(id, value),
-        Err(e) => JsonRpcResponse::error(id, JsonRpcError::from_rpc_error(&e)),
-    })
+/// Write one serialized message in the given framing and flush it.
+/// Returns the bytes written, including framing.
+async fn write_frame<W: AsyncWrite + Unpin>(
+    writer: &mut W,
+    json: &str,
+    framing: StdioFraming,
+) -> io::Result<usize> {
+    let mut written = json.len();
+    match framing {
+        StdioFraming::Newline => {
+            writer.write_all(json.as_bytes()).await?;
+            writer.write_all(b"\n").await?;
+            written += 1;
+        }
+        StdioFraming::ContentLength => {
+            let header = format!("Content-Length: {}\r\n\r\n", json.len());
+            writer.write_all(header.as_bytes()).await?;
+            writer.write_all(json.as_bytes()).await?;
+            written += header.len();
+        }
+    }
+    writer.flush().await?;
+    Ok(written)
 }
 
-/// Write a response to stdout as newline-delimited JSON.
+/// Serialize a message, compact unless `options.pretty`.
+fn encode<T: Serialize>(message: &T, options: StdioOptions) -> serde_json::Result<String> {
+    if options.pretty {
+        serde_json::to_string_pretty(message)
+    } else {
+        serde_json::to_string(message)
+    }
+}
+
+/// Write a response to stdout.
 async fn write_response(
     stdout: &mut tokio::io::Stdout,
     response: &JsonRpcResponse,
-) -> std::io::Result<()> {
-    let json = serde_json::to_string(response)?;
+    options: StdioOptions,
+) -> io::Result<()> {
+    let json = encode(response, options)?;
     trace!(response = %json, "sending response");
-    stdout.write_all(json.as_bytes()).await?;
-    stdout.write_all(b"\n").await?;
-    stdout.flush().await
+    let written = write_frame(stdout, &json, options.framing).await?;
+    METRICS.rpc_traffic.record_bytes_out(written);
+    Ok(())
 }
 
-/// Write a notification to stdout as newline-delimited JSON.
+/// Write a notification to stdout.
 async fn write_notification(
     stdout: &mut tokio::io::Stdout,
     notification: &JsonRpcNotification,
-) -> std::io::Result<()> {
-    let json = serde_json::to_string(notification)?;
+    options: StdioOptions,
+) -> io::Result<()> {
+    let json = encode(notification, options)?;
     trace!(notification = %json, "sending notification");
-    stdout.write_all(json.as_bytes()).await?;
-    stdout.write_all(b"\n").await?;
-    stdout.flush().await
+    let written = write_frame(stdout, &json, options.framing).await?;
+    METRICS.rpc_traffic.record_bytes_out(written);
+    Ok(())
 }
 
 /// Create a notification sender that can be used to push events to the client.
@@ -149,22 +258,8 @@ pub fn notification_channel() -> (mpsc::Sender<JsonRpcNotification>, mpsc::Recei
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::cache::Cache;
-    use crate::config::ConfigManager;
-    use crate::rpc::Capabilities;
-    use std::sync::Arc;
-
-    fn test_context() -> RpcContext {
-        let cache = Arc::new(Cache::new(100));
-        let config = ConfigManager::default();
-        let caps = Capabilities {
-            ntm: false,
-            tmux: true,
-            stream: false,
-            systemd: false,
-        };
-        RpcContext::with_capabilities(cache, config, caps)
-    }
+    use crate::transport::{test_context, JsonRpcError};
+    use serde_json::Value;
 
     #[test]
     fn process_valid_request() {
@@ -215,7 +310,136 @@ mod tests {
         let response = process_line(line, &ctx);
         assert!(response.is_some());
         let resp = response.unwrap();
-        assert!(resp.error.is_some());
+        assert_eq!(resp.error.unwrap().code, JsonRpcError::METHOD_NOT_FOUND);
+    }
+
+    #[test]
+    fn pretty_mode_spans_lines_and_default_is_one_line() {
+        let ctx = test_context();
+        let line = r#"{"jsonrpc":"2.0","method":"health.get","params":{},"id":1}"#;
+        let response = process_line(line, &ctx).unwrap();
+        let notification = JsonRpcNotification::new("core.hello", serde_json::json!({"label": "x"}));
+
+        let compact = encode(&response, StdioOptions::default()).unwrap();
+        assert!(!compact.contains('\n'), "{compact}");
+        assert!(!encode(&notification, StdioOptions::default()).unwrap().contains('\n'));
+
+        let pretty = StdioOptions {
+            pretty: true,
+            ..StdioOptions::default()
+        };
+        let indented = encode(&response, pretty).unwrap();
+        assert!(indented.lines().count() > 1, "{indented}");
+        assert!(indented.contains("\n  \"jsonrpc\": \"2.0\""), "{indented}");
+        assert!(encode(&notification, pretty).unwrap().lines().count() > 1);
+
+        // Same message either way
+        let a: Value = serde_json::from_str(&compact).unwrap();
+        let b: Value = serde_json::from_str(&indented).unwrap();
+        assert_eq!(a, b);
+    }
+
+    async fn round_trip(framing: StdioFraming, options: StdioOptions) -> Vec<Value> {
+        let ctx = test_context();
+        let request = r#"{"jsonrpc":"2.0","method":"health.get","params":{},"id":7}"#;
+        let notification =
+            JsonRpcNotification::new("pane.output", serde_json::json!({"lines": ["a\nb"]}));
+        let options = StdioOptions { framing, ..options };
+
+        // Client -> daemon
+        let mut wire = Vec::new();
+        write_frame(&mut wire, request, framing).await.unwrap();
+        let mut reader = BufReader::new(wire.as_slice());
+        let received = read_frame(&mut reader, framing).await.unwrap().unwrap();
+        assert_eq!(received, request);
+        assert_eq!(read_frame(&mut reader, framing).await.unwrap(), None);
+
+        // Daemon -> client
+        let response = process_line(&received, &ctx).unwrap();
+        let mut wire = Vec::new();
+        write_frame(&mut wire, &encode(&response, options).unwrap(), framing)
+            .await
+            .unwrap();
+        write_frame(&mut wire, &encode(&notification, options).unwrap(), framing)
+            .await
+            .unwrap();
+        let mut reader = BufReader::new(wire.as_slice());
+        let mut messages = Vec::new();
+        while let Some(frame) = read_frame(&mut reader, framing).await.unwrap() {
+            messages.push(serde_json::from_str(&frame).unwrap());
+        }
+        messages
+    }
+
+    #[tokio::test]
+    async fn newline_framing_round_trips() {
+        let messages = round_trip(StdioFraming::Newline, StdioOptions::default()).await;
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0]["id"], 7);
+        assert!(messages[0]["result"].is_object());
+        assert_eq!(messages[1]["params"]["lines"][0], "a\nb");
+    }
+
+    #[tokio::test]
+    async fn content_length_framing_round_trips_pretty_output() {
+        let pretty = StdioOptions {
+            pretty: true,
+            ..StdioOptions::default()
+        };
+        let messages = round_trip(StdioFraming::ContentLength, pretty).await;
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0]["id"], 7);
+        assert!(messages[0]["result"].is_object());
+        assert_eq!(messages[1]["method"], "pane.output");
+        assert_eq!(messages[1]["params"]["lines"][0], "a\nb");
+    }
+
+    #[tokio::test]
+    async fn content_length_frame_layout() {
+        let mut wire = Vec::new();
+        let written = write_frame(&mut wire, "{\"a\":1}", StdioFraming::ContentLength)
+            .await
+            .unwrap();
+        assert_eq!(wire, b"Content-Length: 7\r\n\r\n{\"a\":1}");
+        assert_eq!(written, wire.len());
+
+        // Extra headers and bare \n line endings are accepted
+        let input = b"Content-Type: application/json\nContent-Length: 2\n\n{}";
+        let mut reader = BufReader::new(&input[..]);
+        let frame = read_frame(&mut reader, StdioFraming::ContentLength).await.unwrap();
+        assert_eq!(frame.as_deref(), Some("{}"));
+    }
+
+    #[tokio::test]
+    async fn content_length_rejects_broken_frames() {
+        for input in [
+            &b"Content-Type: x\r\n\r\n{}"[..],
+            b"Content-Length: abc\r\n\r\n",
+            b"not a header\r\n\r\n",
+            b"Content-Length: 99999999999\r\n\r\n",
+        ] {
+            let mut reader = BufReader::new(input);
+            let err = read_frame(&mut reader, StdioFraming::ContentLength)
+                .await
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData, "{input:?}");
+        }
+
+        // Truncated body or header
+        for input in [&b"Content-Length: 10\r\n\r\n{}"[..], b"Content-Length: 2\r\n"] {
+            let mut reader = BufReader::new(input);
+            let err = read_frame(&mut reader, StdioFraming::ContentLength)
+                .await
+                .unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof, "{input:?}");
+        }
+    }
+
+    #[test]
+    fn unknown_notification_no_response() {
+        let ctx = test_context();
+        let line = r#"{"jsonrpc":"2.0","method":"unknown.method","params":{}}"#;
+        assert!(process_line(line, &ctx).is_none());
     }
 
     // --- New tests for bd-31f7 ---
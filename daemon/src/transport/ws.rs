@@ -3,10 +3,10 @@
 //! This is an optional transport for clients that prefer WebSocket over stdio.
 //! It supports full duplex communication with push notifications.
 
+use crate::metrics::METRICS;
 use crate::rpc::{self, RpcContext};
-use crate::transport::{JsonRpcError, JsonRpcNotification, JsonRpcRequest, JsonRpcResponse};
+use crate::transport::{self, EventPushFeed, JsonRpcNotification, JsonRpcResponse, PaneTailFeed};
 use futures_util::{SinkExt, StreamExt};
-use serde_json::Value;
 use std::collections::HashMap;
 use std::net::SocketAddr;
 use std::sync::Arc;
@@ -78,7 +78,16 @@ impl WsServer {
                 return;
             }
         };
+        self.serve(listener, ctx).await;
+    }
 
+    /// Serve on a listener the caller already bound (see
+    /// [`crate::transport::bind_loopback`]).
+    pub async fn serve(self, listener: TcpListener, ctx: Arc<RpcContext>) {
+        let addr = listener
+            .local_addr()
+            .map(|addr| addr.to_string())
+            .unwrap_or_default();
         info!(addr = %addr, "WebSocket server listening");
 
         let server = Arc::new(self);
@@ -226,6 +235,7 @@ impl WsServer {
             loop {
                 tokio::select! {
                     Some(msg) = rx.recv() => {
+                        METRICS.rpc_traffic.record_bytes_out(msg.len());
                         if let Err(e) = write.send(Message::text(msg)).await {
                             debug!(error = %e, "failed to send message");
                             break;
@@ -236,9 +246,11 @@ impl WsServer {
             }
         });
 
-        // Create a context with admin status
-        let mut client_ctx = (*ctx).clone();
+        // Create a context with admin status and this connection's pane tails
+        let mut client_ctx = ctx.with_pane_tails();
         client_ctx.is_admin = is_admin;
+        let mut tail_feed = PaneTailFeed::new(&client_ctx.bus);
+        let mut event_feed = EventPushFeed::new(&client_ctx.bus);
 
         // Send hello notification immediately after connect for version/capability handshake.
         let hello = JsonRpcNotification::new("core.hello", rpc::hello_payload(&client_ctx));
@@ -247,7 +259,7 @@ impl WsServer {
         }
 
         // Process incoming messages and outgoing notifications concurrently
-        loop {
+        'connection: loop {
             tokio::select! {
                 msg = read.next() => {
                     match msg {
@@ -281,6 +293,20 @@ impl WsServer {
                         }
                     }
                 }
+                Some(notifications) = tail_feed.next(&client_ctx) => {
+                    for notification in notifications {
+                        let json = serde_json::to_string(&notification)?;
+                        if tx.send(json).await.is_err() {
+                            break 'connection;
+                        }
+                    }
+                }
+                Some(notification) = event_feed.next() => {
+                    let json = serde_json::to_string(&notification)?;
+                    if tx.send(json).await.is_err() {
+                        break;
+                    }
+                }
                 notification = notification_rx.recv() => {
                     match notification {
                         Ok(notification) => {
@@ -315,45 +341,7 @@ impl WsServer {
     }
 
     fn process_message(&self, text: &str, ctx: &RpcContext) -> Option<JsonRpcResponse> {
-        // Parse JSON
-        let request: JsonRpcRequest = match serde_json::from_str(text) {
-            Ok(req) => req,
-            Err(e) => {
-                warn!(error = %e, "failed to parse JSON");
-                return Some(JsonRpcResponse::error(
-                    Value::Null,
-                    JsonRpcError::parse_error(format!("Invalid JSON: {e}")),
-                ));
-            }
-        };
-
-        // Validate JSON-RPC version
-        if request.jsonrpc != "2.0" {
-            warn!(version = %request.jsonrpc, "invalid JSON-RPC version");
-            return Some(JsonRpcResponse::error(
-                request.id.unwrap_or(Value::Null),
-                JsonRpcError::invalid_request("Expected jsonrpc: \"2.0\""),
-            ));
-        }
-
-        // If no id, this is a notification - no response expected
-        let id = match request.id {
-            Some(id) => id,
-            None => {
-                debug!(method = %request.method, "received notification (no response)");
-                let _ = rpc::handle(&request.method, request.params, ctx);
-                return None;
-            }
-        };
-
-        // Handle the request
-        debug!(method = %request.method, "handling request");
-        let result = rpc::handle(&request.method, request.params, ctx);
-
-        Some(match result {
-            Ok(value) => JsonRpcResponse::success(id, value),
-            Err(e) => JsonRpcResponse::error(id, JsonRpcError::from_rpc_error(&e)),
-        })
+        transport::process_message(text, ctx)
     }
 
     /// Authenticate a credential and return whether it's an admin credential.
@@ -380,6 +368,21 @@ pub fn notification_channel() -> (broadcast::Sender<JsonRpcNotification>, broadc
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::transport::test_context;
+
+    #[test]
+    fn process_message_echoes_ids_and_skips_notifications() {
+        let server = WsServer::new(WsConfig::default());
+        let ctx = test_context();
+        let text = r#"{"jsonrpc":"2.0","method":"health.get","id":"ws-7"}"#;
+        let resp = server.process_message(text, &ctx).unwrap();
+        assert_eq!(resp.id, serde_json::json!("ws-7"));
+        let text = r#"{"jsonrpc":"2.0","method":"health.get","id":7}"#;
+        let resp = server.process_message(text, &ctx).unwrap();
+        assert_eq!(resp.id, serde_json::json!(7));
+        let text = r#"{"jsonrpc":"2.0","method":"health.get"}"#;
+        assert!(server.process_message(text, &ctx).is_none());
+    }
 
     #[test]
     fn default_config() {
@@ -3,7 +3,7 @@
 //! These tests verify end-to-end behavior with mocked ntm/tmux outputs.
 //! Run with: cargo test --test integration
 
-use ntm_tracker_daemon::cache::{Cache, EventRecord, HealthStatus, StatsAggregate};
+use ntm_tracker_daemon::cache::{Cache, EventRecord, HealthStatus, PollingDatum, StatsAggregate};
 use ntm_tracker_daemon::config::ConfigManager;
 use ntm_tracker_daemon::models::pane::{Pane, PaneStatus};
 use ntm_tracker_daemon::models::session::{Session, SessionStatus};
@@ -54,6 +54,7 @@ fn test_context_with_data() -> RpcContext {
     cache.set_health(HealthStatus {
         status: "ok".to_string(),
         last_error: None,
+        degraded_reason: None,
     });
 
     // Set stats
@@ -77,6 +78,7 @@ fn health_get_returns_ok_status() {
     ctx.cache.set_health(HealthStatus {
         status: "ok".to_string(),
         last_error: None,
+        degraded_reason: None,
     });
 
     let result = handle("health.get", json!(null), &ctx);
@@ -95,6 +97,7 @@ fn health_get_reports_degraded_status() {
     ctx.cache.set_health(HealthStatus {
         status: "degraded".to_string(),
         last_error: Some("Connection timeout".to_string()),
+        degraded_reason: None,
     });
 
     let result = handle("health.get", json!(null), &ctx);
@@ -218,6 +221,57 @@ fn panes_get_returns_pane_by_id() {
     assert!(response["pane"]["status"].is_string());
 }
 
+#[test]
+fn panes_list_prints_panes_for_seeded_session() {
+    let ctx = test_context_with_data();
+    let session_id = ctx.cache.all_sessions()[0].session_uid.clone();
+
+    let result = handle("panes.list", json!({"sessionId": session_id}), &ctx).unwrap();
+    let panes = result["panes"].as_array().unwrap();
+    assert_eq!(panes.len(), 2);
+
+    let table = ntm_tracker_daemon::cli::format_panes_table(panes);
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines.len(), 3, "header plus one row per pane");
+    assert!(lines[1].contains("%0") && lines[1].contains("active") && lines[1].contains("claude"));
+    assert!(lines[2].contains("%1") && lines[2].contains("waiting") && lines[2].contains("bash"));
+}
+
+#[test]
+fn status_polling_prints_each_channel() {
+    let ctx = test_context();
+    ctx.cache.update_polling_snapshot(PollingDatum {
+        interval_ms: 2000,
+        mode: "active".to_string(),
+        reason: "recent_activity".to_string(),
+        last_change_at: 1000,
+    });
+    ctx.cache.update_polling_ntm(PollingDatum {
+        interval_ms: 10000,
+        mode: "degraded".to_string(),
+        reason: "poll_errors".to_string(),
+        last_change_at: 1000,
+    });
+
+    let result = handle("health.get", json!({}), &ctx).unwrap();
+    let output = ntm_tracker_daemon::cli::format_polling_state(&result["polling"]);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].contains("snapshot") && lines[0].contains("mode=active"));
+    assert!(lines[0].contains("interval=2000ms") && lines[0].contains("reason=recent_activity"));
+    assert!(lines[1].contains("tmux") && lines[1].contains("mode=-"));
+    assert!(lines[2].contains("ntm") && lines[2].contains("mode=degraded"));
+    assert!(lines[2].contains("interval=10000ms") && lines[2].contains("reason=poll_errors"));
+}
+
+#[test]
+fn panes_list_unknown_session_returns_not_found() {
+    let ctx = test_context_with_data();
+
+    let result = handle("panes.list", json!({"sessionId": "nonexistent"}), &ctx);
+    assert_eq!(result.unwrap_err().code, "NOT_FOUND");
+}
+
 #[test]
 fn panes_get_returns_not_found() {
     let ctx = test_context();
@@ -276,6 +330,21 @@ fn stats_summary_returns_aggregates() {
     assert_eq!(summary["estimatedTokens"], 50000);
 }
 
+#[test]
+fn stats_summary_prints_seeded_numbers() {
+    let ctx = test_context_with_data();
+
+    let result = handle("stats.summary", json!(null), &ctx).unwrap();
+    let output = ntm_tracker_daemon::cli::format_stats_summary(&result["summary"]);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 5);
+    assert!(lines[0].starts_with("Sessions") && lines[0].ends_with(" 1"));
+    assert!(lines[1].starts_with("Panes") && lines[1].ends_with(" 2"));
+    assert!(lines[2].starts_with("Compacts today") && lines[2].ends_with(" 5"));
+    assert!(lines[3].starts_with("Active minutes") && lines[3].ends_with(" 120"));
+    assert!(lines[4].starts_with("Est. tokens") && lines[4].ends_with(" 50000"));
+}
+
 // ============================================================================
 // Snapshot Tests
 // ============================================================================
@@ -292,6 +361,9 @@ fn snapshot_get_returns_full_state() {
     assert!(response["panes"].is_array());
     assert!(response["events"].is_array());
     assert!(response["stats"].is_object());
+    for channel in ["snapshot", "tmux", "ntm"] {
+        assert!(response["polling"][channel].is_object(), "missing polling.{channel}");
+    }
 
     let sessions = response["sessions"].as_array().unwrap();
     assert_eq!(sessions.len(), 1);
@@ -582,6 +654,7 @@ fn populate_large_dataset(cache: &Cache, num_sessions: usize, panes_per_session:
     cache.set_health(HealthStatus {
         status: "ok".to_string(),
         last_error: None,
+        degraded_reason: None,
     });
 
     cache.set_stats_today(StatsAggregate {
@@ -623,13 +696,17 @@ fn large_dataset_events_list_all() {
     let ctx = RpcContext::new(cache, config);
 
     let start = std::time::Instant::now();
-    let result = handle("events.list", json!(null), &ctx).unwrap();
+    let result = handle("events.list", json!({"limit": 1000}), &ctx).unwrap();
     let elapsed = start.elapsed();
 
     let events = result["events"].as_array().unwrap();
     // Ring buffer is capped at cache capacity (2000), all 1000 fit
     assert_eq!(events.len(), 1000);
 
+    // Omitting the limit falls back to rpc.default-event-limit
+    let result = handle("events.list", json!(null), &ctx).unwrap();
+    assert_eq!(result["events"].as_array().unwrap().len(), 100);
+
     assert!(
         elapsed.as_millis() < 1000,
         "events.list should complete within 1s, took {}ms",
@@ -842,6 +919,7 @@ fn rpc_works_with_degraded_health() {
     ctx.cache.set_health(HealthStatus {
         status: "degraded".to_string(),
         last_error: Some("tmux: command not found".to_string()),
+        degraded_reason: None,
     });
 
     // All RPC methods should still work
@@ -868,6 +946,7 @@ fn health_reflects_recovery() {
     ctx.cache.set_health(HealthStatus {
         status: "degraded".to_string(),
         last_error: Some("connection timeout".to_string()),
+        degraded_reason: None,
     });
     let h1 = handle("health.get", json!(null), &ctx).unwrap();
     assert_eq!(h1["status"], "degraded");
@@ -876,6 +955,7 @@ fn health_reflects_recovery() {
     ctx.cache.set_health(HealthStatus {
         status: "ok".to_string(),
         last_error: None,
+        degraded_reason: None,
     });
     let h2 = handle("health.get", json!(null), &ctx).unwrap();
     assert_eq!(h2["status"], "ok");
@@ -889,6 +969,7 @@ fn snapshot_serves_stale_data_during_failure() {
     ctx.cache.set_health(HealthStatus {
         status: "degraded".to_string(),
         last_error: Some("ntm: unavailable".to_string()),
+        degraded_reason: None,
     });
 
     // Snapshot should still return the previously-cached data
@@ -905,6 +986,7 @@ fn events_list_works_during_degraded() {
     ctx.cache.set_health(HealthStatus {
         status: "degraded".to_string(),
         last_error: Some("tmux timeout".to_string()),
+        degraded_reason: None,
     });
 
     let events = handle("events.list", json!(null), &ctx).unwrap();
@@ -918,6 +1000,7 @@ fn capabilities_get_during_degraded() {
     ctx.cache.set_health(HealthStatus {
         status: "degraded".to_string(),
         last_error: Some("all collectors down".to_string()),
+        degraded_reason: None,
     });
 
     let caps = handle("capabilities.get", json!(null), &ctx).unwrap();
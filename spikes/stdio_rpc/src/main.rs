@@ -66,7 +66,11 @@ async fn run_daemon() -> Result<()> {
             Err(_) => continue,
         };
         let method = value.get("method").and_then(Value::as_str).unwrap_or("");
-        let id = value.get("id").and_then(Value::as_u64);
+        // Echo string and numeric ids verbatim; anything else is unanswerable
+        let id = value
+            .get("id")
+            .filter(|id| id.is_string() || id.is_number() || id.is_null())
+            .cloned();
         let params = value.get("params").cloned().unwrap_or(Value::Null);
 
         match method {
@@ -1,10 +1,14 @@
-use crate::msg::{ConfirmAction, ConnState, EventFilter, FocusArea, Msg, Tab, ToastLevel};
-use crate::rpc::types::{EventView, PaneView, SessionView, StatsSummary};
+use crate::msg::{
+    ConfirmAction, ConnState, EventFilter, FocusArea, Msg, RpcErrorDetail, Severity, Tab, ToastLevel,
+};
+use crate::rpc::types::{
+    EventDetail, EventView, PaneView, PollingState, SessionView, StatsSummary,
+};
 use crate::screens;
 use crate::theme;
 use crate::widgets::{
-    command_palette_wrapper, connection_bar, escalation_inbox, event_timeline, pane_table,
-    session_list, toast_manager,
+    command_palette_wrapper, connection_bar, escalation_inbox, event_timeline, pane_output,
+    pane_table, session_list, toast_manager,
 };
 use ftui::core::geometry::Rect;
 use ftui::{Event, KeyCode, KeyEvent, KeyEventKind, Modifiers};
@@ -21,6 +25,9 @@ use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tracing::warn;
 
+/// Default `snapshot.get` polling interval for daemons that don't push.
+pub const DEFAULT_SNAPSHOT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
 /// Main application state.
 pub struct NtmApp {
     // Navigation
@@ -28,6 +35,10 @@ pub struct NtmApp {
     pub focus: FocusArea,
     pub show_help: bool,
     pub help_scroll: u16,
+    pub show_error_detail: bool,
+    /// Event shown by the detail overlay (`Enter` on an event), once fetched.
+    pub event_detail: Option<EventDetail>,
+    pub event_detail_scroll: u16,
 
     // Data
     pub sessions: Vec<SessionView>,
@@ -35,16 +46,25 @@ pub struct NtmApp {
     pub events: Vec<EventView>,
     pub stats: StatsSummary,
     pub last_event_id: i64,
+    pub polling: PollingState,
+    /// Unix time the last snapshot arrived.
+    pub last_snapshot_at: Option<i64>,
 
     // Connection
     pub conn_state: ConnState,
     pub daemon_version: String,
+    /// Instance label from `core.hello`; may be empty.
+    pub daemon_label: String,
+    /// Most recent RPC failure, shown in full by the `E` overlay.
+    pub last_error: Option<RpcErrorDetail>,
 
     // Animation
     pub spinner_frame: usize,
 
     // Filters
     pub event_filter: EventFilter,
+    /// New events at or above this severity raise a toast.
+    pub toast_min_severity: Severity,
 
     // Confirmation modal
     pub pending_confirm: Option<ConfirmAction>,
@@ -55,6 +75,9 @@ pub struct NtmApp {
     // Tracks which session is selected (for smart pane reset)
     pub selected_session_id: Option<String>,
 
+    // Pane streamed with panes.tail (follow mode); Esc stops it
+    pub follow: Option<pane_output::PaneFollowState>,
+
     // Widget states (RefCell for interior mutability in view())
     pub session_list_state: RefCell<session_list::SessionListState>,
     pub pane_table_state: RefCell<pane_table::PaneTableState>,
@@ -66,6 +89,21 @@ pub struct NtmApp {
     // RPC write channel for fire-and-forget notifications
     pub rpc_tx: Option<tokio::sync::mpsc::Sender<String>>,
 
+    // Asks the RPC client for a fresh snapshot.get (manual refresh)
+    pub snapshot_request_tx: Option<tokio::sync::mpsc::UnboundedSender<()>>,
+
+    // How often to poll snapshot.get when the daemon doesn't push
+    pub snapshot_poll_interval: Duration,
+    // Set from core.hello: the daemon lacks push, so the poll timer runs
+    pub snapshot_polling: bool,
+
+    // Asks the RPC client for events.get on an event id (detail overlay)
+    pub event_detail_request_tx: Option<tokio::sync::mpsc::UnboundedSender<i64>>,
+
+    // Asks the RPC client for panes.tail on a pane id (follow mode); a
+    // failure comes back as Msg::FollowFailed
+    pub follow_request_tx: Option<tokio::sync::mpsc::UnboundedSender<String>>,
+
     // Daemon message bridge (subscription drains this into the update loop)
     daemon_rx: Arc<Mutex<tokio::sync::mpsc::UnboundedReceiver<Msg>>>,
 }
@@ -87,21 +125,30 @@ impl NtmApp {
             focus: FocusArea::SessionList,
             show_help: false,
             help_scroll: 0,
+            show_error_detail: false,
+            event_detail: None,
+            event_detail_scroll: 0,
 
             sessions: vec![],
             panes: vec![],
             events: vec![],
             stats: StatsSummary::default(),
             last_event_id: 0,
+            polling: PollingState::default(),
+            last_snapshot_at: None,
 
             conn_state: ConnState::Disconnected,
             daemon_version: String::new(),
+            daemon_label: String::new(),
+            last_error: None,
 
             spinner_frame: 0,
             event_filter: EventFilter::All,
+            toast_min_severity: Severity::High,
             pending_confirm: None,
             send_input_buf: String::new(),
             selected_session_id: None,
+            follow: None,
 
             session_list_state: RefCell::new(session_list::SessionListState::new()),
             pane_table_state: RefCell::new(pane_table::PaneTableState::new()),
@@ -111,6 +158,11 @@ impl NtmApp {
             palette_state: RefCell::new(command_palette_wrapper::PaletteState::new()),
 
             rpc_tx: None,
+            snapshot_request_tx: None,
+            snapshot_poll_interval: DEFAULT_SNAPSHOT_POLL_INTERVAL,
+            snapshot_polling: false,
+            event_detail_request_tx: None,
+            follow_request_tx: None,
             daemon_rx: Arc::new(Mutex::new(daemon_rx)),
         }
     }
@@ -119,6 +171,96 @@ impl NtmApp {
         self.rpc_tx = Some(tx);
     }
 
+    pub fn set_toast_min_severity(&mut self, severity: Severity) {
+        self.toast_min_severity = severity;
+    }
+
+    /// Interval for polling `snapshot.get` when the daemon doesn't push.
+    pub fn set_snapshot_poll_interval(&mut self, interval: Duration) {
+        self.snapshot_poll_interval = interval;
+    }
+
+    /// Cap how many sessions the session list renders (`None` for all).
+    pub fn set_max_sessions(&mut self, max: Option<usize>) {
+        self.session_list_state.get_mut().set_max_visible(max);
+    }
+
+    /// Raise toasts for events newer than `last_event_id` that meet the
+    /// severity threshold. Bursts collapse into a single summary toast.
+    fn toast_new_events(&self, events: &[EventView]) {
+        const MAX_EVENT_TOASTS: usize = 3;
+
+        let urgent: Vec<(&EventView, Severity)> = events
+            .iter()
+            .filter(|e| e.id > self.last_event_id)
+            .filter_map(|e| {
+                let severity = Severity::parse(e.severity.as_deref()?)?;
+                (severity >= self.toast_min_severity).then_some((e, severity))
+            })
+            .collect();
+
+        let mut queue = self.toast_queue.borrow_mut();
+        if urgent.len() > MAX_EVENT_TOASTS {
+            queue.push(
+                format!("{} new {}+ events", urgent.len(), self.toast_min_severity.label()),
+                ToastLevel::Error,
+            );
+            return;
+        }
+        for (event, severity) in urgent {
+            queue.push(
+                format!(
+                    "{} {} in {}",
+                    severity.label(),
+                    event.event_type,
+                    event.session_id
+                ),
+                ToastLevel::Error,
+            );
+        }
+    }
+
+    pub fn set_snapshot_request_tx(&mut self, tx: tokio::sync::mpsc::UnboundedSender<()>) {
+        self.snapshot_request_tx = Some(tx);
+    }
+
+    /// Ask for a snapshot now instead of waiting for the next push.
+    fn request_snapshot(&self) {
+        let Some(tx) = &self.snapshot_request_tx else { return };
+        if tx.send(()).is_err() {
+            warn!("snapshot request failed: channel closed");
+            return;
+        }
+        self.toast_queue
+            .borrow_mut()
+            .push("Refreshing snapshot".to_string(), ToastLevel::Info);
+    }
+
+    pub fn set_event_detail_request_tx(&mut self, tx: tokio::sync::mpsc::UnboundedSender<i64>) {
+        self.event_detail_request_tx = Some(tx);
+    }
+
+    pub fn set_follow_request_tx(&mut self, tx: tokio::sync::mpsc::UnboundedSender<String>) {
+        self.follow_request_tx = Some(tx);
+    }
+
+    /// Fetch the highlighted event with `events.get`; the overlay opens when
+    /// the reply arrives as `Msg::EventDetailReceived`.
+    fn request_event_detail(&self, filter: EventFilter) {
+        let Some(event_id) = self
+            .event_timeline_state
+            .borrow()
+            .selected_event(&self.events, filter)
+            .map(|e| e.id)
+        else {
+            return;
+        };
+        let Some(tx) = &self.event_detail_request_tx else { return };
+        if tx.send(event_id).is_err() {
+            warn!("event detail request failed: channel closed");
+        }
+    }
+
     /// Send a fire-and-forget JSON-RPC notification (no id, no response expected).
     fn fire_rpc(&self, method: &str, params: serde_json::Value) {
         let Some(tx) = &self.rpc_tx else { return };
@@ -155,10 +297,73 @@ impl NtmApp {
         }
     }
 
+    /// The highlighted pane in the pane table with a `session #index` label.
+    fn selected_pane(&self) -> Option<(&PaneView, String)> {
+        let session = self
+            .session_list_state
+            .borrow()
+            .selected_session_index()
+            .and_then(|i| self.sessions.get(i))?;
+        let pane_idx = self.pane_table_state.borrow().selected()?;
+        let pane = self
+            .panes
+            .iter()
+            .filter(|p| p.session_id == session.session_id)
+            .nth(pane_idx)?;
+        Some((pane, format!("{} #{}", session.name, pane.pane_index)))
+    }
+
+    /// Follow the highlighted pane: subscribe with `panes.tail` and stream
+    /// its output into the follow panel. Replaces any pane already followed.
+    /// The subscription is a request, so a refusal closes the panel again
+    /// (`Msg::FollowFailed`).
+    fn start_follow(&mut self) {
+        let Some((pane, label)) = self.selected_pane() else { return };
+        let follow = pane_output::PaneFollowState::new(pane.pane_id.clone(), label);
+        self.stop_follow();
+        if let Some(tx) = &self.follow_request_tx {
+            if tx.send(follow.pane_id.clone()).is_err() {
+                warn!("follow request failed: channel closed");
+            }
+        }
+        self.follow = Some(follow);
+    }
+
+    /// Leave follow mode, unsubscribing unless the daemon already ended the stream.
+    fn stop_follow(&mut self) {
+        let Some(follow) = self.follow.take() else { return };
+        if !follow.ended {
+            self.fire_rpc("panes.untail", json!({ "paneId": follow.pane_id }));
+        }
+    }
+
     fn session_count(&self) -> usize {
         self.sessions.len()
     }
 
+    /// Escalations still awaiting attention (shown as a header badge).
+    pub fn pending_escalation_count(&self) -> usize {
+        self.events
+            .iter()
+            .filter(|e| e.event_type == "escalation" && e.status.as_deref() == Some("pending"))
+            .count()
+    }
+
+    /// Header title naming the attached daemon, e.g.
+    /// `NTM Tracker — wsl-main v0.4.2`, once `core.hello` has arrived.
+    pub fn header_title(&self) -> String {
+        let mut title = String::from("NTM Tracker");
+        if !self.daemon_label.is_empty() {
+            title.push_str(" — ");
+            title.push_str(&self.daemon_label);
+        }
+        if !self.daemon_version.is_empty() {
+            title.push_str(" v");
+            title.push_str(&self.daemon_version);
+        }
+        title
+    }
+
     fn handle_key(&mut self, key: KeyEvent) -> Cmd<Msg> {
         // Only handle Press events
         if key.kind != KeyEventKind::Press {
@@ -190,11 +395,15 @@ impl NtmApp {
                 return Cmd::None;
             }
             KeyCode::Char('p') if key.modifiers.contains(Modifiers::CTRL) => {
-                self.palette_state.borrow_mut().toggle(&self.sessions, &self.panes);
+                self.palette_state
+                    .borrow_mut()
+                    .toggle(&self.sessions, &self.panes, &self.events);
                 return Cmd::None;
             }
             KeyCode::Char('/') => {
-                self.palette_state.borrow_mut().open(&self.sessions, &self.panes);
+                self.palette_state
+                    .borrow_mut()
+                    .open(&self.sessions, &self.panes, &self.events);
                 return Cmd::None;
             }
             _ => {}
@@ -217,6 +426,38 @@ impl NtmApp {
             return Cmd::None;
         }
 
+        // Error detail overlay: Esc closes, other keys are swallowed
+        if self.show_error_detail {
+            if key.code == KeyCode::Escape {
+                self.show_error_detail = false;
+            }
+            return Cmd::None;
+        }
+
+        // Event detail overlay: j/k scroll, Esc closes, other keys are swallowed
+        if self.event_detail.is_some() {
+            match key.code {
+                KeyCode::Char('j') | KeyCode::Down => {
+                    self.event_detail_scroll = self.event_detail_scroll.saturating_add(1);
+                }
+                KeyCode::Char('k') | KeyCode::Up => {
+                    self.event_detail_scroll = self.event_detail_scroll.saturating_sub(1);
+                }
+                KeyCode::Escape => {
+                    self.event_detail = None;
+                    self.event_detail_scroll = 0;
+                }
+                _ => {}
+            }
+            return Cmd::None;
+        }
+
+        // Follow mode: Esc stops following
+        if key.code == KeyCode::Escape && self.follow.is_some() {
+            self.stop_follow();
+            return Cmd::None;
+        }
+
         // Tab switching
         match key.code {
             KeyCode::Char('1') => {
@@ -235,6 +476,20 @@ impl NtmApp {
                 self.tab = Tab::Health;
                 return Cmd::None;
             }
+            KeyCode::Char('R') => {
+                self.request_snapshot();
+                return Cmd::None;
+            }
+            KeyCode::Char('E') if self.last_error.is_some() => {
+                self.show_error_detail = true;
+                return Cmd::None;
+            }
+            KeyCode::Char('!') => {
+                // Jump straight to the escalation inbox
+                self.tab = Tab::Events;
+                self.focus = FocusArea::EscalationInbox;
+                return Cmd::None;
+            }
             KeyCode::Tab => {
                 self.focus = self.focus.next();
                 return Cmd::None;
@@ -265,6 +520,10 @@ impl NtmApp {
                     self.event_filter = EventFilter::Sessions;
                     return Cmd::None;
                 }
+                KeyCode::Enter => {
+                    self.request_event_detail(self.event_filter);
+                    return Cmd::None;
+                }
                 _ => {}
             }
         }
@@ -286,6 +545,7 @@ impl NtmApp {
             KeyCode::Char('g') => state.select_first_session(),
             KeyCode::Char('G') => state.select_last_session(),
             KeyCode::Enter | KeyCode::Char('l') => state.toggle_expand(),
+            KeyCode::Char('z') => state.toggle_grouped(),
             KeyCode::Char('K') => {
                 // Kill session confirmation
                 if let Some(i) = state.selected_session_index() {
@@ -347,6 +607,10 @@ impl NtmApp {
                     }
                 }
             }
+            KeyCode::Char('f') => {
+                drop(state);
+                self.start_follow();
+            }
             _ => {}
         }
         Cmd::None
@@ -358,6 +622,10 @@ impl NtmApp {
         match key.code {
             KeyCode::Char('j') | KeyCode::Down => state.select_next(len),
             KeyCode::Char('k') | KeyCode::Up => state.select_prev(),
+            KeyCode::Enter => {
+                drop(state);
+                self.request_event_detail(EventFilter::All);
+            }
             _ => {}
         }
         Cmd::None
@@ -467,6 +735,33 @@ impl NtmApp {
         Cmd::None
     }
 
+    /// Switch to the Events tab with event `event_id` selected in the
+    /// timeline, clearing a filter that would hide it.
+    fn select_event(&mut self, event_id: i64) {
+        let Some(event) = self.events.iter().find(|e| e.id == event_id) else {
+            return;
+        };
+        if !self.event_filter.matches(&event.event_type) {
+            self.event_filter = EventFilter::All;
+        }
+        // Rows are rendered newest first
+        let row = self
+            .events
+            .iter()
+            .filter(|e| self.event_filter.matches(&e.event_type))
+            .rev()
+            .take(event_timeline::MAX_VISIBLE_EVENTS)
+            .position(|e| e.id == event_id);
+        if let Some(row) = row {
+            self.event_timeline_state
+                .borrow_mut()
+                .list_state
+                .select(Some(row));
+            self.tab = Tab::Events;
+            self.focus = FocusArea::EventTimeline;
+        }
+    }
+
     pub fn handle_palette_action(&mut self, action_id: &str) -> Cmd<Msg> {
         if let Some(tab_name) = action_id.strip_prefix("tab:") {
             match tab_name {
@@ -486,6 +781,10 @@ impl NtmApp {
                 self.session_list_state.borrow_mut().select_session_by_index(idx);
                 self.tab = Tab::Sessions;
             }
+        } else if let Some(event_id) = action_id.strip_prefix("event:") {
+            if let Ok(event_id) = event_id.parse() {
+                self.select_event(event_id);
+            }
         } else if let Some(session_id) = action_id.strip_prefix("kill:") {
             if let Some(s) = self.sessions.iter().find(|s| s.session_id == session_id) {
                 self.pending_confirm = Some(ConfirmAction::KillSession {
@@ -539,11 +838,17 @@ impl Model for NtmApp {
                 Cmd::None
             }
             Msg::SnapshotReceived(snap) => {
+                // The first snapshot is the backlog, not news
+                if self.last_snapshot_at.is_some() {
+                    self.toast_new_events(&snap.events);
+                }
                 self.sessions = snap.sessions;
                 self.panes = snap.panes;
                 self.events = snap.events;
                 self.stats = snap.stats.summary;
                 self.last_event_id = snap.last_event_id;
+                self.polling = snap.polling;
+                self.last_snapshot_at = Some(chrono::Utc::now().timestamp());
 
                 // Auto-select: ensure valid selection
                 let session_count = self.sessions.len();
@@ -577,20 +882,73 @@ impl Model for NtmApp {
 
                 Cmd::None
             }
+            Msg::EventsPushed(events) => {
+                // Snapshots may already hold some of these; keep only new ones
+                let new: Vec<EventView> = events
+                    .into_iter()
+                    .filter(|e| e.id > self.last_event_id)
+                    .collect();
+                if let Some(max_id) = new.iter().map(|e| e.id).max() {
+                    self.toast_new_events(&new);
+                    self.last_event_id = max_id;
+                    self.events.extend(new);
+                }
+                Cmd::None
+            }
             Msg::ConnectionChanged(state) => {
                 self.conn_state = state;
                 Cmd::None
             }
-            Msg::HelloReceived(version) => {
-                self.daemon_version = version;
+            Msg::HelloReceived(hello) => {
+                self.daemon_version = hello.version;
+                self.daemon_label = hello.label;
+                self.snapshot_polling = !hello.push;
+                Cmd::None
+            }
+            Msg::PollSnapshot => {
+                if let Some(tx) = &self.snapshot_request_tx {
+                    let _ = tx.send(());
+                }
                 Cmd::None
             }
             Msg::RpcError(err) => {
                 self.toast_queue.borrow_mut().push(
-                    format!("RPC error: {err}"),
+                    format!("RPC error: {} (E for details)", err.message),
+                    ToastLevel::Error,
+                );
+                self.conn_state = ConnState::Error(err.message.clone());
+                self.last_error = Some(err);
+                Cmd::None
+            }
+            Msg::FollowFailed { pane_id, error } => {
+                if self.follow.as_ref().is_some_and(|f| f.pane_id == pane_id) {
+                    self.follow = None;
+                }
+                self.toast_queue.borrow_mut().push(
+                    format!("Follow failed: {} (E for details)", error.message),
                     ToastLevel::Error,
                 );
-                self.conn_state = ConnState::Error(err);
+                self.last_error = Some(error);
+                Cmd::None
+            }
+            Msg::EventDetailReceived(detail) => {
+                self.event_detail = Some(detail);
+                self.event_detail_scroll = 0;
+                Cmd::None
+            }
+            Msg::PaneOutputAppended { pane_id, lines, ended } => {
+                let Some(follow) = self.follow.as_mut().filter(|f| f.pane_id == pane_id) else {
+                    // Output for a pane we stopped following; drop it
+                    return Cmd::None;
+                };
+                follow.append(lines);
+                if ended && !follow.ended {
+                    follow.ended = true;
+                    let label = follow.label.clone();
+                    self.toast_queue
+                        .borrow_mut()
+                        .push(format!("Pane {label} ended"), ToastLevel::Info);
+                }
                 Cmd::None
             }
             Msg::DismissEscalation(event_id) => {
@@ -641,8 +999,13 @@ impl Model for NtmApp {
             .split(area);
 
         // Header: tab bar
-        let escalation_count = self.events.iter().filter(|e| e.event_type == "escalation").count();
-        render_header(frame, rows[0], self.tab, escalation_count);
+        render_header(
+            frame,
+            rows[0],
+            &self.header_title(),
+            self.tab,
+            self.pending_escalation_count(),
+        );
 
         // Content: active tab
         match self.tab {
@@ -658,10 +1021,12 @@ impl Model for NtmApp {
             rows[2],
             &self.conn_state,
             &self.daemon_version,
+            self.polling.primary(),
             self.session_count(),
             self.tab,
             self.spinner_frame,
             self.focus,
+            self.last_snapshot_at,
         );
 
         // Confirmation modal overlay
@@ -687,6 +1052,18 @@ impl Model for NtmApp {
                 .render(palette_area, frame);
         }
 
+        // Error detail overlay
+        if self.show_error_detail {
+            if let Some(err) = &self.last_error {
+                screens::error_detail::render(frame, area, err);
+            }
+        }
+
+        // Event detail overlay
+        if let Some(detail) = &self.event_detail {
+            screens::event_detail::render(frame, area, detail, self.event_detail_scroll);
+        }
+
         // Help overlay (on top of everything)
         if self.show_help {
             screens::help::render(frame, area, self.help_scroll);
@@ -694,12 +1071,17 @@ impl Model for NtmApp {
     }
 
     fn subscriptions(&self) -> Vec<Box<dyn Subscription<Msg>>> {
-        vec![
+        let mut subs: Vec<Box<dyn Subscription<Msg>>> = vec![
             Box::new(Every::new(Duration::from_millis(100), || Msg::Tick)),
             Box::new(DaemonSubscription {
                 receiver: self.daemon_rx.clone(),
             }),
-        ]
+        ];
+        // Daemons that push keep the TUI current on their own
+        if self.snapshot_polling {
+            subs.push(Box::new(Every::new(self.snapshot_poll_interval, || Msg::PollSnapshot)));
+        }
+        subs
     }
 }
 
@@ -732,33 +1114,36 @@ impl Subscription<Msg> for DaemonSubscription {
     }
 }
 
-fn render_header(frame: &mut Frame, area: Rect, active_tab: Tab, escalation_count: usize) {
-    let mut header = String::from(" NTM Tracker ");
+fn render_header(
+    frame: &mut Frame,
+    area: Rect,
+    title: &str,
+    active_tab: Tab,
+    pending_escalations: usize,
+) {
+    let mut header = format!(" {title} ");
     header.push_str(&theme::BOX_HORIZONTAL.repeat(2));
     header.push(' ');
 
     for (i, tab) in Tab::all().iter().enumerate() {
         let num = i + 1;
+        let label = if *tab == Tab::Events && pending_escalations > 0 {
+            format!("{} ({})", tab.label(), pending_escalations)
+        } else {
+            tab.label().to_string()
+        };
         if *tab == active_tab {
-            header.push_str(&format!("[{}:{}]", num, tab.label()));
+            header.push_str(&format!("[{num}:{label}]"));
         } else {
-            header.push_str(&format!(" {}:{} ", num, tab.label()));
+            header.push_str(&format!(" {num}:{label} "));
         }
         if i < 3 {
             header.push(' ');
         }
     }
 
-    if escalation_count > 0 {
-        let badge = format!(" \u{25cf} {} ", escalation_count);
-        let used = header.len() + badge.len();
-        let pad = (area.width as usize).saturating_sub(used);
-        header.push_str(&" ".repeat(pad));
-        header.push_str(&badge);
-    } else {
-        let pad = (area.width as usize).saturating_sub(header.len());
-        header.push_str(&" ".repeat(pad));
-    }
+    let pad = (area.width as usize).saturating_sub(header.chars().count());
+    header.push_str(&" ".repeat(pad));
 
     let para = Paragraph::new(header).style(Style::new().fg(theme::TEXT_PRIMARY).bg(theme::BG_RAISED));
     para.render(area, frame);
@@ -828,6 +1213,7 @@ fn render_toast(frame: &mut Frame, area: Rect, toast: &toast_manager::ToastEntry
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::msg::HelloInfo;
     use crate::rpc::types::{EventView, PaneView, SessionView, Snapshot, StatsEnvelope, StatsSummary};
 
     // === Test helpers ===
@@ -953,6 +1339,7 @@ mod tests {
                 ..Default::default()
             },
             last_event_id: 42,
+            ..Default::default()
         };
         let cmd = app.update(Msg::SnapshotReceived(snap));
         assert!(matches!(cmd, Cmd::None));
@@ -965,6 +1352,93 @@ mod tests {
         assert_eq!(app.last_event_id, 42);
     }
 
+    #[test]
+    fn test_update_snapshot_records_arrival_time() {
+        let mut app = NtmApp::new();
+        assert_eq!(app.last_snapshot_at, None);
+        let before = chrono::Utc::now().timestamp();
+        app.update(Msg::SnapshotReceived(Snapshot::default()));
+        let at = app.last_snapshot_at.expect("timestamp set");
+        assert!(at >= before && at <= chrono::Utc::now().timestamp());
+    }
+
+    fn snapshot_with_event(id: i64, severity: &str) -> Snapshot {
+        let mut event = make_event(id, "escalation", "s1");
+        event.severity = Some(severity.to_string());
+        Snapshot {
+            events: vec![event],
+            last_event_id: id,
+            ..Snapshot::default()
+        }
+    }
+
+    #[test]
+    fn test_snapshot_new_high_severity_event_toasts() {
+        let mut app = NtmApp::new();
+        app.update(Msg::SnapshotReceived(snapshot_with_event(1, "low")));
+        app.update(Msg::SnapshotReceived(snapshot_with_event(2, "high")));
+        let queue = app.toast_queue.borrow();
+        assert_eq!(queue.toasts.len(), 1);
+        let toast = queue.active().unwrap();
+        assert_eq!(toast.message, "high escalation in s1");
+        assert_eq!(toast.level, ToastLevel::Error);
+    }
+
+    #[test]
+    fn test_snapshot_low_severity_event_does_not_toast() {
+        let mut app = NtmApp::new();
+        app.update(Msg::SnapshotReceived(snapshot_with_event(1, "high")));
+        app.update(Msg::SnapshotReceived(snapshot_with_event(2, "low")));
+        assert!(app.toast_queue.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_does_not_toast_already_seen_events() {
+        let mut app = NtmApp::new();
+        app.update(Msg::SnapshotReceived(snapshot_with_event(3, "critical")));
+        app.update(Msg::SnapshotReceived(snapshot_with_event(3, "critical")));
+        assert!(app.toast_queue.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_toast_min_severity_is_configurable() {
+        let mut app = NtmApp::new();
+        app.set_toast_min_severity(Severity::Low);
+        app.update(Msg::SnapshotReceived(snapshot_with_event(1, "info")));
+        app.update(Msg::SnapshotReceived(snapshot_with_event(2, "low")));
+        assert_eq!(app.toast_queue.borrow().active().unwrap().message, "low escalation in s1");
+    }
+
+    #[test]
+    fn test_events_push_appends_without_touching_sessions() {
+        let mut app = populated_app();
+        let mut pushed = make_event(6, "compact", "s2");
+        pushed.severity = Some("low".to_string());
+        app.update(Msg::EventsPushed(vec![pushed]));
+
+        assert_eq!(app.events.len(), 6);
+        assert_eq!(app.events.last().unwrap().id, 6);
+        assert_eq!(app.last_event_id, 6);
+        assert_eq!(app.sessions.len(), 3);
+        assert_eq!(app.panes.len(), 7);
+        assert!(app.toast_queue.borrow().is_empty());
+    }
+
+    #[test]
+    fn test_events_push_skips_already_seen_events() {
+        let mut app = populated_app();
+        app.update(Msg::EventsPushed(vec![
+            make_event(5, "escalation", "s1"),
+            make_event(7, "escalation", "s3"),
+        ]));
+        assert_eq!(app.events.len(), 6);
+        assert_eq!(app.last_event_id, 7);
+        assert_eq!(
+            app.toast_queue.borrow().active().unwrap().message,
+            "high escalation in s3"
+        );
+    }
+
     #[test]
     fn test_update_snapshot_replaces_previous() {
         let mut app = populated_app();
@@ -976,6 +1450,7 @@ mod tests {
             events: vec![],
             stats: StatsEnvelope::default(),
             last_event_id: 100,
+            ..Default::default()
         };
         app.update(Msg::SnapshotReceived(snap));
         assert_eq!(app.sessions.len(), 1);
@@ -995,34 +1470,174 @@ mod tests {
         assert_eq!(app.conn_state, ConnState::Connected);
     }
 
+    fn hello(version: &str, label: &str) -> Msg {
+        Msg::HelloReceived(HelloInfo {
+            version: version.to_string(),
+            label: label.to_string(),
+            push: true,
+        })
+    }
+
     #[test]
     fn test_update_hello_received() {
         let mut app = NtmApp::new();
         assert!(app.daemon_version.is_empty());
-        app.update(Msg::HelloReceived("1.2.3".to_string()));
+        app.update(hello("1.2.3", "wsl-main"));
         assert_eq!(app.daemon_version, "1.2.3");
+        assert_eq!(app.daemon_label, "wsl-main");
     }
 
     #[test]
     fn test_update_hello_overwrites() {
         let mut app = NtmApp::new();
-        app.update(Msg::HelloReceived("1.0".to_string()));
-        app.update(Msg::HelloReceived("2.0".to_string()));
+        app.update(hello("1.0", "a"));
+        app.update(hello("2.0", "b"));
         assert_eq!(app.daemon_version, "2.0");
+        assert_eq!(app.daemon_label, "b");
+    }
+
+    #[test]
+    fn test_header_title_from_hello() {
+        let mut app = NtmApp::new();
+        assert_eq!(app.header_title(), "NTM Tracker");
+        app.update(hello("1.2.3", ""));
+        assert_eq!(app.header_title(), "NTM Tracker v1.2.3");
+        app.update(hello("1.2.3", "wsl-main"));
+        assert_eq!(app.header_title(), "NTM Tracker — wsl-main v1.2.3");
     }
 
     #[test]
     fn test_update_rpc_error_sets_error_state() {
         let mut app = NtmApp::new();
         app.conn_state = ConnState::Connected;
-        app.update(Msg::RpcError("timeout".to_string()));
+        app.update(Msg::RpcError("timeout".to_string().into()));
         assert_eq!(app.conn_state, ConnState::Error("timeout".to_string()));
     }
 
+    #[test]
+    fn test_update_rpc_error_retains_detail() {
+        let mut app = NtmApp::new();
+        let detail = RpcErrorDetail {
+            message: "Invalid params: missing field 'sessionId'".to_string(),
+            code: Some(-32602),
+            data: Some(json!({ "field": "sessionId", "reason": "missing" })),
+        };
+        app.update(Msg::RpcError(detail.clone()));
+        assert_eq!(app.last_error, Some(detail));
+        assert_eq!(
+            app.conn_state,
+            ConnState::Error("Invalid params: missing field 'sessionId'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_shift_e_opens_error_overlay_and_esc_closes() {
+        let mut app = NtmApp::new();
+        app.handle_key(key(KeyCode::Char('E')));
+        assert!(!app.show_error_detail, "no error yet, nothing to show");
+
+        app.update(Msg::RpcError("Session not found: s9".to_string().into()));
+        app.handle_key(key(KeyCode::Char('E')));
+        assert!(app.show_error_detail);
+
+        // Other keys are swallowed while the overlay is open
+        app.handle_key(key(KeyCode::Char('2')));
+        assert_eq!(app.tab, Tab::Dashboard);
+        assert!(app.show_error_detail);
+
+        app.handle_key(key(KeyCode::Escape));
+        assert!(!app.show_error_detail);
+    }
+
+    #[test]
+    fn test_error_overlay_renders_message() {
+        let mut app = NtmApp::new();
+        app.update(Msg::RpcError(RpcErrorDetail {
+            message: "Forbidden: admin authentication required".to_string(),
+            code: Some(-32002),
+            data: None,
+        }));
+        app.handle_key(key(KeyCode::Char('E')));
+        test_frame!(pool, frame, 100, 40);
+        app.view(&mut frame);
+        assert_text_present(&frame.buffer, "Last Error");
+        assert_text_present(&frame.buffer, "Forbidden: admin authentication required");
+        assert_text_present(&frame.buffer, "-32002");
+    }
+
+    fn make_event_detail(id: i64) -> EventDetail {
+        EventDetail {
+            event: make_event(id, "escalation", "s2"),
+            message: Some("Agent is waiting for input".to_string()),
+            payload: Some(json!({ "rule": "prompt_wait" })),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_enter_on_event_requests_detail_for_selected_row() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut app = populated_app();
+        app.set_event_detail_request_tx(tx);
+        app.handle_key(key(KeyCode::Char('3')));
+        app.handle_key(key(KeyCode::Char('e')));
+
+        // Rows are newest first and only count events passing the filter
+        app.event_timeline_state.borrow_mut().list_state.select(Some(1));
+        app.handle_key(key(KeyCode::Enter));
+        assert_eq!(rx.try_recv().ok(), Some(3));
+        assert!(app.event_detail.is_none(), "overlay waits for the reply");
+    }
+
+    #[test]
+    fn test_enter_without_selection_requests_nothing() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut app = populated_app();
+        app.set_event_detail_request_tx(tx);
+        app.tab = Tab::Events;
+        app.handle_key(key(KeyCode::Enter));
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_event_detail_received_opens_overlay() {
+        let mut app = populated_app();
+        app.event_detail_scroll = 4;
+        app.update(Msg::EventDetailReceived(make_event_detail(3)));
+        assert_eq!(app.event_detail.as_ref().map(|d| d.event.id), Some(3));
+        assert_eq!(app.event_detail_scroll, 0);
+
+        test_frame!(pool, frame, 100, 40);
+        app.view(&mut frame);
+        assert_text_present(&frame.buffer, "Event #3");
+        assert_text_present(&frame.buffer, "Agent is waiting for input");
+        assert_text_present(&frame.buffer, "\"rule\": \"prompt_wait\"");
+    }
+
+    #[test]
+    fn test_event_detail_overlay_scrolls_and_esc_closes() {
+        let mut app = populated_app();
+        app.update(Msg::EventDetailReceived(make_event_detail(3)));
+
+        app.handle_key(key(KeyCode::Char('j')));
+        app.handle_key(key(KeyCode::Char('j')));
+        app.handle_key(key(KeyCode::Char('k')));
+        assert_eq!(app.event_detail_scroll, 1);
+
+        // Other keys are swallowed while the overlay is open
+        app.handle_key(key(KeyCode::Char('2')));
+        assert_eq!(app.tab, Tab::Dashboard);
+        assert!(app.event_detail.is_some());
+
+        app.handle_key(key(KeyCode::Escape));
+        assert!(app.event_detail.is_none());
+        assert_eq!(app.event_detail_scroll, 0);
+    }
+
     #[test]
     fn test_update_rpc_error_creates_toast() {
         let mut app = NtmApp::new();
-        app.update(Msg::RpcError("timeout".to_string()));
+        app.update(Msg::RpcError("timeout".to_string().into()));
         let q = app.toast_queue.borrow();
         assert!(!q.is_empty());
         assert_eq!(q.active().unwrap().level, ToastLevel::Error);
@@ -1211,6 +1826,37 @@ mod tests {
         assert_eq!(app.tab, Tab::Dashboard);
     }
 
+    #[test]
+    fn test_bang_jumps_to_escalation_inbox() {
+        let mut app = populated_app();
+        assert_eq!(app.tab, Tab::Dashboard);
+        assert_eq!(app.focus, FocusArea::SessionList);
+
+        let cmd = app.handle_key(key(KeyCode::Char('!')));
+        assert!(matches!(cmd, Cmd::None));
+        assert_eq!(app.tab, Tab::Events);
+        assert_eq!(app.focus, FocusArea::EscalationInbox);
+    }
+
+    #[test]
+    fn test_shift_r_requests_snapshot() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut app = populated_app();
+        app.set_snapshot_request_tx(tx);
+
+        let cmd = app.handle_key(key(KeyCode::Char('R')));
+        assert!(matches!(cmd, Cmd::None));
+        assert!(rx.try_recv().is_ok());
+        assert!(rx.try_recv().is_err(), "one request per key press");
+    }
+
+    #[test]
+    fn test_shift_r_without_client_is_noop() {
+        let mut app = populated_app();
+        app.handle_key(key(KeyCode::Char('R')));
+        assert!(app.toast_queue.borrow().is_empty());
+    }
+
     #[test]
     fn test_tab_key_cycles_focus() {
         let mut app = NtmApp::new();
@@ -1630,6 +2276,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_palette_action_event_selects_event() {
+        let mut app = populated_app();
+        app.handle_palette_action("event:2");
+        assert_eq!(app.tab, Tab::Events);
+        assert_eq!(app.focus, FocusArea::EventTimeline);
+        // Newest first: ids 5, 4, 3, 2, 1
+        assert_eq!(
+            app.event_timeline_state.borrow().list_state.selected(),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn test_palette_action_event_clears_hiding_filter() {
+        let mut app = populated_app();
+        app.event_filter = EventFilter::Escalations;
+        app.handle_palette_action("event:4");
+        assert_eq!(app.event_filter, EventFilter::All);
+        assert_eq!(
+            app.event_timeline_state.borrow().list_state.selected(),
+            Some(1)
+        );
+
+        // A matching filter is kept and rows are counted within it
+        app.event_filter = EventFilter::Escalations;
+        app.handle_palette_action("event:3");
+        assert_eq!(app.event_filter, EventFilter::Escalations);
+        assert_eq!(
+            app.event_timeline_state.borrow().list_state.selected(),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_palette_action_unknown_event_noop() {
+        let mut app = populated_app();
+        app.handle_palette_action("event:99");
+        app.handle_palette_action("event:abc");
+        assert_eq!(app.tab, Tab::Dashboard);
+        assert_eq!(
+            app.event_timeline_state.borrow().list_state.selected(),
+            None
+        );
+    }
+
     #[test]
     fn test_palette_action_kill_session() {
         let mut app = populated_app();
@@ -1882,6 +2574,117 @@ mod tests {
         assert!(app.send_input_buf.is_empty());
     }
 
+    // ========================================================
+    // Follow mode (panes.tail)
+    // ========================================================
+
+    type FollowingApp = (
+        NtmApp,
+        tokio::sync::mpsc::Receiver<String>,
+        tokio::sync::mpsc::UnboundedReceiver<String>,
+    );
+
+    /// An app following pane p2, with its notification and `panes.tail`
+    /// request channels.
+    fn following_app() -> FollowingApp {
+        let (tx, rx) = tokio::sync::mpsc::channel(8);
+        let (follow_tx, follow_rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut app = populated_app();
+        app.set_rpc_tx(tx);
+        app.set_follow_request_tx(follow_tx);
+        app.focus = FocusArea::PaneTable;
+        app.session_list_state.borrow_mut().list_state.select(Some(0));
+        app.pane_table_state.borrow_mut().table_state.select(Some(1));
+        app.handle_key(key(KeyCode::Char('f')));
+        (app, rx, follow_rx)
+    }
+
+    fn sent_rpc(rx: &mut tokio::sync::mpsc::Receiver<String>) -> serde_json::Value {
+        serde_json::from_str(&rx.try_recv().unwrap()).unwrap()
+    }
+
+    fn output(pane_id: &str, lines: &[&str], ended: bool) -> Msg {
+        Msg::PaneOutputAppended {
+            pane_id: pane_id.to_string(),
+            lines: lines.iter().map(|l| l.to_string()).collect(),
+            ended,
+        }
+    }
+
+    #[test]
+    fn test_f_follows_selected_pane() {
+        let (app, mut rx, mut follow_rx) = following_app();
+        let follow = app.follow.as_ref().unwrap();
+        assert_eq!(follow.pane_id, "p2");
+        assert_eq!(follow.label, "project-a #0");
+        assert_eq!(follow_rx.try_recv().unwrap(), "p2");
+        assert!(rx.try_recv().is_err(), "panes.tail is a request, not a notification");
+    }
+
+    #[test]
+    fn test_failed_tail_closes_follow_panel_and_reports() {
+        let (mut app, _rx, _follow_rx) = following_app();
+        app.update(Msg::FollowFailed {
+            pane_id: "p2".to_string(),
+            error: "Pane not found: p2".to_string().into(),
+        });
+        assert!(app.follow.is_none());
+        assert_eq!(app.last_error.as_ref().unwrap().message, "Pane not found: p2");
+        assert!(!matches!(app.conn_state, ConnState::Error(_)), "the connection is fine");
+    }
+
+    #[test]
+    fn test_failed_tail_for_another_pane_keeps_follow() {
+        let (mut app, _rx, _follow_rx) = following_app();
+        app.update(Msg::FollowFailed {
+            pane_id: "p1".to_string(),
+            error: "Pane not found: p1".to_string().into(),
+        });
+        assert_eq!(app.follow.as_ref().unwrap().pane_id, "p2");
+    }
+
+    #[test]
+    fn test_pane_output_appends_grow_follow_buffer() {
+        let (mut app, _rx, _follow_rx) = following_app();
+        app.update(output("p2", &["building"], false));
+        app.update(output("p2", &["compiled", "done"], false));
+        // Output for another pane is ignored
+        app.update(output("p1", &["elsewhere"], false));
+
+        let follow = app.follow.as_ref().unwrap();
+        assert_eq!(follow.lines, ["building", "compiled", "done"]);
+        assert!(!follow.ended);
+    }
+
+    #[test]
+    fn test_esc_stops_following_and_untails() {
+        let (mut app, mut rx, _follow_rx) = following_app();
+        app.update(output("p2", &["building"], false));
+
+        app.handle_key(key(KeyCode::Escape));
+        assert!(app.follow.is_none());
+        let sent = sent_rpc(&mut rx);
+        assert_eq!(sent["method"], "panes.untail");
+        assert_eq!(sent["params"]["paneId"], "p2");
+
+        // Late output after stopping is dropped
+        app.update(output("p2", &["late"], false));
+        assert!(app.follow.is_none());
+    }
+
+    #[test]
+    fn test_ended_stream_keeps_output_and_esc_skips_untail() {
+        let (mut app, mut rx, _follow_rx) = following_app();
+        app.update(output("p2", &["last line"], false));
+        app.update(output("p2", &[], true));
+        assert!(app.follow.as_ref().unwrap().ended);
+        assert_eq!(app.follow.as_ref().unwrap().lines, ["last line"]);
+
+        app.handle_key(key(KeyCode::Escape));
+        assert!(app.follow.is_none());
+        assert!(rx.try_recv().is_err(), "daemon already ended the stream");
+    }
+
     // ========================================================
     // fire_rpc with real channels — JSON-RPC notification tests
     // ========================================================
@@ -1996,6 +2799,42 @@ mod tests {
         assert!(!app.toast_queue.borrow().is_empty());
     }
 
+    #[test]
+    fn test_pane_send_typed_text_is_sent_on_enter() {
+        let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+        let mut app = populated_app();
+        app.set_rpc_tx(tx);
+        app.handle_palette_action("send:%5:work");
+        for c in "git status".chars() {
+            app.handle_key(key(KeyCode::Char(c)));
+        }
+        assert!(rx.try_recv().is_err(), "typing must not send");
+
+        app.handle_key(key(KeyCode::Enter));
+        let msg = rx.try_recv().unwrap();
+        let parsed: serde_json::Value = serde_json::from_str(&msg).unwrap();
+        assert_eq!(parsed["method"], "actions.paneSend");
+        assert_eq!(parsed["params"]["paneId"], "%5");
+        assert_eq!(parsed["params"]["payload"], "git status");
+        assert!(app.pending_confirm.is_none());
+    }
+
+    #[test]
+    fn test_pane_send_escape_after_typing_sends_nothing() {
+        let (tx, mut rx) = tokio::sync::mpsc::channel(8);
+        let mut app = populated_app();
+        app.set_rpc_tx(tx);
+        app.handle_palette_action("send:%5:work");
+        for c in "rm -rf".chars() {
+            app.handle_key(key(KeyCode::Char(c)));
+        }
+
+        app.handle_key(key(KeyCode::Escape));
+        assert!(rx.try_recv().is_err());
+        assert!(app.pending_confirm.is_none());
+        assert!(app.send_input_buf.is_empty());
+    }
+
     #[test]
     fn test_fire_rpc_multiple_calls_queued() {
         let (tx, mut rx) = tokio::sync::mpsc::channel(8);
@@ -2028,6 +2867,15 @@ mod tests {
         assert_text_present(&frame.buffer, "Dashboard");
     }
 
+    #[test]
+    fn test_view_header_shows_label_and_version() {
+        test_frame!(pool, frame, 120, 30);
+        let mut app = NtmApp::new();
+        app.update(hello("0.4.2", "build-box"));
+        app.view(&mut frame);
+        assert_text_present(&frame.buffer, "NTM Tracker — build-box v0.4.2");
+    }
+
     #[test]
     fn test_view_renders_footer_connection_bar() {
         test_frame!(pool, frame, 100, 30);
@@ -2135,6 +2983,32 @@ mod tests {
         assert_text_present(&frame.buffer, "[3:Events]");
     }
 
+    #[test]
+    fn test_view_header_badge_counts_pending_escalations() {
+        test_frame!(pool, frame, 100, 30);
+        let mut app = populated_app();
+        app.view(&mut frame);
+        assert_text_present(&frame.buffer, "3:Events (2)");
+
+        // Handled escalations no longer count toward the badge
+        app.events[2].status = Some("dismissed".to_string());
+        test_frame!(pool2, frame2, 100, 30);
+        app.view(&mut frame2);
+        assert_text_present(&frame2.buffer, "3:Events (1)");
+    }
+
+    #[test]
+    fn test_view_header_badge_clears_without_pending_escalations() {
+        test_frame!(pool, frame, 100, 30);
+        let mut app = populated_app();
+        for event in &mut app.events {
+            event.status = None;
+        }
+        app.view(&mut frame);
+        assert_text_present(&frame.buffer, "3:Events");
+        assert_text_absent(&frame.buffer, "Events (");
+    }
+
     // ========================================================
     // subscriptions() test
     // ========================================================
@@ -2145,4 +3019,31 @@ mod tests {
         let subs = app.subscriptions();
         assert_eq!(subs.len(), 2);
     }
+
+    #[test]
+    fn test_push_capable_daemon_schedules_no_poll_timer() {
+        let mut app = NtmApp::new();
+        app.update(hello("1.0", ""));
+        assert!(!app.snapshot_polling);
+        assert_eq!(app.subscriptions().len(), 2);
+    }
+
+    #[test]
+    fn test_daemon_without_push_schedules_poll_timer() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut app = NtmApp::new();
+        app.set_snapshot_request_tx(tx);
+        app.set_snapshot_poll_interval(Duration::from_secs(2));
+        app.update(Msg::HelloReceived(HelloInfo {
+            version: "0.9".to_string(),
+            label: String::new(),
+            push: false,
+        }));
+        assert!(app.snapshot_polling);
+        assert_eq!(app.subscriptions().len(), 3);
+
+        app.update(Msg::PollSnapshot);
+        assert!(rx.try_recv().is_ok(), "timer requests a snapshot");
+        assert!(app.toast_queue.borrow().is_empty(), "polls are silent");
+    }
 }
@@ -3,6 +3,7 @@
 pub mod test_helpers;
 
 pub mod app;
+pub mod logging;
 pub mod msg;
 pub mod rpc;
 pub mod screens;
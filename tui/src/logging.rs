@@ -0,0 +1,67 @@
+//! File logging for the TUI; stdout belongs to the terminal UI.
+
+use std::path::Path;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+
+/// An appender writing to `path`, creating its directory if needed.
+pub fn file_appender(path: &Path) -> Result<RollingFileAppender, String> {
+    let filename = path
+        .file_name()
+        .ok_or_else(|| format!("log path {} has no file name", path.display()))?;
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    RollingFileAppender::builder()
+        .rotation(Rotation::NEVER)
+        .filename_prefix(filename.to_string_lossy())
+        .build(dir)
+        .map_err(|err| format!("cannot open log file {}: {err}", path.display()))
+}
+
+/// Send tracing output to `path`. When the file can't be opened the reason
+/// goes to stderr, which is still visible before the alternate screen comes
+/// up, and the TUI runs without file logging. Returns whether logging is on.
+pub fn init_file_logging(path: &Path) -> bool {
+    let appender = match file_appender(path) {
+        Ok(appender) => appender,
+        Err(err) => {
+            eprintln!("ntm-tui: {err}; continuing without file logging");
+            return false;
+        }
+    };
+    let filter = tracing_subscriber::EnvFilter::try_from_default_env()
+        .unwrap_or_else(|_| tracing_subscriber::EnvFilter::new("ntm_tracker_tui=debug"));
+    match tracing_subscriber::fmt()
+        .with_writer(appender)
+        .with_env_filter(filter)
+        .try_init()
+    {
+        Ok(()) => true,
+        Err(err) => {
+            eprintln!("ntm-tui: cannot install logger: {err}; continuing without file logging");
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unwritable_log_path_falls_back_without_panicking() {
+        let dir = std::env::temp_dir().join(format!("ntm-tui-log-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        // A regular file where the log directory should be
+        let blocker = dir.join("not-a-dir");
+        std::fs::write(&blocker, "").unwrap();
+        let path = blocker.join("tui.log");
+
+        assert!(file_appender(&path).is_err());
+        assert!(!init_file_logging(&path));
+        assert!(file_appender(Path::new("/")).is_err(), "no file name");
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
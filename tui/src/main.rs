@@ -1,8 +1,14 @@
 use clap::Parser;
 use ftui::{App, ScreenMode};
 use ntm_tracker_tui::app::NtmApp;
+use ntm_tracker_tui::logging;
 use ntm_tracker_tui::msg::{self, Msg};
 use ntm_tracker_tui::rpc::client::RpcClient;
+use ntm_tracker_tui::rpc::discovery::{self, Endpoint, InstanceInfo};
+use ntm_tracker_tui::rpc::supervisor::{RespawnPolicy, SupervisedClient};
+use ntm_tracker_tui::rpc::types::EventsGetResult;
+use std::path::PathBuf;
+use std::sync::Arc;
 use tracing::info;
 
 /// NTM Tracker TUI — terminal dashboard for the NTM Tracker daemon.
@@ -17,63 +23,102 @@ struct Cli {
     #[arg(long)]
     no_daemon: bool,
 
+    /// Connect to a running daemon's WebSocket port instead of the one its
+    /// instance file advertises.
+    #[arg(long, conflicts_with = "uds_path")]
+    port: Option<u16>,
+
+    /// Connect to a running daemon's Unix socket instead of the one its
+    /// instance file advertises.
+    #[arg(long)]
+    uds_path: Option<PathBuf>,
+
     /// Log file path.
     #[arg(long)]
     log_file: Option<String>,
+
+    /// Minimum severity of new events that raise a toast
+    /// (info, low, medium, high, critical).
+    #[arg(long, default_value = "high")]
+    toast_min_severity: msg::Severity,
+
+    /// Most sessions shown in the session list before collapsing the rest
+    /// into a "+N more" row (0 shows all).
+    #[arg(long, default_value_t = 50)]
+    max_sessions: usize,
+
+    /// How often to poll `snapshot.get` when the daemon doesn't push
+    /// snapshots (older daemons without the `stream` capability).
+    #[arg(long, default_value_t = 5000)]
+    snapshot_interval_ms: u64,
+
+    /// Respawn the daemon with backoff (up to 5 times in a row) if it exits.
+    #[arg(long)]
+    respawn: bool,
 }
 
 fn main() -> std::io::Result<()> {
     let cli = Cli::parse();
 
-    // Set up file-based logging (stdout is used by the TUI).
+    // Set up file-based logging (stdout is used by the TUI). A log file that
+    // can't be opened is reported on stderr and skipped.
     if let Some(log_path) = &cli.log_file {
-        let path = std::path::Path::new(log_path);
-        let dir = path.parent().unwrap_or(std::path::Path::new("."));
-        let filename = path.file_name().unwrap_or_default();
-        let file_appender = tracing_appender::rolling::never(dir, filename);
-        tracing_subscriber::fmt()
-            .with_writer(file_appender)
-            .with_env_filter(
-                tracing_subscriber::EnvFilter::try_from_default_env()
-                    .unwrap_or_else(|_| "ntm_tracker_tui=debug".parse().unwrap()),
-            )
-            .init();
+        logging::init_file_logging(std::path::Path::new(log_path));
     }
 
     // Create the message channel (daemon → TUI).
     let (msg_tx, msg_rx) = tokio::sync::mpsc::unbounded_channel::<Msg>();
     let mut app = NtmApp::with_daemon_rx(msg_rx);
+    app.set_toast_min_severity(cli.toast_min_severity);
+    app.set_max_sessions((cli.max_sessions > 0).then_some(cli.max_sessions));
+    app.set_snapshot_poll_interval(std::time::Duration::from_millis(
+        cli.snapshot_interval_ms.max(500),
+    ));
+
+    // A daemon that is already running holds the single-instance lock, so a
+    // spawned stdio daemon would exit straight away; connect to it instead.
+    let running = discovery::discover();
+    if let Some(info) = &running {
+        info!(pid = info.pid, port = ?info.port, ws_port = ?info.ws_port, "found running daemon");
+    }
+    let endpoint = match (cli.port, &cli.uds_path) {
+        (Some(port), _) => Some(Endpoint::Ws(port)),
+        (None, Some(path)) => Some(Endpoint::Uds(path.clone())),
+        (None, None) => running.as_ref().and_then(InstanceInfo::endpoint),
+    };
 
-    // If not --no-daemon, spawn daemon and wire up RPC.
-    if !cli.no_daemon {
+    if let Some(endpoint) = endpoint {
+        let rt = tokio::runtime::Runtime::new().expect("tokio runtime");
+        let _guard = rt.enter();
+        match rt.block_on(connect(&endpoint, msg_tx.clone())) {
+            Ok(client) => {
+                info!(?endpoint, "connected to running daemon");
+                let client = SupervisedClient::connected(client);
+                wire_client(&mut app, &rt, client, &msg_tx, tokio::sync::mpsc::unbounded_channel());
+                // Keep the runtime alive.
+                std::mem::forget(rt);
+            }
+            Err(e) => {
+                app.conn_state = msg::ConnState::Error(e);
+            }
+        }
+    } else if let Some(info) = &running {
+        app.conn_state = msg::ConnState::Error(format!(
+            "{} serves no WebSocket or socket transport; pass --port or --uds-path, or stop it",
+            info.describe()
+        ));
+    } else if !cli.no_daemon {
+        // Spawn the daemon and wire up RPC.
         let rt = tokio::runtime::Runtime::new().expect("tokio runtime");
         let _guard = rt.enter();
 
-        match RpcClient::spawn(&cli.daemon_bin, msg_tx.clone()) {
+        let policy = cli.respawn.then(RespawnPolicy::default);
+        // Respawns ask for a snapshot through the same channel as R
+        let (refresh_tx, refresh_rx) = tokio::sync::mpsc::unbounded_channel();
+        match SupervisedClient::spawn(&cli.daemon_bin, msg_tx.clone(), policy, refresh_tx.clone()) {
             Ok(client) => {
                 info!("Daemon spawned successfully");
-
-                // Store write channel on app for fire-and-forget RPCs.
-                app.set_rpc_tx(client.write_sender());
-
-                // Request initial snapshot after short delay.
-                let msg_tx2 = msg_tx.clone();
-                rt.spawn(async move {
-                    tokio::time::sleep(std::time::Duration::from_millis(500)).await;
-                    match client.get_snapshot().await {
-                        Ok(rx) => {
-                            if let Ok(Ok(value)) = rx.await {
-                                if let Ok(snap) = serde_json::from_value(value) {
-                                    let _ = msg_tx2.send(Msg::SnapshotReceived(snap));
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            let _ = msg_tx2.send(Msg::RpcError(e));
-                        }
-                    }
-                });
-
+                wire_client(&mut app, &rt, client, &msg_tx, (refresh_tx, refresh_rx));
                 // Keep the runtime alive.
                 std::mem::forget(rt);
             }
@@ -88,3 +133,144 @@ fn main() -> std::io::Result<()> {
         .screen_mode(ScreenMode::AltScreen)
         .run()
 }
+
+/// Connect to a daemon that is already running.
+async fn connect(
+    endpoint: &Endpoint,
+    msg_tx: tokio::sync::mpsc::UnboundedSender<Msg>,
+) -> Result<RpcClient, String> {
+    match endpoint {
+        Endpoint::Ws(port) => RpcClient::connect_ws(*port, msg_tx).await,
+        #[cfg(unix)]
+        Endpoint::Uds(path) => RpcClient::connect_uds(path, msg_tx).await,
+        #[cfg(not(unix))]
+        Endpoint::Uds(path) => Err(format!(
+            "Unix sockets are not supported here ({})",
+            path.display()
+        )),
+    }
+}
+
+/// Hand `client` to the app and serve its snapshot, event-detail and follow
+/// requests. Snapshot requests arrive on `refresh`.
+fn wire_client(
+    app: &mut NtmApp,
+    rt: &tokio::runtime::Runtime,
+    client: SupervisedClient,
+    msg_tx: &tokio::sync::mpsc::UnboundedSender<Msg>,
+    refresh: (
+        tokio::sync::mpsc::UnboundedSender<()>,
+        tokio::sync::mpsc::UnboundedReceiver<()>,
+    ),
+) {
+    let client = Arc::new(client);
+
+    // Store write channel on app for fire-and-forget RPCs.
+    app.set_rpc_tx(client.write_sender());
+
+    // Manual refreshes (R) are served by the same task.
+    let (refresh_tx, mut refresh_rx) = refresh;
+    app.set_snapshot_request_tx(refresh_tx);
+
+    // Event detail overlay (Enter on an event) fetches events.get.
+    let (detail_tx, mut detail_rx) = tokio::sync::mpsc::unbounded_channel();
+    app.set_event_detail_request_tx(detail_tx);
+    let detail_client = client.clone();
+    let detail_msg_tx = msg_tx.clone();
+    rt.spawn(async move {
+        while let Some(event_id) = detail_rx.recv().await {
+            let client = detail_client.current();
+            fetch_event_detail(&client, &detail_msg_tx, event_id).await;
+        }
+    });
+
+    // Follow mode (f) subscribes with panes.tail; a refusal closes the panel.
+    let (follow_tx, mut follow_rx) = tokio::sync::mpsc::unbounded_channel();
+    app.set_follow_request_tx(follow_tx);
+    let follow_client = client.clone();
+    let follow_msg_tx = msg_tx.clone();
+    rt.spawn(async move {
+        while let Some(pane_id) = follow_rx.recv().await {
+            let client = follow_client.current();
+            start_tail(&client, &follow_msg_tx, pane_id).await;
+        }
+    });
+
+    // Request initial snapshot after short delay, then on demand (R, or
+    // the poll timer for daemons that do not push).
+    let msg_tx2 = msg_tx.clone();
+    rt.spawn(async move {
+        tokio::time::sleep(std::time::Duration::from_millis(500)).await;
+        fetch_snapshot(&client.current(), &msg_tx2).await;
+        while refresh_rx.recv().await.is_some() {
+            fetch_snapshot(&client.current(), &msg_tx2).await;
+        }
+    });
+}
+
+/// Request `snapshot.get` and forward the result to the update loop.
+async fn fetch_snapshot(client: &RpcClient, msg_tx: &tokio::sync::mpsc::UnboundedSender<Msg>) {
+    match client.get_snapshot().await {
+        Ok(rx) => match rx.await {
+            Ok(Ok(value)) => {
+                if let Ok(snap) = serde_json::from_value(value) {
+                    let _ = msg_tx.send(Msg::SnapshotReceived(snap));
+                }
+            }
+            Ok(Err(detail)) => {
+                let _ = msg_tx.send(Msg::RpcError(detail));
+            }
+            Err(_) => {}
+        },
+        Err(e) => {
+            let _ = msg_tx.send(Msg::RpcError(e.into()));
+        }
+    }
+}
+
+/// Request `panes.tail` for a followed pane, reporting a failure to the
+/// update loop. Output then arrives as `pane.output` notifications.
+async fn start_tail(
+    client: &RpcClient,
+    msg_tx: &tokio::sync::mpsc::UnboundedSender<Msg>,
+    pane_id: String,
+) {
+    let params = serde_json::json!({ "paneId": pane_id });
+    let error = match client.request("panes.tail", params).await {
+        Ok(rx) => match rx.await {
+            Ok(Ok(_)) => return,
+            Ok(Err(detail)) => detail,
+            Err(_) => "daemon closed before answering panes.tail".to_string().into(),
+        },
+        Err(e) => e.into(),
+    };
+    let _ = msg_tx.send(Msg::FollowFailed { pane_id, error });
+}
+
+/// Request `events.get` for one event and forward the detail to the update loop.
+async fn fetch_event_detail(
+    client: &RpcClient,
+    msg_tx: &tokio::sync::mpsc::UnboundedSender<Msg>,
+    event_id: i64,
+) {
+    let params = serde_json::json!({ "eventId": event_id });
+    match client.request("events.get", params).await {
+        Ok(rx) => match rx.await {
+            Ok(Ok(value)) => match serde_json::from_value::<EventsGetResult>(value) {
+                Ok(result) => {
+                    let _ = msg_tx.send(Msg::EventDetailReceived(result.event));
+                }
+                Err(e) => {
+                    let _ = msg_tx.send(Msg::RpcError(format!("bad events.get reply: {e}").into()));
+                }
+            },
+            Ok(Err(detail)) => {
+                let _ = msg_tx.send(Msg::RpcError(detail));
+            }
+            Err(_) => {}
+        },
+        Err(e) => {
+            let _ = msg_tx.send(Msg::RpcError(e.into()));
+        }
+    }
+}
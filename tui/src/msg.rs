@@ -111,6 +111,50 @@ impl EventFilter {
     }
 }
 
+/// Event severity, ordered from least to most urgent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Low,
+    Medium,
+    High,
+    Critical,
+}
+
+impl Severity {
+    /// Parse a daemon severity string; unknown values yield `None`.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "info" => Some(Severity::Info),
+            "low" => Some(Severity::Low),
+            "medium" | "warning" => Some(Severity::Medium),
+            "high" => Some(Severity::High),
+            "critical" => Some(Severity::Critical),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Low => "low",
+            Severity::Medium => "medium",
+            Severity::High => "high",
+            Severity::Critical => "critical",
+        }
+    }
+}
+
+impl std::str::FromStr for Severity {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Severity::parse(&s.to_ascii_lowercase()).ok_or_else(|| {
+            format!("unknown severity '{s}' (expected info, low, medium, high, or critical)")
+        })
+    }
+}
+
 /// Action needing confirmation.
 #[derive(Debug, Clone)]
 pub enum ConfirmAction {
@@ -127,12 +171,31 @@ pub enum Msg {
     Tick,
     /// Snapshot received from daemon.
     SnapshotReceived(crate::rpc::types::Snapshot),
+    /// New events pushed by the daemon between snapshots.
+    EventsPushed(Vec<crate::rpc::types::EventView>),
     /// Connection state changed.
     ConnectionChanged(ConnState),
     /// Daemon hello received.
-    HelloReceived(String),
+    HelloReceived(HelloInfo),
     /// RPC error.
-    RpcError(String),
+    RpcError(RpcErrorDetail),
+    /// New output lines from a pane followed with `panes.tail`; `ended`
+    /// marks the last message for that pane.
+    PaneOutputAppended {
+        pane_id: String,
+        lines: Vec<String>,
+        ended: bool,
+    },
+    /// `panes.tail` was refused or never answered; closes the follow
+    /// panel if it is still showing `pane_id`.
+    FollowFailed {
+        pane_id: String,
+        error: RpcErrorDetail,
+    },
+    /// Full event detail from `events.get` (opens the detail overlay).
+    EventDetailReceived(crate::rpc::types::EventDetail),
+    /// The snapshot polling timer fired (daemons without push).
+    PollSnapshot,
     /// Dismiss an escalation.
     DismissEscalation(i64),
     /// Kill session requested (shows confirmation).
@@ -145,6 +208,46 @@ pub enum Msg {
     None,
 }
 
+/// Identity fields from the daemon's `core.hello`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HelloInfo {
+    pub version: String,
+    /// Instance label (configured, else the daemon's hostname); may be empty.
+    pub label: String,
+    /// The daemon pushes snapshots and events (`capabilities.stream`);
+    /// without it the TUI polls `snapshot.get`.
+    pub push: bool,
+}
+
+/// Everything known about a failed RPC, kept for the error detail overlay.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcErrorDetail {
+    pub message: String,
+    /// JSON-RPC error code; `None` for transport failures.
+    pub code: Option<i64>,
+    pub data: Option<serde_json::Value>,
+}
+
+impl From<String> for RpcErrorDetail {
+    fn from(message: String) -> Self {
+        Self {
+            message,
+            code: None,
+            data: None,
+        }
+    }
+}
+
+impl From<crate::rpc::types::JsonRpcError> for RpcErrorDetail {
+    fn from(err: crate::rpc::types::JsonRpcError) -> Self {
+        Self {
+            message: err.message,
+            code: Some(err.code),
+            data: err.data,
+        }
+    }
+}
+
 /// Toast severity level.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ToastLevel {
@@ -183,6 +286,16 @@ mod tests {
         assert_eq!(all[3], Tab::Health);
     }
 
+    #[test]
+    fn test_severity_parse_and_order() {
+        assert_eq!(Severity::parse("warning"), Some(Severity::Medium));
+        assert_eq!(Severity::parse("bogus"), None);
+        assert!(Severity::Critical > Severity::High);
+        assert!(Severity::Info < Severity::Low);
+        assert_eq!("HIGH".parse::<Severity>(), Ok(Severity::High));
+        assert!("loud".parse::<Severity>().is_err());
+    }
+
     #[test]
     fn test_tab_clone_eq() {
         let t = Tab::Dashboard;
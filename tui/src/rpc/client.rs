@@ -1,19 +1,23 @@
-use crate::msg::{ConnState, Msg};
-use crate::rpc::types::{JsonRpcMessage, JsonRpcRequest, Snapshot};
+use crate::msg::{ConnState, HelloInfo, Msg, RpcErrorDetail};
+use crate::rpc::types::{EventsPush, JsonRpcMessage, JsonRpcRequest, PaneOutput, Snapshot};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::process::Stdio;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use futures_util::{SinkExt, StreamExt};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader, DuplexStream};
 use tokio::process::{Child, Command};
-use tokio::sync::{mpsc, oneshot, Mutex};
+use tokio::sync::{mpsc, oneshot, watch, Mutex};
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
 use tracing::{debug, error, info, warn};
 
 /// Pending request waiting for a response.
-type PendingMap = Arc<Mutex<HashMap<u64, oneshot::Sender<Result<Value, String>>>>>;
+type PendingMap = Arc<Mutex<HashMap<u64, oneshot::Sender<Result<Value, RpcErrorDetail>>>>>;
 
-/// JSON-RPC client that communicates with the daemon over stdio.
+/// JSON-RPC client that talks to a daemon it spawned (over stdio) or to a
+/// running one (over its Unix socket or WebSocket).
 pub struct RpcClient {
     /// Channel to send serialized JSON lines to the writer task.
     write_tx: mpsc::Sender<String>,
@@ -21,8 +25,10 @@ pub struct RpcClient {
     next_id: AtomicU64,
     /// Pending request map.
     pending: PendingMap,
-    /// Daemon child process handle.
-    _child: Child,
+    /// Becomes `true` once the daemon connection closes (it exited or crashed).
+    closed_rx: watch::Receiver<bool>,
+    /// Daemon child process handle; `None` when connected to a running daemon.
+    _child: Option<Child>,
 }
 
 impl RpcClient {
@@ -42,24 +48,63 @@ impl RpcClient {
 
         let stdin = child.stdin.take().ok_or("No stdin on daemon process")?;
         let stdout = child.stdout.take().ok_or("No stdout on daemon process")?;
+        Ok(Self::over_io(stdout, stdin, msg_tx, Some(child)))
+    }
+
+    /// Connect to a running daemon's Unix socket, which speaks the same
+    /// line-delimited JSON-RPC as stdio. Must be called inside a tokio runtime.
+    #[cfg(unix)]
+    pub async fn connect_uds(
+        path: &std::path::Path,
+        msg_tx: mpsc::UnboundedSender<Msg>,
+    ) -> Result<Self, String> {
+        let stream = tokio::net::UnixStream::connect(path)
+            .await
+            .map_err(|e| format!("Failed to connect to {}: {e}", path.display()))?;
+        let (reader, writer) = stream.into_split();
+        Ok(Self::over_io(reader, writer, msg_tx, None))
+    }
+
+    /// Connect to a running daemon's WebSocket transport on loopback `port`.
+    /// Each text frame carries one JSON-RPC message. Must be called inside a
+    /// tokio runtime.
+    pub async fn connect_ws(port: u16, msg_tx: mpsc::UnboundedSender<Msg>) -> Result<Self, String> {
+        let url = format!("ws://127.0.0.1:{port}");
+        let (socket, _) = tokio_tungstenite::connect_async(url.as_str())
+            .await
+            .map_err(|e| format!("Failed to connect to {url}: {e}"))?;
+        // Bridge frames to lines so the stdio reader and writer can be reused.
+        let (ours, theirs) = tokio::io::duplex(64 * 1024);
+        tokio::spawn(bridge_ws(socket, theirs));
+        let (reader, writer) = tokio::io::split(ours);
+        Ok(Self::over_io(reader, writer, msg_tx, None))
+    }
 
+    /// Start the reader and writer tasks over a line-delimited JSON-RPC
+    /// stream. `child` is the daemon this client spawned, if any.
+    fn over_io(
+        reader: impl AsyncRead + Unpin + Send + 'static,
+        writer: impl AsyncWrite + Unpin + Send + 'static,
+        msg_tx: mpsc::UnboundedSender<Msg>,
+        child: Option<Child>,
+    ) -> Self {
         let pending: PendingMap = Arc::new(Mutex::new(HashMap::new()));
 
         // Writer task: sends lines to daemon stdin.
         let (write_tx, mut write_rx) = mpsc::channel::<String>(64);
         tokio::spawn(async move {
-            let mut stdin = stdin;
+            let mut writer = writer;
             while let Some(line) = write_rx.recv().await {
-                if let Err(e) = stdin.write_all(line.as_bytes()).await {
-                    error!("stdin write error: {e}");
+                if let Err(e) = writer.write_all(line.as_bytes()).await {
+                    error!("daemon write error: {e}");
                     break;
                 }
-                if let Err(e) = stdin.write_all(b"\n").await {
-                    error!("stdin newline error: {e}");
+                if let Err(e) = writer.write_all(b"\n").await {
+                    error!("daemon newline error: {e}");
                     break;
                 }
-                if let Err(e) = stdin.flush().await {
-                    error!("stdin flush error: {e}");
+                if let Err(e) = writer.flush().await {
+                    error!("daemon flush error: {e}");
                     break;
                 }
             }
@@ -67,11 +112,12 @@ impl RpcClient {
         });
 
         // Reader task: reads lines from daemon stdout.
+        let (closed_tx, closed_rx) = watch::channel(false);
         let pending_clone = pending.clone();
         let msg_tx_reader = msg_tx.clone();
         tokio::spawn(async move {
             let msg_tx = msg_tx_reader;
-            let reader = BufReader::new(stdout);
+            let reader = BufReader::new(reader);
             let mut lines = reader.lines();
 
             while let Ok(Some(line)) = lines.next_line().await {
@@ -94,8 +140,8 @@ impl RpcClient {
                     let id = id_val.as_u64().unwrap_or(0);
                     let mut pending = pending_clone.lock().await;
                     if let Some(tx) = pending.remove(&id) {
-                        if let Some(err) = &message.error {
-                            let _ = tx.send(Err(err.message.clone()));
+                        if let Some(err) = message.error {
+                            let _ = tx.send(Err(err.into()));
                         } else {
                             let _ = tx.send(Ok(message.result.unwrap_or(Value::Null)));
                         }
@@ -103,18 +149,33 @@ impl RpcClient {
                 }
             }
 
-            info!("reader task ended — daemon stdout closed");
+            info!("reader task ended — daemon connection closed");
+            // No reply will come; dropping the senders wakes waiting callers
+            pending_clone.lock().await.clear();
             let _ = msg_tx.send(Msg::ConnectionChanged(ConnState::Disconnected));
+            let _ = closed_tx.send(true);
         });
 
         let _ = msg_tx.send(Msg::ConnectionChanged(ConnState::Connecting));
 
-        Ok(Self {
+        Self {
             write_tx,
             next_id: AtomicU64::new(1),
             pending,
+            closed_rx,
             _child: child,
-        })
+        }
+    }
+
+    /// Wait until the daemon's connection closes (for a spawned daemon, its
+    /// stdout).
+    pub async fn closed(&self) {
+        let mut closed_rx = self.closed_rx.clone();
+        while !*closed_rx.borrow_and_update() {
+            if closed_rx.changed().await.is_err() {
+                break;
+            }
+        }
     }
 
     /// Send a request and return a oneshot receiver for the result.
@@ -122,7 +183,7 @@ impl RpcClient {
         &self,
         method: &str,
         params: Value,
-    ) -> Result<oneshot::Receiver<Result<Value, String>>, String> {
+    ) -> Result<oneshot::Receiver<Result<Value, RpcErrorDetail>>, String> {
         let id = self.next_id.fetch_add(1, Ordering::Relaxed);
         let req = JsonRpcRequest::new(id, method, params);
         let json = serde_json::to_string(&req).map_err(|e| e.to_string())?;
@@ -142,7 +203,9 @@ impl RpcClient {
     }
 
     /// Convenience: send snapshot.get and deserialize.
-    pub async fn get_snapshot(&self) -> Result<oneshot::Receiver<Result<Value, String>>, String> {
+    pub async fn get_snapshot(
+        &self,
+    ) -> Result<oneshot::Receiver<Result<Value, RpcErrorDetail>>, String> {
         self.request("snapshot.get", Value::Null).await
     }
 
@@ -152,18 +215,64 @@ impl RpcClient {
     }
 }
 
+/// Pump text frames from `socket` into `lines` as newline-terminated lines,
+/// and lines written to `lines` back out as text frames, until either closes.
+async fn bridge_ws(
+    socket: WebSocketStream<MaybeTlsStream<tokio::net::TcpStream>>,
+    lines: DuplexStream,
+) {
+    let (mut sink, mut frames) = socket.split();
+    let (reader, mut writer) = tokio::io::split(lines);
+    let mut outgoing = BufReader::new(reader).lines();
+    loop {
+        tokio::select! {
+            frame = frames.next() => match frame {
+                Some(Ok(Message::Text(text))) => {
+                    if writer.write_all(text.as_bytes()).await.is_err()
+                        || writer.write_all(b"\n").await.is_err()
+                    {
+                        break;
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+            line = outgoing.next_line() => match line {
+                Ok(Some(line)) => {
+                    if sink.send(Message::Text(line.into())).await.is_err() {
+                        break;
+                    }
+                }
+                _ => break,
+            },
+        }
+    }
+    debug!("websocket bridge ended");
+}
+
 fn handle_notification(msg: &JsonRpcMessage, tx: &mpsc::UnboundedSender<Msg>) {
     let method = msg.method.as_deref().unwrap_or("");
     match method {
         "core.hello" => {
-            let version = msg
+            let field = |key: &str| {
+                msg.params
+                    .as_ref()
+                    .and_then(|p| p.get(key))
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+            };
+            let push = msg
                 .params
                 .as_ref()
-                .and_then(|p| p.get("daemonVersion"))
-                .and_then(|v| v.as_str())
-                .unwrap_or("unknown")
-                .to_string();
-            let _ = tx.send(Msg::HelloReceived(version));
+                .and_then(|p| p.pointer("/capabilities/stream"))
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false);
+            let hello = HelloInfo {
+                version: field("daemonVersion").unwrap_or_else(|| "unknown".to_string()),
+                label: field("label").unwrap_or_default(),
+                push,
+            };
+            let _ = tx.send(Msg::HelloReceived(hello));
             let _ = tx.send(Msg::ConnectionChanged(ConnState::Connected));
         }
         "sessions.snapshot" => {
@@ -178,8 +287,73 @@ fn handle_notification(msg: &JsonRpcMessage, tx: &mpsc::UnboundedSender<Msg>) {
                 }
             }
         }
+        "events.push" => {
+            if let Some(params) = &msg.params {
+                match serde_json::from_value::<EventsPush>(params.clone()) {
+                    Ok(push) => {
+                        let _ = tx.send(Msg::EventsPushed(push.events));
+                    }
+                    Err(e) => {
+                        warn!("Failed to parse events.push notification: {e}");
+                    }
+                }
+            }
+        }
+        "pane.output" => {
+            if let Some(params) = &msg.params {
+                match serde_json::from_value::<PaneOutput>(params.clone()) {
+                    Ok(output) => {
+                        let _ = tx.send(Msg::PaneOutputAppended {
+                            pane_id: output.pane_id,
+                            lines: output.lines,
+                            ended: output.ended,
+                        });
+                    }
+                    Err(e) => {
+                        warn!("Failed to parse pane.output notification: {e}");
+                    }
+                }
+            }
+        }
         _ => {
             debug!("Unhandled notification: {method}");
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn websocket_client_gets_hello_and_replies() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        // A daemon that greets, then answers one request with its params
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let mut socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+            let hello = json!({ "jsonrpc": "2.0", "method": "core.hello", "params": { "daemonVersion": "1.2.3" } });
+            socket.send(Message::Text(hello.to_string().into())).await.unwrap();
+            while let Some(Ok(Message::Text(text))) = socket.next().await {
+                let request: Value = serde_json::from_str(&text).unwrap();
+                let reply = json!({ "jsonrpc": "2.0", "id": request["id"], "result": request["params"] });
+                socket.send(Message::Text(reply.to_string().into())).await.unwrap();
+            }
+        });
+
+        let (msg_tx, mut msg_rx) = mpsc::unbounded_channel();
+        let client = RpcClient::connect_ws(port, msg_tx).await.unwrap();
+        let hello = loop {
+            match msg_rx.recv().await.unwrap() {
+                Msg::HelloReceived(hello) => break hello,
+                _ => continue,
+            }
+        };
+        assert_eq!(hello.version, "1.2.3");
+
+        let reply = client.request("echo", json!({ "n": 7 })).await.unwrap();
+        assert_eq!(reply.await.unwrap().unwrap(), json!({ "n": 7 }));
+    }
+}
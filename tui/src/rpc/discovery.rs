@@ -0,0 +1,154 @@
+//! Finding a daemon that is already running via its `instance.json`.
+
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// File the daemon writes into its data dir while it runs.
+pub const INSTANCE_FILE: &str = "instance.json";
+
+/// Endpoint details a running daemon records for clients.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceInfo {
+    pub pid: u32,
+    #[serde(default)]
+    pub instance_id: String,
+    #[serde(default)]
+    pub label: String,
+    /// HTTP RPC port.
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub ws_port: Option<u16>,
+    #[serde(default)]
+    pub uds_path: Option<PathBuf>,
+}
+
+impl InstanceInfo {
+    /// Short description for the status bar.
+    pub fn describe(&self) -> String {
+        let mut out = format!("daemon pid {}", self.pid);
+        if !self.label.is_empty() {
+            out.push_str(&format!(" ({})", self.label));
+        }
+        match self.port {
+            Some(port) => out.push_str(&format!(" on port {port}")),
+            None => out.push_str(" (stdio only)"),
+        }
+        out
+    }
+}
+
+/// How to reach a daemon the TUI did not spawn.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Endpoint {
+    /// Line-delimited JSON-RPC on a Unix socket.
+    Uds(PathBuf),
+    /// JSON-RPC over WebSocket on a loopback port.
+    Ws(u16),
+}
+
+impl InstanceInfo {
+    /// The transport to connect through: the Unix socket if advertised, else
+    /// the WebSocket port. The HTTP `port` cannot push snapshots or events,
+    /// so a daemon serving only HTTP has no usable endpoint.
+    pub fn endpoint(&self) -> Option<Endpoint> {
+        self.uds_path
+            .clone()
+            .map(Endpoint::Uds)
+            .or(self.ws_port.map(Endpoint::Ws))
+    }
+}
+
+/// The daemon's data dir; mirrors `service::data_dir` in the daemon.
+pub fn data_dir() -> PathBuf {
+    if let Some(dir) = std::env::var_os("XDG_DATA_HOME") {
+        PathBuf::from(dir).join("ntm-tracker")
+    } else if let Some(home) = std::env::var_os("HOME") {
+        PathBuf::from(home).join(".local/share/ntm-tracker")
+    } else {
+        PathBuf::from("/tmp/ntm-tracker")
+    }
+}
+
+/// The running daemon, if its instance file exists and its pid is alive.
+pub fn discover() -> Option<InstanceInfo> {
+    discover_in(&data_dir(), pid_alive)
+}
+
+/// Read `instance.json` from `dir`, dropping files whose pid `alive` rejects.
+pub fn discover_in(dir: &Path, alive: impl Fn(u32) -> bool) -> Option<InstanceInfo> {
+    let raw = std::fs::read_to_string(dir.join(INSTANCE_FILE)).ok()?;
+    let info: InstanceInfo = serde_json::from_str(&raw).ok()?;
+    alive(info.pid).then_some(info)
+}
+
+#[cfg(target_os = "linux")]
+fn pid_alive(pid: u32) -> bool {
+    Path::new("/proc").join(pid.to_string()).exists()
+}
+
+/// Without procfs, trust the file; the daemon removes it on exit.
+#[cfg(not(target_os = "linux"))]
+fn pid_alive(_pid: u32) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("ntm-tui-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn discover_reads_instance_file() {
+        let dir = scratch_dir("discover");
+        std::fs::write(
+            dir.join(INSTANCE_FILE),
+            r#"{"pid": 4242, "instanceId": "abc", "label": "wsl-main", "port": 3901, "wsPort": 3900}"#,
+        )
+        .unwrap();
+
+        let info = discover_in(&dir, |_| true).unwrap();
+        assert_eq!(info.pid, 4242);
+        assert_eq!(info.instance_id, "abc");
+        assert_eq!(info.port, Some(3901));
+        assert_eq!(info.ws_port, Some(3900));
+        assert_eq!(info.uds_path, None);
+        assert_eq!(info.describe(), "daemon pid 4242 (wsl-main) on port 3901");
+
+        assert_eq!(discover_in(&dir, |_| false), None);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn endpoint_prefers_the_socket_over_websocket() {
+        let mut info = InstanceInfo {
+            pid: 1,
+            instance_id: String::new(),
+            label: String::new(),
+            port: Some(3901),
+            ws_port: None,
+            uds_path: None,
+        };
+        assert_eq!(info.endpoint(), None, "HTTP alone cannot push");
+        info.ws_port = Some(3900);
+        assert_eq!(info.endpoint(), Some(Endpoint::Ws(3900)));
+        info.uds_path = Some(PathBuf::from("/run/ntm.sock"));
+        assert_eq!(info.endpoint(), Some(Endpoint::Uds(PathBuf::from("/run/ntm.sock"))));
+    }
+
+    #[test]
+    fn discover_falls_back_when_file_is_absent_or_invalid() {
+        let dir = scratch_dir("absent");
+        assert_eq!(discover_in(&dir, |_| true), None);
+
+        std::fs::write(dir.join(INSTANCE_FILE), "not json").unwrap();
+        assert_eq!(discover_in(&dir, |_| true), None);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
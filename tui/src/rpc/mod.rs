@@ -1,2 +1,4 @@
 pub mod client;
+pub mod discovery;
+pub mod supervisor;
 pub mod types;
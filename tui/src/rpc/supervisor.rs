@@ -0,0 +1,315 @@
+//! Respawning the stdio daemon when it exits (`--respawn`).
+
+use crate::msg::{ConnState, Msg};
+use crate::rpc::client::RpcClient;
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc;
+use tracing::{info, warn};
+
+/// How often and how fast a crashed daemon is respawned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RespawnPolicy {
+    /// Respawns tried in a row before giving up.
+    pub max_attempts: u32,
+    /// Delay before the first respawn; doubles on each further attempt.
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+    /// A daemon that ran at least this long resets the attempt count, so
+    /// rare crashes never exhaust it.
+    pub stable_after: Duration,
+}
+
+impl Default for RespawnPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            stable_after: Duration::from_secs(60),
+        }
+    }
+}
+
+impl RespawnPolicy {
+    /// Backoff before respawn `attempt` (1-based).
+    fn delay(&self, attempt: u32) -> Duration {
+        self.base_delay
+            .saturating_mul(1 << attempt.saturating_sub(1).min(16))
+            .min(self.max_delay)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SupervisorState {
+    /// A daemon is running with its pipes attached.
+    Connected,
+    /// The daemon exited; respawn `attempt` is due after `delay`.
+    Waiting { attempt: u32, delay: Duration },
+    /// Respawn is off or out of attempts; the TUI stays disconnected.
+    GaveUp,
+}
+
+/// The crash→respawn→connected state machine, kept free of I/O.
+#[derive(Debug, Clone)]
+pub struct Supervisor {
+    policy: Option<RespawnPolicy>,
+    /// Respawns tried since the daemon last ran for `stable_after`.
+    attempts: u32,
+    state: SupervisorState,
+}
+
+impl Supervisor {
+    /// `None` disables respawning: the first exit gives up.
+    pub fn new(policy: Option<RespawnPolicy>) -> Self {
+        Self {
+            policy,
+            attempts: 0,
+            state: SupervisorState::Connected,
+        }
+    }
+
+    pub fn state(&self) -> &SupervisorState {
+        &self.state
+    }
+
+    /// The daemon exited after running for `uptime` (zero when a respawn
+    /// failed to start it at all).
+    pub fn on_exit(&mut self, uptime: Duration) -> &SupervisorState {
+        self.state = match self.policy {
+            None => SupervisorState::GaveUp,
+            Some(policy) => {
+                if uptime >= policy.stable_after {
+                    self.attempts = 0;
+                }
+                if self.attempts >= policy.max_attempts {
+                    SupervisorState::GaveUp
+                } else {
+                    self.attempts += 1;
+                    SupervisorState::Waiting {
+                        attempt: self.attempts,
+                        delay: policy.delay(self.attempts),
+                    }
+                }
+            }
+        };
+        &self.state
+    }
+
+    /// A respawned daemon started.
+    pub fn on_respawned(&mut self) {
+        self.state = SupervisorState::Connected;
+    }
+
+    /// What to show in the status bar for the current state. `None` while
+    /// connected (the client reports its own progress) and when respawn is
+    /// off, where the plain `Disconnected` from the client stands.
+    pub fn conn_state(&self) -> Option<ConnState> {
+        let policy = self.policy?;
+        match &self.state {
+            SupervisorState::Connected => None,
+            SupervisorState::Waiting { attempt, delay } => Some(ConnState::Error(format!(
+                "daemon exited; respawning in {}ms (attempt {attempt}/{})",
+                delay.as_millis(),
+                policy.max_attempts
+            ))),
+            SupervisorState::GaveUp => Some(ConnState::Error(format!(
+                "daemon exited; gave up after {} respawns",
+                policy.max_attempts
+            ))),
+        }
+    }
+}
+
+/// An [`RpcClient`] that survives daemon respawns. Requests go to whichever
+/// daemon is current; the write channel forwards to it.
+pub struct SupervisedClient {
+    current: Arc<RwLock<Arc<RpcClient>>>,
+    write_tx: mpsc::Sender<String>,
+}
+
+impl SupervisedClient {
+    /// Spawn the daemon and, with a `policy`, respawn it whenever it exits.
+    /// Each respawn sends on `refresh_tx` so a fresh snapshot is requested
+    /// from the new daemon. Must be called inside a tokio runtime.
+    pub fn spawn(
+        daemon_bin: &str,
+        msg_tx: mpsc::UnboundedSender<Msg>,
+        policy: Option<RespawnPolicy>,
+        refresh_tx: mpsc::UnboundedSender<()>,
+    ) -> Result<Self, String> {
+        let client = Arc::new(RpcClient::spawn(daemon_bin, msg_tx.clone())?);
+        let current = Arc::new(RwLock::new(client.clone()));
+
+        let (write_tx, mut write_rx) = mpsc::channel::<String>(64);
+        let forward_to = current.clone();
+        tokio::spawn(async move {
+            while let Some(line) = write_rx.recv().await {
+                let sender = read_current(&forward_to).write_sender();
+                let _ = sender.send(line).await;
+            }
+        });
+
+        tokio::spawn(supervise(
+            daemon_bin.to_string(),
+            msg_tx,
+            refresh_tx,
+            Supervisor::new(policy),
+            current.clone(),
+            client,
+        ));
+        Ok(Self { current, write_tx })
+    }
+
+    /// Wrap a client connected to a daemon this process did not spawn. Its
+    /// lifetime is not ours to manage, so it is never respawned.
+    pub fn connected(client: RpcClient) -> Self {
+        let client = Arc::new(client);
+        let write_tx = client.write_sender();
+        Self {
+            current: Arc::new(RwLock::new(client)),
+            write_tx,
+        }
+    }
+
+    /// The client for the daemon running now.
+    pub fn current(&self) -> Arc<RpcClient> {
+        read_current(&self.current)
+    }
+
+    /// A write channel that keeps working across respawns.
+    pub fn write_sender(&self) -> mpsc::Sender<String> {
+        self.write_tx.clone()
+    }
+}
+
+fn read_current(current: &RwLock<Arc<RpcClient>>) -> Arc<RpcClient> {
+    current.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Drive `supervisor` from the daemon's exits until it gives up.
+async fn supervise(
+    daemon_bin: String,
+    msg_tx: mpsc::UnboundedSender<Msg>,
+    refresh_tx: mpsc::UnboundedSender<()>,
+    mut supervisor: Supervisor,
+    current: Arc<RwLock<Arc<RpcClient>>>,
+    mut client: Arc<RpcClient>,
+) {
+    loop {
+        let started = Instant::now();
+        client.closed().await;
+        let mut uptime = started.elapsed();
+
+        loop {
+            let state = supervisor.on_exit(uptime).clone();
+            if let Some(conn_state) = supervisor.conn_state() {
+                let _ = msg_tx.send(Msg::ConnectionChanged(conn_state));
+            }
+            let SupervisorState::Waiting { attempt, delay } = state else {
+                info!("daemon exited; not respawning");
+                return;
+            };
+            tokio::time::sleep(delay).await;
+            match RpcClient::spawn(&daemon_bin, msg_tx.clone()) {
+                Ok(respawned) => {
+                    info!(attempt, "daemon respawned");
+                    supervisor.on_respawned();
+                    client = Arc::new(respawned);
+                    *current.write().unwrap_or_else(|e| e.into_inner()) = client.clone();
+                    // The new daemon starts with an empty view; fetch it
+                    let _ = refresh_tx.send(());
+                    break;
+                }
+                Err(e) => {
+                    warn!(attempt, "daemon respawn failed: {e}");
+                    uptime = Duration::ZERO;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(max_attempts: u32) -> RespawnPolicy {
+        RespawnPolicy {
+            max_attempts,
+            base_delay: Duration::from_millis(100),
+            max_delay: Duration::from_millis(300),
+            stable_after: Duration::from_secs(60),
+        }
+    }
+
+    #[test]
+    fn crash_respawn_connected() {
+        let mut supervisor = Supervisor::new(Some(policy(3)));
+        assert_eq!(supervisor.state(), &SupervisorState::Connected);
+        assert_eq!(supervisor.conn_state(), None);
+
+        let waiting = SupervisorState::Waiting { attempt: 1, delay: Duration::from_millis(100) };
+        assert_eq!(supervisor.on_exit(Duration::from_secs(1)), &waiting);
+        assert_eq!(
+            supervisor.conn_state(),
+            Some(ConnState::Error(
+                "daemon exited; respawning in 100ms (attempt 1/3)".to_string()
+            ))
+        );
+
+        supervisor.on_respawned();
+        assert_eq!(supervisor.state(), &SupervisorState::Connected);
+        assert_eq!(supervisor.conn_state(), None);
+    }
+
+    #[test]
+    fn repeated_crashes_back_off_then_give_up() {
+        let mut supervisor = Supervisor::new(Some(policy(3)));
+        let delays: Vec<_> = (0..3)
+            .map(|_| match supervisor.on_exit(Duration::ZERO).clone() {
+                SupervisorState::Waiting { delay, .. } => {
+                    supervisor.on_respawned();
+                    delay.as_millis()
+                }
+                other => panic!("expected a respawn, got {other:?}"),
+            })
+            .collect();
+        assert_eq!(delays, [100, 200, 300], "doubling, capped at max_delay");
+
+        assert_eq!(supervisor.on_exit(Duration::ZERO), &SupervisorState::GaveUp);
+        assert_eq!(
+            supervisor.conn_state(),
+            Some(ConnState::Error("daemon exited; gave up after 3 respawns".to_string()))
+        );
+    }
+
+    #[test]
+    fn stable_run_resets_attempts() {
+        let mut supervisor = Supervisor::new(Some(policy(1)));
+        supervisor.on_exit(Duration::ZERO);
+        supervisor.on_respawned();
+        let waiting = SupervisorState::Waiting { attempt: 1, delay: Duration::from_millis(100) };
+        assert_eq!(supervisor.on_exit(Duration::from_secs(120)), &waiting);
+    }
+
+    #[test]
+    fn without_policy_first_exit_gives_up_quietly() {
+        let mut supervisor = Supervisor::new(None);
+        assert_eq!(supervisor.on_exit(Duration::from_secs(5)), &SupervisorState::GaveUp);
+        assert_eq!(supervisor.conn_state(), None);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn respawn_requests_a_snapshot() {
+        let (msg_tx, _msg_rx) = mpsc::unbounded_channel();
+        let (refresh_tx, mut refresh_rx) = mpsc::unbounded_channel();
+        // `true` exits at once, standing in for a daemon that crashed
+        let _client = SupervisedClient::spawn("true", msg_tx, Some(policy(1)), refresh_tx)
+            .expect("spawn");
+
+        let refresh = tokio::time::timeout(Duration::from_secs(5), refresh_rx.recv()).await;
+        assert_eq!(refresh.expect("refresh after respawn"), Some(()));
+    }
+}
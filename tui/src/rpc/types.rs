@@ -15,6 +15,41 @@ pub struct Snapshot {
     pub stats: StatsEnvelope,
     #[serde(default)]
     pub last_event_id: i64,
+    #[serde(default)]
+    pub polling: PollingState,
+}
+
+/// Daemon polling decisions per channel (keys are snake_case on the wire).
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct PollingState {
+    #[serde(default)]
+    pub snapshot: PollingDatum,
+    #[serde(default)]
+    pub tmux: PollingDatum,
+    #[serde(default)]
+    pub ntm: PollingDatum,
+}
+
+impl PollingState {
+    /// The channel to surface in the UI: ntm drives session discovery, so
+    /// prefer it, falling back to tmux and then the snapshot notifier.
+    pub fn primary(&self) -> Option<&PollingDatum> {
+        [&self.ntm, &self.tmux, &self.snapshot]
+            .into_iter()
+            .find(|datum| !datum.mode.is_empty())
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct PollingDatum {
+    #[serde(default)]
+    pub interval_ms: u64,
+    #[serde(default)]
+    pub mode: String,
+    #[serde(default)]
+    pub reason: String,
+    #[serde(default)]
+    pub last_change_at: i64,
 }
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
@@ -50,6 +85,8 @@ pub struct SessionView {
     pub session_id: String,
     #[serde(default)]
     pub name: String,
+    /// Name reported by the source when `name` is a client alias.
+    pub raw_name: Option<String>,
     #[serde(default)]
     pub status: String,
     pub status_reason: Option<String>,
@@ -64,6 +101,11 @@ pub struct SessionView {
     #[serde(default)]
     pub source_id: String,
     pub metadata: Option<Value>,
+    /// Project group computed by the daemon's grouping rule.
+    pub group: Option<String>,
+    /// Client-assigned tags (`sessions.setTags`).
+    #[serde(default)]
+    pub tags: Vec<String>,
 }
 
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
@@ -108,6 +150,46 @@ pub struct EventView {
     pub status: Option<String>,
 }
 
+/// Result of `events.get`: one event with everything the daemon knows.
+/// Database-only fields are `None` when the event came from the cache.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventDetail {
+    #[serde(flatten)]
+    pub event: EventView,
+    pub source: Option<String>,
+    pub confidence: Option<f64>,
+    pub resolved_at: Option<i64>,
+    pub trigger: Option<String>,
+    pub message: Option<String>,
+    pub context_before: Option<i64>,
+    pub payload: Option<Value>,
+}
+
+/// Envelope of an `events.get` response.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct EventsGetResult {
+    pub event: EventDetail,
+}
+
+/// Params of an `events.push` notification: events recorded since the last push.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct EventsPush {
+    #[serde(default)]
+    pub events: Vec<EventView>,
+}
+
+/// Params of a `pane.output` notification for a pane tailed with `panes.tail`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaneOutput {
+    pub pane_id: String,
+    #[serde(default)]
+    pub lines: Vec<String>,
+    #[serde(default)]
+    pub ended: bool,
+}
+
 #[derive(Debug, Clone, Default, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct EscalationView {
@@ -135,11 +217,16 @@ pub struct HealthData {
     pub instance_id: String,
     #[serde(default)]
     pub run_id: String,
+    /// Daemon instance label (configured, else its hostname).
+    #[serde(default)]
+    pub label: String,
     #[serde(default)]
     pub schema_version: String,
     #[serde(default)]
     pub protocol_version: String,
     pub last_error: Option<String>,
+    /// Categorized cause when status is "degraded" (e.g. "db_locked").
+    pub degraded_reason: Option<String>,
     #[serde(default)]
     pub last_event_id: i64,
 }
@@ -271,6 +358,41 @@ mod tests {
         assert_eq!(ev.detected_at, 1700000500);
     }
 
+    #[test]
+    fn test_events_push_deserialize() {
+        let json = r#"{"events": [
+            {"id": 7, "eventType": "compact", "sessionId": "s1", "paneId": "p1", "detectedAt": 10},
+            {"id": 8, "eventType": "escalation", "sessionId": "s1", "paneId": "p2", "detectedAt": 11, "severity": "high"}
+        ]}"#;
+        let push: EventsPush = serde_json::from_str(json).unwrap();
+        assert_eq!(push.events.len(), 2);
+        assert_eq!(push.events[1].id, 8);
+        assert_eq!(push.events[1].severity.as_deref(), Some("high"));
+    }
+
+    #[test]
+    fn test_pane_output_deserialize() {
+        let json = r#"{"paneId": "p1", "lines": ["building", "done"], "ended": false}"#;
+        let output: PaneOutput = serde_json::from_str(json).unwrap();
+        assert_eq!(output.pane_id, "p1");
+        assert_eq!(output.lines, vec!["building", "done"]);
+        assert!(!output.ended);
+    }
+
+    #[test]
+    fn test_events_get_result_deserialize() {
+        let json = r#"{"event": {
+            "id": 9, "eventType": "escalation", "sessionId": "s1", "paneId": "p1",
+            "detectedAt": 12, "severity": "high", "status": "pending",
+            "source": "detector", "message": "needs input", "payload": {"rule": "prompt"}
+        }}"#;
+        let result: EventsGetResult = serde_json::from_str(json).unwrap();
+        assert_eq!(result.event.event.id, 9);
+        assert_eq!(result.event.message.as_deref(), Some("needs input"));
+        assert_eq!(result.event.payload, Some(serde_json::json!({"rule": "prompt"})));
+        assert_eq!(result.event.confidence, None);
+    }
+
     #[test]
     fn test_stats_summary_default() {
         let s = StatsSummary::default();
@@ -323,6 +445,20 @@ mod tests {
         assert!(snap.panes.is_empty());
         assert!(snap.events.is_empty());
         assert_eq!(snap.last_event_id, 0);
+        assert!(snap.polling.primary().is_none());
+    }
+
+    #[test]
+    fn test_snapshot_polling_prefers_ntm_channel() {
+        let json = r#"{"polling": {
+            "snapshot": {"interval_ms": 2000, "mode": "active", "reason": "recent_activity", "last_change_at": 1},
+            "tmux": {"interval_ms": 0, "mode": "", "reason": "", "last_change_at": 0},
+            "ntm": {"interval_ms": 5000, "mode": "idle", "reason": "idle_timeout", "last_change_at": 2}
+        }}"#;
+        let snap: Snapshot = serde_json::from_str(json).unwrap();
+        let primary = snap.polling.primary().unwrap();
+        assert_eq!(primary.mode, "idle");
+        assert_eq!(primary.interval_ms, 5000);
     }
 
     #[test]
@@ -408,9 +544,11 @@ mod tests {
             "version": "0.5.0",
             "instanceId": "inst-abc123",
             "runId": "run-xyz789",
+            "label": "wsl-main",
             "schemaVersion": "1.0",
             "protocolVersion": "2.0",
             "lastError": "connection timeout",
+            "degradedReason": "command_timeout",
             "lastEventId": 42
         }"#;
         let h: HealthData = serde_json::from_str(json).unwrap();
@@ -419,9 +557,11 @@ mod tests {
         assert_eq!(h.version, "0.5.0");
         assert_eq!(h.instance_id, "inst-abc123");
         assert_eq!(h.run_id, "run-xyz789");
+        assert_eq!(h.label, "wsl-main");
         assert_eq!(h.schema_version, "1.0");
         assert_eq!(h.protocol_version, "2.0");
         assert_eq!(h.last_error, Some("connection timeout".to_string()));
+        assert_eq!(h.degraded_reason.as_deref(), Some("command_timeout"));
         assert_eq!(h.last_event_id, 42);
     }
 
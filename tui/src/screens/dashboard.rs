@@ -1,7 +1,8 @@
 use crate::app::NtmApp;
 use crate::msg::{ConnState, EventFilter, FocusArea};
 use crate::widgets::{
-    activity_spark, escalation_inbox, event_timeline, overview_cards, pane_table, session_list,
+    activity_spark, escalation_inbox, event_timeline, overview_cards, pane_output, pane_table,
+    session_list,
 };
 use ftui::core::geometry::Rect;
 use ftui::layout::{Constraint, Flex};
@@ -59,9 +60,20 @@ pub fn render(frame: &mut Frame, area: Rect, app: &NtmApp) {
         ("--", vec![])
     };
 
+    // Follow mode splits the pane column: table above, streamed output below
+    let pane_area = if let Some(follow) = &app.follow {
+        let pane_rows = Flex::vertical()
+            .constraints([Constraint::Ratio(1, 3), Constraint::Ratio(2, 3)])
+            .split(main_cols[1]);
+        pane_output::render(frame, pane_rows[1], follow);
+        pane_rows[0]
+    } else {
+        main_cols[1]
+    };
+
     pane_table::render(
         frame,
-        main_cols[1],
+        pane_area,
         &session_panes,
         session_name,
         &mut app.pane_table_state.borrow_mut(),
@@ -184,6 +196,19 @@ mod tests {
         assert_text_present(&frame.buffer, "escalation");
     }
 
+    #[test]
+    fn test_render_shows_followed_pane_output() {
+        test_frame!(pool, frame, 100, 40);
+        let area = Rect::new(0, 0, 100, 40);
+        let mut app = populated_app();
+        let mut follow = pane_output::PaneFollowState::new("p1", "dev-session #0");
+        follow.append(vec!["cargo test".to_string(), "test result: ok".to_string()]);
+        app.follow = Some(follow);
+        render(&mut frame, area, &app);
+        assert_text_present(&frame.buffer, "Following dev-session #0");
+        assert_text_present(&frame.buffer, "test result: ok");
+    }
+
     #[test]
     fn test_render_shows_escalation_inbox() {
         test_frame!(pool, frame, 100, 30);
@@ -0,0 +1,71 @@
+use crate::msg::RpcErrorDetail;
+use crate::theme;
+use ftui::core::geometry::Rect;
+use ftui::render::frame::Frame;
+use ftui::Style;
+use ftui::widgets::paragraph::Paragraph;
+use ftui::widgets::Widget;
+
+/// Render the last RPC error in full: message, code, and structured data.
+pub fn render(frame: &mut Frame, area: Rect, err: &RpcErrorDetail) {
+    let width = 72u16.min(area.width.saturating_sub(4));
+    let height = 20u16.min(area.height.saturating_sub(4));
+    let x = area.x + (area.width.saturating_sub(width)) / 2;
+    let y = area.y + (area.height.saturating_sub(height)) / 2;
+    let popup = Rect::new(x, y, width, height);
+
+    let code = err
+        .code
+        .map(|code| code.to_string())
+        .unwrap_or_else(|| "--".to_string());
+    let mut text = format!("  MESSAGE\n  {}\n\n  CODE\n  {code}\n", err.message);
+    if let Some(data) = &err.data {
+        let pretty = serde_json::to_string_pretty(data).unwrap_or_else(|_| data.to_string());
+        text.push_str("\n  DATA\n");
+        for line in pretty.lines() {
+            text.push_str("  ");
+            text.push_str(line);
+            text.push('\n');
+        }
+    }
+    text.push_str("\n        Esc to close");
+
+    let block = theme::panel_block(" Last Error ", true);
+
+    let para = Paragraph::new(text)
+        .style(Style::new().fg(theme::TEXT_PRIMARY).bg(theme::BG_RAISED))
+        .block(block);
+
+    para.render(popup, frame);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+
+    #[test]
+    fn test_render_shows_message_code_and_data() {
+        test_frame!(pool, frame, 80, 30);
+        let area = Rect::new(0, 0, 80, 30);
+        let err = RpcErrorDetail {
+            message: "Invalid params: missing field 'sessionId'".to_string(),
+            code: Some(-32602),
+            data: Some(serde_json::json!({ "field": "sessionId", "reason": "missing" })),
+        };
+        render(&mut frame, area, &err);
+        assert_text_present(&frame.buffer, "Last Error");
+        assert_text_present(&frame.buffer, "missing field 'sessionId'");
+        assert_text_present(&frame.buffer, "-32602");
+        assert_text_present(&frame.buffer, "\"reason\": \"missing\"");
+    }
+
+    #[test]
+    fn test_render_without_code_or_data() {
+        test_frame!(pool, frame, 80, 30);
+        let area = Rect::new(0, 0, 80, 30);
+        render(&mut frame, area, &"write channel closed".to_string().into());
+        assert_text_present(&frame.buffer, "write channel closed");
+        assert_text_absent(&frame.buffer, "DATA");
+    }
+}
@@ -0,0 +1,146 @@
+use crate::rpc::types::EventDetail;
+use crate::theme;
+use ftui::core::geometry::Rect;
+use ftui::render::frame::Frame;
+use ftui::Style;
+use ftui::widgets::paragraph::Paragraph;
+use ftui::widgets::Widget;
+
+/// Render one event in full (from `events.get`) with scroll support.
+pub fn render(frame: &mut Frame, area: Rect, detail: &EventDetail, scroll: u16) {
+    let width = 72u16.min(area.width.saturating_sub(4));
+    let height = 24u16.min(area.height.saturating_sub(4));
+    let x = area.x + (area.width.saturating_sub(width)) / 2;
+    let y = area.y + (area.height.saturating_sub(height)) / 2;
+    let popup = Rect::new(x, y, width, height);
+
+    let event = &detail.event;
+    let or_dash = |value: Option<&str>| value.unwrap_or("--").to_string();
+    let mut rows = vec![
+        ("Type", event.event_type.clone()),
+        ("Session", event.session_id.clone()),
+        ("Pane", event.pane_id.clone()),
+        ("Severity", or_dash(event.severity.as_deref())),
+        ("Status", or_dash(event.status.as_deref())),
+        ("Detected", format_time(event.detected_at)),
+    ];
+    if let Some(resolved_at) = detail.resolved_at {
+        rows.push(("Resolved", format_time(resolved_at)));
+    }
+    if let Some(source) = &detail.source {
+        rows.push(("Source", source.clone()));
+    }
+    if let Some(confidence) = detail.confidence {
+        rows.push(("Confidence", format!("{confidence:.2}")));
+    }
+    if let Some(trigger) = &detail.trigger {
+        rows.push(("Trigger", trigger.clone()));
+    }
+    if let Some(context_before) = detail.context_before {
+        rows.push(("Context", context_before.to_string()));
+    }
+
+    let mut text = String::new();
+    for (label, value) in rows {
+        text.push_str(&format!("  {label:<12}{value}\n"));
+    }
+    if let Some(message) = &detail.message {
+        text.push_str("\n  MESSAGE\n");
+        for line in message.lines() {
+            text.push_str("  ");
+            text.push_str(line);
+            text.push('\n');
+        }
+    }
+    if let Some(payload) = &detail.payload {
+        let pretty = serde_json::to_string_pretty(payload).unwrap_or_else(|_| payload.to_string());
+        text.push_str("\n  DATA\n");
+        for line in pretty.lines() {
+            text.push_str("  ");
+            text.push_str(line);
+            text.push('\n');
+        }
+    }
+    text.push_str("\n        j/k to scroll, Esc to close");
+
+    let title = format!(" Event #{} ", event.id);
+    let block = theme::panel_block(&title, true);
+
+    let para = Paragraph::new(text)
+        .style(Style::new().fg(theme::TEXT_PRIMARY).bg(theme::BG_RAISED))
+        .block(block)
+        .scroll((scroll, 0));
+
+    para.render(popup, frame);
+}
+
+fn format_time(ts: i64) -> String {
+    use chrono::prelude::*;
+    chrono::DateTime::from_timestamp(ts, 0)
+        .map(|dt| {
+            dt.with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_else(|| ts.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc::types::EventView;
+    use crate::test_helpers::*;
+
+    fn make_detail() -> EventDetail {
+        EventDetail {
+            event: EventView {
+                id: 42,
+                event_type: "escalation".to_string(),
+                session_id: "s1".to_string(),
+                pane_id: "p3".to_string(),
+                detected_at: 1_700_000_000,
+                severity: Some("high".to_string()),
+                status: Some("pending".to_string()),
+            },
+            message: Some("Agent is waiting for input".to_string()),
+            payload: Some(serde_json::json!({ "rule": "prompt_wait" })),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_render_shows_fields_and_data() {
+        test_frame!(pool, frame, 80, 40);
+        let area = Rect::new(0, 0, 80, 40);
+        render(&mut frame, area, &make_detail(), 0);
+        assert_text_present(&frame.buffer, "Event #42");
+        assert_text_present(&frame.buffer, "escalation");
+        assert_text_present(&frame.buffer, "p3");
+        assert_text_present(&frame.buffer, "pending");
+        assert_text_present(&frame.buffer, "Agent is waiting for input");
+        assert_text_present(&frame.buffer, "\"rule\": \"prompt_wait\"");
+    }
+
+    #[test]
+    fn test_render_omits_missing_optional_fields() {
+        test_frame!(pool, frame, 80, 40);
+        let area = Rect::new(0, 0, 80, 40);
+        let detail = EventDetail {
+            payload: None,
+            message: None,
+            ..make_detail()
+        };
+        render(&mut frame, area, &detail, 0);
+        assert_text_absent(&frame.buffer, "DATA");
+        assert_text_absent(&frame.buffer, "Confidence");
+    }
+
+    #[test]
+    fn test_render_scrolls_top_lines_away() {
+        test_frame!(pool, frame, 80, 40);
+        let area = Rect::new(0, 0, 80, 40);
+        render(&mut frame, area, &make_detail(), 3);
+        assert_text_absent(&frame.buffer, "Session");
+        assert_text_present(&frame.buffer, "Severity");
+    }
+}
@@ -18,7 +18,10 @@ pub fn render(frame: &mut Frame, area: Rect, scroll: u16) {
   1-4          Switch screen
   Tab          Cycle focus to next panel
   Shift+Tab    Cycle focus to previous panel
-  Ctrl+P  /    Open command palette
+  !            Jump to escalation inbox
+  R            Refresh snapshot now
+  E            Show last RPC error in full
+  Ctrl+P  /    Open command palette (tabs, sessions, events)
   ?            Toggle this help
   q  Ctrl+C    Quit
 
@@ -27,11 +30,14 @@ pub fn render(frame: &mut Frame, area: Rect, scroll: u16) {
   k / Up       Select previous item
   g            Jump to first item
   G            Jump to last item
-  Enter / l    Expand or collapse session
+  Enter / l    Expand or collapse session or group
+  Enter        Show selected event in full
+  z            Group sessions by project
 
   ACTIONS
   K            Kill selected session
   s            Send text to selected pane
+  f            Follow selected pane's output (Esc to stop)
   d            Dismiss selected escalation
 
   EVENTS SCREEN FILTERS
@@ -3,3 +3,5 @@ pub mod session_detail;
 pub mod events;
 pub mod health;
 pub mod help;
+pub mod error_detail;
+pub mod event_detail;
@@ -1,4 +1,5 @@
-use crate::rpc::types::{PaneView, SessionView};
+use crate::rpc::types::{EventView, PaneView, SessionView};
+use crate::widgets::event_timeline::MAX_VISIBLE_EVENTS;
 use ftui::widgets::command_palette::{ActionItem, CommandPalette, PaletteAction};
 use ftui::Event;
 
@@ -6,6 +7,7 @@ use ftui::Event;
 pub fn build_actions(
     sessions: &[SessionView],
     panes: &[PaneView],
+    events: &[EventView],
 ) -> Vec<ActionItem> {
     let mut actions = Vec::new();
 
@@ -62,6 +64,22 @@ pub fn build_actions(
         }
     }
 
+    // Jump to recent events, newest first (same window as the timeline)
+    for ev in events.iter().rev().take(MAX_VISIBLE_EVENTS) {
+        let session = sessions
+            .iter()
+            .find(|s| s.session_id == ev.session_id)
+            .map_or(ev.session_id.as_str(), |s| s.name.as_str());
+        actions.push(
+            ActionItem::new(
+                format!("event:{}", ev.id),
+                format!("Event #{}: {} in {session}", ev.id, ev.event_type),
+            )
+            .with_category("Events")
+            .with_tags(&[&ev.event_type, &ev.session_id]),
+        );
+    }
+
     actions
 }
 
@@ -79,8 +97,8 @@ impl PaletteState {
         }
     }
 
-    pub fn open(&mut self, sessions: &[SessionView], panes: &[PaneView]) {
-        let actions = build_actions(sessions, panes);
+    pub fn open(&mut self, sessions: &[SessionView], panes: &[PaneView], events: &[EventView]) {
+        let actions = build_actions(sessions, panes, events);
         self.palette.replace_actions(actions);
         self.palette.open();
         self.visible = true;
@@ -91,11 +109,11 @@ impl PaletteState {
         self.visible = false;
     }
 
-    pub fn toggle(&mut self, sessions: &[SessionView], panes: &[PaneView]) {
+    pub fn toggle(&mut self, sessions: &[SessionView], panes: &[PaneView], events: &[EventView]) {
         if self.visible {
             self.close();
         } else {
-            self.open(sessions, panes);
+            self.open(sessions, panes, events);
         }
     }
 
@@ -145,7 +163,7 @@ mod tests {
     #[test]
     fn test_build_actions_includes_tabs() {
         let sessions = vec![make_session("s1", "project")];
-        let actions = build_actions(&sessions, &[]);
+        let actions = build_actions(&sessions, &[], &[]);
         assert!(actions.iter().any(|a| a.title == "Tab: Dashboard"));
         assert!(actions.iter().any(|a| a.title == "Tab: Health"));
     }
@@ -156,16 +174,40 @@ mod tests {
             make_session("s1", "project-a"),
             make_session("s2", "project-b"),
         ];
-        let actions = build_actions(&sessions, &[]);
+        let actions = build_actions(&sessions, &[], &[]);
         assert!(actions.iter().any(|a| a.title == "Go to: project-a"));
         assert!(actions.iter().any(|a| a.title == "Kill: project-b"));
     }
 
+    #[test]
+    fn test_build_actions_includes_events() {
+        let sessions = vec![make_session("s1", "project-a")];
+        let events = vec![
+            EventView {
+                id: 7,
+                event_type: "compact".to_string(),
+                session_id: "s1".to_string(),
+                ..Default::default()
+            },
+            EventView {
+                id: 9,
+                event_type: "escalation".to_string(),
+                session_id: "gone".to_string(),
+                ..Default::default()
+            },
+        ];
+        let actions = build_actions(&sessions, &[], &events);
+        let position = |title: &str| actions.iter().position(|a| a.title == title);
+        let older = position("Event #7: compact in project-a").expect("named session");
+        let newer = position("Event #9: escalation in gone").expect("raw session id");
+        assert!(newer < older, "newest events come first");
+    }
+
     #[test]
     fn test_build_actions_includes_send_entries() {
         let sessions = vec![make_session("s1", "project-a")];
         let panes = vec![make_pane("p1", "s1")];
-        let actions = build_actions(&sessions, &panes);
+        let actions = build_actions(&sessions, &panes, &[]);
         assert!(actions.iter().any(|a| a.title == "Send to: project-a #0"));
     }
 
@@ -173,16 +215,16 @@ mod tests {
     fn test_palette_state_toggle() {
         let mut state = PaletteState::new();
         assert!(!state.visible);
-        state.toggle(&[], &[]);
+        state.toggle(&[], &[], &[]);
         assert!(state.visible);
-        state.toggle(&[], &[]);
+        state.toggle(&[], &[], &[]);
         assert!(!state.visible);
     }
 
     #[test]
     fn test_palette_state_close() {
         let mut state = PaletteState::new();
-        state.open(&[], &[]);
+        state.open(&[], &[], &[]);
         assert!(state.visible);
         state.close();
         assert!(!state.visible);
@@ -208,7 +250,7 @@ mod tests {
     fn test_handle_event_escape_closes_and_returns_none() {
         let mut state = PaletteState::new();
         let sessions = vec![make_session("s1", "proj")];
-        state.open(&sessions, &[]);
+        state.open(&sessions, &[], &[]);
         assert!(state.visible);
         let result = state.handle_event(&press(ftui::KeyCode::Escape));
         assert!(result.is_none());
@@ -219,7 +261,7 @@ mod tests {
     fn test_handle_event_enter_executes_and_returns_action_id() {
         let mut state = PaletteState::new();
         let sessions = vec![make_session("s1", "my-project")];
-        state.open(&sessions, &[]);
+        state.open(&sessions, &[], &[]);
         assert!(state.visible);
         // Enter on default selection returns some action ID
         let result = state.handle_event(&press(ftui::KeyCode::Enter));
@@ -237,7 +279,7 @@ mod tests {
     fn test_handle_event_typing_keeps_palette_open() {
         let mut state = PaletteState::new();
         let sessions = vec![make_session("s1", "proj")];
-        state.open(&sessions, &[]);
+        state.open(&sessions, &[], &[]);
         // Type some characters
         let result1 = state.handle_event(&press(ftui::KeyCode::Char('h')));
         assert!(result1.is_none());
@@ -251,7 +293,7 @@ mod tests {
     fn test_handle_event_down_arrow_keeps_palette_open() {
         let mut state = PaletteState::new();
         let sessions = vec![make_session("s1", "proj")];
-        state.open(&sessions, &[]);
+        state.open(&sessions, &[], &[]);
         let result = state.handle_event(&press(ftui::KeyCode::Down));
         assert!(result.is_none());
         assert!(state.visible);
@@ -261,7 +303,7 @@ mod tests {
     fn test_handle_event_navigate_then_execute() {
         let mut state = PaletteState::new();
         let sessions = vec![make_session("s1", "proj")];
-        state.open(&sessions, &[]);
+        state.open(&sessions, &[], &[]);
         // Move down once, then execute. (We don't assert a specific selection index since the
         // underlying widget may choose a non-zero default selection.)
         state.handle_event(&press(ftui::KeyCode::Down));
@@ -281,13 +323,13 @@ mod tests {
     fn test_open_replaces_actions() {
         let mut state = PaletteState::new();
         let sessions1 = vec![make_session("s1", "first")];
-        state.open(&sessions1, &[]);
+        state.open(&sessions1, &[], &[]);
         // Execute first action
         state.handle_event(&press(ftui::KeyCode::Enter));
         assert!(!state.visible);
         // Open with different sessions
         let sessions2 = vec![make_session("s2", "second")];
-        state.open(&sessions2, &[]);
+        state.open(&sessions2, &[], &[]);
         assert!(state.visible);
         // Navigate to goto action and execute
         // Tab actions are first (4), then goto actions
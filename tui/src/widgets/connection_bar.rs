@@ -1,4 +1,5 @@
 use crate::msg::{ConnState, FocusArea, Tab};
+use crate::rpc::types::PollingDatum;
 use crate::theme;
 use ftui::core::geometry::Rect;
 use ftui::render::frame::Frame;
@@ -7,15 +8,18 @@ use ftui::widgets::paragraph::Paragraph;
 use ftui::widgets::Widget;
 
 /// Render the segmented bottom status bar.
+#[allow(clippy::too_many_arguments)]
 pub fn render(
     frame: &mut Frame,
     area: Rect,
     conn: &ConnState,
     version: &str,
+    polling: Option<&PollingDatum>,
     _session_count: usize,
     active_tab: Tab,
     spinner_frame: usize,
     focus: FocusArea,
+    last_snapshot_at: Option<i64>,
 ) {
     let (conn_icon, conn_color) = match conn {
         ConnState::Connected => ("●", theme::ACTIVE),
@@ -27,11 +31,22 @@ pub fn render(
         ConnState::Error(_) => ("✕", theme::ERROR),
     };
 
-    // Left segment: connection status
-    let left = format!(
+    // Left segment: connection status, plus the daemon's polling mode
+    let mut left = format!(
         " {conn_icon} {label} v{version}",
         label = conn.label()
     );
+    if let Some(polling) = polling {
+        left.push_str(&format!(
+            " · {mode} {interval}",
+            mode = polling.mode,
+            interval = format_interval(polling.interval_ms),
+        ));
+    }
+
+    if let Some(ts) = last_snapshot_at {
+        left.push_str(&format!(" · updated {}", theme::relative_time(ts)));
+    }
 
     // Middle segment: navigation breadcrumb
     let breadcrumb = format!(" {tab} ", tab = active_tab.label());
@@ -71,6 +86,15 @@ pub fn render(
     para.render(area, frame);
 }
 
+/// Compact interval label: whole seconds as "5s", otherwise milliseconds.
+fn format_interval(ms: u64) -> String {
+    if ms >= 1000 && ms % 1000 == 0 {
+        format!("{}s", ms / 1000)
+    } else {
+        format!("{ms}ms")
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -81,8 +105,8 @@ mod tests {
         test_frame!(pool, frame, 120, 1);
         let area = Rect::new(0, 0, 120, 1);
         render(
-            &mut frame, area, &ConnState::Connected, "1.2.3",
-            3, Tab::Dashboard, 0, FocusArea::SessionList,
+            &mut frame, area, &ConnState::Connected, "1.2.3", None,
+            3, Tab::Dashboard, 0, FocusArea::SessionList, None,
         );
         let row = row_text(&frame.buffer, 0);
         assert!(row.contains("●"), "Missing connected icon: {row}");
@@ -95,8 +119,8 @@ mod tests {
         test_frame!(pool, frame, 120, 1);
         let area = Rect::new(0, 0, 120, 1);
         render(
-            &mut frame, area, &ConnState::Disconnected, "0.1",
-            0, Tab::Dashboard, 0, FocusArea::SessionList,
+            &mut frame, area, &ConnState::Disconnected, "0.1", None,
+            0, Tab::Dashboard, 0, FocusArea::SessionList, None,
         );
         let row = row_text(&frame.buffer, 0);
         assert!(row.contains("○"), "Missing disconnected icon: {row}");
@@ -108,8 +132,8 @@ mod tests {
         test_frame!(pool, frame, 120, 1);
         let area = Rect::new(0, 0, 120, 1);
         render(
-            &mut frame, area, &ConnState::Error("timeout".into()), "0.1",
-            0, Tab::Dashboard, 0, FocusArea::SessionList,
+            &mut frame, area, &ConnState::Error("timeout".into()), "0.1", None,
+            0, Tab::Dashboard, 0, FocusArea::SessionList, None,
         );
         let row = row_text(&frame.buffer, 0);
         assert!(row.contains("✕"), "Missing error icon: {row}");
@@ -121,8 +145,8 @@ mod tests {
         test_frame!(pool, frame, 120, 1);
         let area = Rect::new(0, 0, 120, 1);
         render(
-            &mut frame, area, &ConnState::Connected, "1.0",
-            0, Tab::Sessions, 0, FocusArea::SessionList,
+            &mut frame, area, &ConnState::Connected, "1.0", None,
+            0, Tab::Sessions, 0, FocusArea::SessionList, None,
         );
         let row = row_text(&frame.buffer, 0);
         assert!(row.contains("Sessions"), "Missing tab breadcrumb: {row}");
@@ -133,8 +157,8 @@ mod tests {
         test_frame!(pool, frame, 120, 1);
         let area = Rect::new(0, 0, 120, 1);
         render(
-            &mut frame, area, &ConnState::Connected, "1.0",
-            0, Tab::Dashboard, 0, FocusArea::SessionList,
+            &mut frame, area, &ConnState::Connected, "1.0", None,
+            0, Tab::Dashboard, 0, FocusArea::SessionList, None,
         );
         let row = row_text(&frame.buffer, 0);
         assert!(row.contains("K:kill"), "Missing kill hint: {row}");
@@ -146,8 +170,8 @@ mod tests {
         test_frame!(pool, frame, 120, 1);
         let area = Rect::new(0, 0, 120, 1);
         render(
-            &mut frame, area, &ConnState::Connected, "1.0",
-            0, Tab::Dashboard, 0, FocusArea::PaneTable,
+            &mut frame, area, &ConnState::Connected, "1.0", None,
+            0, Tab::Dashboard, 0, FocusArea::PaneTable, None,
         );
         let row = row_text(&frame.buffer, 0);
         assert!(row.contains("s:send"), "Missing send hint: {row}");
@@ -158,8 +182,8 @@ mod tests {
         test_frame!(pool, frame, 120, 1);
         let area = Rect::new(0, 0, 120, 1);
         render(
-            &mut frame, area, &ConnState::Connected, "1.0",
-            0, Tab::Events, 0, FocusArea::EventTimeline,
+            &mut frame, area, &ConnState::Connected, "1.0", None,
+            0, Tab::Events, 0, FocusArea::EventTimeline, None,
         );
         let row = row_text(&frame.buffer, 0);
         assert!(row.contains("a:all"), "Missing filter hints: {row}");
@@ -171,11 +195,49 @@ mod tests {
         test_frame!(pool, frame, 120, 1);
         let area = Rect::new(0, 0, 120, 1);
         render(
-            &mut frame, area, &ConnState::Connected, "1.0",
-            0, Tab::Dashboard, 0, FocusArea::SessionList,
+            &mut frame, area, &ConnState::Connected, "1.0", None,
+            0, Tab::Dashboard, 0, FocusArea::SessionList, None,
         );
         let row = row_text(&frame.buffer, 0);
         assert!(row.contains("q:quit"), "Missing quit hint: {row}");
         assert!(row.contains("?:help"), "Missing help hint: {row}");
     }
+
+    #[test]
+    fn test_render_shows_polling_mode_and_interval() {
+        test_frame!(pool, frame, 120, 1);
+        let area = Rect::new(0, 0, 120, 1);
+        let polling = PollingDatum {
+            interval_ms: 5000,
+            mode: "idle".into(),
+            reason: "idle_timeout".into(),
+            last_change_at: 0,
+        };
+        render(
+            &mut frame, area, &ConnState::Connected, "1.0", Some(&polling),
+            0, Tab::Dashboard, 0, FocusArea::SessionList, None,
+        );
+        let row = row_text(&frame.buffer, 0);
+        assert!(row.contains("idle 5s"), "Missing polling mode: {row}");
+    }
+
+    #[test]
+    fn test_render_shows_last_snapshot_time() {
+        test_frame!(pool, frame, 120, 1);
+        let area = Rect::new(0, 0, 120, 1);
+        let now = chrono::Utc::now().timestamp();
+        render(
+            &mut frame, area, &ConnState::Connected, "1.0", None,
+            0, Tab::Dashboard, 0, FocusArea::SessionList, Some(now - 90),
+        );
+        let row = row_text(&frame.buffer, 0);
+        assert!(row.contains("updated 1m"), "Missing refresh time: {row}");
+    }
+
+    #[test]
+    fn test_format_interval() {
+        assert_eq!(format_interval(2000), "2s");
+        assert_eq!(format_interval(2500), "2500ms");
+        assert_eq!(format_interval(250), "250ms");
+    }
 }
@@ -9,6 +9,9 @@ use ftui::widgets::list::{List, ListItem, ListState};
 use ftui::widgets::paragraph::Paragraph;
 use ftui::widgets::{StatefulWidget, Widget};
 
+/// Most recent events shown in the timeline.
+pub const MAX_VISIBLE_EVENTS: usize = 50;
+
 pub struct EventTimelineState {
     pub list_state: ListState,
 }
@@ -32,6 +35,21 @@ impl EventTimelineState {
         let i = self.list_state.selected().unwrap_or(0);
         self.list_state.select(Some(i.saturating_sub(1)));
     }
+
+    /// The highlighted event, matching the newest-first order `render` uses.
+    pub fn selected_event<'a>(
+        &self,
+        events: &'a [EventView],
+        filter: EventFilter,
+    ) -> Option<&'a EventView> {
+        let index = self.list_state.selected()?;
+        events
+            .iter()
+            .filter(|e| filter.matches(&e.event_type))
+            .rev()
+            .take(MAX_VISIBLE_EVENTS)
+            .nth(index)
+    }
 }
 
 /// Render recent events with type icons and severity coloring.
@@ -66,7 +84,7 @@ pub fn render(
     let items: Vec<ListItem> = filtered
         .iter()
         .rev()
-        .take(50)
+        .take(MAX_VISIBLE_EVENTS)
         .map(|ev| {
             let time = format_timestamp(ev.detected_at);
             let color = event_type_color(&ev.event_type);
@@ -299,4 +317,23 @@ mod tests {
         assert_text_present(&frame.buffer, "session_start");
         assert_text_absent(&frame.buffer, "compact");
     }
+
+    #[test]
+    fn test_selected_event_follows_newest_first_order() {
+        let events = vec![
+            make_event("compact", "s1"),
+            make_event("session_start", "s2"),
+            make_event("escalation", "s3"),
+        ];
+        let mut state = EventTimelineState::new();
+        assert!(state.selected_event(&events, EventFilter::All).is_none());
+
+        state.list_state.select(Some(0));
+        let top = state.selected_event(&events, EventFilter::All).unwrap();
+        assert_eq!(top.session_id, "s3");
+
+        // Indices count rows of the filtered list, not of all events
+        let filtered = state.selected_event(&events, EventFilter::Compacts).unwrap();
+        assert_eq!(filtered.session_id, "s1");
+    }
 }
@@ -8,3 +8,4 @@ pub mod status_badge;
 pub mod connection_bar;
 pub mod toast_manager;
 pub mod command_palette_wrapper;
+pub mod pane_output;
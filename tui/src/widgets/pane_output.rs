@@ -0,0 +1,96 @@
+use crate::theme;
+use ftui::core::geometry::Rect;
+use ftui::render::frame::Frame;
+use ftui::Style;
+use ftui::widgets::paragraph::Paragraph;
+use ftui::widgets::Widget;
+use std::collections::VecDeque;
+
+/// Lines kept for a followed pane; older lines scroll off.
+pub const FOLLOW_BUFFER_LINES: usize = 500;
+
+/// A pane streamed with `panes.tail` (follow mode).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PaneFollowState {
+    pub pane_id: String,
+    /// Shown in the panel title, e.g. `dev-session #1`.
+    pub label: String,
+    pub lines: VecDeque<String>,
+    /// The daemon reported the pane ended; no more output will arrive.
+    pub ended: bool,
+}
+
+impl PaneFollowState {
+    pub fn new(pane_id: impl Into<String>, label: impl Into<String>) -> Self {
+        Self {
+            pane_id: pane_id.into(),
+            label: label.into(),
+            lines: VecDeque::new(),
+            ended: false,
+        }
+    }
+
+    /// Append streamed lines, dropping the oldest past [`FOLLOW_BUFFER_LINES`].
+    pub fn append(&mut self, lines: Vec<String>) {
+        self.lines.extend(lines);
+        while self.lines.len() > FOLLOW_BUFFER_LINES {
+            self.lines.pop_front();
+        }
+    }
+}
+
+/// Render the followed pane's output, scrolled to the newest lines.
+pub fn render(frame: &mut Frame, area: Rect, state: &PaneFollowState) {
+    let status = if state.ended { "ended" } else { "Esc to stop" };
+    let title = format!(" Following {} ({status}) ", state.label);
+    let block = theme::panel_block(&title, true);
+
+    if state.lines.is_empty() {
+        let empty = Paragraph::new("  Waiting for output...")
+            .style(theme::muted_style())
+            .block(block);
+        empty.render(area, frame);
+        return;
+    }
+
+    // Auto-scroll: show only what fits inside the borders
+    let visible = area.height.saturating_sub(2) as usize;
+    let skip = state.lines.len().saturating_sub(visible);
+    let text = state
+        .lines
+        .iter()
+        .skip(skip)
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let para = Paragraph::new(text)
+        .style(Style::new().fg(theme::TEXT_PRIMARY))
+        .block(block);
+    para.render(area, frame);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::*;
+
+    #[test]
+    fn test_append_caps_buffer() {
+        let mut state = PaneFollowState::new("p1", "dev #0");
+        state.append((0..FOLLOW_BUFFER_LINES + 10).map(|i| format!("line {i}")).collect());
+        assert_eq!(state.lines.len(), FOLLOW_BUFFER_LINES);
+        assert_eq!(state.lines.front().map(String::as_str), Some("line 10"));
+    }
+
+    #[test]
+    fn test_render_shows_newest_lines() {
+        test_frame!(pool, frame, 60, 4);
+        let mut state = PaneFollowState::new("p1", "dev #0");
+        state.append(vec!["old line".into(), "middle".into(), "newest line".into()]);
+        render(&mut frame, Rect::new(0, 0, 60, 4), &state);
+        assert_text_present(&frame.buffer, "Following dev #0");
+        assert_text_present(&frame.buffer, "newest line");
+        assert_text_absent(&frame.buffer, "old line");
+    }
+}
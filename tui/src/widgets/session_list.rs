@@ -6,12 +6,17 @@ use ftui::Style;
 use ftui::widgets::list::{List, ListItem, ListState};
 use ftui::widgets::paragraph::Paragraph;
 use ftui::widgets::{StatefulWidget, Widget};
+use std::collections::HashSet;
 
 /// Maps a visual row in the session list to its logical item.
 #[derive(Debug, Clone, PartialEq)]
 pub enum RowKind {
+    /// Group header in grouped mode, keyed by group name.
+    Group(String),
     Session(usize),
     Pane { session_idx: usize, pane_idx: usize },
+    /// Trailing "+N more" row when sessions exceed the visible cap.
+    More(usize),
 }
 
 /// State for session list selection.
@@ -19,6 +24,39 @@ pub struct SessionListState {
     pub list_state: ListState,
     pub expanded_index: Option<usize>,
     pub row_map: Vec<RowKind>,
+    /// Render sessions as a tree grouped by project.
+    pub grouped: bool,
+    /// Groups whose sessions are hidden in grouped mode.
+    pub collapsed_groups: HashSet<String>,
+    /// Most sessions rendered at once; the rest collapse into a "+N more"
+    /// row and are reached through the command palette.
+    pub max_visible: Option<usize>,
+    /// Session past the cap that was jumped to and is shown anyway.
+    pub pinned: Option<usize>,
+}
+
+/// Rows the cursor stops on when moving between sessions (groups and
+/// sessions; pane and "more" rows are skipped).
+fn is_nav_stop(kind: &RowKind) -> bool {
+    matches!(kind, RowKind::Group(_) | RowKind::Session(_))
+}
+
+/// The group a session belongs to; ungrouped sessions stand alone.
+pub fn group_key(session: &SessionView) -> &str {
+    session.group.as_deref().unwrap_or(&session.name)
+}
+
+/// Session indices bucketed by group, in order of first appearance.
+fn grouped_sessions(sessions: &[SessionView]) -> Vec<(&str, Vec<usize>)> {
+    let mut groups: Vec<(&str, Vec<usize>)> = Vec::new();
+    for (i, session) in sessions.iter().enumerate() {
+        let key = group_key(session);
+        match groups.iter_mut().find(|(name, _)| *name == key) {
+            Some((_, members)) => members.push(i),
+            None => groups.push((key, vec![i])),
+        }
+    }
+    groups
 }
 
 impl SessionListState {
@@ -29,6 +67,10 @@ impl SessionListState {
             list_state,
             expanded_index: None,
             row_map: Vec::new(),
+            grouped: false,
+            collapsed_groups: HashSet::new(),
+            max_visible: None,
+            pinned: None,
         }
     }
 
@@ -43,7 +85,7 @@ impl SessionListState {
         match self.row_map.get(row) {
             Some(RowKind::Session(i)) => Some(*i),
             Some(RowKind::Pane { session_idx, .. }) => Some(*session_idx),
-            None => None,
+            Some(RowKind::Group(_)) | Some(RowKind::More(_)) | None => None,
         }
     }
 
@@ -56,15 +98,15 @@ impl SessionListState {
         }
     }
 
-    /// Navigate to the next session row, skipping pane rows.
+    /// Navigate to the next session or group row, skipping pane rows.
     pub fn select_next_session(&mut self) {
         if self.row_map.is_empty() {
             return;
         }
         let current = self.list_state.selected().unwrap_or(0);
-        // Find the next Session row after current
+        // Find the next Session (or group header) row after current
         for (i, kind) in self.row_map.iter().enumerate().skip(current + 1) {
-            if matches!(kind, RowKind::Session(_)) {
+            if is_nav_stop(kind) {
                 self.list_state.select(Some(i));
                 return;
             }
@@ -72,7 +114,7 @@ impl SessionListState {
         // No next session found — stay put
     }
 
-    /// Navigate to the previous session row, skipping pane rows.
+    /// Navigate to the previous session or group row, skipping pane rows.
     pub fn select_prev_session(&mut self) {
         if self.row_map.is_empty() {
             return;
@@ -83,7 +125,7 @@ impl SessionListState {
         }
         // Search backward from current-1
         for i in (0..current).rev() {
-            if matches!(self.row_map.get(i), Some(RowKind::Session(_))) {
+            if self.row_map.get(i).is_some_and(is_nav_stop) {
                 self.list_state.select(Some(i));
                 return;
             }
@@ -92,15 +134,15 @@ impl SessionListState {
 
     /// Jump to the first session row.
     pub fn select_first_session(&mut self) {
-        if !self.row_map.is_empty() {
-            self.list_state.select(Some(0));
+        if let Some(i) = self.row_map.iter().position(is_nav_stop) {
+            self.list_state.select(Some(i));
         }
     }
 
     /// Jump to the last session row.
     pub fn select_last_session(&mut self) {
         for (i, kind) in self.row_map.iter().enumerate().rev() {
-            if matches!(kind, RowKind::Session(_)) {
+            if is_nav_stop(kind) {
                 self.list_state.select(Some(i));
                 return;
             }
@@ -108,6 +150,8 @@ impl SessionListState {
     }
 
     /// Select the row corresponding to a given session index.
+    /// A session hidden behind the cap is pinned into view just above the
+    /// "+N more" row.
     pub fn select_session_by_index(&mut self, session_idx: usize) {
         for (i, kind) in self.row_map.iter().enumerate() {
             if matches!(kind, RowKind::Session(s) if *s == session_idx) {
@@ -115,9 +159,48 @@ impl SessionListState {
                 return;
             }
         }
+        let Some(&RowKind::More(mut hidden)) = self.row_map.last() else {
+            return;
+        };
+        self.row_map.pop();
+        match self.pinned.replace(session_idx) {
+            // The previous pin goes back behind the more row
+            Some(old) => self.row_map.retain(|kind| match kind {
+                RowKind::Session(i) => *i != old,
+                RowKind::Pane { session_idx, .. } => *session_idx != old,
+                _ => true,
+            }),
+            None => hidden -= 1,
+        }
+        self.row_map.push(RowKind::Session(session_idx));
+        self.list_state.select(Some(self.row_map.len() - 1));
+        if hidden > 0 {
+            self.row_map.push(RowKind::More(hidden));
+        }
+    }
+
+    /// Cap the number of sessions rendered; `None` shows them all.
+    pub fn set_max_visible(&mut self, max: Option<usize>) {
+        self.max_visible = max;
+        self.pinned = None;
+    }
+
+    /// Group name if the cursor is on a group header.
+    pub fn selected_group(&self) -> Option<&str> {
+        let row = self.list_state.selected()?;
+        match self.row_map.get(row) {
+            Some(RowKind::Group(name)) => Some(name.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Switch between the flat list and the grouped tree.
+    pub fn toggle_grouped(&mut self) {
+        self.grouped = !self.grouped;
+        self.list_state.select(Some(0));
     }
 
-    /// Total visual rows (sessions + expanded panes).
+    /// Total visual rows (groups + sessions + expanded panes).
     pub fn total_rows(&self) -> usize {
         self.row_map.len()
     }
@@ -147,6 +230,14 @@ impl SessionListState {
     }
 
     pub fn toggle_expand(&mut self) {
+        // On a group header, collapse or expand the group instead
+        if let Some(group) = self.selected_group().map(str::to_string) {
+            if !self.collapsed_groups.remove(&group) {
+                self.collapsed_groups.insert(group);
+            }
+            return;
+        }
+
         // Use row_map to find the session index at cursor
         let session_idx = self.selected_session_index();
         if self.expanded_index == session_idx {
@@ -158,18 +249,84 @@ impl SessionListState {
 
     /// Build row_map from session/pane data without rendering.
     /// Each session gets a Session row; if expanded, its panes follow.
+    /// In grouped mode each group gets a header row, and sessions of
+    /// collapsed groups are omitted. Past `max_visible` sessions, the
+    /// remainder (minus any pinned session) becomes one `More` row.
     pub fn build_row_map(&mut self, sessions: &[SessionView], panes: &[PaneView]) {
         self.row_map.clear();
-        for (i, session) in sessions.iter().enumerate() {
-            self.row_map.push(RowKind::Session(i));
-            if self.expanded_index == Some(i) {
-                let session_panes: Vec<_> = panes
-                    .iter()
-                    .filter(|p| p.session_id == session.session_id)
-                    .collect();
-                for (pi, _) in session_panes.iter().enumerate() {
-                    self.row_map.push(RowKind::Pane { session_idx: i, pane_idx: pi });
+        let cap = self.max_visible.unwrap_or(usize::MAX);
+        if self.pinned.is_some_and(|i| i >= sessions.len()) {
+            self.pinned = None;
+        }
+        let mut shown = 0;
+        let mut hidden = 0;
+        if self.grouped {
+            for (group, members) in grouped_sessions(sessions) {
+                if shown >= cap {
+                    hidden += members.len();
+                    continue;
+                }
+                self.row_map.push(RowKind::Group(group.to_string()));
+                if self.collapsed_groups.contains(group) {
+                    continue;
+                }
+                for i in members {
+                    if shown < cap {
+                        self.push_session_rows(i, &sessions[i], panes);
+                        shown += 1;
+                    } else {
+                        hidden += 1;
+                    }
+                }
+            }
+        } else {
+            for (i, session) in sessions.iter().enumerate() {
+                if shown < cap {
+                    self.push_session_rows(i, session, panes);
+                    shown += 1;
+                } else {
+                    hidden += 1;
+                }
+            }
+        }
+
+        if hidden > 0 {
+            match self.pinned {
+                Some(i) if !self.row_map.contains(&RowKind::Session(i)) => {
+                    self.push_session_rows(i, &sessions[i], panes);
+                    hidden -= 1;
                 }
+                _ => self.pinned = None,
+            }
+            if hidden > 0 {
+                self.row_map.push(RowKind::More(hidden));
+            }
+        }
+        self.clamp_selection();
+    }
+
+    /// Keep the cursor on a selectable row after the row map shrinks.
+    fn clamp_selection(&mut self) {
+        let Some(row) = self.list_state.selected() else {
+            return;
+        };
+        let selectable = |kind: &RowKind| !matches!(kind, RowKind::More(_));
+        if self.row_map.get(row).is_some_and(selectable) {
+            return;
+        }
+        let last = self.row_map.iter().rposition(selectable);
+        self.list_state.select(Some(last.unwrap_or(0)));
+    }
+
+    fn push_session_rows(&mut self, i: usize, session: &SessionView, panes: &[PaneView]) {
+        self.row_map.push(RowKind::Session(i));
+        if self.expanded_index == Some(i) {
+            let pane_count = panes
+                .iter()
+                .filter(|p| p.session_id == session.session_id)
+                .count();
+            for pi in 0..pane_count {
+                self.row_map.push(RowKind::Pane { session_idx: i, pane_idx: pi });
             }
         }
     }
@@ -203,34 +360,47 @@ pub fn render(
         return;
     }
 
-    let mut items: Vec<ListItem> = Vec::new();
-    state.row_map.clear();
-
-    for (i, s) in sessions.iter().enumerate() {
-        let is_expanded = state.expanded_index == Some(i);
-        let marker = if is_expanded { "▾" } else { "▸" };
-        let badge = theme::status_badge(&s.status);
-        let color = theme::status_color(&s.status);
-        let rel_time = theme::relative_time(s.last_seen_at);
+    state.build_row_map(sessions, panes);
+    let indent = if state.grouped { "  " } else { "" };
+    let mut items: Vec<ListItem> = Vec::with_capacity(state.row_map.len());
 
-        let line = format!(
-            " {marker} {name:<16} {badge} {status:<8} {count}p  {rel_time}",
-            name = truncate(&s.name, 16),
-            status = s.status,
-            count = s.pane_count,
-        );
-        items.push(ListItem::new(line).style(Style::new().fg(color)));
-        state.row_map.push(RowKind::Session(i));
-
-        // If expanded, show inline pane summaries with tree guide chars
-        if is_expanded {
-            let session_panes: Vec<&PaneView> = panes
-                .iter()
-                .filter(|p| p.session_id == s.session_id)
-                .collect();
+    for row in &state.row_map {
+        match row {
+            RowKind::Group(group) => {
+                let collapsed = state.collapsed_groups.contains(group);
+                let marker = if collapsed { "▸" } else { "▾" };
+                let count = sessions.iter().filter(|s| group_key(s) == group.as_str()).count();
+                let line = format!(" {marker} {group} ({count})");
+                items.push(ListItem::new(line).style(theme::title_style()));
+            }
+            RowKind::Session(i) => {
+                let s = &sessions[*i];
+                let is_expanded = state.expanded_index == Some(*i);
+                let marker = if is_expanded { "▾" } else { "▸" };
+                let badge = theme::status_badge(&s.status);
+                let color = theme::status_color(&s.status);
+                let rel_time = theme::relative_time(s.last_seen_at);
+
+                let line = format!(
+                    "{indent} {marker} {name:<16} {badge} {status:<8} {count}p  {rel_time}",
+                    name = truncate(&s.name, 16),
+                    status = s.status,
+                    count = s.pane_count,
+                );
+                items.push(ListItem::new(line).style(Style::new().fg(color)));
+            }
+            // Expanded sessions show inline pane summaries with tree guide chars
+            RowKind::Pane { session_idx, pane_idx } => {
+                let session_id = &sessions[*session_idx].session_id;
+                let session_panes: Vec<&PaneView> = panes
+                    .iter()
+                    .filter(|p| p.session_id == *session_id)
+                    .collect();
+                let Some(pane) = session_panes.get(*pane_idx) else {
+                    continue;
+                };
 
-            for (pi, pane) in session_panes.iter().enumerate() {
-                let guide = if pi == session_panes.len() - 1 {
+                let guide = if *pane_idx == session_panes.len() - 1 {
                     theme::TREE_LAST
                 } else {
                     theme::TREE_BRANCH
@@ -245,13 +415,16 @@ pub fn render(
                     _ => pane.current_command.as_deref().unwrap_or("--"),
                 };
                 let pane_line = format!(
-                    "   {guide}#{idx} {agent} {p_badge} {status:<8} {cmd}",
+                    "{indent}   {guide}#{idx} {agent} {p_badge} {status:<8} {cmd}",
                     idx = pane.pane_index,
                     status = pane.status,
                     cmd = truncate(cmd, 20),
                 );
                 items.push(ListItem::new(pane_line).style(Style::new().fg(p_color)));
-                state.row_map.push(RowKind::Pane { session_idx: i, pane_idx: pi });
+            }
+            RowKind::More(hidden) => {
+                let line = format!("{indent}   +{hidden} more (/ to search)");
+                items.push(ListItem::new(line).style(theme::muted_style()));
             }
         }
     }
@@ -709,4 +882,195 @@ mod tests {
         let has_tree = lines.iter().any(|l| l.contains("├") || l.contains("└"));
         assert!(has_tree, "Missing tree guides in: {lines:?}");
     }
+
+    // === Grouped tree tests ===
+
+    fn grouped_session(id: &str, name: &str, group: &str) -> SessionView {
+        SessionView {
+            group: Some(group.to_string()),
+            ..make_session(id, name, "active")
+        }
+    }
+
+    /// Sessions 0 and 2 share group "tracker"; session 1 is in "docs".
+    fn grouped_fixture() -> Vec<SessionView> {
+        vec![
+            grouped_session("s0", "tracker-api", "tracker"),
+            grouped_session("s1", "docs", "docs"),
+            grouped_session("s2", "tracker-tui", "tracker"),
+        ]
+    }
+
+    #[test]
+    fn test_grouped_row_map_orders_by_group() {
+        let sessions = grouped_fixture();
+        let mut state = SessionListState::new();
+        state.grouped = true;
+        state.build_row_map(&sessions, &[]);
+        assert_eq!(
+            state.row_map,
+            vec![
+                RowKind::Group("tracker".into()),
+                RowKind::Session(0),
+                RowKind::Session(2),
+                RowKind::Group("docs".into()),
+                RowKind::Session(1),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_collapsing_group_removes_its_session_rows() {
+        let sessions = grouped_fixture();
+        let mut state = SessionListState::new();
+        state.grouped = true;
+        state.build_row_map(&sessions, &[]);
+
+        state.list_state.select(Some(0)); // "tracker" header
+        state.toggle_expand();
+        assert!(state.collapsed_groups.contains("tracker"));
+        state.build_row_map(&sessions, &[]);
+        assert_eq!(
+            state.row_map,
+            vec![
+                RowKind::Group("tracker".into()),
+                RowKind::Group("docs".into()),
+                RowKind::Session(1),
+            ]
+        );
+
+        state.toggle_expand();
+        state.build_row_map(&sessions, &[]);
+        assert_eq!(state.total_rows(), 5, "expanding restores the rows");
+    }
+
+    #[test]
+    fn test_navigation_skips_collapsed_sessions() {
+        let sessions = grouped_fixture();
+        let mut state = SessionListState::new();
+        state.grouped = true;
+        state.collapsed_groups.insert("tracker".into());
+        state.build_row_map(&sessions, &[]);
+
+        state.select_first_session();
+        assert_eq!(state.selected_group(), Some("tracker"));
+        assert_eq!(state.selected_session_index(), None);
+        state.select_next_session();
+        assert_eq!(state.selected_group(), Some("docs"));
+        state.select_next_session();
+        assert_eq!(state.selected_session_index(), Some(1));
+        state.select_prev_session();
+        state.select_prev_session();
+        assert_eq!(state.selected_group(), Some("tracker"));
+    }
+
+    #[test]
+    fn test_toggle_grouped_resets_cursor() {
+        let mut state = SessionListState::new();
+        state.list_state.select(Some(3));
+        state.toggle_grouped();
+        assert!(state.grouped);
+        assert_eq!(state.selected(), Some(0));
+        state.toggle_grouped();
+        assert!(!state.grouped);
+    }
+
+    #[test]
+    fn test_render_grouped_shows_headers_with_counts() {
+        test_frame!(pool, frame, 80, 10);
+        let area = Rect::new(0, 0, 80, 10);
+        let sessions = grouped_fixture();
+        let mut state = SessionListState::new();
+        state.grouped = true;
+        state.collapsed_groups.insert("tracker".into());
+        render(&mut frame, area, &sessions, &[], &mut state, true, true, 0);
+        assert_text_present(&frame.buffer, "tracker (2)");
+        assert_text_present(&frame.buffer, "docs (1)");
+        let lines = buf_to_lines(&frame.buffer);
+        assert!(
+            !lines.iter().any(|l| l.contains("tracker-api")),
+            "collapsed sessions rendered: {lines:?}"
+        );
+    }
+
+    // === Visible cap ===
+
+    fn many_sessions(n: usize) -> Vec<SessionView> {
+        (0..n)
+            .map(|i| make_session(&format!("s{i}"), &format!("sess-{i:02}"), "active"))
+            .collect()
+    }
+
+    #[test]
+    fn test_cap_renders_limit_plus_more_row() {
+        test_frame!(pool, frame, 80, 20);
+        let area = Rect::new(0, 0, 80, 20);
+        let sessions = many_sessions(25);
+        let mut state = SessionListState::new();
+        state.set_max_visible(Some(10));
+        render(&mut frame, area, &sessions, &[], &mut state, true, true, 0);
+
+        assert_eq!(state.total_rows(), 11);
+        assert_eq!(state.row_map.last(), Some(&RowKind::More(15)));
+        assert_text_present(&frame.buffer, "sess-09");
+        assert_text_absent(&frame.buffer, "sess-10");
+        assert_text_present(&frame.buffer, "+15 more");
+    }
+
+    #[test]
+    fn test_cap_navigation_stays_within_visible_sessions() {
+        let sessions = many_sessions(25);
+        let mut state = SessionListState::new();
+        state.set_max_visible(Some(10));
+        state.build_row_map(&sessions, &[]);
+
+        for _ in 0..30 {
+            state.select_next_session();
+        }
+        assert_eq!(state.selected(), Some(9));
+        assert_eq!(state.selected_session_index(), Some(9));
+
+        state.select_first_session();
+        state.select_last_session();
+        assert_eq!(state.selected(), Some(9), "more row is not a cursor stop");
+    }
+
+    #[test]
+    fn test_cap_clamps_cursor_left_past_the_end() {
+        let sessions = many_sessions(25);
+        let mut state = SessionListState::new();
+        state.list_state.select(Some(20));
+        state.set_max_visible(Some(10));
+        state.build_row_map(&sessions, &[]);
+        assert_eq!(state.selected(), Some(9));
+    }
+
+    #[test]
+    fn test_selecting_hidden_session_pins_it_above_more_row() {
+        let sessions = many_sessions(25);
+        let mut state = SessionListState::new();
+        state.set_max_visible(Some(10));
+        state.build_row_map(&sessions, &[]);
+
+        state.select_session_by_index(17);
+        assert_eq!(state.selected_session_index(), Some(17));
+        assert_eq!(state.row_map.last(), Some(&RowKind::More(14)));
+
+        // The pin survives a rebuild and moves when another session is picked
+        state.build_row_map(&sessions, &[]);
+        assert_eq!(state.selected_session_index(), Some(17));
+        state.select_session_by_index(21);
+        assert_eq!(state.selected_session_index(), Some(21));
+        assert!(!state.row_map.contains(&RowKind::Session(17)));
+        assert_eq!(state.row_map.last(), Some(&RowKind::More(14)));
+    }
+
+    #[test]
+    fn test_no_cap_has_no_more_row() {
+        let sessions = many_sessions(25);
+        let mut state = SessionListState::new();
+        state.build_row_map(&sessions, &[]);
+        assert_eq!(state.total_rows(), 25);
+        assert!(!state.row_map.iter().any(|kind| matches!(kind, RowKind::More(_))));
+    }
 }
@@ -2,7 +2,7 @@ mod helpers;
 
 use helpers::logging::TestLogger;
 use ntm_tracker_tui::app::NtmApp;
-use ntm_tracker_tui::msg::{ConnState, Msg};
+use ntm_tracker_tui::msg::{ConnState, HelloInfo, Msg};
 use ntm_tracker_tui::msg::{ConfirmAction, FocusArea, ToastLevel};
 use ntm_tracker_tui::rpc::types::{PaneView, SessionView, Snapshot, StatsEnvelope, StatsSummary};
 use ntm_tracker_tui::rpc::types::JsonRpcRequest;
@@ -443,7 +443,11 @@ fn test_data_flow_hello_to_app() {
     assert_eq!(app.conn_state, ConnState::Connecting);
     logger.step_result(true, "Connecting state set");
 
-    app.update(Msg::HelloReceived("0.1.0".to_string()));
+    app.update(Msg::HelloReceived(HelloInfo {
+        version: "0.1.0".to_string(),
+        label: String::new(),
+        push: true,
+    }));
     assert_eq!(app.daemon_version, "0.1.0");
     logger.step_result(true, "Version set from hello");
 
@@ -451,7 +455,7 @@ fn test_data_flow_hello_to_app() {
     assert_eq!(app.conn_state, ConnState::Connected);
     logger.step_result(true, "Connected state set");
 
-    app.update(Msg::RpcError("timeout".to_string()));
+    app.update(Msg::RpcError("timeout".to_string().into()));
     assert_eq!(app.conn_state, ConnState::Error("timeout".to_string()));
     logger.step_result(true, "Error state set from RpcError");
 
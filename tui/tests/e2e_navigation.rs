@@ -3,7 +3,7 @@ mod helpers;
 use helpers::logging::TestLogger;
 use helpers::render::TestFrame;
 use ntm_tracker_tui::app::NtmApp;
-use ntm_tracker_tui::msg::{ConfirmAction, ConnState, FocusArea, Msg, Tab, ToastLevel};
+use ntm_tracker_tui::msg::{ConfirmAction, ConnState, FocusArea, HelloInfo, Msg, Tab, ToastLevel};
 use ntm_tracker_tui::rpc::types::*;
 use ftui::{Cmd, Event, KeyCode, KeyEvent, KeyEventKind, Model, Modifiers};
 
@@ -507,7 +507,7 @@ fn test_toast_from_rpc_error() {
     let mut app = NtmApp::new();
 
     logger.step("Send Msg::RpcError to trigger error toast");
-    app.update(Msg::RpcError("Connection refused".to_string()));
+    app.update(Msg::RpcError("Connection refused".to_string().into()));
 
     {
         let q = app.toast_queue.borrow();
@@ -871,7 +871,7 @@ fn test_error_unknown_method() {
     app.conn_state = ConnState::Connected;
 
     logger.step("Send RPC error for unknown method");
-    app.update(Msg::RpcError("Method not found: bogus.method".to_string()));
+    app.update(Msg::RpcError("Method not found: bogus.method".to_string().into()));
 
     assert_eq!(
         app.conn_state,
@@ -904,7 +904,7 @@ fn test_error_invalid_params() {
     app.conn_state = ConnState::Connected;
 
     logger.step("Send invalid params error");
-    app.update(Msg::RpcError("Invalid params: missing 'sessionId'".to_string()));
+    app.update(Msg::RpcError("Invalid params: missing 'sessionId'".to_string().into()));
 
     assert_eq!(
         app.conn_state,
@@ -926,7 +926,7 @@ fn test_error_not_found() {
     app.conn_state = ConnState::Connected;
 
     logger.step("Send not-found error");
-    app.update(Msg::RpcError("Session not found: nonexistent-id".to_string()));
+    app.update(Msg::RpcError("Session not found: nonexistent-id".to_string().into()));
 
     let q = app.toast_queue.borrow();
     assert_eq!(q.active().unwrap().level, ToastLevel::Error);
@@ -962,7 +962,7 @@ fn test_error_forbidden() {
     app.conn_state = ConnState::Connected;
 
     logger.step("Send forbidden error");
-    app.update(Msg::RpcError("Forbidden: admin authentication required".to_string()));
+    app.update(Msg::RpcError("Forbidden: admin authentication required".to_string().into()));
 
     assert_eq!(
         app.conn_state,
@@ -987,7 +987,7 @@ fn test_error_and_recovery() {
     logger.step_result(true, "Connected");
 
     logger.step("Error occurs");
-    app.update(Msg::RpcError("timeout waiting for response".to_string()));
+    app.update(Msg::RpcError("timeout waiting for response".to_string().into()));
     assert_eq!(
         app.conn_state,
         ConnState::Error("timeout waiting for response".to_string()),
@@ -1019,9 +1019,9 @@ fn test_multiple_consecutive_errors() {
     app.conn_state = ConnState::Connected;
 
     logger.step("Send 3 different errors");
-    app.update(Msg::RpcError("Error 1: timeout".to_string()));
-    app.update(Msg::RpcError("Error 2: connection refused".to_string()));
-    app.update(Msg::RpcError("Error 3: internal error".to_string()));
+    app.update(Msg::RpcError("Error 1: timeout".to_string().into()));
+    app.update(Msg::RpcError("Error 2: connection refused".to_string().into()));
+    app.update(Msg::RpcError("Error 3: internal error".to_string().into()));
 
     let q = app.toast_queue.borrow();
     assert!(q.toasts.len() >= 3, "Should have at least 3 error toasts, got {}", q.toasts.len());
@@ -1059,7 +1059,11 @@ fn test_connection_lifecycle() {
     logger.step_result(true, "Connecting");
 
     logger.step("Receive hello → version set");
-    app.update(Msg::HelloReceived("1.2.3".to_string()));
+    app.update(Msg::HelloReceived(HelloInfo {
+        version: "1.2.3".to_string(),
+        label: String::new(),
+        push: true,
+    }));
     assert_eq!(app.daemon_version, "1.2.3");
     logger.step_result(true, "Hello received with version");
 
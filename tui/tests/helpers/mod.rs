@@ -2,7 +2,7 @@ pub mod fixtures;
 pub mod logging;
 pub mod render;
 
-use ntm_tracker_tui::msg::{ConnState, Msg};
+use ntm_tracker_tui::msg::{ConnState, HelloInfo, Msg};
 use ntm_tracker_tui::rpc::types::JsonRpcMessage;
 use std::process::Stdio;
 use std::time::Duration;
@@ -76,14 +76,26 @@ impl TestHarness {
                     let method = message.method.as_deref().unwrap_or("");
                     match method {
                         "core.hello" => {
-                            let version = message
-                                .params
-                                .as_ref()
-                                .and_then(|p| p.get("daemonVersion"))
-                                .and_then(|v| v.as_str())
-                                .unwrap_or("unknown")
-                                .to_string();
-                            let _ = msg_tx.send(Msg::HelloReceived(version));
+                            let field = |key: &str| {
+                                message
+                                    .params
+                                    .as_ref()
+                                    .and_then(|p| p.get(key))
+                                    .and_then(|v| v.as_str())
+                                    .map(str::to_string)
+                            };
+                            let hello = HelloInfo {
+                                version: field("daemonVersion")
+                                    .unwrap_or_else(|| "unknown".to_string()),
+                                label: field("label").unwrap_or_default(),
+                                push: message
+                                    .params
+                                    .as_ref()
+                                    .and_then(|p| p.pointer("/capabilities/stream"))
+                                    .and_then(|v| v.as_bool())
+                                    .unwrap_or(false),
+                            };
+                            let _ = msg_tx.send(Msg::HelloReceived(hello));
                             let _ = msg_tx.send(Msg::ConnectionChanged(ConnState::Connected));
                         }
                         "sessions.snapshot" => {
@@ -134,13 +146,13 @@ impl TestHarness {
         }
     }
 
-    /// Wait for HelloReceived message.
+    /// Wait for HelloReceived message and return the daemon version.
     pub async fn wait_for_hello(&mut self, timeout: Duration) -> Option<String> {
         let msg = self
             .wait_for_msg(timeout, |m| matches!(m, Msg::HelloReceived(_)))
             .await;
         match msg {
-            Some(Msg::HelloReceived(v)) => Some(v),
+            Some(Msg::HelloReceived(hello)) => Some(hello.version),
             _ => None,
         }
     }